@@ -0,0 +1,192 @@
+//! Template complexity statistics, e.g. for the `fervid analyze` command.
+//!
+//! The statistics are gathered from the transformed template,
+//! so that the patch flags and the resolved `v-for` keys are the same as in the compiled code.
+
+use fervid_core::{AttributeOrBinding, ElementKind, ElementNode, Node, SfcTemplateBlock};
+use fervid_parser::SfcParser;
+use fervid_transform::{transform_sfc, TransformSfcOptions};
+
+use crate::{errors::CompileError, stack::with_compile_stack};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TemplateStats {
+    /// Size of the `<template>` in bytes
+    pub template_size: u32,
+    /// Elements, components and built-ins
+    pub elements: u32,
+    /// Bound attributes, event listeners, interpolations and directive expressions
+    pub dynamic_bindings: u32,
+    /// `v-for` without a `key`
+    pub missing_keys: u32,
+    /// Elements which can never be hoisted, because they or their children are dynamic
+    pub unhoistable_nodes: u32,
+}
+
+/// Parses and transforms the SFC to gather the statistics of its `<template>`.
+/// The SFC without a template has empty statistics.
+pub fn analyze_sfc(source: &str, filename: &str) -> Result<TemplateStats, CompileError> {
+    with_compile_stack(source, || analyze_sfc_impl(source, filename))
+}
+
+fn analyze_sfc_impl(source: &str, filename: &str) -> Result<TemplateStats, CompileError> {
+    let mut parse_errors = Vec::new();
+    let mut parser = SfcParser::new(source, &mut parse_errors);
+    let sfc = parser.parse_sfc()?;
+
+    let transform_options = TransformSfcOptions {
+        is_prod: false,
+        is_ce: false,
+        props_destructure: Default::default(),
+        scope_id: "",
+        filename,
+        warn_unused_bindings: false,
+        suspense: true,
+        feature_flags: Default::default(),
+        minify_css: false,
+        is_native_tag: None,
+        hoist_static: false,
+        cache_handlers: false,
+        whitespace: Default::default(),
+        comments: None,
+        functional: false,
+        max_depth: None,
+        fs: None,
+    };
+    let transform_result = transform_sfc(sfc, transform_options, &mut Vec::new());
+
+    let mut stats = TemplateStats::default();
+    if let Some(ref template_block) = transform_result.template_block {
+        analyze_template(template_block, &mut stats);
+    }
+
+    // Keys were already resolved by the transformer
+    stats.missing_keys = transform_result
+        .bindings_helper
+        .v_for_keys
+        .iter()
+        .filter(|v_for_key| v_for_key.key.is_none())
+        .count() as u32;
+
+    Ok(stats)
+}
+
+fn analyze_template(template_block: &SfcTemplateBlock, stats: &mut TemplateStats) {
+    stats.template_size = template_block.span.hi.0 - template_block.span.lo.0;
+
+    for root in template_block.roots.iter() {
+        analyze_node(root, stats);
+    }
+}
+
+/// Collects the statistics of the node and its children.
+/// Returns whether the node is static, i.e. can be hoisted.
+fn analyze_node(node: &Node, stats: &mut TemplateStats) -> bool {
+    match node {
+        Node::Text(_, _) | Node::Comment(_, _) => true,
+
+        Node::Interpolation(interpolation) => {
+            stats.dynamic_bindings += 1;
+            !interpolation.patch_flag
+        }
+
+        Node::Element(element_node) => analyze_element(element_node, stats),
+
+        // Conditional nodes are never static
+        Node::ConditionalSeq(conditional_seq) => {
+            stats.dynamic_bindings += 1 + conditional_seq.else_if_nodes.len() as u32;
+
+            analyze_element(&conditional_seq.if_node.node, stats);
+            for else_if_node in conditional_seq.else_if_nodes.iter() {
+                analyze_element(&else_if_node.node, stats);
+            }
+            if let Some(ref else_node) = conditional_seq.else_node {
+                analyze_element(else_node, stats);
+            }
+
+            false
+        }
+    }
+}
+
+fn analyze_element(element_node: &ElementNode, stats: &mut TemplateStats) -> bool {
+    stats.elements += 1;
+
+    let mut is_static = matches!(element_node.kind, ElementKind::Element)
+        && element_node.patch_hints.flags.is_empty()
+        && !element_node.patch_hints.should_use_block;
+
+    for attr in element_node.starting_tag.attributes.iter() {
+        if let AttributeOrBinding::VBind(_) | AttributeOrBinding::VOn(_) = attr {
+            stats.dynamic_bindings += 1;
+            is_static = false;
+        }
+    }
+
+    if let Some(ref directives) = element_node.starting_tag.directives {
+        let directive_bindings = directives.v_for.is_some() as u32
+            + directives.v_html.is_some() as u32
+            + directives.v_memo.is_some() as u32
+            + directives.v_show.is_some() as u32
+            + directives.v_text.is_some() as u32
+            + directives.v_model.len() as u32
+            + directives
+                .custom
+                .iter()
+                .filter(|custom| custom.value.is_some())
+                .count() as u32;
+
+        stats.dynamic_bindings += directive_bindings;
+
+        // Some directives without expressions prevent hoisting as well, e.g. `v-once`
+        is_static &= directive_bindings == 0
+            && directives.v_once.is_none()
+            && directives.v_slot.is_none()
+            && directives.custom.is_empty();
+    }
+
+    // All the children are visited to be counted
+    for child in element_node.children.iter() {
+        is_static &= analyze_node(child, stats);
+    }
+
+    if !is_static {
+        stats.unhoistable_nodes += 1;
+    }
+
+    is_static
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_analyzes_script_setup_sfc() {
+        let source = r#"<script setup>
+import { ref } from "vue"
+const id = ref("main")
+const list = ref([1, 2, 3])
+</script>
+
+<template>
+  <div :id="id">
+    <span v-for="item in list">{{ item }}</span>
+    <p>static</p>
+  </div>
+</template>
+"#;
+
+        let stats = analyze_sfc(source, "App.vue").expect("should analyze");
+        assert_eq!(
+            TemplateStats {
+                template_size: 115,
+                elements: 3,
+                dynamic_bindings: 3,
+                missing_keys: 1,
+                unhoistable_nodes: 2,
+            },
+            stats
+        );
+    }
+}
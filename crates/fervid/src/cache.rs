@@ -0,0 +1,260 @@
+//! Cache keys of the compilations, e.g. for the external build caches.
+//!
+//! The key only depends on the source, the options after applying their defaults
+//! and the version of fervid, so it is the same across processes, machines and platforms.
+
+use fervid_codegen::STRINGIFY_THRESHOLD;
+
+use crate::{file_hash, CompileMode, CompileOptions, MAX_SOURCE_SIZE};
+
+/// Computes the key under which the output of [`crate::compile`] can be cached, as a hex string.
+///
+/// The options are normalized first, i.e. an unset option and its default value give the same key.
+/// `fs` and `cancellation` are not part of the key: the files read through `fs`
+/// are reported in [`crate::CompileResult::deps`] and should be tracked by the caller.
+///
+/// Returns `None` when `is_native_tag`, `script_preprocessor` or `template_preprocessor` is set:
+/// a closure or a preprocessor cannot be hashed, so such compilations must not be cached.
+pub fn cache_key(source: &str, options: &CompileOptions) -> Option<String> {
+    if options.is_native_tag.is_some()
+        || options.script_preprocessor.is_some()
+        || options.template_preprocessor.is_some()
+    {
+        return None;
+    }
+
+    Some(versioned_cache_key(
+        env!("CARGO_PKG_VERSION"),
+        source,
+        options,
+    ))
+}
+
+fn versioned_cache_key(version: &str, source: &str, options: &CompileOptions) -> String {
+    let is_prod = options.is_prod.unwrap_or_default();
+    let is_ssr = options.ssr.unwrap_or_default();
+    let hot_reload = options.hot_reload.unwrap_or_default() && !is_prod && !is_ssr;
+
+    let mut hasher = StableHasher::new();
+    hasher.write_str(version);
+    hasher.write_str(source);
+    hasher.write_str(&options.filename);
+    hasher.write_str(&options.id);
+    hasher.write_str(&file_hash(source, options.scope_id.as_deref()));
+    hasher.write_bool(is_prod);
+    hasher.write_bool(options.is_custom_element.unwrap_or_default());
+    hasher.write_bool(is_ssr);
+    hasher.write_debug(&options.props_destructure.unwrap_or_default());
+    hasher.write_debug(&options.gen_default_as);
+    hasher.write_bool(options.source_map.unwrap_or_default());
+    hasher.write_bool(options.warn_unused_bindings.unwrap_or_default());
+    hasher.write_bool(options.suspense.unwrap_or(true));
+    hasher.write_debug(&options.feature_flags.unwrap_or_default());
+    hasher.write_bool(options.minify_css.unwrap_or_default());
+    hasher.write_bool(options.hoist_static.unwrap_or_default());
+    hasher.write_u64(
+        options
+            .stringify_static_threshold
+            .unwrap_or(STRINGIFY_THRESHOLD) as u64,
+    );
+    hasher.write_bool(options.cache_handlers.unwrap_or_default());
+    hasher.write_debug(&options.whitespace.unwrap_or_default());
+    hasher.write_bool(options.comments.unwrap_or(!is_prod));
+    hasher.write_bool(options.functional.unwrap_or_default());
+    // Unlike the other options, no format and no padding are not the same as any of the variants
+    hasher.write_debug(&options.output_format);
+    hasher.write_debug(&options.pad);
+    hasher.write_u64(
+        options
+            .max_source_size
+            .map_or(MAX_SOURCE_SIZE, |max_size| max_size.min(MAX_SOURCE_SIZE)) as u64,
+    );
+    hasher.write_debug(&options.mode.unwrap_or(CompileMode::Strict));
+    hasher.write_bool(hot_reload);
+    if hot_reload {
+        hasher.write_debug(&options.prev_source);
+    }
+    hasher.write_bool(options.expose_filename.unwrap_or_default() && !is_prod);
+    hasher.write_bool(options.custom_block_imports.unwrap_or_default());
+
+    format!("{:032x}", hasher.finish())
+}
+
+/// 128-bit FNV-1a. Unlike `std::hash::Hash`, the bytes written do not depend
+/// on the platform (e.g. the size of `usize`) or on the Rust version.
+/// 128 bits keep the collisions unlikely across all the SFCs of a shared cache.
+struct StableHasher(u128);
+
+impl StableHasher {
+    const OFFSET_BASIS: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+    const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+    fn new() -> Self {
+        StableHasher(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Length-prefixed, so that `("ab", "c")` and `("a", "bc")` give different keys
+    fn write_str(&mut self, value: &str) {
+        self.write(&(value.len() as u64).to_le_bytes());
+        self.write(value.as_bytes());
+    }
+
+    fn write_bool(&mut self, value: bool) {
+        self.write(&[value as u8]);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// For the enums and the optional values, their `Debug` output only changes with the version
+    fn write_debug(&mut self, value: &impl std::fmt::Debug) {
+        self.write_str(&format!("{value:?}"));
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    const SOURCE: &str = "<template><div>{{ msg }}</div></template>";
+
+    #[test]
+    fn it_normalizes_default_options() {
+        let default_key = cache_key(SOURCE, &CompileOptions::default());
+
+        let explicit_defaults = CompileOptions {
+            is_prod: Some(false),
+            ssr: Some(false),
+            hot_reload: Some(false),
+            mode: Some(CompileMode::Strict),
+            suspense: Some(true),
+            stringify_static_threshold: Some(STRINGIFY_THRESHOLD),
+            max_source_size: Some(MAX_SOURCE_SIZE),
+            ..Default::default()
+        };
+        assert_eq!(default_key, cache_key(SOURCE, &explicit_defaults));
+
+        // Bigger sizes are capped at the upper bound
+        let over_max_source_size = CompileOptions {
+            max_source_size: Some(usize::MAX),
+            ..Default::default()
+        };
+        assert_eq!(default_key, cache_key(SOURCE, &over_max_source_size));
+    }
+
+    #[test]
+    fn it_has_128_bit_key() {
+        let key = cache_key(SOURCE, &CompileOptions::default()).expect("should have a key");
+        assert_eq!(32, key.len());
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn it_changes_key_with_options_and_source() {
+        let default_key = cache_key(SOURCE, &CompileOptions::default());
+
+        let changed_options = [
+            CompileOptions {
+                mode: Some(CompileMode::Tolerant),
+                ..Default::default()
+            },
+            CompileOptions {
+                ssr: Some(true),
+                ..Default::default()
+            },
+            CompileOptions {
+                scope_id: Some("custom".into()),
+                ..Default::default()
+            },
+        ];
+        for options in changed_options {
+            assert_ne!(default_key, cache_key(SOURCE, &options));
+        }
+
+        assert_ne!(
+            default_key,
+            cache_key("<template><p></p></template>", &CompileOptions::default())
+        );
+    }
+
+    #[test]
+    fn it_has_no_key_with_custom_native_tags() {
+        let options = CompileOptions {
+            is_native_tag: Some(Arc::new(|tag| tag == "view")),
+            ..Default::default()
+        };
+        assert!(cache_key(SOURCE, &CompileOptions::default()).is_some());
+        assert_eq!(None, cache_key(SOURCE, &options));
+    }
+
+    #[test]
+    fn it_has_no_key_with_script_preprocessor() {
+        #[derive(Debug)]
+        struct NoopPreprocessor;
+
+        impl crate::ScriptPreprocessor for NoopPreprocessor {
+            fn preprocess_script(
+                &self,
+                _lang: &str,
+                _content: &str,
+            ) -> Option<crate::PreprocessedScript> {
+                None
+            }
+        }
+
+        let options = CompileOptions {
+            script_preprocessor: Some(Arc::new(NoopPreprocessor)),
+            ..Default::default()
+        };
+        assert_eq!(None, cache_key(SOURCE, &options));
+    }
+
+    #[test]
+    fn it_has_no_key_with_template_preprocessor() {
+        #[derive(Debug)]
+        struct NoopPreprocessor;
+
+        impl crate::TemplatePreprocessor for NoopPreprocessor {
+            fn preprocess_template(&self, _lang: &str, _content: &str) -> Option<String> {
+                None
+            }
+        }
+
+        let options = CompileOptions {
+            template_preprocessor: Some(Arc::new(NoopPreprocessor)),
+            ..Default::default()
+        };
+        assert_eq!(None, cache_key(SOURCE, &options));
+    }
+
+    #[test]
+    fn it_includes_version_in_key() {
+        let options = CompileOptions::default();
+        assert_eq!(
+            cache_key(SOURCE, &options),
+            Some(versioned_cache_key(
+                env!("CARGO_PKG_VERSION"),
+                SOURCE,
+                &options
+            ))
+        );
+        assert_ne!(
+            versioned_cache_key("0.0.1", SOURCE, &options),
+            versioned_cache_key("0.0.2", SOURCE, &options)
+        );
+    }
+}
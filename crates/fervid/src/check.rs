@@ -0,0 +1,147 @@
+//! Diagnostics of an SFC without the code generation, e.g. for the `fervid check` command.
+
+use std::sync::Arc;
+
+use fervid_core::error::ErrorCode;
+use fervid_parser::SfcParser;
+use fervid_transform::{fs::FileSystem, transform_sfc, TransformSfcOptions};
+use swc_core::common::Spanned;
+
+use crate::{errors::CompileError, stack::with_compile_stack};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckDiagnostic {
+    /// Line of the error start, starting from 1
+    pub line: u32,
+    /// Column of the error start in characters, starting from 1
+    pub column: u32,
+    /// See [`CompileError::code`]
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Parses and transforms the SFC to collect all its errors.
+/// Unused `<script setup>` bindings are reported as well.
+pub fn check_sfc(
+    source: &str,
+    filename: &str,
+    fs: Option<Arc<dyn FileSystem>>,
+) -> Vec<CheckDiagnostic> {
+    with_compile_stack(source, || check_sfc_impl(source, filename, fs))
+}
+
+fn check_sfc_impl(
+    source: &str,
+    filename: &str,
+    fs: Option<Arc<dyn FileSystem>>,
+) -> Vec<CheckDiagnostic> {
+    let mut all_errors = Vec::<CompileError>::new();
+
+    let mut parse_errors = Vec::new();
+    let mut parser = SfcParser::new(source, &mut parse_errors);
+    let sfc = parser.parse_sfc();
+    all_errors.extend(parse_errors.into_iter().map(From::from));
+
+    match sfc {
+        Ok(sfc) => {
+            let transform_options = TransformSfcOptions {
+                is_prod: false,
+                is_ce: false,
+                props_destructure: Default::default(),
+                scope_id: "",
+                filename,
+                warn_unused_bindings: true,
+                suspense: true,
+                feature_flags: Default::default(),
+                minify_css: false,
+                is_native_tag: None,
+                hoist_static: false,
+                cache_handlers: false,
+                whitespace: Default::default(),
+                comments: None,
+                functional: false,
+                max_depth: None,
+                fs,
+            };
+
+            let mut transform_errors = Vec::new();
+            transform_sfc(sfc, transform_options, &mut transform_errors);
+            all_errors.extend(transform_errors.into_iter().map(From::from));
+        }
+        Err(e) => all_errors.push(e.into()),
+    }
+
+    all_errors
+        .iter()
+        .map(|error| {
+            let (line, column) = line_and_column(source, error.span().lo.0);
+            CheckDiagnostic {
+                line,
+                column,
+                code: error.code(),
+                message: error.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Converts the span position to the line and column, both starting from 1
+pub fn line_and_column(source: &str, pos: u32) -> (u32, u32) {
+    // Spans start from 1
+    let offset = (pos.saturating_sub(1) as usize).min(source.len());
+
+    let mut line = 1;
+    let mut column = 1;
+    for (idx, c) in source.char_indices() {
+        if idx >= offset {
+            break;
+        }
+
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reports_diagnostics() {
+        let source = r#"<script setup>
+const msg = "hi"
+const unused = 1
+</script>
+
+<template>
+  <div>{{ msg }}</div>
+</template>
+"#;
+
+        assert_eq!(
+            vec![CheckDiagnostic {
+                line: 3,
+                column: 7,
+                code: "script/unused-setup-binding",
+                message: "The `<script setup>` binding is never used.".to_owned(),
+            }],
+            check_sfc(source, "App.vue", None)
+        );
+    }
+
+    #[test]
+    fn it_converts_positions_to_lines_and_columns() {
+        let source = "ab\ncd";
+        // Spans start from 1
+        assert_eq!((1, 1), line_and_column(source, 1));
+        assert_eq!((1, 3), line_and_column(source, 3));
+        assert_eq!((2, 1), line_and_column(source, 4));
+        assert_eq!((2, 3), line_and_column(source, 100));
+    }
+}
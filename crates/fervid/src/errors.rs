@@ -1,8 +1,9 @@
 //! Error definitions for the glue code of `fervid`
 
+use fervid_core::error::{ErrorCode, Severity, SeverityLevel};
 use fervid_parser::ParseError as SfcParseError;
 use fervid_transform::error::TransformError;
-use swc_core::common::Spanned;
+use swc_core::common::{Span, Spanned, DUMMY_SP};
 
 #[derive(Debug)]
 pub enum CompileError {
@@ -18,12 +19,52 @@ pub enum CompileError {
     SfcParse(SfcParseError),
 
     /// An error during the transformation of an SFC.
-    TransformError(TransformError)
+    TransformError(TransformError),
+
+    /// The compilation was cancelled by the caller before it could finish.
+    Cancelled,
+
+    /// The source is bigger than [`CompileOptions::max_source_size`](crate::CompileOptions::max_source_size)
+    /// or than [`MAX_SOURCE_SIZE`](crate::MAX_SOURCE_SIZE), which the spans can address.
+    /// It is rejected before parsing, because the memory used by a compilation grows with the source.
+    SourceTooLarge { size: usize, max_size: usize },
+}
+
+impl ErrorCode for CompileError {
+    fn code(&self) -> &'static str {
+        match self {
+            CompileError::SfcParse(e) => e.kind.code(),
+            CompileError::TransformError(e) => e.code(),
+            CompileError::Cancelled => "compile/cancelled",
+            CompileError::SourceTooLarge { .. } => "compile/source-too-large",
+        }
+    }
+}
+
+impl CompileError {
+    /// Replaces the span of the error, e.g. to point it into the original source
+    pub(crate) fn map_span(&mut self, map: impl FnOnce(Span) -> Span) {
+        let span = match self {
+            CompileError::SfcParse(e) => &mut e.span,
+            CompileError::TransformError(e) => e.span_mut(),
+            CompileError::Cancelled | CompileError::SourceTooLarge { .. } => return,
+        };
+        *span = map(*span);
+    }
 }
 
 impl std::fmt::Display for CompileError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            CompileError::SfcParse(e) => e.fmt(f),
+            CompileError::TransformError(e) => e.fmt(f),
+            CompileError::Cancelled => write!(f, "The compilation was cancelled."),
+            CompileError::SourceTooLarge { size, max_size } => write!(
+                f,
+                "The source is {} bytes, which is more than the maximum of {} bytes.",
+                size, max_size
+            ),
+        }
     }
 }
 
@@ -43,7 +84,48 @@ impl Spanned for CompileError {
     fn span(&self) -> swc_core::common::Span {
         match self {
             CompileError::SfcParse(e) => e.span,
-            CompileError::TransformError(e) => e.span()
+            CompileError::TransformError(e) => e.span(),
+            CompileError::Cancelled | CompileError::SourceTooLarge { .. } => DUMMY_SP,
+        }
+    }
+}
+
+impl Severity for CompileError {
+    fn get_severity(&self) -> SeverityLevel {
+        match self {
+            CompileError::SfcParse(e) => e.get_severity(),
+            CompileError::TransformError(e) => e.get_severity(),
+            CompileError::Cancelled | CompileError::SourceTooLarge { .. } => {
+                SeverityLevel::UnrecoverableError
+            }
         }
     }
 }
+
+/// A [`CompileError`] flattened for the tooling, e.g. to show it in the editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileDiagnostic {
+    /// See [`ErrorCode`]
+    pub code: &'static str,
+    pub message: String,
+    /// Erroneous code, [`DUMMY_SP`] when not applicable
+    pub span: Span,
+    pub severity: SeverityLevel,
+}
+
+impl From<&CompileError> for CompileDiagnostic {
+    fn from(value: &CompileError) -> Self {
+        CompileDiagnostic {
+            code: value.code(),
+            message: value.to_string(),
+            span: value.span(),
+            severity: value.get_severity(),
+        }
+    }
+}
+
+impl Severity for CompileDiagnostic {
+    fn get_severity(&self) -> SeverityLevel {
+        self.severity
+    }
+}
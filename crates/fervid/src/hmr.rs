@@ -0,0 +1,124 @@
+//! Hot module replacement (HMR) code for the dev servers.
+//!
+//! The component gets a `__hmrId` and registers itself in `__VUE_HMR_RUNTIME__`.
+//! When the module is replaced, the new version either only re-renders the existing instances
+//! (when just the `<template>` changed) or reloads them.
+
+use std::hash::{Hash, Hasher};
+
+use fervid_parser::{parse_lossy, LossyParseOptions};
+use fxhash::FxHasher32;
+
+/// Stable id of the component for HMR, derived from the path of the SFC.
+/// Unlike the scope id, it does not change when the contents of the SFC change.
+pub fn hmr_id(filename: &str) -> String {
+    let mut hasher = FxHasher32::default();
+    filename.hash(&mut hasher);
+    format!("{:08x}", hasher.finish() as u32)
+}
+
+/// Whether only the `<template>` changed between the two versions of an SFC,
+/// so that the existing instances can keep their state and only re-render.
+/// Any change outside of the `<template>` (including the whitespace) needs a reload.
+pub fn is_rerender_only(prev_source: &str, source: &str) -> bool {
+    let (Some(prev), Some(next)) = (without_template(prev_source), without_template(source)) else {
+        return false;
+    };
+
+    prev == next && prev_source != source
+}
+
+/// Source of the SFC with its `<template>` block cut out
+fn without_template(source: &str) -> Option<String> {
+    let parse_options = LossyParseOptions {
+        max_input_size: usize::MAX,
+        ..Default::default()
+    };
+    // Recoverable errors are not checked, e.g. `<div/>` in the template is reported by the HTML parser.
+    // A template span which is off keeps a part of the template in the rest, which then needs a reload
+    let parse_result = parse_lossy(source, parse_options);
+
+    // Spans start from 1
+    let span = parse_result.descriptor?.template?.span;
+    let lo = span.lo.0.checked_sub(1)? as usize;
+    let hi = span.hi.0.checked_sub(1)? as usize;
+
+    let mut rest = String::with_capacity(source.len() - (hi - lo));
+    rest.push_str(source.get(..lo)?);
+    rest.push_str(source.get(hi..)?);
+    Some(rest)
+}
+
+/// Code appended to the compiled module: the component registration and the `accept` handler.
+/// `_rerender_only` tells the handler of the previous version how to apply this one.
+pub(crate) fn hmr_footer(component: &str, hmr_id: &str, rerender_only: bool) -> String {
+    let mut footer = format!(
+        r#"
+{component}.__hmrId = "{hmr_id}";
+typeof __VUE_HMR_RUNTIME__ !== "undefined" && __VUE_HMR_RUNTIME__.createRecord({component}.__hmrId, {component});"#
+    );
+
+    if rerender_only {
+        footer.push_str("\nexport const _rerender_only = true;");
+    }
+
+    footer.push_str(
+        r#"
+import.meta.hot.accept((mod) => {
+    if (!mod) return;
+    const { default: updated, _rerender_only } = mod;
+    if (_rerender_only) {
+        __VUE_HMR_RUNTIME__.rerender(updated.__hmrId, updated.render);
+    } else {
+        __VUE_HMR_RUNTIME__.reload(updated.__hmrId, updated);
+    }
+});"#,
+    );
+
+    footer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"<template><div>{{ msg }}</div></template>
+<script setup>
+const msg = "hi"
+</script>
+<style scoped>
+div { color: red }
+</style>
+"#;
+
+    #[test]
+    fn it_detects_template_only_edit() {
+        let edited = SOURCE.replace("<div>{{ msg }}</div>", "<p>{{ msg }}!</p>");
+        assert!(is_rerender_only(SOURCE, &edited));
+    }
+
+    #[test]
+    fn it_needs_reload_for_script_and_style_edits() {
+        let script_edit = SOURCE.replace(r#""hi""#, r#""hello""#);
+        assert!(!is_rerender_only(SOURCE, &script_edit));
+
+        let style_edit = SOURCE.replace("color: red", "color: blue");
+        assert!(!is_rerender_only(SOURCE, &style_edit));
+
+        // Nothing changed
+        assert!(!is_rerender_only(SOURCE, SOURCE));
+    }
+
+    #[test]
+    fn it_generates_hmr_footer() {
+        let footer = hmr_footer("__sfc__", "7ba5bd90", false);
+        assert!(footer.contains(r#"__sfc__.__hmrId = "7ba5bd90";"#));
+        assert!(footer.contains("__VUE_HMR_RUNTIME__.createRecord(__sfc__.__hmrId, __sfc__)"));
+        assert!(footer.contains("__VUE_HMR_RUNTIME__.rerender(updated.__hmrId, updated.render)"));
+        assert!(footer.contains("__VUE_HMR_RUNTIME__.reload(updated.__hmrId, updated)"));
+        assert!(!footer.contains("_rerender_only = true"));
+
+        let footer = hmr_footer("__sfc__", "7ba5bd90", true);
+        assert!(footer.contains("export const _rerender_only = true;"));
+    }
+}
@@ -25,7 +25,19 @@
 //!   is_ce: false,
 //!   props_destructure: fervid_transform::PropsDestructureConfig::default(),
 //!   scope_id: "filehash",
-//!   filename: "input.vue"
+//!   filename: "input.vue",
+//!   warn_unused_bindings: false,
+//!   suspense: true,
+//!   feature_flags: Default::default(),
+//!   minify_css: false,
+//!   is_native_tag: None,
+//!   hoist_static: false,
+//!   cache_handlers: false,
+//!   whitespace: Default::default(),
+//!   comments: None,
+//!   functional: false,
+//!   max_depth: None,
+//!   fs: None,
 //! };
 //! let transform_result = fervid_transform::transform_sfc(sfc, transform_options, &mut transform_errors);
 //!
@@ -51,37 +63,82 @@
 
 extern crate lazy_static;
 
+pub mod analyze;
+pub mod cache;
+pub mod check;
 pub mod errors;
+pub mod hmr;
 #[deprecated]
 pub mod parser_old;
+pub mod stack;
 
-use errors::CompileError;
+use errors::{CompileDiagnostic, CompileError};
 use fervid_codegen::CodegenContext;
 pub use fervid_core::*;
-use fervid_parser::SfcParser;
+use fervid_parser::{
+    inline_src_blocks, pad_block_content, parse_lossy_with, LossyParseOptions, SfcParser,
+};
+pub use fervid_parser::{
+    BlockPadding, PreprocessedScript, ScriptPreprocessor, TemplatePreprocessor,
+    MAX_INPUT_SIZE as MAX_SOURCE_SIZE,
+};
+use fervid_transform::fs::join_paths;
+pub use fervid_transform::fs::{FileSystem, MemoryFileSystem, OsFileSystem};
+pub use fervid_transform::IsNativeTag;
 use fervid_transform::{
-    style::should_transform_style_block, transform_sfc, PropsDestructureConfig, SetupBinding, TransformSfcOptions
+    error::{TemplateError, TemplateErrorKind, TransformError},
+    misc::expose_filename,
+    style::{
+        create_style_scope, record_style_deps, should_transform_style_block, transform_style_blocks,
+    },
+    template::transform_and_record_template,
+    transform_sfc, BindingsHelper, FeatureFlags, PropsDestructureConfig, SetupBinding,
+    TransformSfcOptions,
 };
 use fxhash::FxHasher32;
+use stack::with_compile_stack;
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use swc_core::{
+    common::{BytePos, FileName, Span},
+    ecma::ast::Expr,
 };
-use swc_core::{common::FileName, ecma::ast::Expr};
 
 // TODO Add severity to errors
 // TODO Better structs
 
-#[derive(Debug, Clone)]
+#[derive(Clone, Default)]
 pub struct CompileOptions<'o> {
     // ast?: RootNode;
     pub filename: Cow<'o, str>,
     pub id: Cow<'o, str>,
+    /// Scope ID of the component, e.g. `data-v-7ba5bd90` or `7ba5bd90`, used for the scoped styles,
+    /// CSS variables and `__scopeId`. Allows the integrations to use their own hashing scheme.
+    /// Default: hash of the source
+    pub scope_id: Option<Cow<'o, str>>,
     // pub scoped: Option<bool>,
     // pub slotted: Option<bool>,
     pub is_prod: Option<bool>,
     pub is_custom_element: Option<bool>,
+    /// Generate the `ssrRender` function instead of `render`. Default: `false`.
     pub ssr: Option<bool>,
+    /// Recognizes the native elements of the target platform, e.g. for canvas or native renderers.
+    /// Other tags are compiled as components. Default: HTML, SVG and MathML tags.
+    pub is_native_tag: Option<IsNativeTag>,
+    /// Provides the files to the compiler, e.g. to resolve `defineProps<Props>()`
+    /// where `Props` is imported from `./types`. Default: an empty in-memory file system.
+    ///
+    /// `<template src>` and `<style src>` are also loaded through it, relative to `filename`.
+    /// The spans of the result and the source map still refer to the SFC,
+    /// positions inside of a loaded block point to its `src` attribute.
+    /// `<script src>` is always compiled to an import for the bundler to resolve.
+    pub fs: Option<Arc<dyn FileSystem>>,
     pub props_destructure: Option<PropsDestructureConfig>,
     // pub ssrCssVars?: string[],
     // pub inMap?: RawSourceMap,
@@ -102,6 +159,140 @@ pub struct CompileOptions<'o> {
 
     // fervid-specific
     pub source_map: Option<bool>,
+    /// Report unused `<script setup>` bindings as warnings
+    pub warn_unused_bindings: Option<bool>,
+    /// Whether `<Suspense>` is supported by the consumer (default `true`).
+    /// When disabled, components with async `setup` are reported as errors.
+    pub suspense: Option<bool>,
+    /// Compile-time feature flags (`__VUE_OPTIONS_API__`, `__VUE_PROD_DEVTOOLS__`)
+    /// matching the `define` settings of the bundler
+    pub feature_flags: Option<FeatureFlags>,
+    /// Minify the emitted `lang="css"` styles. Default: `false`
+    pub minify_css: Option<bool>,
+    /// Create the fully static elements of the template once as module-level constants
+    /// instead of on every render. Default: `false`
+    pub hoist_static: Option<bool>,
+    /// Minimum number of nodes in the consecutive static elements to create them from HTML
+    /// with a single `createStaticVNode`. Only used with `hoist_static`. Default: `20`
+    pub stringify_static_threshold: Option<usize>,
+    /// Cache the inline `v-on` handlers in `_cache`, so that the child components
+    /// are not re-rendered because of the new function identities. Default: `false`
+    pub cache_handlers: Option<bool>,
+    /// Whether the whitespace of the template is condensed or preserved. Default: condensed
+    pub whitespace: Option<WhitespaceMode>,
+    /// Render the template comments. Default: in development only
+    pub comments: Option<bool>,
+    /// Compile an SFC without scripts as a functional component: a plain function of the props
+    /// and the context which returns the vnodes, without an instance and `setup`.
+    /// SFCs with scripts are compiled as the regular components with an error. Ignored in SSR. Default: `false`
+    pub functional: Option<bool>,
+    /// Formatting of the emitted JavaScript. Default: indented code without the comments
+    pub output_format: Option<OutputFormat>,
+    /// Pad the emitted styles which were not compiled by fervid,
+    /// so that their lines match the original file when they are compiled independently.
+    /// Scripts are always compiled by fervid, and their source map already points to the original file
+    pub pad: Option<BlockPadding>,
+    /// When set to `true` (e.g. from another thread), the compilation stops
+    /// at the next phase boundary with [`CompileError::Cancelled`]
+    pub cancellation: Option<&'o AtomicBool>,
+    /// Maximum size of the source in bytes, e.g. to protect the shared CI runners
+    /// from the pathological inputs like megabyte-long attribute values.
+    /// Bigger sources fail with [`CompileError::SourceTooLarge`].
+    /// Default and upper bound: [`MAX_SOURCE_SIZE`], the biggest source which the spans can address
+    pub max_source_size: Option<usize>,
+    /// How the malformed sources are handled. Default: [`CompileMode::Strict`]
+    pub mode: Option<CompileMode>,
+    /// Append the HMR code: the `__hmrId` derived from the `filename`, the registration in
+    /// `__VUE_HMR_RUNTIME__` and the `import.meta.hot.accept` handler which re-renders
+    /// or reloads the component. With `gen_default_as`, the caller still has to export
+    /// the component as default. Ignored in production and SSR. Default: `false`
+    pub hot_reload: Option<bool>,
+    /// Previous source of the SFC when it is compiled again for HMR.
+    /// When only the `<template>` changed, the module exports `_rerender_only`,
+    /// so that the component is re-rendered instead of reloaded. Only used with `hot_reload`
+    pub prev_source: Option<Cow<'o, str>>,
+    /// Add the `filename` to the component as `__file`, which the devtools and the runtime warnings
+    /// show. Ignored in production, where the paths should not leak into the bundle. Default: `false`
+    pub expose_filename: Option<bool>,
+    /// Import the custom blocks (e.g. `<i18n>`) by their [`custom_block_id`]s and call their
+    /// default exports with the component. The bundler has to serve these ids, see
+    /// [`CompileResult::other_assets`]. Default: `false`, the blocks are only reported
+    pub custom_block_imports: Option<bool>,
+    /// Compiles the `<script>` blocks with a `lang` which fervid does not support,
+    /// e.g. `<script lang="coffee">`, to JavaScript or TypeScript. Compilations using it are not cached,
+    /// see [`cache::cache_key`]. Default: such scripts are rejected
+    pub script_preprocessor: Option<Arc<dyn ScriptPreprocessor + Send + Sync>>,
+    /// Converts the `<template>` blocks with a `lang` other than `html`, e.g. `<template lang="pug">`,
+    /// to HTML. Compilations using it are not cached, see [`cache::cache_key`].
+    /// Default: such templates are rejected
+    pub template_preprocessor: Option<Arc<dyn TemplatePreprocessor + Send + Sync>>,
+}
+
+// `is_native_tag` is a closure, so `Debug` cannot be derived
+impl std::fmt::Debug for CompileOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompileOptions")
+            .field("filename", &self.filename)
+            .field("id", &self.id)
+            .field("scope_id", &self.scope_id)
+            .field("is_prod", &self.is_prod)
+            .field("is_custom_element", &self.is_custom_element)
+            .field("ssr", &self.ssr)
+            .field(
+                "is_native_tag",
+                &self.is_native_tag.as_ref().map(|_| "Fn(&str) -> bool"),
+            )
+            .field("fs", &self.fs)
+            .field("props_destructure", &self.props_destructure)
+            .field("gen_default_as", &self.gen_default_as)
+            .field("source_map", &self.source_map)
+            .field("warn_unused_bindings", &self.warn_unused_bindings)
+            .field("suspense", &self.suspense)
+            .field("feature_flags", &self.feature_flags)
+            .field("minify_css", &self.minify_css)
+            .field("hoist_static", &self.hoist_static)
+            .field(
+                "stringify_static_threshold",
+                &self.stringify_static_threshold,
+            )
+            .field("cache_handlers", &self.cache_handlers)
+            .field("whitespace", &self.whitespace)
+            .field("comments", &self.comments)
+            .field("functional", &self.functional)
+            .field("output_format", &self.output_format)
+            .field("pad", &self.pad)
+            .field("cancellation", &self.cancellation)
+            .field("max_source_size", &self.max_source_size)
+            .field("mode", &self.mode)
+            .field("hot_reload", &self.hot_reload)
+            .field("prev_source", &self.prev_source)
+            .field("expose_filename", &self.expose_filename)
+            .field("custom_block_imports", &self.custom_block_imports)
+            .field("script_preprocessor", &self.script_preprocessor)
+            .field("template_preprocessor", &self.template_preprocessor)
+            .finish()
+    }
+}
+
+/// Handling of the malformed sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompileMode {
+    /// Unparseable SFCs fail with [`CompileError::SfcParse`]
+    #[default]
+    Strict,
+    /// Always produce a module from the parts which could be parsed and report the rest
+    /// in [`CompileResult::errors`], e.g. for the IDEs compiling the file while it is edited
+    Tolerant,
+}
+
+/// Formatting of the emitted JavaScript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Indented code with the patch flags followed by their names, e.g. `1 /* TEXT */`,
+    /// to read it the same way as the output of the official compiler
+    Pretty,
+    /// Code without the whitespace, smaller without running a separate minifier
+    Compact,
 }
 
 pub struct CompileResult {
@@ -112,6 +303,51 @@ pub struct CompileResult {
     pub other_assets: Vec<CompileEmittedAsset>,
     pub source_map: Option<String>,
     pub setup_bindings: Vec<SetupBinding>,
+    /// Whether `setup` is async and thus needs a `<Suspense>` boundary
+    pub is_async_setup: bool,
+    /// Files the SFC depends on (type imports, `src` blocks and style `@import`/`url()` references)
+    pub deps: Vec<String>,
+    /// Bindings of the scripts, to be cached for [`compile_template_rerender`]
+    pub script_bindings: ScriptBindings,
+    /// `v-for` directives of the template with their keys, in order of appearance
+    pub v_for_keys: Vec<CompileVForKey>,
+    /// Runtime helpers imported by each part of `code`
+    pub helpers: CompileHelpers,
+    /// Id of the component for HMR, only present with `hot_reload`
+    pub hmr_id: Option<String>,
+}
+
+/// Runtime helpers imported by the parts of a compiled module, e.g. for the bundlers
+/// deduplicating the helper imports when concatenating the modules.
+/// Helpers are listed by their exported names, e.g. `createVNode`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompileHelpers {
+    /// Helpers from `vue` used by the `<script>` and `<script setup>`, e.g. `defineComponent`
+    pub script: Vec<&'static str>,
+    /// Helpers from `vue` used by the render function
+    pub template: Vec<&'static str>,
+    /// Helpers from `vue/server-renderer` used by the `ssrRender` function
+    pub ssr: Vec<&'static str>,
+}
+
+impl CompileResult {
+    /// Errors and warnings of the compilation with their codes and severities
+    pub fn diagnostics(&self) -> Vec<CompileDiagnostic> {
+        self.errors.iter().map(CompileDiagnostic::from).collect()
+    }
+}
+
+/// Bindings of the `<script>` and `<script setup>` of a compiled SFC.
+/// When only the `<template>` of the SFC changes, these are reused
+/// to compile the template without re-compiling the scripts.
+#[derive(Debug)]
+pub struct ScriptBindings(BindingsHelper);
+
+impl ScriptBindings {
+    /// Whether the scripts are written in TypeScript, and so is the compiled module
+    pub fn is_ts(&self) -> bool {
+        self.0.is_ts
+    }
 }
 
 pub struct CompileEmittedStyle {
@@ -119,38 +355,109 @@ pub struct CompileEmittedStyle {
     pub is_compiled: bool,
     pub lang: String,
     pub is_scoped: bool,
+    pub module: Option<String>,
+}
+
+pub struct CompileVForKey {
+    /// Byte offset of the `v-for` directive start in the source
+    pub lo: u32,
+    /// Byte offset of the `v-for` directive end in the source
+    pub hi: u32,
+    /// Compiled `key` expression, e.g. `item.id`. `None` when the list is not keyed
+    pub key: Option<String>,
 }
 
 pub struct CompileEmittedAsset {
+    /// Byte offset of the block start in the source
     pub lo: u32,
+    /// Byte offset of the block end in the source
     pub hi: u32,
     pub tag_name: String,
+    /// Value of the `lang` attribute, e.g. `json` for `<i18n lang="json">`
+    pub lang: Option<String>,
+    /// Static attributes of the block, e.g. `("locale", "en")` for `<i18n locale="en">`
+    pub attributes: Vec<(String, String)>,
     pub content: String,
+    /// Id under which the block is imported by `code`, only present with `custom_block_imports`
+    pub import_id: Option<String>,
+}
+
+/// Name of the component when the module has no `gen_default_as`, but code is appended to it
+const SFC_MAIN: &str = "_sfc_main";
+
+/// Id of a custom block imported by the compiled module, e.g. `App.vue?vue&type=i18n&index=0&lang.json`.
+/// `index` counts the custom blocks only. Without the `lang` attribute, the language is the block name.
+pub fn custom_block_id(filename: &str, block_type: &str, index: usize, lang: &str) -> String {
+    format!("{filename}?vue&type={block_type}&index={index}&lang.{lang}")
 }
 
 /// A more general-purpose SFC compilation function.
 /// Not production-ready yet.
 pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, CompileError> {
+    with_compile_stack(source, || compile_impl(source, options))
+}
+
+fn compile_impl(source: &str, options: CompileOptions) -> Result<CompileResult, CompileError> {
     let mut all_errors = Vec::<CompileError>::new();
 
     // Options
     let is_prod = options.is_prod.unwrap_or_default();
     let is_custom_element = options.is_custom_element.unwrap_or_default();
+    let minify_css = options.minify_css.unwrap_or_default();
+    let is_ssr = options.ssr.unwrap_or_default();
+    let hot_reload = options.hot_reload.unwrap_or_default() && !is_prod && !is_ssr;
+
+    // Cancellation is only checked between the phases
+    let check_cancelled = || match options.cancellation {
+        Some(cancellation) if cancellation.load(Ordering::Relaxed) => Err(CompileError::Cancelled),
+        _ => Ok(()),
+    };
+
+    // Bigger sources would overflow the spans and corrupt the source map
+    let max_size = options
+        .max_source_size
+        .map_or(MAX_SOURCE_SIZE, |max_size| max_size.min(MAX_SOURCE_SIZE));
+    let check_size = |source: &str| match source.len() {
+        size if size > max_size => Err(CompileError::SourceTooLarge { size, max_size }),
+        _ => Ok(()),
+    };
+    check_size(source)?;
+
+    // External blocks, e.g. `<template src="./template.html">`, are loaded relative to the SFC
+    let mut src_deps = Vec::new();
+    let mut inlined = inline_src_blocks(source, &mut |src| {
+        let path = join_paths(&options.filename, src);
+        let content = options.fs.as_ref()?.read_file(&path)?;
+        src_deps.push(path);
+        Some(content)
+    });
+    all_errors.extend(
+        std::mem::take(&mut inlined.errors)
+            .into_iter()
+            .map(From::from),
+    );
+    // The spans of the result are reported against the original source
+    let original_source = source;
+    let source = inlined.source.as_ref();
+    check_size(source)?;
 
     // Parse
-    let mut sfc_parsing_errors = Vec::new();
-    let mut parser = SfcParser::new(source, &mut sfc_parsing_errors);
-    let sfc = parser.parse_sfc()?;
-    all_errors.extend(sfc_parsing_errors.into_iter().map(From::from));
+    check_cancelled()?;
+    let sfc = parse_sfc_with_mode(
+        source,
+        options.mode.unwrap_or_default(),
+        options.script_preprocessor.as_ref(),
+        options.template_preprocessor.as_ref(),
+        &mut all_errors,
+    )
+    .map_err(|mut e| {
+        e.map_span(|span| inlined.original_span(span));
+        e
+    })?;
+    check_cancelled()?;
 
     // For scopes
-    // TODO Research if it's better to compute that on the caller site or here
-    let file_hash = {
-        let mut hasher = FxHasher32::default();
-        source.hash(&mut hasher);
-        let num = hasher.finish();
-        format!("{:x}", num)
-    };
+    let file_hash = file_hash(source, options.scope_id.as_deref());
 
     // Transform
     let mut transform_errors = Vec::new();
@@ -160,58 +467,214 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
         props_destructure: options.props_destructure.unwrap_or_default(),
         scope_id: &file_hash,
         filename: &options.filename,
+        warn_unused_bindings: options.warn_unused_bindings.unwrap_or_default(),
+        suspense: options.suspense.unwrap_or(true),
+        feature_flags: options.feature_flags.unwrap_or_default(),
+        minify_css,
+        is_native_tag: options.is_native_tag.clone(),
+        hoist_static: options.hoist_static.unwrap_or_default(),
+        cache_handlers: options.cache_handlers.unwrap_or_default(),
+        whitespace: options.whitespace.unwrap_or_default(),
+        comments: options.comments,
+        functional: options.functional.unwrap_or_default(),
+        max_depth: max_template_depth(options.mode.unwrap_or_default()),
+        fs: options.fs.clone(),
     };
-    let transform_result = transform_sfc(sfc, transform_options, &mut transform_errors);
+    let mut transform_result = transform_sfc(sfc, transform_options, &mut transform_errors);
     all_errors.extend(transform_errors.into_iter().map(From::from));
+    let is_async_setup = transform_result.is_async_setup;
+    let mut deps = src_deps;
+    deps.extend(transform_result.deps);
+    let script_bindings = ScriptBindings(transform_result.bindings_helper.clone_script_bindings());
+    if options.expose_filename.unwrap_or_default() && !is_prod {
+        expose_filename(&mut transform_result.exported_obj, &options.filename);
+    }
+    check_cancelled()?;
 
     // Codegen
     let mut ctx = CodegenContext::with_bindings_helper(transform_result.bindings_helper);
+    ctx.is_ssr = is_ssr;
+    ctx.stringify_threshold = options.stringify_static_threshold;
+    ctx.annotate_patch_flags = matches!(options.output_format, Some(OutputFormat::Pretty));
 
-    let template_expr: Option<Expr> = transform_result
-        .template_block
-        .and_then(|template_block| ctx.generate_sfc_template(&template_block));
+    // Template helpers are collected separately to report them per part of the module
+    let mut script_vue_imports = std::mem::take(&mut ctx.bindings_helper.vue_imports)
+        - transform_result.template_vue_imports;
+    ctx.bindings_helper.vue_imports = transform_result.template_vue_imports;
 
+    let template_expr: Option<Expr> = transform_result.template_block.and_then(|template_block| {
+        if ctx.is_ssr {
+            ctx.generate_ssr_render(&template_block)
+        } else {
+            ctx.generate_sfc_template(&template_block)
+        }
+    });
+
+    let template_vue_imports = ctx.bindings_helper.vue_imports;
+    ctx.bindings_helper.vue_imports |= script_vue_imports;
+    let module_vue_imports = ctx.bindings_helper.vue_imports;
+
+    // Code appended to the module needs the component in a variable
+    let has_custom_block_imports = options.custom_block_imports.unwrap_or_default()
+        && !transform_result.custom_blocks.is_empty();
+    let gen_default_as = match options.gen_default_as.as_deref() {
+        None if hot_reload || has_custom_block_imports => Some(SFC_MAIN),
+        gen_default_as => gen_default_as,
+    };
     let sfc_module = ctx.generate_module(
         template_expr,
         *transform_result.module,
         transform_result.exported_obj,
         transform_result.setup_fn,
-        options.gen_default_as.as_deref(),
+        gen_default_as,
     );
+    script_vue_imports |= ctx.bindings_helper.vue_imports - module_vue_imports;
+    check_cancelled()?;
+
+    let helpers = CompileHelpers {
+        script: vue_helper_names(script_vue_imports),
+        template: vue_helper_names(template_vue_imports),
+        ssr: ctx
+            .ssr_imports
+            .into_iter()
+            .map(|import| &import.as_str()[1..])
+            .collect(),
+    };
 
     // Convert AST to string
-    let (code, source_map) = CodegenContext::stringify(
-        &source,
+    let (mut code, source_map) = CodegenContext::stringify_with_original(
+        source,
+        original_source,
+        &|pos| inlined.original_pos(pos),
         &sfc_module,
         FileName::Custom(options.filename.to_string()),
         options.source_map.unwrap_or(false),
-        false,
+        matches!(options.output_format, Some(OutputFormat::Compact)),
     );
 
+    let custom_block_imports = options.custom_block_imports.unwrap_or_default();
+    let other_assets: Vec<CompileEmittedAsset> = transform_result
+        .custom_blocks
+        .into_iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let attributes: Vec<(String, String)> = block
+                .starting_tag
+                .attributes
+                .iter()
+                .filter_map(|attr| match attr {
+                    AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                        Some((name.to_string(), value.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let tag_name = block.starting_tag.tag_name.to_string();
+            let lang = attributes
+                .iter()
+                .find_map(|(name, value)| (name == "lang").then(|| value.to_owned()));
+            let import_id = custom_block_imports.then(|| {
+                let import_lang = lang.as_deref().unwrap_or(&tag_name);
+                custom_block_id(&options.filename, &tag_name, index, import_lang)
+            });
+
+            // Spans start from 1
+            let span = inlined.original_span(block.span);
+            CompileEmittedAsset {
+                lo: span.lo.0.saturating_sub(1),
+                hi: span.hi.0.saturating_sub(1),
+                tag_name,
+                lang,
+                attributes,
+                content: block.content.to_string(),
+                import_id,
+            }
+        })
+        .collect();
+
+    // Appended to keep the source map of the code intact.
+    // Custom blocks export a function which receives the component, e.g. to attach the translations of `<i18n>`
+    if let Some(component) = gen_default_as {
+        for (index, import_id) in other_assets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, asset)| Some((index, asset.import_id.as_deref()?)))
+        {
+            code.push_str(&format!(
+                "\nimport block{index} from '{import_id}';\nif (typeof block{index} === 'function') block{index}({component});"
+            ));
+        }
+    }
+
+    let hmr_id = hot_reload.then(|| hmr::hmr_id(&options.filename));
+    if let (Some(hmr_id), Some(component)) = (hmr_id.as_deref(), gen_default_as) {
+        let rerender_only = options
+            .prev_source
+            .as_deref()
+            .is_some_and(|prev_source| hmr::is_rerender_only(prev_source, original_source));
+        code.push_str(&hmr::hmr_footer(component, hmr_id, rerender_only));
+    }
+
+    if options.gen_default_as.is_none() {
+        if let Some(component) = gen_default_as {
+            code.push_str(&format!("\nexport default {component};"));
+        }
+    }
+
     let styles = transform_result
         .style_blocks
         .into_iter()
-        .map(|style_block| CompileEmittedStyle {
-            code: style_block.content.to_string(),
-            is_compiled: should_transform_style_block(&style_block),
-            lang: style_block.lang.to_string(),
-            is_scoped: style_block.is_scoped,
+        .map(|style_block| {
+            let is_compiled = should_transform_style_block(&style_block, minify_css);
+            // The positions of a loaded style are already the ones in its file
+            let code = match options.pad {
+                Some(padding) if !is_compiled && !inlined.is_inlined(style_block.span) => {
+                    pad_block_content(
+                        original_source,
+                        inlined.original_span(style_block.span),
+                        padding,
+                    )
+                    .unwrap_or_else(|mut error| {
+                        // Spans of the errors are mapped to the original source below
+                        error.span = style_block.span;
+                        all_errors.push(error.into());
+                        style_block.content.to_string()
+                    })
+                }
+                _ => style_block.content.to_string(),
+            };
+
+            CompileEmittedStyle {
+                code,
+                is_compiled,
+                lang: style_block.lang.to_string(),
+                is_scoped: style_block.is_scoped,
+                module: style_block.module.map(|module| module.to_string()),
+            }
         })
         .collect();
 
-    let other_assets = transform_result
-        .custom_blocks
-        .into_iter()
-        .map(|block| {
-            CompileEmittedAsset {
-                lo: 0, // todo
-                hi: 0, // todo
-                tag_name: block.starting_tag.tag_name.to_string(),
-                content: block.content.to_string(),
+    let v_for_keys = ctx
+        .bindings_helper
+        .v_for_keys
+        .iter()
+        .map(|v_for_key| {
+            // Spans start from 1
+            let span = inlined.original_span(v_for_key.span);
+            CompileVForKey {
+                lo: span.lo.0.saturating_sub(1),
+                hi: span.hi.0.saturating_sub(1),
+                key: v_for_key.key.as_deref().map(|key| {
+                    CodegenContext::stringify(source, key, FileName::Anon, false, false).0
+                }),
             }
         })
         .collect();
 
+    for error in all_errors.iter_mut() {
+        error.map_span(|span| inlined.original_span(span));
+    }
+
     Ok(CompileResult {
         code,
         file_hash,
@@ -220,9 +683,326 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
         other_assets,
         source_map,
         setup_bindings: ctx.bindings_helper.setup_bindings,
+        is_async_setup,
+        deps,
+        script_bindings,
+        v_for_keys,
+        helpers,
+        hmr_id,
+    })
+}
+
+/// Scope id without the `data-v-` prefix, by default the hash of the source
+pub(crate) fn file_hash(source: &str, scope_id: Option<&str>) -> String {
+    match scope_id {
+        Some(scope_id) => scope_id
+            .strip_prefix("data-v-")
+            .unwrap_or(scope_id)
+            .to_string(),
+        None => {
+            let mut hasher = FxHasher32::default();
+            source.hash(&mut hasher);
+            let num = hasher.finish();
+            format!("{:x}", num)
+        }
+    }
+}
+
+/// Exported names of the helpers, e.g. `createVNode` for `_createVNode`
+fn vue_helper_names(vue_imports: VueImportsSet) -> Vec<&'static str> {
+    vue_imports
+        .into_iter()
+        .map(|import| &import.as_str()[1..])
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileStyleOptions<'o> {
+    /// Scope ID, e.g. `data-v-7ba5bd90` or `7ba5bd90`
+    pub id: Cow<'o, str>,
+    pub scoped: Option<bool>,
+    /// Minify the output. Default: `false`
+    pub minify: Option<bool>,
+}
+
+pub struct CompileStyleResult {
+    pub code: String,
+    pub errors: Vec<CompileError>,
+    /// Files referenced by `@import` and `url()`
+    pub deps: Vec<String>,
+}
+
+/// Compiles a CSS style block independently from an SFC,
+/// similar to `compileStyle` of the official compiler.
+/// Sources bigger than [`MAX_SOURCE_SIZE`] are not compiled and fail with [`CompileError::SourceTooLarge`].
+pub fn compile_style(source: &str, options: CompileStyleOptions) -> CompileStyleResult {
+    // Bigger sources would overflow the spans and corrupt the source map
+    if source.len() > MAX_SOURCE_SIZE {
+        return CompileStyleResult {
+            code: String::new(),
+            errors: vec![CompileError::SourceTooLarge {
+                size: source.len(),
+                max_size: MAX_SOURCE_SIZE,
+            }],
+            deps: Vec::new(),
+        };
+    }
+
+    let mut style_blocks = vec![SfcStyleBlock {
+        lang: fervid_atom!("css"),
+        content: source.into(),
+        is_scoped: options.scoped.unwrap_or_default(),
+        module: None,
+        span: Span {
+            lo: BytePos(1),
+            hi: BytePos(1 + source.len() as u32),
+        },
+    }];
+
+    let mut deps = Default::default();
+    record_style_deps(&style_blocks, &mut deps);
+
+    let scope_id = options.id.strip_prefix("data-v-").unwrap_or(&options.id);
+    let scope = create_style_scope(scope_id);
+
+    let mut transform_errors = Vec::new();
+    transform_style_blocks(
+        &mut style_blocks,
+        &scope,
+        options.minify.unwrap_or_default(),
+        &mut Vec::new(),
+        &mut transform_errors,
+    );
+
+    CompileStyleResult {
+        code: style_blocks.remove(0).content.to_string(),
+        errors: transform_errors.into_iter().map(From::from).collect(),
+        deps: deps.into_iter().collect(),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileTemplateOptions<'o> {
+    pub filename: Cow<'o, str>,
+    pub is_prod: Option<bool>,
+    pub source_map: Option<bool>,
+    /// Prefix the template identifiers, e.g. `_ctx.foo`. Default: `true`.
+    /// When disabled, the result is a function body returning the render function
+    /// which resolves the identifiers using `with (_ctx)`, meant for `new Function()`.
+    pub prefix_identifiers: Option<bool>,
+    /// Whether the whitespace is condensed or preserved. Default: condensed
+    pub whitespace: Option<WhitespaceMode>,
+    /// Render the comments. Default: in development only
+    pub comments: Option<bool>,
+}
+
+pub struct CompileTemplateResult {
+    pub code: String,
+    pub errors: Vec<CompileError>,
+    pub source_map: Option<String>,
+}
+
+/// Compiles the contents of a `<template>` independently from an SFC,
+/// similar to `compileTemplate` of the official compiler.
+/// The result is a module exporting the `render` function.
+pub fn compile_template(
+    source: &str,
+    options: CompileTemplateOptions,
+) -> Result<CompileTemplateResult, CompileError> {
+    with_compile_stack(source, || compile_template_impl(source, options))
+}
+
+fn compile_template_impl(
+    source: &str,
+    options: CompileTemplateOptions,
+) -> Result<CompileTemplateResult, CompileError> {
+    let mut all_errors = Vec::<CompileError>::new();
+
+    // Parse
+    let mut template_parsing_errors = Vec::new();
+    let mut parser = SfcParser::new(source, &mut template_parsing_errors);
+    let mut template_block = parser.parse_template()?;
+    all_errors.extend(template_parsing_errors.into_iter().map(From::from));
+
+    // Transform. Without a script, all the bindings come from the component instance
+    let mut bindings_helper = BindingsHelper {
+        is_prod: options.is_prod.unwrap_or_default(),
+        with_ctx: !options.prefix_identifiers.unwrap_or(true),
+        whitespace: options.whitespace.unwrap_or_default(),
+        comments: options.comments,
+        ..Default::default()
+    };
+    let mut transform_errors = Vec::new();
+    transform_and_record_template(
+        &mut template_block,
+        &mut bindings_helper,
+        &mut transform_errors,
+    );
+    all_errors.extend(transform_errors.into_iter().map(From::from));
+
+    // Codegen
+    let mut ctx = CodegenContext::with_bindings_helper(bindings_helper);
+    let template_expr = ctx.generate_sfc_template(&template_block);
+    let template_module = if ctx.bindings_helper.with_ctx {
+        ctx.generate_template_function(template_expr)
+    } else {
+        ctx.generate_template_module(template_expr)
+    };
+
+    let (code, source_map) = CodegenContext::stringify(
+        source,
+        &template_module,
+        FileName::Custom(options.filename.to_string()),
+        options.source_map.unwrap_or(false),
+        false,
+    );
+
+    Ok(CompileTemplateResult {
+        code,
+        errors: all_errors,
+        source_map,
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileTemplateRerenderOptions<'o> {
+    pub filename: Cow<'o, str>,
+    pub source_map: Option<bool>,
+    /// How the malformed sources are handled. Default: [`CompileMode::Strict`]
+    pub mode: Option<CompileMode>,
+    /// Same as [`CompileOptions::script_preprocessor`], the scripts are still parsed to find the template
+    pub script_preprocessor: Option<Arc<dyn ScriptPreprocessor + Send + Sync>>,
+    /// Same as [`CompileOptions::template_preprocessor`]
+    pub template_preprocessor: Option<Arc<dyn TemplatePreprocessor + Send + Sync>>,
+}
+
+/// Compiles only the `<template>` of an SFC using the script bindings of its previous compilation.
+/// This is meant for the template-only edits during HMR, when the component only needs to re-render.
+///
+/// The `source` is the whole SFC and the result is a module exporting the `render` function.
+/// The `render` function accesses the script bindings via `$setup`, thus the previous compilation
+/// should not inline the template, i.e. it should be a development build.
+pub fn compile_template_rerender(
+    source: &str,
+    script_bindings: &ScriptBindings,
+    options: CompileTemplateRerenderOptions,
+) -> Result<CompileTemplateResult, CompileError> {
+    with_compile_stack(source, || {
+        compile_template_rerender_impl(source, script_bindings, options)
+    })
+}
+
+fn compile_template_rerender_impl(
+    source: &str,
+    script_bindings: &ScriptBindings,
+    options: CompileTemplateRerenderOptions,
+) -> Result<CompileTemplateResult, CompileError> {
+    let mut all_errors = Vec::<CompileError>::new();
+
+    // Bigger sources would overflow the spans and corrupt the source map
+    if source.len() > MAX_SOURCE_SIZE {
+        return Err(CompileError::SourceTooLarge {
+            size: source.len(),
+            max_size: MAX_SOURCE_SIZE,
+        });
+    }
+
+    // Parse
+    let sfc = parse_sfc_with_mode(
+        source,
+        options.mode.unwrap_or_default(),
+        options.script_preprocessor.as_ref(),
+        options.template_preprocessor.as_ref(),
+        &mut all_errors,
+    )?;
+
+    // Transform the template against the cached bindings, the scripts are not touched
+    let mut bindings_helper = script_bindings.0.clone_script_bindings();
+    bindings_helper.max_depth = max_template_depth(options.mode.unwrap_or_default());
+    let mut transform_errors = Vec::new();
+    let template_block = sfc.template.and_then(|mut template| {
+        if template.lang != "html" {
+            transform_errors.push(TransformError::TemplateError(TemplateError {
+                span: template.span,
+                kind: TemplateErrorKind::UnsupportedLang,
+            }));
+            return None;
+        }
+
+        transform_and_record_template(&mut template, &mut bindings_helper, &mut transform_errors);
+        Some(template)
+    });
+    all_errors.extend(transform_errors.into_iter().map(From::from));
+
+    // Codegen
+    let mut ctx = CodegenContext::with_bindings_helper(bindings_helper);
+    let template_expr = template_block
+        .as_ref()
+        .and_then(|template_block| ctx.generate_sfc_template(template_block));
+    let template_module = ctx.generate_template_module(template_expr);
+
+    let (code, source_map) = CodegenContext::stringify(
+        source,
+        &template_module,
+        FileName::Custom(options.filename.to_string()),
+        options.source_map.unwrap_or(false),
+        false,
+    );
+
+    Ok(CompileTemplateResult {
+        code,
+        errors: all_errors,
+        source_map,
     })
 }
 
+/// Parses the SFC according to the [`CompileMode`], the parsing errors are added to `errors`.
+/// The callers limit the size of the `source` before parsing it.
+fn parse_sfc_with_mode(
+    source: &str,
+    mode: CompileMode,
+    script_preprocessor: Option<&Arc<dyn ScriptPreprocessor + Send + Sync>>,
+    template_preprocessor: Option<&Arc<dyn TemplatePreprocessor + Send + Sync>>,
+    errors: &mut Vec<CompileError>,
+) -> Result<SfcDescriptor, CompileError> {
+    let configure = |parser: &mut SfcParser| {
+        parser.script_preprocessor =
+            script_preprocessor.map(|p| Box::new(p.clone()) as Box<dyn ScriptPreprocessor>);
+        parser.template_preprocessor =
+            template_preprocessor.map(|p| Box::new(p.clone()) as Box<dyn TemplatePreprocessor>);
+    };
+
+    match mode {
+        CompileMode::Strict => {
+            let mut sfc_parsing_errors = Vec::new();
+            let mut parser = SfcParser::new(source, &mut sfc_parsing_errors);
+            configure(&mut parser);
+            let sfc = parser.parse_sfc()?;
+            errors.extend(sfc_parsing_errors.into_iter().map(From::from));
+            Ok(sfc)
+        }
+        CompileMode::Tolerant => {
+            // The size is already checked by the caller
+            let parse_options = LossyParseOptions {
+                max_input_size: usize::MAX,
+                ..Default::default()
+            };
+            let parse_result = parse_lossy_with(source, parse_options, configure);
+            errors.extend(parse_result.errors.into_iter().map(From::from));
+            Ok(parse_result.descriptor.unwrap_or_default())
+        }
+    }
+}
+
+/// Maximum nesting of the template elements in the [`CompileMode`].
+/// Only the tolerant mode limits it, the same as its parser does
+fn max_template_depth(mode: CompileMode) -> Option<u32> {
+    match mode {
+        CompileMode::Strict => None,
+        CompileMode::Tolerant => Some(LossyParseOptions::default().max_depth),
+    }
+}
+
 /// Naive implementation of the SFC compilation, meaning that:
 /// - it handles the standard flow without plugins;
 /// - it compiles to `String` instead of SWC module;
@@ -230,6 +1010,10 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
 /// This implementation is mostly meant for the WASM and NAPI beta.
 /// Later on, it will be replaced with a stable API.
 pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String> {
+    with_compile_stack(source, || compile_sync_naive_impl(source, is_prod))
+}
+
+fn compile_sync_naive_impl(source: &str, is_prod: bool) -> Result<String, String> {
     // let (_, mut sfc) = parse_sfc(&source).map_err(|err| {
     //     return err.to_string();
     // })?;
@@ -257,6 +1041,18 @@ pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String>
         props_destructure: PropsDestructureConfig::default(),
         scope_id: &file_hash,
         filename: "anonymous.vue".into(),
+        warn_unused_bindings: false,
+        suspense: true,
+        feature_flags: Default::default(),
+        minify_css: false,
+        is_native_tag: None,
+        hoist_static: false,
+        cache_handlers: false,
+        whitespace: Default::default(),
+        comments: None,
+        functional: false,
+        max_depth: None,
+        fs: None,
     };
     let transform_result = transform_sfc(sfc, transform_options, &mut transform_errors);
 
@@ -280,3 +1076,423 @@ pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String>
 
     Ok(compiled_code)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fervid_core::error::Severity;
+
+    const SOURCE: &str = "<template><div>Hello</div></template>";
+
+    #[test]
+    fn it_rejects_source_over_max_source_size() {
+        let options = CompileOptions {
+            max_source_size: Some(16),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            compile(SOURCE, options),
+            Err(CompileError::SourceTooLarge {
+                size,
+                max_size: 16
+            }) if size == SOURCE.len()
+        ));
+    }
+
+    #[test]
+    fn it_compiles_source_at_max_source_size() {
+        let options = CompileOptions {
+            max_source_size: Some(SOURCE.len()),
+            ..Default::default()
+        };
+
+        assert!(compile(SOURCE, options).is_ok());
+    }
+
+    #[test]
+    fn it_limits_template_depth_only_in_tolerant_mode() {
+        let source = format!(
+            "<template>{}{}</template>",
+            "<div>".repeat(300),
+            "</div>".repeat(300)
+        );
+
+        let compile_result = compile(&source, CompileOptions::default()).expect("should compile");
+        assert!(compile_result.errors.is_empty());
+        assert_eq!(300, compile_result.code.matches("\"div\"").count());
+
+        let options = CompileOptions {
+            mode: Some(CompileMode::Tolerant),
+            ..Default::default()
+        };
+        let compile_result = compile(&source, options).expect("should compile");
+        assert!(!compile_result.errors.is_empty());
+        assert!(compile_result.code.matches("\"div\"").count() < 300);
+    }
+
+    #[test]
+    fn it_compiles_deeply_nested_template() {
+        // Far deeper than the stack of the test thread allows for the recursive parser and codegen
+        let source = format!(
+            "<template>{}{}</template>",
+            "<div>".repeat(3000),
+            "</div>".repeat(3000)
+        );
+        let options = CompileOptions {
+            output_format: Some(OutputFormat::Compact),
+            ..Default::default()
+        };
+
+        let compile_result = compile(&source, options).expect("should compile");
+        assert!(compile_result.errors.is_empty());
+        assert_eq!(3000, compile_result.code.matches("\"div\"").count());
+    }
+
+    #[test]
+    fn it_reports_readable_diagnostic_messages() {
+        let source =
+            "<template><div v-else>Hello</div></template>\n<script setup>\nconst a = ;\n</script>";
+        let compile_result = compile(source, CompileOptions::default()).expect("should compile");
+
+        let messages: Vec<String> = compile_result
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            vec![
+                "Expression expected".to_owned(),
+                "v-else has no adjacent v-if or v-else-if, the element is rendered unconditionally."
+                    .to_owned()
+            ],
+            messages
+        );
+
+        let options = CompileOptions {
+            max_source_size: Some(16),
+            ..Default::default()
+        };
+        let Err(error) = compile(SOURCE, options) else {
+            panic!("should fail")
+        };
+        assert_eq!(
+            "The source is 37 bytes, which is more than the maximum of 16 bytes.",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn it_reports_no_diagnostics_for_self_closing_components() {
+        let source = "<template><div><slot /><Foo /></div></template>";
+        let compile_result = compile(source, CompileOptions::default()).expect("should compile");
+
+        assert!(compile_result.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn it_renders_empty_and_comments_only_template() {
+        let compile_with = |source: &str, is_prod: bool| {
+            let options = CompileOptions {
+                is_prod: Some(is_prod),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            let diagnostics = compile_result.diagnostics();
+            assert_eq!(1, diagnostics.len());
+            assert_eq!("template/empty-template", diagnostics[0].code);
+            assert!(diagnostics[0].is_warning());
+            compile_result.code
+        };
+
+        for source in ["<template></template>", "<template>\n</template>"] {
+            let code = compile_with(source, false);
+            assert!(code.contains("render (_ctx, _cache, $props, $setup, $data, $options) {\n        return _createCommentVNode(\"\");"), "{}", code);
+
+            let code = compile_with(source, true);
+            assert!(code.contains("render (_ctx, _cache, $props, $setup, $data, $options) {\n        return null;"), "{}", code);
+        }
+
+        let source = "<template><!-- comment --></template>";
+        let code = compile_with(source, false);
+        assert!(
+            code.contains("return _createCommentVNode(\" comment \");"),
+            "{}",
+            code
+        );
+        let code = compile_with(source, true);
+        assert!(code.contains("return null;"), "{}", code);
+
+        // Inline render of `<script setup>`
+        let source = "<script setup>const a = 1</script><template><!-- comment --></template>";
+        let code = compile_with(source, true);
+        assert!(code.contains("return (_ctx, _cache)=>null;"), "{}", code);
+    }
+
+    #[test]
+    fn it_warns_about_component_without_template_and_render() {
+        let missing_render = |source: &str, is_prod: bool| {
+            let options = CompileOptions {
+                is_prod: Some(is_prod),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            let diagnostics = compile_result.diagnostics();
+            let is_reported = diagnostics
+                .iter()
+                .any(|d| d.code == "template/missing-render" && d.is_warning());
+            (is_reported, compile_result.code)
+        };
+
+        // Nothing renders the component
+        let (is_reported, code) = missing_render("<script setup>const a = 1</script>", true);
+        assert!(is_reported);
+        assert!(code.contains("return ()=>{};"), "{}", code);
+
+        let (is_reported, _) =
+            missing_render("<script>export default { name: 'Foo' }</script>", false);
+        assert!(is_reported);
+
+        // Style-only SFC exports an empty component
+        let (is_reported, code) = missing_render("<style>.a { color: red }</style>", false);
+        assert!(is_reported);
+        assert!(!code.contains("render"), "{}", code);
+
+        // Rendered manually, by the template or by the JSX
+        for source in [
+            "<script>export default { render() { return null } }</script>",
+            "<script>export default { setup() { return () => null } }</script>",
+            "<script>import Foo from './Foo'\nexport default { ...Foo }</script>",
+            "<script>export default { render() { return null } }</script><script setup>const a = 1</script>",
+            "<template><div /></template>",
+            "<script setup lang=\"tsx\">export default <div /></script>",
+        ] {
+            let (is_reported, code) = missing_render(source, true);
+            assert!(!is_reported, "{}", source);
+            assert!(!code.contains("return ()=>{};"), "{}", code);
+        }
+    }
+
+    #[test]
+    fn it_renders_jsx_of_script_setup_without_template() {
+        let source = r#"<script setup lang="tsx">
+import { ref } from 'vue'
+const count = ref(0)
+export default <div class="count">{count.value}</div>
+</script>"#;
+
+        for is_prod in [false, true] {
+            let options = CompileOptions {
+                is_prod: Some(is_prod),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            assert!(compile_result.diagnostics().is_empty());
+            let code = &compile_result.code;
+            assert!(code.contains("h as _h"), "{}", code);
+            assert!(
+                code.contains("return ()=>_h(\"div\", {\n                class: \"count\"\n            }, count.value);\n    }\n});"),
+                "{}",
+                code
+            );
+            assert!(!code.contains("render"), "{}", code);
+        }
+    }
+
+    #[test]
+    fn it_reports_html_tree_construction_as_warnings() {
+        // Valid Vue templates, which the HTML parsing rules do not allow
+        let sources = [
+            "<template><table><MyRow /></table></template>",
+            "<template><p><div>x</div></p></template>",
+        ];
+
+        for source in sources {
+            for mode in [CompileMode::Strict, CompileMode::Tolerant] {
+                let options = CompileOptions {
+                    mode: Some(mode),
+                    ..Default::default()
+                };
+                let compile_result = compile(source, options).expect("should compile");
+
+                let diagnostics = compile_result.diagnostics();
+                assert!(!diagnostics.is_empty(), "{}", source);
+                assert!(
+                    diagnostics.iter().all(|d| d.is_warning()),
+                    "{}: {:?}",
+                    source,
+                    diagnostics
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_compiles_lt_in_interpolations() {
+        let source = "<template><div>{{ a<b }} {{ c < d }}<span>x</span></div></template>";
+
+        for mode in [CompileMode::Strict, CompileMode::Tolerant] {
+            let options = CompileOptions {
+                mode: Some(mode),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            assert!(compile_result.diagnostics().is_empty());
+            assert!(compile_result.code.contains(
+                "_toDisplayString(_ctx.a < _ctx.b) + \" \" + _toDisplayString(_ctx.c < _ctx.d)"
+            ));
+            assert!(compile_result
+                .code
+                .contains("_createElementVNode(\"span\", null, \"x\")"));
+        }
+    }
+
+    #[test]
+    fn it_emits_css_modules_of_other_languages() {
+        let source = r#"<template><div :class="$style.red" /></template>
+<style module lang="scss">
+$color: red;
+.red { color: $color }
+</style>"#;
+        let compile_result = compile(source, CompileOptions::default()).expect("should compile");
+
+        assert!(compile_result.diagnostics().is_empty());
+        let code = &compile_result.code;
+        assert!(code.contains("__cssModules.$style.red"), "{}", code);
+        assert!(code.contains("const __cssModules = {};"), "{}", code);
+        assert!(code.contains("    __cssModules,\n"), "{}", code);
+
+        assert_eq!(1, compile_result.styles.len());
+        let style = &compile_result.styles[0];
+        assert!(!style.is_compiled);
+        assert_eq!(Some("$style"), style.module.as_deref());
+    }
+
+    #[test]
+    fn it_reports_loaded_blocks_against_the_sfc() {
+        let source =
+            "<template src=\"./tpl.html\"></template>\n<script setup>\nconst a = ;\n</script>";
+        let fs: MemoryFileSystem = [("/src/tpl.html", "<div v-else>Hello</div>")]
+            .into_iter()
+            .collect();
+        let options = CompileOptions {
+            filename: "/src/App.vue".into(),
+            fs: Some(Arc::new(fs)),
+            source_map: Some(true),
+            ..Default::default()
+        };
+        let compile_result = compile(source, options).expect("should compile");
+
+        // Errors in the loaded template point to its `src`, the rest stays in place
+        let reported: Vec<(String, &str)> = compile_result
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| {
+                // Spans start from 1
+                let lo = diagnostic.span.lo.0 as usize - 1;
+                let hi = diagnostic.span.hi.0 as usize - 1;
+                (diagnostic.message, &source[lo..hi])
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("Expression expected".to_owned(), ";"),
+                (
+                    "v-else has no adjacent v-if or v-else-if, the element is rendered unconditionally."
+                        .to_owned(),
+                    "src=\"./tpl.html\""
+                )
+            ],
+            reported
+        );
+
+        let source_map = compile_result.source_map.expect("should have a source map");
+        assert!(
+            source_map.contains(r#""sourcesContent":["<template src=\"./tpl.html\"></template>"#)
+        );
+    }
+
+    #[test]
+    fn it_compiles_scripts_with_preprocessor() {
+        #[derive(Debug)]
+        struct CoffeePreprocessor;
+
+        impl ScriptPreprocessor for CoffeePreprocessor {
+            fn preprocess_script(&self, lang: &str, content: &str) -> Option<PreprocessedScript> {
+                if lang != "coffee" {
+                    return None;
+                }
+
+                Some(PreprocessedScript {
+                    code: content.replace("msg = 'Hello'", "const msg = 'Hello'"),
+                    lang: SfcScriptLang::Es,
+                })
+            }
+        }
+
+        let source = "<script setup lang=\"coffee\">msg = 'Hello'</script>\n<template><div>{{ msg }}</div></template>";
+
+        // Without a preprocessor the `lang` is not supported
+        let compile_result = compile(source, CompileOptions::default()).expect("should compile");
+        assert_eq!(
+            vec!["<script lang=\"coffee\"> is not supported. Provide a `ScriptPreprocessor` to compile it to JavaScript or TypeScript.".to_owned()],
+            compile_result
+                .diagnostics()
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect::<Vec<_>>()
+        );
+
+        for mode in [CompileMode::Strict, CompileMode::Tolerant] {
+            let options = CompileOptions {
+                mode: Some(mode),
+                script_preprocessor: Some(Arc::new(CoffeePreprocessor)),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            assert!(compile_result.diagnostics().is_empty());
+            assert!(compile_result.code.contains("const msg = 'Hello'"));
+        }
+    }
+
+    #[test]
+    fn it_compiles_templates_with_preprocessor() {
+        #[derive(Debug)]
+        struct PugPreprocessor;
+
+        impl TemplatePreprocessor for PugPreprocessor {
+            fn preprocess_template(&self, lang: &str, content: &str) -> Option<String> {
+                if lang != "pug" {
+                    return None;
+                }
+
+                // Only enough of pug for the test
+                let (tag, text) = content.trim().split_once(' ')?;
+                Some(format!("<{tag}>{text}</{tag}>"))
+            }
+        }
+
+        let source = "<template lang=\"pug\">\nspan {{ msg }}\n</template>\n<script setup>const msg = 'Hello'</script>";
+
+        // Without a preprocessor the `lang` is not supported
+        let compile_result = compile(source, CompileOptions::default()).expect("should compile");
+        assert_eq!(1, compile_result.diagnostics().len());
+
+        for mode in [CompileMode::Strict, CompileMode::Tolerant] {
+            let options = CompileOptions {
+                mode: Some(mode),
+                template_preprocessor: Some(Arc::new(PugPreprocessor)),
+                ..Default::default()
+            };
+            let compile_result = compile(source, options).expect("should compile");
+
+            assert!(compile_result.diagnostics().is_empty());
+            assert!(compile_result.code.contains("_createElementBlock(\"span\""));
+        }
+    }
+}
@@ -87,6 +87,7 @@ fn parse_vanilla_attr<'i>(
             out.push(AttributeOrBinding::RegularAttribute {
                 name: attr_name.into(),
                 value: attr_name.into(),
+                raw_value: None,
                 span: DUMMY_SP
             });
             Ok((input, ()))
@@ -101,6 +102,7 @@ fn parse_vanilla_attr<'i>(
             out.push(AttributeOrBinding::RegularAttribute {
                 name: attr_name.into(),
                 value: attr_value.into(),
+                raw_value: None,
                 span: DUMMY_SP
             });
 
@@ -304,6 +306,7 @@ fn parse_directive<'i>(
                     }
                 }),
                 modifiers,
+                is_cacheable: false,
                 span: DUMMY_SP
             }));
         }
@@ -241,7 +241,8 @@ fn parse_root_block<'a>(
         lang: lang.into(),
         content: content.into(),
         is_scoped,
-        is_module,
+        // `<style module>` is exposed as `$style`
+        module: is_module.then(|| "$style".into()),
         span: DUMMY_SP
     });
 
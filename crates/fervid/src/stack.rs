@@ -0,0 +1,13 @@
+//! Stack for the compilations of the deep templates.
+//!
+//! The parser and the codegen grow the stack by themselves, but `swc_html_parser`, `swc_ecma_codegen`
+//! and the drop of the generated AST also recurse on the nested elements. The compilation
+//! runs on a new stack only when the current one cannot fit the nesting of the source,
+//! shallow templates run on the stack of the caller.
+
+use fervid_core::{ensure_stack_for_depth, max_nesting_depth};
+
+/// Runs `f` on a new stack when the current one cannot fit the nesting of `source`
+pub(crate) fn with_compile_stack<R>(source: &str, f: impl FnOnce() -> R) -> R {
+    ensure_stack_for_depth(max_nesting_depth(source), f)
+}
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use fervid::analyze::{analyze_sfc, TemplateStats};
+
+use crate::{escape_json, files::expand_pattern, ANALYZE_USAGE};
+
+/// `fervid analyze [--json] src/**/*.vue`
+pub fn analyze_command(args: &[String]) {
+    let mut is_json = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--json" => is_json = true,
+            pattern => files.extend(expand_pattern(pattern)),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("{ANALYZE_USAGE}");
+        std::process::exit(1);
+    }
+
+    let mut reports = Vec::with_capacity(files.len());
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: {e}", file.display());
+                continue;
+            }
+        };
+
+        match analyze_sfc(&source, &file.to_string_lossy()) {
+            Ok(stats) => reports.push((file, stats)),
+            Err(e) => eprintln!("{}: {e}", file.display()),
+        }
+    }
+
+    // Biggest templates first
+    reports.sort_by(|a, b| b.1.template_size.cmp(&a.1.template_size));
+
+    if is_json {
+        print_json(&reports);
+    } else {
+        print_table(&reports);
+    }
+}
+
+fn print_table(reports: &[(PathBuf, TemplateStats)]) {
+    const HEADERS: [&str; 6] = [
+        "File",
+        "Size",
+        "Elements",
+        "Dynamic bindings",
+        "Missing keys",
+        "Unhoistable",
+    ];
+
+    let rows: Vec<[String; 6]> = reports
+        .iter()
+        .map(|(file, stats)| {
+            [
+                file.display().to_string(),
+                stats.template_size.to_string(),
+                stats.elements.to_string(),
+                stats.dynamic_bindings.to_string(),
+                stats.missing_keys.to_string(),
+                stats.unhoistable_nodes.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    // File names are left-aligned, numbers are right-aligned
+    let print_row = |row: [&str; 6]| {
+        let mut line = format!("{:<width$}", row[0], width = widths[0]);
+        for (cell, width) in row.iter().zip(widths.iter()).skip(1) {
+            line.push_str(&format!("  {:>width$}", cell, width = width));
+        }
+        println!("{line}");
+    };
+
+    print_row(HEADERS);
+    for row in rows.iter() {
+        print_row(row.each_ref().map(String::as_str));
+    }
+}
+
+fn print_json(reports: &[(PathBuf, TemplateStats)]) {
+    let entries: Vec<String> = reports
+        .iter()
+        .map(|(file, stats)| {
+            format!(
+                "{{\"file\":\"{}\",\"templateSize\":{},\"elements\":{},\"dynamicBindings\":{},\"missingKeys\":{},\"unhoistableNodes\":{}}}",
+                escape_json(&file.to_string_lossy()),
+                stats.template_size,
+                stats.elements,
+                stats.dynamic_bindings,
+                stats.missing_keys,
+                stats.unhoistable_nodes
+            )
+        })
+        .collect();
+
+    println!("[{}]", entries.join(","));
+}
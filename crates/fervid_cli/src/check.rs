@@ -0,0 +1,133 @@
+use std::{path::PathBuf, sync::Arc};
+
+use fervid::{
+    check::{check_sfc, CheckDiagnostic},
+    FileSystem, OsFileSystem,
+};
+
+use crate::{
+    escape_json,
+    files::expand_pattern,
+    CHECK_USAGE,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum CheckFormat {
+    Text,
+    Json,
+    /// Workflow commands which GitHub Actions show as annotations
+    Github,
+}
+
+/// `fervid check [--format text|json|github] src/**/*.vue`.
+/// Returns `false` when any file has errors.
+pub fn check_command(args: &[String]) -> bool {
+    let mut format = CheckFormat::Text;
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("text") => CheckFormat::Text,
+                    Some("json") => CheckFormat::Json,
+                    Some("github") => CheckFormat::Github,
+                    _ => {
+                        eprintln!("{CHECK_USAGE}");
+                        return false;
+                    }
+                }
+            }
+            pattern => files.extend(expand_pattern(pattern)),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("{CHECK_USAGE}");
+        return false;
+    }
+
+    let fs: Arc<dyn FileSystem> = Arc::new(OsFileSystem);
+    let mut is_ok = true;
+    let mut reports: Vec<(PathBuf, CheckDiagnostic)> = Vec::new();
+    for file in files {
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: {e}", file.display());
+                is_ok = false;
+                continue;
+            }
+        };
+
+        let diagnostics = check_sfc(&source, &file.to_string_lossy(), Some(fs.clone()));
+        is_ok &= diagnostics.is_empty();
+        reports.extend(
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| (file.clone(), diagnostic)),
+        );
+    }
+
+    match format {
+        CheckFormat::Text => {
+            for (file, diagnostic) in reports.iter() {
+                println!(
+                    "{}:{}:{}: {}",
+                    file.display(),
+                    diagnostic.line,
+                    diagnostic.column,
+                    diagnostic.message
+                );
+            }
+            eprintln!("Found {} error(s)", reports.len());
+        }
+
+        CheckFormat::Json => {
+            let entries: Vec<String> = reports
+                .iter()
+                .map(|(file, diagnostic)| {
+                    format!(
+                        "{{\"file\":\"{}\",\"line\":{},\"column\":{},\"code\":\"{}\",\"message\":\"{}\"}}",
+                        escape_json(&file.to_string_lossy()),
+                        diagnostic.line,
+                        diagnostic.column,
+                        diagnostic.code,
+                        escape_json(&diagnostic.message)
+                    )
+                })
+                .collect();
+
+            println!("[{}]", entries.join(","));
+        }
+
+        CheckFormat::Github => {
+            for (file, diagnostic) in reports.iter() {
+                println!(
+                    "::error file={},line={},col={}::{}",
+                    escape_github_property(&file.to_string_lossy()),
+                    diagnostic.line,
+                    diagnostic.column,
+                    escape_github_data(&diagnostic.message)
+                );
+            }
+        }
+    }
+
+    is_ok
+}
+
+/// Escapes the message of a GitHub workflow command
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes the property of a GitHub workflow command, e.g. `file=`
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
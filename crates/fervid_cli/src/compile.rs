@@ -0,0 +1,277 @@
+use std::{
+    borrow::Cow,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use fervid::{
+    check::line_and_column, compile, error::Severity, errors::CompileDiagnostic, CompileOptions,
+    OsFileSystem,
+};
+
+use crate::{
+    files::{component_id, expand_pattern},
+    COMPILE_USAGE,
+};
+
+/// Options of `fervid compile` applied to every compiled file
+#[derive(Default)]
+struct CompileArgs {
+    ssr: bool,
+    source_map: bool,
+    is_prod: bool,
+    scope_id: Option<String>,
+    out_dir: Option<PathBuf>,
+}
+
+/// `fervid compile [--ssr] [--sourcemap] [--prod] [--scope-id <id>] [--out-dir <dir>] src/**/*.vue`.
+/// Without the files (or with `-`) the SFC is read from stdin.
+/// Without `--out-dir` the compiled modules are written to stdout.
+/// Returns `false` when any file fails to compile or has errors.
+pub fn compile_command(args: &[String]) -> bool {
+    let mut compile_args = CompileArgs::default();
+    let mut files = Vec::new();
+    let mut has_patterns = false;
+    let mut use_stdin = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ssr" => compile_args.ssr = true,
+            "--sourcemap" => compile_args.source_map = true,
+            "--prod" => compile_args.is_prod = true,
+            "--scope-id" => {
+                let Some(scope_id) = args.next() else {
+                    eprintln!("{COMPILE_USAGE}");
+                    return false;
+                };
+                compile_args.scope_id = Some(scope_id.to_owned());
+            }
+            "--out-dir" => {
+                let Some(out_dir) = args.next() else {
+                    eprintln!("{COMPILE_USAGE}");
+                    return false;
+                };
+                compile_args.out_dir = Some(PathBuf::from(out_dir));
+            }
+            "-" => use_stdin = true,
+            pattern => {
+                has_patterns = true;
+                files.extend(expand_pattern(pattern));
+            }
+        }
+    }
+
+    // `cat App.vue | fervid compile > App.js`
+    if use_stdin || !has_patterns {
+        let mut source = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+            eprintln!("stdin: {e}");
+            return false;
+        }
+
+        return compile_file(&source, Path::new("stdin.vue"), None, &compile_args);
+    }
+
+    if files.is_empty() {
+        eprintln!("No files found");
+        return false;
+    }
+
+    let mut is_ok = true;
+    for file in files.iter() {
+        let source = match std::fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: {e}", file.display());
+                is_ok = false;
+                continue;
+            }
+        };
+
+        // Modules written to stdout are told apart by their paths
+        if compile_args.out_dir.is_none() && files.len() > 1 {
+            println!("// {}", file.display());
+        }
+
+        let out_dir = compile_args.out_dir.as_deref();
+        is_ok &= compile_file(&source, file, out_dir, &compile_args);
+    }
+
+    is_ok
+}
+
+/// Compiles one SFC and writes the module either to `out_dir` or to stdout.
+/// The diagnostics are reported to stderr.
+fn compile_file(source: &str, file: &Path, out_dir: Option<&Path>, args: &CompileArgs) -> bool {
+    let filename = file.to_string_lossy();
+    let id = component_id(file);
+    let compile_result = compile(
+        source,
+        CompileOptions {
+            filename: Cow::Borrowed(&filename),
+            // Scopes of the styles stay the same when the file is edited
+            scope_id: Some(Cow::Borrowed(args.scope_id.as_deref().unwrap_or(&id))),
+            id: Cow::Borrowed(&id),
+            is_prod: Some(args.is_prod),
+            ssr: Some(args.ssr),
+            fs: Some(Arc::new(OsFileSystem)),
+            source_map: Some(args.source_map),
+            ..Default::default()
+        },
+    );
+
+    let compiled = match compile_result {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            print_diagnostic(file, source, &CompileDiagnostic::from(&e));
+            return false;
+        }
+    };
+
+    let mut is_ok = true;
+    for diagnostic in compiled.diagnostics() {
+        print_diagnostic(file, source, &diagnostic);
+        is_ok &= diagnostic.is_warning();
+    }
+
+    let extension = if compiled.script_bindings.is_ts() {
+        "ts"
+    } else {
+        "js"
+    };
+    let mut code = compiled.code;
+
+    let Some(out_dir) = out_dir else {
+        if let Some(source_map) = compiled.source_map {
+            code.push_str("\n//# sourceMappingURL=data:application/json;base64,");
+            code.push_str(&encode_base64(source_map.as_bytes()));
+        }
+        println!("{code}");
+        return is_ok;
+    };
+
+    let out_path = output_path(out_dir, file, extension);
+    if let Some(parent) = out_path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            eprintln!("{}: {e}", parent.display());
+            return false;
+        }
+    }
+
+    if let Some(source_map) = compiled.source_map {
+        let mut map_path = out_path.clone().into_os_string();
+        map_path.push(".map");
+        let map_path = PathBuf::from(map_path);
+
+        if let Err(e) = std::fs::write(&map_path, source_map) {
+            eprintln!("{}: {e}", map_path.display());
+            return false;
+        }
+
+        if let Some(map_name) = map_path.file_name() {
+            code.push_str("\n//# sourceMappingURL=");
+            code.push_str(&map_name.to_string_lossy());
+        }
+    }
+
+    if let Err(e) = std::fs::write(&out_path, code) {
+        eprintln!("{}: {e}", out_path.display());
+        return false;
+    }
+
+    is_ok
+}
+
+fn print_diagnostic(file: &Path, source: &str, diagnostic: &CompileDiagnostic) {
+    let (line, column) = line_and_column(source, diagnostic.span.lo.0);
+    let level = if diagnostic.is_warning() {
+        "warning"
+    } else {
+        "error"
+    };
+
+    eprintln!(
+        "{}:{line}:{column}: {level}[{}]: {}",
+        file.display(),
+        diagnostic.code,
+        diagnostic.message
+    );
+}
+
+/// Keeps the directory structure of the input, e.g. `src/App.vue` becomes `<out-dir>/src/App.js`
+fn output_path(out_dir: &Path, file: &Path, extension: &str) -> PathBuf {
+    let relative: PathBuf = file
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+
+    out_dir.join(relative).with_extension(extension)
+}
+
+/// Standard base64 with padding, for the inline source maps
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(ALPHABET[(triple >> (18 - idx * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_succeeds_for_clean_sfc() {
+        let source = r#"<template><div v-if="ok">hi</div></template>
+<script setup>
+const ok = true
+</script>"#;
+        assert!(compile_file(
+            source,
+            Path::new("App.vue"),
+            None,
+            &CompileArgs::default()
+        ));
+    }
+
+    #[test]
+    fn it_fails_for_sfc_with_errors() {
+        let source = "<template><div v-else>hi</div></template>";
+        assert!(!compile_file(
+            source,
+            Path::new("App.vue"),
+            None,
+            &CompileArgs::default()
+        ));
+    }
+
+    #[test]
+    fn it_derives_id_from_relative_path() {
+        let id = component_id(Path::new("src/App.vue"));
+        assert_eq!(id, component_id(Path::new("./src/App.vue")));
+        let cwd = std::env::current_dir().expect("should have cwd");
+        assert_eq!(id, component_id(&cwd.join("src/App.vue")));
+        assert_ne!(id, component_id(Path::new("src/Other.vue")));
+    }
+
+    #[test]
+    fn it_fails_for_missing_files() {
+        assert!(!compile_command(&["does-not-exist.vue".to_owned()]));
+    }
+}
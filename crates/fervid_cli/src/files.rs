@@ -0,0 +1,115 @@
+use std::path::{Component, Path, PathBuf};
+
+use fervid::hmr::hmr_id;
+
+/// Id of the component, the hash of its path relative to the working directory.
+/// It does not depend on the location of the project nor on the contents of the file
+pub fn component_id(file: &Path) -> String {
+    let cwd = std::env::current_dir().ok();
+    let relative = cwd
+        .as_deref()
+        .and_then(|cwd| file.strip_prefix(cwd).ok())
+        .unwrap_or(file);
+
+    // `./src/App.vue` and `src\App.vue` are the same as `src/App.vue`
+    let relative: Vec<_> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .collect();
+
+    hmr_id(&relative.join("/"))
+}
+
+/// Expands the `*`, `?` and `**` wildcards, because not every shell does.
+/// Directories are searched for `.vue` files.
+pub fn expand_pattern(pattern: &str) -> Vec<PathBuf> {
+    let path = Path::new(pattern);
+    if !pattern.contains(['*', '?']) {
+        if !path.is_dir() {
+            return vec![path.to_path_buf()];
+        }
+
+        let mut files = Vec::new();
+        collect_files(path, &mut files);
+        files.retain(|file| file.extension().is_some_and(|ext| ext == "vue"));
+        files.sort();
+        return files;
+    }
+
+    // The search starts from the part of the pattern without wildcards
+    let components: Vec<&str> = pattern.split('/').collect();
+    let static_len = components
+        .iter()
+        .position(|component| component.contains(['*', '?']))
+        .unwrap_or(components.len());
+    let base = if static_len == 0 {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(components[..static_len].join("/"))
+    };
+    let glob = &components[static_len..];
+
+    let mut files = Vec::new();
+    collect_files(&base, &mut files);
+    files.retain(|file| {
+        let Ok(relative) = file.strip_prefix(&base) else {
+            return false;
+        };
+        let parts: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        matches_glob(glob, &parts)
+    });
+    files.sort();
+    files
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            files.push(path);
+        } else if entry.file_name() != "node_modules" {
+            collect_files(&path, files);
+        }
+    }
+}
+
+/// Matches the path components, where `**` matches any number of them
+fn matches_glob(glob: &[&str], parts: &[String]) -> bool {
+    match (glob.first(), parts.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            matches_glob(&glob[1..], parts)
+                || (!parts.is_empty() && matches_glob(glob, &parts[1..]))
+        }
+        (Some(pattern), Some(part)) => {
+            matches_wildcard(pattern.as_bytes(), part.as_bytes())
+                && matches_glob(&glob[1..], &parts[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Matches a single path component with `*` and `?`
+fn matches_wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches_wildcard(&pattern[1..], text)
+                || (!text.is_empty() && matches_wildcard(pattern, &text[1..]))
+        }
+        (Some(b'?'), Some(_)) => matches_wildcard(&pattern[1..], &text[1..]),
+        (Some(a), Some(b)) => a == b && matches_wildcard(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
@@ -0,0 +1,54 @@
+mod analyze;
+mod check;
+mod compile;
+mod files;
+mod test_fixtures;
+
+use analyze::analyze_command;
+use check::check_command;
+use compile::compile_command;
+use test_fixtures::test_fixtures_command;
+
+const USAGE: &str =
+    "Usage: fervid <compile|check|analyze|test-fixtures> [options] <files, directories or globs>...";
+const ANALYZE_USAGE: &str = "Usage: fervid analyze [--json] <files, directories or globs>...";
+const CHECK_USAGE: &str =
+    "Usage: fervid check [--format text|json|github] <files, directories or globs>...";
+const COMPILE_USAGE: &str = "Usage: fervid compile [--ssr] [--sourcemap] [--prod] [--scope-id <id>] [--out-dir <dir>] [<files, directories or globs>... | -]";
+const TEST_FIXTURES_USAGE: &str = "Usage: fervid test-fixtures [--ssr] [--prod] <directory>";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("analyze") => analyze_command(&args[1..]),
+        Some("check") => {
+            let is_ok = check_command(&args[1..]);
+            std::process::exit(if is_ok { 0 } else { 1 });
+        }
+        Some("compile") => {
+            let is_ok = compile_command(&args[1..]);
+            std::process::exit(if is_ok { 0 } else { 1 });
+        }
+        Some("test-fixtures") => {
+            let is_ok = test_fixtures_command(&args[1..]);
+            std::process::exit(if is_ok { 0 } else { 1 });
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
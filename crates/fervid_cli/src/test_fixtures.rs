@@ -0,0 +1,197 @@
+use std::{borrow::Cow, path::Path, sync::Arc};
+
+use fervid::{compile, errors::CompileDiagnostic, CompileOptions, OsFileSystem};
+use swc_core::{
+    common::{sync::Lrc, BytePos, SourceMap},
+    ecma::{
+        ast::{EsVersion, Module, Number, Str},
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsSyntax};
+
+use crate::{
+    files::{component_id, expand_pattern},
+    TEST_FIXTURES_USAGE,
+};
+
+/// How many differing lines are printed for one fixture
+const MAX_REPORTED_LINES: usize = 5;
+
+/// Options of `fervid test-fixtures` applied to every fixture
+#[derive(Default)]
+struct FixtureArgs {
+    ssr: bool,
+    is_prod: bool,
+}
+
+/// `fervid test-fixtures [--ssr] [--prod] <dir>`.
+/// Compiles every `X.vue` of the directory and compares the result with `X.js` next to it,
+/// e.g. the output of `@vue/compiler-sfc`. Both modules are re-emitted from their AST,
+/// so that the formatting, comments and quotes do not count as differences.
+/// Returns `false` when any fixture differs or cannot be compiled.
+pub fn test_fixtures_command(args: &[String]) -> bool {
+    let mut fixture_args = FixtureArgs::default();
+    let mut dir = None;
+    for arg in args.iter() {
+        match arg.as_str() {
+            "--ssr" => fixture_args.ssr = true,
+            "--prod" => fixture_args.is_prod = true,
+            path if dir.is_none() => dir = Some(path),
+            _ => {
+                eprintln!("{TEST_FIXTURES_USAGE}");
+                return false;
+            }
+        }
+    }
+
+    let Some(dir) = dir.filter(|dir| Path::new(dir).is_dir()) else {
+        eprintln!("{TEST_FIXTURES_USAGE}");
+        return false;
+    };
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    for file in expand_pattern(dir) {
+        let expected_path = file.with_extension("js");
+        let Ok(expected) = std::fs::read_to_string(&expected_path) else {
+            skipped += 1;
+            continue;
+        };
+
+        match run_fixture(&file, &expected, &fixture_args) {
+            Ok(()) => {
+                passed += 1;
+                println!("ok   {}", file.display());
+            }
+            Err(report) => {
+                failed += 1;
+                println!("FAIL {}", file.display());
+                println!("{report}");
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed, {skipped} skipped (no expected .js)");
+    failed == 0
+}
+
+/// Compiles the fixture and returns the report of the differences, if any
+fn run_fixture(file: &Path, expected: &str, args: &FixtureArgs) -> Result<(), String> {
+    let source = std::fs::read_to_string(file).map_err(|e| format!("  {e}"))?;
+    let filename = file.to_string_lossy();
+    let id = component_id(file);
+    let compiled = compile(
+        &source,
+        CompileOptions {
+            filename: Cow::Borrowed(&filename),
+            id: Cow::Borrowed(&id),
+            is_prod: Some(args.is_prod),
+            ssr: Some(args.ssr),
+            fs: Some(Arc::new(OsFileSystem)),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| format!("  fervid: {}", CompileDiagnostic::from(&e).message))?;
+
+    let actual = normalize(&compiled.code).map_err(|e| format!("  fervid output: {e}"))?;
+    let expected = normalize(expected).map_err(|e| format!("  expected output: {e}"))?;
+
+    match diff_lines(&expected, &actual) {
+        Some(report) => Err(report),
+        None => Ok(()),
+    }
+}
+
+/// Parses the module and emits it again in one canonical formatting
+fn normalize(code: &str) -> Result<String, String> {
+    let lexer = Lexer::new(
+        Syntax::Typescript(TsSyntax::default()),
+        EsVersion::EsNext,
+        StringInput::new(code, BytePos(1), BytePos(1 + code.len() as u32)),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let mut module = parser
+        .parse_typescript_module()
+        .map_err(|e| e.kind().msg().to_string())?;
+
+    module.visit_mut_with(&mut RawRemover);
+
+    Ok(emit(&module))
+}
+
+fn emit(module: &Module) -> String {
+    let cm: Lrc<SourceMap> = Default::default();
+    let mut buff: Vec<u8> = Vec::new();
+    let writer = JsWriter::new(cm.clone(), "\n", &mut buff, None);
+
+    let mut emitter = Emitter {
+        cfg: swc_ecma_codegen::Config::default(),
+        comments: None,
+        wr: writer,
+        cm,
+    };
+
+    module.emit_with(&mut emitter).expect("Failed to emit");
+    String::from_utf8(buff).expect("Invalid UTF-8")
+}
+
+/// Makes `'foo'` and `"foo"`, or `0x10` and `16`, emit the same
+struct RawRemover;
+
+impl VisitMut for RawRemover {
+    fn visit_mut_str(&mut self, n: &mut Str) {
+        n.raw = None;
+    }
+
+    fn visit_mut_number(&mut self, n: &mut Number) {
+        n.raw = None;
+    }
+}
+
+/// Reports the first lines which differ, with the line numbers of the normalized `expected`
+fn diff_lines(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+
+    // Lines equal at the start and at the end are not interesting
+    let prefix = expected_lines
+        .iter()
+        .zip(actual_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = expected_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(actual_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let expected_diff = &expected_lines[prefix..expected_lines.len() - suffix];
+    let actual_diff = &actual_lines[prefix..actual_lines.len() - suffix];
+
+    let mut report = format!("  @@ line {} @@", prefix + 1);
+    for line in expected_diff.iter().take(MAX_REPORTED_LINES) {
+        report.push_str("\n  - ");
+        report.push_str(line);
+    }
+    for line in actual_diff.iter().take(MAX_REPORTED_LINES) {
+        report.push_str("\n  + ");
+        report.push_str(line);
+    }
+
+    let omitted = expected_diff.len().saturating_sub(MAX_REPORTED_LINES)
+        + actual_diff.len().saturating_sub(MAX_REPORTED_LINES);
+    if omitted > 0 {
+        report.push_str(&format!("\n  ... {omitted} more line(s)"));
+    }
+
+    Some(report)
+}
@@ -6,9 +6,10 @@ use regex::Regex;
 use swc_core::{
     common::{Span, Spanned, DUMMY_SP},
     ecma::ast::{
-        ArrayLit, ArrowExpr, BinExpr, BinaryOp, BlockStmt, BlockStmtOrExpr, CallExpr, Callee,
-        ComputedPropName, Expr, ExprOrSpread, Ident, IdentName, KeyValueProp, Lit, ObjectLit, Prop,
-        PropName, PropOrSpread, Str,
+        ArrayLit, ArrowExpr, AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BlockStmt,
+        BlockStmtOrExpr, CallExpr, Callee, ComputedPropName, Expr, ExprOrSpread, Ident, IdentName,
+        KeyValueProp, Lit, MemberExpr, MemberProp, Number, ObjectLit, ParenExpr, Prop, PropName,
+        PropOrSpread, SimpleAssignTarget, SpreadElement, Str,
     },
 };
 
@@ -68,18 +69,24 @@ impl CodegenContext {
             match attribute {
                 // First, we check the special case: `class` and `style` attributes
                 // class
-                AttributeOrBinding::RegularAttribute { name, value, span } if name == "class" => {
+                AttributeOrBinding::RegularAttribute {
+                    name, value, span, ..
+                } if name == "class" => {
                     class_regular_attr = Some((value, *span));
                 }
 
                 // style
-                AttributeOrBinding::RegularAttribute { name, value, span } if name == "style" => {
+                AttributeOrBinding::RegularAttribute {
+                    name, value, span, ..
+                } if name == "style" => {
                     style_regular_attr = Some((value, *span));
                 }
 
                 // Any regular attribute will be added as an object entry,
                 // where key is attribute name and value is attribute value as string literal
-                AttributeOrBinding::RegularAttribute { name, value, span } => {
+                AttributeOrBinding::RegularAttribute {
+                    name, value, span, ..
+                } => {
                     // let raw = Some(Atom::from(value.as_ref()));
 
                     out.push(PropOrSpread::Prop(Box::from(Prop::KeyValue(
@@ -128,6 +135,25 @@ impl CodegenContext {
                     //   onClick: _cache[1] || (_cache[1] = () => {})
                     // })
                     result_hints.v_bind_no_arg = Some(v_bind);
+
+                    // `class` and `style` before the object belong to the preceding `_mergeProps` argument
+                    result_hints.class_patch_flag |= self.generate_class_bindings(
+                        class_regular_attr.take(),
+                        class_bound.take(),
+                        out,
+                    );
+                    result_hints.style_patch_flag |= self.generate_style_bindings(
+                        style_regular_attr.take(),
+                        style_bound.take(),
+                        out,
+                    );
+
+                    // Keep the position of the object relative to other attributes,
+                    // it is later used as a `_mergeProps` argument in [`CodegenContext::generate_props_expr`]
+                    out.push(PropOrSpread::Spread(SpreadElement {
+                        dot3_token: v_bind.span,
+                        expr: v_bind.value.to_owned(),
+                    }));
                 }
 
                 // `v-on` directive without event name also needs its own processing
@@ -189,9 +215,9 @@ impl CodegenContext {
                     event: Some(event),
                     handler,
                     modifiers,
+                    is_cacheable,
                     span,
                 }) => {
-                    // TODO Use _cache
                     let span = *span;
 
                     // Transform or default to () => {}
@@ -216,7 +242,7 @@ impl CodegenContext {
                         })
                         .collect();
 
-                    let handler_expr = if modifiers.len() != 0 {
+                    let mut handler_expr = if !modifiers.is_empty() {
                         let with_modifiers_import =
                             self.get_and_add_import_ident(VueImports::WithModifiers);
 
@@ -247,7 +273,10 @@ impl CodegenContext {
                         handler
                     };
 
-                    // TODO Cache
+                    // `_cache[0] || (_cache[0] = handler)`, except inside `v-once`
+                    if *is_cacheable && !self.is_cache_disabled {
+                        handler_expr = self.generate_cached_handler(handler_expr, span);
+                    }
 
                     // TODO Dynamic events are hard, but similar to `v-on`
                     // IN:
@@ -293,16 +322,136 @@ impl CodegenContext {
             }
         }
 
-        result_hints.class_patch_flag =
+        result_hints.class_patch_flag |=
             self.generate_class_bindings(class_regular_attr, class_bound, out);
-        result_hints.style_patch_flag =
+        result_hints.style_patch_flag |=
             self.generate_style_bindings(style_regular_attr, style_bound, out);
 
         result_hints
     }
 
+    /// Converts the generated attributes to the props expression.
+    ///
+    /// Objects bound using `v-bind="obj"` are merged with the rest of attributes in order:
+    /// - `foo="bar" v-bind="obj"` -> `_mergeProps({foo:"bar"}, obj)`;
+    /// - `v-bind="obj"` -> `_normalizeProps(_guardReactiveProps(obj))`.
+    ///
+    /// Returns `None` when there are no attributes.
+    pub fn generate_props_expr(&mut self, props: Vec<PropOrSpread>, span: Span) -> Option<Expr> {
+        if props.is_empty() {
+            return None;
+        }
+
+        if !props.iter().any(|prop| prop.is_spread()) {
+            return Some(Expr::Object(ObjectLit { span, props }));
+        }
+
+        // Split into the bound objects and the literals between them
+        let mut merge_args: Vec<ExprOrSpread> = Vec::new();
+        let mut current_obj: Vec<PropOrSpread> = Vec::new();
+        for prop in props {
+            match prop {
+                PropOrSpread::Spread(spread) => {
+                    if !current_obj.is_empty() {
+                        merge_args.push(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(Expr::Object(ObjectLit {
+                                span,
+                                props: std::mem::take(&mut current_obj),
+                            })),
+                        });
+                    }
+                    merge_args.push(ExprOrSpread {
+                        spread: None,
+                        expr: spread.expr,
+                    });
+                }
+                PropOrSpread::Prop(_) => current_obj.push(prop),
+            }
+        }
+        if !current_obj.is_empty() {
+            merge_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Object(ObjectLit {
+                    span,
+                    props: current_obj,
+                })),
+            });
+        }
+
+        macro_rules! call {
+            ($import: expr, $args: expr) => {
+                Expr::Call(CallExpr {
+                    span,
+                    ctxt: Default::default(),
+                    callee: Callee::Expr(Box::new(Expr::Ident(
+                        self.get_and_add_import_ident($import)
+                            .into_ident_spanned(span),
+                    ))),
+                    args: $args,
+                    type_args: None,
+                })
+            };
+        }
+
+        // A single object is not merged, but it still needs to be normalized,
+        // and it must not be reactive (same as in `compiler-core`)
+        if merge_args.len() == 1 {
+            let guarded = call!(VueImports::GuardReactiveProps, merge_args);
+            return Some(call!(
+                VueImports::NormalizeProps,
+                vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(guarded),
+                }]
+            ));
+        }
+
+        Some(call!(VueImports::MergeProps, merge_args))
+    }
+
     /// Process `class` attribute. We may have a regular one, a bound one, both or neither.
     /// Returns `true` when there were JavaScript bindings
+    /// Stores the handler in the cache, so that it keeps its identity between renders.
+    /// `_cache[0] || (_cache[0] = handler)`
+    fn generate_cached_handler(&mut self, handler: Box<Expr>, span: Span) -> Box<Expr> {
+        let cache_idx = self.allocate_next_cache_entry();
+
+        // `_cache[0]`
+        let cache_member_expr = MemberExpr {
+            span,
+            obj: Box::new(Expr::Ident(fervid_atom!("_cache").into_ident_spanned(span))),
+            prop: MemberProp::Computed(ComputedPropName {
+                span,
+                expr: Box::new(Expr::Lit(Lit::Num(Number {
+                    span,
+                    value: cache_idx as f64,
+                    raw: None,
+                }))),
+            }),
+        };
+
+        // `(_cache[0] = handler)`
+        let cache_assign = Box::new(Expr::Paren(ParenExpr {
+            span,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(
+                    cache_member_expr.to_owned(),
+                )),
+                right: handler,
+            })),
+        }));
+
+        Box::new(Expr::Bin(BinExpr {
+            span,
+            op: BinaryOp::LogicalOr,
+            left: Box::new(Expr::Member(cache_member_expr)),
+            right: cache_assign,
+        }))
+    }
+
     fn generate_class_bindings(
         &mut self,
         class_regular_attr: Option<(&FervidAtom, Span)>,
@@ -582,7 +731,7 @@ fn empty_arrow_expr(span: Span) -> Expr {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{AttributeOrBinding, VOnDirective};
+    use fervid_core::{AttributeOrBinding, VBindDirective, VOnDirective};
     use swc_core::{common::DUMMY_SP, ecma::ast::ObjectLit};
 
     use crate::{
@@ -683,6 +832,7 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: None,
                 modifiers: vec![],
+                is_cacheable: false,
                 span: DUMMY_SP,
             })],
             r"{onClick:()=>{}}",
@@ -694,6 +844,7 @@ mod tests {
                 event: Some("onMultiWordEvent".into()),
                 handler: None,
                 modifiers: vec![],
+                is_cacheable: false,
                 span: DUMMY_SP,
             })],
             r"{onMultiWordEvent:()=>{}}",
@@ -739,6 +890,7 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: None,
                 modifiers: vec!["stop".into(), "prevent".into(), "self".into()],
+                is_cacheable: false,
                 span: DUMMY_SP,
             })],
             r#"{onClick:_withModifiers(()=>{},["stop","prevent","self"])}"#,
@@ -750,12 +902,106 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: Some(js("$event => handleClick($event, foo, bar)")),
                 modifiers: vec!["stop".into()],
+                is_cacheable: false,
                 span: DUMMY_SP,
             })],
             r#"{onClick:_withModifiers($event=>handleClick($event,foo,bar),["stop"])}"#,
         );
     }
 
+    #[test]
+    fn it_generates_cached_v_on() {
+        let cached_v_on = |event: &str, handler: &str| {
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some(event.into()),
+                handler: Some(js(handler)),
+                modifiers: vec![],
+                is_cacheable: true,
+                span: DUMMY_SP,
+            })
+        };
+
+        // @click="count++" @focus="onFocus"
+        test_out(
+            vec![
+                cached_v_on("onClick", "$event => count++"),
+                cached_v_on("onFocus", "(...args) => onFocus && onFocus(...args)"),
+            ],
+            r#"{onClick:_cache[0]||(_cache[0]=$event=>count++),onFocus:_cache[1]||(_cache[1]=(...args)=>onFocus&&onFocus(...args))}"#,
+        );
+
+        // Inside `v-once`
+        let mut ctx = CodegenContext {
+            is_cache_disabled: true,
+            ..Default::default()
+        };
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(
+            &[cached_v_on("onClick", "$event => count++")],
+            &mut out.props,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{onClick:$event=>count++}"#
+        );
+        assert_eq!(0, ctx.next_cache_index);
+    }
+
+    #[test]
+    fn it_generates_v_bind_object() {
+        // v-bind="{ ...a, b: 1 }"
+        test_props_expr(
+            vec![v_bind_object("{ ...a, b: 1 }")],
+            r#"_normalizeProps(_guardReactiveProps({...a,b:1}))"#,
+        );
+
+        // foo="bar" v-bind="obj"
+        test_props_expr(
+            vec![regular_attribute("foo", "bar"), v_bind_object("obj")],
+            r#"_mergeProps({foo:"bar"},obj)"#,
+        );
+
+        // v-bind="obj" :baz="qux" v-bind="{ ...rest }"
+        test_props_expr(
+            vec![
+                v_bind_object("obj"),
+                v_bind_attribute("baz", "qux"),
+                v_bind_object("{ ...rest }"),
+            ],
+            r#"_mergeProps(obj,{baz:qux},{...rest})"#,
+        );
+
+        // class="a" v-bind="obj"
+        test_props_expr(
+            vec![regular_attribute("class", "a"), v_bind_object("obj")],
+            r#"_mergeProps({class:"a"},obj)"#,
+        );
+    }
+
+    fn v_bind_object(value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: None,
+            value: js(value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: DUMMY_SP,
+        })
+    }
+
+    fn test_props_expr(input: Vec<AttributeOrBinding>, expected: &str) {
+        let mut ctx = CodegenContext::default();
+        let mut props = vec![];
+        ctx.generate_attributes(&input, &mut props);
+        let out = ctx
+            .generate_props_expr(props, DUMMY_SP)
+            .expect("Should generate props");
+        assert_eq!(crate::test_utils::to_str(out), expected)
+    }
+
     fn test_out(input: Vec<AttributeOrBinding>, expected: &str) {
         let mut ctx = CodegenContext::default();
         let mut out = ObjectLit {
@@ -28,13 +28,13 @@ impl CodegenContext {
 
         // Expression to put as the first argument to `resolveDynamicComponent()`
         let is_attribute_expr = match component_is_attribute {
-            AttributeOrBinding::RegularAttribute { name, value, span } if name == "is" => {
-                Expr::Lit(Lit::Str(Str {
-                    span: *span,
-                    value: value.to_owned(),
-                    raw: None,
-                }))
-            }
+            AttributeOrBinding::RegularAttribute {
+                name, value, span, ..
+            } if name == "is" => Expr::Lit(Lit::Str(Str {
+                span: *span,
+                value: value.to_owned(),
+                raw: None,
+            })),
 
             AttributeOrBinding::VBind(VBindDirective {
                 argument: Some(StrOrExpr::Str(name)),
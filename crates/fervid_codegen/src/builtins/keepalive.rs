@@ -4,7 +4,10 @@ use swc_core::ecma::ast::{ArrayLit, Expr, ExprOrSpread};
 use crate::CodegenContext;
 
 impl CodegenContext {
-    /// Generates `(_openBlock(), _createBlock(_KeepAlive, null, [keepalive_children], 1024))`
+    /// Generates `(_openBlock(), _createBlock(_KeepAlive, null, [keepalive_children], 1024))`.
+    ///
+    /// `include`, `exclude` and `max` are regular props of `KeepAlive`
+    /// and are passed as they are, e.g. `max="10"` stays a string (the runtime accepts both).
     pub fn generate_keepalive(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
@@ -36,16 +39,23 @@ impl CodegenContext {
             None
         };
 
+        // `<KeepAlive>` is compiled with raw children instead of slot functions,
+        // so that it can be used inside `<Transition>` and other Transition-wrapping HOCs.
+        // It needs to be a block and always be updated (same as `compiler-core`).
         let should_use_block = keepalive_children.is_some();
 
+        // Keep the hints of dynamic props, e.g. `:include`
+        let mut flags: PatchFlagsSet = element_node.patch_hints.flags;
+        if should_use_block {
+            flags |= PatchFlags::DynamicSlots;
+        }
+
         let patch_hints = PatchHints {
-            flags: if should_use_block {
-                PatchFlagsSet::from(PatchFlags::DynamicSlots)
-            } else {
-                PatchFlagsSet::default()
-            },
-            props: vec![],
+            flags,
+            props: element_node.patch_hints.props.to_owned(),
             should_use_block,
+            hoisted: false,
+            constant_type: element_node.patch_hints.constant_type,
         };
 
         self.generate_componentlike(
@@ -154,6 +164,117 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_keepalive_inside_transition() {
+        // <transition><keep-alive><component :is="view" /></keep-alive></transition>
+        let component = ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Component),
+            starting_tag: StartingTag {
+                tag_name: "component".into(),
+                attributes: vec![v_bind_attribute("is", "view")],
+                directives: None,
+            },
+            children: vec![],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+        let keepalive = ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::KeepAlive),
+            starting_tag: StartingTag {
+                tag_name: "keep-alive".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![Node::Element(component)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+        let transition = ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Transition),
+            starting_tag: StartingTag {
+                tag_name: "transition".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![Node::Element(keepalive)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        // `<KeepAlive>` stays a raw child of the `<Transition>` default slot
+        let mut ctx = CodegenContext::default();
+        let out = crate::test_utils::to_str(ctx.generate_transition(&transition));
+        assert!(out.contains(
+            "_withCtx(()=>[(_openBlock(),_createBlock(_KeepAlive,null,[(_openBlock(),_createBlock(_resolveDynamicComponent(view)))],1024))])"
+        ));
+    }
+
+    #[test]
+    fn it_keeps_dynamic_props_hints() {
+        // <keep-alive :include="cached">foobar</keep-alive>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::Props;
+        patch_hints.props.push("include".into());
+
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::KeepAlive),
+                starting_tag: StartingTag {
+                    tag_name: "keep-alive".into(),
+                    attributes: vec![v_bind_attribute("include", "cached")],
+                    directives: None,
+                },
+                children: vec![Node::Text("foobar".into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"(_openBlock(),_createBlock(_KeepAlive,{include:cached},[_createTextVNode("foobar")],1032,["include"]))"#,
+        )
+    }
+
+    #[test]
+    fn it_generates_keepalive_include_exclude_max() {
+        // <keep-alive include="a,b" :exclude="excluded" :max="10"><component :is="view" /></keep-alive>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::Props;
+        patch_hints.props.push("exclude".into());
+
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::KeepAlive),
+                starting_tag: StartingTag {
+                    tag_name: "keep-alive".into(),
+                    attributes: vec![
+                        regular_attribute("include", "a,b"),
+                        v_bind_attribute("exclude", "excluded"),
+                        v_bind_attribute("max", "10"),
+                    ],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Builtin(BuiltinType::Component),
+                    starting_tag: StartingTag {
+                        tag_name: "component".into(),
+                        attributes: vec![v_bind_attribute("is", "view")],
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"(_openBlock(),_createBlock(_KeepAlive,{include:"a,b",exclude:excluded,max:10},[(_openBlock(),_createBlock(_resolveDynamicComponent(view)))],1032,["exclude"]))"#,
+        )
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_keepalive(&input);
@@ -2,7 +2,7 @@ use fervid_core::{
     check_attribute_name, fervid_atom, AttributeOrBinding, ElementNode, IntoIdent, VueImports,
 };
 use swc_core::ecma::ast::{
-    ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Lit, MemberExpr, MemberProp, ObjectLit,
+    ArrayLit, Bool, CallExpr, Callee, Expr, ExprOrSpread, Lit, MemberExpr, MemberProp, ObjectLit,
     Str,
 };
 
@@ -15,6 +15,8 @@ impl CodegenContext {
     /// ```js
     /// renderSlot(_ctx.$slots, "slot-name", /*optional*/ { slot: attributes }, /*optional*/ [slot, children])
     /// ```
+    /// With the scoped styles not using `:slotted()`, `true` is passed as the fifth argument,
+    /// so that the slot content does not get the `-s` scope id.
     pub fn generate_slot(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
@@ -34,7 +36,10 @@ impl CodegenContext {
         let has_attributes =
             element_node.starting_tag.attributes.len() > idx_of_name.map_or(0, |_| 1);
 
-        let render_slot_args_len = if has_children {
+        let no_slotted = self.bindings_helper.no_slotted;
+        let render_slot_args_len = if no_slotted {
+            5
+        } else if has_children {
             4
         } else if has_attributes {
             3
@@ -112,7 +117,7 @@ impl CodegenContext {
                 spread: None,
                 expr: Box::new(Expr::Object(attrs_obj)),
             });
-        } else if has_children {
+        } else if has_children || no_slotted {
             // Pushes `{}` as third argument
             render_slot_args.push(ExprOrSpread {
                 spread: None,
@@ -144,6 +149,22 @@ impl CodegenContext {
                     elems: slot_children,
                 })),
             });
+        } else if no_slotted {
+            // Pushes `undefined` as fourth argument
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(
+                    fervid_atom!("undefined").into_ident_spanned(span),
+                )),
+            });
+        }
+
+        // Fifth arg (optional): `noSlotted`
+        if no_slotted {
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
+            });
         }
 
         // `renderSlot(_ctx.$slots, "slot-name", { slot: attributes }, [slot, children])`
@@ -357,6 +378,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_generates_no_slotted() {
+        // <slot /> with `<style scoped>` not using `:slotted()`
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.no_slotted = true;
+        let out = ctx.generate_slot(&slot!(vec![], vec![]));
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_renderSlot(_ctx.$slots,"default",{},undefined,true)"#
+        );
+
+        // <slot foo="bar" />
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.no_slotted = true;
+        let out = ctx.generate_slot(&slot!(vec![regular_attribute("foo", "bar")], vec![]));
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_renderSlot(_ctx.$slots,"default",{foo:"bar"},undefined,true)"#
+        );
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_slot(&input);
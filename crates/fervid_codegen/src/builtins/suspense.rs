@@ -34,7 +34,7 @@ impl CodegenContext {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag};
+    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag, VSlotDirective, VueDirectives};
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::{regular_attribute, v_bind_attribute};
@@ -127,6 +127,67 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_suspense_default_and_fallback() {
+        // <suspense>
+        //   <template #default><async-comp /></template>
+        //   <template #fallback>Loading...</template>
+        // </suspense>
+        let template_slot = |name: &str, children: Vec<Node>| {
+            Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "template".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: Some(name.into()),
+                            value: None,
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children,
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Suspense),
+                starting_tag: StartingTag {
+                    tag_name: "suspense".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![
+                    template_slot(
+                        "default",
+                        vec![Node::Element(ElementNode {
+                            kind: ElementKind::Component,
+                            starting_tag: StartingTag {
+                                tag_name: "async-comp".into(),
+                                attributes: vec![],
+                                directives: None,
+                            },
+                            children: vec![],
+                            template_scope: 0,
+                            patch_hints: Default::default(),
+                            span: DUMMY_SP,
+                        })],
+                    ),
+                    template_slot("fallback", vec![Node::Text("Loading...".into(), DUMMY_SP)]),
+                ],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"(_openBlock(),_createBlock(_Suspense,null,{default:_withCtx(()=>[_createVNode(_component_async_comp)]),fallback:_withCtx(()=>[_createTextVNode("Loading...")]),_:1}))"#,
+        )
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_suspense(&input);
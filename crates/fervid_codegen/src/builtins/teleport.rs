@@ -4,7 +4,9 @@ use swc_core::ecma::ast::{ArrayLit, Expr, ExprOrSpread, Ident};
 use crate::CodegenContext;
 
 impl CodegenContext {
-    /// Generates `(_openBlock(), _createBlock(_Teleport, null, [teleport_children]))`
+    /// Generates `(_openBlock(), _createBlock(_Teleport, { to, disabled }, [teleport_children]))`.
+    ///
+    /// Teleport children are passed as an array and not as slots (same as `compiler-core`).
     pub fn generate_teleport(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
@@ -51,7 +53,7 @@ impl CodegenContext {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag};
+    use fervid_core::{BuiltinType, ElementKind, Node, PatchFlags, PatchHints, StartingTag};
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::{regular_attribute, v_bind_attribute};
@@ -144,6 +146,45 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_teleport_to_and_disabled() {
+        // <teleport :to="target" :disabled="isDisabled"><div /></teleport>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::Props;
+        patch_hints.props.push("to".into());
+        patch_hints.props.push("disabled".into());
+
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Teleport),
+                starting_tag: StartingTag {
+                    tag_name: "teleport".into(),
+                    attributes: vec![
+                        v_bind_attribute("to", "target"),
+                        v_bind_attribute("disabled", "isDisabled"),
+                    ],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "div".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"(_openBlock(),_createBlock(_Teleport,{to:target,disabled:isDisabled},[_createElementVNode("div")],8,["to","disabled"]))"#,
+        )
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_teleport(&input);
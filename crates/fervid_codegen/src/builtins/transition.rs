@@ -1,5 +1,8 @@
-use fervid_core::{ElementNode, VueImports};
-use swc_core::ecma::ast::{Expr, Ident};
+use fervid_core::{fervid_atom, ElementNode, Node, VueImports};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, Ident, IdentName, KeyValueProp, Lit, Prop, PropName, PropOrSpread, Str},
+};
 
 use crate::CodegenContext;
 
@@ -15,8 +18,27 @@ impl CodegenContext {
             optional: false,
         });
 
-        let transition_attrs =
-            self.generate_builtin_attrs(&element_node.starting_tag.attributes, span);
+        let attributes = &element_node.starting_tag.attributes;
+        let mut transition_props = Vec::with_capacity(attributes.len() + 1);
+        self.generate_attributes(attributes, &mut transition_props);
+
+        // A single child with `v-show` needs `persisted` (same as compiler-dom transition transform)
+        if has_single_v_show_child(element_node) {
+            transition_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("persisted"),
+                }),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: fervid_atom!(""),
+                    raw: None,
+                }))),
+            }))));
+        }
+
+        // `v-bind="attrs"` is merged with the rest, e.g. `_mergeProps(attrs, { persisted: "" })`
+        let transition_attrs = self.generate_props_expr(transition_props, span);
 
         let transition_slots = self.generate_builtin_slots(element_node);
 
@@ -31,12 +53,30 @@ impl CodegenContext {
     }
 }
 
+/// Checks if `<Transition>` has exactly one child (comments excluded) and it has `v-show`
+fn has_single_v_show_child(element_node: &ElementNode) -> bool {
+    let mut children = element_node
+        .children
+        .iter()
+        .filter(|child| !matches!(child, Node::Comment(_, _)));
+
+    match (children.next(), children.next()) {
+        (Some(Node::Element(child)), None) => child
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_show.is_some()),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag};
-    use swc_core::common::DUMMY_SP;
+    use fervid_core::{
+        AttributeOrBinding, BuiltinType, ElementKind, StartingTag, VBindDirective, VueDirectives,
+    };
 
-    use crate::test_utils::{regular_attribute, v_bind_attribute};
+    use crate::test_utils::{js, regular_attribute, v_bind_attribute};
 
     use super::*;
 
@@ -126,6 +166,112 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_persisted_for_v_show_child() {
+        // <transition><div v-show="ok"></div></transition>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_transition(&transition_with_children(vec![Node::Element(
+            child_element("div", true),
+        )]));
+
+        let out = crate::test_utils::to_str(out);
+        assert!(out.starts_with(r#"_createVNode(_Transition,{persisted:""},{default:_withCtx("#));
+        assert!(out.contains("[[_vShow,ok]]"));
+    }
+
+    #[test]
+    fn it_merges_persisted_into_v_bind_object() {
+        // <transition v-bind="attrs"><div v-show="ok"></div></transition>
+        let mut input = transition_with_children(vec![Node::Element(child_element("div", true))]);
+        input
+            .starting_tag
+            .attributes
+            .push(AttributeOrBinding::VBind(VBindDirective {
+                argument: None,
+                value: js("attrs"),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            }));
+
+        let mut ctx = CodegenContext::default();
+        let out = crate::test_utils::to_str(ctx.generate_transition(&input));
+        assert!(out.starts_with(
+            r#"_createVNode(_Transition,_mergeProps(attrs,{persisted:""}),{default:_withCtx("#
+        ));
+    }
+
+    #[test]
+    fn it_merges_persisted_with_v_bind_call_and_attributes() {
+        // <transition v-bind="getAttrs()" name="fade"><div v-show="ok"></div></transition>
+        let mut input = transition_with_children(vec![Node::Element(child_element("div", true))]);
+        input.starting_tag.attributes = vec![
+            AttributeOrBinding::VBind(VBindDirective {
+                argument: None,
+                value: js("getAttrs()"),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            }),
+            regular_attribute("name", "fade"),
+        ];
+
+        let mut ctx = CodegenContext::default();
+        let out = crate::test_utils::to_str(ctx.generate_transition(&input));
+        assert!(out.starts_with(
+            r#"_createVNode(_Transition,_mergeProps(getAttrs(),{name:"fade",persisted:""}),{default:_withCtx("#
+        ));
+    }
+
+    #[test]
+    fn it_does_not_generate_persisted_for_multiple_children() {
+        // <transition><div v-show="ok"></div><span></span></transition>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_transition(&transition_with_children(vec![
+            Node::Element(child_element("div", true)),
+            Node::Element(child_element("span", false)),
+        ]));
+
+        assert!(!crate::test_utils::to_str(out).contains("persisted"));
+    }
+
+    fn transition_with_children(children: Vec<Node>) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Transition),
+            starting_tag: StartingTag {
+                tag_name: "transition".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn child_element(tag_name: &str, has_v_show: bool) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: has_v_show.then(|| {
+                    Box::new(VueDirectives {
+                        v_show: Some(js("ok")),
+                        ..Default::default()
+                    })
+                }),
+            },
+            children: vec![],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_transition(&input);
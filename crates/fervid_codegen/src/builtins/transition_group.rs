@@ -126,6 +126,40 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_transition_group_tag_and_move_class() {
+        // <transition-group tag="ul" move-class="move"><li key="a">a</li></transition-group>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::TransitionGroup),
+                starting_tag: StartingTag {
+                    tag_name: "transition-group".into(),
+                    attributes: vec![
+                        regular_attribute("tag", "ul"),
+                        regular_attribute("move-class", "move"),
+                    ],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "li".into(),
+                        attributes: vec![regular_attribute("key", "a")],
+                        directives: None,
+                    },
+                    children: vec![Node::Text("a".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_TransitionGroup,{tag:"ul","move-class":"move"},{default:_withCtx(()=>[_createElementVNode("li",{key:"a"},"a")]),_:1})"#,
+        )
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_transition_group(&input);
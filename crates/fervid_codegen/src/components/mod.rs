@@ -1,13 +1,12 @@
 use fervid_core::{
     fervid_atom, str_or_expr_to_propname, ComponentBinding, ElementNode, FervidAtom, Node,
-    PatchHints, StartingTag, StrOrExpr, VSlotDirective, VueDirectives, VueImports,
+    PatchHints, StartingTag, StrOrExpr, VSlotDirective, VueImports,
 };
 use swc_core::{
     common::{Span, DUMMY_SP},
     ecma::ast::{
         ArrayLit, ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread,
-        Ident, KeyValueProp, Lit, Null, Number, ObjectLit, Pat, Prop, PropOrSpread, Str,
-        VarDeclarator,
+        Ident, KeyValueProp, Lit, Null, ObjectLit, Pat, Prop, PropOrSpread, Str, VarDeclarator,
     },
 };
 
@@ -26,11 +25,7 @@ impl CodegenContext {
 
         let attributes_obj = self.generate_component_attributes(component_node);
         // TODO Apply all the directives and modifications
-        let attributes_expr = if attributes_obj.props.len() != 0 {
-            Some(Expr::Object(attributes_obj))
-        } else {
-            None
-        };
+        let attributes_expr = self.generate_props_expr(attributes_obj.props, attributes_obj.span);
 
         let children_slots = self.generate_component_children(component_node);
 
@@ -116,11 +111,7 @@ impl CodegenContext {
         if expected_component_args_count >= 4 {
             create_component_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: patch_hints.flags.bits().into(),
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(patch_hints.flags, span)),
             });
 
             // Props array
@@ -245,7 +236,10 @@ impl CodegenContext {
         result
     }
 
-    fn generate_component_attributes<'e>(&mut self, component_node: &'e ElementNode) -> ObjectLit {
+    pub(crate) fn generate_component_attributes(
+        &mut self,
+        component_node: &ElementNode,
+    ) -> ObjectLit {
         let mut result_props = Vec::new();
 
         self.generate_attributes(&component_node.starting_tag.attributes, &mut result_props);
@@ -292,9 +286,43 @@ impl CodegenContext {
         }
 
         // Prepare the necessities.
-        let component_span = DUMMY_SP; // todo
+        let component_span = component_node.span;
         let mut default_slot_children: Vec<Expr> = Vec::new();
 
+        // `v-slot` on the component itself, e.g. `<Comp v-slot="{ item }">{{ item }}</Comp>`.
+        // All the children belong to this slot. Mixing it with `<template v-slot>`
+        // is reported by the transformer, such templates are generated as regular children.
+        if let Some(v_slot) = component_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_slot.as_ref())
+        {
+            self.generate_node_sequence(
+                &mut component_node.children.iter(),
+                &mut default_slot_children,
+                total_children,
+                false,
+            );
+
+            let slot_name = v_slot
+                .slot_name
+                .to_owned()
+                .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+
+            result_static_slots.push(self.generate_slot_shell(
+                slot_name,
+                default_slot_children,
+                v_slot.value.as_deref(),
+                component_span,
+            ));
+
+            return Some(Expr::Object(ObjectLit {
+                span: component_span,
+                props: result_static_slots,
+            }));
+        }
+
         // `SlottedIterator` will iterate over sequences of default or named slots,
         // and it will stop yielding elements unless [`SlottedIterator::toggle_mode`] is called.
         let mut slotted_iterator = SlottedIterator::new(&component_node.children);
@@ -382,12 +410,7 @@ impl CodegenContext {
 
                 // At this point, we have `<template v-slot="maybeSomeBinding">`
                 // We need to generate it as if it was a named slot
-                self.generate_named_slot(
-                    v_slot_directive,
-                    &children,
-                    &directives,
-                    &mut result_static_slots,
-                );
+                self.generate_named_slot(v_slot_directive, children, &mut result_static_slots);
 
                 // Advance the iterator forward
                 slotted_iterator.next();
@@ -415,7 +438,6 @@ impl CodegenContext {
                 self.generate_named_slot(
                     v_slot_directive,
                     &slotted_node.children,
-                    &directives,
                     &mut result_static_slots,
                 );
             }
@@ -453,65 +475,55 @@ impl CodegenContext {
         &mut self,
         v_slot: &VSlotDirective,
         slot_children: &[Node],
-        directives: &VueDirectives,
         out_static_slots: &mut Vec<PropOrSpread>,
     ) {
-        // Extra logic is needed if this is more than just `<template v-slot>`
-        let is_complex = directives.v_if.is_some()
-            || directives.v_else_if.is_some()
-            || directives.v_else.is_some()
-            || directives.v_for.is_some();
-
-        if is_complex {
-            todo!("createSlots is not supported yet");
-            // https://play.vuejs.org/#eNqVVNtuozAQ/ZUpWolGKo2y+xaRqFVfdr9gH0qlOngIVo2NbEMbRfz7js0l0G33okTgmfGcMzfmHN3X9W3bYLSNUpsbUTuw6JoaJFPHXRY5m0X7TImq1sbBGQwW0EFhdAUxucWZylSulXVQ2SPsvP06/o5SavipjeRX8SpT6bqHJiASBpolQY/xWjKHLRoCiktB4PgWeDkWrJHEnykAzhy7XvVnIELXGDVK4MOwW+hDIACv6vyLHvRfRuKwqiUxkgSQctFCm1RYoQ0KyFxabvbnc8CErgM6ThF2Xboma8+QClU3zntrjpKyIo+QVjDypqpOSU5mrchGmWYRCQehOIkH3SjuNXe5FPkLab4QgXKw20PJFJf44PXXvXYVYDPnf+lVksC9lOBKBE0PA1wYzJ1o0QIzCOKotEEOooDnNhHFMwgLhSe8hR/8xeuFiy3k2ng/SJIhZop6LA5FaqV226EH0wWAUkyX14tSfuj/yE+KVSJ/miH4aoLSr3/A+RSPGmXpLAo/RKZBKsy/hBH6SwJKi72zAKFgM7XL5/UbULoemjj1NAzLjLwfHU3y49MoeVKt8v7lp8JzB6PDN0cKqmAE6zCMAbXcUHc8xoEZslxqNt69m2vm8tdBMTCNY7qEr2HLhWUHiX7wiGmWdGprpvalODH6RvzxUg0/XwZHFOhHpiLFskM+5qFY9eA98bZJoc1Y628hznnjLlxLBCq7aOk060d0Ez3o6v3OWi4TCnHcVzS1QqH30Mp/VO9310cLhgqKtELKsEJu+rz/unTI4f/WzbRa5qvk00Uyr0D3Cy9D1W8=
-            // idk if that should be wrapped in block or not
-            // _createSlots({
-            //     default: _withCtx(() => [
-            //       _createTextVNode(" hi ")
-            //     ]),
-            //     _: 2 /* DYNAMIC */
-            //   }, [
-            //     _renderList(1, (i) => {
-            //       return {
-            //         name: "memes",
-            //         fn: _withCtx(() => [
-            //           _createTextVNode(" hi")
-            //         ])
-            //       }
-            //     })
-            //   ]), 1040 /* FULL_PROPS, DYNAMIC_SLOTS */)
-            // let generated = self.generate_node(slotted_node, false);
-        } else {
-            // Generate the children of the `<template v-slot>`
-            let total_children = slot_children.len();
-            let mut slotted_children_results = Vec::with_capacity(total_children);
-            let mut slotted_children_iter = slot_children.iter();
-
-            self.generate_node_sequence(
-                &mut slotted_children_iter,
-                &mut slotted_children_results,
-                total_children,
-                false,
-            );
+        // TODO `<template v-slot v-for>` needs `createSlots`, e.g.
+        // https://play.vuejs.org/#eNqVVNtuozAQ/ZUpWolGKo2y+xaRqFVfdr9gH0qlOngIVo2NbEMbRfz7js0l0G33okTgmfGcMzfmHN3X9W3bYLSNUpsbUTuw6JoaJFPHXRY5m0X7TImq1sbBGQwW0EFhdAUxucWZylSulXVQ2SPsvP06/o5SavipjeRX8SpT6bqHJiASBpolQY/xWjKHLRoCiktB4PgWeDkWrJHEnykAzhy7XvVnIELXGDVK4MOwW+hDIACv6vyLHvRfRuKwqiUxkgSQctFCm1RYoQ0KyFxabvbnc8CErgM6ThF2Xboma8+QClU3zntrjpKyIo+QVjDypqpOSU5mrchGmWYRCQehOIkH3SjuNXe5FPkLab4QgXKw20PJFJf44PXXvXYVYDPnf+lVksC9lOBKBE0PA1wYzJ1o0QIzCOKotEEOooDnNhHFMwgLhSe8hR/8xeuFiy3k2ng/SJIhZop6LA5FaqV226EH0wWAUkyX14tSfuj/yE+KVSJ/miH4aoLSr3/A+RSPGmXpLAo/RKZBKsy/hBH6SwJKi72zAKFgM7XL5/UbULoemjj1NAzLjLwfHU3y49MoeVKt8v7lp8JzB6PDN0cKqmAE6zCMAbXcUHc8xoEZslxqNt69m2vm8tdBMTCNY7qEr2HLhWUHiX7wiGmWdGprpvalODH6RvzxUg0/XwZHFOhHpiLFskM+5qFY9eA98bZJoc1Y628hznnjLlxLBCq7aOk060d0Ez3o6v3OWi4TCnHcVzS1QqH30Mp/VO9310cLhgqKtELKsEJu+rz/unTI4f/WzbRa5qvk00Uyr0D3Cy9D1W8=
+        // _createSlots({
+        //     default: _withCtx(() => [
+        //       _createTextVNode(" hi ")
+        //     ]),
+        //     _: 2 /* DYNAMIC */
+        //   }, [
+        //     _renderList(1, (i) => {
+        //       return {
+        //         name: "memes",
+        //         fn: _withCtx(() => [
+        //           _createTextVNode(" hi")
+        //         ])
+        //       }
+        //     })
+        //   ]), 1040 /* FULL_PROPS, DYNAMIC_SLOTS */)
+        // Until then, the transformer reports it and the slot is generated once without the loop.
+
+        // Generate the children of the `<template v-slot>`
+        let total_children = slot_children.len();
+        let mut slotted_children_results = Vec::with_capacity(total_children);
+        let mut slotted_children_iter = slot_children.iter();
+
+        self.generate_node_sequence(
+            &mut slotted_children_iter,
+            &mut slotted_children_results,
+            total_children,
+            false,
+        );
 
-            let slot_name = v_slot
-                .slot_name
-                .to_owned()
-                .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
-            let span = DUMMY_SP; // todo?
+        let slot_name = v_slot
+            .slot_name
+            .to_owned()
+            .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+        let span = DUMMY_SP; // todo?
 
-            out_static_slots.push(self.generate_slot_shell(
-                slot_name,
-                slotted_children_results,
-                v_slot.value.as_deref(),
-                span,
-            ));
-        }
+        out_static_slots.push(self.generate_slot_shell(
+            slot_name,
+            slotted_children_results,
+            v_slot.value.as_deref(),
+            span,
+        ));
     }
 
     /// Creates the SWC identifier from a tag name. Will fetch from cache if present
-    fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
+    pub(crate) fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
         // Cached
         let existing_component_binding = self.bindings_helper.components.get(tag_name);
         match existing_component_binding {
@@ -640,7 +652,7 @@ fn null(span: Span) -> Box<Expr> {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementKind, Interpolation, Node, StartingTag};
+    use fervid_core::{ElementKind, Interpolation, Node, StartingTag, VueDirectives};
 
     use crate::test_utils::{js, regular_attribute, v_bind_attribute};
 
@@ -709,6 +721,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_generates_text_flag_for_dynamic_slot_text() {
+        // <test-component>{{ msg }}</test-component>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Interpolation(Interpolation {
+                    value: js("msg"),
+                    template_scope: 0,
+                    patch_flag: true,
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,{default:_withCtx(()=>[_createTextVNode(_toDisplayString(msg),1)])})"#,
+            false,
+        );
+    }
+
     #[test]
     fn it_generates_default_slot() {
         // <test-component>hello from component<div>hello from div</div></test-component>
@@ -1207,6 +1245,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_generates_slot_on_component() {
+        // <test-component v-slot="{ item }">{{ item }}</test-component>
+        let Expr::Arrow(arrow) = *js("({ item }) => {}") else {
+            unreachable!()
+        };
+        let slot_binding = arrow
+            .params
+            .into_iter()
+            .next()
+            .expect("Should have a param");
+
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: None,
+                            value: Some(Box::new(slot_binding)),
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Interpolation(Interpolation {
+                    value: js("item"),
+                    template_scope: 1,
+                    patch_flag: true,
+                    span: DUMMY_SP,
+                })],
+                template_scope: 1,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,{default:_withCtx(({item})=>[_createTextVNode(_toDisplayString(item),1)])})"#,
+            false,
+        );
+    }
+
     fn test_out(input: ElementNode, expected: &str, wrap_in_block: bool) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_component_vnode(&input, wrap_in_block);
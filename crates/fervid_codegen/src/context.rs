@@ -1,10 +1,28 @@
+use fervid_core::SsrImportsSet;
 use fervid_transform::BindingsHelper;
+use swc_core::ecma::ast::Expr;
 
 #[derive(Debug, Default)]
 pub struct CodegenContext {
     pub bindings_helper: BindingsHelper,
     pub is_cache_disabled: bool,
-    pub next_cache_index: u8,
+    /// Next free index of the `_cache` array, used by `v-once`, `v-memo` and `v-for` with `v-memo`
+    pub next_cache_index: u32,
+    /// Whether the template is compiled to the `ssrRender` function
+    pub is_ssr: bool,
+    /// Helpers used by the `ssrRender` function
+    pub ssr_imports: SsrImportsSet,
+    /// Static nodes created once outside of the render function, `_hoisted_1` is the first one
+    pub hoists: Vec<Expr>,
+    /// Minimum number of nodes in the consecutive hoisted elements
+    /// to create them from HTML with a single `createStaticVNode`. Default: 20
+    pub stringify_threshold: Option<usize>,
+    /// Follow the patch flags with their names for readability, e.g. `1 /* TEXT */`
+    pub annotate_patch_flags: bool,
+    /// The next element or component rendered by `ssrRender` is the root and gets `_attrs`
+    pub(crate) ssr_root_attrs: bool,
+    /// `v-model` of the `<select>` whose options are currently rendered by `ssrRender`
+    pub(crate) ssr_select_model: Option<Expr>,
 }
 
 impl CodegenContext {
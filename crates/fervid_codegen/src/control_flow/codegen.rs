@@ -1,9 +1,12 @@
-use fervid_core::{ElementKind, ElementNode, IntoIdent, Node, VueImports};
+use fervid_core::{
+    ensure_sufficient_stack, AttributeOrBinding, ElementKind, ElementNode, IntoIdent, Node,
+    PatchFlags, StrOrExpr, VBindDirective, VueImports,
+};
 use smallvec::SmallVec;
 use swc_core::{
     common::{BytePos, Span},
     ecma::ast::{
-        BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, Lit, Number, ParenExpr, SeqExpr,
+        BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, Lit, ParenExpr, SeqExpr, Str,
     },
 };
 
@@ -13,6 +16,10 @@ type TextNodesConcatenationVec = SmallVec<[Expr; 3]>;
 
 impl CodegenContext {
     pub fn generate_node(&mut self, node: &Node, wrap_in_block: bool) -> Expr {
+        ensure_sufficient_stack(|| self.generate_node_impl(node, wrap_in_block))
+    }
+
+    fn generate_node_impl(&mut self, node: &Node, wrap_in_block: bool) -> Expr {
         match node {
             Node::Text(contents, span) => self.generate_text_node(contents, span.to_owned()),
 
@@ -34,6 +41,11 @@ impl CodegenContext {
         element_node: &ElementNode,
         wrap_in_block: bool,
     ) -> Expr {
+        // Static elements are created once outside of the render function
+        if element_node.patch_hints.hoisted {
+            return self.generate_hoisted(element_node);
+        }
+
         // `v-once` logic is common for all
         let has_v_once = element_node
             .starting_tag
@@ -42,10 +54,29 @@ impl CodegenContext {
             .and_then(|directives| directives.v_once)
             .is_some();
 
-        // Disable caching if `v-once` is present
+        // Disable caching if `v-once` is present.
+        // The cached node is created while the block tracking is paused, so it is never a block.
         let old_is_cache_disabled = self.is_cache_disabled;
+        let mut wrap_in_block = wrap_in_block;
+
+        // `v-memo` may return the cached node, which must be a block.
+        // Inside `v-for`, the items are blocks unless the fragment is stable.
+        // The items of `<template v-for>` are Fragments, which are always blocks.
+        if let Some(ref directives) = element_node.starting_tag.directives {
+            if directives.v_memo.is_some() {
+                wrap_in_block = match directives.v_for {
+                    Some(ref v_for) => !v_for.patch_flags.contains(PatchFlags::StableFragment),
+                    None => true,
+                };
+            } else if directives.v_for.is_some() && element_node.starting_tag.tag_name == "template"
+            {
+                wrap_in_block = true;
+            }
+        }
+
         if has_v_once {
             self.is_cache_disabled = true;
+            wrap_in_block = false;
         }
 
         // Generate the relevant render code depending on ElementKind
@@ -68,8 +99,13 @@ impl CodegenContext {
                     result = self.generate_v_for(v_for, Box::new(result));
                 }
                 (Some(v_for), Some(v_memo)) => {
-                    result =
-                        self.generate_v_for_memoized(v_for, Box::new(result), v_memo.to_owned());
+                    let key = find_key(element_node);
+                    result = self.generate_v_for_memoized(
+                        v_for,
+                        Box::new(result),
+                        v_memo.to_owned(),
+                        key,
+                    );
                 }
             }
         }
@@ -134,7 +170,26 @@ impl CodegenContext {
             };
         }
 
+        let mut iter = iter.peekable();
         while let Some(node) = iter.next() {
+            // Consecutive static elements may be created together from their HTML
+            if let Node::Element(element_node) = node {
+                if element_node.patch_hints.hoisted {
+                    let mut siblings = vec![element_node];
+                    while let Some(Node::Element(sibling)) =
+                        iter.next_if(|next| is_hoisted_element(next))
+                    {
+                        siblings.push(sibling);
+                    }
+
+                    maybe_concatenate_text_nodes!();
+                    patch_flag_text = false;
+
+                    self.generate_hoisted_siblings(&siblings, out);
+                    continue;
+                }
+            }
+
             let generated = self.generate_node(node, false);
             let is_text_node = matches!(node, Node::Text(_, _) | Node::Interpolation { .. });
 
@@ -206,7 +261,7 @@ impl CodegenContext {
 
     /// Produce the index for a next `cache[idx]` entry.
     /// This is useful for a `v-once` or event handlers.
-    pub fn allocate_next_cache_entry(&mut self) -> u8 {
+    pub fn allocate_next_cache_entry(&mut self) -> u32 {
         let idx = self.next_cache_index;
         self.next_cache_index += 1;
         idx
@@ -227,23 +282,23 @@ impl CodegenContext {
             return concatenation;
         }
 
-        // `concatenation`
+        // `concatenation`.
+        // A single space is the default value of the argument and is omitted (same as `compiler-core`)
         let mut create_text_vnode_args = Vec::with_capacity(if patch_flag_text { 2 } else { 1 });
-        create_text_vnode_args.push(ExprOrSpread {
-            spread: None,
-            expr: Box::new(concatenation),
-        });
+        let is_single_space = matches!(&concatenation, Expr::Lit(Lit::Str(s)) if s.value == " ");
+        if !is_single_space {
+            create_text_vnode_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(concatenation),
+            });
+        }
 
         // Add patch flag
         // `concatenation, 1`
         if patch_flag_text {
             create_text_vnode_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: 1.0,
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(PatchFlags::Text.into(), span)),
             })
         }
 
@@ -278,3 +333,31 @@ fn join_exprs_to_concatenation(exprs: &mut TextNodesConcatenationVec, span: Span
 
     expr
 }
+
+/// Finds the `key` of the element, e.g. `:key="item.id"`
+fn find_key(element_node: &ElementNode) -> Option<Box<Expr>> {
+    element_node
+        .starting_tag
+        .attributes
+        .iter()
+        .find_map(|attr| match attr {
+            AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(argument)),
+                value,
+                ..
+            }) if argument == "key" => Some(value.to_owned()),
+            AttributeOrBinding::RegularAttribute {
+                name, value, span, ..
+            } if name == "key" => Some(Box::new(Expr::Lit(Lit::Str(Str {
+                span: *span,
+                value: value.to_owned(),
+                raw: None,
+            })))),
+            _ => None,
+        })
+}
+
+#[inline]
+fn is_hoisted_element(node: &Node) -> bool {
+    matches!(node, Node::Element(element_node) if element_node.patch_hints.hoisted)
+}
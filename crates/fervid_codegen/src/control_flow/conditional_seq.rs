@@ -87,9 +87,11 @@ fn should_wrap_in_block(element_node: &ElementNode) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{Conditional, ElementKind, ElementNode, Node, StartingTag};
+    use fervid_core::{
+        Conditional, ElementKind, ElementNode, Node, PatchFlags, PatchHints, StartingTag,
+    };
 
-    use crate::test_utils::js;
+    use crate::test_utils::{js, v_bind_attribute};
 
     use super::*;
 
@@ -291,6 +293,50 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_keyed_fragment() {
+        // <template v-if="foo"><a /><b /></template>
+        let element = |tag_name: &str| {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::StableFragment;
+
+        test_out(
+            ConditionalNodeSequence {
+                if_node: Box::new(Conditional {
+                    condition: *js("foo"),
+                    node: ElementNode {
+                        starting_tag: StartingTag {
+                            tag_name: "template".into(),
+                            attributes: vec![v_bind_attribute("key", "0")],
+                            directives: None,
+                        },
+                        children: vec![element("a"), element("b")],
+                        template_scope: 0,
+                        kind: ElementKind::Element,
+                        patch_hints,
+                        span: DUMMY_SP,
+                    },
+                }),
+                else_if_nodes: vec![],
+                else_node: None,
+            },
+            r#"foo?(_openBlock(),_createElementBlock(_Fragment,{key:0},[_createElementVNode("a"),_createElementVNode("b")],64)):_createCommentVNode("v-if")"#,
+        )
+    }
+
     fn test_out(input: ConditionalNodeSequence, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_conditional_seq(&input);
@@ -1,6 +1,7 @@
 use fervid_core::{
     fervid_atom, BindingTypes, FervidAtom, IntoIdent, SfcTemplateBlock, TemplateGenerationMode, VueImports
 };
+use fervid_transform::misc::has_render_prop;
 use swc_core::{
     atoms::Atom,
     common::{
@@ -8,7 +9,7 @@ use swc_core::{
     },
     ecma::{
         ast::{
-            ArrowExpr, AssignExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, ExportDefaultExpr, Expr, ExprOrSpread, ExprStmt, Function, GetterProp, Ident, IdentName, ImportDecl, MethodProp, Module, ModuleDecl, ModuleItem, ObjectLit, Param, Pat, Prop, PropName, PropOrSpread, ReturnStmt, SetterProp, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator
+            ArrayLit, ArrowExpr, AssignExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, Bool, CallExpr, Callee, Decl, ExportDecl, ExportDefaultExpr, Expr, ExprOrSpread, ExprStmt, FnDecl, FnExpr, Function, GetterProp, Ident, IdentName, ImportDecl, ImportSpecifier, KeyValuePatProp, KeyValueProp, Lit, MemberExpr, MemberProp, MethodProp, Module, ModuleDecl, ModuleExportName, ModuleItem, Null, ObjectLit, ObjectPat, ObjectPatProp, Param, Pat, Prop, PropName, PropOrSpread, ReturnStmt, SetterProp, SpreadElement, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator, WithStmt
         },
         visit::{noop_visit_type, Visit, VisitWith},
     },
@@ -39,25 +40,37 @@ impl CodegenContext {
             let first_child = &sfc_template.roots[0];
             Some(self.generate_node(&first_child, true))
         } else {
-            None
+            // Empty template renders nothing
+            Some(Expr::Lit(Lit::Null(Null { span: DUMMY_SP })))
         }
     }
 
     pub fn generate_module(
         &mut self,
         template_expr: Option<Expr>,
-        mut script: Module,
+        script: Module,
         mut sfc_export_obj: ObjectLit,
         mut synthetic_setup_fn: Option<Box<Function>>,
         gen_default_as: Option<&str>,
     ) -> Module {
+        // A functional component is its render function, there is no `setup` or `render` to attach
+        if self.bindings_helper.functional && !self.is_ssr {
+            let sfc_exported = self.generate_functional_component(template_expr, sfc_export_obj);
+            return self.finalize_module(script, sfc_exported, gen_default_as);
+        }
+
         let template_generation_mode = &self.bindings_helper.template_generation_mode;
 
         if let Some(template_expr) = template_expr {
             match template_generation_mode {
                 // Generates the render expression and appends it to the end of the `setup` function.
                 TemplateGenerationMode::Inline => {
-                    let render_arrow = self.generate_render_arrow(template_expr);
+                    // SSR render function is already an arrow
+                    let render_expr = if self.is_ssr {
+                        template_expr
+                    } else {
+                        Expr::Arrow(self.generate_render_arrow(template_expr))
+                    };
 
                     let setup_function = synthetic_setup_fn.get_or_insert_with(|| {
                         Box::new(Function {
@@ -81,14 +94,34 @@ impl CodegenContext {
 
                     setup_body.stmts.push(Stmt::Return(ReturnStmt {
                         span: DUMMY_SP,
-                        arg: Some(Box::new(Expr::Arrow(render_arrow))),
+                        arg: Some(Box::new(render_expr)),
                     }));
+
+                    // `__ssrInlineRender: true` tells the server renderer to use `setup` result
+                    if self.is_ssr {
+                        sfc_export_obj
+                            .props
+                            .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                                key: PropName::Ident(
+                                    fervid_atom!("__ssrInlineRender").into_ident().into(),
+                                ),
+                                value: Box::new(Expr::Lit(Lit::Bool(Bool {
+                                    span: DUMMY_SP,
+                                    value: true,
+                                }))),
+                            }))));
+                    }
                 }
 
                 // Generates the render Function to be used as a property in exported object.
                 // `render(_ctx, _cache, $props, $setup, $data, $options) { /*...*/ }`
+                // or `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) { /*...*/ }`
                 TemplateGenerationMode::RenderFn => {
-                    let render_fn = self.generate_render_fn(template_expr);
+                    let (render_fn_name, render_fn) = if self.is_ssr {
+                        ("ssrRender", self.generate_ssr_render_fn(template_expr))
+                    } else {
+                        ("render", self.generate_render_fn(template_expr))
+                    };
 
                     // When a synthetic setup function is present,
                     // we need to return bindings as its last statement
@@ -115,16 +148,30 @@ impl CodegenContext {
                         .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
                             key: PropName::Ident(IdentName {
                                 span: DUMMY_SP,
-                                sym: FervidAtom::from("render"),
+                                sym: FervidAtom::from(render_fn_name),
                             }),
                             function: Box::new(render_fn),
                         }))));
                 }
             }
-        } else if matches!(template_generation_mode, TemplateGenerationMode::RenderFn) {
-            // No template but dev mode: still generate return bindings for setup
-            if let Some(ref mut setup_fn) = synthetic_setup_fn {
-                if let Some(ref mut setup_body) = setup_fn.body {
+        } else if let Some(ref mut setup_fn) = synthetic_setup_fn {
+            // No template, but there is a synthetic `setup`.
+            // When `render` is provided manually in `<script>` or when in dev mode,
+            // bindings must be returned for it to access them.
+            // Otherwise (inline mode), return an empty render function like the official compiler.
+            let has_manual_render = has_render_prop(&sfc_export_obj);
+            let is_render_fn_mode =
+                matches!(template_generation_mode, TemplateGenerationMode::RenderFn);
+
+            // Nothing to add when `setup` already returns the render function,
+            // e.g. for `export default <div />` in `<script setup lang="tsx">`
+            let setup_body = setup_fn
+                .body
+                .as_mut()
+                .filter(|_| !self.bindings_helper.setup_render);
+
+            if let Some(setup_body) = setup_body {
+                if is_render_fn_mode || has_manual_render {
                     let return_bindings = self.generate_return_bindings();
                     if !return_bindings.props.is_empty() {
                         setup_body.stmts.push(Stmt::Return(ReturnStmt {
@@ -132,6 +179,25 @@ impl CodegenContext {
                             arg: Some(Box::new(Expr::Object(return_bindings))),
                         }));
                     }
+                } else {
+                    // `return () => {}`
+                    setup_body.stmts.push(Stmt::Return(ReturnStmt {
+                        span: DUMMY_SP,
+                        arg: Some(Box::new(Expr::Arrow(ArrowExpr {
+                            span: DUMMY_SP,
+                            ctxt: Default::default(),
+                            params: vec![],
+                            body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                                span: DUMMY_SP,
+                                ctxt: Default::default(),
+                                stmts: vec![],
+                            })),
+                            is_async: false,
+                            is_generator: false,
+                            type_params: None,
+                            return_type: None,
+                        }))),
+                    }));
                 }
             }
         }
@@ -183,6 +249,16 @@ impl CodegenContext {
             Box::new(Expr::Object(sfc_export_obj))
         };
 
+        self.finalize_module(script, sfc_exported, gen_default_as)
+    }
+
+    /// Appends the imports, the hoisted nodes and the default export (or `const`) of the component to the module
+    fn finalize_module(
+        &mut self,
+        mut script: Module,
+        sfc_exported: Box<Expr>,
+        gen_default_as: Option<&str>,
+    ) -> Module {
         // Do `export default` or `const _smth = ` where variable name is passed
         let gen_default_as = if let Some(options_gen_default_as) = gen_default_as {
             ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
@@ -232,12 +308,317 @@ impl CodegenContext {
                 })));
         }
 
+        // Append the server renderer imports
+        let used_ssr_imports = self.generate_ssr_imports();
+        if !used_ssr_imports.is_empty() {
+            script
+                .body
+                .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    span: DUMMY_SP,
+                    specifiers: used_ssr_imports,
+                    src: Box::new(Str {
+                        span: DUMMY_SP,
+                        value: FervidAtom::from("vue/server-renderer"),
+                        raw: None,
+                    }),
+                    type_only: false,
+                    with: None,
+                    phase: Default::default(),
+                })));
+        }
+
+        // Append the static nodes created once
+        script.body.extend(
+            self.generate_hoisted_decls()
+                .into_iter()
+                .map(ModuleItem::Stmt),
+        );
+
         // Append the default export/const
         script.body.push(gen_default_as);
 
         script
     }
 
+    /// Generates a functional component, i.e. a render function of the props and the context.
+    /// `_ctx` is assembled from them, so that the template identifiers resolve the same way as in `render`.
+    /// The exported object properties (e.g. `__name`, `__scopeId`) are assigned to the function.
+    ///
+    /// `Object.assign(function (_props, _fctx) { const _ctx = { ..._props, $props: _props, /*...*/ }, _cache = []; /*...*/ }, { /*...*/ })`
+    pub fn generate_functional_component(
+        &mut self,
+        template_expr: Option<Expr>,
+        sfc_export_obj: ObjectLit,
+    ) -> Box<Expr> {
+        // Empty template renders nothing
+        let template_expr =
+            template_expr.unwrap_or_else(|| Expr::Lit(Lit::Null(Null { span: DUMMY_SP })));
+        let mut render_fn = self.generate_render_fn(template_expr);
+
+        let ident = |sym: &str| Ident {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            sym: FervidAtom::from(sym),
+            optional: false,
+        };
+        let param = |sym: &str| Param {
+            span: DUMMY_SP,
+            decorators: vec![],
+            pat: Pat::Ident(BindingIdent {
+                id: ident(sym),
+                type_ann: None,
+            }),
+        };
+        let key_value = |key: &str, value: Expr| {
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(ident(key).into()),
+                value: Box::new(value),
+            })))
+        };
+        let fctx_member = |prop: &str| {
+            Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(ident("_fctx"))),
+                prop: MemberProp::Ident(ident(prop).into()),
+            })
+        };
+
+        // `{ ..._props, $props: _props, $attrs: _fctx.attrs, $slots: _fctx.slots, $emit: _fctx.emit }`
+        let ctx_obj = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                PropOrSpread::Spread(SpreadElement {
+                    dot3_token: DUMMY_SP,
+                    expr: Box::new(Expr::Ident(ident("_props"))),
+                }),
+                key_value("$props", Expr::Ident(ident("_props"))),
+                key_value("$attrs", fctx_member("attrs")),
+                key_value("$slots", fctx_member("slots")),
+                key_value("$emit", fctx_member("emit")),
+            ],
+        };
+
+        // There is no instance to cache in across the renders, e.g. for `v-once`
+        let cache_arr = ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![],
+        };
+
+        let var_declarator = |name: &str, init: Expr| VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: ident(name),
+                type_ann: None,
+            }),
+            init: Some(Box::new(init)),
+            definite: false,
+        };
+
+        if let Some(ref mut body) = render_fn.body {
+            body.stmts.insert(
+                0,
+                Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![
+                        var_declarator("_ctx", Expr::Object(ctx_obj)),
+                        var_declarator("_cache", Expr::Array(cache_arr)),
+                    ],
+                }))),
+            );
+        }
+        render_fn.params = vec![param("_props"), param("_fctx")];
+
+        let render_fn_expr = Expr::Fn(FnExpr {
+            ident: None,
+            function: Box::new(render_fn),
+        });
+
+        if sfc_export_obj.props.is_empty() {
+            return Box::new(render_fn_expr);
+        }
+
+        // `Object.assign(function (_props, _fctx) { /*...*/ }, { __name: "Foo" })`
+        Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(ident("Object"))),
+                prop: MemberProp::Ident(ident("assign").into()),
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(render_fn_expr),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Object(sfc_export_obj)),
+                },
+            ],
+            type_args: None,
+        }))
+    }
+
+    /// Generates a standalone module exporting the render function,
+    /// similar to `compileTemplate` of the official compiler.
+    ///
+    /// `import { /*...*/ } from "vue"; export function render(_ctx, _cache) { /*...*/ }`
+    pub fn generate_template_module(&mut self, template_expr: Option<Expr>) -> Module {
+        // Empty template renders nothing
+        let template_expr =
+            template_expr.unwrap_or_else(|| Expr::Lit(Lit::Null(Null { span: DUMMY_SP })));
+        let render_fn = self.generate_render_fn(template_expr);
+
+        let mut body = Vec::with_capacity(2);
+
+        // Imports are known only after the render function was generated
+        let used_imports = self.generate_imports();
+        if !used_imports.is_empty() {
+            body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                span: DUMMY_SP,
+                specifiers: used_imports,
+                src: Box::new(Str {
+                    span: DUMMY_SP,
+                    value: FervidAtom::from("vue"),
+                    raw: None,
+                }),
+                type_only: false,
+                with: None,
+                phase: Default::default(),
+            })));
+        }
+
+        body.extend(
+            self.generate_hoisted_decls()
+                .into_iter()
+                .map(ModuleItem::Stmt),
+        );
+
+        body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            span: DUMMY_SP,
+            decl: Decl::Fn(FnDecl {
+                ident: Ident {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    sym: FervidAtom::from("render"),
+                    optional: false,
+                },
+                declare: false,
+                function: Box::new(render_fn),
+            }),
+        })));
+
+        Module {
+            span: DUMMY_SP,
+            body,
+            shebang: None,
+        }
+    }
+
+    /// Generates the body of a function which returns the render function,
+    /// similar to `compile` of the official compiler with `prefixIdentifiers: false`.
+    /// The code is meant to be evaluated by `new Function()`,
+    /// therefore the helpers come from the global `Vue` instead of imports.
+    ///
+    /// `const _Vue = Vue; return function render(_ctx, _cache) { with (_ctx) { const { /*...*/ } = _Vue; /*...*/ } }`
+    pub fn generate_template_function(&mut self, template_expr: Option<Expr>) -> Module {
+        self.bindings_helper.with_ctx = true;
+
+        // Empty template renders nothing
+        let template_expr =
+            template_expr.unwrap_or_else(|| Expr::Lit(Lit::Null(Null { span: DUMMY_SP })));
+        let mut render_fn = self.generate_render_fn(template_expr);
+
+        // Helpers are known only after the render function was generated
+        let helpers: Vec<ObjectPatProp> = self
+            .generate_imports()
+            .into_iter()
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::Named(named) => Some(ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: match named.imported {
+                        Some(ModuleExportName::Ident(imported)) => PropName::Ident(imported.into()),
+                        _ => return None,
+                    },
+                    value: Box::new(Pat::Ident(named.local.into())),
+                })),
+                _ => None,
+            })
+            .collect();
+
+        let vue_ident = || Box::new(Expr::Ident(fervid_atom!("_Vue").into_ident()));
+        let with_block = render_fn
+            .body
+            .as_mut()
+            .and_then(|body| body.stmts.first_mut())
+            .and_then(|stmt| match stmt {
+                Stmt::With(with_stmt) => match with_stmt.body.as_mut() {
+                    Stmt::Block(block) => Some(block),
+                    _ => None,
+                },
+                _ => None,
+            });
+        if let Some(with_block) = with_block {
+            // Helpers are only available inside `render`, thus the static nodes are created there as well
+            with_block.stmts.splice(0..0, self.generate_hoisted_decls());
+
+            if !helpers.is_empty() {
+                with_block.stmts.insert(
+                    0,
+                    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                        span: DUMMY_SP,
+                        ctxt: Default::default(),
+                        kind: VarDeclKind::Const,
+                        declare: false,
+                        decls: vec![VarDeclarator {
+                            span: DUMMY_SP,
+                            name: Pat::Object(ObjectPat {
+                                span: DUMMY_SP,
+                                props: helpers,
+                                optional: false,
+                                type_ann: None,
+                            }),
+                            init: Some(vue_ident()),
+                            definite: false,
+                        }],
+                    }))),
+                );
+            }
+        }
+
+        let vue_decl = Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(fervid_atom!("_Vue").into_ident().into()),
+                init: Some(Box::new(Expr::Ident(fervid_atom!("Vue").into_ident()))),
+                definite: false,
+            }],
+        })));
+
+        // Only the first two params are used by the runtime-compiled render
+        render_fn.params.truncate(2);
+        let render_fn_expr = Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(Expr::Fn(FnExpr {
+                ident: Some(fervid_atom!("render").into_ident()),
+                function: Box::new(render_fn),
+            }))),
+        });
+
+        Module {
+            span: DUMMY_SP,
+            body: vec![ModuleItem::Stmt(vue_decl), ModuleItem::Stmt(render_fn_expr)],
+            shebang: None,
+        }
+    }
+
     /// Wraps the render function in an arrow expression
     ///
     /// `(_ctx, _cache) => { /*...*/ }` or `(_ctx, _cache) => /*...*/`
@@ -332,6 +713,19 @@ impl CodegenContext {
             span: DUMMY_SP,
         }));
 
+        // `with (_ctx) { /*...*/ }` resolves the non-prefixed identifiers
+        if self.bindings_helper.with_ctx {
+            fn_body_stmts = vec![Stmt::With(WithStmt {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(fervid_atom!("_ctx").into_ident())),
+                body: Box::new(Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    stmts: fn_body_stmts,
+                })),
+            })];
+        }
+
         macro_rules! param {
             ($ident: expr) => {
                 Param {
@@ -529,6 +923,32 @@ impl CodegenContext {
         generate_source_map: bool,
         minify: bool,
     ) -> (String, Option<String>)
+    where
+        T: Node + VisitWith<IdentCollector>,
+    {
+        Self::stringify_with_original(
+            source,
+            source,
+            &|pos| pos,
+            module,
+            filename,
+            generate_source_map,
+            minify,
+        )
+    }
+
+    /// Same as [`CodegenContext::stringify`] for a `source` derived from `original_source`,
+    /// e.g. with the external blocks inlined. The positions of the source map are converted
+    /// with `original_pos`, so that the source map points into `original_source`.
+    pub fn stringify_with_original<T>(
+        source: &str,
+        original_source: &str,
+        original_pos: &dyn Fn(BytePos) -> BytePos,
+        module: &T,
+        filename: FileName,
+        generate_source_map: bool,
+        minify: bool,
+    ) -> (String, Option<String>)
     where
         T: Node + VisitWith<IdentCollector>,
     {
@@ -573,7 +993,20 @@ impl CodegenContext {
                 v.names
             };
 
-            let map = cm.build_source_map_with_config(
+            // Emitting needs the spans of `source`, the source map is built for `original_source`
+            let original_cm = if original_source == source {
+                cm
+            } else {
+                let original_cm: Lrc<SourceMap> = Default::default();
+                original_cm
+                    .new_source_file(Lrc::new(filename.to_owned()), original_source.to_owned());
+                original_cm
+            };
+            for (pos, _) in source_map_buf.iter_mut() {
+                *pos = original_pos(*pos);
+            }
+
+            let map = original_cm.build_source_map_with_config(
                 &source_map_buf,
                 None,
                 SourceMapConfig {
@@ -628,3 +1061,259 @@ impl Visit for IdentCollector {
         self.names.insert(ident.span.lo, ident.sym.clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, ElementNode, Node, StartingTag};
+    use swc_core::{common::Span, ecma::ast::Module};
+
+    use super::*;
+    use crate::test_utils::to_str;
+
+    fn empty_setup_fn() -> Box<Function> {
+        Box::new(Function {
+            params: vec![],
+            decorators: vec![],
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                stmts: vec![],
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        })
+    }
+
+    fn ssr_template_block() -> SfcTemplateBlock {
+        SfcTemplateBlock {
+            lang: fervid_atom!("html"),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: fervid_atom!("p"),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Text(fervid_atom!("hi"), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        }
+    }
+
+    fn empty_module() -> Module {
+        Module {
+            span: DUMMY_SP,
+            body: vec![],
+            shebang: None,
+        }
+    }
+
+    #[test]
+    fn it_keeps_render_returned_by_setup() {
+        // `<script setup lang="tsx">` with `export default <div />` and no `<template>`
+        for mode in [
+            TemplateGenerationMode::Inline,
+            TemplateGenerationMode::RenderFn,
+        ] {
+            let mut ctx = CodegenContext::default();
+            ctx.bindings_helper.template_generation_mode = mode;
+            ctx.bindings_helper.setup_render = true;
+            ctx.bindings_helper
+                .setup_bindings
+                .push(fervid_transform::SetupBinding(
+                    FervidAtom::from("msg"),
+                    BindingTypes::SetupConst,
+                ));
+
+            let mut setup_fn = empty_setup_fn();
+            if let Some(ref mut body) = setup_fn.body {
+                body.stmts.push(Stmt::Return(ReturnStmt {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP })))),
+                }));
+            }
+
+            let module = ctx.generate_module(
+                None,
+                empty_module(),
+                ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![],
+                },
+                Some(setup_fn),
+                None,
+            );
+            assert_eq!("export default{setup(){return null;}};", to_str(module));
+        }
+    }
+
+    #[test]
+    fn it_generates_functional_component() {
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.functional = true;
+
+        let name_prop = Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(fervid_atom!("__name").into_ident().into()),
+            value: Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: fervid_atom!("Foo"),
+                raw: None,
+            }))),
+        });
+
+        let module = ctx.generate_module(
+            Some(Expr::Ident(fervid_atom!("msg").into_ident())),
+            empty_module(),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![PropOrSpread::Prop(Box::new(name_prop))],
+            },
+            None,
+            None,
+        );
+        assert_eq!(
+            "export default Object.assign(function(_props,_fctx){const _ctx={..._props,$props:_props,$attrs:_fctx.attrs,$slots:_fctx.slots,$emit:_fctx.emit},_cache=[];return msg;},{__name:\"Foo\"});",
+            to_str(module)
+        );
+    }
+
+    #[test]
+    fn it_generates_template_function_with_ctx() {
+        let mut ctx = CodegenContext::default();
+        ctx.add_to_imports(VueImports::ToDisplayString);
+        let module =
+            ctx.generate_template_function(Some(Expr::Ident(fervid_atom!("msg").into_ident())));
+        assert_eq!(
+            "const _Vue=Vue;return function render(_ctx,_cache){with(_ctx){const{toDisplayString:_toDisplayString}=_Vue;return msg;}};",
+            to_str(module)
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_render() {
+        // Inline mode
+        let mut ctx = CodegenContext {
+            is_ssr: true,
+            ..Default::default()
+        };
+        ctx.bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        let ssr_render = ctx.generate_ssr_render(&ssr_template_block());
+        let module = ctx.generate_module(
+            ssr_render,
+            empty_module(),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            Some(empty_setup_fn()),
+            None,
+        );
+        assert_eq!(
+            "import{ssrRenderAttrs as _ssrRenderAttrs}from\"vue/server-renderer\";export default{__ssrInlineRender:true,setup(){return(_ctx,_push,_parent,_attrs)=>{_push(`<p${_ssrRenderAttrs(_attrs)}>hi</p>`);};}};",
+            to_str(module)
+        );
+
+        // Render function mode
+        let mut ctx = CodegenContext {
+            is_ssr: true,
+            ..Default::default()
+        };
+        ctx.bindings_helper.template_generation_mode = TemplateGenerationMode::RenderFn;
+        let ssr_render = ctx.generate_ssr_render(&ssr_template_block());
+        let module = ctx.generate_module(
+            ssr_render,
+            empty_module(),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+            None,
+        );
+        assert_eq!(
+            "import{ssrRenderAttrs as _ssrRenderAttrs}from\"vue/server-renderer\";export default{ssrRender(_ctx,_push,_parent,_attrs,$props,$setup,$data,$options){_push(`<p${_ssrRenderAttrs(_attrs)}>hi</p>`);}};",
+            to_str(module)
+        );
+    }
+
+    #[test]
+    fn it_generates_source_map() {
+        // `msg` in `<template>{{ msg }}</template>`
+        let source = "<template>{{ msg }}</template>";
+        let msg_span = Span::new(BytePos(14), BytePos(17));
+        let module = Module {
+            span: DUMMY_SP,
+            body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Ident(
+                    fervid_atom!("msg").into_ident_spanned(msg_span),
+                )),
+            }))],
+            shebang: None,
+        };
+
+        let (code, source_map) = CodegenContext::stringify(
+            source,
+            &module,
+            FileName::Custom("input.vue".into()),
+            true,
+            false,
+        );
+        assert_eq!("msg;", code.trim());
+
+        let source_map = source_map.expect("Source map must be generated");
+        assert!(source_map.contains(r#""sources":["input.vue"]"#));
+        assert!(source_map.contains(r#""names":["msg"]"#));
+    }
+
+    #[test]
+    fn it_generates_template_module() {
+        let mut ctx = CodegenContext::default();
+        let module = ctx.generate_template_module(None);
+        assert_eq!(
+            "export function render(_ctx,_cache,$props,$setup,$data,$options){return null;}",
+            to_str(module)
+        );
+    }
+
+    #[test]
+    fn it_generates_hoisted_nodes_before_render() {
+        // <div><span>static</span></div>
+        let mut span = ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: fervid_atom!("span"),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![Node::Text(fervid_atom!("static"), DUMMY_SP)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+        span.patch_hints.hoisted = true;
+        let mut template_block = ssr_template_block();
+        let Node::Element(ref mut root) = template_block.roots[0] else {
+            unreachable!()
+        };
+        root.starting_tag.tag_name = fervid_atom!("div");
+        root.children = vec![Node::Element(span)];
+
+        let mut ctx = CodegenContext::default();
+        let template_expr = ctx.generate_sfc_template(&template_block);
+        let out = to_str(ctx.generate_template_module(template_expr));
+
+        let hoisted_decl = r#"const _hoisted_1=_createElementVNode("span",null,"static",-1);"#;
+        let render_fn = r#"export function render(_ctx,_cache,$props,$setup,$data,$options){return(_openBlock(),_createElementBlock("div",null,[_hoisted_1]));}"#;
+        assert!(out.contains(hoisted_decl));
+        assert!(out.ends_with(render_fn));
+        assert!(ctx.hoists.is_empty());
+    }
+}
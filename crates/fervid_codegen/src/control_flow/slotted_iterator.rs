@@ -246,6 +246,7 @@ mod tests {
                         event: Some("event".into()),
                         handler: Some(js("baz")),
                         modifiers: vec![],
+                        is_cacheable: false,
                         span: DUMMY_SP,
                     }),
                 ],
@@ -1,13 +1,16 @@
-use fervid_core::{str_to_propname, CustomDirectiveBinding, FervidAtom, StrOrExpr, VueDirectives, VueImports};
+use fervid_core::{
+    str_to_propname, CustomDirectiveBinding, FervidAtom, StrOrExpr, VCustomDirective,
+    VueDirectives, VueImports,
+};
 use swc_core::{
-    common::{Span, DUMMY_SP},
+    common::{Span, Spanned, DUMMY_SP},
     ecma::ast::{
         ArrayLit, BindingIdent, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, KeyValueProp,
         Lit, Number, ObjectLit, Pat, Prop, PropOrSpread, Str, UnaryExpr, UnaryOp, VarDeclarator,
     },
 };
 
-use crate::CodegenContext;
+use crate::{utils::to_valid_asset_id, CodegenContext};
 
 mod v_for;
 mod v_html;
@@ -38,7 +41,7 @@ impl CodegenContext {
 
         // v-show
         if let Some(ref v_show) = directives.v_show {
-            let span = DUMMY_SP; // TODO Span
+            let span = v_show.span();
             let v_show_identifier = Expr::Ident(Ident {
                 span,
                 ctxt: Default::default(),
@@ -58,24 +61,36 @@ impl CodegenContext {
             }))
         }
 
+        // `v-cloak` is not emitted, same as in the official compiler:
+        // it only hides the in-DOM templates until they are mounted
+
         // Generate custom directives last
         for custom_directive in directives.custom.iter() {
-            let span = DUMMY_SP; // TODO Span
-            let directive_ident = self.get_custom_directive_ident(&custom_directive.name, span);
-
             out.push(Some(ExprOrSpread {
                 spread: None,
-                expr: Box::new(self.generate_directive_from_parts(
-                    directive_ident,
-                    custom_directive.value.as_deref(),
-                    custom_directive.argument.as_ref(),
-                    &custom_directive.modifiers,
-                    span,
-                )),
+                expr: Box::new(self.generate_custom_directive(custom_directive)),
             }));
         }
     }
 
+    /// Generates `[_directive_custom, value, "arg", { modifier: true }]` for `v-custom:arg.modifier="value"`.
+    /// The directive is either resolved to a setup binding or resolved during runtime.
+    pub(crate) fn generate_custom_directive(
+        &mut self,
+        custom_directive: &VCustomDirective,
+    ) -> Expr {
+        let span = DUMMY_SP; // TODO Span
+        let directive_ident = self.get_custom_directive_ident(&custom_directive.name, span);
+
+        self.generate_directive_from_parts(
+            directive_ident,
+            custom_directive.value.as_deref(),
+            custom_directive.argument.as_ref(),
+            &custom_directive.modifiers,
+            span,
+        )
+    }
+
     /// Generates `withDirectives(/* render code */, [/* directives array */])`
     pub fn maybe_generate_with_directives(
         &mut self,
@@ -232,8 +247,7 @@ impl CodegenContext {
         }
 
         // _directive_ prefix plus directive name
-        let mut directive_ident_raw = directive_name.replace('-', "_");
-        directive_ident_raw.insert_str(0, "_directive_");
+        let directive_ident_raw = to_valid_asset_id(directive_name, "_directive_");
         let directive_ident_atom = FervidAtom::from(directive_ident_raw);
 
         // Directive will be resolved during runtime, this provides a variable name,
@@ -330,3 +344,84 @@ fn void0() -> Expr {
         }))),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::fervid_atom;
+
+    use super::*;
+    use crate::test_utils::{js, to_str};
+
+    #[test]
+    fn it_generates_runtime_resolved_directive_with_argument_and_modifiers() {
+        // v-custom-dir:arg.foo.bar="value"
+        let mut ctx = CodegenContext::default();
+        let directives = VueDirectives {
+            custom: vec![VCustomDirective {
+                name: fervid_atom!("custom-dir"),
+                argument: Some(StrOrExpr::Str(fervid_atom!("arg"))),
+                modifiers: vec![fervid_atom!("foo"), fervid_atom!("bar")],
+                value: Some(js("value")),
+            }],
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        ctx.generate_directives_to_array(&directives, &mut out);
+        assert_eq!(
+            r#"[_directive_custom_dir,value,"arg",{foo:true,bar:true}]"#,
+            to_str(*out.pop().flatten().expect("directive").expr)
+        );
+
+        let resolves = ctx.generate_directive_resolves();
+        assert_eq!(1, resolves.len());
+        assert_eq!(
+            r#"_directive_custom_dir=_resolveDirective("custom-dir")"#,
+            to_str(resolves[0].clone())
+        );
+    }
+
+    #[test]
+    fn it_generates_directive_with_modifiers_only() {
+        // v-custom.foo
+        let mut ctx = CodegenContext::default();
+        let directives = VueDirectives {
+            custom: vec![VCustomDirective {
+                name: fervid_atom!("custom"),
+                argument: None,
+                modifiers: vec![fervid_atom!("foo")],
+                value: None,
+            }],
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        ctx.generate_directives_to_array(&directives, &mut out);
+        assert_eq!(
+            r#"[_directive_custom,void 0,void 0,{foo:true}]"#,
+            to_str(*out.pop().flatten().expect("directive").expr)
+        );
+    }
+
+    #[test]
+    fn it_generates_directive_with_dynamic_argument() {
+        // v-custom:[arg]="value"
+        let mut ctx = CodegenContext::default();
+        let directives = VueDirectives {
+            custom: vec![VCustomDirective {
+                name: fervid_atom!("custom"),
+                argument: Some(StrOrExpr::Expr(js("arg"))),
+                modifiers: vec![],
+                value: Some(js("value")),
+            }],
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        ctx.generate_directives_to_array(&directives, &mut out);
+        assert_eq!(
+            r#"[_directive_custom,value,arg]"#,
+            to_str(*out.pop().flatten().expect("directive").expr)
+        );
+    }
+}
@@ -4,8 +4,8 @@ use swc_core::{
     ecma::ast::{
         ArrowExpr, AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BindingIdent, BlockStmt,
         BlockStmtOrExpr, CallExpr, Callee, Decl, Expr, ExprOrSpread, ExprStmt, Ident, IfStmt, Lit,
-        MemberExpr, MemberProp, Null, Number, Pat, ReturnStmt, SimpleAssignTarget, Stmt, VarDecl,
-        VarDeclKind, VarDeclarator,
+        MemberExpr, MemberProp, Null, Number, ParenExpr, Pat, ReturnStmt, SeqExpr,
+        SimpleAssignTarget, Stmt, VarDecl, VarDeclKind, VarDeclarator,
     },
 };
 
@@ -81,11 +81,7 @@ impl CodegenContext {
         });
         create_element_block_args.push(ExprOrSpread {
             spread: None,
-            expr: Box::new(Expr::Lit(Lit::Num(Number {
-                span,
-                value: v_for.patch_flags.bits().into(),
-                raw: None,
-            }))),
+            expr: Box::new(self.generate_patch_flag(v_for.patch_flags, span)),
         });
 
         let create_element_block = Expr::Call(CallExpr {
@@ -114,16 +110,20 @@ impl CodegenContext {
     /// (_openBlock(), _createElementBlock(_Fragment, null, _renderList(3, (i, __, ___, _cached) => {
     ///   const _memo = ([])
     ///   if (_cached && _isMemoSame(_cached, _memo)) return _cached
-    ///   const _item = (_openBlock(), _createElementBlock("div"))
+    ///   const _item = _createElementVNode("div")
     ///   _item.memo = _memo
     ///   return _item
     /// }, _cache, 0), 64 /* STABLE_FRAGMENT */))
     /// ```
+    ///
+    /// With a `key`, the cached item is only reused for the same key,
+    /// e.g. `if (_cached && _cached.key === item.id && _isMemoSame(_cached, _memo)) return _cached`.
     pub fn generate_v_for_memoized(
         &mut self,
         v_for: &VForDirective,
         item_render_expr: Box<Expr>,
         memo_expr: Box<Expr>,
+        key: Option<Box<Expr>>,
     ) -> Expr {
         // Prepare
         let span = v_for.span;
@@ -139,9 +139,10 @@ impl CodegenContext {
         let render_list_arrow = ExprOrSpread {
             spread: None,
             expr: self.generate_memoized_render_arrow(
-                v_for.itervar.to_owned(),
+                &v_for.itervar,
                 item_render_expr,
                 memo_expr,
+                key,
             ),
         };
 
@@ -206,11 +207,7 @@ impl CodegenContext {
         // 2.4. Fragment patch flag
         let fragment_patch_flag = ExprOrSpread {
             spread: None,
-            expr: Box::new(Expr::Lit(Lit::Num(Number {
-                span,
-                value: v_for.patch_flags.bits().into(),
-                raw: None,
-            }))),
+            expr: Box::new(self.generate_patch_flag(v_for.patch_flags, span)),
         };
 
         // 2.5. Generate `_createElementBlock`
@@ -245,16 +242,17 @@ impl CodegenContext {
     /// (i, __, ___, _cached) => {
     ///   const _memo = ([])
     ///   if (_cached && _isMemoSame(_cached, _memo)) return _cached
-    ///   const _item = (_openBlock(), _createElementBlock("div"))
+    ///   const _item = _createElementVNode("div")
     ///   _item.memo = _memo
     ///   return _item
     /// }
     /// ```
     fn generate_memoized_render_arrow(
         &mut self,
-        itervar: Box<Expr>,
+        itervar: &Expr,
         item_render_expr: Box<Expr>,
         memo_expr: Box<Expr>,
+        key: Option<Box<Expr>>,
     ) -> Box<Expr> {
         // `_cached`
         let cached_ident = fervid_atom!("_cached").into_ident();
@@ -271,15 +269,30 @@ impl CodegenContext {
                 })
             };
         }
-        let arrow_params = vec![
-            Pat::Expr(itervar),
-            param!("__"),
-            param!("___"),
-            Pat::Ident(BindingIdent {
-                id: cached_ident.to_owned(),
-                type_ann: None,
-            }),
-        ];
+
+        // `(item, index)` is split into the separate params,
+        // the missing ones are filled up to `_cached`
+        let mut itervar = itervar;
+        while let Expr::Paren(ParenExpr { expr, .. }) = itervar {
+            itervar = &**expr;
+        }
+        let mut arrow_params: Vec<Pat> = match itervar {
+            Expr::Seq(SeqExpr { exprs, .. }) => exprs
+                .iter()
+                .map(|expr| Pat::Expr(expr.to_owned()))
+                .collect(),
+            itervar => vec![Pat::Expr(Box::new(itervar.to_owned()))],
+        };
+        if arrow_params.len() < 2 {
+            arrow_params.push(param!("__"));
+        }
+        if arrow_params.len() < 3 {
+            arrow_params.push(param!("___"));
+        }
+        arrow_params.push(Pat::Ident(BindingIdent {
+            id: cached_ident.to_owned(),
+            type_ann: None,
+        }));
 
         // `const _memo = ([])`
         let const_memo = Stmt::Decl(Decl::Var(Box::new(VarDecl {
@@ -321,11 +334,33 @@ impl CodegenContext {
             type_args: None,
         }));
 
+        // `_cached` or `_cached && _cached.key === key`
+        let mut cache_cond = Box::new(Expr::Ident(cached_ident.to_owned()));
+        if let Some(key) = key {
+            let is_same_key = Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::EqEqEq,
+                left: Box::new(Expr::Member(MemberExpr {
+                    span: DUMMY_SP,
+                    obj: Box::new(Expr::Ident(cached_ident.to_owned())),
+                    prop: MemberProp::Ident(fervid_atom!("key").into_ident().into()),
+                })),
+                right: key,
+            }));
+
+            cache_cond = Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::LogicalAnd,
+                left: cache_cond,
+                right: is_same_key,
+            }));
+        }
+
         // `_cached && _isMemoSame(_cached, _memo)`
         let cache_cond = Box::new(Expr::Bin(BinExpr {
             span: DUMMY_SP,
             op: BinaryOp::LogicalAnd,
-            left: Box::new(Expr::Ident(cached_ident.to_owned())),
+            left: cache_cond,
             right: is_memo_same,
         }));
 
@@ -431,6 +466,7 @@ mod tests {
             &v_for,
             js("_createElementVNode(\"div\")"),
             js("[msg.value]"),
+            None,
         );
 
         assert_eq!(
@@ -438,4 +474,29 @@ mod tests {
             "(_openBlock(),_createElementBlock(_Fragment,null,_renderList(3,(item,__,___,_cached)=>{const _memo=[msg.value];if(_cached&&_isMemoSame(_cached,_memo))return _cached;const _item=_createElementVNode(\"div\");_item.memo=_memo;return _item;},_cache,0),64))"
         );
     }
+
+    #[test]
+    fn it_generates_v_for_memoized_with_key() {
+        let mut ctx = CodegenContext::default();
+
+        // `<div v-for="(item, index) in list" :key="item.id" v-memo="[item.selected]"></div>`
+        let v_for = VForDirective {
+            iterable: js("list.value"),
+            itervar: js("(item, index)"),
+            patch_flags: PatchFlags::KeyedFragment.into(),
+            span: DUMMY_SP,
+        };
+
+        let res = ctx.generate_v_for_memoized(
+            &v_for,
+            js("(_openBlock(),_createElementBlock(\"div\",{key:item.id}))"),
+            js("[item.selected]"),
+            Some(js("item.id")),
+        );
+
+        assert_eq!(
+            crate::test_utils::to_str(res),
+            "(_openBlock(),_createElementBlock(_Fragment,null,_renderList(list.value,(item,index,___,_cached)=>{const _memo=[item.selected];if(_cached&&_cached.key===item.id&&_isMemoSame(_cached,_memo))return _cached;const _item=(_openBlock(),_createElementBlock(\"div\",{key:item.id}));_item.memo=_memo;return _item;},_cache,0),128))"
+        );
+    }
 }
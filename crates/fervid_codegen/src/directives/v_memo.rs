@@ -71,6 +71,8 @@ impl CodegenContext {
 
 #[cfg(test)]
 mod tests {
+    use fervid_core::{ElementKind, ElementNode, Node, StartingTag, VueDirectives};
+
     use crate::test_utils::js;
 
     use super::*;
@@ -86,4 +88,32 @@ mod tests {
             "_withMemo([msg.value],()=>_createElementVNode(\"div\"),_cache,0)"
         );
     }
+
+    #[test]
+    fn it_generates_v_memo_element_as_block() {
+        // <div v-memo="[msg]">hello</div>
+        let mut ctx = CodegenContext::default();
+        let element_node = ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_memo: Some(js("[msg.value]")),
+                    ..Default::default()
+                })),
+            },
+            children: vec![Node::Text("hello".into(), DUMMY_SP)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        // Even as a child, the memoized element is a block
+        let res = ctx.generate_element_or_component(&element_node, false);
+        assert_eq!(
+            crate::test_utils::to_str(res),
+            "_withMemo([msg.value],()=>(_openBlock(),_createElementBlock(\"div\",null,\"hello\")),_cache,0)"
+        );
+    }
 }
@@ -295,6 +295,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_generates_multiple_models() {
+        // v-model:first="foo" v-model:second.trim="bar"
+        test_out(
+            vec![
+                VModelDirective {
+                    argument: Some("first".into()),
+                    value: js("foo"),
+                    update_handler: js("$event=>((foo)=$event)").into(),
+                    modifiers: Vec::new(),
+                    span: DUMMY_SP,
+                },
+                VModelDirective {
+                    argument: Some("second".into()),
+                    value: js("bar"),
+                    update_handler: js("$event=>((bar)=$event)").into(),
+                    modifiers: vec!["trim".into()],
+                    span: DUMMY_SP,
+                },
+            ],
+            r#"{first:foo,"onUpdate:first":$event=>((foo)=$event),second:bar,"onUpdate:second":$event=>((bar)=$event),secondModifiers:{trim:true}}"#,
+        );
+    }
+
     #[test]
     fn it_generates_dynamic_model_name() {
         // v-model:[foo]="bar"
@@ -115,6 +115,8 @@ impl CodegenContext {
 
 #[cfg(test)]
 mod tests {
+    use fervid_core::{ElementKind, ElementNode, Node, StartingTag, VueDirectives};
+
     use crate::test_utils::js;
 
     use super::*;
@@ -140,4 +142,32 @@ mod tests {
             "_cache[1]||(_setBlockTracking(-1),_cache[1]=_createElementVNode(\"div\"),_setBlockTracking(1),_cache[1])"
         );
     }
+
+    #[test]
+    fn it_generates_v_once_element_without_block() {
+        // <div v-once>hello</div>
+        let mut ctx = CodegenContext::default();
+        let element_node = ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_once: Some(()),
+                    ..Default::default()
+                })),
+            },
+            children: vec![Node::Text("hello".into(), DUMMY_SP)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        // Even as a root, the cached element is not a block
+        let v_once_expr = ctx.generate_element_or_component(&element_node, true);
+        assert_eq!(
+            crate::test_utils::to_str(v_once_expr),
+            "_cache[0]||(_setBlockTracking(-1),_cache[0]=_createElementVNode(\"div\",null,\"hello\"),_setBlockTracking(1),_cache[0])"
+        );
+    }
 }
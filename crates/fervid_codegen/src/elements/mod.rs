@@ -1,12 +1,12 @@
 use fervid_core::{
-    AttributeOrBinding, ElementNode, IntoIdent, StartingTag, StrOrExpr, VBindDirective, VueImports,
+    AttributeOrBinding, ElementNode, IntoIdent, PatchFlags, PatchFlagsSet, StartingTag, StrOrExpr,
+    VBindDirective, VueImports,
 };
 use swc_core::{
-    common::DUMMY_SP,
+    common::{Span, DUMMY_SP},
     ecma::{
         ast::{
-            ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Lit, Null, Number, ObjectLit,
-            PropOrSpread, Str,
+            ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Lit, Null, Number, PropOrSpread, Str,
         },
         atoms::JsWord,
     },
@@ -20,19 +20,12 @@ impl CodegenContext {
         element_node: &ElementNode,
         wrap_in_block: bool,
     ) -> Expr {
-        let span = DUMMY_SP;
+        let span = element_node.span;
         let starting_tag = &element_node.starting_tag;
 
         // Generate attributes
         let attributes = self.generate_element_attributes(element_node);
-        let attributes_expr = if attributes.len() != 0 {
-            Some(Expr::Object(ObjectLit {
-                span,
-                props: attributes,
-            }))
-        } else {
-            None
-        };
+        let attributes_expr = self.generate_props_expr(attributes, span);
 
         // There is a special case here: `<template>` with `v-if`/`v-else-if`/`v-else`/`v-for`
         let should_generate_fragment_instead = (wrap_in_block
@@ -141,15 +134,9 @@ impl CodegenContext {
 
         // Arg 4 (optional): patch flags (default to nothing)
         if expected_element_args_count >= 4 {
-            let patch_flag_value = element_node.patch_hints.flags.bits();
-
             create_element_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: patch_flag_value.into(),
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(element_node.patch_hints.flags, span)),
             });
 
             if !element_node.patch_hints.props.is_empty() {
@@ -212,9 +199,9 @@ impl CodegenContext {
         create_element_expr
     }
 
-    fn generate_element_attributes<'e>(
+    pub(crate) fn generate_element_attributes(
         &mut self,
-        element_node: &'e ElementNode,
+        element_node: &ElementNode,
     ) -> Vec<PropOrSpread> {
         let mut result_props = Vec::new();
 
@@ -292,10 +279,9 @@ impl CodegenContext {
 
         // Element `v-model` needs a special processing compared to a component one
         if directives.v_model.len() != 0 {
-            let span = DUMMY_SP; // TODO Span
             let v_model_identifier = Expr::Ident(
                 self.get_element_vmodel_directive_name(&element_node.starting_tag)
-                    .into_ident_spanned(span),
+                    .into_ident(),
             );
 
             for v_model in directives.v_model.iter() {
@@ -306,7 +292,7 @@ impl CodegenContext {
                         Some(&v_model.value),
                         v_model.argument.as_ref(),
                         &v_model.modifiers,
-                        DUMMY_SP,
+                        v_model.span,
                     )),
                 }));
             }
@@ -366,11 +352,57 @@ impl CodegenContext {
             _ => self.get_and_add_import_ident(VueImports::VModelText),
         }
     }
+
+    /// Generates the patch flag argument, e.g. `9`.
+    /// With [`CodegenContext::annotate_patch_flags`], the names of the flags follow it
+    /// the same way as in the official compiler, e.g. `9 /* TEXT, PROPS */`
+    pub(crate) fn generate_patch_flag(&self, flags: PatchFlagsSet, span: Span) -> Expr {
+        let value = flags.bits();
+        let raw = if self.annotate_patch_flags && value > 0 {
+            Some(format!("{} /* {} */", value, patch_flag_names(flags)).into())
+        } else {
+            None
+        };
+
+        Expr::Lit(Lit::Num(Number {
+            span,
+            value: value.into(),
+            raw,
+        }))
+    }
+}
+
+/// Names of the flags joined by a comma, e.g. `TEXT, PROPS`
+fn patch_flag_names(flags: PatchFlagsSet) -> String {
+    flags
+        .into_iter()
+        .map(|flag| match flag {
+            PatchFlags::Text => "TEXT",
+            PatchFlags::Class => "CLASS",
+            PatchFlags::Style => "STYLE",
+            PatchFlags::Props => "PROPS",
+            PatchFlags::FullProps => "FULL_PROPS",
+            PatchFlags::NeedHydration => "NEED_HYDRATION",
+            PatchFlags::StableFragment => "STABLE_FRAGMENT",
+            PatchFlags::KeyedFragment => "KEYED_FRAGMENT",
+            PatchFlags::UnkeyedFragment => "UNKEYED_FRAGMENT",
+            PatchFlags::NeedPatch => "NEED_PATCH",
+            PatchFlags::DynamicSlots => "DYNAMIC_SLOTS",
+            PatchFlags::DevRootFragment => "DEV_ROOT_FRAGMENT",
+            PatchFlags::Hoisted => "HOISTED",
+            PatchFlags::Bail => "BAIL",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementKind, Interpolation, Node, StartingTag};
+    use fervid_core::{
+        ConstantTypes, ElementKind, Interpolation, Node, PatchFlags, PatchHints, StartingTag,
+        VCustomDirective, VueDirectives,
+    };
+    use swc_core::common::{BytePos, Span, Spanned};
 
     use super::*;
     use crate::test_utils::{js, regular_attribute, v_bind_attribute, v_on_attribute};
@@ -543,6 +575,195 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_text_vnodes_with_text_flag() {
+        // <div>{{ msg }} <span>bye!</span> <span>bye!</span></div>
+        let span = || {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "span".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Text("bye!".into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![
+                    Node::Interpolation(Interpolation {
+                        value: js("msg"),
+                        template_scope: 0,
+                        patch_flag: true,
+                        span: DUMMY_SP,
+                    }),
+                    Node::Text(" ".into(), DUMMY_SP),
+                    span(),
+                    Node::Text(" ".into(), DUMMY_SP),
+                    span(),
+                ],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createElementVNode("div",null,[_createTextVNode(_toDisplayString(msg)+" ",1),_createElementVNode("span",null,"bye!"),_createTextVNode(),_createElementVNode("span",null,"bye!")])"#,
+            false,
+        )
+    }
+
+    #[test]
+    fn it_generates_custom_directives() {
+        // <div v-focus v-tooltip:top.once="msg">hello from div</div>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        custom: vec![
+                            VCustomDirective {
+                                name: "focus".into(),
+                                ..Default::default()
+                            },
+                            VCustomDirective {
+                                name: "tooltip".into(),
+                                argument: Some(StrOrExpr::Str("top".into())),
+                                modifiers: vec!["once".into()],
+                                value: Some(js("msg")),
+                            },
+                        ],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text("hello from div".into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives(_createElementVNode("div",null,"hello from div"),[[_directive_focus],[_directive_tooltip,msg,"top",{once:true}]])"#,
+            false,
+        )
+    }
+
+    #[test]
+    fn it_generates_v_show() {
+        // <div v-show="visible" v-cloak>hello</div>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_cloak: Some(()),
+                        v_show: Some(js("visible")),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: PatchHints {
+                    flags: PatchFlags::NeedPatch.into(),
+                    props: vec![],
+                    should_use_block: false,
+                    hoisted: false,
+                    constant_type: ConstantTypes::NotConstant,
+                },
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives(_createElementVNode("div",null,"hello",512),[[_vShow,visible]])"#,
+            false,
+        )
+    }
+
+    #[test]
+    fn it_generates_v_pre_as_static() {
+        // <div v-pre><MyComp :foo="bar">{{ msg }}</MyComp></div>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_pre: Some(()),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "MyComp".into(),
+                        attributes: vec![regular_attribute(":foo", "bar")],
+                        directives: None,
+                    },
+                    children: vec![Node::Text("{{ msg }}".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createElementVNode("div",null,[_createElementVNode("MyComp",{":foo":"bar"},"{{ msg }}")])"#,
+            false,
+        )
+    }
+
+    #[test]
+    fn it_keeps_element_span() {
+        // Spans are used for the source maps
+        let span = Span::new(BytePos(11), BytePos(30));
+        let element = ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span,
+        };
+
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_vnode(&element, false);
+        assert_eq!(span, out.span());
+    }
+
+    #[test]
+    fn it_annotates_patch_flags() {
+        let flags = PatchFlags::Text | PatchFlags::Props;
+
+        let ctx = CodegenContext::default();
+        assert!(matches!(
+            ctx.generate_patch_flag(flags, DUMMY_SP),
+            Expr::Lit(Lit::Num(Number { value, raw: None, .. })) if value == 9.0
+        ));
+
+        let ctx = CodegenContext {
+            annotate_patch_flags: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ctx.generate_patch_flag(flags, DUMMY_SP),
+            Expr::Lit(Lit::Num(Number { raw: Some(raw), .. })) if &*raw == "9 /* TEXT, PROPS */"
+        ));
+    }
+
     fn test_out(input: ElementNode, expected: &str, wrap_in_block: bool) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_element_vnode(&input, wrap_in_block);
@@ -0,0 +1,431 @@
+//! Generates the static nodes marked by the transformer as `hoisted`.
+//!
+//! A hoisted node is created once as a module-level constant and reused by every render:
+//! ```js
+//! const _hoisted_1 = _createElementVNode("p", { class: "intro" }, "Static", -1)
+//! ```
+//! Big static trees and runs of static siblings are created from their HTML instead,
+//! the second argument being the number of the root nodes:
+//! ```js
+//! const _hoisted_1 = _createStaticVNode("<ul><li>1</li><li>2</li>...</ul>", 1)
+//! const _hoisted_2 = _createStaticVNode("<h2>Title</h2><p>Text</p>...", 12)
+//! ```
+
+use fervid_core::{
+    AttributeOrBinding, ConstantTypes, ElementNode, FervidAtom, IntoIdent, Node, VueImports,
+    WhitespaceMode,
+};
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::ast::{
+        CallExpr, Callee, Decl, Expr, ExprOrSpread, Lit, Null, Number, Pat, Stmt, Str, UnaryExpr,
+        UnaryOp, VarDecl, VarDeclKind, VarDeclarator,
+    },
+};
+
+use crate::{
+    context::CodegenContext,
+    ssr::{escape_attr, VOID_TAGS},
+    text::shorten_text,
+};
+
+/// Static trees with at least this many nodes are generated with `createStaticVNode`,
+/// unless [`CodegenContext::stringify_threshold`] is set
+pub const STRINGIFY_THRESHOLD: usize = 20;
+
+/// Elements which cannot be created from HTML outside of a `<table>`
+const NON_STRINGIFIABLE_TAGS: [&str; 9] = [
+    "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+];
+
+impl CodegenContext {
+    /// Generates the hoisted element once and returns its `_hoisted_N` identifier
+    pub fn generate_hoisted(&mut self, element_node: &ElementNode) -> Expr {
+        let hoisted_expr = if self.should_stringify(&[element_node]) {
+            let mut html = String::new();
+            stringify_element(element_node, self.bindings_helper.whitespace, &mut html);
+            self.generate_static_vnode(html, 1)
+        } else {
+            let mut vnode = self.generate_element_vnode(element_node, false);
+            add_hoisted_patch_flag(&mut vnode, self.annotate_patch_flags);
+            vnode
+        };
+
+        self.hoists.push(hoisted_expr);
+        Expr::Ident(hoisted_ident(self.hoists.len()).into_ident_spanned(element_node.span))
+    }
+
+    /// Generates the consecutive hoisted siblings.
+    /// When they are big enough together, a single `_hoisted_N` is created from their HTML,
+    /// otherwise each of them is hoisted separately.
+    pub fn generate_hoisted_siblings(&mut self, siblings: &[&ElementNode], out: &mut Vec<Expr>) {
+        // A single element is stringified on its own
+        if siblings.len() < 2 || !self.should_stringify(siblings) {
+            for element_node in siblings {
+                out.push(self.generate_hoisted(element_node));
+            }
+            return;
+        }
+
+        let mut html = String::new();
+        for element_node in siblings {
+            stringify_element(element_node, self.bindings_helper.whitespace, &mut html);
+        }
+        let static_vnode = self.generate_static_vnode(html, siblings.len());
+
+        self.hoists.push(static_vnode);
+        let span = Span {
+            lo: siblings[0].span.lo,
+            hi: siblings[siblings.len() - 1].span.hi,
+        };
+        out.push(Expr::Ident(
+            hoisted_ident(self.hoists.len()).into_ident_spanned(span),
+        ));
+    }
+
+    /// Generates `const _hoisted_N = /*...*/` declarations in the order of hoisting
+    pub fn generate_hoisted_decls(&mut self) -> Vec<Stmt> {
+        self.hoists
+            .drain(..)
+            .enumerate()
+            .map(|(idx, hoisted_expr)| {
+                Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(hoisted_ident(idx + 1).into_ident().into()),
+                        init: Some(Box::new(hoisted_expr)),
+                        definite: false,
+                    }],
+                })))
+            })
+            .collect()
+    }
+
+    /// Checks whether the static elements are created from HTML
+    fn should_stringify(&self, elements: &[&ElementNode]) -> bool {
+        let threshold = self.stringify_threshold.unwrap_or(STRINGIFY_THRESHOLD);
+        let node_count: usize = elements.iter().map(|el| count_nodes(el)).sum();
+
+        node_count >= threshold && elements.iter().all(|el| is_stringifiable(el))
+    }
+
+    /// `createStaticVNode("<div>...</div>", 1)`
+    fn generate_static_vnode(&mut self, html: String, root_count: usize) -> Expr {
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            callee: Callee::Expr(Box::new(Expr::Ident(
+                self.get_and_add_import_ident(VueImports::CreateStaticVNode)
+                    .into_ident(),
+            ))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: FervidAtom::from(html),
+                        raw: None,
+                    }))),
+                },
+                // Number of the root nodes in the HTML
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Num(Number {
+                        span: DUMMY_SP,
+                        value: root_count as f64,
+                        raw: None,
+                    }))),
+                },
+            ],
+            type_args: None,
+        })
+    }
+}
+
+#[inline]
+fn hoisted_ident(idx: usize) -> FervidAtom {
+    FervidAtom::from(format!("_hoisted_{idx}"))
+}
+
+/// Adds the `HOISTED` patch flag (`-1`) as the 4th argument of `createElementVNode`,
+/// or `-1 /* HOISTED */` when `annotate` is set
+fn add_hoisted_patch_flag(vnode: &mut Expr, annotate: bool) {
+    let Expr::Call(create_element_call) = vnode else {
+        return;
+    };
+
+    let args = &mut create_element_call.args;
+    while args.len() < 3 {
+        args.push(ExprOrSpread {
+            spread: None,
+            expr: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+        });
+    }
+
+    args.push(ExprOrSpread {
+        spread: None,
+        expr: Box::new(Expr::Unary(UnaryExpr {
+            span: DUMMY_SP,
+            op: UnaryOp::Minus,
+            arg: Box::new(Expr::Lit(Lit::Num(Number {
+                span: DUMMY_SP,
+                value: 1.0,
+                raw: annotate.then(|| "1 /* HOISTED */".into()),
+            }))),
+        })),
+    });
+}
+
+/// Counts the element itself and all its descendants
+fn count_nodes(element_node: &ElementNode) -> usize {
+    1 + element_node
+        .children
+        .iter()
+        .map(|child| match child {
+            Node::Element(child_element) => count_nodes(child_element),
+            _ => 1,
+        })
+        .sum::<usize>()
+}
+
+/// Checks that the static tree renders the same when created from HTML.
+/// Only the attributes which are never set as DOM properties are allowed.
+fn is_stringifiable(element_node: &ElementNode) -> bool {
+    let tag_name: &str = &element_node.starting_tag.tag_name;
+    if NON_STRINGIFIABLE_TAGS.contains(&tag_name)
+        || element_node.patch_hints.constant_type != ConstantTypes::CanStringify
+    {
+        return false;
+    }
+
+    let has_stringifiable_attrs =
+        element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .all(|attr| match attr {
+                AttributeOrBinding::RegularAttribute { name, .. } => is_stringifiable_attr(name),
+                _ => false,
+            });
+
+    has_stringifiable_attrs
+        && element_node.children.iter().all(|child| match child {
+            Node::Element(child_element) => is_stringifiable(child_element),
+            Node::Text(_, _) | Node::Comment(_, _) => true,
+            Node::Interpolation(_) | Node::ConditionalSeq(_) => false,
+        })
+}
+
+#[inline]
+fn is_stringifiable_attr(name: &str) -> bool {
+    matches!(
+        name,
+        "alt"
+            | "class"
+            | "dir"
+            | "height"
+            | "href"
+            | "id"
+            | "lang"
+            | "rel"
+            | "role"
+            | "src"
+            | "style"
+            | "tabindex"
+            | "target"
+            | "title"
+            | "width"
+    ) || name.starts_with("data-")
+        || name.starts_with("aria-")
+}
+
+/// Renders the static tree to HTML the same way as the SSR render function
+fn stringify_element(element_node: &ElementNode, whitespace: WhitespaceMode, out: &mut String) {
+    let tag_name: &str = &element_node.starting_tag.tag_name;
+
+    out.push('<');
+    out.push_str(tag_name);
+    for attr in element_node.starting_tag.attributes.iter() {
+        let AttributeOrBinding::RegularAttribute { name, value, .. } = attr else {
+            continue;
+        };
+
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        out.push_str(&escape_attr(value));
+        out.push('"');
+    }
+    out.push('>');
+
+    if VOID_TAGS.contains(&tag_name) {
+        return;
+    }
+
+    for child in element_node.children.iter() {
+        match child {
+            Node::Element(child_element) => stringify_element(child_element, whitespace, out),
+            Node::Text(contents, _) => out.push_str(&shorten_text(contents, whitespace)),
+            Node::Comment(comment, _) => {
+                out.push_str("<!--");
+                out.push_str(comment);
+                out.push_str("-->");
+            }
+            Node::Interpolation(_) | Node::ConditionalSeq(_) => {}
+        }
+    }
+
+    out.push_str("</");
+    out.push_str(tag_name);
+    out.push('>');
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, StartingTag};
+
+    use super::*;
+    use crate::test_utils::{regular_attribute, to_str};
+
+    fn element(tag_name: &str, children: Vec<Node>) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    #[test]
+    fn it_generates_hoisted_element() {
+        // <p class="intro">Static</p>
+        let mut p = element("p", vec![Node::Text("Static".into(), DUMMY_SP)]);
+        p.starting_tag
+            .attributes
+            .push(regular_attribute("class", "intro"));
+
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_hoisted(&p);
+        assert_eq!("_hoisted_1", to_str(out));
+
+        // <br>
+        let out = ctx.generate_hoisted(&element("br", vec![]));
+        assert_eq!("_hoisted_2", to_str(out));
+
+        let decls: Vec<String> = ctx
+            .generate_hoisted_decls()
+            .into_iter()
+            .map(to_str)
+            .collect();
+        assert_eq!(
+            vec![
+                r#"const _hoisted_1=_createElementVNode("p",{class:"intro"},"Static",-1);"#,
+                r#"const _hoisted_2=_createElementVNode("br",null,null,-1);"#,
+            ],
+            decls
+        );
+    }
+
+    #[test]
+    fn it_generates_big_tree_as_static_vnode() {
+        // <ul><li>0</li>...<li>9</li></ul>
+        let items = (0..10)
+            .map(|idx| {
+                Node::Element(element(
+                    "li",
+                    vec![Node::Text(idx.to_string().into(), DUMMY_SP)],
+                ))
+            })
+            .collect();
+        let mut ul = element("ul", items);
+        ul.starting_tag
+            .attributes
+            .push(regular_attribute("class", "list"));
+
+        let mut ctx = CodegenContext::default();
+        ctx.generate_hoisted(&ul);
+
+        let decls: Vec<String> = ctx
+            .generate_hoisted_decls()
+            .into_iter()
+            .map(to_str)
+            .collect();
+        assert_eq!(
+            vec![format!(
+                r#"const _hoisted_1=_createStaticVNode('<ul class="list">{}</ul>',1);"#,
+                (0..10)
+                    .map(|idx| format!("<li>{idx}</li>"))
+                    .collect::<Vec<_>>()
+                    .concat()
+            )],
+            decls
+        );
+    }
+
+    #[test]
+    fn it_stringifies_consecutive_hoisted_siblings() {
+        // <div><p>0</p><p>1</p><p>2</p>text</div>
+        let paragraph = |idx: usize| {
+            let mut p = element("p", vec![Node::Text(idx.to_string().into(), DUMMY_SP)]);
+            p.patch_hints.hoisted = true;
+            Node::Element(p)
+        };
+        let mut children: Vec<Node> = (0..3).map(paragraph).collect();
+        children.push(Node::Text("text".into(), DUMMY_SP));
+        let div = element("div", children);
+
+        // 6 nodes in total
+        let mut ctx = CodegenContext {
+            stringify_threshold: Some(6),
+            ..Default::default()
+        };
+        let (out, _) = ctx.generate_element_children(&div, false);
+        let out: Vec<String> = out.into_iter().map(to_str).collect();
+        assert_eq!(vec!["_hoisted_1", r#"_createTextVNode("text")"#], out);
+
+        let decls: Vec<String> = ctx
+            .generate_hoisted_decls()
+            .into_iter()
+            .map(to_str)
+            .collect();
+        assert_eq!(
+            vec![r#"const _hoisted_1=_createStaticVNode("<p>0</p><p>1</p><p>2</p>",3);"#],
+            decls
+        );
+
+        // Below the threshold, each sibling is hoisted separately
+        let mut ctx = CodegenContext::default();
+        let (out, _) = ctx.generate_element_children(&div, false);
+        assert_eq!(4, out.len());
+        assert_eq!(3, ctx.hoists.len());
+    }
+
+    #[test]
+    fn it_does_not_stringify_table_parts() {
+        // <tbody><tr><td>0</td></tr>...</tbody>
+        let rows = (0..10)
+            .map(|idx| {
+                Node::Element(element(
+                    "tr",
+                    vec![Node::Element(element(
+                        "td",
+                        vec![Node::Text(idx.to_string().into(), DUMMY_SP)],
+                    ))],
+                ))
+            })
+            .collect();
+        let tbody = element("tbody", rows);
+        assert!(count_nodes(&tbody) >= STRINGIFY_THRESHOLD);
+        assert!(!is_stringifiable(&tbody));
+    }
+}
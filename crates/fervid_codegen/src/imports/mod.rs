@@ -1,4 +1,4 @@
-use fervid_core::{FervidAtom, IntoIdent, VueImports};
+use fervid_core::{FervidAtom, IntoIdent, SsrImports, VueImports};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{ImportNamedSpecifier, ImportSpecifier, ModuleExportName},
@@ -21,29 +21,45 @@ impl CodegenContext {
         vue_import.as_atom()
     }
 
+    pub fn get_and_add_ssr_import_ident(&mut self, ssr_import: SsrImports) -> FervidAtom {
+        self.ssr_imports |= ssr_import;
+        ssr_import.as_atom()
+    }
+
     /// Generates all the imports used by template generation.
     /// All of the imports come from 'vue'.
     pub fn generate_imports(&self) -> Vec<ImportSpecifier> {
-        let mut result = Vec::new();
-        for import in self.bindings_helper.vue_imports.into_iter() {
-            let import_raw = import.as_str();
+        self.bindings_helper
+            .vue_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
+    }
 
-            let import_local = import.as_atom().into_ident();
+    /// Generates the imports used by the SSR render function.
+    /// All of the imports come from 'vue/server-renderer'.
+    pub fn generate_ssr_imports(&self) -> Vec<ImportSpecifier> {
+        self.ssr_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
+    }
+}
 
-            let import_vue = Some(ModuleExportName::Ident(
-                FervidAtom::from(&import_raw[1..]).into_ident(),
-            ));
+/// `foo as _foo`, where `import_raw` is `_foo`
+fn generate_import_specifier(import_raw: &'static str) -> ImportSpecifier {
+    let import_local = FervidAtom::from(import_raw).into_ident();
 
-            result.push(ImportSpecifier::Named(ImportNamedSpecifier {
-                span: DUMMY_SP,
-                local: import_local,
-                imported: import_vue,
-                is_type_only: false,
-            }));
-        }
+    let import_vue = Some(ModuleExportName::Ident(
+        FervidAtom::from(&import_raw[1..]).into_ident(),
+    ));
 
-        result
-    }
+    ImportSpecifier::Named(ImportNamedSpecifier {
+        span: DUMMY_SP,
+        local: import_local,
+        imported: import_vue,
+        is_type_only: false,
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,284 @@
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, BuiltinType, ElementNode, FervidAtom,
+    IntoIdent, SsrImports, StrOrExpr, VBindDirective,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, BlockStmtOrExpr, Bool, Expr, ExprStmt, KeyValueProp, Lit, MemberExpr,
+        MemberProp, Number, ObjectLit, Pat, Prop, PropName, PropOrSpread, Stmt,
+    },
+};
+
+use super::{
+    block,
+    components::{as_template_v_slot, null},
+    elements::str_lit,
+    ident, param, SsrBuffer,
+};
+use crate::context::CodegenContext;
+
+impl CodegenContext {
+    pub(crate) fn generate_ssr_builtin(
+        &mut self,
+        element_node: &ElementNode,
+        builtin_type: BuiltinType,
+        buffer: &mut SsrBuffer,
+    ) {
+        match builtin_type {
+            BuiltinType::Component => self.generate_ssr_component_builtin(element_node, buffer),
+            // Transitions and caching do not exist on the server
+            BuiltinType::KeepAlive | BuiltinType::Transition => {
+                self.generate_ssr_children(&element_node.children, buffer)
+            }
+            BuiltinType::Slot => self.generate_ssr_slot_outlet(element_node, buffer),
+            BuiltinType::Suspense => self.generate_ssr_suspense(element_node, buffer),
+            BuiltinType::Teleport => self.generate_ssr_teleport(element_node, buffer),
+            BuiltinType::TransitionGroup => {
+                self.generate_ssr_transition_group(element_node, buffer)
+            }
+        }
+    }
+
+    /// Generates `_ssrRenderVNode(_push, /* component vnode */, _parent)`
+    fn generate_ssr_component_builtin(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let vnode = self.generate_component_builtin(element_node);
+        let render_vnode = self.generate_ssr_call(
+            SsrImports::SsrRenderVNode,
+            vec![ident("_push"), vnode, ident("_parent")],
+        );
+        buffer.push_stmt(expr_stmt(render_vnode));
+    }
+
+    /// Generates the code for `<slot>` element:
+    /// ```js
+    /// _ssrRenderSlot(_ctx.$slots, "slot-name", { slot: attributes }, () => { /* fallback */ }, _push, _parent)
+    /// ```
+    fn generate_ssr_slot_outlet(&mut self, element_node: &ElementNode, buffer: &mut SsrBuffer) {
+        let attributes = &element_node.starting_tag.attributes;
+        let idx_of_name = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "name"));
+
+        // Slot name (`name="foo"`), slot expression (`:name="foo"`) or "default"
+        let name_expr = match idx_of_name.map(|idx| &attributes[idx]) {
+            Some(AttributeOrBinding::RegularAttribute { value, .. }) => str_lit(value),
+            Some(AttributeOrBinding::VBind(v_bind)) => (*v_bind.value).to_owned(),
+            _ => str_lit("default"),
+        };
+
+        // The `name` attribute is not passed to the slot
+        let mut props = Vec::with_capacity(attributes.len());
+        match idx_of_name {
+            Some(idx) => {
+                self.generate_attributes(&attributes[..idx], &mut props);
+                self.generate_attributes(&attributes[(idx + 1)..], &mut props);
+            }
+            None => {
+                self.generate_attributes(attributes, &mut props);
+            }
+        }
+
+        // Fallback content is rendered when the slot is not provided
+        let fallback = if element_node.children.is_empty() {
+            null()
+        } else {
+            let mut fallback_buffer = SsrBuffer::new();
+            self.generate_ssr_children(&element_node.children, &mut fallback_buffer);
+            render_arrow(vec![], fallback_buffer.finish())
+        };
+
+        let slots = Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(ident("_ctx")),
+            prop: MemberProp::Ident(fervid_atom!("$slots").into_ident().into()),
+        });
+
+        let render_slot = self.generate_ssr_call(
+            SsrImports::SsrRenderSlot,
+            vec![
+                slots,
+                name_expr,
+                Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                }),
+                fallback,
+                ident("_push"),
+                ident("_parent"),
+            ],
+        );
+        buffer.push_stmt(expr_stmt(render_slot));
+    }
+
+    /// Generates `_ssrRenderSuspense(_push, { default: () => { /*...*/ }, _: 1 })`.
+    /// Only the default content is rendered on the server.
+    fn generate_ssr_suspense(&mut self, element_node: &ElementNode, buffer: &mut SsrBuffer) {
+        let mut default_buffer = SsrBuffer::new();
+        for child in element_node.children.iter() {
+            match as_template_v_slot(child) {
+                // `<template #default>`
+                Some((v_slot, slot_children)) if is_default_slot(&v_slot.slot_name) => {
+                    self.generate_ssr_children(slot_children, &mut default_buffer)
+                }
+                // `<template #fallback>`
+                Some(_) => {}
+                None => self.generate_ssr_node(child, &mut default_buffer),
+            }
+        }
+
+        let slots = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                key_value("default", render_arrow(vec![], default_buffer.finish())),
+                key_value(
+                    "_",
+                    Expr::Lit(Lit::Num(Number {
+                        span: DUMMY_SP,
+                        value: 1.0,
+                        raw: None,
+                    })),
+                ),
+            ],
+        });
+
+        let render_suspense =
+            self.generate_ssr_call(SsrImports::SsrRenderSuspense, vec![ident("_push"), slots]);
+        buffer.push_stmt(expr_stmt(render_suspense));
+    }
+
+    /// Generates `_ssrRenderTeleport(_push, (_push) => { /*...*/ }, to, disabled, _parent)`
+    fn generate_ssr_teleport(&mut self, element_node: &ElementNode, buffer: &mut SsrBuffer) {
+        let mut to = None;
+        let mut disabled = None;
+        for attr in element_node.starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "to" => {
+                    to = Some(str_lit(value));
+                }
+                AttributeOrBinding::RegularAttribute { name, .. } if name == "disabled" => {
+                    disabled = Some(bool_lit(true));
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "to" => {
+                    to = Some((**value).to_owned());
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "disabled" => {
+                    disabled = Some((**value).to_owned());
+                }
+                _ => {}
+            }
+        }
+
+        let mut content_buffer = SsrBuffer::new();
+        self.generate_ssr_children(&element_node.children, &mut content_buffer);
+
+        let render_teleport = self.generate_ssr_call(
+            SsrImports::SsrRenderTeleport,
+            vec![
+                ident("_push"),
+                render_arrow(vec![param("_push")], content_buffer.finish()),
+                to.unwrap_or_else(null),
+                disabled.unwrap_or_else(|| bool_lit(false)),
+                ident("_parent"),
+            ],
+        );
+        buffer.push_stmt(expr_stmt(render_teleport));
+    }
+
+    /// Generates `<tag>children</tag>` when `tag` is set, otherwise a fragment `<!--[-->children<!--]-->`
+    fn generate_ssr_transition_group(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let tag = element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .find(|attr| check_attribute_name(attr, "tag"));
+
+        match tag {
+            Some(AttributeOrBinding::RegularAttribute { value, .. }) => {
+                buffer.push_str("<");
+                buffer.push_str(value);
+                buffer.push_str(">");
+                self.generate_ssr_children(&element_node.children, buffer);
+                buffer.push_str("</");
+                buffer.push_str(value);
+                buffer.push_str(">");
+            }
+            Some(AttributeOrBinding::VBind(v_bind)) => {
+                buffer.push_str("<");
+                buffer.push_expr((*v_bind.value).to_owned());
+                buffer.push_str(">");
+                self.generate_ssr_children(&element_node.children, buffer);
+                buffer.push_str("</");
+                buffer.push_expr((*v_bind.value).to_owned());
+                buffer.push_str(">");
+            }
+            _ => {
+                buffer.push_str("<!--[-->");
+                self.generate_ssr_children(&element_node.children, buffer);
+                buffer.push_str("<!--]-->");
+            }
+        }
+    }
+}
+
+/// `v-slot`, `v-slot:default` or `#default`
+fn is_default_slot(slot_name: &Option<StrOrExpr>) -> bool {
+    match slot_name {
+        None => true,
+        Some(StrOrExpr::Str(name)) => name == "default",
+        Some(StrOrExpr::Expr(_)) => false,
+    }
+}
+
+/// Generates `(params) => { stmts }`
+fn render_arrow(params: Vec<Pat>, stmts: Vec<Stmt>) -> Expr {
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        params,
+        body: Box::new(BlockStmtOrExpr::BlockStmt(block(stmts))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+fn key_value(key: &str, value: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(FervidAtom::from(key).into_ident().into()),
+        value: Box::new(value),
+    })))
+}
+
+#[inline]
+fn bool_lit(value: bool) -> Expr {
+    Expr::Lit(Lit::Bool(Bool {
+        span: DUMMY_SP,
+        value,
+    }))
+}
+
+#[inline]
+fn expr_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
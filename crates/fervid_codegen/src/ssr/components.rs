@@ -0,0 +1,209 @@
+use fervid_core::{
+    fervid_atom, str_or_expr_to_propname, ElementNode, Node, SsrImports, StartingTag, StrOrExpr,
+    VSlotDirective, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, ArrowExpr, BlockStmtOrExpr, Expr, ExprOrSpread, IfStmt, KeyValueProp, Lit, Null,
+        ObjectLit, Pat, Prop, PropOrSpread, ReturnStmt, Stmt,
+    },
+};
+
+use super::{block, call, ident, param, SsrBuffer};
+use crate::context::CodegenContext;
+
+impl CodegenContext {
+    /// Generates `_push(_ssrRenderComponent(_component_name, props, slots, _parent))`
+    pub(crate) fn generate_ssr_component(
+        &mut self,
+        component_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let span = component_node.span;
+
+        let component_identifier =
+            self.get_component_identifier(&component_node.starting_tag.tag_name, span);
+
+        let is_root = std::mem::take(&mut self.ssr_root_attrs);
+        let attributes_obj = self.generate_component_attributes(component_node);
+        let mut attributes_expr =
+            self.generate_props_expr(attributes_obj.props, attributes_obj.span);
+
+        // The root component passes `_attrs` through: `_mergeProps(props, _attrs)`
+        if is_root {
+            attributes_expr = Some(match attributes_expr {
+                Some(props) => {
+                    let merge_props = self.get_and_add_import_ident(VueImports::MergeProps);
+                    call(merge_props, vec![props, ident("_attrs")])
+                }
+                None => ident("_attrs"),
+            });
+        }
+
+        let slots = self.generate_ssr_slots(component_node);
+
+        let render_component = self.generate_ssr_call(
+            SsrImports::SsrRenderComponent,
+            vec![
+                component_identifier,
+                attributes_expr.unwrap_or_else(null),
+                slots.unwrap_or_else(null),
+                ident("_parent"),
+            ],
+        );
+        buffer.push_call(render_component);
+    }
+
+    /// Generates the slots of a component.
+    /// Each slot renders either to the SSR buffer, or to the vnodes when rendered on the client:
+    ///
+    /// `{ default: _withCtx((_, _push, _parent, _scopeId) => { if (_push) { /*...*/ } else { return [/*...*/] } }) }`
+    pub(crate) fn generate_ssr_slots(&mut self, component_node: &ElementNode) -> Option<Expr> {
+        if component_node.children.is_empty() {
+            return None;
+        }
+
+        let mut slots = Vec::new();
+        let mut default_slot_children = Vec::with_capacity(component_node.children.len());
+
+        for child in component_node.children.iter() {
+            // `<template v-slot:name="binding">`
+            // TODO Support `v-if` and `v-for` on slots (`createSlots`)
+            let Some((v_slot, slot_children)) = as_template_v_slot(child) else {
+                default_slot_children.push(child);
+                continue;
+            };
+
+            let slot_name = v_slot
+                .slot_name
+                .to_owned()
+                .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+            let slot_children: Vec<&Node> = slot_children.iter().collect();
+            slots.push(self.generate_ssr_slot(slot_name, v_slot.value.as_deref(), &slot_children));
+        }
+
+        if !default_slot_children.is_empty() {
+            // `<some-component v-slot="binding">`
+            let default_slot_binding = component_node
+                .starting_tag
+                .directives
+                .as_ref()
+                .and_then(|directives| directives.v_slot.as_ref())
+                .and_then(|v_slot| v_slot.value.as_deref());
+
+            slots.push(self.generate_ssr_slot(
+                StrOrExpr::Str(fervid_atom!("default")),
+                default_slot_binding,
+                &default_slot_children,
+            ));
+        }
+
+        Some(Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: slots,
+        }))
+    }
+
+    /// Generates `name: _withCtx((binding, _push, _parent, _scopeId) => { /*...*/ })`
+    fn generate_ssr_slot(
+        &mut self,
+        slot_name: StrOrExpr,
+        slot_binding: Option<&Pat>,
+        slot_children: &[&Node],
+    ) -> PropOrSpread {
+        // Rendering on the server
+        let mut slot_buffer = SsrBuffer::new();
+        for child in slot_children.iter() {
+            self.generate_ssr_node(child, &mut slot_buffer);
+        }
+
+        // Rendering on the client, e.g. when the slot is passed to a client-only component
+        let mut vnodes = Vec::with_capacity(slot_children.len());
+        self.generate_node_sequence(
+            &mut slot_children.iter().copied(),
+            &mut vnodes,
+            slot_children.len(),
+            false,
+        );
+        let return_vnodes = Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: vnodes
+                    .into_iter()
+                    .map(|vnode| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(vnode),
+                        })
+                    })
+                    .collect(),
+            }))),
+        });
+
+        // `if (_push) { /*...*/ } else { return [/*...*/] }`
+        let if_push = Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(ident("_push")),
+            cons: Box::new(Stmt::Block(block(slot_buffer.finish()))),
+            alt: Some(Box::new(Stmt::Block(block(vec![return_vnodes])))),
+        });
+
+        let params = vec![
+            slot_binding.cloned().unwrap_or_else(|| param("_")),
+            param("_push"),
+            param("_parent"),
+            param("_scopeId"),
+        ];
+
+        let slot_arrow = Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            params,
+            body: Box::new(BlockStmtOrExpr::BlockStmt(block(vec![if_push]))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        let with_ctx = self.get_and_add_import_ident(VueImports::WithCtx);
+
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(slot_name, DUMMY_SP),
+            value: Box::new(call(with_ctx, vec![slot_arrow])),
+        })))
+    }
+}
+
+/// Matches `<template v-slot:name>` and returns its `v-slot` and children
+pub(super) fn as_template_v_slot(node: &Node) -> Option<(&VSlotDirective, &[Node])> {
+    let Node::Element(ElementNode {
+        starting_tag:
+            StartingTag {
+                tag_name,
+                directives: Some(directives),
+                ..
+            },
+        children,
+        ..
+    }) = node
+    else {
+        return None;
+    };
+
+    if tag_name != "template" {
+        return None;
+    }
+
+    directives
+        .v_slot
+        .as_ref()
+        .map(|v_slot| (v_slot, children.as_slice()))
+}
+
+#[inline]
+pub(super) fn null() -> Expr {
+    Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))
+}
@@ -0,0 +1,605 @@
+use fervid_core::{
+    fervid_atom, AttributeOrBinding, ElementNode, FervidAtom, IntoIdent, PatchFlags, SsrImports,
+    StrOrExpr, VBindDirective, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, BinExpr, BinaryOp, CallExpr, Callee, CondExpr, Expr, ExprOrSpread, KeyValueProp,
+        Lit, MemberExpr, MemberProp, Null, ObjectLit, Prop, PropName, PropOrSpread, Str,
+    },
+};
+
+use super::{call, has_single_root, ident, SsrBuffer};
+use crate::context::CodegenContext;
+
+/// Elements without the closing tag
+pub(crate) const VOID_TAGS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Attributes which are rendered without a value when truthy
+const BOOLEAN_ATTRS: [&str; 26] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "readonly",
+    "required",
+    "reversed",
+    "scoped",
+    "seamless",
+    "selected",
+];
+
+impl CodegenContext {
+    /// Generates the HTML of an element, e.g. `<div class="a">children</div>`
+    /// `select_model` is the `v-model` of the `<select>` this element is an option of
+    pub(crate) fn generate_ssr_element(
+        &mut self,
+        element_node: &ElementNode,
+        select_model: Option<&Expr>,
+        buffer: &mut SsrBuffer,
+    ) {
+        let tag_name = &element_node.starting_tag.tag_name;
+        let directives = element_node.starting_tag.directives.as_deref();
+
+        // `<template v-if>` and `<template v-for>` only render their children.
+        // The only child of a root `<template v-if>` is the root
+        if self.should_generate_fragment(element_node) {
+            self.ssr_root_attrs &= has_single_root(&element_node.children);
+            self.ssr_select_model = select_model.cloned();
+            self.generate_ssr_children(&element_node.children, buffer);
+            return;
+        }
+
+        // `<template v-if>` with multiple children is a Fragment
+        if tag_name == "template"
+            && element_node
+                .patch_hints
+                .flags
+                .contains(PatchFlags::StableFragment)
+        {
+            self.ssr_root_attrs = false;
+            self.ssr_select_model = select_model.cloned();
+            buffer.push_str("<!--[-->");
+            self.generate_ssr_children(&element_node.children, buffer);
+            buffer.push_str("<!--]-->");
+            return;
+        }
+
+        buffer.push_str("<");
+        buffer.push_str(tag_name);
+        self.generate_ssr_element_attributes(element_node, buffer);
+
+        // `${_ssrIncludeBooleanAttr(Array.isArray(model) ? _ssrLooseContain(model, value) : _ssrLooseEqual(model, value)) ? " selected" : ""}`
+        if let Some(select_model) = select_model.filter(|_| tag_name == "option") {
+            let attributes = &element_node.starting_tag.attributes;
+            if find_attr(attributes, "selected").is_none() {
+                let value = find_attr(attributes, "value").unwrap_or_else(null);
+                let contain = self.generate_ssr_call(
+                    SsrImports::SsrLooseContain,
+                    vec![select_model.to_owned(), value.to_owned()],
+                );
+                let equal = self.generate_ssr_call(
+                    SsrImports::SsrLooseEqual,
+                    vec![select_model.to_owned(), value],
+                );
+                let selected = is_array_cond(select_model, contain, equal);
+                self.push_ssr_boolean_attr("selected", selected, buffer);
+            }
+        }
+
+        buffer.push_str(">");
+
+        if VOID_TAGS.contains(&tag_name.as_ref()) {
+            return;
+        }
+
+        // Options of `<select v-model>`, also inside `<optgroup>`, are marked as `selected`
+        let v_model = directives.and_then(|directives| directives.v_model.first());
+        self.ssr_select_model = match tag_name.as_ref() {
+            "select" => v_model.map(|v_model| (*v_model.value).to_owned()),
+            "optgroup" => select_model.cloned(),
+            _ => None,
+        };
+
+        let v_model_textarea = v_model.filter(|_| tag_name == "textarea");
+
+        if let Some(v_html) = directives.and_then(|directives| directives.v_html.as_ref()) {
+            // `${html ?? ""}`
+            buffer.push_expr(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::NullishCoalescing,
+                left: v_html.to_owned(),
+                right: Box::new(str_lit("")),
+            }));
+        } else if let Some(v_text) = directives.and_then(|directives| directives.v_text.as_ref()) {
+            let interpolate =
+                self.generate_ssr_call(SsrImports::SsrInterpolate, vec![(**v_text).to_owned()]);
+            buffer.push_expr(interpolate);
+        } else if let Some(v_model) = v_model_textarea {
+            let interpolate = self.generate_ssr_call(
+                SsrImports::SsrInterpolate,
+                vec![(*v_model.value).to_owned()],
+            );
+            buffer.push_expr(interpolate);
+        } else {
+            self.generate_ssr_children(&element_node.children, buffer);
+        }
+
+        buffer.push_str("</");
+        buffer.push_str(tag_name);
+        buffer.push_str(">");
+    }
+
+    /// Generates the attributes of an element.
+    /// Static attributes are rendered as-is, bindings are rendered by the server renderer helpers.
+    fn generate_ssr_element_attributes(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let attributes = &element_node.starting_tag.attributes;
+        let directives = element_node.starting_tag.directives.as_deref();
+        let is_root = std::mem::take(&mut self.ssr_root_attrs);
+
+        // `v-show` is rendered as a part of `style`
+        let v_show_style = directives
+            .and_then(|directives| directives.v_show.as_ref())
+            .map(|v_show| generate_v_show_style(v_show.to_owned()));
+
+        // Keys are not known for `v-bind="obj"` and `:[key]`,
+        // therefore everything is rendered by the runtime.
+        // The same applies to custom directives, which may provide any props via `getSSRProps`.
+        let has_dynamic_keys = attributes.iter().any(|attr| {
+            matches!(
+                attr,
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: None | Some(StrOrExpr::Expr(_)),
+                    ..
+                })
+            )
+        });
+        let has_custom_directives =
+            directives.is_some_and(|directives| !directives.custom.is_empty());
+        // The root also merges `_attrs`, which may contain any attribute
+        if has_dynamic_keys || has_custom_directives || is_root {
+            let props = self.generate_element_attributes(element_node);
+            let mut props_parts: Vec<Expr> = self
+                .generate_props_expr(props, DUMMY_SP)
+                .into_iter()
+                .collect();
+
+            // `{ style: /* v-show */ }`
+            if let Some(v_show_style) = v_show_style {
+                props_parts.push(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(fervid_atom!("style").into_ident().into()),
+                        value: Box::new(v_show_style),
+                    })))],
+                }));
+            }
+
+            // `_ssrGetDirectiveProps(_ctx, _directive_custom, value, "arg", { modifier: true })`
+            for custom_directive in directives
+                .into_iter()
+                .flat_map(|directives| &directives.custom)
+            {
+                let Expr::Array(directive_arr) = self.generate_custom_directive(custom_directive)
+                else {
+                    unreachable!("Directives are always generated as arrays")
+                };
+
+                let args = std::iter::once(ident("_ctx"))
+                    .chain(
+                        directive_arr
+                            .elems
+                            .into_iter()
+                            .flatten()
+                            .map(|directive_part| *directive_part.expr),
+                    )
+                    .collect();
+                props_parts.push(self.generate_ssr_call(SsrImports::SsrGetDirectiveProps, args));
+            }
+
+            // `{ value: model }` or `{ checked: /* model */ }`
+            if element_node.starting_tag.tag_name == "input" {
+                if let Some(v_model) = directives.and_then(|directives| directives.v_model.first())
+                {
+                    let model_props =
+                        self.generate_ssr_input_model_props(attributes, &v_model.value);
+                    props_parts.push(model_props);
+                }
+            }
+
+            if is_root {
+                props_parts.push(ident("_attrs"));
+            }
+
+            // `mergeProps(props, { style: /* v-show */ }, /* directive props */, _attrs)`
+            let props_expr = match props_parts.len() {
+                0 => Expr::Object(ObjectLit::default()),
+                1 => props_parts.remove(0),
+                _ => {
+                    let merge_props = self.get_and_add_import_ident(VueImports::MergeProps);
+                    call(merge_props, props_parts)
+                }
+            };
+
+            let render_attrs = self.generate_ssr_call(SsrImports::SsrRenderAttrs, vec![props_expr]);
+            buffer.push_expr(render_attrs);
+            return;
+        }
+
+        let mut static_class: Option<&FervidAtom> = None;
+        let mut bound_class: Option<&Expr> = None;
+        let mut static_style: Option<&FervidAtom> = None;
+        let mut bound_style: Option<&Expr> = None;
+
+        for attr in attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => match name.as_ref() {
+                    "class" => static_class = Some(value),
+                    "style" => static_style = Some(value),
+                    name if is_ssr_ignored_attr(name) => {}
+                    name => {
+                        buffer.push_str(" ");
+                        buffer.push_str(name);
+                        if !value.is_empty() {
+                            buffer.push_str("=\"");
+                            buffer.push_str(&escape_attr(value));
+                            buffer.push_str("\"");
+                        }
+                    }
+                },
+
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) => match name.as_ref() {
+                    "class" => bound_class = Some(&**value),
+                    "style" => bound_style = Some(&**value),
+                    name if is_ssr_ignored_attr(name) => {}
+
+                    name if BOOLEAN_ATTRS.contains(&name) => {
+                        self.push_ssr_boolean_attr(name, (**value).to_owned(), buffer)
+                    }
+
+                    // `${_ssrRenderAttr("name", value)}`
+                    name => {
+                        let render_attr = self.generate_ssr_call(
+                            SsrImports::SsrRenderAttr,
+                            vec![str_lit(name), (**value).to_owned()],
+                        );
+                        buffer.push_expr(render_attr);
+                    }
+                },
+
+                // Handled above
+                AttributeOrBinding::VBind(_) => {}
+
+                // Event listeners are not rendered
+                AttributeOrBinding::VOn(_) => {}
+            }
+        }
+
+        // `v-model` renders the current value or the checked state
+        if element_node.starting_tag.tag_name == "input" {
+            if let Some(v_model) = directives.and_then(|directives| directives.v_model.first()) {
+                self.generate_ssr_input_model(attributes, &v_model.value, buffer);
+            }
+        }
+
+        // ` class="${_ssrRenderClass(["static", bound])}"`
+        match (static_class, bound_class) {
+            (Some(static_class), None) => {
+                buffer.push_str(" class=\"");
+                buffer.push_str(&escape_attr(static_class));
+                buffer.push_str("\"");
+            }
+            (static_class, Some(bound_class)) => {
+                let class_parts = static_class
+                    .map(|static_class| str_lit(static_class))
+                    .into_iter()
+                    .chain(std::iter::once(bound_class.to_owned()))
+                    .collect();
+                let render_class =
+                    self.generate_ssr_call(SsrImports::SsrRenderClass, vec![join(class_parts)]);
+
+                buffer.push_str(" class=\"");
+                buffer.push_expr(render_class);
+                buffer.push_str("\"");
+            }
+            (None, None) => {}
+        }
+
+        // ` style="${_ssrRenderStyle(["static", bound, vShow])}"`
+        match (static_style, bound_style, v_show_style) {
+            (Some(static_style), None, None) => {
+                buffer.push_str(" style=\"");
+                buffer.push_str(&escape_attr(static_style));
+                buffer.push_str("\"");
+            }
+            (None, None, None) => {}
+            (static_style, bound_style, v_show_style) => {
+                let style_parts = static_style
+                    .map(|static_style| str_lit(static_style))
+                    .into_iter()
+                    .chain(bound_style.cloned())
+                    .chain(v_show_style)
+                    .collect();
+                let render_style =
+                    self.generate_ssr_call(SsrImports::SsrRenderStyle, vec![join(style_parts)]);
+
+                buffer.push_str(" style=\"");
+                buffer.push_expr(render_style);
+                buffer.push_str("\"");
+            }
+        }
+    }
+}
+
+/// How `v-model` of an `<input>` is rendered, depending on its `type`
+enum SsrInputModel {
+    /// `checked` of a checkbox or a radio
+    Checked(Expr),
+    /// `value` of a text input
+    Value(Expr),
+    /// The `type` is only known at runtime
+    Dynamic { input_type: Expr, value: Expr },
+}
+
+impl CodegenContext {
+    /// Generates `v-model` of an `<input>` as an attribute
+    fn generate_ssr_input_model(
+        &mut self,
+        attributes: &[AttributeOrBinding],
+        model: &Expr,
+        buffer: &mut SsrBuffer,
+    ) {
+        match self.get_ssr_input_model(attributes, model) {
+            SsrInputModel::Checked(checked) => {
+                self.push_ssr_boolean_attr("checked", checked, buffer)
+            }
+
+            // `${_ssrRenderAttr("value", model)}`
+            SsrInputModel::Value(model) => {
+                let render_attr = self
+                    .generate_ssr_call(SsrImports::SsrRenderAttr, vec![str_lit("value"), model]);
+                buffer.push_expr(render_attr);
+            }
+
+            // `${_ssrRenderDynamicModel(type, model, value)}`
+            SsrInputModel::Dynamic { input_type, value } => {
+                let render_model = self.generate_ssr_call(
+                    SsrImports::SsrRenderDynamicModel,
+                    vec![input_type, model.to_owned(), value],
+                );
+                buffer.push_expr(render_model);
+            }
+        }
+    }
+
+    /// Generates `v-model` of an `<input>` as props for `_ssrRenderAttrs`
+    fn generate_ssr_input_model_props(
+        &mut self,
+        attributes: &[AttributeOrBinding],
+        model: &Expr,
+    ) -> Expr {
+        match self.get_ssr_input_model(attributes, model) {
+            // `{ checked: checked }`
+            SsrInputModel::Checked(checked) => object_lit(vec![("checked", checked)]),
+
+            // `{ value: model }`
+            SsrInputModel::Value(model) => object_lit(vec![("value", model)]),
+
+            // `_ssrGetDynamicModelProps({ type: type, value: value }, model)`
+            SsrInputModel::Dynamic { input_type, value } => self.generate_ssr_call(
+                SsrImports::SsrGetDynamicModelProps,
+                vec![
+                    object_lit(vec![("type", input_type), ("value", value)]),
+                    model.to_owned(),
+                ],
+            ),
+        }
+    }
+
+    fn get_ssr_input_model(
+        &mut self,
+        attributes: &[AttributeOrBinding],
+        model: &Expr,
+    ) -> SsrInputModel {
+        let value = || find_attr(attributes, "value").unwrap_or_else(null);
+
+        match find_attr(attributes, "type") {
+            // `_ssrLooseEqual(model, trueValue)`
+            // `Array.isArray(model) ? _ssrLooseContain(model, value) : model`
+            Some(Expr::Lit(Lit::Str(ref input_type))) if input_type.value == "checkbox" => {
+                let checked = if let Some(true_value) = find_attr(attributes, "true-value") {
+                    self.generate_ssr_call(
+                        SsrImports::SsrLooseEqual,
+                        vec![model.to_owned(), true_value],
+                    )
+                } else {
+                    let contain = self.generate_ssr_call(
+                        SsrImports::SsrLooseContain,
+                        vec![model.to_owned(), value()],
+                    );
+                    is_array_cond(model, contain, model.to_owned())
+                };
+                SsrInputModel::Checked(checked)
+            }
+
+            // `_ssrLooseEqual(model, value)`
+            Some(Expr::Lit(Lit::Str(ref input_type))) if input_type.value == "radio" => {
+                SsrInputModel::Checked(
+                    self.generate_ssr_call(
+                        SsrImports::SsrLooseEqual,
+                        vec![model.to_owned(), value()],
+                    ),
+                )
+            }
+
+            None | Some(Expr::Lit(_)) => SsrInputModel::Value(model.to_owned()),
+
+            Some(input_type) => SsrInputModel::Dynamic {
+                input_type,
+                value: value(),
+            },
+        }
+    }
+
+    /// Generates `${_ssrIncludeBooleanAttr(value) ? " name" : ""}`
+    fn push_ssr_boolean_attr(&mut self, name: &str, value: Expr, buffer: &mut SsrBuffer) {
+        let include = self.generate_ssr_call(SsrImports::SsrIncludeBooleanAttr, vec![value]);
+        buffer.push_expr(Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(include),
+            cons: Box::new(str_lit(&format!(" {name}"))),
+            alt: Box::new(str_lit("")),
+        }));
+    }
+}
+
+/// Finds an attribute or a binding by its name, e.g. `"x"` for `value="x"` or `x` for `:value="x"`
+fn find_attr(attributes: &[AttributeOrBinding], attr_name: &str) -> Option<Expr> {
+    attributes.iter().find_map(|attr| match attr {
+        AttributeOrBinding::RegularAttribute { name, value, .. } if name == attr_name => {
+            Some(str_lit(value))
+        }
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(name)),
+            value,
+            ..
+        }) if name == attr_name => Some((**value).to_owned()),
+        _ => None,
+    })
+}
+
+/// Generates `Array.isArray(model) ? cons : alt`
+fn is_array_cond(model: &Expr, cons: Expr, alt: Expr) -> Expr {
+    Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(ident("Array")),
+                prop: MemberProp::Ident(fervid_atom!("isArray").into_ident().into()),
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(model.to_owned()),
+            }],
+            type_args: None,
+        })),
+        cons: Box::new(cons),
+        alt: Box::new(alt),
+    })
+}
+
+/// Generates `{ key: value }`
+fn object_lit(props: Vec<(&str, Expr)>) -> Expr {
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props: props
+            .into_iter()
+            .map(|(key, value)| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(FervidAtom::from(key).into_ident().into()),
+                    value: Box::new(value),
+                })))
+            })
+            .collect(),
+    })
+}
+
+#[inline]
+fn null() -> Expr {
+    Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))
+}
+
+/// Generates `cond ? null : { display: "none" }`
+fn generate_v_show_style(condition: Box<Expr>) -> Expr {
+    Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: condition,
+        cons: Box::new(null()),
+        alt: Box::new(Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(fervid_atom!("display").into_ident().into()),
+                value: Box::new(str_lit("none")),
+            })))],
+        })),
+    })
+}
+
+/// Uses the only part as-is, otherwise generates an array of parts
+fn join(mut parts: Vec<Expr>) -> Expr {
+    if parts.len() == 1 {
+        return parts.remove(0);
+    }
+
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: parts
+            .into_iter()
+            .map(|part| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(part),
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Attributes only meaningful to the client runtime
+#[inline]
+fn is_ssr_ignored_attr(name: &str) -> bool {
+    matches!(name, "key" | "ref" | "ref_key" | "ref_for")
+}
+
+#[inline]
+pub(crate) fn escape_attr(value: &str) -> String {
+    value.replace('"', "&quot;")
+}
+
+#[inline]
+pub(crate) fn escape_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[inline]
+pub(crate) fn str_lit(value: &str) -> Expr {
+    Expr::Lit(Lit::Str(Str {
+        span: DUMMY_SP,
+        value: FervidAtom::from(value),
+        raw: None,
+    }))
+}
@@ -0,0 +1,964 @@
+//! Code generation of the SSR render function.
+//!
+//! Instead of creating vnodes, `ssrRender` pushes the rendered HTML to the buffer of the server renderer:
+//! ```js
+//! _push(`<div class="msg">${_ssrInterpolate(_ctx.msg)}</div>`)
+//! ```
+//! Adjacent static and dynamic parts are joined into a single `_push` call.
+
+use fervid_core::{
+    ensure_sufficient_stack, fervid_atom, ConditionalNodeSequence, ElementKind, ElementNode,
+    FervidAtom, IntoIdent, Node, SfcTemplateBlock, SsrImports, VForDirective,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr,
+        ExprOrSpread, ExprStmt, Function, IfStmt, Param, Pat, Stmt, Tpl, TplElement, VarDecl,
+        VarDeclKind,
+    },
+};
+
+use crate::context::CodegenContext;
+
+mod builtins;
+mod components;
+mod elements;
+
+pub(crate) use elements::{escape_attr, VOID_TAGS};
+
+/// Collects the HTML rendered by the SSR render function.
+///
+/// Strings and expressions are accumulated into a template literal
+/// until a statement (e.g. `if` or `_ssrRenderList`) needs to be emitted.
+pub(crate) struct SsrBuffer {
+    stmts: Vec<Stmt>,
+    /// Static parts of the current template literal, always one more than `exprs`
+    quasis: Vec<String>,
+    /// Dynamic parts of the current template literal
+    exprs: Vec<Expr>,
+}
+
+impl SsrBuffer {
+    pub fn new() -> SsrBuffer {
+        SsrBuffer {
+            stmts: Vec::new(),
+            quasis: vec![String::new()],
+            exprs: Vec::new(),
+        }
+    }
+
+    /// Appends static HTML
+    pub fn push_str(&mut self, html: &str) {
+        if let Some(quasi) = self.quasis.last_mut() {
+            quasi.push_str(html);
+        }
+    }
+
+    /// Appends an expression evaluating to the HTML string
+    pub fn push_expr(&mut self, expr: Expr) {
+        self.exprs.push(expr);
+        self.quasis.push(String::new());
+    }
+
+    /// Finishes the current template literal and generates `_push(expr)`
+    pub fn push_call(&mut self, expr: Expr) {
+        self.flush();
+        self.stmts.push(push_stmt(expr));
+    }
+
+    /// Finishes the current template literal and appends a statement
+    pub fn push_stmt(&mut self, stmt: Stmt) {
+        self.flush();
+        self.stmts.push(stmt);
+    }
+
+    pub fn finish(mut self) -> Vec<Stmt> {
+        self.flush();
+        self.stmts
+    }
+
+    /// Generates `_push(`<the>${accumulated}</html>`)`
+    fn flush(&mut self) {
+        if self.exprs.is_empty() && self.quasis.iter().all(|quasi| quasi.is_empty()) {
+            return;
+        }
+
+        let quasis = std::mem::replace(&mut self.quasis, vec![String::new()]);
+        let exprs = std::mem::take(&mut self.exprs)
+            .into_iter()
+            .map(Box::new)
+            .collect();
+        let last_idx = quasis.len() - 1;
+
+        let quasis = quasis
+            .into_iter()
+            .enumerate()
+            .map(|(idx, quasi)| TplElement {
+                span: DUMMY_SP,
+                tail: idx == last_idx,
+                raw: FervidAtom::from(escape_template_literal(&quasi)),
+                cooked: Some(FervidAtom::from(quasi)),
+            })
+            .collect();
+
+        self.stmts.push(push_stmt(Expr::Tpl(Tpl {
+            span: DUMMY_SP,
+            exprs,
+            quasis,
+        })));
+    }
+}
+
+/// Whether only one of the `nodes` can get the fallthrough attributes.
+/// Comments and whitespace-only text are rendered next to the root, but they are not roots
+pub(crate) fn has_single_root(nodes: &[Node]) -> bool {
+    nodes
+        .iter()
+        .filter(|node| match node {
+            Node::Comment(_, _) => false,
+            Node::Text(text, _) => !text.trim().is_empty(),
+            _ => true,
+        })
+        .count()
+        == 1
+}
+
+impl CodegenContext {
+    /// Generates the SSR render function as an arrow, similar to `compiler-ssr` of the official compiler.
+    ///
+    /// `(_ctx, _push, _parent, _attrs) => { _push(`<div>${_ssrInterpolate(_ctx.msg)}</div>`) }`
+    pub fn generate_ssr_render(&mut self, sfc_template: &SfcTemplateBlock) -> Option<Expr> {
+        // Multiple roots are merged into a `<template>` by the transformer,
+        // only its children are rendered
+        let roots = match sfc_template.roots.as_slice() {
+            [Node::Element(root)]
+                if root.starting_tag.tag_name == "template"
+                    && root.starting_tag.directives.is_none() =>
+            {
+                &root.children
+            }
+            roots => roots,
+        };
+
+        // Several roots are hydrated as a Fragment, which needs the markers:
+        // `<!--[--><div></div><p></p><!--]-->`. Only text and interpolations are not a Fragment
+        let is_fragment = roots.len() > 1
+            && roots
+                .iter()
+                .any(|root| !matches!(root, Node::Text(_, _) | Node::Interpolation(_)));
+
+        // The attributes falling through to the component, e.g. `class`,
+        // are merged into the only root. A Fragment has no element to render them on
+        self.ssr_root_attrs = has_single_root(roots);
+
+        let mut buffer = SsrBuffer::new();
+        if is_fragment {
+            buffer.push_str("<!--[-->");
+        }
+        self.generate_ssr_children(roots, &mut buffer);
+        if is_fragment {
+            buffer.push_str("<!--]-->");
+        }
+        self.ssr_root_attrs = false;
+        let body_stmts = buffer.finish();
+
+        // Resolves are known only after the body was generated
+        let mut stmts = Vec::with_capacity(body_stmts.len() + 1);
+        let mut resolves = self.generate_component_resolves();
+        resolves.extend(self.generate_directive_resolves());
+        if !resolves.is_empty() {
+            stmts.push(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                kind: VarDeclKind::Const,
+                declare: false,
+                decls: resolves,
+            }))));
+        }
+        stmts.extend(body_stmts);
+
+        Some(Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            params: ["_ctx", "_push", "_parent", "_attrs"]
+                .into_iter()
+                .map(param)
+                .collect(),
+            body: Box::new(BlockStmtOrExpr::BlockStmt(block(stmts))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        }))
+    }
+
+    /// Converts the SSR render arrow to a Function to be used in the exported object.
+    ///
+    /// `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) { /*...*/ }`
+    pub fn generate_ssr_render_fn(&mut self, ssr_render: Expr) -> Function {
+        let Expr::Arrow(ArrowExpr { params, body, .. }) = ssr_render else {
+            unreachable!("SSR render must be generated by `generate_ssr_render`")
+        };
+        let BlockStmtOrExpr::BlockStmt(body) = *body else {
+            unreachable!("SSR render must have a block body")
+        };
+
+        let params = params
+            .into_iter()
+            .chain(
+                ["$props", "$setup", "$data", "$options"]
+                    .into_iter()
+                    .map(param),
+            )
+            .map(|pat| Param {
+                span: DUMMY_SP,
+                decorators: vec![],
+                pat,
+            })
+            .collect();
+
+        Function {
+            params,
+            decorators: vec![],
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            body: Some(body),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }
+    }
+
+    pub(crate) fn generate_ssr_children(&mut self, children: &[Node], buffer: &mut SsrBuffer) {
+        for child in children.iter() {
+            self.generate_ssr_node(child, buffer);
+        }
+    }
+
+    pub(crate) fn generate_ssr_node(&mut self, node: &Node, buffer: &mut SsrBuffer) {
+        ensure_sufficient_stack(|| self.generate_ssr_node_impl(node, buffer))
+    }
+
+    fn generate_ssr_node_impl(&mut self, node: &Node, buffer: &mut SsrBuffer) {
+        match node {
+            // Entities are already decoded by the parser and must be encoded again
+            Node::Text(contents, _) => buffer.push_str(&elements::escape_text(
+                &crate::text::shorten_text(contents, self.bindings_helper.whitespace),
+            )),
+
+            Node::Interpolation(interpolation) => {
+                let interpolate = self.generate_ssr_call(
+                    SsrImports::SsrInterpolate,
+                    vec![(*interpolation.value).to_owned()],
+                );
+                buffer.push_expr(interpolate);
+            }
+
+            Node::Comment(comment, _) => {
+                buffer.push_str("<!--");
+                buffer.push_str(comment);
+                buffer.push_str("-->");
+            }
+
+            Node::Element(element_node) => {
+                self.generate_ssr_element_or_component(element_node, buffer)
+            }
+
+            Node::ConditionalSeq(conditional_seq) => {
+                self.generate_ssr_conditional_seq(conditional_seq, buffer)
+            }
+        }
+    }
+
+    /// Generates the HTML element, component or a Vue built-in, as well as its `v-for`
+    fn generate_ssr_element_or_component(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let v_for = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_for.as_ref());
+
+        // `v-for` renders a fragment
+        if let Some(v_for) = v_for {
+            self.ssr_root_attrs = false;
+
+            let mut item_buffer = SsrBuffer::new();
+            self.generate_ssr_element_kind(element_node, &mut item_buffer);
+            let render_list = self.generate_ssr_render_list(v_for, item_buffer.finish());
+
+            buffer.push_str("<!--[-->");
+            buffer.push_stmt(render_list);
+            buffer.push_str("<!--]-->");
+        } else {
+            self.generate_ssr_element_kind(element_node, buffer);
+        }
+    }
+
+    fn generate_ssr_element_kind(&mut self, element_node: &ElementNode, buffer: &mut SsrBuffer) {
+        // Only the direct options of a `<select v-model>` see its model, not the slots of components
+        let select_model = self.ssr_select_model.take();
+
+        match element_node.kind {
+            // E.g. `<Teleport>` or `<slot>` have no attributes of their own to merge with
+            ElementKind::Builtin(builtin_type) => {
+                self.ssr_root_attrs = false;
+                self.generate_ssr_builtin(element_node, builtin_type, buffer)
+            }
+            ElementKind::Element => {
+                self.generate_ssr_element(element_node, select_model.as_ref(), buffer)
+            }
+            ElementKind::Component => self.generate_ssr_component(element_node, buffer),
+        }
+
+        self.ssr_select_model = select_model;
+    }
+
+    /// Generates `if (cond) { /*...*/ } else if (cond2) { /*...*/ } else { _push(`<!---->`) }`
+    fn generate_ssr_conditional_seq(
+        &mut self,
+        conditional_seq: &ConditionalNodeSequence,
+        buffer: &mut SsrBuffer,
+    ) {
+        // Each branch of the root `v-if` is the root
+        let is_root = self.ssr_root_attrs;
+
+        let mut branches = Vec::with_capacity(1 + conditional_seq.else_if_nodes.len());
+        for conditional in std::iter::once(conditional_seq.if_node.as_ref())
+            .chain(conditional_seq.else_if_nodes.iter())
+        {
+            self.ssr_root_attrs = is_root;
+            let mut branch_buffer = SsrBuffer::new();
+            self.generate_ssr_element_or_component(&conditional.node, &mut branch_buffer);
+            branches.push((conditional.condition.to_owned(), branch_buffer.finish()));
+        }
+
+        // Without `v-else`, an empty comment is rendered as a placeholder
+        let mut else_buffer = SsrBuffer::new();
+        self.ssr_root_attrs = is_root;
+        match conditional_seq.else_node {
+            Some(ref else_node) => {
+                self.generate_ssr_element_or_component(else_node, &mut else_buffer)
+            }
+            None => else_buffer.push_str("<!---->"),
+        }
+        self.ssr_root_attrs = false;
+
+        // Fold the branches starting from the last one
+        let mut alternate = Stmt::Block(block(else_buffer.finish()));
+        for (condition, stmts) in branches.into_iter().rev() {
+            alternate = Stmt::If(IfStmt {
+                span: DUMMY_SP,
+                test: Box::new(condition),
+                cons: Box::new(Stmt::Block(block(stmts))),
+                alt: Some(Box::new(alternate)),
+            });
+        }
+
+        buffer.push_stmt(alternate);
+    }
+
+    /// Generates `_ssrRenderList(<list>, (<item>) => { /*...*/ })`
+    fn generate_ssr_render_list(&mut self, v_for: &VForDirective, item_stmts: Vec<Stmt>) -> Stmt {
+        let render_item_arrow = Expr::Arrow(ArrowExpr {
+            span: v_for.span,
+            ctxt: Default::default(),
+            params: vec![Pat::Expr(v_for.itervar.to_owned())],
+            body: Box::new(BlockStmtOrExpr::BlockStmt(block(item_stmts))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        let render_list = self.generate_ssr_call(
+            SsrImports::SsrRenderList,
+            vec![(*v_for.iterable).to_owned(), render_item_arrow],
+        );
+
+        Stmt::Expr(ExprStmt {
+            span: v_for.span,
+            expr: Box::new(render_list),
+        })
+    }
+
+    /// Generates a call of an SSR helper, e.g. `_ssrInterpolate(_ctx.msg)`
+    pub(crate) fn generate_ssr_call(&mut self, ssr_import: SsrImports, args: Vec<Expr>) -> Expr {
+        let callee = self.get_and_add_ssr_import_ident(ssr_import);
+        call(callee, args)
+    }
+}
+
+/// Generates `callee(args)`
+pub(crate) fn call(callee: FervidAtom, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        callee: Callee::Expr(Box::new(Expr::Ident(callee.into_ident()))),
+        args: args
+            .into_iter()
+            .map(|arg| ExprOrSpread {
+                spread: None,
+                expr: Box::new(arg),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+/// Identifier of the SSR render function parameter, e.g. `_push`
+pub(crate) fn ident(sym: &str) -> Expr {
+    Expr::Ident(FervidAtom::from(sym).into_ident())
+}
+
+pub(crate) fn param(sym: &str) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: FervidAtom::from(sym).into_ident(),
+        type_ann: None,
+    })
+}
+
+pub(crate) fn block(stmts: Vec<Stmt>) -> BlockStmt {
+    BlockStmt {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        stmts,
+    }
+}
+
+/// Generates `_push(expr)`
+fn push_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(call(fervid_atom!("_push"), vec![expr])),
+    })
+}
+
+/// Escapes the raw value of a template literal quasi
+fn escape_template_literal(quasi: &str) -> String {
+    quasi
+        .replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        AttributeOrBinding, BuiltinType, Conditional, ElementKind, Interpolation, StartingTag,
+        VCustomDirective, VModelDirective, VueDirectives,
+    };
+
+    use super::*;
+    use crate::test_utils::{js, regular_attribute, to_str, v_bind_attribute};
+
+    #[test]
+    fn it_generates_element_with_attributes() {
+        // <section><div class="a" :class="b" :id="c" disabled :checked="d" @click="e">{{ msg }}</div></section>
+        test_out(
+            vec![element(
+                "section",
+                vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "div".into(),
+                        attributes: vec![
+                            regular_attribute("class", "a"),
+                            v_bind_attribute("class", "b"),
+                            v_bind_attribute("id", "c"),
+                            regular_attribute("disabled", ""),
+                            v_bind_attribute("checked", "d"),
+                            crate::test_utils::v_on_attribute("click", "e"),
+                        ],
+                        directives: None,
+                    },
+                    children: vec![interpolation("msg")],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<section${_ssrRenderAttrs(_attrs)}><div${_ssrRenderAttr("id",c)} disabled${_ssrIncludeBooleanAttr(d)?" checked":""} class="${_ssrRenderClass(["a",b])}">${_ssrInterpolate(msg)}</div></section>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_void_element_and_v_bind_object() {
+        // <input v-bind="attrs" v-show="visible"><br>
+        test_out(
+            vec![
+                Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "input".into(),
+                        attributes: vec![AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                            argument: None,
+                            value: js("attrs"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: false,
+                            span: DUMMY_SP,
+                        })],
+                        directives: Some(Box::new(VueDirectives {
+                            v_show: Some(js("visible")),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                }),
+                element("br", vec![]),
+            ],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<!--[--><input${_ssrRenderAttrs(_mergeProps(_normalizeProps(_guardReactiveProps(attrs)),{style:visible?null:{display:"none"}}))}><br><!--]-->`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_custom_directives() {
+        // <div class="a" v-focus v-tooltip:top.once="msg"></div>
+        test_out(
+            vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![regular_attribute("class", "a")],
+                    directives: Some(Box::new(VueDirectives {
+                        custom: vec![
+                            VCustomDirective {
+                                name: "focus".into(),
+                                ..Default::default()
+                            },
+                            VCustomDirective {
+                                name: "tooltip".into(),
+                                argument: Some(fervid_core::StrOrExpr::Str("top".into())),
+                                modifiers: vec!["once".into()],
+                                value: Some(js("msg")),
+                            },
+                        ],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            r#"(_ctx,_push,_parent,_attrs)=>{const _directive_focus=_resolveDirective("focus"),_directive_tooltip=_resolveDirective("tooltip");_push(`<div${_ssrRenderAttrs(_mergeProps({class:"a"},_ssrGetDirectiveProps(_ctx,_directive_focus),_ssrGetDirectiveProps(_ctx,_directive_tooltip,msg,"top",{once:true}),_attrs))}></div>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_mathml_elements() {
+        // <math display="block"><mfrac><mi>{{ x }}</mi><mn>2</mn></mfrac><mspace /></math>
+        test_out(
+            vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "math".into(),
+                    attributes: vec![regular_attribute("display", "block")],
+                    directives: None,
+                },
+                children: vec![
+                    element(
+                        "mfrac",
+                        vec![
+                            element("mi", vec![interpolation("x")]),
+                            element("mn", vec![Node::Text("2".into(), DUMMY_SP)]),
+                        ],
+                    ),
+                    element("mspace", vec![]),
+                ],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<math${_ssrRenderAttrs(_mergeProps({display:"block"},_attrs))}><mfrac><mi>${_ssrInterpolate(x)}</mi><mn>2</mn></mfrac><mspace></mspace></math>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_conditional_seq_and_v_for() {
+        // <p v-if="a">a</p><li v-for="i in list">{{ i }}</li>
+        test_out(
+            vec![
+                Node::ConditionalSeq(ConditionalNodeSequence {
+                    if_node: Box::new(Conditional {
+                        condition: *js("a"),
+                        node: ElementNode {
+                            kind: ElementKind::Element,
+                            starting_tag: StartingTag {
+                                tag_name: "p".into(),
+                                attributes: vec![],
+                                directives: None,
+                            },
+                            children: vec![Node::Text("a".into(), DUMMY_SP)],
+                            template_scope: 0,
+                            patch_hints: Default::default(),
+                            span: DUMMY_SP,
+                        },
+                    }),
+                    else_if_nodes: vec![],
+                    else_node: None,
+                }),
+                Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "li".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_for: Some(fervid_core::VForDirective {
+                                iterable: js("list"),
+                                itervar: js("i"),
+                                patch_flags: Default::default(),
+                                span: DUMMY_SP,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![interpolation("i")],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                }),
+            ],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<!--[-->`);if(a){_push(`<p>a</p>`);}else{_push(`<!---->`);}_push(`<!--[-->`);_ssrRenderList(list,(i)=>{_push(`<li>${_ssrInterpolate(i)}</li>`);});_push(`<!--]--><!--]-->`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_fragment_markers_for_multiple_roots() {
+        // <div></div><p>{{ msg }}</p>
+        test_out(
+            vec![
+                element("div", vec![]),
+                element("p", vec![interpolation("msg")]),
+            ],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<!--[--><div></div><p>${_ssrInterpolate(msg)}</p><!--]-->`);}"#,
+        );
+
+        // Only text: hi {{ msg }}
+        test_out(
+            vec![Node::Text("hi ".into(), DUMMY_SP), interpolation("msg")],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`hi ${_ssrInterpolate(msg)}`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_merges_attrs_into_root() {
+        // <p v-if="a">a</p><div v-else></div>
+        let Node::Element(if_node) = element("p", vec![Node::Text("a".into(), DUMMY_SP)]) else {
+            unreachable!()
+        };
+        let Node::Element(else_node) = element("div", vec![]) else {
+            unreachable!()
+        };
+        test_out(
+            vec![Node::ConditionalSeq(ConditionalNodeSequence {
+                if_node: Box::new(Conditional {
+                    condition: *js("a"),
+                    node: if_node,
+                }),
+                else_if_nodes: vec![],
+                else_node: Some(Box::new(else_node)),
+            })],
+            r#"(_ctx,_push,_parent,_attrs)=>{if(a){_push(`<p${_ssrRenderAttrs(_attrs)}>a</p>`);}else{_push(`<div${_ssrRenderAttrs(_attrs)}></div>`);}}"#,
+        );
+
+        // Only the root gets `_attrs`: <div><p></p></div>
+        test_out(
+            vec![element("div", vec![element("p", vec![])])],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_attrs)}><p></p></div>`);}"#,
+        );
+
+        // Comments and whitespace are not roots: <!-- c --> <div></div>
+        test_out(
+            vec![
+                Node::Comment(" c ".into(), DUMMY_SP),
+                Node::Text(" ".into(), DUMMY_SP),
+                element("div", vec![]),
+            ],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<!--[--><!-- c --> <div${_ssrRenderAttrs(_attrs)}></div><!--]-->`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_component_with_slots() {
+        // <my-comp :foo="bar">hi {{ msg }}</my-comp>
+        test_out(
+            vec![Node::Element(ElementNode {
+                kind: ElementKind::Component,
+                starting_tag: StartingTag {
+                    tag_name: "my-comp".into(),
+                    attributes: vec![v_bind_attribute("foo", "bar")],
+                    directives: None,
+                },
+                children: vec![Node::Text("hi ".into(), DUMMY_SP), interpolation("msg")],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            r#"(_ctx,_push,_parent,_attrs)=>{const _component_my_comp=_resolveComponent("my-comp");_push(_ssrRenderComponent(_component_my_comp,_mergeProps({foo:bar},_attrs),{default:_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`hi ${_ssrInterpolate(msg)}`);}else{return[_createTextVNode("hi "+_toDisplayString(msg),1)];}})},_parent));}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_slot_and_teleport() {
+        // <Teleport to="body"><slot name="foo" :a="b">fallback</slot></Teleport>
+        test_out(
+            vec![Node::Element(ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Teleport),
+                starting_tag: StartingTag {
+                    tag_name: "Teleport".into(),
+                    attributes: vec![regular_attribute("to", "body")],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Builtin(BuiltinType::Slot),
+                    starting_tag: StartingTag {
+                        tag_name: "slot".into(),
+                        attributes: vec![
+                            regular_attribute("name", "foo"),
+                            v_bind_attribute("a", "b"),
+                        ],
+                        directives: None,
+                    },
+                    children: vec![Node::Text("fallback".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            r#"(_ctx,_push,_parent,_attrs)=>{_ssrRenderTeleport(_push,_push=>{_ssrRenderSlot(_ctx.$slots,"foo",{a:b},()=>{_push(`fallback`);},_push,_parent);},"body",false,_parent);}"#,
+        );
+    }
+
+    #[test]
+    fn it_escapes_template_literals() {
+        // <pre>`${a}\</pre>
+        test_out(
+            vec![element("pre", vec![Node::Text("`${a}\\".into(), DUMMY_SP)])],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<pre${_ssrRenderAttrs(_attrs)}>\`\${a}\\</pre>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_escapes_text() {
+        // <p>&lt;script&gt;alert(1)&lt;/script&gt; 5 &gt; 3 &amp;</p>
+        test_out(
+            vec![element(
+                "p",
+                vec![Node::Text(
+                    "<script>alert(1)</script> 5 > 3 &".into(),
+                    DUMMY_SP,
+                )],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<p${_ssrRenderAttrs(_attrs)}>&lt;script&gt;alert(1)&lt;/script&gt; 5 &gt; 3 &amp;</p>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_v_model_of_checkbox() {
+        // <input type="checkbox" v-model="arr" value="x">
+        test_out(
+            vec![element_with_model(
+                "input",
+                vec![
+                    regular_attribute("type", "checkbox"),
+                    regular_attribute("value", "x"),
+                ],
+                "arr",
+                vec![],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<input${_ssrRenderAttrs(_mergeProps({type:"checkbox",value:"x"},{checked:Array.isArray(arr)?_ssrLooseContain(arr,"x"):arr},_attrs))}>`);}"#,
+        );
+
+        // <div><input type="checkbox" v-model="arr" value="x"></div>
+        test_out(
+            vec![element(
+                "div",
+                vec![element_with_model(
+                    "input",
+                    vec![
+                        regular_attribute("type", "checkbox"),
+                        regular_attribute("value", "x"),
+                    ],
+                    "arr",
+                    vec![],
+                )],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_attrs)}><input type="checkbox" value="x"${_ssrIncludeBooleanAttr(Array.isArray(arr)?_ssrLooseContain(arr,"x"):arr)?" checked":""}></div>`);}"#,
+        );
+
+        // <div><input type="checkbox" v-model="flag" true-value="yes"></div>
+        test_out(
+            vec![element(
+                "div",
+                vec![element_with_model(
+                    "input",
+                    vec![
+                        regular_attribute("type", "checkbox"),
+                        regular_attribute("true-value", "yes"),
+                    ],
+                    "flag",
+                    vec![],
+                )],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_attrs)}><input type="checkbox" true-value="yes"${_ssrIncludeBooleanAttr(_ssrLooseEqual(flag,"yes"))?" checked":""}></div>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_v_model_of_radio_and_dynamic_type() {
+        // <div><input type="radio" v-model="picked" :value="a"><input :type="t" v-model="m"><input v-model="text"></div>
+        test_out(
+            vec![element(
+                "div",
+                vec![
+                    element_with_model(
+                        "input",
+                        vec![
+                            regular_attribute("type", "radio"),
+                            v_bind_attribute("value", "a"),
+                        ],
+                        "picked",
+                        vec![],
+                    ),
+                    element_with_model("input", vec![v_bind_attribute("type", "t")], "m", vec![]),
+                    element_with_model("input", vec![], "text", vec![]),
+                ],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_attrs)}><input type="radio"${_ssrRenderAttr("value",a)}${_ssrIncludeBooleanAttr(_ssrLooseEqual(picked,a))?" checked":""}><input${_ssrRenderAttr("type",t)}${_ssrRenderDynamicModel(t,m,null)}><input${_ssrRenderAttr("value",text)}></div>`);}"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_v_model_of_select() {
+        // <div><select v-model="sel"><option value="a">A</option><optgroup><option :value="b">B</option></optgroup><option selected>C</option></select></div>
+        test_out(
+            vec![element(
+                "div",
+                vec![element_with_model(
+                    "select",
+                    vec![],
+                    "sel",
+                    vec![
+                        element_with_attrs(
+                            "option",
+                            vec![regular_attribute("value", "a")],
+                            vec![Node::Text("A".into(), DUMMY_SP)],
+                        ),
+                        element(
+                            "optgroup",
+                            vec![element_with_attrs(
+                                "option",
+                                vec![v_bind_attribute("value", "b")],
+                                vec![Node::Text("B".into(), DUMMY_SP)],
+                            )],
+                        ),
+                        element_with_attrs(
+                            "option",
+                            vec![regular_attribute("selected", "")],
+                            vec![Node::Text("C".into(), DUMMY_SP)],
+                        ),
+                    ],
+                )],
+            )],
+            r#"(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_attrs)}><select><option value="a"${_ssrIncludeBooleanAttr(Array.isArray(sel)?_ssrLooseContain(sel,"a"):_ssrLooseEqual(sel,"a"))?" selected":""}>A</option><optgroup><option${_ssrRenderAttr("value",b)}${_ssrIncludeBooleanAttr(Array.isArray(sel)?_ssrLooseContain(sel,b):_ssrLooseEqual(sel,b))?" selected":""}>B</option></optgroup><option selected>C</option></select></div>`);}"#,
+        );
+    }
+
+    fn element_with_attrs(
+        tag_name: &str,
+        attributes: Vec<AttributeOrBinding>,
+        children: Vec<Node>,
+    ) -> Node {
+        Node::Element(ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn element_with_model(
+        tag_name: &str,
+        attributes: Vec<AttributeOrBinding>,
+        model: &str,
+        children: Vec<Node>,
+    ) -> Node {
+        Node::Element(ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives: Some(Box::new(VueDirectives {
+                    v_model: vec![VModelDirective {
+                        argument: None,
+                        value: js(model),
+                        update_handler: None,
+                        modifiers: vec![],
+                        span: DUMMY_SP,
+                    }],
+                    ..Default::default()
+                })),
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn element(tag_name: &str, children: Vec<Node>) -> Node {
+        Node::Element(ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn interpolation(value: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: js(value),
+            template_scope: 0,
+            patch_flag: true,
+            span: DUMMY_SP,
+        })
+    }
+
+    fn test_out(roots: Vec<Node>, expected: &str) {
+        let mut ctx = CodegenContext::default();
+        let sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots,
+            span: DUMMY_SP,
+        };
+        let ssr_render = ctx
+            .generate_ssr_render(&sfc_template)
+            .expect("Template is not empty");
+        assert_eq!(to_str(ssr_render), expected);
+    }
+}
@@ -38,6 +38,7 @@ pub fn regular_attribute(name: &str, value: &str) -> AttributeOrBinding {
     AttributeOrBinding::RegularAttribute {
         name: name.into(),
         value: value.into(),
+        raw_value: None,
         span: DUMMY_SP,
     }
 }
@@ -62,6 +63,7 @@ pub fn v_on_attribute(name: &str, value: &str) -> AttributeOrBinding {
         event: Some(name.into()),
         handler: Some(js(value)),
         modifiers: vec![],
+        is_cacheable: false,
         span: DUMMY_SP,
     })
 }
@@ -1,4 +1,4 @@
-use fervid_core::FervidAtom;
+use fervid_core::{FervidAtom, WhitespaceMode};
 use swc_core::{
     common::Span,
     ecma::ast::{Expr, Lit, Str},
@@ -8,34 +8,41 @@ use crate::context::CodegenContext;
 
 impl CodegenContext {
     pub fn generate_text_node(&mut self, contents: &str, span: Span) -> Expr {
-        let has_start_whitespace = contents.starts_with(char::is_whitespace);
-        let has_end_whitespace = contents.ends_with(char::is_whitespace);
-        let needs_shortening = has_start_whitespace || has_end_whitespace;
-
-        let value = if needs_shortening {
-            let trimmed = contents.trim();
-            let new_len =
-                trimmed.len() + (has_start_whitespace as usize) + (has_end_whitespace as usize);
-
-            // Re-create a string with all start and end whitespace replaced by a single space
-            let mut shortened = String::with_capacity(new_len);
-            if has_start_whitespace {
-                shortened.push(' ');
-            }
-            shortened.push_str(trimmed);
-            if has_end_whitespace && trimmed.len() > 0 {
-                shortened.push(' ');
-            }
-
-            FervidAtom::from(shortened)
-        } else {
-            FervidAtom::from(contents)
-        };
-
         Expr::Lit(Lit::Str(Str {
             span,
-            value,
+            value: shorten_text(contents, self.bindings_helper.whitespace),
             raw: None,
         }))
     }
 }
+
+/// Replaces all the start and end whitespace of a text node by a single space.
+/// The text is kept as-is when the whitespace is preserved.
+pub(crate) fn shorten_text(contents: &str, whitespace: WhitespaceMode) -> FervidAtom {
+    if matches!(whitespace, WhitespaceMode::Preserve) {
+        return FervidAtom::from(contents);
+    }
+
+    let has_start_whitespace = contents.starts_with(char::is_whitespace);
+    let has_end_whitespace = contents.ends_with(char::is_whitespace);
+    let needs_shortening = has_start_whitespace || has_end_whitespace;
+
+    if !needs_shortening {
+        return FervidAtom::from(contents);
+    }
+
+    let trimmed = contents.trim();
+    let new_len = trimmed.len() + (has_start_whitespace as usize) + (has_end_whitespace as usize);
+
+    // Re-create a string with all start and end whitespace replaced by a single space
+    let mut shortened = String::with_capacity(new_len);
+    if has_start_whitespace {
+        shortened.push(' ');
+    }
+    shortened.push_str(trimmed);
+    if has_end_whitespace && !trimmed.is_empty() {
+        shortened.push(' ');
+    }
+
+    FervidAtom::from(shortened)
+}
@@ -21,3 +21,25 @@ pub fn to_camelcase(s: &str, buf: &mut impl Write) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Creates a valid JS identifier for a runtime-resolved asset,
+/// e.g. `_directive_foo_bar` for `foo-bar` or `_component_foo46bar` for `foo.bar`.
+///
+/// Adapted from `toValidAssetId` of the official compiler:
+/// `-` is replaced with `_` and other non-word characters with their char codes.
+pub fn to_valid_asset_id(name: &str, prefix: &str) -> String {
+    let mut result = String::with_capacity(prefix.len() + name.len());
+    result.push_str(prefix);
+
+    for ch in name.chars() {
+        match ch {
+            '-' => result.push('_'),
+            ch if ch.is_ascii_alphanumeric() || ch == '_' => result.push(ch),
+            ch => {
+                let _ = write!(result, "{}", ch as u32);
+            }
+        }
+    }
+
+    result
+}
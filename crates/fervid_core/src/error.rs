@@ -1,3 +1,11 @@
+/// Stable identifier of an error, e.g. for the tooling to link to the docs or to suppress the error.
+///
+/// The identifiers are prefixed with the compilation phase which reports them,
+/// e.g. `parse/duplicate-template` or `template/v-else-without-v-if`.
+pub trait ErrorCode {
+    fn code(&self) -> &'static str;
+}
+
 pub trait Severity {
     fn get_severity(&self) -> SeverityLevel;
 
@@ -26,7 +34,7 @@ pub trait Severity {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(unused)]
 pub enum SeverityLevel {
     UnrecoverableError,
@@ -1,7 +1,9 @@
 mod all_html_tags;
+mod all_mathml_tags;
 mod bindings;
 pub mod error;
 mod sfc;
+mod stack;
 mod structs;
 mod template;
 mod vue_builtins;
@@ -9,10 +11,12 @@ mod vue_imports;
 mod utils;
 
 pub use all_html_tags::is_html_tag;
+pub use all_mathml_tags::is_mathml_tag;
 pub use bindings::*;
 pub use sfc::*;
+pub use stack::{ensure_stack_for_depth, ensure_sufficient_stack, max_nesting_depth};
 pub use structs::*;
 pub use template::is_from_default_slot;
-pub use vue_builtins::VUE_BUILTINS;
-pub use vue_imports::{VueImports, VueImportsSet};
+pub use vue_builtins::{get_vue_builtin, VUE_BUILTINS};
+pub use vue_imports::{SsrImports, SsrImportsSet, VueImports, VueImportsSet};
 pub use utils::*;
@@ -31,7 +31,8 @@ pub struct SfcStyleBlock {
     pub lang: FervidAtom,
     pub content: FervidAtom,
     pub is_scoped: bool,
-    pub is_module: bool,
+    /// Name of the CSS Module, e.g. `$style` for `<style module>` or `classes` for `<style module="classes">`
+    pub module: Option<FervidAtom>,
     pub span: Span,
 }
 
@@ -42,8 +43,24 @@ pub struct SfcCustomBlock {
     pub span: Span,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SfcScriptLang {
     Es,
     Typescript,
+    /// `lang="jsx"`
+    Jsx,
+    /// `lang="tsx"`
+    Tsx,
+}
+
+impl SfcScriptLang {
+    /// Whether the block is `ts` or `tsx`
+    pub fn is_ts(&self) -> bool {
+        matches!(self, SfcScriptLang::Typescript | SfcScriptLang::Tsx)
+    }
+
+    /// Whether the block is `jsx` or `tsx`
+    pub fn is_jsx(&self) -> bool {
+        matches!(self, SfcScriptLang::Jsx | SfcScriptLang::Tsx)
+    }
 }
@@ -0,0 +1,62 @@
+//! Stack growth for the recursion on the nested template elements.
+//!
+//! The parser and the codegen recurse on the nested elements, each level taking several kilobytes
+//! of the stack. Deep templates would overflow the stack of the caller, e.g. the 2 MiB of a spawned
+//! thread, so the recursion continues on a new stack segment when the current one runs low.
+//! Shallow templates never reach the red zone and run on the stack of the caller.
+
+/// When less than this is left on the stack, a new segment is allocated
+const RED_ZONE: usize = 100 * 1024;
+
+/// Size of a new stack segment, enough for a few hundred nested elements
+const STACK_PER_RECURSION: usize = 1024 * 1024;
+
+/// Stack taken by one nesting level of a recursion which cannot be split into segments,
+/// e.g. `swc_html_parser` or `swc_ecma_codegen`. A compilation was measured
+/// at about 5 KiB per level in the debug builds and below 1 KiB in the release ones
+const STACK_PER_LEVEL: usize = if cfg!(debug_assertions) {
+    8 * 1024
+} else {
+    2 * 1024
+};
+
+/// Runs `f` on a new stack segment when the current stack is close to overflowing.
+/// Must be called at each level of a recursion which is as deep as the template
+#[inline]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "arm")))]
+pub fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, STACK_PER_RECURSION, f)
+}
+
+/// Runs `f` on a new stack segment when the current stack cannot fit a recursion `depth` levels deep.
+/// For the recursion outside of this codebase, where [`ensure_sufficient_stack`] cannot be called
+#[inline]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "arm")))]
+pub fn ensure_stack_for_depth<R>(depth: usize, f: impl FnOnce() -> R) -> R {
+    let needed = RED_ZONE.saturating_add(depth.saturating_mul(STACK_PER_LEVEL));
+    stacker::maybe_grow(needed, needed, f)
+}
+
+/// Upper bound of the element nesting in `markup`, i.e. the number of the start tags.
+/// Counting the end tags would be closer, but the text, e.g. in `<script>`, could then hide the depth
+pub fn max_nesting_depth(markup: &str) -> usize {
+    markup
+        .as_bytes()
+        .windows(2)
+        .filter(|pair| pair[0] == b'<' && pair[1].is_ascii_alphabetic())
+        .count()
+}
+
+/// The stack cannot be switched on these targets, `f` runs on the stack of the caller
+#[inline]
+#[cfg(any(target_arch = "wasm32", target_arch = "arm"))]
+pub fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// The stack cannot be switched on these targets, `f` runs on the stack of the caller
+#[inline]
+#[cfg(any(target_arch = "wasm32", target_arch = "arm"))]
+pub fn ensure_stack_for_depth<R>(_depth: usize, f: impl FnOnce() -> R) -> R {
+    f()
+}
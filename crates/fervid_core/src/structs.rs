@@ -158,6 +158,9 @@ pub enum AttributeOrBinding {
     RegularAttribute {
         name: FervidAtom,
         value: FervidAtom,
+        /// Value as written in the source, with its quotes and undecoded entities, e.g. `'a &amp; b'`.
+        /// `None` when the attribute has no value or does not come from the source.
+        raw_value: Option<FervidAtom>,
         span: Span,
     },
     /// `v-bind` directive
@@ -193,6 +196,28 @@ pub struct PatchHints {
     pub props: Vec<JsWord>,
     /// Whether the node codegen needs to be surrounded by `(openBlock(),`
     pub should_use_block: bool,
+    /// Whether the node and its children are fully static
+    /// and are generated once as a module-level `_hoisted_N` constant
+    pub hoisted: bool,
+    /// How constant the element itself is (without its children), see [`ConstantTypes`]
+    pub constant_type: ConstantTypes,
+}
+
+/// How constant an expression or an element is, from the least to the most constant,
+/// similar to `ConstantTypes` of the official compiler.
+/// The levels are ordered, so several of them combine into their minimum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstantTypes {
+    /// Depends on the state, e.g. `{{ count }}`, and needs to be patched
+    NotConstant,
+    /// Never changes, but references the component, e.g. a `<script setup>` const in the inline mode.
+    /// Needs no patching, but cannot be created outside of `render`
+    CanSkipPatch,
+    /// Can be created once at the module level, e.g. `:tabindex="-1"`
+    CanHoist,
+    /// Can also be rendered to an HTML string, e.g. `class="intro"`
+    #[default]
+    CanStringify,
 }
 
 flagset::flags! {
@@ -348,6 +373,9 @@ pub struct VOnDirective {
     pub handler: Option<Box<Expr>>,
     /// A list of modifiers after the dot, e.g. `stop` and `prevent` in `@click.stop.prevent="handleClick"`
     pub modifiers: Vec<FervidAtom>,
+    /// Whether the handler is stored in `_cache`, so that it keeps its identity between renders.
+    /// Set by the transformer when handlers caching is enabled and the handler does not use the template scope.
+    pub is_cacheable: bool,
     /// Byte location in source
     pub span: Span,
 }
@@ -463,3 +491,16 @@ pub enum TemplateGenerationMode {
     #[default]
     RenderFn,
 }
+
+/// Handling of the whitespace in the template (`whitespace` option of the official compiler)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WhitespaceMode {
+    /// Whitespace-only text nodes are removed when they are the first or the last child,
+    /// are next to a comment, or are between two elements and contain a newline.
+    /// Other whitespace, including the one inside the texts, is collapsed to a single space.
+    #[default]
+    Condense,
+    /// Only the first and the last whitespace-only children are removed,
+    /// other whitespace-only text nodes become a single space. Texts are kept as-is.
+    Preserve,
+}
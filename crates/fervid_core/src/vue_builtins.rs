@@ -3,6 +3,8 @@ use phf::phf_map;
 use crate::BuiltinType;
 
 pub static VUE_BUILTINS: phf::Map<&'static str, BuiltinType> = phf_map! {
+    "component" => BuiltinType::Component,
+    "Component" => BuiltinType::Component,
     "keep-alive" => BuiltinType::KeepAlive,
     "KeepAlive" => BuiltinType::KeepAlive,
     "slot" => BuiltinType::Slot,
@@ -16,3 +18,45 @@ pub static VUE_BUILTINS: phf::Map<&'static str, BuiltinType> = phf_map! {
     "transition-group" => BuiltinType::TransitionGroup,
     "TransitionGroup" => BuiltinType::TransitionGroup,
 };
+
+/// Finds a Vue built-in by its tag name.
+/// Kebab-case (`keep-alive`), PascalCase (`KeepAlive`) and camelCase (`keepAlive`) forms are recognized.
+pub fn get_vue_builtin(tag_name: &str) -> Option<BuiltinType> {
+    if let Some(builtin_type) = VUE_BUILTINS.get(tag_name) {
+        return Some(*builtin_type);
+    }
+
+    // Only the camelCase and the mixed kebab-case forms are not in the map,
+    // e.g. `keepAlive` or `Keep-alive`. Other tags without `-`, like `div`, are not built-ins
+    let is_camel_case = tag_name.starts_with(|c: char| c.is_ascii_lowercase())
+        && tag_name.bytes().any(|b| b.is_ascii_uppercase());
+    if !is_camel_case && !tag_name.contains('-') {
+        return None;
+    }
+
+    VUE_BUILTINS
+        .entries()
+        .find(|(name, _)| is_pascal_case_of(name, tag_name))
+        .map(|(_, builtin_type)| *builtin_type)
+}
+
+/// Whether `pascal_case` is the PascalCase form of `tag_name`,
+/// e.g. `KeepAlive` for `keepAlive` or `Keep-alive`. Compares without allocating
+fn is_pascal_case_of(pascal_case: &str, tag_name: &str) -> bool {
+    let mut expected = pascal_case.chars();
+    for segment in tag_name.split('-') {
+        let mut chars = segment.chars();
+        if let Some(first) = chars.next() {
+            if expected.next() != Some(first.to_ascii_uppercase()) {
+                return false;
+            }
+        }
+        for c in chars {
+            if expected.next() != Some(c) {
+                return false;
+            }
+        }
+    }
+
+    expected.next().is_none()
+}
@@ -13,6 +13,8 @@ flags! {
         CreateElementBlock,
         #[strum(serialize = "_createElementVNode")]
         CreateElementVNode,
+        #[strum(serialize = "_createStaticVNode")]
+        CreateStaticVNode,
         #[strum(serialize = "_createTextVNode")]
         CreateTextVNode,
         #[strum(serialize = "_createVNode")]
@@ -21,6 +23,10 @@ flags! {
         DefineComponent,
         #[strum(serialize = "_Fragment")]
         Fragment,
+        #[strum(serialize = "_guardReactiveProps")]
+        GuardReactiveProps,
+        #[strum(serialize = "_h")]
+        H,
         #[strum(serialize = "_isMemoSame")]
         IsMemoSame,
         #[strum(serialize = "_isRef")]
@@ -31,8 +37,12 @@ flags! {
         MergeDefaults,
         #[strum(serialize = "_mergeModels")]
         MergeModels,
+        #[strum(serialize = "_mergeProps")]
+        MergeProps,
         #[strum(serialize = "_normalizeClass")]
         NormalizeClass,
+        #[strum(serialize = "_normalizeProps")]
+        NormalizeProps,
         #[strum(serialize = "_normalizeStyle")]
         NormalizeStyle,
         #[strum(serialize = "_openBlock")]
@@ -61,6 +71,8 @@ flags! {
         TransitionGroup,
         #[strum(serialize = "_unref")]
         Unref,
+        #[strum(serialize = "_useCssVars")]
+        UseCssVars,
         #[strum(serialize = "_useModel")]
         UseModel,
         #[strum(serialize = "_useSlots")]
@@ -101,3 +113,60 @@ impl VueImports {
 }
 
 pub type VueImportsSet = FlagSet<VueImports>;
+
+flags! {
+    /// Helpers of the SSR render function, imported from `vue/server-renderer`
+    #[derive(AsRefStr, EnumString, IntoStaticStr)]
+    pub enum SsrImports: u32 {
+        #[strum(serialize = "_ssrGetDirectiveProps")]
+        SsrGetDirectiveProps,
+        #[strum(serialize = "_ssrGetDynamicModelProps")]
+        SsrGetDynamicModelProps,
+        #[strum(serialize = "_ssrIncludeBooleanAttr")]
+        SsrIncludeBooleanAttr,
+        #[strum(serialize = "_ssrInterpolate")]
+        SsrInterpolate,
+        #[strum(serialize = "_ssrLooseContain")]
+        SsrLooseContain,
+        #[strum(serialize = "_ssrLooseEqual")]
+        SsrLooseEqual,
+        #[strum(serialize = "_ssrRenderAttr")]
+        SsrRenderAttr,
+        #[strum(serialize = "_ssrRenderAttrs")]
+        SsrRenderAttrs,
+        #[strum(serialize = "_ssrRenderClass")]
+        SsrRenderClass,
+        #[strum(serialize = "_ssrRenderComponent")]
+        SsrRenderComponent,
+        #[strum(serialize = "_ssrRenderDynamicAttr")]
+        SsrRenderDynamicAttr,
+        #[strum(serialize = "_ssrRenderDynamicModel")]
+        SsrRenderDynamicModel,
+        #[strum(serialize = "_ssrRenderList")]
+        SsrRenderList,
+        #[strum(serialize = "_ssrRenderSlot")]
+        SsrRenderSlot,
+        #[strum(serialize = "_ssrRenderStyle")]
+        SsrRenderStyle,
+        #[strum(serialize = "_ssrRenderSuspense")]
+        SsrRenderSuspense,
+        #[strum(serialize = "_ssrRenderTeleport")]
+        SsrRenderTeleport,
+        #[strum(serialize = "_ssrRenderVNode")]
+        SsrRenderVNode,
+    }
+}
+
+impl SsrImports {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+
+    #[inline]
+    pub fn as_atom(self) -> FervidAtom {
+        self.as_str().into()
+    }
+}
+
+pub type SsrImportsSet = FlagSet<SsrImports>;
@@ -1,16 +1,22 @@
 mod codegen;
+mod deps;
 mod error;
+mod modules;
 mod parse;
 mod transform;
+mod vars;
 
-use fervid_core::error::Severity;
+use fervid_core::{error::Severity, FervidAtom};
 use swc_core::common::Span;
 use swc_css_parser::parser::ParserConfig;
 
 pub use codegen::{stringify, StringifyOptions};
-pub use error::CssError;
+pub use deps::parse_css_deps;
+pub use error::{CssError, CssErrorKind};
+pub use modules::CssModulesTransformer;
 pub use parse::parse_stylesheet;
 pub use transform::ScopedTransformer;
+pub use vars::{gen_css_var_name, parse_css_vars, rewrite_css_vars};
 
 #[derive(Default)]
 pub struct TransformCssConfig {
@@ -25,6 +31,48 @@ pub fn transform_css(
     scope: Option<&str>,
     errors: &mut Vec<CssError>,
     config: TransformCssConfig,
+) -> Option<String> {
+    transform_css_impl(content, span, scope, None, errors, config)
+}
+
+/// Transforms raw CSS of a CSS Module (`<style module>`), also handles the scopes.
+/// The classes are renamed using the `module_hash`, e.g. `.foo` becomes `.foo_abcd1234`.
+///
+/// Returns the CSS and the pairs of the original and the renamed class names.
+pub fn transform_css_module(
+    content: &str,
+    span: Span,
+    scope: Option<&str>,
+    module_hash: &str,
+    errors: &mut Vec<CssError>,
+    config: TransformCssConfig,
+) -> Option<(String, Vec<(FervidAtom, FervidAtom)>)> {
+    let mut modules_transformer = CssModulesTransformer::new(module_hash);
+    let transformed = transform_css_impl(
+        content,
+        span,
+        scope,
+        Some(&mut modules_transformer),
+        errors,
+        config,
+    )?;
+
+    Some((transformed, modules_transformer.take_classes()))
+}
+
+/// Checks whether the CSS uses `:slotted()` or `::v-slotted()`.
+/// Like in the official compiler, this is a text search and does not parse the CSS.
+pub fn has_slotted_selectors(content: &str) -> bool {
+    content.contains(":slotted(") || content.contains("::v-slotted(")
+}
+
+fn transform_css_impl(
+    content: &str,
+    span: Span,
+    scope: Option<&str>,
+    modules_transformer: Option<&mut CssModulesTransformer>,
+    errors: &mut Vec<CssError>,
+    config: TransformCssConfig,
 ) -> Option<String> {
     // Parse and collect errors
     let mut parse_errors = Vec::new();
@@ -48,5 +96,10 @@ pub fn transform_css(
         return None;
     }
 
+    // CSS Modules are renamed after scoping, so that the contents of `:deep()` are renamed as well
+    if let Some(modules_transformer) = modules_transformer {
+        modules_transformer.transform(&mut stylesheet);
+    }
+
     Some(stringify(&stylesheet, config.stringify))
 }
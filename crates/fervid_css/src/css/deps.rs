@@ -0,0 +1,227 @@
+//! Analysis of the style dependencies, e.g. `@import "./foo.css"` or `background: url(./bar.png)`
+
+const IMPORT: &str = "@import";
+const URL: &str = "url(";
+
+/// Languages where `//` starts a comment until the end of the line
+const LINE_COMMENT_LANGS: [&str; 5] = ["less", "sass", "scss", "styl", "stylus"];
+
+/// Collects the unique local files referenced from `@import` and `url()`.
+/// Works on the source text, therefore it supports preprocessor languages as well.
+///
+/// External URLs, `data:` URIs, fragments and variables (e.g. `url($foo)`) are skipped.
+/// Paths are reported as written, resolving them (including aliases like `@/`) is up to the consumer.
+/// `lang` is the one of `<style lang>`, e.g. `css` or `scss`.
+pub fn parse_css_deps(content: &str, lang: &str) -> Vec<String> {
+    let mut deps: Vec<String> = Vec::new();
+    let content = strip_comments(content, LINE_COMMENT_LANGS.contains(&lang));
+
+    // `@import "foo.css"` and `@import 'foo.css'`.
+    // `@import url(foo.css)` is covered by the `url()` search.
+    let mut search_start = 0;
+    while let Some(found_idx) = content[search_start..].find(IMPORT) {
+        search_start += found_idx + IMPORT.len();
+
+        if let Some(path) = read_quoted(content[search_start..].trim_start()) {
+            add_dep(&mut deps, path);
+        }
+    }
+
+    // `url(foo.png)`, `url('foo.png')` and `url("foo.png")`
+    search_start = 0;
+    while let Some(found_idx) = content[search_start..].find(URL) {
+        let start = search_start + found_idx + URL.len();
+        search_start = start;
+
+        // Ignore functions like `myurl(`
+        let is_part_of_ident = content[..start - URL.len()]
+            .chars()
+            .next_back()
+            .map_or(false, |c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if is_part_of_ident {
+            continue;
+        }
+
+        let rest = content[start..].trim_start();
+        let path = match read_quoted(rest) {
+            Some(path) => path,
+            None => match rest.find(')') {
+                Some(end) => rest[..end].trim_end(),
+                None => break,
+            },
+        };
+
+        add_dep(&mut deps, path);
+    }
+
+    deps
+}
+
+/// Removes `/* */` comments, and `//` comments when `has_line_comments`.
+/// Strings and unquoted `url()` are kept intact, e.g. `url(https://example.com/a.png)`
+fn strip_comments(content: &str, has_line_comments: bool) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(idx) = rest.find(['/', '\'', '"', 'u', 'U']) {
+        let (before, after) = rest.split_at(idx);
+        result.push_str(before);
+
+        // Length of the part which is kept as is
+        let kept_len = if after.starts_with("/*") {
+            rest = after.find("*/").map_or("", |end| &after[end + 2..]);
+            continue;
+        } else if has_line_comments && after.starts_with("//") {
+            rest = after.find('\n').map_or("", |end| &after[end..]);
+            continue;
+        } else if after.starts_with(['\'', '"']) {
+            string_len(after)
+        } else if after
+            .get(..URL.len())
+            .is_some_and(|it| it.eq_ignore_ascii_case(URL))
+        {
+            let args = &after[URL.len()..];
+            let args_start = args.len() - args.trim_start().len();
+            if args[args_start..].starts_with(['\'', '"']) {
+                URL.len()
+            } else {
+                // Unquoted `url()` ends with the first `)`
+                args.find(')')
+                    .map_or(after.len(), |end| URL.len() + end + 1)
+            }
+        } else {
+            1
+        };
+
+        result.push_str(&after[..kept_len]);
+        rest = &after[kept_len..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Length of the string at the start of `input`, including the quotes.
+/// Unterminated strings end at the end of the line
+fn string_len(input: &str) -> usize {
+    let quote = input.as_bytes()[0];
+    let mut is_escaped = false;
+    for (idx, byte) in input.bytes().enumerate().skip(1) {
+        match byte {
+            _ if is_escaped => is_escaped = false,
+            b'\\' => is_escaped = true,
+            b'\n' => return idx,
+            _ if byte == quote => return idx + 1,
+            _ => {}
+        }
+    }
+
+    input.len()
+}
+
+/// Reads the contents of a string starting with `'` or `"`
+fn read_quoted(input: &str) -> Option<&str> {
+    let quote = input.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let end = input[1..].find(quote)?;
+    Some(&input[1..end + 1])
+}
+
+fn add_dep(deps: &mut Vec<String>, path: &str) {
+    if is_local_dep(path) && !deps.iter().any(|dep| dep == path) {
+        deps.push(path.to_owned());
+    }
+}
+
+fn is_local_dep(path: &str) -> bool {
+    const SKIPPED_PREFIXES: [&str; 6] = ["data:", "http:", "https:", "//", "#", "$"];
+
+    // Less variables (`@foo`) are skipped, but aliases (`@/foo`) are kept
+    let is_less_variable = path.starts_with('@') && !path.starts_with("@/");
+
+    !path.is_empty()
+        && !is_less_variable
+        && !path.contains(['(', '{'])
+        && !SKIPPED_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_collects_imports() {
+        assert_eq!(
+            vec!["./foo.css", "bar.css", "./baz.css"],
+            parse_css_deps(
+                "@import './foo.css';\n@import \"bar.css\" screen;\n@import url(./baz.css);",
+                "css"
+            )
+        );
+    }
+
+    #[test]
+    fn it_collects_urls() {
+        assert_eq!(
+            vec!["./a.png", "../b.svg", "c.woff2"],
+            parse_css_deps(
+                ".foo { background: url(./a.png) } .bar { mask: url( '../b.svg' ) } @font-face { src: url(\"c.woff2\") format('woff2') }",
+                "css"
+            )
+        );
+    }
+
+    #[test]
+    fn it_skips_non_local_urls() {
+        assert!(parse_css_deps(
+            ".a { background: url(data:image/png;base64,AAAA) }
+            .b { background: url(https://example.com/a.png) }
+            .c { background: url(//cdn.example.com/a.png) }
+            .d { filter: url(#svg-filter) }
+            .e { background: url($image) }
+            .e2 { background: url(@image) }
+            .f { background: myurl(./g.png) }
+            @import 'https://fonts.example.com/font.css';",
+            "css"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn it_deduplicates_and_ignores_comments() {
+        assert_eq!(
+            vec!["./a.png"],
+            parse_css_deps(
+                "/* @import './commented.css'; */\n.a { background: url(./a.png) }\n.b { background: url(./a.png) }",
+                "css"
+            )
+        );
+    }
+
+    #[test]
+    fn it_keeps_double_slashes_in_css() {
+        assert_eq!(
+            vec!["./b.png"],
+            parse_css_deps(
+                ".a{background:url(https://example.com/a.png)}.b{background:url(./b.png)}",
+                "css"
+            )
+        );
+        assert_eq!(
+            vec!["./b.png"],
+            parse_css_deps(".a { content: '//'; background: url(./b.png) }", "css")
+        );
+    }
+
+    #[test]
+    fn it_ignores_line_comments_in_preprocessors() {
+        assert_eq!(
+            vec!["./b.png", "./c.png"],
+            parse_css_deps(
+                "// @import './commented.scss';\n.a { background: url(http://example.com/a.png) } // url(./d.png)\n.b { background: url(./b.png); content: '//' }\n.c { background: url(./c.png) }",
+                "scss"
+            )
+        );
+    }
+}
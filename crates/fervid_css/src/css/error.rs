@@ -1,5 +1,5 @@
-use fervid_core::error::{Severity, SeverityLevel};
-use swc_core::common::{Spanned, Span};
+use fervid_core::error::{ErrorCode, Severity, SeverityLevel};
+use swc_core::common::{Spanned, Span, DUMMY_SP};
 use swc_css_parser::error::{ErrorKind as ParseErrorKind, Error as ParseError};
 
 #[derive(Debug)]
@@ -14,6 +14,8 @@ pub enum CssErrorKind {
     ParseUnrecoverable(ParseErrorKind),
     ParseDeepRecoverable(ParseErrorKind),
     ParseDeepUnrecoverable(ParseErrorKind),
+    /// The expression inside `v-bind()` is not a valid JS expression
+    VBindInvalidExpression,
     // MinifyError(Error<MinifyErrorKind>),
     // PrinterError(Error<PrinterErrorKind>),
 }
@@ -36,6 +38,47 @@ impl CssError {
     }
 }
 
+impl ErrorCode for CssErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            CssErrorKind::ParseRecoverable(_) | CssErrorKind::ParseUnrecoverable(_) => {
+                "css/parse-error"
+            }
+            CssErrorKind::ParseDeepRecoverable(_) | CssErrorKind::ParseDeepUnrecoverable(_) => {
+                "css/deep-parse-error"
+            }
+            CssErrorKind::VBindInvalidExpression => "css/v-bind-invalid-expression",
+        }
+    }
+}
+
+impl std::fmt::Display for CssErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parse_error_message =
+            |kind: &ParseErrorKind| ParseError::new(DUMMY_SP, kind.clone()).message();
+
+        match self {
+            CssErrorKind::ParseRecoverable(kind) | CssErrorKind::ParseUnrecoverable(kind) => {
+                write!(f, "{}", parse_error_message(kind))
+            }
+            CssErrorKind::ParseDeepRecoverable(kind)
+            | CssErrorKind::ParseDeepUnrecoverable(kind) => {
+                write!(f, "Invalid :deep() selector: {}", parse_error_message(kind))
+            }
+            CssErrorKind::VBindInvalidExpression => write!(
+                f,
+                "The expression inside v-bind() is not a valid JavaScript expression."
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for CssError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
 impl Severity for CssError {
     fn get_severity(&self) -> SeverityLevel {
         match &self.kind {
@@ -43,6 +86,7 @@ impl Severity for CssError {
             CssErrorKind::ParseUnrecoverable(_) => SeverityLevel::UnrecoverableError,
             CssErrorKind::ParseDeepRecoverable(_) => SeverityLevel::RecoverableError,
             CssErrorKind::ParseDeepUnrecoverable(_) => SeverityLevel::UnrecoverableError,
+            CssErrorKind::VBindInvalidExpression => SeverityLevel::RecoverableError,
         }
     }
 }
@@ -0,0 +1,192 @@
+//! CSS Modules, e.g. `<style module>`, where the class names are made unique per component
+
+use fervid_core::FervidAtom;
+use swc_css_ast::{
+    AtRule, ComplexSelector, ComplexSelectorChildren, ComponentValue, CompoundSelector,
+    ListOfComponentValues, PseudoClassSelectorChildren, QualifiedRule, QualifiedRulePrelude,
+    RelativeSelectorList, Rule, SelectorList, SimpleBlock, Stylesheet, SubclassSelector,
+};
+
+/// Renames the classes, e.g. `.foo` becomes `.foo_abcd1234`,
+/// and remembers the original and the renamed class names.
+pub struct CssModulesTransformer<'h> {
+    hash: &'h str,
+    classes: Vec<(FervidAtom, FervidAtom)>,
+}
+
+impl<'h> CssModulesTransformer<'h> {
+    pub fn new(hash: &'h str) -> Self {
+        Self {
+            hash,
+            classes: vec![],
+        }
+    }
+
+    pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
+        for rule in stylesheet.rules.iter_mut() {
+            match rule {
+                Rule::QualifiedRule(qualified_rule) => {
+                    self.transform_qualified_rule(qualified_rule);
+                }
+
+                Rule::AtRule(at_rule) => {
+                    self.transform_at_rule(at_rule);
+                }
+
+                Rule::ListOfComponentValues(list_of_component_values) => {
+                    self.transform_list_of_component_values(list_of_component_values);
+                }
+            }
+        }
+    }
+
+    /// Takes the pairs of the original and the renamed class names in order of appearance
+    pub fn take_classes(&mut self) -> Vec<(FervidAtom, FervidAtom)> {
+        std::mem::take(&mut self.classes)
+    }
+
+    fn transform_compound_selector(&mut self, compound_selector: &mut CompoundSelector) {
+        for subclass_selector in compound_selector.subclass_selectors.iter_mut() {
+            match subclass_selector {
+                SubclassSelector::Class(class_selector) => {
+                    let renamed = self.rename(&class_selector.text.value);
+                    class_selector.text.value = (&*renamed).into();
+                    class_selector.text.raw = None;
+                }
+
+                // Classes inside `:not(.foo)`, `:is(.foo)`, etc.
+                SubclassSelector::PseudoClass(pseudo_class) => {
+                    let Some(ref mut children) = pseudo_class.children else {
+                        continue;
+                    };
+
+                    for child in children.iter_mut() {
+                        match child {
+                            PseudoClassSelectorChildren::SelectorList(selector_list) => {
+                                self.transform_selector_list(selector_list);
+                            }
+                            PseudoClassSelectorChildren::ComplexSelector(complex_selector) => {
+                                self.transform_complex_selector(complex_selector);
+                            }
+                            PseudoClassSelectorChildren::CompoundSelector(compound_selector) => {
+                                self.transform_compound_selector(compound_selector);
+                            }
+                            PseudoClassSelectorChildren::RelativeSelectorList(
+                                relative_selector_list,
+                            ) => {
+                                self.transform_relative_selector_list(relative_selector_list);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    /// Renames the class and remembers it, e.g. `foo` becomes `foo_abcd1234`
+    fn rename(&mut self, class_name: &str) -> FervidAtom {
+        if let Some((_, renamed)) = self
+            .classes
+            .iter()
+            .find(|(original, _)| original == class_name)
+        {
+            return renamed.to_owned();
+        }
+
+        let renamed = FervidAtom::from(format!("{}_{}", class_name, self.hash));
+        self.classes
+            .push((FervidAtom::from(class_name), renamed.to_owned()));
+        renamed
+    }
+
+    fn transform_complex_selector(&mut self, complex_selector: &mut ComplexSelector) {
+        for complex_selector_child in complex_selector.children.iter_mut() {
+            if let ComplexSelectorChildren::CompoundSelector(compound_selector) =
+                complex_selector_child
+            {
+                self.transform_compound_selector(compound_selector);
+            }
+        }
+    }
+
+    fn transform_at_rule(&mut self, at_rule: &mut AtRule) {
+        if let Some(ref mut at_rule_block) = at_rule.block {
+            self.transform_simple_block(at_rule_block);
+        };
+    }
+
+    fn transform_qualified_rule(&mut self, qualified_rule: &mut QualifiedRule) {
+        match qualified_rule.prelude {
+            QualifiedRulePrelude::SelectorList(ref mut selector_list) => {
+                self.transform_selector_list(selector_list);
+            }
+            QualifiedRulePrelude::RelativeSelectorList(ref mut relative_selector_list) => {
+                self.transform_relative_selector_list(relative_selector_list);
+            }
+            QualifiedRulePrelude::ListOfComponentValues(ref mut list_of_component_values) => {
+                self.transform_list_of_component_values(list_of_component_values);
+            }
+        }
+
+        self.transform_simple_block(&mut qualified_rule.block);
+    }
+
+    fn transform_component_value(&mut self, component_value: &mut ComponentValue) {
+        match component_value {
+            ComponentValue::QualifiedRule(qual) => {
+                self.transform_qualified_rule(qual);
+            }
+
+            ComponentValue::ComplexSelector(complex_selector) => {
+                self.transform_complex_selector(complex_selector);
+            }
+
+            ComponentValue::AtRule(at_rule) => {
+                self.transform_at_rule(at_rule);
+            }
+
+            ComponentValue::SimpleBlock(simple_block) => {
+                self.transform_simple_block(simple_block);
+            }
+
+            ComponentValue::ListOfComponentValues(list_of_component_values) => {
+                self.transform_list_of_component_values(list_of_component_values);
+            }
+
+            _ => {}
+        }
+    }
+
+    fn transform_list_of_component_values(
+        &mut self,
+        list_of_component_values: &mut ListOfComponentValues,
+    ) {
+        for component_value in list_of_component_values.children.iter_mut() {
+            self.transform_component_value(component_value);
+        }
+    }
+
+    fn transform_relative_selector_list(
+        &mut self,
+        relative_selector_list: &mut RelativeSelectorList,
+    ) {
+        for relative_selector in relative_selector_list.children.iter_mut() {
+            self.transform_complex_selector(&mut relative_selector.selector);
+        }
+    }
+
+    fn transform_selector_list(&mut self, selector_list: &mut SelectorList) {
+        for complex_selector in selector_list.children.iter_mut() {
+            self.transform_complex_selector(complex_selector);
+        }
+    }
+
+    fn transform_simple_block(&mut self, simple_block: &mut SimpleBlock) {
+        for component_value in simple_block.value.iter_mut() {
+            self.transform_component_value(component_value);
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use swc_core::common::{Span, Spanned, DUMMY_SP};
 use swc_css_ast::{
     AtRule, AttributeSelector, Combinator, ComplexSelector, ComplexSelectorChildren,
-    ComponentValue, Ident, ListOfComponentValues, PseudoClassSelectorChildren,
-    PseudoElementSelectorChildren, QualifiedRulePrelude, Rule, SelectorList, SimpleBlock,
-    Stylesheet, SubclassSelector, WqName,
+    ComponentValue, CompoundSelector, Ident, ListOfComponentValues, NestingSelector,
+    PseudoClassSelectorChildren, PseudoElementSelectorChildren, QualifiedRule,
+    QualifiedRulePrelude, RelativeSelectorList, Rule, SelectorList, SimpleBlock, Stylesheet,
+    SubclassSelector, WqName,
 };
 
 use super::{
@@ -17,6 +18,9 @@ use super::{
 pub struct ScopedTransformer<'s> {
     scope: &'s str,
     errors: Vec<CssError>,
+    /// Whether the current rule is nested in a rule with `:deep`,
+    /// e.g. `.bar` in `:deep(.foo) { .bar { } }` is not scoped
+    is_inside_deep: bool,
 }
 
 impl<'i, 's> ScopedTransformer<'s> {
@@ -24,23 +28,16 @@ impl<'i, 's> ScopedTransformer<'s> {
         Self {
             scope,
             errors: vec![],
+            is_inside_deep: false,
         }
     }
 
     pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
         for rule in stylesheet.rules.iter_mut() {
             match rule {
-                Rule::QualifiedRule(qualified_rule) => match qualified_rule.prelude {
-                    QualifiedRulePrelude::SelectorList(ref mut selector_list) => {
-                        self.transform_selector_list(selector_list);
-                    }
-                    QualifiedRulePrelude::RelativeSelectorList(_) => {}
-                    QualifiedRulePrelude::ListOfComponentValues(
-                        ref mut list_of_component_values,
-                    ) => {
-                        self.transform_list_of_component_values(list_of_component_values);
-                    }
-                },
+                Rule::QualifiedRule(qualified_rule) => {
+                    self.transform_qualified_rule(qualified_rule);
+                }
 
                 Rule::AtRule(at_rule) => {
                     self.transform_at_rule(at_rule);
@@ -59,6 +56,11 @@ impl<'i, 's> ScopedTransformer<'s> {
 
     /// This is the meat of the scoped transform
     pub fn transform_complex_selector(&mut self, complex_selector: &mut ComplexSelector) {
+        // `:global` and `:slotted` are scoped differently
+        if self.transform_global_or_slotted(complex_selector) {
+            return;
+        }
+
         let mut deep_idx: Option<usize> = None;
         let mut deep_children: Option<ComplexSelector> = None;
         let mut is_deep_alone = false;
@@ -131,6 +133,10 @@ impl<'i, 's> ScopedTransformer<'s> {
         // Check that we actually found `:deep`.
         // If not, just add to the `previous_compound_selector_idx`
         let Some(deep_idx) = deep_idx else {
+            if self.is_inside_deep {
+                return;
+            }
+
             if let Some(ComplexSelectorChildren::CompoundSelector(last_compound_selector)) =
                 complex_selector
                     .children
@@ -206,43 +212,172 @@ impl<'i, 's> ScopedTransformer<'s> {
         complex_selector.children.append(&mut selectors_after_deep);
     }
 
+    /// Handles `:global()` and `:slotted()` (as well as `::v-global()` and `::v-slotted()`):
+    /// - `.foo :global(.bar)` becomes `.bar` and is not scoped;
+    /// - `.foo :slotted(.bar)` becomes `.foo .bar[data-v-abcd1234-s]`.
+    ///
+    /// Returns `true` when the selector was handled.
+    fn transform_global_or_slotted(&mut self, complex_selector: &mut ComplexSelector) -> bool {
+        // Find the `CompoundSelector` and the position of pseudo selector in it
+        let found = complex_selector.children.iter().enumerate().find_map(
+            |(idx, complex_selector_child)| {
+                let ComplexSelectorChildren::CompoundSelector(compound_selector) =
+                    complex_selector_child
+                else {
+                    return None;
+                };
+
+                compound_selector
+                    .subclass_selectors
+                    .iter()
+                    .position(|sel| get_global_or_slotted(sel).is_some())
+                    .map(|pos| (idx, pos))
+            },
+        );
+        let Some((idx, pos)) = found else {
+            return false;
+        };
+
+        let Some(ComplexSelectorChildren::CompoundSelector(compound_selector)) =
+            complex_selector.children.get_mut(idx)
+        else {
+            unreachable!()
+        };
+
+        // Take the contents of pseudo selector
+        let pseudo = compound_selector.subclass_selectors.remove(pos);
+        let (is_global, children) = match pseudo {
+            SubclassSelector::PseudoClass(pseudo_class) => (
+                pseudo_class.name.value == "global",
+                pseudo_class
+                    .children
+                    .and_then(|children| process_pseudo_class_children(children, &mut self.errors)),
+            ),
+            SubclassSelector::PseudoElement(pseudo_element) => (
+                pseudo_element.name.value == "v-global",
+                pseudo_element.children.and_then(|children| {
+                    process_pseudo_element_children(children, &mut self.errors)
+                }),
+            ),
+            _ => unreachable!(),
+        };
+
+        let Some(mut children) = children else {
+            // Nothing to scope inside `:global()`
+            if is_global {
+                return true;
+            }
+
+            // `:slotted()` without contents is scoped like the regular selector
+            return false;
+        };
+
+        // `:global` replaces the whole selector
+        if is_global {
+            complex_selector.children = children.children;
+            return true;
+        }
+
+        // `:slotted` contents are scoped with the slot scope, e.g. `[data-v-abcd1234-s]`
+        if let Some(ComplexSelectorChildren::CompoundSelector(last_compound_selector)) = children
+            .children
+            .iter_mut()
+            .rev()
+            .find(|child| matches!(child, ComplexSelectorChildren::CompoundSelector(_)))
+        {
+            let slot_scope = format!("{}-s", self.scope);
+            last_compound_selector
+                .subclass_selectors
+                .push(create_attribute_selector(&slot_scope));
+        }
+
+        let is_slotted_alone = compound_selector.subclass_selectors.is_empty()
+            && compound_selector.type_selector.is_none()
+            && compound_selector.nesting_selector.is_none();
+
+        if is_slotted_alone {
+            // `.foo :slotted(.bar)`: replace the `CompoundSelector`
+            complex_selector
+                .children
+                .splice(idx..=idx, children.children);
+        } else {
+            // `.foo:slotted(.bar)`: treated as `.foo :slotted(.bar)`
+            let mut to_insert = Vec::with_capacity(children.children.len() + 1);
+            to_insert.push(ComplexSelectorChildren::Combinator(Combinator {
+                span: DUMMY_SP,
+                value: swc_css_ast::CombinatorValue::Descendant,
+            }));
+            to_insert.append(&mut children.children);
+            complex_selector
+                .children
+                .splice((idx + 1)..(idx + 1), to_insert);
+        }
+
+        true
+    }
+
     /// 0. Prepare what selector to add.
     /// It is always an attribute selector, e.g. `[data-v-abcd1234]`
     fn get_subclass_selector_to_add(&self) -> SubclassSelector {
-        SubclassSelector::Attribute(Box::new(AttributeSelector {
-            span: DUMMY_SP,
-            name: WqName {
-                span: DUMMY_SP,
-                prefix: None,
-                value: Ident {
-                    span: DUMMY_SP,
-                    value: self.scope.into(),
-                    raw: None,
-                },
-            },
-            matcher: None,
-            value: None,
-            modifier: None,
-        }))
+        create_attribute_selector(self.scope)
     }
 
+    /// Transforms the rules inside the at-rule block, e.g. `@media`, `@supports` or `@layer`.
+    /// The prelude is left intact. Keyframe selectors are not rules and are not affected.
     fn transform_at_rule(&mut self, at_rule: &mut AtRule) {
         if let Some(ref mut at_rule_block) = at_rule.block {
             self.transform_simple_block(at_rule_block);
         };
     }
 
-    fn transform_component_value(&mut self, component_value: &mut ComponentValue) {
-        match component_value {
-            ComponentValue::QualifiedRule(qual) => match qual.prelude {
+    /// Transforms the selectors of a rule and the rules nested in its block (CSS nesting).
+    ///
+    /// Same as the official compiler, a rule with the nested rules is not scoped itself.
+    /// Its declarations are moved to a nested `&` rule, which is scoped like the other nested rules:
+    /// `.foo { color: red; .bar { } }` becomes `.foo { &[data-v-abcd1234] { color: red } .bar[data-v-abcd1234] { } }`.
+    /// This way, the outer selector is only matched once, and not as `.foo[data-v-abcd1234] .bar[data-v-abcd1234]`.
+    fn transform_qualified_rule(&mut self, qualified_rule: &mut QualifiedRule) {
+        let has_nested_rules = qualified_rule
+            .block
+            .value
+            .iter()
+            .any(|value| matches!(value, ComponentValue::QualifiedRule(_)));
+        let is_deep = has_deep(&qualified_rule.prelude);
+
+        if has_nested_rules && !is_deep {
+            wrap_declarations(&mut qualified_rule.block);
+            for value in qualified_rule.block.value.iter_mut() {
+                if let ComponentValue::AtRule(at_rule) = value {
+                    if let Some(ref mut at_rule_block) = at_rule.block {
+                        wrap_declarations(at_rule_block);
+                    }
+                }
+            }
+        } else {
+            match qualified_rule.prelude {
                 QualifiedRulePrelude::SelectorList(ref mut selector_list) => {
                     self.transform_selector_list(selector_list);
                 }
-                QualifiedRulePrelude::RelativeSelectorList(_) => {}
+                QualifiedRulePrelude::RelativeSelectorList(ref mut relative_selector_list) => {
+                    self.transform_relative_selector_list(relative_selector_list);
+                }
                 QualifiedRulePrelude::ListOfComponentValues(ref mut list_of_component_values) => {
                     self.transform_list_of_component_values(list_of_component_values);
                 }
-            },
+            }
+        }
+
+        let was_inside_deep = self.is_inside_deep;
+        self.is_inside_deep |= is_deep;
+        self.transform_simple_block(&mut qualified_rule.block);
+        self.is_inside_deep = was_inside_deep;
+    }
+
+    fn transform_component_value(&mut self, component_value: &mut ComponentValue) {
+        match component_value {
+            ComponentValue::QualifiedRule(qual) => {
+                self.transform_qualified_rule(qual);
+            }
 
             ComponentValue::ComplexSelector(complex_selector) => {
                 self.transform_complex_selector(complex_selector);
@@ -273,6 +408,16 @@ impl<'i, 's> ScopedTransformer<'s> {
         }
     }
 
+    /// Nested rules, e.g. `> .bar` or `& .bar` inside `.foo { }`
+    fn transform_relative_selector_list(
+        &mut self,
+        relative_selector_list: &mut RelativeSelectorList,
+    ) {
+        for relative_selector in relative_selector_list.children.iter_mut() {
+            self.transform_complex_selector(&mut relative_selector.selector);
+        }
+    }
+
     fn transform_selector_list(&mut self, selector_list: &mut SelectorList) {
         for complex_selector in selector_list.children.iter_mut() {
             self.transform_complex_selector(complex_selector);
@@ -286,6 +431,116 @@ impl<'i, 's> ScopedTransformer<'s> {
     }
 }
 
+/// Moves the declarations of a block to a nested `&` rule at its start,
+/// e.g. `{ color: red; .bar { } }` becomes `{ & { color: red } .bar { } }`
+fn wrap_declarations(simple_block: &mut SimpleBlock) {
+    let (declarations, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut simple_block.value)
+        .into_iter()
+        .partition(|value| matches!(value, ComponentValue::Declaration(_)));
+    simple_block.value = rest;
+
+    if declarations.is_empty() {
+        return;
+    }
+
+    let span = simple_block.span;
+    let nesting_rule = QualifiedRule {
+        span,
+        prelude: QualifiedRulePrelude::SelectorList(SelectorList {
+            span,
+            children: vec![ComplexSelector {
+                span,
+                children: vec![ComplexSelectorChildren::CompoundSelector(
+                    CompoundSelector {
+                        span,
+                        nesting_selector: Some(NestingSelector { span }),
+                        type_selector: None,
+                        subclass_selectors: vec![],
+                    },
+                )],
+            }],
+        }),
+        block: SimpleBlock {
+            span,
+            name: simple_block.name.clone(),
+            value: declarations,
+        },
+    };
+    simple_block
+        .value
+        .insert(0, ComponentValue::QualifiedRule(Box::new(nesting_rule)));
+}
+
+/// Whether the rule selector has `:deep` or `::v-deep`
+fn has_deep(prelude: &QualifiedRulePrelude) -> bool {
+    let is_deep_compound = |complex_selector: &ComplexSelector| {
+        complex_selector.children.iter().any(|child| {
+            let ComplexSelectorChildren::CompoundSelector(compound_selector) = child else {
+                return false;
+            };
+
+            compound_selector
+                .subclass_selectors
+                .iter()
+                .any(|sel| match sel {
+                    SubclassSelector::PseudoClass(pseudo) => pseudo.name.value == "deep",
+                    SubclassSelector::PseudoElement(pseudo) => pseudo.name.value == "v-deep",
+                    _ => false,
+                })
+        })
+    };
+
+    match prelude {
+        QualifiedRulePrelude::SelectorList(selector_list) => {
+            selector_list.children.iter().any(is_deep_compound)
+        }
+        QualifiedRulePrelude::RelativeSelectorList(relative_selector_list) => {
+            relative_selector_list
+                .children
+                .iter()
+                .any(|relative_selector| is_deep_compound(&relative_selector.selector))
+        }
+        QualifiedRulePrelude::ListOfComponentValues(_) => false,
+    }
+}
+
+/// Creates an attribute selector, e.g. `[data-v-abcd1234]`
+fn create_attribute_selector(name: &str) -> SubclassSelector {
+    SubclassSelector::Attribute(Box::new(AttributeSelector {
+        span: DUMMY_SP,
+        name: WqName {
+            span: DUMMY_SP,
+            prefix: None,
+            value: Ident {
+                span: DUMMY_SP,
+                value: name.into(),
+                raw: None,
+            },
+        },
+        matcher: None,
+        value: None,
+        modifier: None,
+    }))
+}
+
+/// Checks if a selector is `:global`/`::v-global` (`Some(true)`)
+/// or `:slotted`/`::v-slotted` (`Some(false)`)
+fn get_global_or_slotted(selector: &SubclassSelector) -> Option<bool> {
+    match selector {
+        SubclassSelector::PseudoClass(pseudo) => match &*pseudo.name.value {
+            "global" => Some(true),
+            "slotted" => Some(false),
+            _ => None,
+        },
+        SubclassSelector::PseudoElement(pseudo) => match &*pseudo.name.value {
+            "v-global" => Some(true),
+            "v-slotted" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 // Processes contents of `:deep`
 fn process_pseudo_class_children(
     children: Vec<PseudoClassSelectorChildren>,
@@ -0,0 +1,347 @@
+//! Analysis of CSS `v-bind()`, e.g. `color: v-bind(color)` or `width: v-bind('size.width')`
+
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use fxhash::FxHasher32;
+
+const V_BIND: &str = "v-bind";
+
+/// Same as `cssVarNameEscapeSymbolsRE` of the official compiler
+const CSS_VAR_NAME_ESCAPE_SYMBOLS: &str = " !\"#$%&'()*+,./:;<=>?@[\\]^`{|}~";
+
+/// Collects the unique expressions used inside `v-bind()`.
+/// Quotes around the expression are removed, e.g. `v-bind('foo.bar')` produces `foo.bar`.
+/// The fallback after a top-level comma is not a part of the expression,
+/// e.g. `v-bind(color, red)` produces `color`.
+///
+/// Adapted from the official compiler `parseCssVars`.
+/// Like the official compiler, `v-bind()` inside comments is ignored.
+pub fn parse_css_vars(content: &str) -> Vec<String> {
+    let mut vars: Vec<String> = Vec::new();
+    let content = strip_comments(content);
+
+    for css_var in find_css_vars(&content) {
+        if !vars.iter().any(|v| v == css_var.expression) {
+            vars.push(css_var.expression.to_owned());
+        }
+    }
+
+    vars
+}
+
+/// Replaces `v-bind()` with the CSS custom property,
+/// e.g. `v-bind(color)` becomes `var(--abcd1234-color)` where `abcd1234` is the `id`.
+/// The fallback is kept, e.g. `v-bind(color, var(--accent, red))` becomes
+/// `var(--abcd1234-color, var(--accent, red))`, and does not change the name of the property.
+/// Comments are preserved as-is.
+pub fn rewrite_css_vars(content: &str, id: &str, is_prod: bool) -> String {
+    let mut result = String::with_capacity(content.len());
+
+    for_each_segment(content, |segment, is_comment| {
+        if is_comment {
+            result.push_str(segment);
+            return;
+        }
+
+        let mut last_end = 0;
+        for css_var in find_css_vars(segment) {
+            result.push_str(&segment[last_end..css_var.range.start]);
+            result.push_str("var(--");
+            let var_name = gen_css_var_name(id, css_var.expression, is_prod);
+            if is_prod {
+                result.push_str(&var_name);
+            } else {
+                escape_css_var_name(&var_name, &mut result);
+            }
+            if let Some(fallback) = css_var.fallback {
+                result.push_str(", ");
+                result.push_str(fallback);
+            }
+            result.push(')');
+            last_end = css_var.range.end;
+        }
+        result.push_str(&segment[last_end..]);
+    });
+
+    result
+}
+
+/// Generates the name of the CSS custom property (without `--`) which is used at runtime by `useCssVars`.
+/// In production the name is hashed, e.g. `1a2b3c4d`, in development it is readable, e.g. `abcd1234-color`.
+pub fn gen_css_var_name(id: &str, variable: &str, is_prod: bool) -> String {
+    if is_prod {
+        let mut hasher = FxHasher32::default();
+        id.hash(&mut hasher);
+        variable.hash(&mut hasher);
+        format!("{:08x}", hasher.finish())
+    } else {
+        format!("{}-{}", id, variable)
+    }
+}
+
+/// Escapes the characters which are not allowed in CSS identifiers, e.g. `foo.bar` becomes `foo\.bar`
+fn escape_css_var_name(var_name: &str, out: &mut String) {
+    for c in var_name.chars() {
+        if CSS_VAR_NAME_ESCAPE_SYMBOLS.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// A `v-bind()` usage, e.g. `v-bind('theme.color', red)`
+struct CssVar<'c> {
+    /// Byte range of the whole `v-bind()`
+    range: Range<usize>,
+    /// Normalized expression, e.g. `theme.color`
+    expression: &'c str,
+    /// CSS after the top-level comma, e.g. `red`
+    fallback: Option<&'c str>,
+}
+
+/// Finds the `v-bind()` usages
+fn find_css_vars(content: &str) -> Vec<CssVar> {
+    let mut vars = Vec::new();
+
+    let mut search_start = 0;
+    while let Some(found_idx) = content[search_start..].find(V_BIND) {
+        let v_bind_start = search_start + found_idx;
+        let after_v_bind = v_bind_start + V_BIND.len();
+        search_start = after_v_bind;
+
+        // `v-bind` must be followed by optional whitespace and `(`
+        let rest = &content[after_v_bind..];
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with('(') {
+            continue;
+        }
+
+        let start = after_v_bind + (rest.len() - trimmed.len()) + 1;
+        let Some(end) = find_top_level(content, start, ')') else {
+            continue;
+        };
+
+        // The comma inside of the quotes or the parens belongs to the expression,
+        // e.g. `v-bind('a, b')` or `v-bind(fn(a, b))`
+        let binding = &content[start..end];
+        let (expression, fallback) = match find_top_level(binding, 0, ',') {
+            Some(comma) => (&binding[..comma], Some(binding[comma + 1..].trim())),
+            None => (binding, None),
+        };
+
+        vars.push(CssVar {
+            range: v_bind_start..end + 1,
+            expression: normalize_expression(expression),
+            fallback,
+        });
+
+        search_start = end;
+    }
+
+    vars
+}
+
+/// Removes `/* */` and `//` comments (the latter are supported by Less, Sass and Stylus)
+fn strip_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    for_each_segment(content, |segment, is_comment| {
+        if !is_comment {
+            result.push_str(segment);
+        }
+    });
+    result
+}
+
+/// Splits the content into comments and the rest, calls `f` for each of the parts in order.
+/// The second argument of `f` is `true` for comments.
+fn for_each_segment(content: &str, mut f: impl FnMut(&str, bool)) {
+    let mut rest = content;
+
+    loop {
+        let block_idx = rest.find("/*");
+        let line_idx = rest.find("//");
+
+        match (block_idx, line_idx) {
+            (Some(block), line) if line.map_or(true, |line| block < line) => {
+                f(&rest[..block], false);
+                match rest[block + 2..].find("*/") {
+                    Some(end) => {
+                        let comment_end = block + 2 + end + 2;
+                        f(&rest[block..comment_end], true);
+                        rest = &rest[comment_end..];
+                    }
+                    None => {
+                        f(&rest[block..], true);
+                        return;
+                    }
+                }
+            }
+            (_, Some(line)) => {
+                f(&rest[..line], false);
+                match rest[line..].find('\n') {
+                    Some(end) => {
+                        f(&rest[line..line + end], true);
+                        rest = &rest[line + end..];
+                    }
+                    None => {
+                        f(&rest[line..], true);
+                        return;
+                    }
+                }
+            }
+            _ => {
+                f(rest, false);
+                return;
+            }
+        }
+    }
+}
+
+/// Finds `target` outside of the nested parens and strings, e.g. the closing `)` of `v-bind(`
+fn find_top_level(content: &str, start: usize, target: char) -> Option<usize> {
+    enum LexerState {
+        Parens,
+        SingleQuoteString,
+        DoubleQuoteString,
+    }
+
+    let mut state = LexerState::Parens;
+    let mut paren_depth = 0;
+
+    for (idx, c) in content[start..].char_indices() {
+        match state {
+            LexerState::Parens => match c {
+                '\'' => state = LexerState::SingleQuoteString,
+                '"' => state = LexerState::DoubleQuoteString,
+                c if c == target && paren_depth == 0 => return Some(start + idx),
+                '(' => paren_depth += 1,
+                ')' if paren_depth > 0 => paren_depth -= 1,
+                _ => {}
+            },
+            LexerState::SingleQuoteString if c == '\'' => state = LexerState::Parens,
+            LexerState::DoubleQuoteString if c == '"' => state = LexerState::Parens,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn normalize_expression(expr: &str) -> &str {
+    let expr = expr.trim();
+    let is_quoted = expr.len() >= 2
+        && ((expr.starts_with('\'') && expr.ends_with('\''))
+            || (expr.starts_with('"') && expr.ends_with('"')));
+
+    if is_quoted {
+        &expr[1..expr.len() - 1]
+    } else {
+        expr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_css_vars() {
+        assert_eq!(
+            vec!["color", "size.width", "theme.fg ?? 'red'"],
+            parse_css_vars(
+                ".foo { color: v-bind(color); width: v-bind('size.width'); background: v-bind (\"theme.fg ?? 'red'\") }"
+            )
+        );
+    }
+
+    #[test]
+    fn it_deduplicates_css_vars() {
+        assert_eq!(
+            vec!["color"],
+            parse_css_vars(".foo { color: v-bind(color) } .bar { color: v-bind( color ) }")
+        );
+    }
+
+    #[test]
+    fn it_handles_nested_parens() {
+        assert_eq!(
+            vec!["fn(a, (b))"],
+            parse_css_vars(".foo { color: v-bind(fn(a, (b))) }")
+        );
+    }
+
+    #[test]
+    fn it_rewrites_css_vars() {
+        assert_eq!(
+            ".foo { color: var(--abcd1234-color); width: var(--abcd1234-size\\.width) }",
+            rewrite_css_vars(
+                ".foo { color: v-bind(color); width: v-bind('size.width') }",
+                "abcd1234",
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn it_rewrites_css_vars_in_prod() {
+        let var_name = gen_css_var_name("abcd1234", "size.width", true);
+        assert_eq!(8, var_name.len());
+        assert_eq!(
+            format!(".foo{{width:var(--{})}}", var_name),
+            rewrite_css_vars(".foo{width:v-bind(\"size.width\")}", "abcd1234", true)
+        );
+    }
+
+    #[test]
+    fn it_separates_fallback() {
+        assert_eq!(
+            vec!["color", "a, b", "fn(a, b)"],
+            parse_css_vars(
+                ".foo { color: v-bind(color, red); content: v-bind('a, b'); width: v-bind(fn(a, b)) } .bar { color: v-bind(color) }"
+            )
+        );
+    }
+
+    #[test]
+    fn it_rewrites_css_vars_with_fallback() {
+        assert_eq!(
+            ".foo { color: var(--abcd1234-color, red); background: var(--abcd1234-theme\\.bg, var(--accent, rgb(0, 0, 0))) }",
+            rewrite_css_vars(
+                ".foo { color: v-bind(color,red); background: v-bind('theme.bg', var(--accent, rgb(0, 0, 0))) }",
+                "abcd1234",
+                false
+            )
+        );
+
+        // Same property with and without the fallback
+        assert_eq!(
+            rewrite_css_vars(".foo{color:v-bind(color)}", "abcd1234", true).replace(')', ", red)"),
+            rewrite_css_vars(".foo{color:v-bind(color, red)}", "abcd1234", true)
+        );
+    }
+
+    #[test]
+    fn it_keeps_comments_when_rewriting() {
+        assert_eq!(
+            "/* v-bind(commented) */ .foo { color: var(--abcd1234-real) }",
+            rewrite_css_vars(
+                "/* v-bind(commented) */ .foo { color: v-bind(real) }",
+                "abcd1234",
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn it_ignores_comments() {
+        assert_eq!(
+            vec!["real"],
+            parse_css_vars(
+                "/* v-bind(commented) */\n.foo {\n  // v-bind(line)\n  color: v-bind(real)\n}"
+            )
+        );
+    }
+}
@@ -31,6 +31,7 @@ pub use css::*;
 #[cfg(test)]
 #[allow(unused)]
 mod tests {
+    use fervid_core::FervidAtom;
     use swc_core::common::{Span, BytePos};
     use crate::css;
 
@@ -185,6 +186,40 @@ mod tests {
             minify_yes!()
         );
 
+        //
+        // With `:slotted` and `:global`
+        //
+
+        test_ok!(
+            ":slotted(.bar) { background: #ff0 }",
+            ".bar[data-v-abcd1234-s]{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo :slotted(.bar .baz) { background: #ff0 }",
+            ".foo .bar .baz[data-v-abcd1234-s]{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "::v-slotted(.bar) { background: #ff0 }",
+            ".bar[data-v-abcd1234-s]{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ":global(.foo .bar) { background: #ff0 }",
+            ".foo .bar{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo :global(.bar), .qux { background: #ff0 }",
+            ".bar,.qux[data-v-abcd1234]{background:#ff0}",
+            minify_yes!()
+        );
+
         //
         // At-rules
         //
@@ -193,5 +228,94 @@ mod tests {
             "@media screen and (min-width:500px){.foo[data-v-abcd1234]{background:#ff0}}",
             minify_yes!()
         );
+        test_ok!(
+            "@supports (display: grid) { .foo { display: grid } }",
+            "@supports(display:grid){.foo[data-v-abcd1234]{display:grid}}",
+            minify_yes!()
+        );
+        test_ok!(
+            "@layer base { .foo { background: #ff0 } }",
+            "@layer base{.foo[data-v-abcd1234]{background:#ff0}}",
+            minify_yes!()
+        );
+        test_ok!(
+            "@media screen { @supports (display: grid) { .foo { display: grid } } }",
+            "@media screen{@supports(display:grid){.foo[data-v-abcd1234]{display:grid}}}",
+            minify_yes!()
+        );
+
+        // Keyframe selectors are not scoped
+        test_ok!(
+            "@keyframes fade { from { opacity: 0 } to { opacity: 1 } }",
+            "@keyframes fade{from{opacity:0}to{opacity:1}}",
+            minify_yes!()
+        );
+
+        //
+        // Nesting
+        //
+        // Same as compiler-sfc, the declarations of the outer rule are scoped with `&`
+        test_ok!(
+            ".foo { .bar { background: #ff0 } }",
+            ".foo{.bar[data-v-abcd1234]{background:#ff0}}",
+            minify_yes!()
+        );
+        test_ok!(
+            ".foo { color: red; .bar { background: #ff0 } }",
+            ".foo{&[data-v-abcd1234]{color:red}.bar[data-v-abcd1234]{background:#ff0}}",
+            minify_yes!()
+        );
+        test_ok!(
+            ".foo { color: red; & .bar { color: blue } @media screen { color: green; .baz { color: white } } }",
+            ".foo{&[data-v-abcd1234]{color:red}& .bar[data-v-abcd1234]{color:blue}@media screen{&[data-v-abcd1234]{color:green}.baz[data-v-abcd1234]{color:white}}}",
+            minify_yes!()
+        );
+
+        // The rules nested in `:deep` are not scoped
+        test_ok!(
+            ":deep(.foo) { color: red; .bar { color: blue } }",
+            "[data-v-abcd1234] .foo{color:red;.bar{color:blue}}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_module() {
+        let input = ".foo { color: red } .foo.bar:not(.baz) > #qux { color: blue } @media screen { .bar { color: green } }";
+        let span = Span::new(BytePos(1), BytePos(1 + input.len() as u32));
+        let mut errors = Vec::new();
+
+        let (out, classes) = css::transform_css_module(
+            input,
+            span,
+            None,
+            "abcd1234",
+            &mut errors,
+            Default::default(),
+        )
+        .expect("Should transform");
+
+        assert_eq!(
+            ".foo_abcd1234{color:red}.foo_abcd1234.bar_abcd1234:not(.baz_abcd1234)>#qux{color:blue}@media screen{.bar_abcd1234{color:green}}",
+            out
+        );
+        assert_eq!(
+            vec![
+                (FervidAtom::from("foo"), FervidAtom::from("foo_abcd1234")),
+                (FervidAtom::from("bar"), FervidAtom::from("bar_abcd1234")),
+                (FervidAtom::from("baz"), FervidAtom::from("baz_abcd1234")),
+            ],
+            classes
+        );
+    }
+
+    #[test]
+    fn detects_slotted_selectors() {
+        assert!(css::has_slotted_selectors(":slotted(.foo) { color: red }"));
+        assert!(css::has_slotted_selectors(
+            ".bar ::v-slotted(.foo) { color: red }"
+        ));
+        assert!(!css::has_slotted_selectors(".slotted { color: red }"));
+        assert!(!css::has_slotted_selectors(":deep(.foo) { color: red }"));
     }
 }
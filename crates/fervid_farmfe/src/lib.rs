@@ -88,12 +88,36 @@ impl Plugin for FarmPluginVueFervid {
             CompileOptions {
                 filename: std::borrow::Cow::Borrowed(param.resolved_path),
                 id: param.module_id.clone().into(),
+                scope_id: None,
                 is_prod: Some(true),
                 is_custom_element: Some(is_custom_element),
                 props_destructure: None,
                 ssr: None,
+                is_native_tag: None,
+                fs: None,
                 gen_default_as: None,
                 source_map: None,
+                warn_unused_bindings: None,
+                suspense: None,
+                feature_flags: None,
+                minify_css: None,
+                hoist_static: None,
+                stringify_static_threshold: None,
+                cache_handlers: None,
+                whitespace: None,
+                comments: None,
+                functional: None,
+                output_format: None,
+                pad: None,
+                cancellation: None,
+                max_source_size: None,
+                mode: None,
+                hot_reload: None,
+                prev_source: None,
+                expose_filename: None,
+                custom_block_imports: None,
+                script_preprocessor: None,
+                template_preprocessor: None,
             },
         );
 
@@ -1,6 +1,7 @@
+import { getEventListeners } from 'node:events'
 import { test, expect } from 'vitest'
 
-import { Compiler, FervidCompileOptions } from '../index'
+import { Compiler, FervidCompileOptions, compileAsync, compileSync } from '../index'
 
 const options: FervidCompileOptions = {
   filename: 'anonymous.vue',
@@ -58,3 +59,63 @@ test('should work', () => {
     "
   `)
 })
+
+test('should reject when aborted', async () => {
+  const controller = new AbortController()
+  const result = new Compiler().compileAsync(HELLO_WORLD, options, controller.signal)
+  controller.abort()
+
+  await expect(result).rejects.toThrow('AbortError')
+})
+
+test('should resolve when not aborted', async () => {
+  const controller = new AbortController()
+  const result = await new Compiler().compileAsync(HELLO_WORLD, options, controller.signal)
+
+  expect(result.errors).toHaveLength(0)
+})
+
+test('should remove the abort listener once settled', async () => {
+  const controller = new AbortController()
+  await new Compiler().compileAsync(HELLO_WORLD, options, controller.signal)
+
+  expect(getEventListeners(controller.signal, 'abort')).toHaveLength(0)
+})
+
+test('should compile without a compiler instance', async () => {
+  const expected = new Compiler().compileSync(HELLO_WORLD, options)
+
+  const compiled = compileSync(HELLO_WORLD, options)
+  expect(compiled.code).toBe(expected.code)
+  expect(compiled.errors).toEqual([])
+
+  const compiledAsync = await Promise.all([
+    compileAsync(HELLO_WORLD, options),
+    compileAsync(HELLO_WORLD, { ...options, filename: 'other.vue' }),
+  ])
+  expect(compiledAsync[0].code).toBe(expected.code)
+  expect(compiledAsync[1].code).toContain('__name: "other"')
+})
+
+test('should report and import custom blocks', () => {
+  const source = `${HELLO_WORLD}<i18n lang="json" locale="en">{ "hello": "Hello" }</i18n>`
+
+  const reported = new Compiler().compileSync(source, options)
+  expect(reported.customBlocks).toEqual([
+    expect.objectContaining({
+      content: '{ "hello": "Hello" }',
+      tagName: 'i18n',
+      lang: 'json',
+      attrs: { lang: 'json', locale: 'en' },
+    }),
+  ])
+  expect(reported.customBlocks[0].importId).toBeUndefined()
+  expect(reported.code).not.toContain('import block0')
+
+  const imported = new Compiler({ customBlockImports: true }).compileSync(source, options)
+  const importId = 'anonymous.vue?vue&type=i18n&index=0&lang.json'
+  expect(imported.customBlocks[0].importId).toBe(importId)
+  expect(imported.code).toContain(`import block0 from '${importId}';`)
+  expect(imported.code).toContain(`if (typeof block0 === 'function') block0(_sfc_main);`)
+  expect(imported.code.trimEnd().endsWith('export default _sfc_main;')).toBe(true)
+})
@@ -0,0 +1,48 @@
+import { test, expect, describe } from 'vitest'
+
+import { Compiler } from '../index'
+
+describe('compileStyle', () => {
+  test('should scope the style', () => {
+    const result = new Compiler().compileStyle('.foo { color: red }', {
+      id: 'data-v-abcd1234',
+      scoped: true,
+      minify: true,
+    })
+
+    expect(result.errors).toHaveLength(0)
+    expect(result.code).toBe('.foo[data-v-abcd1234]{color:red}')
+  })
+
+  test('should report dependencies', async () => {
+    const result = await new Compiler().compileStyleAsync(
+      `@import './base.css';\n.foo { background: url(./bg.png) }`,
+      { id: 'data-v-abcd1234' },
+    )
+
+    expect(result).toMatchObject({ deps: ['./base.css', './bg.png'] })
+  })
+})
+
+describe('compileTemplate', () => {
+  test('should export the render function', () => {
+    const result = new Compiler().compileTemplate('<div>{{ msg }}</div>', {
+      filename: 'anonymous.vue',
+    })
+
+    expect(result.errors).toHaveLength(0)
+    expect(result.code).toContain('export function render(_ctx, _cache')
+    expect(result.code).toContain('_ctx.msg')
+  })
+
+  test('should resolve identifiers with `with` when not prefixing', () => {
+    const result = new Compiler().compileTemplate('<div>{{ msg }}</div>', {
+      filename: 'anonymous.vue',
+      prefixIdentifiers: false,
+    })
+
+    expect(result.errors).toHaveLength(0)
+    expect(result.code).toContain('with (_ctx)')
+    expect(result.code).not.toContain('_ctx.msg')
+  })
+})
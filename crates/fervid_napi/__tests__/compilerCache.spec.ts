@@ -0,0 +1,58 @@
+import { test, expect, describe } from 'vitest'
+
+import { Compiler, FervidCompileOptions } from '../index'
+
+const options: FervidCompileOptions = {
+  filename: 'anonymous.vue',
+  id: '',
+}
+
+const SFC = `<template><div>{{ msg }}</div></template>`
+
+describe('compiler cache', () => {
+  test('should reuse the output for unchanged files', () => {
+    const compiler = new Compiler({ cache: true })
+    const first = compiler.compileSync(SFC, options)
+    const second = compiler.compileSync(SFC, options)
+
+    expect(second.code).toBe(first.code)
+    expect(compiler.cacheStats()).toEqual({ hits: 1, misses: 1, entries: 1 })
+  })
+
+  test('should keep one entry per file', async () => {
+    const compiler = new Compiler({ cache: true })
+    compiler.compileSync(SFC, options)
+    await compiler.compileAsync(`<template><span /></template>`, options)
+    compiler.compileSync(SFC, { ...options, filename: 'other.vue' })
+
+    expect(compiler.cacheStats()).toEqual({ hits: 0, misses: 3, entries: 2 })
+
+    compiler.clearCache()
+    expect(compiler.cacheStats()).toEqual({ hits: 0, misses: 0, entries: 0 })
+  })
+
+  test('should not cache by default', () => {
+    const compiler = new Compiler()
+    compiler.compileSync(SFC, options)
+    compiler.compileSync(SFC, options)
+
+    expect(compiler.cacheStats()).toEqual({ hits: 0, misses: 0, entries: 0 })
+  })
+
+  test('should not cache when disabled', () => {
+    const compiler = new Compiler({ cache: false })
+    compiler.compileSync(SFC, options)
+    compiler.compileSync(SFC, options)
+
+    expect(compiler.cacheStats()).toEqual({ hits: 0, misses: 0, entries: 0 })
+  })
+
+  test('should expose a stable cache key', () => {
+    const key = new Compiler().cacheKey(SFC, options)
+
+    expect(key).toMatch(/^[0-9a-f]{32}$/)
+    expect(new Compiler().cacheKey(SFC, options)).toBe(key)
+    expect(new Compiler().cacheKey(SFC, { ...options, scopeId: 'data-v-1234' })).not.toBe(key)
+    expect(new Compiler({ isProduction: true }).cacheKey(SFC, options)).not.toBe(key)
+  })
+})
@@ -0,0 +1,134 @@
+import { test, expect, describe } from 'vitest'
+
+import { Compiler, FervidCompileOptions } from '../index'
+
+const options: FervidCompileOptions = {
+  filename: 'anonymous.vue',
+  id: '',
+}
+
+describe('errors', () => {
+  const SFC = `<template>\n  <div v-else>Hello</div>\n</template>`
+
+  test('should report 0-based offsets', () => {
+    const [error] = new Compiler().compileSync(SFC, options).errors
+
+    expect(error.severity).toBe('error')
+    expect(SFC.slice(error.lo, error.hi)).toBe('<div v-else>Hello</div>')
+    expect(error.loc).toBeUndefined()
+    expect(error.frame).toBeUndefined()
+  })
+
+  test('should add the location and the code frame', () => {
+    const compiler = new Compiler({ errorFormat: { loc: true, codeFrame: true } })
+    const [error] = compiler.compileSync(SFC, options).errors
+
+    expect(error.loc).toEqual({
+      start: { line: 2, column: 3 },
+      end: { line: 2, column: 26 },
+    })
+    expect(error.frame).toBe(
+      ['1  |  <template>', '2  |    <div v-else>Hello</div>', '   |    ^^^^^^^^^^^^^^^^^^^^^^^', '3  |  </template>'].join(
+        '\n',
+      ),
+    )
+  })
+})
+
+describe('nested options', () => {
+  const SFC = `<template><div>{{ msg }}</div></template>`
+
+  test('should accept the object form of `ssr`', () => {
+    expect(new Compiler({ ssr: { enabled: true } }).compileSync(SFC, options).code).toContain('ssrRender')
+    expect(new Compiler({ ssr: { enabled: false } }).compileSync(SFC, options).code).not.toContain('ssrRender')
+  })
+
+  test('should drop `sourcesContent` of the source map', () => {
+    const withContent = new Compiler({ sourceMap: true }).compileSync(SFC, options)
+    const withoutContent = new Compiler({ sourceMap: { sourcesContent: false } }).compileSync(SFC, options)
+
+    expect(JSON.parse(withContent.sourceMap!)).toHaveProperty('sourcesContent')
+    expect(JSON.parse(withoutContent.sourceMap!)).not.toHaveProperty('sourcesContent')
+  })
+
+  test('should compile the matching files as custom elements', () => {
+    // Custom elements keep the runtime types of the props in production
+    const SFC = `<script setup lang="ts">defineProps<{ foo: number }>()</script>`
+    const compiler = new Compiler({ isProduction: true, customElement: { include: ['.element.vue'] } })
+
+    expect(compiler.compileSync(SFC, { ...options, filename: 'Foo.element.vue' }).code).toContain('type: Number')
+    expect(compiler.compileSync(SFC, { ...options, filename: 'Foo.ce.vue' }).code).not.toContain('type: Number')
+    expect(
+      compiler.compileSync(SFC, { ...options, filename: 'Foo.ce.vue', isCustomElement: true }).code,
+    ).toContain('type: Number')
+    expect(new Compiler({ isProduction: true }).compileSync(SFC, { ...options, filename: 'Foo.ce.vue' }).code).toContain(
+      'type: Number',
+    )
+  })
+})
+
+describe('limits', () => {
+  const SFC = `<template><div>Hello</div></template>`
+
+  test('should reject the files over `maxSourceSize`', async () => {
+    const compiler = new Compiler({ maxSourceSize: 16 })
+    const message = `The source is ${SFC.length} bytes, which is more than the maximum of 16 bytes.`
+
+    expect(() => compiler.compileSync(SFC, options)).toThrow(message)
+    await expect(compiler.compileAsync(SFC, options)).rejects.toThrow(message)
+    expect(new Compiler({ maxSourceSize: SFC.length }).compileSync(SFC, options).errors).toHaveLength(0)
+  })
+
+  test('should compile all the files with `maxConcurrency`', async () => {
+    const compiler = new Compiler({ maxConcurrency: 1 })
+    const results = await Promise.all(
+      Array.from({ length: 5 }, (_, idx) =>
+        compiler.compileAsync(`<template><div>${idx}</div></template>`, { ...options, filename: `${idx}.vue` }),
+      ),
+    )
+
+    results.forEach((result, idx) => expect(result.code).toContain(`"${idx}"`))
+  })
+
+  test('should abort the queued compilations with `maxConcurrency`', async () => {
+    const controller = new AbortController()
+    controller.abort()
+
+    await expect(
+      new Compiler({ maxConcurrency: 1 }).compileAsync(SFC, options, controller.signal),
+    ).rejects.toThrow('AbortError')
+  })
+})
+
+describe('hot reload', () => {
+  const SFC = `<template><div>{{ msg }}</div></template>\n<script setup>\nconst msg = 'hi'\n</script>`
+
+  test('should add the HMR code', () => {
+    const { code } = new Compiler().compileSync(SFC, { ...options, hotReload: true })
+
+    expect(code).toContain('__hmrId = ')
+    expect(code).toContain('import.meta.hot.accept(')
+    expect(code).not.toContain('_rerender_only = true')
+  })
+
+  test('should re-render after a template-only edit', () => {
+    const compiler = new Compiler()
+    const edited = SFC.replace('<div>', '<p>').replace('</div>', '</p>')
+
+    const templateEdit = compiler.compileSync(edited, { ...options, hotReload: true, prevSource: SFC })
+    expect(templateEdit.code).toContain('_rerender_only = true')
+
+    const scriptEdit = compiler.compileSync(SFC.replace("'hi'", "'hello'"), {
+      ...options,
+      hotReload: true,
+      prevSource: SFC,
+    })
+    expect(scriptEdit.code).not.toContain('_rerender_only = true')
+  })
+
+  test('should not add the HMR code in production', () => {
+    const { code } = new Compiler({ isProduction: true }).compileSync(SFC, { ...options, hotReload: true })
+
+    expect(code).not.toContain('__hmrId')
+  })
+})
@@ -7,17 +7,10 @@
 export interface FervidJsCompilerOptions {
   /** Apply production optimizations. Default: false */
   isProduction?: boolean
-  /**
-   * TODO Support SSR
-   * Enable SSR. Default: false
-   */
-  ssr?: boolean
-  /**
-   * TODO Find a performant solution to source-maps
-   * TODO Implement source-maps
-   * Enable source maps
-   */
-  sourceMap?: boolean
+  /** Generate the `ssrRender` function instead of `render`. Default: false */
+  ssr?: boolean | FervidJsCompilerOptionsSsr
+  /** Produce the source maps of the compiled modules. Default: false */
+  sourceMap?: boolean | FervidJsCompilerOptionsSourceMap
   /** Script compilation options */
   script?: FervidJsCompilerOptionsScript
   /** Template compilation options */
@@ -25,16 +18,116 @@ export interface FervidJsCompilerOptions {
   /** Style compilation options */
   style?: FervidJsCompilerOptionsStyle
   /**
-   * TODO Regex handling logic is needed (plus sanitation)
-   * TODO Implement custom element mode (low priority)
    * Transform Vue SFCs into custom elements.
-   *  - `true`: all `*.vue` imports are converted into custom elements
-   *  - `string | RegExp`: matched files are converted into custom elements
+   *  - `true`: all the files are compiled as custom elements
+   *  - `{ include }`: files ending with one of the suffixes are compiled as custom elements
+   * `isCustomElement` of the file options takes precedence.
    * Default: files ending with `.ce.vue`
    */
-  customElement?: undefined
+  customElement?: boolean | FervidJsCompilerOptionsCustomElement
+  /** Details added to the reported `errors` */
+  errorFormat?: FervidJsCompilerOptionsErrorFormat
+  /**
+   * Compile-time feature flags.
+   * These should match the `define` settings of the bundler.
+   */
+  features?: FervidJsCompilerFeatureFlags
+  /**
+   * Keep the output of the last compilation of each file and reuse it
+   * when the same file is compiled again with the same contents and options
+   * and its `deps` were not modified since.
+   * A cached result is shared between the calls, e.g. the same `errors` are reported again.
+   * Default: false
+   */
+  cache?: boolean
+  /**
+   * Maximum size of an SFC in bytes. Bigger files fail to compile without being parsed,
+   * which limits the memory used by a single compilation.
+   * Default and upper bound: about 4 GiB, the biggest file which the source maps can address
+   */
+  maxSourceSize?: number
+  /**
+   * Maximum number of files compiled at once by `compileAsync` of this compiler,
+   * e.g. to leave the CPU cores of a shared CI runner to the other jobs.
+   * The compilations above the limit wait in a queue.
+   * Default: limited by the worker threads of Node.js (`UV_THREADPOOL_SIZE`)
+   */
+  maxConcurrency?: number
+  /**
+   * Formatting of the emitted JavaScript.
+   *  - `pretty`: indented code with the patch flags annotated by their names
+   *  - `compact`: code without the whitespace
+   * Default: indented code without the comments
+   */
+  outputFormat?: 'pretty' | 'compact'
+  /**
+   * Produce a module from the parts of the malformed SFCs which could be parsed
+   * and report the rest in `errors` instead of failing the compilation,
+   * e.g. for the IDEs compiling the file while it is edited. Default: false
+   */
+  tolerant?: boolean
+  /**
+   * Import the custom blocks (e.g. `<i18n>`) from `code` as
+   * `<filename>?vue&type=<block>&index=<index>&lang.<lang>` and call their default exports
+   * with the component. The bundler has to serve these ids from `customBlocks`. Default: false
+   */
+  customBlockImports?: boolean
+}
+export interface FervidJsCompilerOptionsSsr {
+  /** Generate the `ssrRender` function. Default: true */
+  enabled?: boolean
+}
+export interface FervidJsCompilerOptionsSourceMap {
+  /** Produce the source maps. Default: true */
+  enabled?: boolean
+  /** Embed the source of the SFC in `sourcesContent`. Default: true */
+  sourcesContent?: boolean
+}
+export interface FervidJsCompilerOptionsCustomElement {
+  /** Suffixes of the files compiled as custom elements. Default: `['.ce.vue']` */
+  include?: Array<string>
+}
+export interface FervidJsCompilerOptionsErrorFormat {
+  /** Add the line and column of the erroneous code as `loc`. Default: false */
+  loc?: boolean
+  /**
+   * Add the erroneous lines of the source with the code underlined as `frame`,
+   * in the same format as `generateCodeFrame` of `@vue/shared`. Default: false
+   */
+  codeFrame?: boolean
+}
+export interface FervidJsCompilerOptionsTemplate {
+  /**
+   * Create the fully static elements once as module-level constants
+   * instead of on every render. Default: false
+   */
+  hoistStatic?: boolean
+  /**
+   * Minimum number of nodes in the consecutive static elements
+   * to create them from HTML. Only used with `hoistStatic`. Default: 20
+   */
+  stringifyStaticThreshold?: number
+  /**
+   * Cache the inline event handlers, so that the child components
+   * are not re-rendered because of the new function identities. Default: false
+   */
+  cacheHandlers?: boolean
+  /**
+   * Handling of the whitespace, same as in `@vue/compiler-core`.
+   *  - `condense`: remove the whitespace-only nodes between the elements and collapse the rest
+   *  - `preserve`: only remove the whitespace at the start and the end of the elements
+   * Default: condense
+   */
+  whitespace?: 'condense' | 'preserve'
+  /** Render the template comments. Default: `true` in development, `false` in production */
+  comments?: boolean
+}
+export interface FervidJsCompilerFeatureFlags {
+  /** `__VUE_OPTIONS_API__`. Default: true */
+  optionsApi?: boolean
+  /** `__VUE_PROD_DEVTOOLS__`. Default: false */
+  prodDevtools?: boolean
 }
-export interface FervidJsCompilerOptionsTemplate {}
 export interface FervidJsCompilerOptionsScript {
   /**
    * Ignored
@@ -49,49 +142,214 @@ export interface FervidJsCompilerOptionsScript {
 export interface FervidJsCompilerOptionsStyle {
   /** Ignored */
   trim?: boolean
+  /** Minify the emitted CSS. Default: false */
+  minify?: boolean
+  /**
+   * Pad the styles not compiled by fervid, so that their lines match the original file.
+   * Default: no padding
+   */
+  pad?: 'line' | 'space'
 }
 export interface FervidCompileOptions {
   /** Scope ID for prefixing injected CSS variables */
   id: string
   /** Filename is used for automatic component name inference and self-referential imports */
   filename: string
+  /**
+   * Scope ID of the component (e.g. `data-v-7ba5bd90` or `7ba5bd90`) used for the scoped styles,
+   * CSS variables and `__scopeId`, e.g. when the integration has its own hashing scheme.
+   * Default: hash of the source
+   */
+  scopeId?: string
   /**
    * Is the currently compiled file a custom element.
    * To give more flexibility, this option only accepts a boolean, allowing to compute the value on the JS side,
    * instead of relying on a hacky RegEx/JS function calls from the Fervid side.
    */
   isCustomElement?: boolean
+  /**
+   * Compile the SFC without scripts as a functional component (a plain render function of the props),
+   * e.g. for the presentational design-system primitives. Ignored in SSR. Default: false
+   */
+  functional?: boolean
   /** Generate a const instead of default export */
   genDefaultAs?: string
   /** Enable, disable or error on props destructure */
   propsDestructure?: boolean | 'error'
   /** Whether setup bindings need to be serialized */
   outputSetupBindings?: boolean
+  /**
+   * Append the HMR code, i.e. `__hmrId` and the `import.meta.hot.accept` handler.
+   * Ignored in production and SSR. Default: false
+   */
+  hotReload?: boolean
+  /**
+   * Previous source of the SFC, so that an edit of only the `<template>` re-renders the component
+   * instead of reloading it. Only used with `hotReload`
+   */
+  prevSource?: string
 }
 export interface CompileResult {
+  /** Compiled JS module of the SFC (`<script>`, `<script setup>` and `<template>`) */
   code: string
-  styles: Array<Style>
+  /**
+   * Style blocks in the order of their appearance.
+   * Compiled styles are always CSS, the others must be compiled by a preprocessor
+   */
+  styles: Array<Style & ({ isCompiled: true; lang: 'css' } | { isCompiled: false })>
+  /** Errors and warnings of all the compilation phases */
   errors: Array<SerializedError>
+  /** Blocks other than `<template>`, `<script>` and `<style>` */
   customBlocks: Array<CustomBlock>
+  /** Source map of `code`, only present when `sourceMap` is enabled */
   sourceMap?: string
+  /** Bindings of `<script setup>`, only present when `outputSetupBindings` is enabled */
   setupBindings?: Record<string, BindingTypes> | undefined
+  /** Whether the component has an async `setup` and needs to be rendered inside `<Suspense>` */
+  isAsyncSetup: boolean
+  /** Files the SFC depends on (type imports and style `@import`/`url()` references) */
+  deps: Array<string>
+  /** Hash of the SFC used in the `data-v-` attribute of scoped styles */
+  scopeId: string
+  /** `v-for` directives of the template with their keys, in order of appearance */
+  vForKeys: Array<VForKey>
+  /** Runtime helpers imported by each part of `code` */
+  helpers: Helpers
+}
+export interface FervidCompileStyleOptions {
+  /** Scope ID, e.g. `data-v-7ba5bd90` */
+  id: string
+  /** Whether the style is scoped. Default: false */
+  scoped?: boolean
+  /** Minify the output. Default: `style.minify` of the compiler options */
+  minify?: boolean
+}
+export interface CompileStyleResult {
+  /** Compiled CSS */
+  code: string
+  errors: Array<SerializedError>
+  /** Files referenced by `@import` and `url()` */
+  deps: Array<string>
+}
+export interface FervidCompileTemplateOptions {
+  /** Filename is used for source maps */
+  filename: string
+  /**
+   * Prefix the template identifiers, e.g. `_ctx.foo`. Default: true.
+   * When disabled, the code is a function body returning the render function,
+   * which resolves the identifiers using `with (_ctx)` and is meant for `new Function()`.
+   */
+  prefixIdentifiers?: boolean
+}
+export interface CompileTemplateResult {
+  /** Module exporting the `render` function */
+  code: string
+  errors: Array<SerializedError>
+  sourceMap?: string
+}
+export interface CacheStats {
+  /** Compilations served from the cache */
+  hits: number
+  /** Compilations which were not found in the cache */
+  misses: number
+  /** Files currently in the cache */
+  entries: number
 }
 export interface Style {
+  /** Contents of the block, compiled when `isCompiled` is `true` */
   code: string
+  /**
+   * Whether fervid already scoped or minified the CSS.
+   * Styles in other languages must be compiled by a preprocessor.
+   */
   isCompiled: boolean
-  lang: string
+  /** Value of the `lang` attribute, `css` by default */
+  lang: 'css' | 'scss' | 'sass' | 'less' | 'stylus' | (string & {})
+  /** Whether the block has the `scoped` attribute */
   isScoped: boolean
+  /**
+   * Name of the CSS Module, e.g. `$style` for `<style module>`.
+   * Modules which are not compiled by fervid must be assigned to `__cssModules[module]`
+   * after the preprocessor, e.g. `__cssModules["$style"] = style0`
+   */
+  module?: string
 }
 export interface CustomBlock {
+  /** Raw contents of the block */
   content: string
+  /** Byte offset of the block start in the source (0-based) */
   lo: number
+  /** Byte offset of the block end in the source (0-based) */
   hi: number
+  /** Tag name of the block, e.g. `i18n` for `<i18n>` */
   tagName: string
+  /** Value of the `lang` attribute, e.g. `json` for `<i18n lang="json">` */
+  lang?: string
+  /** Static attributes of the block */
+  attrs: Record<string, string>
+  /** Id under which the block is imported by `code`, only present with `customBlockImports` */
+  importId?: string
 }
-export interface SerializedError {
+export interface VForKey {
+  /** Byte offset of the `v-for` directive start in the source (0-based) */
   lo: number
+  /** Byte offset of the `v-for` directive end in the source (0-based) */
   hi: number
+  /** Compiled `key` expression, absent when the list is not keyed */
+  key?: string
+}
+/**
+ * Runtime helpers imported by the parts of a compiled module, by their exported names,
+ * e.g. for deduplicating the helper imports when concatenating the modules
+ */
+export interface Helpers {
+  /** Helpers from `vue` used by `<script>` and `<script setup>` */
+  script: Array<string>
+  /** Helpers from `vue` used by the render function */
+  template: Array<string>
+  /** Helpers from `vue/server-renderer` used by the `ssrRender` function */
+  ssr: Array<string>
+}
+export interface SerializedError {
+  /**
+   * Byte offset of the erroneous code start in the source (0-based),
+   * `undefined` when the error has no position
+   */
+  lo?: number
+  /**
+   * Byte offset of the erroneous code end in the source (0-based),
+   * `undefined` when the error has no position
+   */
+  hi?: number
+  /** Human-readable description of the error */
   message: string
+  /** Stable identifier of the error prefixed with the compilation phase, e.g. `parse/duplicate-template` */
+  code: string
+  /**
+   * Only the errors fail the compilation of the SFC by the bundlers,
+   * the warnings point to the suspicious code
+   */
+  severity: 'error' | 'warning'
+  /**
+   * Position of the erroneous code, only present with `errorFormat.loc`
+   * when the error has a position
+   */
+  loc?: SourceLocation
+  /**
+   * Erroneous lines of the source, only present with `errorFormat.codeFrame`
+   * when the error has a position
+   */
+  frame?: string
+}
+export interface SourceLocation {
+  start: Position
+  end: Position
+}
+export interface Position {
+  /** 1-based line number */
+  line: number
+  /** 1-based column in UTF-16 code units, i.e. an index into the line as a JS string plus one */
+  column: number
 }
 /**
  * This is a copied enum from `fervid_core` with `napi` implementation to avoid littering the core crate.
@@ -140,11 +398,47 @@ export const enum BindingTypes {
   /** a non-resolved variable, presumably from the global Vue context */
   UNRESOLVED = 14,
 }
+/**
+ * Compiles the SFC with the default compiler options, e.g. from a bundler loader.
+ * Use `Compiler` to configure the compilation and to cache the results.
+ */
+export declare function compileSync(source: string, options: FervidCompileOptions): CompileResult
+/**
+ * Compiles the SFC with the default compiler options on a worker thread.
+ * Several files compiled at once are compiled in parallel.
+ */
+export declare function compileAsync(source: string, options: FervidCompileOptions): Promise<CompileResult>
 export type FervidJsCompiler = Compiler
 /** Fervid: a compiler for Vue.js written in Rust */
 export declare class Compiler {
   options: FervidJsCompilerOptions
   constructor(options?: FervidJsCompilerOptions | undefined | null)
   compileSync(source: string, options: FervidCompileOptions): CompileResult
-  compileAsync(source: string, options: FervidCompileOptions, signal?: AbortSignal | undefined | null): Promise<unknown>
+  /**
+   * Compiles the SFC on a worker thread.
+   * When `signal` is aborted, the compilation stops at the next phase boundary
+   * and the promise is rejected with an `AbortError`.
+   */
+  compileAsync(source: string, options: FervidCompileOptions, signal?: AbortSignal | undefined | null): Promise<CompileResult>
+  /** Compiles a CSS style block independently from an SFC */
+  compileStyle(source: string, options: FervidCompileStyleOptions): CompileStyleResult
+  compileStyleAsync(source: string, options: FervidCompileStyleOptions, signal?: AbortSignal | undefined | null): Promise<CompileStyleResult>
+  /**
+   * Compiles the contents of a `<template>` independently from an SFC.
+   * The result is a module exporting the `render` function.
+   */
+  compileTemplate(source: string, options: FervidCompileTemplateOptions): CompileTemplateResult
+  /**
+   * Key of the compilation in the cache of this compiler: a hash of the source,
+   * the normalized compiler and file options and the version of fervid.
+   * The key is stable across processes, so external build caches can use it as well.
+   * The files read while compiling, e.g. the imported types, are not a part of the key:
+   * external caches must also track the `deps` of the result and invalidate on their changes.
+   * `null` when the compilation cannot be cached, see `fervid::cache::cache_key`.
+   */
+  cacheKey(source: string, options: FervidCompileOptions): string | null
+  /** Statistics of the compilation cache */
+  cacheStats(): CacheStats
+  /** Drops all the cached compilations and resets the statistics */
+  clearCache(): void
 }
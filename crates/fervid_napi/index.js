@@ -284,7 +284,9 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Compiler, BindingTypes } = nativeBinding
+const { Compiler, compileSync, compileAsync, BindingTypes } = nativeBinding
 
 module.exports.Compiler = Compiler
+module.exports.compileSync = compileSync
+module.exports.compileAsync = compileAsync
 module.exports.BindingTypes = BindingTypes
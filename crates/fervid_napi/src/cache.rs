@@ -0,0 +1,116 @@
+//! Compilation cache which lives as long as the `Compiler` instance.
+//!
+//! Dev servers request the same SFC several times (e.g. once per block),
+//! so the output of the last compilation of each file is kept and reused
+//! until the file contents, the compile options or the files it depends on change.
+
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
+use crate::structs::{CacheStats, CustomBlock, Helpers, SerializedError, Style, VForKey};
+
+/// Compilation output which does not depend on the JS environment,
+/// so that it can be cached and shared between threads
+pub struct CompileOutput {
+    pub code: String,
+    pub styles: Vec<Style>,
+    pub errors: Vec<SerializedError>,
+    pub custom_blocks: Vec<CustomBlock>,
+    pub source_map: Option<String>,
+    pub setup_bindings: Vec<(String, fervid::BindingTypes)>,
+    pub is_async_setup: bool,
+    pub deps: Vec<String>,
+    pub scope_id: String,
+    pub v_for_keys: Vec<VForKey>,
+    pub helpers: Helpers,
+}
+
+#[derive(Default)]
+pub struct CompileCache {
+    /// One entry per filename, so that outdated versions of a file get evicted
+    entries: HashMap<String, CacheEntry>,
+    hits: u32,
+    misses: u32,
+}
+
+struct CacheEntry {
+    /// See [`fervid::cache::cache_key`]
+    key: String,
+    output: Arc<CompileOutput>,
+    /// Modification times of the `deps` of the output, e.g. of the files with the imported types.
+    /// They are not a part of the key, because they are only known after compiling
+    deps_modified: Vec<Option<SystemTime>>,
+}
+
+impl CompileCache {
+    pub fn get(&mut self, filename: &str, key: &str) -> Option<Arc<CompileOutput>> {
+        match self.entries.get(filename) {
+            Some(entry)
+                if entry.key == key && entry.deps_modified == deps_modified(&entry.output) =>
+            {
+                self.hits += 1;
+                Some(entry.output.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, filename: String, key: String, output: Arc<CompileOutput>) {
+        let deps_modified = deps_modified(&output);
+        self.entries.insert(
+            filename,
+            CacheEntry {
+                key,
+                output,
+                deps_modified,
+            },
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len() as u32,
+        }
+    }
+}
+
+/// A dependency which cannot be read, e.g. a style `@import` of a package, stays `None`
+fn deps_modified(output: &CompileOutput) -> Vec<Option<SystemTime>> {
+    output
+        .deps
+        .iter()
+        .map(|dep| std::fs::metadata(dep).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+impl From<fervid::CompileResult> for CompileOutput {
+    fn from(result: fervid::CompileResult) -> Self {
+        CompileOutput {
+            code: result.code,
+            styles: result.styles.into_iter().map(From::from).collect(),
+            errors: result.errors.into_iter().map(From::from).collect(),
+            custom_blocks: result.other_assets.into_iter().map(From::from).collect(),
+            source_map: result.source_map,
+            setup_bindings: result
+                .setup_bindings
+                .into_iter()
+                .map(|binding| (binding.0.to_string(), binding.1))
+                .collect(),
+            is_async_setup: result.is_async_setup,
+            deps: result.deps,
+            scope_id: result.file_hash,
+            v_for_keys: result.v_for_keys.into_iter().map(From::from).collect(),
+            helpers: result.helpers.into(),
+        }
+    }
+}
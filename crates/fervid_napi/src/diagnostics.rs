@@ -0,0 +1,149 @@
+//! Serialization of the errors with the details requested by `errorFormat`
+
+use std::cmp::Ordering;
+
+use fervid::errors::CompileError;
+
+use crate::structs::{
+    FervidJsCompilerOptionsErrorFormat, Position, SerializedError, SourceLocation,
+};
+
+/// Lines shown before and after the erroneous lines of a code frame, same as in `@vue/shared`
+const CODE_FRAME_RANGE: usize = 2;
+
+pub fn serialize_errors(
+    errors: Vec<CompileError>,
+    source: &str,
+    format: Option<&FervidJsCompilerOptionsErrorFormat>,
+) -> Vec<SerializedError> {
+    let with_loc = format.and_then(|format| format.loc).unwrap_or_default();
+    let with_frame = format
+        .and_then(|format| format.code_frame)
+        .unwrap_or_default();
+
+    errors
+        .into_iter()
+        .map(|error| {
+            let mut serialized = SerializedError::from(error);
+            let (Some(lo), Some(hi)) = (serialized.lo, serialized.hi) else {
+                return serialized;
+            };
+            if !(with_loc || with_frame) {
+                return serialized;
+            }
+
+            let lo = floor_char_boundary(source, lo as usize);
+            let hi = floor_char_boundary(source, hi as usize);
+            if with_loc {
+                serialized.loc = Some(SourceLocation {
+                    start: position(source, lo),
+                    end: position(source, hi),
+                });
+            }
+            if with_frame {
+                serialized.frame = Some(code_frame(
+                    source,
+                    utf16_len(&source[..lo]),
+                    utf16_len(&source[..hi]),
+                ));
+            }
+
+            serialized
+        })
+        .collect()
+}
+
+/// Position of the byte `offset` in the `source`
+fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+    Position {
+        line: before.matches('\n').count() as u32 + 1,
+        column: utf16_len(&before[line_start..]) as u32 + 1,
+    }
+}
+
+/// Port of `generateCodeFrame` from `@vue/shared`, `start` and `end` are in UTF-16 code units
+fn code_frame(source: &str, start: usize, end: usize) -> String {
+    // Lines with the lengths of their line breaks
+    let lines: Vec<(&str, usize)> = source
+        .split('\n')
+        .map(|line| match line.strip_suffix('\r') {
+            Some(line) => (line, 2),
+            None => (line, 1),
+        })
+        .collect();
+    let last_line_idx = lines.len() - 1;
+    let line_break_len = |idx: usize| {
+        if idx == last_line_idx {
+            0
+        } else {
+            lines[idx].1
+        }
+    };
+
+    let mut result = Vec::new();
+    let mut count = 0;
+    for (idx, (line, _)) in lines.iter().enumerate() {
+        count += utf16_len(line) + line_break_len(idx);
+        if count < start {
+            continue;
+        }
+
+        let mut frame_idx = idx.saturating_sub(CODE_FRAME_RANGE);
+        while frame_idx < lines.len() && (frame_idx <= idx + CODE_FRAME_RANGE || end > count) {
+            let frame_line = lines[frame_idx].0;
+            let line_number = (frame_idx + 1).to_string();
+            result.push(format!(
+                "{line_number}{}|  {frame_line}",
+                " ".repeat(3usize.saturating_sub(line_number.len()))
+            ));
+
+            let line_len = utf16_len(frame_line);
+            let line_break_len = line_break_len(frame_idx);
+            match frame_idx.cmp(&idx) {
+                Ordering::Equal => {
+                    // Underline the erroneous part of the line
+                    let pad = start - (count - (line_len + line_break_len));
+                    let underline_len = if end > count {
+                        line_len.saturating_sub(pad)
+                    } else {
+                        end - start
+                    };
+                    result.push(format!(
+                        "   |  {}{}",
+                        " ".repeat(pad),
+                        "^".repeat(underline_len.max(1))
+                    ));
+                }
+                Ordering::Greater => {
+                    if end > count {
+                        let underline_len = (end - count).min(line_len).max(1);
+                        result.push(format!("   |  {}", "^".repeat(underline_len)));
+                    }
+                    count += line_len + line_break_len;
+                }
+                Ordering::Less => {}
+            }
+
+            frame_idx += 1;
+        }
+        break;
+    }
+
+    result.join("\n")
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+/// Offset clamped to the `source` and moved back to the start of a character
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
@@ -4,17 +4,35 @@
 #[global_allocator]
 static ALLOC: mimalloc_rust::GlobalMiMalloc = mimalloc_rust::GlobalMiMalloc;
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
-use fervid_transform::PropsDestructureConfig;
-use napi::bindgen_prelude::*;
+use fervid_transform::{FeatureFlags, PropsDestructureConfig};
+use napi::{bindgen_prelude::*, JsObject, Ref};
 use napi_derive::napi;
 
-use fervid::{compile, CompileOptions};
+use cache::CompileOutput;
+use diagnostics::serialize_errors;
+use fervid::{
+    cache::cache_key, compile, compile_style, compile_template, errors::CompileError, BlockPadding,
+    CompileMode, CompileOptions, CompileStyleOptions, CompileTemplateOptions, OsFileSystem,
+    OutputFormat, WhitespaceMode,
+};
+use pool::WorkerPool;
 use structs::{
-    BindingTypes, CompileResult, FervidCompileOptions, FervidJsCompiler, FervidJsCompilerOptions,
+    BindingTypes, CacheStats, CompileResult, CompileStyleResult, CompileTemplateResult,
+    FervidCompileOptions, FervidCompileStyleOptions, FervidCompileTemplateOptions,
+    FervidJsCompiler, FervidJsCompilerOptions, FervidJsCompilerOptionsCustomElement,
 };
 
+mod cache;
+mod diagnostics;
+mod pool;
 mod structs;
 
 #[napi]
@@ -22,7 +40,14 @@ impl FervidJsCompiler {
     #[napi(constructor)]
     pub fn new(options: Option<FervidJsCompilerOptions>) -> Self {
         let options = options.unwrap_or_else(Default::default);
-        FervidJsCompiler { options }
+        let pool = options
+            .max_concurrency
+            .map(|max_concurrency| Arc::new(WorkerPool::new(max_concurrency as usize)));
+        FervidJsCompiler {
+            options,
+            cache: Default::default(),
+            pool,
+        }
     }
 
     #[napi]
@@ -32,31 +57,349 @@ impl FervidJsCompiler {
         source: String,
         options: FervidCompileOptions,
     ) -> Result<CompileResult> {
-        let compiled = compile_impl(self, &source, &options)?;
-        Ok(convert(env, compiled, &options))
+        let compiled = compile_cached(self, &source, &options, None)?;
+        Ok(convert(env, &compiled, &options))
     }
 
-    #[napi]
+    /// Compiles the SFC on a worker thread.
+    /// When `signal` is aborted, the compilation stops at the next phase boundary
+    /// and the promise is rejected with an `AbortError`.
+    #[napi(ts_return_type = "Promise<CompileResult>")]
     pub fn compile_async(
         &self,
+        env: Env,
         source: String,
         options: FervidCompileOptions,
+        #[napi(ts_arg_type = "AbortSignal | undefined | null")] signal: Option<JsObject>,
+    ) -> Result<Either<AsyncTask<CompileTask>, JsObject>> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let abort_listener = match signal {
+            Some(signal) => AbortListener::new(env, signal, cancelled.clone())?,
+            None => None,
+        };
+
+        let mut task = CompileTask {
+            compiler: self.to_owned(),
+            input: source,
+            options,
+            cancelled,
+            abort_listener,
+        };
+
+        let Some(ref pool) = self.pool else {
+            return Ok(Either::A(AsyncTask::new(task)));
+        };
+
+        // The cancellation is checked by the compilation, also when it waited in the queue
+        let (deferred, promise) = env.create_deferred()?;
+        pool.execute(move || {
+            let result = task.compute();
+            deferred.resolve(move |env| {
+                task.finally(env)?;
+                result.and_then(|output| task.resolve(env, output))
+            })
+        });
+        Ok(Either::B(promise))
+    }
+
+    /// Compiles a CSS style block independently from an SFC
+    #[napi]
+    pub fn compile_style(
+        &self,
+        source: String,
+        options: FervidCompileStyleOptions,
+    ) -> CompileStyleResult {
+        compile_style_impl(self, &source, &options)
+    }
+
+    #[napi(ts_return_type = "Promise<CompileStyleResult>")]
+    pub fn compile_style_async(
+        &self,
+        source: String,
+        options: FervidCompileStyleOptions,
         signal: Option<AbortSignal>,
-    ) -> AsyncTask<CompileTask> {
-        let task = CompileTask {
+    ) -> AsyncTask<CompileStyleTask> {
+        let task = CompileStyleTask {
             compiler: self.to_owned(),
             input: source,
             options,
         };
         AsyncTask::with_optional_signal(task, signal)
     }
+
+    /// Compiles the contents of a `<template>` independently from an SFC.
+    /// The result is a module exporting the `render` function.
+    #[napi]
+    pub fn compile_template(
+        &self,
+        source: String,
+        options: FervidCompileTemplateOptions,
+    ) -> Result<CompileTemplateResult> {
+        let compiled = compile_template(
+            &source,
+            CompileTemplateOptions {
+                filename: Cow::Borrowed(&options.filename),
+                is_prod: self.options.is_production,
+                source_map: source_map(self),
+                prefix_identifiers: options.prefix_identifiers,
+                whitespace: whitespace_mode(self),
+                comments: self
+                    .options
+                    .template
+                    .as_ref()
+                    .and_then(|template| template.comments),
+            },
+        )
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+
+        Ok(CompileTemplateResult {
+            code: compiled.code,
+            errors: serialize_errors(compiled.errors, &source, self.options.error_format.as_ref()),
+            source_map: compiled
+                .source_map
+                .map(|source_map| format_source_map(self, source_map)),
+        })
+    }
+
+    /// Key of the compilation in the cache of this compiler: a hash of the source,
+    /// the normalized compiler and file options and the version of fervid.
+    /// The key is stable across processes, so external build caches can use it as well.
+    /// The files read while compiling, e.g. the imported types, are not a part of the key:
+    /// external caches must also track the `deps` of the result and invalidate on their changes.
+    /// `null` when the compilation cannot be cached, see `fervid::cache::cache_key`.
+    #[napi]
+    pub fn cache_key(&self, source: String, options: FervidCompileOptions) -> Option<String> {
+        cache_key(&source, &compile_options(self, &options, None))
+    }
+
+    /// Statistics of the compilation cache
+    #[napi]
+    pub fn cache_stats(&self) -> CacheStats {
+        self.lock_cache().stats()
+    }
+
+    /// Drops all the cached compilations and resets the statistics
+    #[napi]
+    pub fn clear_cache(&self) {
+        self.lock_cache().clear();
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, cache::CompileCache> {
+        // The cache does not hold invariants which a panic could break
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Compiles the SFC with the default compiler options, e.g. from a bundler loader.
+/// Use `Compiler` to configure the compilation and to cache the results.
+#[napi]
+pub fn compile_sync(
+    env: Env,
+    source: String,
+    options: FervidCompileOptions,
+) -> Result<CompileResult> {
+    let compiler = default_compiler();
+    let compiled = compile_cached(&compiler, &source, &options, None)?;
+    Ok(convert(env, &compiled, &options))
+}
+
+/// Compiles the SFC with the default compiler options on a worker thread.
+/// Several files compiled at once are compiled in parallel.
+#[napi(ts_return_type = "Promise<CompileResult>")]
+pub fn compile_async(source: String, options: FervidCompileOptions) -> AsyncTask<CompileTask> {
+    let task = CompileTask {
+        compiler: default_compiler(),
+        input: source,
+        options,
+        cancelled: Default::default(),
+        abort_listener: None,
+    };
+    AsyncTask::new(task)
+}
+
+/// A one-off compiler, without the cache which would never be reused
+fn default_compiler() -> FervidJsCompiler {
+    FervidJsCompiler::new(None)
+}
+
+fn compile_style_impl(
+    compiler: &FervidJsCompiler,
+    source: &str,
+    options: &FervidCompileStyleOptions,
+) -> CompileStyleResult {
+    let minify = options.minify.or_else(|| {
+        compiler
+            .options
+            .style
+            .as_ref()
+            .and_then(|style| style.minify)
+    });
+
+    let compiled = compile_style(
+        source,
+        CompileStyleOptions {
+            id: Cow::Borrowed(&options.id),
+            scoped: options.scoped,
+            minify,
+        },
+    );
+
+    CompileStyleResult {
+        code: compiled.code,
+        errors: serialize_errors(
+            compiled.errors,
+            source,
+            compiler.options.error_format.as_ref(),
+        ),
+        deps: compiled.deps,
+    }
+}
+
+/// `abort` listener of the signal passed to `compileAsync`.
+/// It is removed once the compilation settles, together with the `cancelled` flag it holds.
+struct AbortListener {
+    signal: Ref<()>,
+    on_abort: Ref<()>,
+}
+
+impl AbortListener {
+    /// Marks the compilation as cancelled once `signal` is aborted.
+    /// No listener is added when the signal is already aborted.
+    fn new(env: Env, signal: JsObject, cancelled: Arc<AtomicBool>) -> Result<Option<Self>> {
+        let is_aborted: bool = signal.get_named_property("aborted")?;
+        if is_aborted {
+            cancelled.store(true, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        let on_abort = env.create_function_from_closure("onAbort", move |ctx| {
+            cancelled.store(true, Ordering::Relaxed);
+            ctx.env.get_undefined()
+        })?;
+        let mut listener_options = env.create_object()?;
+        listener_options.set_named_property("once", true)?;
+        let add_event_listener: JsFunction = signal.get_named_property("addEventListener")?;
+        let event = env.create_string("abort")?;
+        let listener = AbortListener {
+            signal: env.create_reference(&signal)?,
+            on_abort: env.create_reference(&on_abort)?,
+        };
+
+        let added = add_event_listener.call(
+            Some(&signal),
+            &[
+                event.into_unknown(),
+                on_abort.into_unknown(),
+                listener_options.into_unknown(),
+            ],
+        );
+        if let Err(e) = added {
+            listener.remove(env)?;
+            return Err(e);
+        }
+
+        Ok(Some(listener))
+    }
+
+    fn remove(mut self, env: Env) -> Result<()> {
+        let removed = (|| {
+            let signal: JsObject = env.get_reference_value(&self.signal)?;
+            let on_abort: JsFunction = env.get_reference_value(&self.on_abort)?;
+            let remove_event_listener: JsFunction =
+                signal.get_named_property("removeEventListener")?;
+            remove_event_listener.call(
+                Some(&signal),
+                &[
+                    env.create_string("abort")?.into_unknown(),
+                    on_abort.into_unknown(),
+                ],
+            )?;
+            Ok(())
+        })();
+
+        // The references are released even when the removal failed
+        self.signal.unref(env)?;
+        self.on_abort.unref(env)?;
+        removed
+    }
+}
+
+/// Same error as the one used by `napi` when a task is aborted before it starts
+fn abort_error() -> Error {
+    Error::new(Status::Cancelled, "AbortError".to_string())
+}
+
+/// Compiles the SFC or takes the output from the compiler cache
+fn compile_cached(
+    compiler: &FervidJsCompiler,
+    source: &str,
+    options: &FervidCompileOptions,
+    cancellation: Option<&AtomicBool>,
+) -> Result<Arc<CompileOutput>> {
+    let key = match compiler.options.cache {
+        Some(true) => cache_key(source, &compile_options(compiler, options, None)),
+        _ => None,
+    };
+    let Some(key) = key else {
+        let compiled = compile_impl(compiler, source, options, cancellation)?;
+        return Ok(Arc::new(compile_output(compiler, source, compiled)));
+    };
+
+    if let Some(cached) = compiler.lock_cache().get(&options.filename, &key) {
+        return Ok(cached);
+    }
+
+    // The lock is not held while compiling, other files can be compiled in parallel
+    let compiled = compile_impl(compiler, source, options, cancellation)?;
+    let output = Arc::new(compile_output(compiler, source, compiled));
+    compiler
+        .lock_cache()
+        .insert(options.filename.to_owned(), key, output.clone());
+
+    Ok(output)
+}
+
+/// Converts the result to the cached output with the errors and the source map formatted
+/// according to the compiler options
+fn compile_output(
+    compiler: &FervidJsCompiler,
+    source: &str,
+    mut compiled: fervid::CompileResult,
+) -> CompileOutput {
+    let errors = serialize_errors(
+        std::mem::take(&mut compiled.errors),
+        source,
+        compiler.options.error_format.as_ref(),
+    );
+
+    let mut output = CompileOutput::from(compiled);
+    output.errors = errors;
+    output.source_map = output
+        .source_map
+        .map(|source_map| format_source_map(compiler, source_map));
+    output
 }
 
 fn compile_impl(
     compiler: &FervidJsCompiler,
     source: &str,
     options: &FervidCompileOptions,
+    cancellation: Option<&AtomicBool>,
 ) -> Result<fervid::CompileResult> {
+    let compile_options = compile_options(compiler, options, cancellation);
+
+    compile(source, compile_options).map_err(|e| match e {
+        CompileError::Cancelled => abort_error(),
+        e => Error::from_reason(e.to_string()),
+    })
+}
+
+/// Normalizes the options to the ones defined in fervid
+fn compile_options<'o>(
+    compiler: &'o FervidJsCompiler,
+    options: &'o FervidCompileOptions,
+    cancellation: Option<&'o AtomicBool>,
+) -> CompileOptions<'o> {
     let props_destructure = match options.props_destructure {
         Some(Either::A(true)) => Some(PropsDestructureConfig::True),
         Some(Either::A(false)) => Some(PropsDestructureConfig::False),
@@ -64,35 +407,170 @@ fn compile_impl(
         _ => None,
     };
 
-    // Normalize options to the ones defined in fervid
-    let compile_options = CompileOptions {
+    let feature_flags = compiler.options.features.as_ref().map(|features| {
+        let defaults = FeatureFlags::default();
+        FeatureFlags {
+            options_api: features.options_api.unwrap_or(defaults.options_api),
+            prod_devtools: features.prod_devtools.unwrap_or(defaults.prod_devtools),
+        }
+    });
+
+    CompileOptions {
         filename: Cow::Borrowed(&options.filename),
         id: Cow::Borrowed(&options.id),
+        scope_id: options.scope_id.as_deref().map(Cow::Borrowed),
         is_prod: compiler.options.is_production,
-        is_custom_element: options.is_custom_element,
+        is_custom_element: options
+            .is_custom_element
+            .or_else(|| Some(is_custom_element(compiler, &options.filename))),
         props_destructure,
-        ssr: compiler.options.ssr,
+        ssr: ssr(compiler),
+        is_native_tag: None,
+        // Node has file system access, resolve the imported types the same way as the official compiler
+        fs: Some(Arc::new(OsFileSystem)),
         gen_default_as: options
             .gen_default_as
             .as_ref()
             .map(|v| Cow::Borrowed(v.as_str())),
-        source_map: compiler.options.source_map,
+        source_map: source_map(compiler),
+        warn_unused_bindings: None,
+        suspense: None,
+        feature_flags,
+        minify_css: compiler
+            .options
+            .style
+            .as_ref()
+            .and_then(|style| style.minify),
+        hoist_static: compiler
+            .options
+            .template
+            .as_ref()
+            .and_then(|template| template.hoist_static),
+        stringify_static_threshold: compiler
+            .options
+            .template
+            .as_ref()
+            .and_then(|template| template.stringify_static_threshold)
+            .map(|threshold| threshold as usize),
+        cache_handlers: compiler
+            .options
+            .template
+            .as_ref()
+            .and_then(|template| template.cache_handlers),
+        whitespace: whitespace_mode(compiler),
+        comments: compiler
+            .options
+            .template
+            .as_ref()
+            .and_then(|template| template.comments),
+        functional: options.functional,
+        pad: match compiler
+            .options
+            .style
+            .as_ref()
+            .and_then(|style| style.pad.as_deref())
+        {
+            Some("line") => Some(BlockPadding::Line),
+            Some("space") => Some(BlockPadding::Space),
+            _ => None,
+        },
+        output_format: match compiler.options.output_format.as_deref() {
+            Some("pretty") => Some(OutputFormat::Pretty),
+            Some("compact") => Some(OutputFormat::Compact),
+            _ => None,
+        },
+        cancellation,
+        max_source_size: compiler
+            .options
+            .max_source_size
+            .map(|max_source_size| max_source_size as usize),
+        mode: match compiler.options.tolerant {
+            Some(true) => Some(CompileMode::Tolerant),
+            _ => None,
+        },
+        hot_reload: options.hot_reload,
+        prev_source: options.prev_source.as_deref().map(Cow::Borrowed),
+        expose_filename: None,
+        custom_block_imports: compiler.options.custom_block_imports,
+        script_preprocessor: None,
+        template_preprocessor: None,
+    }
+}
+
+/// `ssr` option, the object form enables SSR unless `enabled` is `false`
+fn ssr(compiler: &FervidJsCompiler) -> Option<bool> {
+    match compiler.options.ssr {
+        Some(Either::A(ssr)) => Some(ssr),
+        Some(Either::B(ref ssr)) => Some(ssr.enabled.unwrap_or(true)),
+        None => None,
+    }
+}
+
+/// `sourceMap` option, the object form enables the source maps unless `enabled` is `false`
+fn source_map(compiler: &FervidJsCompiler) -> Option<bool> {
+    match compiler.options.source_map {
+        Some(Either::A(source_map)) => Some(source_map),
+        Some(Either::B(ref source_map)) => Some(source_map.enabled.unwrap_or(true)),
+        None => None,
+    }
+}
+
+/// Drops `sourcesContent` from the source map when disabled by `sourceMap.sourcesContent`
+fn format_source_map(compiler: &FervidJsCompiler, source_map: String) -> String {
+    let sources_content = match compiler.options.source_map {
+        Some(Either::B(ref source_map)) => source_map.sources_content.unwrap_or(true),
+        _ => true,
     };
+    if sources_content {
+        return source_map;
+    }
 
-    compile(source, compile_options).map_err(|e| Error::from_reason(e.to_string()))
+    match serde_json::from_str::<serde_json::Value>(&source_map) {
+        Ok(mut parsed) => {
+            if let Some(parsed) = parsed.as_object_mut() {
+                parsed.remove("sourcesContent");
+            }
+            parsed.to_string()
+        }
+        Err(_) => source_map,
+    }
 }
 
-fn convert(
-    env: Env,
-    mut result: fervid::CompileResult,
-    options: &FervidCompileOptions,
-) -> CompileResult {
+/// Whether the file is a custom element according to the `customElement` option
+fn is_custom_element(compiler: &FervidJsCompiler, filename: &str) -> bool {
+    let is_included = |include: &[String]| include.iter().any(|suffix| filename.ends_with(suffix));
+
+    match compiler.options.custom_element {
+        Some(Either::A(is_custom_element)) => is_custom_element,
+        Some(Either::B(FervidJsCompilerOptionsCustomElement {
+            include: Some(ref include),
+        })) => is_included(include),
+        _ => filename.ends_with(".ce.vue"),
+    }
+}
+
+/// Whitespace handling from the `template.whitespace` option
+fn whitespace_mode(compiler: &FervidJsCompiler) -> Option<WhitespaceMode> {
+    let whitespace = compiler
+        .options
+        .template
+        .as_ref()
+        .and_then(|template| template.whitespace.as_deref());
+
+    match whitespace {
+        Some("condense") => Some(WhitespaceMode::Condense),
+        Some("preserve") => Some(WhitespaceMode::Preserve),
+        _ => None,
+    }
+}
+
+fn convert(env: Env, output: &CompileOutput, options: &FervidCompileOptions) -> CompileResult {
     // Serialize bindings if requested
     let setup_bindings = if matches!(options.output_setup_bindings, Some(true)) {
         env.create_object()
             .map(|mut obj| {
-                for binding in result.setup_bindings.drain(..) {
-                    let _ = obj.set(binding.0.as_str(), BindingTypes::from(binding.1));
+                for (name, binding_type) in output.setup_bindings.iter() {
+                    let _ = obj.set(name.as_str(), BindingTypes::from(*binding_type));
                 }
                 obj
             })
@@ -102,20 +580,17 @@ fn convert(
     };
 
     CompileResult {
-        code: result.code,
-        source_map: result.source_map,
-        custom_blocks: result
-            .other_assets
-            .into_iter()
-            .map(|asset| asset.into())
-            .collect(),
-        errors: result.errors.into_iter().map(|e| e.into()).collect(),
-        styles: result
-            .styles
-            .into_iter()
-            .map(|style| style.into())
-            .collect(),
+        code: output.code.to_owned(),
+        source_map: output.source_map.to_owned(),
+        custom_blocks: output.custom_blocks.to_owned(),
+        errors: output.errors.to_owned(),
+        styles: output.styles.to_owned(),
         setup_bindings,
+        is_async_setup: output.is_async_setup,
+        deps: output.deps.to_owned(),
+        scope_id: output.scope_id.to_owned(),
+        v_for_keys: output.v_for_keys.to_owned(),
+        helpers: output.helpers.to_owned(),
     }
 }
 
@@ -123,18 +598,66 @@ pub struct CompileTask {
     compiler: FervidJsCompiler,
     input: String,
     options: FervidCompileOptions,
+    cancelled: Arc<AtomicBool>,
+    abort_listener: Option<AbortListener>,
 }
 
 #[napi]
 impl Task for CompileTask {
     type JsValue = CompileResult;
-    type Output = fervid::CompileResult;
+    type Output = Arc<CompileOutput>;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
-        compile_impl(&self.compiler, &self.input, &self.options)
+        // Aborted while waiting for a thread, the cached output must not be returned either
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(abort_error());
+        }
+
+        compile_cached(
+            &self.compiler,
+            &self.input,
+            &self.options,
+            Some(&self.cancelled),
+        )
     }
 
     fn resolve(&mut self, env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
-        Ok(convert(env, result, &self.options))
+        // Aborted after the last phase boundary
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(abort_error());
+        }
+
+        Ok(convert(env, &result, &self.options))
+    }
+
+    fn finally(&mut self, env: Env) -> napi::Result<()> {
+        match self.abort_listener.take() {
+            Some(abort_listener) => abort_listener.remove(env),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct CompileStyleTask {
+    compiler: FervidJsCompiler,
+    input: String,
+    options: FervidCompileStyleOptions,
+}
+
+#[napi]
+impl Task for CompileStyleTask {
+    type JsValue = CompileStyleResult;
+    type Output = CompileStyleResult;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(compile_style_impl(
+            &self.compiler,
+            &self.input,
+            &self.options,
+        ))
+    }
+
+    fn resolve(&mut self, _env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(result)
     }
 }
@@ -0,0 +1,55 @@
+//! Dedicated threads for `compileAsync` when `maxConcurrency` limits the parallel compilations.
+//!
+//! The compilations above the limit wait in a queue instead of occupying
+//! the worker threads of Node.js, which are shared with e.g. the file system operations.
+
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Arc, Mutex,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+pub struct WorkerPool {
+    sender: Mutex<Sender<Job>>,
+}
+
+impl WorkerPool {
+    /// Starts `size` threads, they stop once the pool is dropped and the queued jobs are done
+    pub fn new(size: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for idx in 0..size.max(1) {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("fervid-worker-{idx}"))
+                .spawn(move || run_jobs(&receiver))
+                .expect("Failed to spawn a worker thread");
+        }
+
+        WorkerPool {
+            sender: Mutex::new(sender),
+        }
+    }
+
+    pub fn execute(&self, job: impl FnOnce() + Send + 'static) {
+        // The receivers only stop when the sender is dropped, so sending cannot fail
+        let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = sender.send(Box::new(job));
+    }
+}
+
+fn run_jobs(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = {
+            let receiver = receiver.lock().unwrap_or_else(|e| e.into_inner());
+            receiver.recv()
+        };
+
+        match job {
+            Ok(job) => job(),
+            Err(_) => break,
+        }
+    }
+}
@@ -1,12 +1,24 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use fervid::error::{ErrorCode, Severity};
 use napi::{Either, JsObject};
 use napi_derive::napi;
-use swc_core::common::Spanned;
+use swc_core::common::{BytePos, Spanned};
+
+use crate::{cache::CompileCache, pool::WorkerPool};
 
 /// Fervid: a compiler for Vue.js written in Rust
 #[napi(js_name = "Compiler")]
 #[derive(Clone)]
 pub struct FervidJsCompiler {
     pub options: FervidJsCompilerOptions,
+    /// Shared with the async tasks spawned by this compiler
+    pub(crate) cache: Arc<Mutex<CompileCache>>,
+    /// Threads of `compileAsync`, only present with `maxConcurrency`
+    pub(crate) pool: Option<Arc<WorkerPool>>,
 }
 
 /// Raw options passed from the Node.js side
@@ -16,14 +28,11 @@ pub struct FervidJsCompilerOptions {
     /// Apply production optimizations. Default: false
     pub is_production: Option<bool>,
 
-    /// TODO Support SSR
-    /// Enable SSR. Default: false
-    pub ssr: Option<bool>,
+    /// Generate the `ssrRender` function instead of `render`. Default: false
+    pub ssr: Option<Either<bool, FervidJsCompilerOptionsSsr>>,
 
-    /// TODO Find a performant solution to source-maps
-    /// TODO Implement source-maps
-    /// Enable source maps
-    pub source_map: Option<bool>,
+    /// Produce the source maps of the compiled modules. Default: false
+    pub source_map: Option<Either<bool, FervidJsCompilerOptionsSourceMap>>,
 
     /// Script compilation options
     pub script: Option<FervidJsCompilerOptionsScript>,
@@ -34,13 +43,54 @@ pub struct FervidJsCompilerOptions {
     /// Style compilation options
     pub style: Option<FervidJsCompilerOptionsStyle>,
 
-    /// TODO Regex handling logic is needed (plus sanitation)
-    /// TODO Implement custom element mode (low priority)
     /// Transform Vue SFCs into custom elements.
-    ///  - `true`: all `*.vue` imports are converted into custom elements
-    ///  - `string | RegExp`: matched files are converted into custom elements
+    ///  - `true`: all the files are compiled as custom elements
+    ///  - `{ include }`: files ending with one of the suffixes are compiled as custom elements
+    /// `isCustomElement` of the file options takes precedence.
     /// Default: files ending with `.ce.vue`
-    pub custom_element: Option<()>,
+    pub custom_element: Option<Either<bool, FervidJsCompilerOptionsCustomElement>>,
+
+    /// Details added to the reported `errors`
+    pub error_format: Option<FervidJsCompilerOptionsErrorFormat>,
+
+    /// Compile-time feature flags.
+    /// These should match the `define` settings of the bundler.
+    pub features: Option<FervidJsCompilerFeatureFlags>,
+
+    /// Keep the output of the last compilation of each file and reuse it
+    /// when the same file is compiled again with the same contents and options
+    /// and its `deps` were not modified since.
+    /// A cached result is shared between the calls, e.g. the same `errors` are reported again.
+    /// Default: false
+    pub cache: Option<bool>,
+
+    /// Maximum size of an SFC in bytes. Bigger files fail to compile without being parsed,
+    /// which limits the memory used by a single compilation.
+    /// Default and upper bound: about 4 GiB, the biggest file which the source maps can address
+    pub max_source_size: Option<u32>,
+
+    /// Maximum number of files compiled at once by `compileAsync` of this compiler,
+    /// e.g. to leave the CPU cores of a shared CI runner to the other jobs.
+    /// The compilations above the limit wait in a queue.
+    /// Default: limited by the worker threads of Node.js (`UV_THREADPOOL_SIZE`)
+    pub max_concurrency: Option<u32>,
+
+    /// Formatting of the emitted JavaScript.
+    ///  - `pretty`: indented code with the patch flags annotated by their names
+    ///  - `compact`: code without the whitespace
+    /// Default: indented code without the comments
+    #[napi(ts_type = "'pretty' | 'compact'")]
+    pub output_format: Option<String>,
+
+    /// Produce a module from the parts of the malformed SFCs which could be parsed
+    /// and report the rest in `errors` instead of failing the compilation,
+    /// e.g. for the IDEs compiling the file while it is edited. Default: false
+    pub tolerant: Option<bool>,
+
+    /// Import the custom blocks (e.g. `<i18n>`) from `code` as
+    /// `<filename>?vue&type=<block>&index=<index>&lang.<lang>` and call their default exports
+    /// with the component. The bundler has to serve these ids from `customBlocks`. Default: false
+    pub custom_block_imports: Option<bool>,
     // Ignored
     // pub compiler: Option<()>,
 
@@ -50,7 +100,67 @@ pub struct FervidJsCompilerOptions {
 
 #[napi(object)]
 #[derive(Clone)]
-pub struct FervidJsCompilerOptionsTemplate {}
+pub struct FervidJsCompilerOptionsSsr {
+    /// Generate the `ssrRender` function. Default: true
+    pub enabled: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerOptionsSourceMap {
+    /// Produce the source maps. Default: true
+    pub enabled: Option<bool>,
+    /// Embed the source of the SFC in `sourcesContent`. Default: true
+    pub sources_content: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerOptionsCustomElement {
+    /// Suffixes of the files compiled as custom elements. Default: `['.ce.vue']`
+    pub include: Option<Vec<String>>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerOptionsErrorFormat {
+    /// Add the line and column of the erroneous code as `loc`. Default: false
+    pub loc: Option<bool>,
+    /// Add the erroneous lines of the source with the code underlined as `frame`,
+    /// in the same format as `generateCodeFrame` of `@vue/shared`. Default: false
+    pub code_frame: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerOptionsTemplate {
+    /// Create the fully static elements once as module-level constants
+    /// instead of on every render. Default: false
+    pub hoist_static: Option<bool>,
+    /// Minimum number of nodes in the consecutive static elements
+    /// to create them from HTML. Only used with `hoistStatic`. Default: 20
+    pub stringify_static_threshold: Option<u32>,
+    /// Cache the inline event handlers, so that the child components
+    /// are not re-rendered because of the new function identities. Default: false
+    pub cache_handlers: Option<bool>,
+    /// Handling of the whitespace, same as in `@vue/compiler-core`.
+    ///  - `condense`: remove the whitespace-only nodes between the elements and collapse the rest
+    ///  - `preserve`: only remove the whitespace at the start and the end of the elements
+    /// Default: condense
+    #[napi(ts_type = "'condense' | 'preserve'")]
+    pub whitespace: Option<String>,
+    /// Render the template comments. Default: `true` in development, `false` in production
+    pub comments: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerFeatureFlags {
+    /// `__VUE_OPTIONS_API__`. Default: true
+    pub options_api: Option<bool>,
+    /// `__VUE_PROD_DEVTOOLS__`. Default: false
+    pub prod_devtools: Option<bool>,
+}
 
 #[napi(object)]
 #[derive(Clone)]
@@ -69,6 +179,12 @@ pub struct FervidJsCompilerOptionsScript {
 pub struct FervidJsCompilerOptionsStyle {
     /// Ignored
     pub trim: Option<bool>,
+    /// Minify the emitted CSS. Default: false
+    pub minify: Option<bool>,
+    /// Pad the styles not compiled by fervid, so that their lines match the original file.
+    /// Default: no padding
+    #[napi(ts_type = "'line' | 'space'")]
+    pub pad: Option<String>,
 }
 
 #[napi(object)]
@@ -80,11 +196,20 @@ pub struct FervidCompileOptions {
     /// Filename is used for automatic component name inference and self-referential imports
     pub filename: String,
 
+    /// Scope ID of the component (e.g. `data-v-7ba5bd90` or `7ba5bd90`) used for the scoped styles,
+    /// CSS variables and `__scopeId`, e.g. when the integration has its own hashing scheme.
+    /// Default: hash of the source
+    pub scope_id: Option<String>,
+
     /// Is the currently compiled file a custom element.
     /// To give more flexibility, this option only accepts a boolean, allowing to compute the value on the JS side,
     /// instead of relying on a hacky RegEx/JS function calls from the Fervid side.
     pub is_custom_element: Option<bool>,
 
+    /// Compile the SFC without scripts as a functional component (a plain render function of the props),
+    /// e.g. for the presentational design-system primitives. Ignored in SSR. Default: false
+    pub functional: Option<bool>,
+
     /// Generate a const instead of default export
     pub gen_default_as: Option<String>,
 
@@ -94,40 +219,198 @@ pub struct FervidCompileOptions {
 
     /// Whether setup bindings need to be serialized
     pub output_setup_bindings: Option<bool>,
+
+    /// Append the HMR code, i.e. `__hmrId` and the `import.meta.hot.accept` handler.
+    /// Ignored in production and SSR. Default: false
+    pub hot_reload: Option<bool>,
+
+    /// Previous source of the SFC, so that an edit of only the `<template>` re-renders the component
+    /// instead of reloading it. Only used with `hotReload`
+    pub prev_source: Option<String>,
 }
 
 #[napi(object)]
 pub struct CompileResult {
+    /// Compiled JS module of the SFC (`<script>`, `<script setup>` and `<template>`)
     pub code: String,
+    /// Style blocks in the order of their appearance.
+    /// Compiled styles are always CSS, the others must be compiled by a preprocessor
+    #[napi(ts_type = "Array<Style & ({ isCompiled: true; lang: 'css' } | { isCompiled: false })>")]
     pub styles: Vec<Style>,
+    /// Errors and warnings of all the compilation phases
     pub errors: Vec<SerializedError>,
+    /// Blocks other than `<template>`, `<script>` and `<style>`
     pub custom_blocks: Vec<CustomBlock>,
+    /// Source map of `code`, only present when `sourceMap` is enabled
     pub source_map: Option<String>,
+    /// Bindings of `<script setup>`, only present when `outputSetupBindings` is enabled
     #[napi(ts_type = "Record<string, BindingTypes> | undefined")]
     pub setup_bindings: Option<JsObject>,
+    /// Whether the component has an async `setup` and needs to be rendered inside `<Suspense>`
+    pub is_async_setup: bool,
+    /// Files the SFC depends on (type imports and style `@import`/`url()` references)
+    pub deps: Vec<String>,
+    /// Hash of the SFC used in the `data-v-` attribute of scoped styles
+    pub scope_id: String,
+    /// `v-for` directives of the template with their keys, in order of appearance
+    pub v_for_keys: Vec<VForKey>,
+    /// Runtime helpers imported by each part of `code`
+    pub helpers: Helpers,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidCompileStyleOptions {
+    /// Scope ID, e.g. `data-v-7ba5bd90`
+    pub id: String,
+
+    /// Whether the style is scoped. Default: false
+    pub scoped: Option<bool>,
+
+    /// Minify the output. Default: `style.minify` of the compiler options
+    pub minify: Option<bool>,
+}
+
+#[napi(object)]
+pub struct CompileStyleResult {
+    /// Compiled CSS
+    pub code: String,
+    pub errors: Vec<SerializedError>,
+    /// Files referenced by `@import` and `url()`
+    pub deps: Vec<String>,
 }
 
 #[napi(object)]
+#[derive(Clone)]
+pub struct FervidCompileTemplateOptions {
+    /// Filename is used for source maps
+    pub filename: String,
+
+    /// Prefix the template identifiers, e.g. `_ctx.foo`. Default: true.
+    /// When disabled, the code is a function body returning the render function,
+    /// which resolves the identifiers using `with (_ctx)` and is meant for `new Function()`.
+    pub prefix_identifiers: Option<bool>,
+}
+
+#[napi(object)]
+pub struct CompileTemplateResult {
+    /// Module exporting the `render` function
+    pub code: String,
+    pub errors: Vec<SerializedError>,
+    pub source_map: Option<String>,
+}
+
+#[napi(object)]
+pub struct CacheStats {
+    /// Compilations served from the cache
+    pub hits: u32,
+    /// Compilations which were not found in the cache
+    pub misses: u32,
+    /// Files currently in the cache
+    pub entries: u32,
+}
+
+#[napi(object)]
+#[derive(Clone)]
 pub struct Style {
+    /// Contents of the block, compiled when `isCompiled` is `true`
     pub code: String,
+    /// Whether fervid already scoped or minified the CSS.
+    /// Styles in other languages must be compiled by a preprocessor.
     pub is_compiled: bool,
+    /// Value of the `lang` attribute, `css` by default
+    #[napi(ts_type = "'css' | 'scss' | 'sass' | 'less' | 'stylus' | (string & {})")]
     pub lang: String,
+    /// Whether the block has the `scoped` attribute
     pub is_scoped: bool,
+    /// Name of the CSS Module, e.g. `$style` for `<style module>`.
+    /// Modules which are not compiled by fervid must be assigned to `__cssModules[module]`
+    /// after the preprocessor, e.g. `__cssModules["$style"] = style0`
+    pub module: Option<String>,
 }
 
 #[napi(object)]
+#[derive(Clone)]
 pub struct CustomBlock {
+    /// Raw contents of the block
     pub content: String,
+    /// Byte offset of the block start in the source (0-based)
     pub lo: u32,
+    /// Byte offset of the block end in the source (0-based)
     pub hi: u32,
+    /// Tag name of the block, e.g. `i18n` for `<i18n>`
     pub tag_name: String,
+    /// Value of the `lang` attribute, e.g. `json` for `<i18n lang="json">`
+    pub lang: Option<String>,
+    /// Static attributes of the block
+    pub attrs: HashMap<String, String>,
+    /// Id under which the block is imported by `code`, only present with `customBlockImports`
+    pub import_id: Option<String>,
 }
 
 #[napi(object)]
-pub struct SerializedError {
+#[derive(Clone)]
+pub struct VForKey {
+    /// Byte offset of the `v-for` directive start in the source (0-based)
     pub lo: u32,
+    /// Byte offset of the `v-for` directive end in the source (0-based)
     pub hi: u32,
+    /// Compiled `key` expression, absent when the list is not keyed
+    pub key: Option<String>,
+}
+
+/// Runtime helpers imported by the parts of a compiled module, by their exported names,
+/// e.g. for deduplicating the helper imports when concatenating the modules
+#[napi(object)]
+#[derive(Clone)]
+pub struct Helpers {
+    /// Helpers from `vue` used by `<script>` and `<script setup>`
+    pub script: Vec<String>,
+    /// Helpers from `vue` used by the render function
+    pub template: Vec<String>,
+    /// Helpers from `vue/server-renderer` used by the `ssrRender` function
+    pub ssr: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct SerializedError {
+    /// Byte offset of the erroneous code start in the source (0-based),
+    /// `undefined` when the error has no position
+    pub lo: Option<u32>,
+    /// Byte offset of the erroneous code end in the source (0-based),
+    /// `undefined` when the error has no position
+    pub hi: Option<u32>,
+    /// Human-readable description of the error
     pub message: String,
+    /// Stable identifier of the error prefixed with the compilation phase, e.g. `parse/duplicate-template`
+    pub code: String,
+    /// Only the errors fail the compilation of the SFC by the bundlers,
+    /// the warnings point to the suspicious code
+    #[napi(ts_type = "'error' | 'warning'")]
+    pub severity: String,
+    /// Position of the erroneous code, only present with `errorFormat.loc`
+    /// when the error has a position
+    pub loc: Option<SourceLocation>,
+    /// Erroneous lines of the source, only present with `errorFormat.codeFrame`
+    /// when the error has a position
+    pub frame: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct SourceLocation {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct Position {
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column in UTF-16 code units, i.e. an index into the line as a JS string plus one
+    pub column: u32,
 }
 
 /// This is a copied enum from `fervid_core` with `napi` implementation to avoid littering the core crate.
@@ -205,6 +488,7 @@ impl From<fervid::CompileEmittedStyle> for Style {
             is_compiled: value.is_compiled,
             lang: value.lang,
             is_scoped: value.is_scoped,
+            module: value.module,
         }
     }
 }
@@ -212,10 +496,43 @@ impl From<fervid::CompileEmittedStyle> for Style {
 impl From<fervid::errors::CompileError> for SerializedError {
     fn from(value: fervid::errors::CompileError) -> Self {
         let span = value.span();
+        let severity = if value.is_warning() {
+            "warning"
+        } else {
+            "error"
+        };
+        // Spans start from 1
+        let offset = |pos: BytePos| (!span.is_dummy()).then(|| pos.0 - 1);
         SerializedError {
-            lo: span.lo.0,
-            hi: span.hi.0,
+            lo: offset(span.lo),
+            hi: offset(span.hi),
             message: value.to_string(),
+            code: value.code().to_owned(),
+            severity: severity.to_owned(),
+            loc: None,
+            frame: None,
+        }
+    }
+}
+
+impl From<fervid::CompileVForKey> for VForKey {
+    fn from(value: fervid::CompileVForKey) -> Self {
+        VForKey {
+            lo: value.lo,
+            hi: value.hi,
+            key: value.key,
+        }
+    }
+}
+
+impl From<fervid::CompileHelpers> for Helpers {
+    fn from(value: fervid::CompileHelpers) -> Self {
+        let to_strings = |helpers: Vec<&str>| helpers.into_iter().map(String::from).collect();
+
+        Helpers {
+            script: to_strings(value.script),
+            template: to_strings(value.template),
+            ssr: to_strings(value.ssr),
         }
     }
 }
@@ -227,6 +544,9 @@ impl From<fervid::CompileEmittedAsset> for CustomBlock {
             lo: value.lo,
             hi: value.hi,
             tag_name: value.tag_name,
+            lang: value.lang,
+            attrs: value.attributes.into_iter().collect(),
+            import_id: value.import_id,
         }
     }
 }
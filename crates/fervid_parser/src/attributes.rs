@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet};
 
 use fervid_core::{
     AttributeOrBinding, FervidAtom, StrOrExpr, VBindDirective, VCustomDirective, VForDirective,
@@ -6,9 +6,11 @@ use fervid_core::{
 };
 use swc_core::{
     common::{BytePos, Span},
-    ecma::ast::Expr,
+    ecma::{
+        ast::Expr,
+        visit::{VisitMut, VisitMutWith},
+    },
 };
-use swc_ecma_parser::Syntax;
 use swc_html_ast::Attribute;
 
 use crate::{
@@ -45,9 +47,11 @@ impl SfcParser<'_, '_, '_> {
         for mut raw_attribute in raw_attributes.into_iter() {
             // Use raw names for attributes, otherwise SWC transforms them to lowercase
             // `-1` is needed because SWC spans start from 1
-            let raw_idx_start = raw_attribute.span.lo.0 as usize - 1;
+            let raw_idx_start = (raw_attribute.span.lo.0 as usize).saturating_sub(1);
             let raw_idx_end = raw_idx_start + raw_attribute.name.len();
-            raw_attribute.name = FervidAtom::from(&self.input[raw_idx_start..raw_idx_end]);
+            if let Some(raw_name) = self.input.get(raw_idx_start..raw_idx_end) {
+                raw_attribute.name = FervidAtom::from(raw_name);
+            }
 
             match self.try_parse_directive(raw_attribute, attrs_or_bindings, vue_directives) {
                 Ok(()) => {
@@ -99,14 +103,21 @@ impl SfcParser<'_, '_, '_> {
             };
         }
 
-        macro_rules! ts {
-            () => {
-                Syntax::Typescript(Default::default())
-            };
-        }
+        let expr_syntax = self.expr_syntax();
 
-        // TODO Should the span be narrower? (It can be narrowed with lo = lo + name.len() + 1 and hi = hi - 1)
+        // Directives span the whole attribute, but their expressions are parsed at the position
+        // of the value, so that the expression spans point to its source text
         let span = raw_attribute.span;
+        let raw_value = raw_value_text(&raw_attribute);
+        let value_span = raw_value.map_or(span, |(value_span, _)| value_span);
+        // The expressions are parsed from the decoded value, so their spans need to be mapped
+        // to the source when the decoding changed the text, e.g. for `a &amp;&amp; b`
+        let mut raw_value_spans = match (raw_value, &raw_attribute.value) {
+            (Some((value_span, raw)), Some(decoded)) if raw != decoded.as_str() => {
+                RawValueSpans::new(value_span.lo, raw, decoded)
+            }
+            _ => None,
+        };
         let raw_name: &str = &raw_attribute.name;
         let mut chars_iter = raw_name.chars().enumerate();
 
@@ -307,7 +318,7 @@ impl SfcParser<'_, '_, '_> {
                     hi: BytePos(span_hi),
                 };
 
-                let parsed = match self.parse_expr(&dynamic_name, ts!(), span) {
+                let parsed = match self.parse_expr(dynamic_name, expr_syntax, span) {
                     Ok(parsed) => parsed,
                     Err(expr_err) => {
                         bail!(js, expr_err);
@@ -344,7 +355,9 @@ impl SfcParser<'_, '_, '_> {
 
         macro_rules! push_directive_js {
             ($key: ident, $value: expr) => {
-                match self.parse_expr($value, ts!(), span) {
+                match self.parse_value(&mut raw_value_spans, |parser| {
+                    parser.parse_expr($value, expr_syntax, value_span)
+                }) {
                     Ok(parsed) => {
                         let directives = get_directives!();
                         directives.$key = Some(parsed);
@@ -386,7 +399,9 @@ impl SfcParser<'_, '_, '_> {
                     }
                 };
 
-                let parsed_expr = match self.parse_expr(&value, ts!(), span) {
+                let parsed_expr = match self.parse_value(&mut raw_value_spans, |parser| {
+                    parser.parse_expr(&value, expr_syntax, value_span)
+                }) {
                     Ok(parsed) => parsed,
                     Err(expr_err) => {
                         bail!(js, expr_err);
@@ -405,7 +420,9 @@ impl SfcParser<'_, '_, '_> {
 
             "on" => {
                 let handler = match raw_attribute.value {
-                    Some(ref value) => match self.parse_expr(&value, ts!(), span) {
+                    Some(ref value) => match self.parse_value(&mut raw_value_spans, |parser| {
+                        parser.parse_expr(value, expr_syntax, value_span)
+                    }) {
                         Ok(parsed) => Some(parsed),
                         Err(expr_err) => {
                             bail!(js, expr_err);
@@ -418,6 +435,7 @@ impl SfcParser<'_, '_, '_> {
                     event: argument,
                     handler,
                     modifiers,
+                    is_cacheable: false,
                     span,
                 }));
             }
@@ -440,13 +458,17 @@ impl SfcParser<'_, '_, '_> {
                 let value = expect_value!();
 
                 let Some(((itervar, itervar_span), (iterable, iterable_span))) =
-                    split_itervar_and_iterable(&value, span)
+                    split_itervar_and_iterable(value, value_span)
                 else {
                     bail!(ParseErrorKind::DirectiveSyntax);
                 };
 
-                match self.parse_expr(itervar, ts!(), itervar_span) {
-                    Ok(itervar) => match self.parse_expr(iterable, ts!(), iterable_span) {
+                match self.parse_value(&mut raw_value_spans, |parser| {
+                    parser.parse_expr(itervar, expr_syntax, itervar_span)
+                }) {
+                    Ok(itervar) => match self.parse_value(&mut raw_value_spans, |parser| {
+                        parser.parse_expr(iterable, expr_syntax, iterable_span)
+                    }) {
                         Ok(iterable) => {
                             push_directive!(
                                 v_for,
@@ -467,7 +489,9 @@ impl SfcParser<'_, '_, '_> {
             "model" => {
                 let value = expect_value!();
 
-                match self.parse_expr(&value, ts!(), span) {
+                match self.parse_value(&mut raw_value_spans, |parser| {
+                    parser.parse_expr(value, expr_syntax, value_span)
+                }) {
                     Ok(model_binding) => {
                         // v-model value must be a valid JavaScript member expression
                         if !matches!(*model_binding, Expr::Member(_) | Expr::Ident(_)) {
@@ -489,13 +513,14 @@ impl SfcParser<'_, '_, '_> {
             }
 
             "slot" => {
-                let value =
-                    raw_attribute
-                        .value
-                        .and_then(|v| match self.parse_pat(&v, ts!(), span) {
-                            Ok(value) => Some(Box::new(value)),
-                            Result::Err(_) => None,
-                        });
+                let value = raw_attribute.value.and_then(|v| {
+                    match self.parse_value(&mut raw_value_spans, |parser| {
+                        parser.parse_pat(&v, expr_syntax, value_span)
+                    }) {
+                        Ok(value) => Some(Box::new(value)),
+                        Result::Err(_) => None,
+                    }
+                });
                 push_directive!(
                     v_slot,
                     VSlotDirective {
@@ -552,7 +577,9 @@ impl SfcParser<'_, '_, '_> {
                 };
 
                 // If there is a value, try parsing it and only include the successfully parsed values
-                match self.parse_expr(&value, ts!(), span) {
+                match self.parse_value(&mut raw_value_spans, |parser| {
+                    parser.parse_expr(&value, expr_syntax, value_span)
+                }) {
                     Ok(parsed) => {
                         let directives = get_directives!();
                         directives.custom.push(VCustomDirective {
@@ -569,6 +596,29 @@ impl SfcParser<'_, '_, '_> {
 
         Ok(())
     }
+
+    /// Parses a part of the attribute value and points the spans of the result
+    /// and of its errors to the raw source text
+    fn parse_value<T: VisitMutWith<RawValueSpans>>(
+        &mut self,
+        raw_value_spans: &mut Option<RawValueSpans>,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let errors_start = self.errors.len();
+        let mut result = parse(self);
+
+        if let Some(raw_value_spans) = raw_value_spans {
+            match result {
+                Ok(ref mut parsed) => parsed.visit_mut_with(raw_value_spans),
+                Err(ref mut error) => raw_value_spans.visit_mut_span(&mut error.span),
+            }
+            for error in self.errors[errors_start..].iter_mut() {
+                raw_value_spans.visit_mut_span(&mut error.span);
+            }
+        }
+
+        result
+    }
 }
 
 /// Creates `AttributeOrBinding::RegularAttribute`
@@ -577,10 +627,167 @@ pub fn create_regular_attribute(raw_attribute: Attribute) -> AttributeOrBinding
     AttributeOrBinding::RegularAttribute {
         name: raw_attribute.name,
         value: raw_attribute.value.unwrap_or_default(),
+        raw_value: raw_attribute.raw_value,
         span: raw_attribute.span,
     }
 }
 
+/// Span and text of the attribute value as written in the source, i.e. without the quotes
+fn raw_value_text(raw_attribute: &Attribute) -> Option<(Span, &str)> {
+    let raw_value: &str = raw_attribute.raw_value.as_ref()?;
+    let (text, closing_quote_len) = match raw_value.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let quoted = &raw_value[1..];
+            match quoted.strip_suffix(quote) {
+                Some(text) => (text, 1),
+                // Unterminated value at the end of input
+                None => (quoted, 0),
+            }
+        }
+        _ => (raw_value, 0),
+    };
+
+    let hi = raw_attribute.span.hi.0.checked_sub(closing_quote_len)?;
+    let lo = hi.checked_sub(text.len() as u32)?;
+    Some((Span::new(BytePos(lo), BytePos(hi)), text))
+}
+
+/// Points the spans of the nodes parsed from a decoded attribute value to its raw source text.
+/// They differ when the value has character references (e.g. `&amp;`) or `\r\n` line breaks.
+struct RawValueSpans {
+    lo: BytePos,
+    /// Offset in the raw text for each byte offset in the decoded text, including its end
+    raw_offsets: Vec<u32>,
+}
+
+impl RawValueSpans {
+    fn new(lo: BytePos, raw: &str, decoded: &str) -> Option<Self> {
+        let mut raw_offsets = vec![0; decoded.len() + 1];
+        let mut dead_ends = HashSet::new();
+        align_raw_value(raw, decoded, 0, 0, &mut raw_offsets, &mut dead_ends)
+            .then_some(RawValueSpans { lo, raw_offsets })
+    }
+
+    fn map(&self, pos: BytePos) -> BytePos {
+        let decoded_offset = pos.0.saturating_sub(self.lo.0) as usize;
+        let raw_offset = self
+            .raw_offsets
+            .get(decoded_offset)
+            .or(self.raw_offsets.last())
+            .copied()
+            .unwrap_or_default();
+
+        BytePos(self.lo.0 + raw_offset)
+    }
+}
+
+impl VisitMut for RawValueSpans {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        if span.is_dummy() {
+            return;
+        }
+
+        span.lo = self.map(span.lo);
+        span.hi = self.map(span.hi);
+    }
+}
+
+/// Fills `raw_offsets` from the position `raw_idx` in the `raw` text and `decoded_idx` in the `decoded` one.
+/// Only the character references branch, because a `&` may also be literal,
+/// and a legacy named reference may end before the `;` (e.g. `&ampx` is `&x`).
+fn align_raw_value(
+    raw: &str,
+    decoded: &str,
+    mut raw_idx: usize,
+    mut decoded_idx: usize,
+    raw_offsets: &mut [u32],
+    dead_ends: &mut HashSet<(usize, usize)>,
+) -> bool {
+    let raw_bytes = raw.as_bytes();
+    let decoded_bytes = decoded.as_bytes();
+
+    loop {
+        raw_offsets[decoded_idx] = raw_idx as u32;
+
+        let Some(&decoded_byte) = decoded_bytes.get(decoded_idx) else {
+            return raw_idx == raw.len();
+        };
+        let Some(&raw_byte) = raw_bytes.get(raw_idx) else {
+            return false;
+        };
+
+        match raw_byte {
+            b'&' => break,
+            b'\r' if decoded_byte == b'\n' => {
+                raw_idx += if raw_bytes.get(raw_idx + 1) == Some(&b'\n') {
+                    2
+                } else {
+                    1
+                };
+                decoded_idx += 1;
+            }
+            _ if raw_byte == decoded_byte => {
+                raw_idx += 1;
+                decoded_idx += 1;
+            }
+            _ => return false,
+        }
+    }
+
+    if dead_ends.contains(&(raw_idx, decoded_idx)) {
+        return false;
+    }
+
+    // Literal `&`, e.g. in `&ampz` where a legacy reference is not decoded
+    if decoded_bytes[decoded_idx] == b'&'
+        && align_raw_value(
+            raw,
+            decoded,
+            raw_idx + 1,
+            decoded_idx + 1,
+            raw_offsets,
+            dead_ends,
+        )
+    {
+        return true;
+    }
+
+    let name_end = raw_idx
+        + 1
+        + raw_bytes[raw_idx + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'#')
+            .count();
+    let terminated_end = (raw_bytes.get(name_end) == Some(&b';')).then_some(name_end + 1);
+    let reference_ends = terminated_end
+        .into_iter()
+        .chain((raw_idx + 2..=name_end).rev());
+
+    for reference_end in reference_ends {
+        // A reference decodes into one or two characters
+        let mut decoded_len = 0;
+        for c in decoded[decoded_idx..].chars().take(2) {
+            decoded_len += c.len_utf8();
+            let decoded_end = decoded_idx + decoded_len;
+            raw_offsets[decoded_idx..decoded_end].fill(raw_idx as u32);
+
+            if align_raw_value(
+                raw,
+                decoded,
+                reference_end,
+                decoded_end,
+                raw_offsets,
+                dead_ends,
+            ) {
+                return true;
+            }
+        }
+    }
+
+    dead_ends.insert((raw_idx, decoded_idx));
+    false
+}
+
 fn split_itervar_and_iterable<'a>(
     raw: &'a str,
     original_span: Span,
@@ -652,7 +859,7 @@ fn to_camel_case(raw: &str, out: &mut String) {
 
 #[cfg(test)]
 mod tests {
-    use swc_core::common::DUMMY_SP;
+    use swc_core::common::{Spanned, DUMMY_SP};
 
     use super::*;
 
@@ -1134,6 +1341,103 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn it_keeps_raw_attribute_values() {
+        let source = r#"<template><div title='a &amp; b' data-x=1 disabled></div></template>"#;
+
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(source, &mut errors);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+        let template = descriptor.template.expect("Should have template");
+        let Some(fervid_core::Node::Element(div)) = template.roots.first() else {
+            panic!("Root is not an element")
+        };
+
+        let raw_values: Vec<_> = div
+            .starting_tag
+            .attributes
+            .iter()
+            .map(|attr| match attr {
+                AttributeOrBinding::RegularAttribute {
+                    value, raw_value, ..
+                } => (value.as_str(), raw_value.as_ref().map(|v| v.as_str())),
+                _ => panic!("Attribute is not regular"),
+            })
+            .collect();
+
+        assert_eq!(
+            vec![("a & b", Some("'a &amp; b'")), ("1", Some("1")), ("", None)],
+            raw_values
+        );
+    }
+
+    #[test]
+    fn it_maps_expression_spans_to_raw_values() {
+        let source = "<template><div :foo=\"a &amp;&amp; b\" v-if='x&lt;y &ampz' @click=\"c\r\n+ &quot;&quot;\"></div></template>";
+        let source_text = |span: Span| &source[span.lo.0 as usize - 1..span.hi.0 as usize - 1];
+
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(source, &mut errors);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+        let template = descriptor.template.expect("Should have template");
+        let Some(fervid_core::Node::Element(div)) = template.roots.first() else {
+            panic!("Root is not an element")
+        };
+
+        let Some(AttributeOrBinding::VBind(v_bind)) = div.starting_tag.attributes.first() else {
+            panic!("Attribute is not a v-bind")
+        };
+        assert_eq!("a &amp;&amp; b", source_text(v_bind.value.span()));
+        let Expr::Bin(ref bin) = *v_bind.value else {
+            panic!("Not a binary expression")
+        };
+        assert_eq!("b", source_text(bin.right.span()));
+
+        let Some(AttributeOrBinding::VOn(v_on)) = div.starting_tag.attributes.get(1) else {
+            panic!("Attribute is not a v-on")
+        };
+        let handler = v_on.handler.as_ref().expect("Should have handler");
+        assert_eq!("c\r\n+ &quot;&quot;", source_text(handler.span()));
+
+        // Legacy reference without `;` is not decoded before an alphanumeric, i.e. `x<y &ampz`
+        let directives = div.starting_tag.directives.as_ref().expect("Should exist");
+        let v_if = directives.v_if.as_ref().expect("Should exist");
+        assert_eq!("x&lt;y &ampz", source_text(v_if.span()));
+        let Expr::Bin(ref bin) = **v_if else {
+            panic!("Not a binary expression")
+        };
+        assert_eq!("ampz", source_text(bin.right.span()));
+    }
+
+    #[test]
+    fn it_parses_directive_expressions_at_value_position() {
+        let source =
+            r#"<template><div :foo="bar" v-if='ok' v-for="item in items"></div></template>"#;
+        let source_text = |span: Span| &source[span.lo.0 as usize - 1..span.hi.0 as usize - 1];
+
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(source, &mut errors);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+        let template = descriptor.template.expect("Should have template");
+        let Some(fervid_core::Node::Element(div)) = template.roots.first() else {
+            panic!("Root is not an element")
+        };
+
+        let Some(AttributeOrBinding::VBind(v_bind)) = div.starting_tag.attributes.first() else {
+            panic!("Attribute is not a v-bind")
+        };
+        assert_eq!("bar", source_text(v_bind.value.span()));
+        assert_eq!(r#":foo="bar""#, source_text(v_bind.span));
+
+        let directives = div.starting_tag.directives.as_ref().expect("Should exist");
+        let v_if = directives.v_if.as_ref().expect("Should exist");
+        assert_eq!("ok", source_text(v_if.span()));
+
+        let v_for = directives.v_for.as_ref().expect("Should exist");
+        assert_eq!("item", source_text(v_for.itervar.span()));
+        assert_eq!("items", source_text(v_for.iterable.span()));
+    }
+
     #[test]
     fn it_correctly_splits_itervar_iterable() {
         macro_rules! check {
@@ -11,6 +11,7 @@ impl SfcParser<'_, '_, '_> {
             .map(|attr| AttributeOrBinding::RegularAttribute {
                 name: attr.name,
                 value: attr.value.unwrap_or_else(|| fervid_atom!("")),
+                raw_value: attr.raw_value,
                 span: attr.span,
             })
             .collect();
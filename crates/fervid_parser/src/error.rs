@@ -1,4 +1,9 @@
-use swc_core::common::{Span, Spanned};
+use fervid_core::{
+    error::{ErrorCode, Severity, SeverityLevel},
+    FervidAtom,
+};
+use swc_core::common::{Span, Spanned, DUMMY_SP};
+use swc_html_parser::error::ErrorKind as HtmlErrorKind;
 
 #[derive(Debug)]
 pub struct ParseError {
@@ -30,14 +35,32 @@ pub enum ParseErrorKind {
     DuplicateAttribute,
     /// Error while parsing EcmaScript/TypeScript
     EcmaSyntaxError(Box<swc_ecma_parser::error::SyntaxError>),
-    /// Unrecoverable error while parsing HTML
-    InvalidHtml(Box<swc_html_parser::error::ErrorKind>),
-    /// Both `<template>` and `<script>` are missing
+    /// The input is bigger than [`LossyParseOptions::max_input_size`](crate::LossyParseOptions::max_input_size)
+    /// or than [`MAX_INPUT_SIZE`](crate::MAX_INPUT_SIZE), which the spans can address
+    InputTooLarge,
+    /// Span of a block does not fall on the character boundaries of the input,
+    /// e.g. the span comes from another source
+    InvalidBlockSpan,
+    /// Error while parsing HTML.
+    /// The tree construction errors are only warnings, see [`ParseErrorKind::is_html_tree_construction`]
+    InvalidHtml(Box<HtmlErrorKind>),
+    /// `<template>`, `<script>` and `<style>` are all missing
     MissingTemplateOrScript,
+    /// Elements are nested deeper than [`SfcParser::max_depth`](crate::SfcParser::max_depth),
+    /// the deeper elements are dropped
+    NestingTooDeep,
     /// `<script>`/`<style>` content was not Text
     UnexpectedNonRawTextContent,
-    /// Language not supported
-    UnsupportedLang,
+    /// `<script>` and `<script setup>` have different `lang`s
+    ScriptLangMismatch,
+    /// `<script setup>` has a `src` attribute
+    ScriptSetupSrc,
+    /// `<script>` has a `src` attribute while `<script setup>` is also present
+    ScriptSrcWithScriptSetup,
+    /// `<template src>` or `<style src>` could not be loaded
+    UnresolvedSrc(FervidAtom),
+    /// `<script>` language is not supported and no preprocessor could handle it
+    UnsupportedScriptLang(FervidAtom),
 }
 
 impl From<swc_ecma_parser::error::Error> for ParseError {
@@ -51,15 +74,187 @@ impl From<swc_ecma_parser::error::Error> for ParseError {
     }
 }
 
+impl ParseErrorKind {
+    /// Whether this is an error of the HTML tree construction rules, e.g. `<div>` inside `<p>`.
+    /// Vue templates do not follow these rules, e.g. components are allowed inside `<table>`,
+    /// but the HTML parser still moves such elements, which is worth a warning.
+    pub fn is_html_tree_construction(&self) -> bool {
+        let ParseErrorKind::InvalidHtml(kind) = self else {
+            return false;
+        };
+
+        matches!(
+            **kind,
+            HtmlErrorKind::StrayStartTag(_)
+                | HtmlErrorKind::StrayEndTag(_)
+                | HtmlErrorKind::UnclosedElements(_)
+                | HtmlErrorKind::UnclosedElementsImplied(_)
+                | HtmlErrorKind::UnclosedElementsCell
+                | HtmlErrorKind::StrayDoctype
+                | HtmlErrorKind::NonConformingDoctype
+                | HtmlErrorKind::NonSpaceCharacterInTrailer
+                | HtmlErrorKind::NonSpaceCharacterAfterFrameset
+                | HtmlErrorKind::NonSpaceCharacterInFrameset
+                | HtmlErrorKind::NonSpaceCharacterAfterBody
+                | HtmlErrorKind::NonSpaceCharacterInColumnGroup
+                | HtmlErrorKind::NonSpaceCharacterInNoscriptInHead
+                | HtmlErrorKind::SomethingBetweenHeadAndBody(_)
+                | HtmlErrorKind::StartTagWithoutDoctype
+                | HtmlErrorKind::StartSelectWhereEndSelectExpected
+                | HtmlErrorKind::StartTagWithSelectOpen(_)
+                | HtmlErrorKind::BadStartTagInNoscriptInHead(_)
+                | HtmlErrorKind::UnexpectedImageStartTag
+                | HtmlErrorKind::SomethingSeenWhenSomethingOpen(_)
+                | HtmlErrorKind::HeadingWhenHeadingOpen
+                | HtmlErrorKind::NoCellToClose
+                | HtmlErrorKind::StartTagInTable(_)
+                | HtmlErrorKind::FormWhenFormOpen
+                | HtmlErrorKind::TableSeenWhileTableOpen
+                | HtmlErrorKind::StartTagInTableBody(_)
+                | HtmlErrorKind::EndTagSeenWithoutDoctype
+                | HtmlErrorKind::EndTagAfterBody
+                | HtmlErrorKind::EndTagSeenWithSelectOpen(_)
+                | HtmlErrorKind::GarbageInColumnGroup
+                | HtmlErrorKind::EndTagBr
+                | HtmlErrorKind::NoElementToCloseButEndTagSeen(_)
+                | HtmlErrorKind::HtmlStartTagInForeignContext(_)
+                | HtmlErrorKind::NoTableRowToClose
+                | HtmlErrorKind::NonSpaceCharacterInTable
+                | HtmlErrorKind::UnclosedChildrenInRuby
+                | HtmlErrorKind::StartTagSeenWithoutRuby(_)
+                | HtmlErrorKind::UnclosedElementsOnStack
+                | HtmlErrorKind::EndTagDidNotMatchCurrentOpenElement(_, _)
+                | HtmlErrorKind::EndTagViolatesNestingRules(_)
+                | HtmlErrorKind::EofWithUnclosedElements
+                | HtmlErrorKind::EndTagWithUnclosedElements(_)
+                | HtmlErrorKind::NonSpaceCharacterWithoutDoctype
+                | HtmlErrorKind::EofWithoutDoctype
+                | HtmlErrorKind::EofInText
+        )
+    }
+}
+
+impl ErrorCode for ParseErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ParseErrorKind::DirectiveSyntax => "parse/directive-syntax",
+            ParseErrorKind::DirectiveSyntaxDirectiveName => "parse/directive-syntax-directive-name",
+            ParseErrorKind::DirectiveSyntaxArgument => "parse/directive-syntax-argument",
+            ParseErrorKind::DirectiveSyntaxDynamicArgument => {
+                "parse/directive-syntax-dynamic-argument"
+            }
+            ParseErrorKind::DirectiveSyntaxUnexpectedCharacterAfterDynamicArgument => {
+                "parse/directive-syntax-unexpected-character-after-dynamic-argument"
+            }
+            ParseErrorKind::DirectiveSyntaxModifier => "parse/directive-syntax-modifier",
+            ParseErrorKind::DuplicateScriptOptions => "parse/duplicate-script-options",
+            ParseErrorKind::DuplicateScriptSetup => "parse/duplicate-script-setup",
+            ParseErrorKind::DuplicateTemplate => "parse/duplicate-template",
+            ParseErrorKind::DuplicateAttribute => "parse/duplicate-attribute",
+            ParseErrorKind::EcmaSyntaxError(_) => "parse/ecma-syntax-error",
+            ParseErrorKind::InputTooLarge => "parse/input-too-large",
+            ParseErrorKind::InvalidBlockSpan => "parse/invalid-block-span",
+            ParseErrorKind::InvalidHtml(_) => "parse/invalid-html",
+            ParseErrorKind::MissingTemplateOrScript => "parse/missing-template-or-script",
+            ParseErrorKind::NestingTooDeep => "parse/nesting-too-deep",
+            ParseErrorKind::UnexpectedNonRawTextContent => "parse/unexpected-non-raw-text-content",
+            ParseErrorKind::ScriptLangMismatch => "parse/script-lang-mismatch",
+            ParseErrorKind::ScriptSetupSrc => "parse/script-setup-src",
+            ParseErrorKind::ScriptSrcWithScriptSetup => "parse/script-src-with-script-setup",
+            ParseErrorKind::UnresolvedSrc(_) => "parse/unresolved-src",
+            ParseErrorKind::UnsupportedScriptLang(_) => "parse/unsupported-script-lang",
+        }
+    }
+}
+
 impl std::fmt::Display for ParseErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            ParseErrorKind::DirectiveSyntax => write!(f, "Malformed directive."),
+            ParseErrorKind::DirectiveSyntaxDirectiveName => {
+                write!(f, "Malformed directive name.")
+            }
+            ParseErrorKind::DirectiveSyntaxArgument => write!(f, "Malformed directive argument."),
+            ParseErrorKind::DirectiveSyntaxDynamicArgument => {
+                write!(f, "Malformed directive dynamic argument.")
+            }
+            ParseErrorKind::DirectiveSyntaxUnexpectedCharacterAfterDynamicArgument => write!(
+                f,
+                "Malformed directive: expected modifiers after the dynamic argument."
+            ),
+            ParseErrorKind::DirectiveSyntaxModifier => write!(f, "Malformed directive modifier."),
+            ParseErrorKind::DuplicateScriptOptions => write!(
+                f,
+                "Single file component can contain only one <script> element."
+            ),
+            ParseErrorKind::DuplicateScriptSetup => write!(
+                f,
+                "Single file component can contain only one <script setup> element."
+            ),
+            ParseErrorKind::DuplicateTemplate => write!(
+                f,
+                "Single file component can contain only one <template> element."
+            ),
+            ParseErrorKind::DuplicateAttribute => {
+                write!(f, "Duplicate attribute on a root element.")
+            }
+            ParseErrorKind::EcmaSyntaxError(e) => write!(f, "{}", e.msg()),
+            ParseErrorKind::InputTooLarge => write!(f, "The input is too large to be parsed."),
+            ParseErrorKind::InvalidBlockSpan => {
+                write!(f, "The block position does not match the source.")
+            }
+            ParseErrorKind::InvalidHtml(kind) => {
+                let error = swc_html_parser::error::Error::new(DUMMY_SP, (**kind).clone());
+                write!(f, "{}", error.message())
+            }
+            ParseErrorKind::MissingTemplateOrScript => write!(
+                f,
+                "At least one <template>, <script> or <style> is required in a single file component."
+            ),
+            ParseErrorKind::NestingTooDeep => write!(
+                f,
+                "Elements are nested too deep, the deeper elements are dropped."
+            ),
+            ParseErrorKind::UnexpectedNonRawTextContent => {
+                write!(f, "<script> and <style> can only contain text.")
+            }
+            ParseErrorKind::ScriptLangMismatch => write!(
+                f,
+                "<script> and <script setup> must have the same language type."
+            ),
+            ParseErrorKind::ScriptSetupSrc => write!(
+                f,
+                "<script setup> cannot use the \"src\" attribute because its syntax will be ambiguous outside of the component."
+            ),
+            ParseErrorKind::ScriptSrcWithScriptSetup => write!(
+                f,
+                "<script> cannot use the \"src\" attribute when <script setup> is also present because they must be processed together."
+            ),
+            ParseErrorKind::UnsupportedScriptLang(lang) => write!(
+                f,
+                "<script lang=\"{}\"> is not supported. Provide a `ScriptPreprocessor` to compile it to JavaScript or TypeScript.",
+                lang
+            ),
+            ParseErrorKind::UnresolvedSrc(src) => {
+                write!(f, "Cannot load the block from src=\"{}\".", src)
+            }
+        }
     }
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        self.kind.fmt(f)
+    }
+}
+
+impl Severity for ParseError {
+    fn get_severity(&self) -> SeverityLevel {
+        if self.kind.is_html_tree_construction() {
+            SeverityLevel::Warning
+        } else {
+            SeverityLevel::RecoverableError
+        }
     }
 }
 
@@ -1,14 +1,29 @@
 mod attributes;
 mod custom_block;
 mod error;
+mod lossy;
+mod mask;
+mod pad;
+mod preprocessor;
 mod script;
 mod sfc;
+mod src_blocks;
 mod style;
 mod template;
 
 pub use error::ParseError;
+pub use lossy::{parse_lossy, parse_lossy_with, LossyParseOptions, LossyParseResult};
+pub use pad::{pad_block_content, BlockPadding};
+pub use preprocessor::{PreprocessedScript, ScriptPreprocessor, TemplatePreprocessor};
+pub use src_blocks::{inline_src_blocks, InlinedSrcBlock, InlinedSrcBlocks};
 use swc_core::common::comments::SingleThreadedComments;
 
+/// Biggest input which the spans can address. Byte offsets are stored as `u32` starting from 1,
+/// and swc reserves the positions above `u32::MAX - 2^16`, e.g. for the synthesized nodes.
+/// Bigger inputs would get overflowing spans and corrupted source maps, so they are rejected.
+/// Splitting a giant input across several source files is not supported.
+pub const MAX_INPUT_SIZE: usize = (u32::MAX - (1 << 16) - 1) as usize;
+
 // Default patterns for interpolation
 pub const INTERPOLATION_START_PAT_DEFAULT: &str = "{{";
 pub const INTERPOLATION_END_PAT_DEFAULT: &str = "}}";
@@ -21,7 +36,20 @@ pub struct SfcParser<'i, 'e, 'p> {
     is_pre: bool,
     interpolation_start_pat: &'p str,
     interpolation_end_pat: &'p str,
+    /// Whether `<` in the interpolations was hidden from the HTML parser, see [`mask`]
+    has_masked_interpolations: bool,
+    /// Whether the template expressions are TypeScript
+    is_ts: bool,
     pub ignore_empty: bool,
+    /// Maximum nesting of the template elements, the deeper ones are dropped with an error.
+    /// Default: unlimited
+    pub max_depth: Option<u32>,
+    /// Nesting of the currently processed element
+    depth: u32,
+    /// Used for `<script>` blocks with an unknown `lang`
+    pub script_preprocessor: Option<Box<dyn ScriptPreprocessor>>,
+    /// Used for `<template>` blocks with a `lang` other than `html`
+    pub template_preprocessor: Option<Box<dyn TemplatePreprocessor>>,
 }
 
 impl<'i, 'e> SfcParser<'i, 'e, 'static> {
@@ -40,17 +68,29 @@ impl<'i, 'e> SfcParser<'i, 'e, 'static> {
             is_pre: false,
             interpolation_start_pat: INTERPOLATION_START_PAT_DEFAULT,
             interpolation_end_pat: INTERPOLATION_END_PAT_DEFAULT,
+            has_masked_interpolations: false,
+            is_ts: true,
             ignore_empty: true,
+            max_depth: None,
+            depth: 0,
+            script_preprocessor: None,
+            template_preprocessor: None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{Node, SfcDescriptor, SfcScriptLang};
-    use swc_core::ecma::ast::{ModuleDecl, ModuleItem};
+    use fervid_core::{ElementNode, Node, SfcDescriptor, SfcScriptLang};
+    use swc_core::{
+        common::Spanned,
+        ecma::ast::{Expr, ModuleDecl, ModuleItem},
+    };
 
-    use crate::{error::ParseErrorKind, ParseError, SfcParser};
+    use crate::{
+        error::ParseErrorKind, ParseError, PreprocessedScript, ScriptPreprocessor, SfcParser,
+        TemplatePreprocessor,
+    };
 
     const SHOULD_EXIST: &str = "Should exist";
 
@@ -126,10 +166,30 @@ font-weight: bold;
 
         let styles = parse(&src).styles;
         assert_eq!(4, styles.len());
-        assert!(styles[0].lang == "css" && !styles[0].is_scoped && !styles[0].is_module);
-        assert!(styles[1].lang == "css" && !styles[1].is_scoped && styles[1].is_module);
-        assert!(styles[2].lang == "css" && styles[2].is_scoped && !styles[2].is_module);
-        assert!(styles[3].lang == "css" && styles[3].is_scoped && !styles[3].is_module);
+        assert!(styles[0].lang == "css" && !styles[0].is_scoped && styles[0].module.is_none());
+        assert!(
+            styles[1].lang == "css"
+                && !styles[1].is_scoped
+                && styles[1].module.as_deref() == Some("$style")
+        );
+        assert!(styles[2].lang == "css" && styles[2].is_scoped && styles[2].module.is_none());
+        assert!(styles[3].lang == "css" && styles[3].is_scoped && styles[3].module.is_none());
+    }
+
+    #[test]
+    fn style_block_named_module() {
+        let (mut src, _) = padding();
+        src.push_str(
+            r#"<style module="classes">
+.red {
+color: red;
+}
+</style>"#,
+        );
+
+        let styles = parse(&src).styles;
+        assert_eq!(1, styles.len());
+        assert_eq!(Some("classes"), styles[0].module.as_deref());
     }
 
     #[test]
@@ -257,17 +317,13 @@ h1 { color: red }
 
     #[test]
     fn handle_empty_nodes_with_src_attribute() {
-        // src imports not supported
-        // supporting them forces fervid to make assumptions about the environment
-        // they can be supported via a FileSystem adapter
+        // `src` blocks are inlined before parsing, see `inline_src_blocks`
         // <script src="com"/>
     }
 
     #[test]
     fn should_not_expose_ast_on_template_node_if_has_src_import() {
-        // src imports not supported
-        // supporting them forces fervid to make assumptions about the environment
-        // they can be supported via a FileSystem adapter
+        // `src` blocks are inlined before parsing, see `inline_src_blocks`
         // <template src="./foo.html"/>
     }
 
@@ -367,6 +423,17 @@ h1 { color: red }
         assert!(errors.is_empty());
     }
 
+    #[test]
+    fn should_allow_self_closing_tags() {
+        let (descriptor, errors) =
+            parse_with_errors("<template><div><slot /><Foo /></div></template>");
+        assert!(errors.is_empty());
+        let Node::Element(ref root) = descriptor.template.expect(SHOULD_EXIST).roots[0] else {
+            panic!("Root should be an element")
+        };
+        assert_eq!(2, root.children.len());
+    }
+
     #[test]
     fn treat_custom_blocks_as_raw_text() {
         let (descriptor, errors) =
@@ -412,6 +479,197 @@ h1 { color: red }
         assert!(errors.is_empty());
     }
 
+    #[test]
+    fn should_error_on_script_setup_src() {
+        let (descriptor, errors) = parse_with_errors("<script setup src=\"./foo.js\"></script>");
+        assert!(errors
+            .iter()
+            .any(|e| matches!(&e.kind, ParseErrorKind::ScriptSetupSrc)));
+        assert!(descriptor.script_setup.is_none());
+    }
+
+    #[test]
+    fn should_error_on_script_src_with_script_setup() {
+        let (descriptor, errors) = parse_with_errors(
+            "<script src=\"./foo.js\"></script><script setup>console.log(1)</script>",
+        );
+        assert!(errors
+            .iter()
+            .any(|e| matches!(&e.kind, ParseErrorKind::ScriptSrcWithScriptSetup)));
+        assert!(descriptor.script_legacy.is_none());
+        assert!(descriptor.script_setup.is_some());
+    }
+
+    #[test]
+    fn should_error_on_mismatched_script_langs() {
+        let (_, errors) = parse_with_errors(
+            "<script lang=\"ts\">console.log(1)</script><script setup>console.log(1)</script>",
+        );
+        assert!(errors
+            .iter()
+            .any(|e| matches!(&e.kind, ParseErrorKind::ScriptLangMismatch)));
+    }
+
+    #[test]
+    fn should_error_on_unknown_script_lang() {
+        let (descriptor, errors) =
+            parse_with_errors("<script lang=\"coffee\">console.log 1</script>");
+        assert!(errors.iter().any(
+            |e| matches!(&e.kind, ParseErrorKind::UnsupportedScriptLang(lang) if lang == "coffee")
+        ));
+        assert!(descriptor.script_legacy.is_none());
+    }
+
+    #[test]
+    fn it_parses_tsx_script_setup() {
+        let (descriptor, errors) = parse_with_errors(
+            "<script setup lang=\"tsx\">const a: number = 1; const b = <div>{a}</div></script>",
+        );
+        assert!(errors.is_empty());
+        let script_setup = descriptor.script_setup.expect(SHOULD_EXIST);
+        assert!(matches!(script_setup.lang, SfcScriptLang::Tsx));
+    }
+
+    #[test]
+    fn it_uses_script_lang_for_template_expressions() {
+        fn interpolation(source: &str) -> Box<Expr> {
+            let descriptor = parse(source);
+            let template = descriptor.template.expect(SHOULD_EXIST);
+            let Some(Node::Interpolation(interpolation)) = template.roots.first() else {
+                panic!("First root is not interpolation")
+            };
+            interpolation.value.to_owned()
+        }
+
+        // Comparisons in JS
+        let expr = interpolation("<template>{{ a < b > (c) }}</template><script setup></script>");
+        assert!(matches!(*expr, Expr::Bin(_)));
+
+        // Call with a type argument in TS, regardless of the block order
+        let expr = interpolation(
+            "<template>{{ a < b > (c) }}</template><script setup lang=\"ts\"></script>",
+        );
+        assert!(matches!(*expr, Expr::Call(_)));
+    }
+
+    #[test]
+    fn should_use_script_preprocessor_for_unknown_lang() {
+        #[derive(Debug)]
+        struct CoffeePreprocessor;
+
+        impl ScriptPreprocessor for CoffeePreprocessor {
+            fn preprocess_script(&self, lang: &str, content: &str) -> Option<PreprocessedScript> {
+                if lang != "coffee" {
+                    return None;
+                }
+
+                Some(PreprocessedScript {
+                    code: content.replace("console.log 1", "console.log(1)"),
+                    lang: SfcScriptLang::Es,
+                })
+            }
+        }
+
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(
+            "<script lang=\"coffee\">console.log 1</script>",
+            &mut errors,
+        );
+        parser.script_preprocessor = Some(Box::new(CoffeePreprocessor));
+        let descriptor = parser.parse_sfc().unwrap();
+        assert!(errors.is_empty());
+
+        let script = descriptor.script_legacy.expect(SHOULD_EXIST);
+        assert!(matches!(script.lang, SfcScriptLang::Es));
+        assert_eq!(1, script.content.body.len());
+    }
+
+    #[test]
+    fn should_keep_preprocessed_script_spans_in_content() {
+        #[derive(Debug)]
+        struct ExpandingPreprocessor;
+
+        impl ScriptPreprocessor for ExpandingPreprocessor {
+            fn preprocess_script(&self, _lang: &str, content: &str) -> Option<PreprocessedScript> {
+                Some(PreprocessedScript {
+                    code: format!("{content}\nexport const generated = true"),
+                    lang: SfcScriptLang::Es,
+                })
+            }
+        }
+
+        let input = "<script lang=\"coffee\">a = 1</script>\n<template><div></div></template>";
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(input, &mut errors);
+        parser.script_preprocessor = Some(Box::new(ExpandingPreprocessor));
+        let descriptor = parser.parse_sfc().unwrap();
+        assert!(errors.is_empty());
+
+        // Spans start from 1
+        let content_lo = input.find("a = 1").expect(SHOULD_EXIST) as u32 + 1;
+        let content_hi = content_lo + "a = 1".len() as u32;
+
+        let script = descriptor.script_legacy.expect(SHOULD_EXIST);
+        assert_eq!(2, script.content.body.len());
+        assert!(script.content.span.lo.0 >= content_lo);
+        assert!(script.content.span.hi.0 <= content_hi);
+        for item in script.content.body.iter() {
+            let span = item.span();
+            assert!(span.lo.0 >= content_lo && span.hi.0 <= content_hi);
+        }
+    }
+
+    #[test]
+    fn should_use_template_preprocessor_for_non_html_lang() {
+        #[derive(Debug)]
+        struct PugPreprocessor;
+
+        impl TemplatePreprocessor for PugPreprocessor {
+            fn preprocess_template(&self, lang: &str, content: &str) -> Option<String> {
+                if lang != "pug" {
+                    return None;
+                }
+
+                // Just enough of Pug for the test, e.g. `div {{ msg }}`
+                let (tag, text) = content.trim().split_once(' ')?;
+                Some(format!("<{tag}>{text}</{tag}>"))
+            }
+        }
+
+        let input = "<script setup></script>\n<template lang=\"pug\">\ndiv {{ msg }}\n</template>";
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(input, &mut errors);
+        parser.template_preprocessor = Some(Box::new(PugPreprocessor));
+        let descriptor = parser.parse_sfc().unwrap();
+        assert!(errors.is_empty());
+
+        let template = descriptor.template.expect(SHOULD_EXIST);
+        assert!(template.lang == "html");
+        let Some(Node::Element(div)) = template
+            .roots
+            .iter()
+            .find(|node| matches!(node, Node::Element(_)))
+        else {
+            panic!("No root element")
+        };
+        assert_eq!(div.starting_tag.tag_name, "div");
+        assert!(matches!(div.children.first(), Some(Node::Interpolation(_))));
+
+        // Spans start at the original content
+        let content_start = input.find("\ndiv").expect(SHOULD_EXIST) as u32;
+        assert_eq!(content_start + 1, div.span.lo.0);
+    }
+
+    #[test]
+    fn should_keep_template_with_unknown_lang_as_text() {
+        let (descriptor, errors) = parse_with_errors("<template lang=\"pug\">div hello</template>");
+        assert!(errors.is_empty());
+
+        let template = descriptor.template.expect(SHOULD_EXIST);
+        assert!(template.lang == "pug");
+        assert!(matches!(template.roots.as_slice(), [Node::Text(text, _)] if text == "div hello"));
+    }
+
     #[test]
     fn should_throw_error_if_no_template_or_script_is_present() {
         let (_, errors) = parse_with_errors("import { ref } from 'vue'");
@@ -420,6 +678,49 @@ h1 { color: red }
             .any(|e| matches!(&e.kind, ParseErrorKind::MissingTemplateOrScript)));
     }
 
+    #[test]
+    fn should_not_error_for_style_only_sfc() {
+        let (descriptor, errors) = parse_with_errors("<style>.a { color: red }</style>");
+        assert!(errors.is_empty());
+        assert!(descriptor.template.is_none());
+        assert_eq!(1, descriptor.styles.len());
+    }
+
+    #[test]
+    fn it_parses_standalone_template() {
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new("<div>{{ msg }}</div><span></span>", &mut errors);
+        let template = parser.parse_template().expect(SHOULD_EXIST);
+
+        assert!(errors.is_empty());
+        assert!(template.lang == "html");
+        assert_eq!(2, template.roots.len());
+        assert!(matches!(template.roots[0], Node::Element(_)));
+    }
+
+    #[test]
+    fn it_parses_deeply_nested_template() {
+        // Far deeper than the stack of the test thread allows for `swc_html_parser`
+        let source = format!(
+            "<template>{}{}</template>",
+            "<div>".repeat(3000),
+            "</div>".repeat(3000)
+        );
+        let (descriptor, errors) = parse_with_errors(&source);
+        assert!(errors.is_empty());
+
+        let mut node = &descriptor.template.expect(SHOULD_EXIST).roots[0];
+        let mut depth = 1;
+        while let Node::Element(ElementNode { children, .. }) = node {
+            let Some(child) = children.first() else {
+                break;
+            };
+            node = child;
+            depth += 1;
+        }
+        assert_eq!(3000, depth);
+    }
+
     fn parse(source: &str) -> SfcDescriptor {
         let mut errors = Vec::new();
         let mut parser = SfcParser::new(source, &mut errors);
@@ -0,0 +1,320 @@
+//! Parsing of the untrusted input, e.g. IDE buffers or user-generated templates.
+//!
+//! The input size and the nesting of the elements are limited,
+//! so that a malicious or broken input cannot exhaust the memory or overflow the stack.
+
+use fervid_core::SfcDescriptor;
+use swc_core::common::{BytePos, Span};
+
+use crate::{
+    error::{ParseError, ParseErrorKind},
+    SfcParser, MAX_INPUT_SIZE,
+};
+
+/// HTML elements which never have children
+const VOID_TAGS: [&[u8]; 14] = [
+    b"area", b"base", b"br", b"col", b"embed", b"hr", b"img", b"input", b"link", b"meta", b"param",
+    b"source", b"track", b"wbr",
+];
+
+/// Elements with the raw text content, e.g. `<script>if (a<b) {}</script>`
+pub(crate) const RAW_TEXT_TAGS: [&[u8]; 4] = [b"script", b"style", b"textarea", b"title"];
+
+#[derive(Debug, Clone, Copy)]
+pub struct LossyParseOptions {
+    /// Maximum size of the input in bytes, at most [`MAX_INPUT_SIZE`]. Default: 1 MiB
+    pub max_input_size: usize,
+    /// Maximum nesting of the elements. Default: 256
+    pub max_depth: u32,
+}
+
+impl Default for LossyParseOptions {
+    fn default() -> Self {
+        Self {
+            max_input_size: 1024 * 1024,
+            max_depth: 256,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LossyParseResult {
+    /// `None` when the input was rejected or could not be parsed at all
+    pub descriptor: Option<SfcDescriptor>,
+    pub errors: Vec<ParseError>,
+}
+
+/// Parses the SFC from an untrusted input.
+///
+/// Unlike [`SfcParser::parse_sfc`], all the problems are reported as errors:
+/// - the input bigger than `max_input_size` is not parsed;
+/// - the input nested deeper than `max_depth` is not parsed,
+///   because the HTML parser builds the tree recursively;
+/// - the elements deeper than `max_depth` are dropped when the nesting is only known after parsing.
+pub fn parse_lossy(input: &str, options: LossyParseOptions) -> LossyParseResult {
+    parse_lossy_with(input, options, |_| {})
+}
+
+/// Same as [`parse_lossy`], but `configure` can set up the parser before parsing,
+/// e.g. to set the preprocessors.
+pub fn parse_lossy_with(
+    input: &str,
+    options: LossyParseOptions,
+    configure: impl FnOnce(&mut SfcParser<'_, '_, 'static>),
+) -> LossyParseResult {
+    let mut errors = Vec::new();
+
+    if input.len() > options.max_input_size.min(MAX_INPUT_SIZE) {
+        errors.push(ParseError {
+            kind: ParseErrorKind::InputTooLarge,
+            span: Span {
+                lo: BytePos(1),
+                hi: BytePos(input.len().min(MAX_INPUT_SIZE) as u32 + 1),
+            },
+        });
+
+        return LossyParseResult {
+            descriptor: None,
+            errors,
+        };
+    }
+
+    if let Some(offset) = find_too_deep_nesting(input, options.max_depth) {
+        errors.push(ParseError {
+            kind: ParseErrorKind::NestingTooDeep,
+            span: Span {
+                lo: BytePos(offset as u32 + 1),
+                hi: BytePos(offset as u32 + 2),
+            },
+        });
+
+        return LossyParseResult {
+            descriptor: None,
+            errors,
+        };
+    }
+
+    let result = {
+        let mut parser = SfcParser::new(input, &mut errors);
+        parser.max_depth = Some(options.max_depth);
+        configure(&mut parser);
+        parser.parse_sfc()
+    };
+
+    let descriptor = match result {
+        Ok(descriptor) => Some(descriptor),
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+
+    LossyParseResult { descriptor, errors }
+}
+
+/// Finds the offset of the first tag nested deeper than `max_depth`.
+///
+/// This is only an estimate from scanning the tags, e.g. the implicitly closed `<p>` is counted as open.
+fn find_too_deep_nesting(input: &str, max_depth: u32) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let len = bytes.len();
+    let mut depth: u32 = 0;
+    let mut idx = 0;
+
+    while idx < len {
+        if bytes[idx] != b'<' {
+            idx += 1;
+            continue;
+        }
+        let tag_start = idx;
+        let rest = &bytes[idx + 1..];
+
+        // Comments
+        if rest.starts_with(b"!--") {
+            idx = find(bytes, idx + 4, b"-->").map_or(len, |end| end + 3);
+            continue;
+        }
+
+        // Closing tags
+        if rest.first() == Some(&b'/') {
+            depth = depth.saturating_sub(1);
+            idx += 2;
+            continue;
+        }
+
+        // Not a tag, e.g. `a < b`
+        if !rest.first().is_some_and(u8::is_ascii_alphabetic) {
+            idx += 1;
+            continue;
+        }
+
+        let name_len = rest
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || matches!(c, b'>' | b'/'))
+            .unwrap_or(rest.len());
+        let tag_name = rest[..name_len].to_ascii_lowercase();
+
+        // Find the end of the starting tag, skipping the quoted attribute values
+        idx += 1 + name_len;
+        let mut quote = None;
+        while idx < len {
+            match (quote, bytes[idx]) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, c @ (b'"' | b'\'')) => quote = Some(c),
+                (None, b'>') => break,
+                _ => {}
+            }
+            idx += 1;
+        }
+        let is_self_closing = idx < len && bytes[idx - 1] == b'/';
+        idx += 1;
+
+        if is_self_closing || VOID_TAGS.contains(&tag_name.as_slice()) {
+            continue;
+        }
+
+        depth += 1;
+        if depth > max_depth {
+            return Some(tag_start);
+        }
+
+        // Raw text is not scanned for tags
+        if RAW_TEXT_TAGS.contains(&tag_name.as_slice()) {
+            let mut closing_tag = b"</".to_vec();
+            closing_tag.extend_from_slice(&tag_name);
+            idx = find_ignore_case(bytes, idx, &closing_tag).unwrap_or(len);
+        }
+    }
+
+    None
+}
+
+pub(crate) fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+pub(crate) fn find_ignore_case(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+        .map(|pos| from + pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::Node;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_valid_input() {
+        let result = parse_lossy(
+            "<template><div>{{ msg }}</div></template>",
+            LossyParseOptions::default(),
+        );
+
+        assert!(result.errors.is_empty());
+        let template = result.descriptor.and_then(|d| d.template);
+        assert!(template.is_some_and(|t| t.roots.len() == 1));
+    }
+
+    #[test]
+    fn it_rejects_too_large_input() {
+        let options = LossyParseOptions {
+            max_input_size: 16,
+            ..Default::default()
+        };
+        let result = parse_lossy("<template><div></div></template>", options);
+
+        assert!(result.descriptor.is_none());
+        assert!(matches!(
+            result.errors.as_slice(),
+            [ParseError {
+                kind: ParseErrorKind::InputTooLarge,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn it_rejects_too_deep_nesting() {
+        let input = format!(
+            "<template>{}{}</template>",
+            "<div>".repeat(100_000),
+            "</div>".repeat(100_000)
+        );
+        let result = parse_lossy(
+            &input,
+            LossyParseOptions {
+                max_input_size: usize::MAX,
+                max_depth: 256,
+            },
+        );
+
+        assert!(result.descriptor.is_none());
+        assert!(matches!(
+            result.errors.as_slice(),
+            [ParseError {
+                kind: ParseErrorKind::NestingTooDeep,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn it_ignores_void_self_closing_and_raw_text() {
+        let input =
+            "<template><br><img><div/><!-- <div> --></template><script>if (a<b) {}</script>";
+        assert_eq!(None, find_too_deep_nesting(input, 1));
+
+        // `<template>` and `<div>`
+        assert_eq!(
+            Some(10),
+            find_too_deep_nesting("<template><div></div></template>", 1)
+        );
+    }
+
+    #[test]
+    fn it_drops_elements_deeper_than_max_depth() {
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(
+            "<template><div><span><b>deep</b></span></div></template>",
+            &mut errors,
+        );
+        parser.max_depth = Some(2);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+
+        let template = descriptor.template.expect("Should have template");
+        let Node::Element(ref div) = template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Node::Element(ref span) = div.children[0] else {
+            panic!("Child is not an element")
+        };
+        assert!(span.children.is_empty());
+
+        assert!(matches!(
+            errors.as_slice(),
+            [ParseError {
+                kind: ParseErrorKind::NestingTooDeep,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn it_does_not_panic_on_doctype_in_template() {
+        let result = parse_lossy(
+            "<template><!DOCTYPE html><div></div></template>",
+            LossyParseOptions::default(),
+        );
+
+        assert!(result.descriptor.is_some());
+    }
+}
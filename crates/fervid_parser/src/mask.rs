@@ -0,0 +1,128 @@
+//! Hiding the template interpolations from the HTML parser.
+//!
+//! Vue reads everything between the interpolation delimiters as an expression,
+//! but the HTML lexer sees a tag in `{{ a<b }}` and breaks the markup around it.
+
+use crate::lossy::{find, find_ignore_case, RAW_TEXT_TAGS};
+
+/// Replaces `<` inside the interpolations, so that the HTML lexer keeps them as text.
+/// It is a single byte like `<`, so that the offsets and spans are kept
+pub(crate) const MASKED_LT: char = '\u{1}';
+
+/// Replaces `<` inside the interpolations of `input` with [`MASKED_LT`].
+/// Returns `None` when there is nothing to replace.
+///
+/// Interpolations are only looked for in the text, not inside the tags, comments or raw text elements.
+pub(crate) fn mask_interpolations(input: &str, start_pat: &str, end_pat: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let len = bytes.len();
+    let mut masked: Option<Vec<u8>> = None;
+    let mut idx = 0;
+
+    while idx < len {
+        let rest = &bytes[idx..];
+
+        if rest.starts_with(start_pat.as_bytes()) {
+            let start = idx + start_pat.len();
+            let Some(end) = find(bytes, start, end_pat.as_bytes()) else {
+                break;
+            };
+
+            for (offset, &c) in bytes[start..end].iter().enumerate() {
+                if c == b'<' {
+                    let masked = masked.get_or_insert_with(|| bytes.to_vec());
+                    masked[start + offset] = MASKED_LT as u8;
+                }
+            }
+
+            idx = end + end_pat.len();
+            continue;
+        }
+
+        if bytes[idx] != b'<' {
+            idx += 1;
+            continue;
+        }
+
+        // Comments
+        if rest.starts_with(b"<!--") {
+            idx = find(bytes, idx + 4, b"-->").map_or(len, |end| end + 3);
+            continue;
+        }
+
+        // Not a tag, e.g. `a < b`
+        let is_end_tag = rest.get(1) == Some(&b'/');
+        let name_start = if is_end_tag { 2 } else { 1 };
+        if !rest
+            .get(name_start)
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, b'!' | b'?'))
+        {
+            idx += 1;
+            continue;
+        }
+
+        let name_len = rest[name_start..]
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || matches!(c, b'>' | b'/'))
+            .unwrap_or(rest.len() - name_start);
+        let tag_name = rest[name_start..name_start + name_len].to_ascii_lowercase();
+
+        // Find the end of the tag, skipping the quoted attribute values
+        idx += name_start + name_len;
+        let mut quote = None;
+        while idx < len {
+            match (quote, bytes[idx]) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, c @ (b'"' | b'\'')) => quote = Some(c),
+                (None, b'>') => break,
+                _ => {}
+            }
+            idx += 1;
+        }
+        idx += 1;
+
+        // Raw text is not scanned for interpolations
+        if !is_end_tag && RAW_TEXT_TAGS.contains(&tag_name.as_slice()) {
+            let mut closing_tag = b"</".to_vec();
+            closing_tag.extend_from_slice(&tag_name);
+            idx = find_ignore_case(bytes, idx, &closing_tag).unwrap_or(len);
+        }
+    }
+
+    // Only ASCII `<` is replaced with ASCII, so the input stays valid UTF-8
+    masked.map(|masked| String::from_utf8(masked).expect("masking keeps UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask(input: &str) -> Option<String> {
+        mask_interpolations(input, "{{", "}}")
+    }
+
+    #[test]
+    fn it_masks_lt_in_interpolations() {
+        assert_eq!(
+            Some("<div>{{ a\u{1}b }} < {{ c \u{1} d }}</div>".to_string()),
+            mask("<div>{{ a<b }} < {{ c < d }}</div>")
+        );
+    }
+
+    #[test]
+    fn it_keeps_input_without_lt_in_interpolations() {
+        assert_eq!(None, mask("<div>{{ a > b }}</div>"));
+        assert_eq!(None, mask("<div>{{ a<b </div>"));
+    }
+
+    #[test]
+    fn it_ignores_interpolations_outside_of_text() {
+        assert_eq!(None, mask("<div title=\"{{ a<b }}\">x</div>"));
+        assert_eq!(None, mask("<!-- {{ a<b }} -->"));
+        assert_eq!(
+            None,
+            mask("<template><div/></template><script>const s = '{{ a<b }}'</script>")
+        );
+    }
+}
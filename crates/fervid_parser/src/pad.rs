@@ -0,0 +1,126 @@
+use swc_core::common::Span;
+
+use crate::error::{ParseError, ParseErrorKind};
+
+/// How the extracted block content is padded, see [`pad_block_content`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPadding {
+    /// Prepend a newline for each line preceding the block, lines stay aligned
+    Line,
+    /// Replace everything preceding the block with spaces (newlines are kept),
+    /// both lines and columns stay aligned
+    Space,
+}
+
+/// Extracts the block content from the SFC `input` and pads it,
+/// so that the positions inside the extracted content match the positions in the original file.
+/// This is useful when a block is compiled independently, e.g. by a preprocessor,
+/// and the resulting source maps must point to the correct lines.
+///
+/// `content_span` is the span of the block content (e.g. [`fervid_core::SfcStyleBlock::span`]).
+/// Scripts need no padding: they are parsed in place, so their spans are already the offsets in `input`.
+///
+/// Fails when the span does not fall on the character boundaries of `input`, e.g. when it comes from another source.
+pub fn pad_block_content(
+    input: &str,
+    content_span: Span,
+    padding: BlockPadding,
+) -> Result<String, ParseError> {
+    // Spans start from 1
+    let lo = (content_span.lo.0.saturating_sub(1) as usize).min(input.len());
+    let hi = (content_span.hi.0.saturating_sub(1) as usize).clamp(lo, input.len());
+
+    let (Some(preceding), Some(content)) = (input.get(..lo), input.get(lo..hi)) else {
+        return Err(ParseError {
+            kind: ParseErrorKind::InvalidBlockSpan,
+            span: content_span,
+        });
+    };
+
+    let mut result = String::with_capacity(hi);
+    match padding {
+        BlockPadding::Line => {
+            let lines_count = preceding.matches('\n').count();
+            result.extend(std::iter::repeat('\n').take(lines_count));
+        }
+        BlockPadding::Space => {
+            result.extend(preceding.chars().map(|c| match c {
+                '\n' | '\r' => c,
+                _ => ' ',
+            }));
+        }
+    }
+    result.push_str(content);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::{BytePos, Spanned};
+
+    use crate::SfcParser;
+
+    use super::*;
+
+    const INPUT: &str =
+        "<template>\n  <div />\n</template>\n\n<style>\n.foo { color: red }\n</style>\n";
+
+    fn style_span() -> Span {
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(INPUT, &mut errors);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+        descriptor.styles[0].span
+    }
+
+    #[test]
+    fn it_pads_with_lines() {
+        assert_eq!(
+            "\n\n\n\n\n.foo { color: red }\n",
+            pad_block_content(INPUT, style_span(), BlockPadding::Line).expect("Should pad")
+        );
+    }
+
+    #[test]
+    fn it_pads_with_spaces() {
+        assert_eq!(
+            "          \n         \n           \n\n       \n.foo { color: red }\n",
+            pad_block_content(INPUT, style_span(), BlockPadding::Space).expect("Should pad")
+        );
+    }
+
+    #[test]
+    fn it_fails_on_span_inside_character() {
+        let input = "<style>.é { color: red }</style>";
+        // `é` is 2 bytes long, the span starts in its middle
+        let lo = input.find('é').expect("Should exist") as u32 + 2;
+        let span = Span::new(BytePos(lo), BytePos(input.len() as u32 - 7));
+
+        let error = pad_block_content(input, span, BlockPadding::Line).expect_err("Should fail");
+        assert!(matches!(error.kind, ParseErrorKind::InvalidBlockSpan));
+        assert_eq!(span, error.span);
+    }
+
+    #[test]
+    fn it_keeps_script_offsets_without_padding() {
+        let input = "<template>\n  <div />\n</template>\n\n<script>\nexport default {}\n</script>\n\n<script setup>\nconst foo = 1\n</script>\n";
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(input, &mut errors);
+        let descriptor = parser.parse_sfc().expect("Should parse");
+
+        // Spans start from 1
+        let stmt_source = |span: Span| &input[span.lo.0 as usize - 1..span.hi.0 as usize - 1];
+
+        let script = descriptor.script_legacy.expect("Should have script");
+        assert_eq!(
+            "export default {}",
+            stmt_source(script.content.body[0].span())
+        );
+
+        let script_setup = descriptor.script_setup.expect("Should have script setup");
+        assert_eq!(
+            "const foo = 1",
+            stmt_source(script_setup.content.body[0].span())
+        );
+    }
+}
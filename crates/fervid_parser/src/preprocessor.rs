@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use fervid_core::SfcScriptLang;
+
+/// A hook for compiling `<script>` blocks written in a language
+/// not natively supported by the parser (e.g. `<script lang="coffee">`).
+///
+/// The parser only calls it for unknown `lang`s,
+/// plain JavaScript and TypeScript are always parsed directly.
+pub trait ScriptPreprocessor: std::fmt::Debug {
+    /// Compiles the raw `content` written in `lang` to JavaScript or TypeScript.
+    /// Returns `None` when `lang` is not supported by this preprocessor.
+    fn preprocess_script(&self, lang: &str, content: &str) -> Option<PreprocessedScript>;
+}
+
+/// A hook for converting `<template>` blocks written in another language
+/// (e.g. `<template lang="pug">`) to HTML before they are parsed.
+///
+/// The parser only calls it for `lang`s other than `html`.
+/// Templates which are not converted are kept as raw text and rejected by the compiler.
+pub trait TemplatePreprocessor: std::fmt::Debug {
+    /// Converts the raw `content` written in `lang` to HTML.
+    /// Returns `None` when `lang` is not supported by this preprocessor.
+    fn preprocess_template(&self, lang: &str, content: &str) -> Option<String>;
+}
+
+// Allows sharing a preprocessor between the parsers, e.g. from the compile options
+impl<T: ScriptPreprocessor + ?Sized> ScriptPreprocessor for Arc<T> {
+    fn preprocess_script(&self, lang: &str, content: &str) -> Option<PreprocessedScript> {
+        (**self).preprocess_script(lang, content)
+    }
+}
+
+impl<T: TemplatePreprocessor + ?Sized> TemplatePreprocessor for Arc<T> {
+    fn preprocess_template(&self, lang: &str, content: &str) -> Option<String> {
+        (**self).preprocess_template(lang, content)
+    }
+}
+
+/// Result of running a [`ScriptPreprocessor`]
+#[derive(Debug)]
+pub struct PreprocessedScript {
+    pub code: String,
+    pub lang: SfcScriptLang,
+}
@@ -1,7 +1,10 @@
-use fervid_core::{SfcScriptBlock, SfcScriptLang};
+use fervid_core::{FervidAtom, SfcScriptBlock, SfcScriptLang};
 use swc_core::{
-    common::Span,
-    ecma::ast::{Expr, Module, Pat},
+    common::{BytePos, Span},
+    ecma::{
+        ast::{Expr, Module, Pat},
+        visit::{VisitMut, VisitMutWith},
+    },
 };
 use swc_ecma_parser::{lexer::Lexer, EsSyntax, Parser, StringInput, Syntax, TsSyntax};
 use swc_html_ast::{Child, Element};
@@ -19,6 +22,7 @@ impl SfcParser<'_, '_, '_> {
         let mut is_setup_seen = false;
         let mut is_lang_seen = false;
         let mut lang = SfcScriptLang::Es;
+        let mut unknown_lang: Option<(FervidAtom, Span)> = None;
         for attr in element.attributes.iter() {
             match attr.name.as_str() {
                 "setup" => {
@@ -41,12 +45,13 @@ impl SfcParser<'_, '_, '_> {
 
                     lang = match attr.value.as_ref().map(|v| v.as_str()) {
                         Some("ts" | "typescript") => SfcScriptLang::Typescript,
+                        Some("tsx") => SfcScriptLang::Tsx,
+                        Some("jsx") => SfcScriptLang::Jsx,
                         None | Some("js" | "javascript") => SfcScriptLang::Es,
-                        Some(_) => {
-                            return Err(ParseError {
-                                kind: ParseErrorKind::UnsupportedLang,
-                                span: attr.span,
-                            });
+                        Some(other) => {
+                            // Needs a preprocessor, checked when the content is known
+                            unknown_lang = Some((FervidAtom::from(other), attr.span));
+                            SfcScriptLang::Es
                         }
                     }
                 }
@@ -54,6 +59,16 @@ impl SfcParser<'_, '_, '_> {
             }
         }
 
+        // Unknown `lang` without a preprocessor is an error
+        if let Some((ref unknown_lang, span)) = unknown_lang {
+            if self.script_preprocessor.is_none() {
+                return Err(ParseError {
+                    kind: ParseErrorKind::UnsupportedScriptLang(unknown_lang.to_owned()),
+                    span,
+                });
+            }
+        }
+
         // `<script>` should always have a single `Text` child
         let script_content = match element.children.get(0) {
             Some(Child::Text(t)) => t,
@@ -86,13 +101,54 @@ impl SfcParser<'_, '_, '_> {
             return Ok(None);
         }
 
+        // Run the preprocessor for an unknown `lang`
+        if let Some((unknown_lang, span)) = unknown_lang {
+            let preprocessed = self
+                .script_preprocessor
+                .as_ref()
+                .and_then(|p| p.preprocess_script(&unknown_lang, &script_content.data));
+
+            let Some(preprocessed) = preprocessed else {
+                return Err(ParseError {
+                    kind: ParseErrorKind::UnsupportedScriptLang(unknown_lang),
+                    span,
+                });
+            };
+
+            // Spans are relative to the preprocessed code, starting at the original content.
+            // The code can be longer than the content, so the spans are clamped into the content
+            let lo = script_content.span.lo;
+            let preprocessed_span = Span {
+                lo,
+                hi: lo + BytePos(preprocessed.code.len() as u32),
+            };
+            let mut clamp_spans = ClampSpans(script_content.span);
+            let errors_start = self.errors.len();
+            let module_content = self.parse_module(
+                &preprocessed.code,
+                script_syntax(&preprocessed.lang),
+                preprocessed_span,
+            );
+            for error in self.errors[errors_start..].iter_mut() {
+                clamp_spans.visit_mut_span(&mut error.span);
+            }
+            let mut module_content = module_content.map_err(|mut e| {
+                clamp_spans.visit_mut_span(&mut e.span);
+                e
+            })?;
+            module_content.visit_mut_with(&mut clamp_spans);
+
+            return Ok(Some(SfcScriptBlock {
+                content: Box::new(module_content),
+                lang: preprocessed.lang,
+                is_setup,
+                span: element.span,
+            }));
+        }
+
         let module_content = self.parse_module(
             &script_content.data,
-            if matches!(lang, SfcScriptLang::Typescript) {
-                Syntax::Typescript(TsSyntax::default())
-            } else {
-                Syntax::Es(EsSyntax::default())
-            },
+            script_syntax(&lang),
             script_content.span,
         )?;
 
@@ -104,6 +160,18 @@ impl SfcParser<'_, '_, '_> {
         }))
     }
 
+    /// Syntax of the template expressions.
+    /// TypeScript is only enabled when one of the scripts is in TypeScript,
+    /// because some expressions are parsed differently, e.g. `a < b > (c)`.
+    #[inline]
+    pub fn expr_syntax(&self) -> Syntax {
+        if self.is_ts {
+            Syntax::Typescript(TsSyntax::default())
+        } else {
+            Syntax::Es(EsSyntax::default())
+        }
+    }
+
     #[inline]
     pub fn parse_module(
         &mut self,
@@ -172,3 +240,33 @@ impl SfcParser<'_, '_, '_> {
         parse_result.map_err(From::from)
     }
 }
+
+/// Moves the spans into the given span, keeping the dummy ones
+struct ClampSpans(Span);
+
+impl VisitMut for ClampSpans {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        if span.is_dummy() {
+            return;
+        }
+
+        span.lo = span.lo.clamp(self.0.lo, self.0.hi);
+        span.hi = span.hi.clamp(span.lo, self.0.hi);
+    }
+}
+
+/// Parser syntax of a `<script>` block based on its `lang`
+pub fn script_syntax(lang: &SfcScriptLang) -> Syntax {
+    match lang {
+        SfcScriptLang::Es => Syntax::Es(EsSyntax::default()),
+        SfcScriptLang::Jsx => Syntax::Es(EsSyntax {
+            jsx: true,
+            ..Default::default()
+        }),
+        SfcScriptLang::Typescript => Syntax::Typescript(TsSyntax::default()),
+        SfcScriptLang::Tsx => Syntax::Typescript(TsSyntax {
+            tsx: true,
+            ..Default::default()
+        }),
+    }
+}
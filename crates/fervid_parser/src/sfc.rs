@@ -1,4 +1,4 @@
-use fervid_core::{fervid_atom, SfcDescriptor};
+use fervid_core::{ensure_stack_for_depth, fervid_atom, max_nesting_depth, SfcDescriptor};
 use swc_core::common::{BytePos, Span, Spanned, DUMMY_SP};
 use swc_ecma_parser::StringInput;
 use swc_html_ast::{Child, DocumentFragment, DocumentMode, Element, Namespace};
@@ -9,15 +9,19 @@ use swc_html_parser::{
 
 use crate::{
     error::{ParseError, ParseErrorKind},
-    SfcParser,
+    mask::mask_interpolations,
+    SfcParser, MAX_INPUT_SIZE,
 };
 
 type SwcHtmlParserError = swc_html_parser::error::Error;
+type SwcHtmlErrorKind = swc_html_parser::error::ErrorKind;
 
 impl SfcParser<'_, '_, '_> {
     /// Parses `self.input` as an SFC, producing an `SfcDescriptor`.
     /// When `Err(ParseError)` is returned, that means unrecoverable error was discovered.
     pub fn parse_sfc(&mut self) -> Result<SfcDescriptor, ParseError> {
+        self.check_input_size()?;
+
         let parsed_html = self.parse_html_document_fragment().map_err(|e| {
             let kind = e.into_inner().1;
 
@@ -32,6 +36,21 @@ impl SfcParser<'_, '_, '_> {
 
         let mut sfc_descriptor = SfcDescriptor::default();
 
+        // Template expressions are TypeScript when any of the scripts is.
+        // This is known before parsing, because `<template>` may come before `<script>`.
+        self.is_ts = parsed_html.children.iter().any(|child| match child {
+            Child::Element(element) if element.tag_name.eq("script") => {
+                element.attributes.iter().any(|attr| {
+                    attr.name.eq("lang")
+                        && matches!(
+                            attr.value.as_ref().map(|v| v.as_str()),
+                            Some("ts" | "typescript" | "tsx")
+                        )
+                })
+            }
+            _ => false,
+        });
+
         macro_rules! report_error {
             ($kind: ident, $span: expr) => {
                 self.report_error(ParseError {
@@ -41,6 +60,9 @@ impl SfcParser<'_, '_, '_> {
             };
         }
 
+        // `<script src>` is only allowed when there is no `<script setup>`
+        let mut script_legacy_src_span: Option<Span> = None;
+
         for root_node in parsed_html.children.into_iter() {
             // Only root elements are supported
             let Child::Element(root_element) = root_node else {
@@ -65,6 +87,26 @@ impl SfcParser<'_, '_, '_> {
 
                 sfc_descriptor.template = template_result;
             } else if tag_name.eq("script") {
+                // `src` is checked early, because such blocks are usually empty and ignored
+                let has_src = root_element
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.name.eq("src"));
+                if has_src {
+                    let has_setup = root_element
+                        .attributes
+                        .iter()
+                        .any(|attr| attr.name.eq("setup"));
+
+                    // `<script setup src>` is ambiguous outside of the component
+                    if has_setup {
+                        report_error!(ScriptSetupSrc, root_node_span);
+                        continue;
+                    }
+
+                    script_legacy_src_span = Some(root_node_span);
+                }
+
                 let sfc_script_block = match self.parse_sfc_script_element(root_element) {
                     Ok(Some(v)) => v,
                     Ok(None) => continue,
@@ -100,10 +142,27 @@ impl SfcParser<'_, '_, '_> {
             }
         }
 
-        // Emit an error if neither of `<template>` and both `<script>`s are present
+        if let Some(ref script_setup) = sfc_descriptor.script_setup {
+            // `<script src>` and `<script setup>` must be processed together
+            if let Some(span) = script_legacy_src_span {
+                report_error!(ScriptSrcWithScriptSetup, span);
+                sfc_descriptor.script_legacy = None;
+            }
+
+            // Both scripts must use the same language
+            if let Some(ref script_legacy) = sfc_descriptor.script_legacy {
+                if script_legacy.lang != script_setup.lang {
+                    report_error!(ScriptLangMismatch, script_setup.span);
+                }
+            }
+        }
+
+        // Emit an error if neither of `<template>` and both `<script>`s are present.
+        // Style-only SFCs are allowed and compile to a style-only module.
         if sfc_descriptor.template.is_none()
             && sfc_descriptor.script_legacy.is_none()
             && sfc_descriptor.script_setup.is_none()
+            && sfc_descriptor.styles.is_empty()
         {
             self.report_error(ParseError {
                 kind: ParseErrorKind::MissingTemplateOrScript,
@@ -114,13 +173,37 @@ impl SfcParser<'_, '_, '_> {
         Ok(sfc_descriptor)
     }
 
+    /// Rejects the input which the spans cannot address, see [`MAX_INPUT_SIZE`]
+    pub(crate) fn check_input_size(&self) -> Result<(), ParseError> {
+        if self.input.len() <= MAX_INPUT_SIZE {
+            return Ok(());
+        }
+
+        Err(ParseError {
+            kind: ParseErrorKind::InputTooLarge,
+            span: Span {
+                lo: BytePos(1),
+                hi: BytePos(MAX_INPUT_SIZE as u32 + 1),
+            },
+        })
+    }
+
     /// Adapted from `swc_html_parser`
     #[inline]
     pub fn parse_html_document_fragment(&mut self) -> Result<DocumentFragment, SwcHtmlParserError> {
-        let lexer = Lexer::new(StringInput::new(
+        // `{{ a<b }}` is an expression in Vue, but a tag for the HTML lexer
+        let masked_input = mask_interpolations(
             self.input,
+            self.interpolation_start_pat,
+            self.interpolation_end_pat,
+        );
+        self.has_masked_interpolations = masked_input.is_some();
+        let input = masked_input.as_deref().unwrap_or(self.input);
+
+        let lexer = Lexer::new(StringInput::new(
+            input,
             BytePos(1),
-            BytePos(self.input.len() as u32),
+            BytePos(input.len() as u32),
         ));
 
         let parser_config = ParserConfig {
@@ -140,7 +223,10 @@ impl SfcParser<'_, '_, '_> {
             is_self_closing: false,
         };
 
-        let result = parser.parse_document_fragment(ctx_element, DocumentMode::NoQuirks, None);
+        // `swc_html_parser` builds the tree recursively, one call per nesting level
+        let result = ensure_stack_for_depth(max_nesting_depth(self.input), || {
+            parser.parse_document_fragment(ctx_element, DocumentMode::NoQuirks, None)
+        });
 
         let html_parse_errors = parser.take_errors();
 
@@ -148,6 +234,22 @@ impl SfcParser<'_, '_, '_> {
         self.errors.reserve(html_parse_errors.len());
         for html_parse_error in html_parse_errors {
             let e = html_parse_error.into_inner();
+
+            // Self-closing tags, e.g. `<slot />` or `<Foo />`, are valid in Vue templates
+            if matches!(
+                e.1,
+                SwcHtmlErrorKind::NonVoidHtmlElementStartTagWithTrailingSolidus
+            ) {
+                continue;
+            }
+
+            // `<` masked in the interpolations
+            if matches!(e.1, SwcHtmlErrorKind::ControlCharacterInInputStream)
+                && self.input.as_bytes().get(e.0.lo.0 as usize - 1) == Some(&b'<')
+            {
+                continue;
+            }
+
             self.errors.push(ParseError {
                 kind: ParseErrorKind::InvalidHtml(Box::new(e.1)),
                 span: e.0,
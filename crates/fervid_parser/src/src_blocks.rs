@@ -0,0 +1,354 @@
+use std::borrow::Cow;
+
+use fervid_core::FervidAtom;
+use swc_core::common::{BytePos, Span};
+use swc_html_ast::Child;
+
+use crate::{
+    error::{ParseError, ParseErrorKind},
+    SfcParser,
+};
+
+/// Result of [`inline_src_blocks`]
+#[derive(Debug)]
+pub struct InlinedSrcBlocks<'i> {
+    /// The SFC with the external blocks in place, to be parsed instead of the original input
+    pub source: Cow<'i, str>,
+    /// `src`s of the loaded blocks, in order of appearance
+    pub loaded: Vec<FervidAtom>,
+    /// Blocks which could not be loaded. Their spans refer to `source`
+    pub errors: Vec<ParseError>,
+    /// Blocks rewritten in `source`, in order of appearance
+    pub blocks: Vec<InlinedSrcBlock>,
+}
+
+/// A block whose `src` was replaced by the loaded content or by the import of the script
+#[derive(Debug, Clone, Copy)]
+pub struct InlinedSrcBlock {
+    /// The `src` attribute in the original input
+    pub src: Span,
+    /// Part of the original input which was replaced, from the `src` attribute to the end of the block
+    pub replaced: Span,
+    /// The replacement in `source`: the rest of the start tag, the content and the end tag
+    pub inlined: Span,
+}
+
+impl InlinedSrcBlocks<'_> {
+    /// Maps a span of `source` to the original input.
+    /// Spans inside of the inlined content point to the `src` attribute of their block,
+    /// e.g. an error in the loaded template is reported at `src="./template.html"`.
+    pub fn original_span(&self, span: Span) -> Span {
+        let lo = match self.locate(span.lo) {
+            Ok(lo) => lo,
+            Err(block) => block.src.lo,
+        };
+        let hi = match self.locate(span.hi) {
+            Ok(hi) => hi,
+            Err(block) => block.src.hi,
+        };
+
+        Span { lo, hi }
+    }
+
+    /// Maps a position of `source` to the original input, see [`InlinedSrcBlocks::original_span`]
+    pub fn original_pos(&self, pos: BytePos) -> BytePos {
+        match self.locate(pos) {
+            Ok(pos) => pos,
+            Err(block) => block.src.lo,
+        }
+    }
+
+    /// Whether the span of `source` starts inside of the inlined content
+    pub fn is_inlined(&self, span: Span) -> bool {
+        self.locate(span.lo).is_err()
+    }
+
+    /// Position in the original input, or the block when `pos` is inside of its replacement
+    fn locate(&self, pos: BytePos) -> Result<BytePos, &InlinedSrcBlock> {
+        // The blocks before `pos` shift it by the difference of their lengths
+        let mut shift = 0i64;
+        for block in self.blocks.iter() {
+            if pos <= block.inlined.lo {
+                break;
+            }
+            if pos < block.inlined.hi {
+                return Err(block);
+            }
+            shift = block.inlined.hi.0 as i64 - block.replaced.hi.0 as i64;
+        }
+
+        Ok(BytePos((pos.0 as i64 - shift) as u32))
+    }
+}
+
+/// Puts the contents of the external blocks (e.g. `<template src="./template.html">`)
+/// into the SFC, so that it can be parsed and compiled as usual.
+///
+/// - `<template src>` and `<style src>` get the contents returned by `load` for their `src`;
+/// - `<script src>` becomes an import of `src`, which the bundler resolves relative to the SFC.
+///   The script is not inlined, because its own imports are relative to its file.
+///   `<script src>` next to `<script setup>` is kept as is for the parser to report.
+///
+/// The spans of the parsed SFC refer to the returned `source`, not to the original input.
+/// Use [`InlinedSrcBlocks::original_span`] to report them against the input.
+pub fn inline_src_blocks<'i>(
+    input: &'i str,
+    load: &mut dyn FnMut(&str) -> Option<String>,
+) -> InlinedSrcBlocks<'i> {
+    let mut result = InlinedSrcBlocks {
+        source: Cow::Borrowed(input),
+        loaded: Vec::new(),
+        errors: Vec::new(),
+        blocks: Vec::new(),
+    };
+
+    // Most of the SFCs do not have external blocks
+    if !input.contains("src") {
+        return result;
+    }
+
+    // The errors are reported when the `source` gets parsed
+    let mut html_errors = Vec::new();
+    let mut parser = SfcParser::new(input, &mut html_errors);
+    let Ok(fragment) = parser.parse_html_document_fragment() else {
+        return result;
+    };
+
+    let has_script_setup = fragment.children.iter().any(|child| match child {
+        Child::Element(element) if element.tag_name.eq("script") => {
+            element.attributes.iter().any(|attr| attr.name.eq("setup"))
+        }
+        _ => false,
+    });
+
+    let mut output = String::new();
+    let mut copied_up_to = 0;
+
+    for child in fragment.children.iter() {
+        let Child::Element(element) = child else {
+            continue;
+        };
+
+        let tag_name = element.tag_name.as_str();
+        if !matches!(tag_name, "template" | "script" | "style") {
+            continue;
+        }
+
+        let Some(src_attr) = element.attributes.iter().find(|attr| attr.name.eq("src")) else {
+            continue;
+        };
+        let Some(ref src) = src_attr.value else {
+            continue;
+        };
+
+        let is_script = tag_name == "script";
+        if is_script && has_script_setup {
+            continue;
+        }
+
+        // Spans start from 1
+        let lo = element.span.lo.0 as usize - 1;
+        let hi = element.span.hi.0 as usize - 1;
+        let src_lo = src_attr.span.lo.0 as usize - 1;
+        let src_hi = src_attr.span.hi.0 as usize - 1;
+
+        // Start tag ends after the last attribute, e.g. `<template src="./foo.html" />`
+        let attrs_hi = element
+            .attributes
+            .iter()
+            .map(|attr| attr.span.hi.0 as usize - 1)
+            .max()
+            .unwrap_or(src_hi);
+        let Some(tag_end) = input[attrs_hi..hi].find('>').map(|idx| attrs_hi + idx) else {
+            continue;
+        };
+        let start_tag_hi = if input[..tag_end].ends_with('/') {
+            tag_end - 1
+        } else {
+            tag_end
+        };
+
+        let content = if is_script {
+            import_src(src)
+        } else if let Some(content) = load(src) {
+            result.loaded.push(src.to_owned());
+            content
+        } else {
+            // The block stays empty, its position in the output is shifted by the previous blocks
+            let output_lo = output.len() + (lo - copied_up_to);
+            result.errors.push(ParseError {
+                kind: ParseErrorKind::UnresolvedSrc(src.to_owned()),
+                span: Span {
+                    lo: BytePos((output_lo + 1) as u32),
+                    hi: BytePos((output_lo + (hi - lo) + 1) as u32),
+                },
+            });
+            continue;
+        };
+
+        // Same start tag without `src`, the loaded content and the end tag
+        output.push_str(&input[copied_up_to..src_lo]);
+        let inlined_lo = output.len();
+        output.push_str(&input[src_hi..start_tag_hi]);
+        output.push('>');
+        output.push_str(&content);
+        output.push_str("</");
+        output.push_str(tag_name);
+        output.push('>');
+        copied_up_to = hi;
+
+        result.blocks.push(InlinedSrcBlock {
+            src: src_attr.span,
+            replaced: Span {
+                lo: BytePos((src_lo + 1) as u32),
+                hi: element.span.hi,
+            },
+            inlined: Span {
+                lo: BytePos((inlined_lo + 1) as u32),
+                hi: BytePos((output.len() + 1) as u32),
+            },
+        });
+    }
+
+    if copied_up_to != 0 {
+        output.push_str(&input[copied_up_to..]);
+        result.source = Cow::Owned(output);
+    }
+
+    result
+}
+
+/// `export *` keeps the named exports of the script,
+/// the default export is spread because the component object is extended by the compiler
+fn import_src(src: &str) -> String {
+    let src = src.replace('\\', "\\\\").replace('"', "\\\"");
+
+    format!(
+        "\nimport _sfc_script from \"{src}\";\nexport * from \"{src}\";\nexport default {{ ..._sfc_script }};\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline<'i>(input: &'i str, files: &[(&str, &str)]) -> InlinedSrcBlocks<'i> {
+        inline_src_blocks(input, &mut |src| {
+            files
+                .iter()
+                .find(|(path, _)| *path == src)
+                .map(|(_, content)| content.to_string())
+        })
+    }
+
+    #[test]
+    fn it_keeps_sfc_without_src() {
+        let input = "<template><div /></template>\n<style>.foo {}</style>";
+        let result = inline(input, &[]);
+        assert!(matches!(result.source, Cow::Borrowed(_)));
+        assert!(result.loaded.is_empty());
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn it_inlines_template_and_style() {
+        let result = inline(
+            "<template src=\"./tpl.html\"></template>\n<style scoped src=\"./style.css\" />\n",
+            &[("./tpl.html", "<div>hi</div>"), ("./style.css", ".foo {}")],
+        );
+
+        // `<style />` is not closed in HTML, so its block spans until the end of the input
+        assert_eq!(
+            "<template ><div>hi</div></template>\n<style scoped  >.foo {}</style>",
+            result.source
+        );
+        let loaded: Vec<&str> = result.loaded.iter().map(|src| src.as_str()).collect();
+        assert_eq!(vec!["./tpl.html", "./style.css"], loaded);
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn it_maps_spans_to_input() {
+        let input =
+            "<template src=\"./tpl.html\"></template>\n<style src=\"./style.css\"></style>\n";
+        let result = inline(
+            input,
+            &[("./tpl.html", "<div>hi</div>"), ("./style.css", ".foo {}")],
+        );
+
+        // Spans start from 1
+        let source_span = |text: &str| {
+            let lo = result.source.find(text).expect("Should be in source") as u32 + 1;
+            Span {
+                lo: BytePos(lo),
+                hi: BytePos(lo + text.len() as u32),
+            }
+        };
+        let input_text = |span: Span| &input[(span.lo.0 - 1) as usize..(span.hi.0 - 1) as usize];
+
+        // Outside of the blocks
+        let newline = source_span("\n<style");
+        assert!(!result.is_inlined(newline));
+        assert_eq!("\n<style", input_text(result.original_span(newline)));
+        assert_eq!(BytePos(1), result.original_pos(BytePos(1)));
+
+        // Inside of the loaded content
+        let div = source_span("<div>");
+        assert!(result.is_inlined(div));
+        assert_eq!("src=\"./tpl.html\"", input_text(result.original_span(div)));
+        let style = source_span(".foo {}");
+        assert_eq!(
+            "src=\"./style.css\"",
+            input_text(result.original_span(style))
+        );
+
+        // Whole blocks
+        let template = source_span("<template ><div>hi</div></template>");
+        assert_eq!(
+            "<template src=\"./tpl.html\"></template>",
+            input_text(result.original_span(template))
+        );
+    }
+
+    #[test]
+    fn it_imports_script_src() {
+        let result = inline("<script lang=\"ts\" src=\"./logic.ts\"></script>", &[]);
+
+        assert_eq!(
+            "<script lang=\"ts\" >\nimport _sfc_script from \"./logic.ts\";\nexport * from \"./logic.ts\";\nexport default { ..._sfc_script };\n</script>",
+            result.source
+        );
+        assert!(result.loaded.is_empty());
+    }
+
+    #[test]
+    fn it_keeps_script_src_with_script_setup() {
+        let input = "<script src=\"./logic.js\"></script><script setup></script>";
+        assert_eq!(input, inline(input, &[]).source);
+    }
+
+    #[test]
+    fn it_reports_unresolved_src() {
+        let result = inline(
+            "<style src=\"./a.css\"></style><template src=\"./missing.html\"></template>",
+            &[("./a.css", ".a {}")],
+        );
+
+        assert_eq!(
+            "<style >.a {}</style><template src=\"./missing.html\"></template>",
+            result.source
+        );
+        assert_eq!(1, result.errors.len());
+        let error = &result.errors[0];
+        assert!(
+            matches!(&error.kind, ParseErrorKind::UnresolvedSrc(src) if src == "./missing.html")
+        );
+
+        // Spans start from 1
+        let span = error.span;
+        assert_eq!(
+            "<template src=\"./missing.html\"></template>",
+            &result.source[(span.lo.0 - 1) as usize..(span.hi.0 - 1) as usize]
+        );
+    }
+}
@@ -8,7 +8,7 @@ impl SfcParser<'_, '_, '_> {
         // Find the attributes
         let mut lang = fervid_atom!("css");
         let mut is_scoped = false;
-        let mut is_module = false;
+        let mut module = None;
 
         for attr in element.attributes.into_iter() {
             if attr.name.eq("lang") {
@@ -20,7 +20,11 @@ impl SfcParser<'_, '_, '_> {
             } else if attr.name.eq("scoped") {
                 is_scoped = true;
             } else if attr.name.eq("module") {
-                is_module = true;
+                // `<style module>` is exposed as `$style`
+                module = match attr.value {
+                    Some(name) if !name.is_empty() => Some(name),
+                    _ => Some(fervid_atom!("$style")),
+                };
             }
         }
 
@@ -42,7 +46,7 @@ impl SfcParser<'_, '_, '_> {
                     lang,
                     content: fervid_atom!(""),
                     is_scoped,
-                    is_module,
+                    module,
                     span: element.span,
                 });
             }
@@ -57,7 +61,7 @@ impl SfcParser<'_, '_, '_> {
             lang,
             content: style_content.data,
             is_scoped,
-            is_module,
+            module,
             span: style_content.span,
         })
     }
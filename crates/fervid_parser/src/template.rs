@@ -1,14 +1,37 @@
 use fervid_core::{
-    fervid_atom, is_html_tag, AttributeOrBinding, ElementNode, FervidAtom, Interpolation, Node,
-    PatchHints, SfcTemplateBlock, StartingTag, VueDirectives,
+    ensure_sufficient_stack, fervid_atom, is_html_tag, AttributeOrBinding, ElementNode, FervidAtom,
+    Interpolation, Node, PatchHints, SfcTemplateBlock, StartingTag, VueDirectives,
 };
 use swc_core::common::{BytePos, Span};
-use swc_ecma_parser::{Syntax, TsSyntax};
 use swc_html_ast::{Child, Element, Text};
 
-use crate::SfcParser;
+use crate::{error::ParseErrorKind, mask::MASKED_LT, ParseError, SfcParser};
 
 impl SfcParser<'_, '_, '_> {
+    /// Parses the whole input as the contents of a `<template>`.
+    /// This is used for compiling templates independently from an SFC.
+    pub fn parse_template(&mut self) -> Result<SfcTemplateBlock, ParseError> {
+        self.check_input_size()?;
+
+        let parsed_html = self.parse_html_document_fragment().map_err(|e| {
+            let kind = e.into_inner().1;
+
+            ParseError {
+                kind: ParseErrorKind::InvalidHtml(Box::new(kind)),
+                span: Span {
+                    lo: BytePos(1),
+                    hi: BytePos(self.input.len() as u32),
+                },
+            }
+        })?;
+
+        Ok(SfcTemplateBlock {
+            lang: fervid_atom!("html"),
+            roots: self.process_element_children(parsed_html.children),
+            span: parsed_html.span,
+        })
+    }
+
     pub fn parse_template_to_ir(&mut self, root_element: Element) -> Option<SfcTemplateBlock> {
         // TODO Errors in template
 
@@ -37,12 +60,30 @@ impl SfcParser<'_, '_, '_> {
             })
             .unwrap_or_else(html_atom);
 
-        // Do not parse non-html templates
+        // Do not parse non-html templates, unless a preprocessor converts them to HTML
         if lang != "html" {
-            let roots = if let Some((content, content_span)) =
-                self.use_rawtext_content(root_element.content.as_ref(), &root_element.children)
-            {
-                vec![Node::Text(FervidAtom::from(content), content_span)]
+            let raw_content = self
+                .use_rawtext_content(root_element.content.as_ref(), &root_element.children)
+                .map(|(content, content_span)| (FervidAtom::from(content), content_span));
+
+            if let Some((ref content, content_span)) = raw_content {
+                let html = self
+                    .template_preprocessor
+                    .as_ref()
+                    .filter(|_| !content_span.is_dummy())
+                    .and_then(|p| p.preprocess_template(&lang, content));
+
+                if let Some(html) = html {
+                    return Some(self.parse_preprocessed_template(
+                        &html,
+                        content_span,
+                        root_element.span,
+                    ));
+                }
+            }
+
+            let roots = if let Some((content, content_span)) = raw_content {
+                vec![Node::Text(content, content_span)]
             } else {
                 vec![]
             };
@@ -67,6 +108,45 @@ impl SfcParser<'_, '_, '_> {
         })
     }
 
+    /// Parses the HTML produced by a [`TemplatePreprocessor`](crate::TemplatePreprocessor).
+    /// Like for the preprocessed scripts, the spans are relative to the HTML
+    /// and start at the original content.
+    fn parse_preprocessed_template(
+        &mut self,
+        html: &str,
+        content_span: Span,
+        span: Span,
+    ) -> SfcTemplateBlock {
+        // The input before the content becomes whitespace, so that the offsets are kept.
+        // Spans start from 1
+        let lo = content_span.lo.0 as usize - 1;
+        let mut padded = String::with_capacity(lo + html.len());
+        padded.extend(self.input.as_bytes()[..lo].iter().map(|&b| match b {
+            b'\n' | b'\r' => b as char,
+            _ => ' ',
+        }));
+        padded.push_str(html);
+
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(&padded, &mut errors);
+        parser.is_ts = self.is_ts;
+        parser.max_depth = self.max_depth;
+        let roots = match parser.parse_template() {
+            Ok(template) => template.roots,
+            Err(e) => {
+                errors.push(e);
+                vec![]
+            }
+        };
+        self.errors.extend(errors);
+
+        SfcTemplateBlock {
+            lang: fervid_atom!("html"),
+            roots,
+            span,
+        }
+    }
+
     fn process_element(&mut self, element: Element) -> Node {
         let children: Vec<Child> = element
             .content
@@ -83,8 +163,10 @@ impl SfcParser<'_, '_, '_> {
         let tag_name = if is_html_tag(&element.tag_name) {
             element.tag_name
         } else {
-            let raw_name = &self.input[raw_idx_start..raw_idx_end];
-            raw_name.into()
+            match self.input.get(raw_idx_start..raw_idx_end) {
+                Some(raw_name) => raw_name.into(),
+                None => element.tag_name,
+            }
         };
 
         // Save old `v-pre` (restored at the end of the function)
@@ -111,10 +193,14 @@ impl SfcParser<'_, '_, '_> {
             directives,
         };
 
+        self.depth += 1;
+        let children = self.process_element_children(children);
+        self.depth -= 1;
+
         let result = Node::Element(ElementNode {
             kind: fervid_core::ElementKind::Element,
             starting_tag,
-            children: self.process_element_children(children),
+            children,
             template_scope: 0,
             patch_hints: PatchHints::default(),
             span: element.span,
@@ -129,8 +215,22 @@ impl SfcParser<'_, '_, '_> {
 
         for child in children {
             match child {
-                Child::DocumentType(_) => unimplemented!("Doctype is unsupported"),
-                Child::Element(element) => out.push(self.process_element(element)),
+                // Doctype has no meaning inside a template
+                Child::DocumentType(_) => {}
+                Child::Element(element) => {
+                    if self
+                        .max_depth
+                        .is_some_and(|max_depth| self.depth >= max_depth)
+                    {
+                        self.report_error(ParseError {
+                            kind: ParseErrorKind::NestingTooDeep,
+                            span: element.span,
+                        });
+                        continue;
+                    }
+
+                    out.push(ensure_sufficient_stack(|| self.process_element(element)))
+                }
                 Child::Text(text) => self.process_text(text, &mut out),
                 Child::Comment(comment) => out.push(Node::Comment(comment.data, comment.span)),
             }
@@ -140,7 +240,12 @@ impl SfcParser<'_, '_, '_> {
     }
 
     /// Separates a raw text into `Node::Text`s and `Node::Interpolation`s
-    fn process_text(&mut self, text: Text, out: &mut Vec<Node>) {
+    fn process_text(&mut self, mut text: Text, out: &mut Vec<Node>) {
+        // Restore `<` hidden from the HTML parser
+        if self.has_masked_interpolations && text.data.contains(MASKED_LT) {
+            text.data = FervidAtom::from(text.data.replace(MASKED_LT, "<"));
+        }
+
         // `v-pre` logic
         if self.is_pre {
             out.push(Node::Text(text.data, text.span));
@@ -196,11 +301,7 @@ impl SfcParser<'_, '_, '_> {
                 BytePos(offset + interpolation.len() as u32),
             );
 
-            match self.parse_expr(
-                interpolation,
-                Syntax::Typescript(TsSyntax::default()),
-                interpolation_span,
-            ) {
+            match self.parse_expr(interpolation, self.expr_syntax(), interpolation_span) {
                 Ok(parsed_interpolation) => out.push(Node::Interpolation(Interpolation {
                     value: parsed_interpolation,
                     template_scope: 0,
@@ -0,0 +1,458 @@
+#![deny(clippy::all)]
+
+//! Integration API for bundlers written in Rust (e.g. rolldown or rspack core),
+//! which allows to compile Vue SFCs in-process without crossing the JS boundary.
+//!
+//! A bundler calls [`VueModuleCompiler::transform`] for every `.vue` module
+//! and gets back the compiled JS chunk, which imports the styles and the custom blocks
+//! by their virtual module ids, e.g. `App.vue?vue&type=style&index=0&lang.css`.
+//! These ids are then served by [`VueModuleCompiler::load_virtual`],
+//! so that the bundler passes them through its own pipeline (e.g. Sass or i18n loaders).
+//!
+//! ```
+//! use fervid_plugin::{FervidPlugin, ModuleRequest, VueModuleCompiler};
+//!
+//! let plugin = FervidPlugin::default();
+//! let compiled = plugin
+//!     .transform(&ModuleRequest {
+//!         filename: "/src/App.vue",
+//!         source: "<template><div>hi</div></template><style>.a{color:red}</style>",
+//!     })
+//!     .unwrap();
+//!
+//! let style_id = &compiled.virtual_modules[0].id;
+//! assert_eq!(style_id, "/src/App.vue?vue&type=style&index=0&lang.css");
+//! assert!(plugin.load_virtual(style_id).is_some());
+//! ```
+
+use std::{borrow::Cow, sync::Mutex};
+
+use fervid::{compile, errors::CompileError, CompileOptions};
+use fxhash::FxHashMap;
+
+/// A request of a `.vue` module from the bundler
+pub struct ModuleRequest<'r> {
+    /// Resolved path of the module
+    pub filename: &'r str,
+    /// Contents of the module
+    pub source: &'r str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkKind {
+    /// JS module of the component
+    Js,
+    /// Style block in the language `lang`, e.g. `css` or `scss`.
+    /// Scoped styles which are not compiled to CSS by fervid (see `lang`)
+    /// are scoped by the bundler after preprocessing, e.g. with `fervid_css`
+    Style {
+        lang: String,
+        is_scoped: bool,
+        /// Scope id of the component, e.g. `data-v-7ba5bd90`
+        scope_id: String,
+    },
+    /// Custom block, e.g. `<i18n lang="json">`.
+    /// Without the `lang` attribute, the language is the block name
+    CustomBlock { block_type: String, lang: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledChunk {
+    /// Id under which the bundler knows the chunk
+    pub id: String,
+    pub kind: ChunkKind,
+    pub code: String,
+    pub source_map: Option<String>,
+}
+
+pub struct CompiledModule {
+    /// Main JS chunk of the component
+    pub main: CompiledChunk,
+    /// Virtual modules imported from the main chunk
+    pub virtual_modules: Vec<CompiledChunk>,
+    /// Errors and warnings of the compilation
+    pub errors: Vec<CompileError>,
+    /// Files the module depends on, which the bundler should watch
+    pub deps: Vec<String>,
+}
+
+/// Interface of a Vue compiler driven by a bundler
+pub trait VueModuleCompiler {
+    /// Compiles a `.vue` module into the JS chunk and the virtual modules it imports
+    fn transform(&self, request: &ModuleRequest) -> Result<CompiledModule, CompileError>;
+
+    /// Loads a virtual module produced by a previous [`VueModuleCompiler::transform`]
+    fn load_virtual(&self, id: &str) -> Option<CompiledChunk>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FervidPluginOptions {
+    pub is_prod: bool,
+    pub source_map: bool,
+    /// Append the HMR code to the main chunks, ignored in production
+    pub hot_reload: bool,
+}
+
+/// Default [`VueModuleCompiler`] which keeps the virtual modules in memory
+#[derive(Default)]
+pub struct FervidPlugin {
+    options: FervidPluginOptions,
+    virtual_modules: Mutex<FxHashMap<String, CompiledChunk>>,
+    /// Last compiled source of each file, to tell the template-only changes for HMR
+    sources: Mutex<FxHashMap<String, String>>,
+}
+
+impl FervidPlugin {
+    pub fn new(options: FervidPluginOptions) -> Self {
+        FervidPlugin {
+            options,
+            virtual_modules: Default::default(),
+            sources: Default::default(),
+        }
+    }
+}
+
+impl VueModuleCompiler for FervidPlugin {
+    fn transform(&self, request: &ModuleRequest) -> Result<CompiledModule, CompileError> {
+        let prev_source = if self.options.hot_reload {
+            self.sources
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(request.filename)
+                .cloned()
+        } else {
+            None
+        };
+
+        let result = compile(
+            request.source,
+            CompileOptions {
+                filename: Cow::Borrowed(request.filename),
+                id: Cow::Borrowed(request.filename),
+                is_prod: Some(self.options.is_prod),
+                is_custom_element: Some(request.filename.ends_with(".ce.vue")),
+                // The component is exported after the custom blocks are applied to it
+                gen_default_as: Some(Cow::Borrowed(SFC_MAIN)),
+                source_map: Some(self.options.source_map),
+                hot_reload: Some(self.options.hot_reload),
+                prev_source: prev_source.map(Cow::Owned),
+                expose_filename: Some(true),
+                // Custom blocks export a function which receives the component,
+                // e.g. to attach the translations of `<i18n>`
+                custom_block_imports: Some(true),
+                ..Default::default()
+            },
+        )?;
+
+        if self.options.hot_reload {
+            self.sources
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(request.filename.to_owned(), request.source.to_owned());
+        }
+
+        // Imports are appended to keep the source map of the code intact
+        let mut code = result.code;
+        let mut virtual_modules =
+            Vec::with_capacity(result.styles.len() + result.other_assets.len());
+        for (index, style) in result.styles.into_iter().enumerate() {
+            // Styles compiled by fervid are plain CSS
+            let lang = if style.is_compiled {
+                "css"
+            } else {
+                style.lang.as_str()
+            };
+            let id = style_module_id(request.filename, index, lang);
+            code.push_str(&format!("\nimport '{id}';"));
+
+            virtual_modules.push(CompiledChunk {
+                id,
+                kind: ChunkKind::Style {
+                    lang: lang.to_owned(),
+                    is_scoped: style.is_scoped,
+                    scope_id: format!("data-v-{}", result.file_hash),
+                },
+                code: style.code,
+                source_map: None,
+            });
+        }
+
+        // Custom blocks are already imported by the code
+        for (index, block) in result.other_assets.into_iter().enumerate() {
+            let lang = block.lang.unwrap_or_else(|| block.tag_name.to_owned());
+            let id = block.import_id.unwrap_or_else(|| {
+                block_module_id(request.filename, &block.tag_name, index, &lang)
+            });
+
+            virtual_modules.push(CompiledChunk {
+                id,
+                kind: ChunkKind::CustomBlock {
+                    block_type: block.tag_name,
+                    lang,
+                },
+                code: block.content,
+                source_map: None,
+            });
+        }
+        code.push_str(&format!("\nexport default {SFC_MAIN};"));
+
+        // Replace the virtual modules of the previous version of the file
+        {
+            let mut stored = self
+                .virtual_modules
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            stored.retain(|id, _| {
+                parse_virtual_module_id(id)
+                    .map_or(true, |parsed| parsed.filename != request.filename)
+            });
+            for chunk in virtual_modules.iter() {
+                stored.insert(chunk.id.to_owned(), chunk.to_owned());
+            }
+        }
+
+        Ok(CompiledModule {
+            main: CompiledChunk {
+                id: request.filename.to_owned(),
+                kind: ChunkKind::Js,
+                code,
+                source_map: result.source_map,
+            },
+            virtual_modules,
+            errors: result.errors,
+            deps: result.deps,
+        })
+    }
+
+    fn load_virtual(&self, id: &str) -> Option<CompiledChunk> {
+        self.virtual_modules
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+    }
+}
+
+/// Name of the component object in the main chunk
+const SFC_MAIN: &str = "_sfc_main";
+
+/// Parsed virtual module id, e.g. `App.vue?vue&type=style&index=0&lang.css`
+#[derive(Debug, PartialEq)]
+pub struct VirtualModuleId<'i> {
+    pub filename: &'i str,
+    pub block_type: &'i str,
+    pub index: usize,
+    pub lang: Option<&'i str>,
+}
+
+/// Creates the id of a style virtual module.
+/// The id ends with `lang.<lang>`, so that bundlers can pick the loaders by extension.
+pub fn style_module_id(filename: &str, index: usize, lang: &str) -> String {
+    block_module_id(filename, "style", index, lang)
+}
+
+/// Creates the id of a block virtual module, e.g. `App.vue?vue&type=i18n&index=0&lang.json`.
+/// `index` counts the blocks of the same kind: the styles and the custom blocks separately.
+pub fn block_module_id(filename: &str, block_type: &str, index: usize, lang: &str) -> String {
+    fervid::custom_block_id(filename, block_type, index, lang)
+}
+
+/// Parses a virtual module id created by this crate.
+/// Returns `None` for the ids of regular modules.
+pub fn parse_virtual_module_id(id: &str) -> Option<VirtualModuleId> {
+    let (filename, query) = id.split_once('?')?;
+
+    let mut is_vue = false;
+    let mut block_type = None;
+    let mut index = None;
+    let mut lang = None;
+    for part in query.split('&') {
+        match part.split_once('=') {
+            Some(("type", value)) => block_type = Some(value),
+            Some(("index", value)) => index = value.parse().ok(),
+            Some(_) => {}
+            None if part == "vue" => is_vue = true,
+            None => lang = part.strip_prefix("lang.").or(lang),
+        }
+    }
+
+    if !is_vue {
+        return None;
+    }
+
+    Some(VirtualModuleId {
+        filename,
+        block_type: block_type?,
+        index: index?,
+        lang,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_virtual_module_ids() {
+        assert_eq!(
+            Some(VirtualModuleId {
+                filename: "/src/App.vue",
+                block_type: "style",
+                index: 2,
+                lang: Some("scss"),
+            }),
+            parse_virtual_module_id(&style_module_id("/src/App.vue", 2, "scss"))
+        );
+
+        assert_eq!(None, parse_virtual_module_id("/src/App.vue"));
+        assert_eq!(None, parse_virtual_module_id("/src/App.vue?raw"));
+        assert_eq!(None, parse_virtual_module_id("/src/App.vue?vue&index=0"));
+    }
+
+    #[test]
+    fn it_emits_custom_blocks() {
+        let plugin = FervidPlugin::default();
+        let compiled = plugin
+            .transform(&ModuleRequest {
+                filename: "/src/App.vue",
+                source:
+                    "<template><div/></template><i18n lang=\"json\">{}</i18n><docs># App</docs>",
+            })
+            .unwrap();
+
+        let ids: Vec<&str> = compiled
+            .virtual_modules
+            .iter()
+            .map(|chunk| chunk.id.as_str())
+            .collect();
+        assert_eq!(
+            vec![
+                "/src/App.vue?vue&type=i18n&index=0&lang.json",
+                "/src/App.vue?vue&type=docs&index=1&lang.docs"
+            ],
+            ids
+        );
+        assert_eq!(
+            ChunkKind::CustomBlock {
+                block_type: "i18n".into(),
+                lang: "json".into()
+            },
+            compiled.virtual_modules[0].kind
+        );
+        assert!(compiled
+            .main
+            .code
+            .contains("import block0 from '/src/App.vue?vue&type=i18n&index=0&lang.json';"));
+        assert!(compiled.main.code.ends_with("export default _sfc_main;"));
+        assert!(plugin
+            .load_virtual("/src/App.vue?vue&type=docs&index=1&lang.docs")
+            .is_some_and(|chunk| chunk.code == "# App"));
+    }
+
+    #[test]
+    fn it_replaces_virtual_modules_of_previous_versions() {
+        let plugin = FervidPlugin::default();
+        let request = |source| ModuleRequest {
+            filename: "/src/App.vue",
+            source,
+        };
+
+        let first = plugin
+            .transform(&request(
+                "<template><div/></template><style>.a{}</style><style lang=\"scss\">.b{}</style>",
+            ))
+            .unwrap();
+        assert_eq!(2, first.virtual_modules.len());
+        assert!(first
+            .main
+            .code
+            .contains("import '/src/App.vue?vue&type=style&index=1&lang.scss';"));
+
+        plugin
+            .transform(&request("<template><div/></template><style>.a{}</style>"))
+            .unwrap();
+        assert!(plugin
+            .load_virtual("/src/App.vue?vue&type=style&index=0&lang.css")
+            .is_some());
+        assert!(plugin
+            .load_virtual("/src/App.vue?vue&type=style&index=1&lang.scss")
+            .is_none());
+    }
+
+    #[test]
+    fn it_exposes_scope_of_styles() {
+        let plugin = FervidPlugin::default();
+        let compiled = plugin
+            .transform(&ModuleRequest {
+                filename: "/src/App.vue",
+                source: "<template><div/></template><style scoped lang=\"scss\">.a{ .b{} }</style><style>.c{}</style>",
+            })
+            .unwrap();
+
+        let ChunkKind::Style {
+            ref lang,
+            is_scoped,
+            ref scope_id,
+        } = compiled.virtual_modules[0].kind
+        else {
+            panic!("should be a style")
+        };
+        assert_eq!("scss", lang);
+        assert!(is_scoped);
+        assert!(scope_id.starts_with("data-v-"));
+        assert!(compiled.main.code.contains(&scope_id[..]));
+
+        assert!(matches!(
+            compiled.virtual_modules[1].kind,
+            ChunkKind::Style {
+                is_scoped: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn it_emits_hmr_code() {
+        let plugin = FervidPlugin::new(FervidPluginOptions {
+            hot_reload: true,
+            ..Default::default()
+        });
+        let request = |source| ModuleRequest {
+            filename: "/src/App.vue",
+            source,
+        };
+
+        let first = plugin
+            .transform(&request(
+                "<template><div/></template><script>export default { data: () => ({ a: 1 }) }</script>",
+            ))
+            .unwrap();
+        let hmr_id = fervid::hmr::hmr_id("/src/App.vue");
+        assert!(first
+            .main
+            .code
+            .contains(&format!("_sfc_main.__hmrId = \"{hmr_id}\";")));
+        assert!(first.main.code.contains("import.meta.hot.accept("));
+        assert!(!first.main.code.contains("export const _rerender_only"));
+        assert!(first.main.code.ends_with("export default _sfc_main;"));
+
+        // Template-only change
+        let second = plugin
+            .transform(&request(
+                "<template><span/></template><script>export default { data: () => ({ a: 1 }) }</script>",
+            ))
+            .unwrap();
+        assert!(second
+            .main
+            .code
+            .contains("export const _rerender_only = true;"));
+
+        // Script change
+        let third = plugin
+            .transform(&request(
+                "<template><span/></template><script>export default {}</script>",
+            ))
+            .unwrap();
+        assert!(!third.main.code.contains("export const _rerender_only"));
+    }
+}
@@ -31,6 +31,7 @@ lazy_static! {
     pub static ref WITH_DEFAULTS: FervidAtom = fervid_atom!("withDefaults");
 
     // Helper atoms
+    pub static ref CSS_MODULES_HELPER: FervidAtom = fervid_atom!("__cssModules");
     pub static ref EMIT: FervidAtom = fervid_atom!("emit");
     pub static ref EMIT_HELPER: FervidAtom = fervid_atom!("__emit");
     pub static ref EXPOSE_HELPER: FervidAtom = fervid_atom!("__expose");
@@ -1,10 +1,12 @@
+use fervid_core::error::{ErrorCode, Severity, SeverityLevel};
 use fervid_css::CssError;
 use swc_core::common::{Span, Spanned};
 
 #[derive(Debug)]
 pub enum TransformError {
     CssError(CssError),
-    ScriptError(ScriptError)
+    ScriptError(ScriptError),
+    TemplateError(TemplateError),
 }
 
 #[derive(Debug)]
@@ -15,6 +17,8 @@ pub struct ScriptError {
 
 #[derive(Debug)]
 pub enum ScriptErrorKind {
+    /// `setup` is async (e.g. top-level `await` in `<script setup>`), but `<Suspense>` support is disabled
+    AsyncSetupWithoutSuspense,
     /// A compiler macro was imported, but it didn't need to
     CompilerMacroImport,
     /// `defineEmits` called with 0 type arguments (e.g. `defineEmits<>()`)
@@ -52,6 +56,9 @@ pub enum ScriptErrorKind {
     /// Different imports using the same local symbol,
     /// e.g `import foo from './foo'` and `import { foo } from './bar'`.
     DuplicateImport,
+    /// The SFC was requested to be a functional component, but it has a `<script>` or `<script setup>`.
+    /// It is compiled as a regular component
+    FunctionalWithScript,
     /// Could not resolve array element type
     ResolveTypeElementType,
     /// "Failed to resolve extends base type"
@@ -74,12 +81,344 @@ pub enum ScriptErrorKind {
     ResolveTypeUnsupportedComputedKey,
     /// Disallow non-type exports inside `<script setup>`
     SetupExport,
+    /// A `<script setup>` binding is never used (warning)
+    UnusedSetupBinding,
     /// `withDefaults` only works with type-only `defineProps`
     WithDefaultsNeedsTypeOnlyDefineProps,
     /// `withDefaults` without `defineProps` inside
     WithDefaultsWithoutDefineProps,
 }
 
+#[derive(Debug)]
+pub struct TemplateError {
+    pub span: Span,
+    pub kind: TemplateErrorKind,
+}
+
+#[derive(Debug)]
+pub enum TemplateErrorKind {
+    /// `<template v-slot v-for>` needs dynamic slots (`createSlots`), which are not supported yet.
+    /// The slot is rendered once without the loop
+    DynamicSlotUnsupported,
+    /// `<template>` is empty or contains only comments (warning)
+    EmptyTemplate,
+    /// Attribute other than `key` on a `<template v-for>` or on a `<template v-if>` with one child.
+    /// The `<template>` is not rendered as an element, so the attribute is ignored (warning)
+    IgnoredTemplateAttribute,
+    /// "`<KeepAlive>` expects exactly one child component",
+    /// e.g. several elements, a `v-for` or a `<template>` fragment
+    KeepAliveInvalidChildren,
+    /// The SFC has no `<template>` and the component has no `render`, so it renders nothing (warning)
+    MissingRender,
+    /// Element is nested deeper than [`BindingsHelper::max_depth`](crate::BindingsHelper::max_depth),
+    /// its children are dropped
+    NestingTooDeep,
+    /// An interpolation or a `v-bind` value changes the state, e.g. `{{ count++ }}`,
+    /// which happens on every render (warning)
+    SideEffectInExpression,
+    /// `<TransitionGroup>` child without a `key` (warning)
+    TransitionGroupUnkeyedChild,
+    /// "`<Transition>` expects exactly one child element or component"
+    TransitionInvalidChildren,
+    /// `<slot name="...">` which is not declared in `defineSlots` (warning)
+    UndeclaredSlot,
+    /// `<template lang="...">` which is not `html` and was not converted by a `TemplatePreprocessor`
+    UnsupportedLang,
+    /// `v-else` without an adjacent `v-if` or `v-else-if`, the element is rendered unconditionally
+    VElseWithoutVIf,
+    /// `v-else-if` without an adjacent `v-if` or `v-else-if`, the element is rendered unconditionally
+    VElseIfWithoutVIf,
+    /// "v-model value must be a valid JavaScript member expression."
+    VModelMalformedExpression,
+    /// "v-model cannot be used on a const binding because it is not writable."
+    VModelOnConst,
+    /// "v-model cannot be used on a prop, because local prop bindings are not writable.
+    /// Use a v-bind binding combined with a v-on listener that emits update:x event instead."
+    VModelOnProps,
+    /// "Mixed v-slot usage on both the component and nested `<template>`.
+    /// When there are multiple named slots, all slots should use `<template>` syntax to avoid scope ambiguity."
+    VSlotMixedSlotUsage,
+}
+
+impl ErrorCode for ScriptErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ScriptErrorKind::AsyncSetupWithoutSuspense => "script/async-setup-without-suspense",
+            ScriptErrorKind::CompilerMacroImport => "script/compiler-macro-import",
+            ScriptErrorKind::DefineEmitsMalformed => "script/define-emits-malformed",
+            ScriptErrorKind::DefineEmitsTypeAndNonTypeArguments => {
+                "script/define-emits-type-and-non-type-arguments"
+            }
+            ScriptErrorKind::DefineEmitsMixedCallAndPropertySyntax => {
+                "script/define-emits-mixed-call-and-property-syntax"
+            }
+            ScriptErrorKind::DefinePropsTypeAndNonTypeArguments => {
+                "script/define-props-type-and-non-type-arguments"
+            }
+            ScriptErrorKind::DefineOptionsTypeArguments => "script/define-options-type-arguments",
+            ScriptErrorKind::DefineOptionsProps => "script/define-options-props",
+            ScriptErrorKind::DefineOptionsEmits => "script/define-options-emits",
+            ScriptErrorKind::DefineOptionsExpose => "script/define-options-expose",
+            ScriptErrorKind::DefineOptionsSlots => "script/define-options-slots",
+            ScriptErrorKind::DefinePropsDestructureForbidden => {
+                "script/define-props-destructure-forbidden"
+            }
+            ScriptErrorKind::DefineSlotsArguments => "script/define-slots-arguments",
+            ScriptErrorKind::DuplicateDefineEmits => "script/duplicate-define-emits",
+            ScriptErrorKind::DuplicateDefineModelName => "script/duplicate-define-model-name",
+            ScriptErrorKind::DuplicateDefineProps => "script/duplicate-define-props",
+            ScriptErrorKind::DuplicateDefineOptions => "script/duplicate-define-options",
+            ScriptErrorKind::DuplicateDefineSlots => "script/duplicate-define-slots",
+            ScriptErrorKind::DuplicateImport => "script/duplicate-import",
+            ScriptErrorKind::FunctionalWithScript => "script/functional-with-script",
+            ScriptErrorKind::ResolveTypeElementType => "script/resolve-type-element-type",
+            ScriptErrorKind::ResolveTypeExtendsBaseType => "script/resolve-type-extends-base-type",
+            ScriptErrorKind::ResolveTypeMissingTypeParam => {
+                "script/resolve-type-missing-type-param"
+            }
+            ScriptErrorKind::ResolveTypeMissingTypeParams => {
+                "script/resolve-type-missing-type-params"
+            }
+            ScriptErrorKind::ResolveTypeUnresolvable => "script/resolve-type-unresolvable",
+            ScriptErrorKind::ResolveTypeUnresolvableIndexType => {
+                "script/resolve-type-unresolvable-index-type"
+            }
+            ScriptErrorKind::ResolveTypeUnsupported => "script/resolve-type-unsupported",
+            ScriptErrorKind::ResolveTypeUnsupportedIndexType => {
+                "script/resolve-type-unsupported-index-type"
+            }
+            ScriptErrorKind::ResolveTypeUnsupportedComputedKey => {
+                "script/resolve-type-unsupported-computed-key"
+            }
+            ScriptErrorKind::SetupExport => "script/setup-export",
+            ScriptErrorKind::UnusedSetupBinding => "script/unused-setup-binding",
+            ScriptErrorKind::WithDefaultsNeedsTypeOnlyDefineProps => {
+                "script/with-defaults-needs-type-only-define-props"
+            }
+            ScriptErrorKind::WithDefaultsWithoutDefineProps => {
+                "script/with-defaults-without-define-props"
+            }
+        }
+    }
+}
+
+impl ErrorCode for TemplateErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            TemplateErrorKind::DynamicSlotUnsupported => "template/dynamic-slot-unsupported",
+            TemplateErrorKind::EmptyTemplate => "template/empty-template",
+            TemplateErrorKind::IgnoredTemplateAttribute => "template/ignored-template-attribute",
+            TemplateErrorKind::KeepAliveInvalidChildren => "template/keep-alive-invalid-children",
+            TemplateErrorKind::MissingRender => "template/missing-render",
+            TemplateErrorKind::NestingTooDeep => "template/nesting-too-deep",
+            TemplateErrorKind::SideEffectInExpression => "template/side-effect-in-expression",
+            TemplateErrorKind::TransitionGroupUnkeyedChild => {
+                "template/transition-group-unkeyed-child"
+            }
+            TemplateErrorKind::TransitionInvalidChildren => "template/transition-invalid-children",
+            TemplateErrorKind::UndeclaredSlot => "template/undeclared-slot",
+            TemplateErrorKind::UnsupportedLang => "template/unsupported-lang",
+            TemplateErrorKind::VElseWithoutVIf => "template/v-else-without-v-if",
+            TemplateErrorKind::VElseIfWithoutVIf => "template/v-else-if-without-v-if",
+            TemplateErrorKind::VModelMalformedExpression => "template/v-model-malformed-expression",
+            TemplateErrorKind::VModelOnConst => "template/v-model-on-const",
+            TemplateErrorKind::VModelOnProps => "template/v-model-on-props",
+            TemplateErrorKind::VSlotMixedSlotUsage => "template/v-slot-mixed-slot-usage",
+        }
+    }
+}
+
+impl std::fmt::Display for ScriptErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ScriptErrorKind::AsyncSetupWithoutSuspense => {
+                "`setup` is async (e.g. top-level `await` in `<script setup>`), but `<Suspense>` support is disabled."
+            }
+            ScriptErrorKind::CompilerMacroImport => {
+                "Compiler macros are globally available and no longer need to be imported."
+            }
+            ScriptErrorKind::DefineEmitsMalformed => {
+                "`defineEmits` expects a type argument, e.g. `defineEmits<{ change: [id: number] }>()`."
+            }
+            ScriptErrorKind::DefineEmitsTypeAndNonTypeArguments => {
+                "`defineEmits` cannot accept both type and non-type arguments at the same time. Use one or the other."
+            }
+            ScriptErrorKind::DefineEmitsMixedCallAndPropertySyntax => {
+                "`defineEmits` type cannot mix call signature and property syntax."
+            }
+            ScriptErrorKind::DefinePropsTypeAndNonTypeArguments => {
+                "`defineProps` cannot accept both type and non-type arguments at the same time. Use one or the other."
+            }
+            ScriptErrorKind::DefineOptionsTypeArguments => {
+                "`defineOptions` cannot accept type arguments."
+            }
+            ScriptErrorKind::DefineOptionsProps => {
+                "`defineOptions` cannot be used to declare props. Use defineProps() instead."
+            }
+            ScriptErrorKind::DefineOptionsEmits => {
+                "`defineOptions` cannot be used to declare emits. Use defineEmits() instead."
+            }
+            ScriptErrorKind::DefineOptionsExpose => {
+                "`defineOptions` cannot be used to declare expose. Use defineExpose() instead."
+            }
+            ScriptErrorKind::DefineOptionsSlots => {
+                "`defineOptions` cannot be used to declare slots. Use defineSlots() instead."
+            }
+            ScriptErrorKind::DefinePropsDestructureForbidden => {
+                "Props destructure is explicitly prohibited via config."
+            }
+            ScriptErrorKind::DefineSlotsArguments => "`defineSlots` cannot accept arguments.",
+            ScriptErrorKind::DuplicateDefineEmits => "Duplicate `defineEmits` call.",
+            ScriptErrorKind::DuplicateDefineModelName => "Duplicate `defineModel` model name.",
+            ScriptErrorKind::DuplicateDefineProps => "Duplicate `defineProps` call.",
+            ScriptErrorKind::DuplicateDefineOptions => "Duplicate `defineOptions` call.",
+            ScriptErrorKind::DuplicateDefineSlots => "Duplicate `defineSlots` call.",
+            ScriptErrorKind::DuplicateImport => {
+                "Different imports use the same local name."
+            }
+            ScriptErrorKind::FunctionalWithScript => {
+                "A functional component cannot have a `<script>` or `<script setup>`, it is compiled as a regular component."
+            }
+            ScriptErrorKind::ResolveTypeElementType => "Could not resolve array element type.",
+            ScriptErrorKind::ResolveTypeExtendsBaseType => "Failed to resolve extends base type.",
+            ScriptErrorKind::ResolveTypeMissingTypeParam => {
+                "A type parameter was not provided, e.g. `ExtractPropTypes<>`."
+            }
+            ScriptErrorKind::ResolveTypeMissingTypeParams => {
+                "Type parameters were not provided, e.g. `ExtractPropTypes`."
+            }
+            ScriptErrorKind::ResolveTypeUnresolvable => {
+                "Unresolvable type: it is not supported during type resolution."
+            }
+            ScriptErrorKind::ResolveTypeUnresolvableIndexType => {
+                "Failed to resolve index type into finite keys."
+            }
+            ScriptErrorKind::ResolveTypeUnsupported => {
+                "Unsupported type construction during type resolution."
+            }
+            ScriptErrorKind::ResolveTypeUnsupportedIndexType => {
+                "Unsupported type when resolving index type."
+            }
+            ScriptErrorKind::ResolveTypeUnsupportedComputedKey => {
+                "Unsupported computed key in type referenced by a macro."
+            }
+            ScriptErrorKind::SetupExport => {
+                "`<script setup>` cannot contain ES module exports."
+            }
+            ScriptErrorKind::UnusedSetupBinding => "The `<script setup>` binding is never used.",
+            ScriptErrorKind::WithDefaultsNeedsTypeOnlyDefineProps => {
+                "`withDefaults` can only be used with type-only `defineProps`."
+            }
+            ScriptErrorKind::WithDefaultsWithoutDefineProps => {
+                "The first argument of `withDefaults` must be a `defineProps` call."
+            }
+        };
+
+        write!(f, "{}", message)
+    }
+}
+
+impl std::fmt::Display for TemplateErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            TemplateErrorKind::DynamicSlotUnsupported => {
+                "`<template v-slot v-for>` needs dynamic slots, which are not supported yet. The slot is rendered once without the loop."
+            }
+            TemplateErrorKind::EmptyTemplate => {
+                "`<template>` is empty or contains only comments."
+            }
+            TemplateErrorKind::IgnoredTemplateAttribute => {
+                "The `<template>` is not rendered as an element, so attributes other than `key` are ignored."
+            }
+            TemplateErrorKind::KeepAliveInvalidChildren => {
+                "`<KeepAlive>` expects exactly one child component."
+            }
+            TemplateErrorKind::MissingRender => {
+                "The component has neither a `<template>` nor a `render` function, so it renders nothing."
+            }
+            TemplateErrorKind::NestingTooDeep => {
+                "Element is nested too deep to be compiled, its children are dropped."
+            }
+            TemplateErrorKind::SideEffectInExpression => {
+                "The expression changes the state, which happens on every render."
+            }
+            TemplateErrorKind::TransitionGroupUnkeyedChild => {
+                "`<TransitionGroup>` children must be keyed."
+            }
+            TemplateErrorKind::TransitionInvalidChildren => {
+                "`<Transition>` expects exactly one child element or component."
+            }
+            TemplateErrorKind::UndeclaredSlot => "The slot is not declared in `defineSlots`.",
+            TemplateErrorKind::UnsupportedLang => {
+                "`<template lang>` is not `html` and was not converted by a `TemplatePreprocessor`."
+            }
+            TemplateErrorKind::VElseWithoutVIf => {
+                "v-else has no adjacent v-if or v-else-if, the element is rendered unconditionally."
+            }
+            TemplateErrorKind::VElseIfWithoutVIf => {
+                "v-else-if has no adjacent v-if or v-else-if, the element is rendered unconditionally."
+            }
+            TemplateErrorKind::VModelMalformedExpression => {
+                "v-model value must be a valid JavaScript member expression."
+            }
+            TemplateErrorKind::VModelOnConst => {
+                "v-model cannot be used on a const binding because it is not writable."
+            }
+            TemplateErrorKind::VModelOnProps => {
+                "v-model cannot be used on a prop, because local prop bindings are not writable. Use a v-bind binding combined with a v-on listener that emits update:x event instead."
+            }
+            TemplateErrorKind::VSlotMixedSlotUsage => {
+                "Mixed v-slot usage on both the component and nested `<template>`. When there are multiple named slots, all slots should use `<template>` syntax to avoid scope ambiguity."
+            }
+        };
+
+        write!(f, "{}", message)
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::fmt::Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransformError::CssError(e) => e.fmt(f),
+            TransformError::ScriptError(e) => e.fmt(f),
+            TransformError::TemplateError(e) => e.fmt(f),
+        }
+    }
+}
+
+impl ErrorCode for TransformError {
+    fn code(&self) -> &'static str {
+        match self {
+            TransformError::CssError(e) => e.kind.code(),
+            TransformError::ScriptError(e) => e.kind.code(),
+            TransformError::TemplateError(e) => e.kind.code(),
+        }
+    }
+}
+
+impl TransformError {
+    /// Mutable span of the error, e.g. to point it into the original source
+    pub fn span_mut(&mut self) -> &mut Span {
+        match self {
+            TransformError::CssError(e) => &mut e.span,
+            TransformError::ScriptError(e) => &mut e.span,
+            TransformError::TemplateError(e) => &mut e.span,
+        }
+    }
+}
+
 impl From<CssError> for TransformError {
     fn from(value: CssError) -> Self {
         TransformError::CssError(value)
@@ -92,11 +431,57 @@ impl From<ScriptError> for TransformError {
     }
 }
 
+impl From<TemplateError> for TransformError {
+    fn from(value: TemplateError) -> Self {
+        TransformError::TemplateError(value)
+    }
+}
+
 impl Spanned for TransformError {
     fn span(&self) -> Span {
         match self {
             TransformError::CssError(e) => e.span,
             TransformError::ScriptError(e) => e.span,
+            TransformError::TemplateError(e) => e.span,
+        }
+    }
+}
+
+impl Severity for TransformError {
+    fn get_severity(&self) -> SeverityLevel {
+        match self {
+            TransformError::CssError(e) => e.get_severity(),
+            TransformError::ScriptError(ScriptError {
+                kind: ScriptErrorKind::UnusedSetupBinding,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::EmptyTemplate,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::MissingRender,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::TransitionGroupUnkeyedChild,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::UndeclaredSlot,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::SideEffectInExpression,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::IgnoredTemplateAttribute,
+                ..
+            }) => SeverityLevel::Warning,
+            TransformError::ScriptError(_) | TransformError::TemplateError(_) => {
+                SeverityLevel::RecoverableError
+            }
         }
     }
 }
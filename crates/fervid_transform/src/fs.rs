@@ -0,0 +1,98 @@
+//! File access of the compiler, e.g. for the types imported by `defineProps<Props>()`.
+//!
+//! The compiler never touches the disk on its own. Consumers with OS access provide [`OsFileSystem`] or their own [`FileSystem`],
+//! while language servers, tests and the WASM build can use [`MemoryFileSystem`] with the virtual files.
+
+use std::{
+    fmt::Debug,
+    path::{Component, Path, PathBuf},
+};
+
+use fxhash::FxHashMap as HashMap;
+
+/// Provides the files to the compiler by their paths
+pub trait FileSystem: Debug + Send + Sync {
+    fn file_exists(&self, path: &str) -> bool;
+
+    /// Returns the file contents, `None` when the file does not exist or cannot be read
+    fn read_file(&self, path: &str) -> Option<String>;
+}
+
+/// In-memory files, empty by default
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFileSystem {
+    files: HashMap<String, String>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file or replaces its contents
+    pub fn add_file(&mut self, path: impl Into<String>, contents: impl Into<String>) {
+        self.files.insert(path.into(), contents.into());
+    }
+
+    /// Removes the file, returning its contents
+    pub fn remove_file(&mut self, path: &str) -> Option<String> {
+        self.files.remove(path)
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn file_exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read_file(&self, path: &str) -> Option<String> {
+        self.files.get(path).cloned()
+    }
+}
+
+/// Reads the files from the disk
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn file_exists(&self, path: &str) -> bool {
+        Path::new(path).is_file()
+    }
+
+    fn read_file(&self, path: &str) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+impl<P: Into<String>, C: Into<String>> FromIterator<(P, C)> for MemoryFileSystem {
+    fn from_iter<T: IntoIterator<Item = (P, C)>>(iter: T) -> Self {
+        MemoryFileSystem {
+            files: iter
+                .into_iter()
+                .map(|(path, contents)| (path.into(), contents.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Joins the relative `source` to the directory of `filename`, e.g. `src/Comp.vue` and `../types` give `types`
+pub fn join_paths(filename: &str, source: &str) -> String {
+    let dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+
+    let mut path = PathBuf::new();
+    for component in dir.join(source).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match path.file_name() {
+                Some(_) => {
+                    path.pop();
+                }
+                None if path.has_root() => {}
+                None => path.push(".."),
+            },
+            component => path.push(component),
+        }
+    }
+
+    path.to_string_lossy().into_owned()
+}
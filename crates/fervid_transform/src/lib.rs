@@ -1,17 +1,24 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
-use error::TransformError;
-use fervid_core::{SfcDescriptor, SfcScriptBlock, SfcScriptLang, TemplateGenerationMode};
-use misc::infer_name;
-use script::transform_and_record_scripts;
-use style::{attach_scope_id, create_style_scope, transform_style_blocks};
-use template::transform_and_record_template;
+use error::{ScriptError, ScriptErrorKind, TemplateError, TemplateErrorKind, TransformError};
+use fervid_core::{SfcDescriptor, SfcScriptBlock, TemplateGenerationMode, VueImportsSet};
+use fs::MemoryFileSystem;
+use misc::{find_async_setup, infer_name, may_render_without_template};
+use script::{report_unused_setup_bindings, transform_and_record_scripts};
+use style::{
+    attach_css_modules, attach_scope_id, create_style_scope, record_css_modules,
+    record_css_vars_usage, record_slotted_styles, record_style_deps, transform_css_vars,
+    transform_style_blocks,
+};
+use swc_core::common::DUMMY_SP;
+use template::{hoist_static_nodes, transform_and_record_template};
 
 #[macro_use]
 extern crate lazy_static;
 
 pub mod atoms;
 pub mod error;
+pub mod fs;
 pub mod misc;
 pub mod script;
 pub mod structs;
@@ -34,6 +41,26 @@ pub fn transform_sfc<'o>(
     // Create the context
     let mut ctx = TransformSfcContext::new(&sfc_descriptor, &options);
 
+    let script_span = sfc_descriptor
+        .script_setup
+        .as_ref()
+        .or(sfc_descriptor.script_legacy.as_ref())
+        .map(|script| script.span);
+
+    // Functional components have no instance to run the scripts for
+    if options.functional {
+        if let Some(span) = script_span {
+            errors.push(TransformError::ScriptError(ScriptError {
+                span,
+                kind: ScriptErrorKind::FunctionalWithScript,
+            }));
+        } else {
+            // There is no `_cache` to store the handlers in across the renders
+            ctx.bindings_helper.functional = true;
+            ctx.bindings_helper.cache_handlers = false;
+        }
+    }
+
     // Transform the scripts
     let mut transform_result = transform_and_record_scripts(
         &mut ctx,
@@ -42,22 +69,106 @@ pub fn transform_sfc<'o>(
         errors,
     );
 
+    // Async `setup` only works inside `<Suspense>`
+    let async_setup_span = find_async_setup(
+        &transform_result.export_obj,
+        transform_result.setup_fn.as_deref(),
+    );
+    if let (Some(span), false) = (async_setup_span, options.suspense) {
+        errors.push(TransformError::ScriptError(ScriptError {
+            span,
+            kind: ScriptErrorKind::AsyncSetupWithoutSuspense,
+        }));
+    }
+
+    // CSS Modules are known to the template, e.g. `$style.foo`
+    record_css_modules(&sfc_descriptor.styles, &mut ctx.bindings_helper);
+
+    // `<slot>` needs to know if the slot content is styled with `:slotted()`
+    record_slotted_styles(&sfc_descriptor.styles, &mut ctx.bindings_helper);
+
     // Transform the template if it is present
     let mut template_block = None;
+    let mut template_vue_imports = VueImportsSet::default();
+    let has_template = sfc_descriptor.template.is_some();
     if let Some(mut template) = sfc_descriptor.template {
-        transform_and_record_template(&mut template, &mut ctx.bindings_helper);
-        if !template.roots.is_empty() {
+        if template.lang != "html" {
+            // Templates in other languages are kept as raw text by the parser,
+            // compiling them would produce garbage
+            errors.push(TransformError::TemplateError(TemplateError {
+                span: template.span,
+                kind: TemplateErrorKind::UnsupportedLang,
+            }));
+        } else {
+            // Helpers of the template expressions are recorded separately from the script ones
+            let script_vue_imports = std::mem::take(&mut ctx.bindings_helper.vue_imports);
+            transform_and_record_template(&mut template, &mut ctx.bindings_helper, errors);
+            template_vue_imports = ctx.bindings_helper.vue_imports;
+            ctx.bindings_helper.vue_imports |= script_vue_imports;
+            if options.hoist_static {
+                hoist_static_nodes(&mut template);
+            }
+            // Empty template is kept to render `null`
             template_block = Some(template);
         }
     }
 
-    // Transform scoped CSS
+    // Without a template, the component renders nothing unless it renders manually
+    // or `<script setup>` exports JSX. A style-only SFC still exports an empty component
+    if !has_template
+        && !ctx.bindings_helper.setup_render
+        && !may_render_without_template(&transform_result.export_obj)
+    {
+        let span = script_span
+            .or(sfc_descriptor.styles.first().map(|style| style.span))
+            .unwrap_or(DUMMY_SP);
+        errors.push(TransformError::TemplateError(TemplateError {
+            span,
+            kind: TemplateErrorKind::MissingRender,
+        }));
+    }
+
+    // Bindings referenced in CSS `v-bind()` are used as well
+    record_css_vars_usage(&sfc_descriptor.styles, &mut ctx.bindings_helper);
+
+    // Report unused bindings when the scripts, the template and the styles were analyzed
+    if options.warn_unused_bindings {
+        report_unused_setup_bindings(
+            &ctx.bindings_helper,
+            &transform_result.module,
+            transform_result.setup_fn.as_deref(),
+            errors,
+        );
+    }
+
+    // Style dependencies are collected from the source before it gets transformed
+    record_style_deps(&sfc_descriptor.styles, &mut ctx.deps);
+
+    // CSS `v-bind()` becomes `useCssVars()` and CSS custom properties
     let mut style_blocks = sfc_descriptor.styles;
+    transform_css_vars(
+        &mut style_blocks,
+        &mut transform_result,
+        &mut ctx.bindings_helper,
+        options.scope_id,
+        errors,
+    );
+
+    // Transform scoped CSS and CSS Modules.
+    // Scoped blocks in other languages are scoped by the integrations, but need `__scopeId` as well
     let scope = create_style_scope(&options.scope_id);
-    let had_scoped_blocks = transform_style_blocks(&mut style_blocks, &scope, errors);
-    if had_scoped_blocks {
+    let mut css_modules = Vec::new();
+    let had_scoped_blocks = transform_style_blocks(
+        &mut style_blocks,
+        &scope,
+        options.minify_css,
+        &mut css_modules,
+        errors,
+    );
+    if had_scoped_blocks || style_blocks.iter().any(|block| block.is_scoped) {
         attach_scope_id(&mut transform_result, &scope);
     }
+    attach_css_modules(&mut transform_result, &style_blocks, css_modules);
 
     // Augment with some metadata
     let mut exported_obj = transform_result.export_obj;
@@ -69,8 +180,11 @@ pub fn transform_sfc<'o>(
         module: transform_result.module,
         setup_fn: transform_result.setup_fn,
         template_block,
+        template_vue_imports,
         style_blocks,
         custom_blocks: sfc_descriptor.custom_blocks,
+        is_async_setup: async_setup_span.is_some(),
+        deps: ctx.deps,
     }
 }
 
@@ -82,11 +196,17 @@ impl TransformSfcContext {
         // Create the bindings helper
         let mut bindings_helper = BindingsHelper::default();
         bindings_helper.is_prod = options.is_prod;
+        bindings_helper
+            .is_native_tag
+            .clone_from(&options.is_native_tag);
+        bindings_helper.cache_handlers = options.cache_handlers;
+        bindings_helper.whitespace = options.whitespace;
+        bindings_helper.comments = options.comments;
+        bindings_helper.max_depth = options.max_depth;
 
         // TS if any of scripts is TS.
         // Unlike the official compiler, we don't care if languages are mixed, because nothing changes.
-        let recognize_lang =
-            |script: &SfcScriptBlock| matches!(script.lang, SfcScriptLang::Typescript);
+        let recognize_lang = |script: &SfcScriptBlock| script.lang.is_ts();
         bindings_helper.is_ts = sfc_descriptor
             .script_setup
             .as_ref()
@@ -97,7 +217,11 @@ impl TransformSfcContext {
                 .map_or(false, recognize_lang);
 
         // Set inline flag in `BindingsHelper`
-        if bindings_helper.is_prod && sfc_descriptor.script_setup.is_some() {
+        // Devtools in production need the bindings to be exposed from `setup`
+        if bindings_helper.is_prod
+            && !options.feature_flags.prod_devtools
+            && sfc_descriptor.script_setup.is_some()
+        {
             bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
         }
 
@@ -106,8 +230,15 @@ impl TransformSfcContext {
             is_ce: options.is_ce,
             props_destructure: options.props_destructure,
             bindings_helper,
+            feature_flags: options.feature_flags,
+            has_template: sfc_descriptor.template.is_some(),
             deps: Default::default(),
+            fs: options
+                .fs
+                .clone()
+                .unwrap_or_else(|| Arc::new(MemoryFileSystem::default())),
             scopes: vec![],
+            file_scopes: Default::default(),
         }
     }
 
@@ -1,10 +1,10 @@
 use fervid_core::fervid_atom;
 use swc_core::{
     atoms::Atom,
-    common::DUMMY_SP,
+    common::{Span, DUMMY_SP},
     ecma::ast::{
-        Expr, GetterProp, IdentName, KeyValueProp, Lit, ObjectLit, Prop, PropName, PropOrSpread,
-        Str,
+        Expr, Function, GetterProp, IdentName, KeyValueProp, Lit, ObjectLit, Prop, PropName,
+        PropOrSpread, Str,
     },
 };
 
@@ -51,7 +51,163 @@ pub fn infer_name(exported_obj: &mut ObjectLit, filename: &str) {
         }))))
 }
 
+/// Adds `__file` with the path of the SFC to the exported object,
+/// which the devtools and the runtime warnings show in development
+pub fn expose_filename(exported_obj: &mut ObjectLit, filename: &str) {
+    exported_obj
+        .props
+        .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(IdentName {
+                span: DUMMY_SP,
+                sym: fervid_atom!("__file"),
+            }),
+            value: Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: filename.into(),
+                raw: None,
+            }))),
+        }))))
+}
+
+/// Finds an async `setup`, either generated from `<script setup>` with top-level `await`,
+/// or user-defined in the exported object of `<script>`, e.g. `export default { async setup() {} }`.
+/// Returns the span of the `setup` function.
+pub fn find_async_setup(exported_obj: &ObjectLit, setup_fn: Option<&Function>) -> Option<Span> {
+    if let Some(setup_fn) = setup_fn {
+        if setup_fn.is_async {
+            return Some(setup_fn.span);
+        }
+    }
+
+    exported_obj.props.iter().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+
+        let (key, function) = match prop.as_ref() {
+            Prop::Method(method_prop) => (&method_prop.key, method_prop.function.as_ref()),
+            Prop::KeyValue(KeyValueProp { key, value }) => match value.as_ref() {
+                Expr::Fn(fn_expr) => (key, fn_expr.function.as_ref()),
+                Expr::Arrow(arrow_expr) if arrow_expr.is_async => {
+                    return is_setup_key(key).then_some(arrow_expr.span);
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        (function.is_async && is_setup_key(key)).then_some(function.span)
+    })
+}
+
+/// Checks if the exported object has a user-provided `render` function or property
+pub fn has_render_prop(exported_obj: &ObjectLit) -> bool {
+    exported_obj.props.iter().any(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return false;
+        };
+
+        let key = match prop.as_ref() {
+            Prop::Shorthand(ident) => return ident.sym == "render",
+            Prop::KeyValue(kv) => &kv.key,
+            Prop::Method(method) => &method.key,
+            Prop::Getter(getter) => &getter.key,
+            _ => return false,
+        };
+
+        match key {
+            PropName::Ident(ident) => ident.sym == "render",
+            PropName::Str(s) => s.value == "render",
+            _ => false,
+        }
+    })
+}
+
+/// Checks if a component without a template may still render something:
+/// it has a `render`, a user-defined `setup` which may return a render function,
+/// or a spread which may contain either, e.g. `export default { ...defineComponent(/* */) }`
+pub fn may_render_without_template(exported_obj: &ObjectLit) -> bool {
+    has_render_prop(exported_obj)
+        || exported_obj.props.iter().any(|prop| match prop {
+            PropOrSpread::Spread(_) => true,
+            PropOrSpread::Prop(prop) => match prop.as_ref() {
+                Prop::Shorthand(ident) => ident.sym == "setup",
+                Prop::KeyValue(KeyValueProp { key, .. }) => is_setup_key(key),
+                Prop::Method(method) => is_setup_key(&method.key),
+                _ => false,
+            },
+        })
+}
+
+#[inline]
+pub(crate) fn is_setup_key(key: &PropName) -> bool {
+    match key {
+        PropName::Ident(id) => id.sym == "setup",
+        PropName::Str(s) => s.value == "setup",
+        _ => false,
+    }
+}
+
 #[inline]
 fn is_valid_name_sym(sym: &Atom) -> bool {
     sym == "name" || sym == "__name"
 }
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::ast::{ModuleDecl, ModuleItem};
+
+    use super::*;
+    use crate::test_utils::parser::parse_typescript_module;
+
+    fn find_in_export_default(code: &str) -> Option<Span> {
+        let module = parse_typescript_module(code, 0, Default::default())
+            .expect("Should be parseable")
+            .0;
+
+        let Some(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export_default))) =
+            module.body.into_iter().next()
+        else {
+            panic!("Expected `export default`");
+        };
+        let Expr::Object(ref exported_obj) = *export_default.expr else {
+            panic!("Expected an object");
+        };
+
+        find_async_setup(exported_obj, None)
+    }
+
+    #[test]
+    fn it_finds_async_setup_in_exported_object() {
+        assert!(find_in_export_default("export default { async setup() {} }").is_some());
+        assert!(find_in_export_default("export default { setup: async () => {} }").is_some());
+        assert!(
+            find_in_export_default("export default { 'setup': async function () {} }").is_some()
+        );
+    }
+
+    #[test]
+    fn it_exposes_filename() {
+        let mut exported_obj = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        expose_filename(&mut exported_obj, "src/App.vue");
+
+        let Some(PropOrSpread::Prop(prop)) = exported_obj.props.first() else {
+            panic!("Expected a prop");
+        };
+        let Prop::KeyValue(KeyValueProp { key, value }) = prop.as_ref() else {
+            panic!("Expected a key-value prop");
+        };
+        assert!(matches!(key, PropName::Ident(id) if id.sym == "__file"));
+        assert!(matches!(value.as_ref(), Expr::Lit(Lit::Str(s)) if s.value == "src/App.vue"));
+    }
+
+    #[test]
+    fn it_ignores_sync_setup() {
+        assert!(find_in_export_default("export default { setup() {} }").is_none());
+        assert!(find_in_export_default("export default { async mounted() {} }").is_none());
+        assert!(find_in_export_default("export default {}").is_none());
+    }
+}
@@ -13,12 +13,16 @@ use crate::{
 
 use self::{
     imports::process_imports,
+    jsx::transform_jsx,
     options_api::{transform_and_record_script_options_api, AnalyzeOptions},
     setup::{merge_sfc_helper, transform_and_record_script_setup},
 };
 
+pub use setup::report_unused_setup_bindings;
+
 pub mod common;
 mod imports;
+mod jsx;
 mod options_api;
 mod resolve_type;
 mod setup;
@@ -52,6 +56,12 @@ pub fn transform_and_record_scripts(
     // but we are modifying the source AST and thus cannot use it at a later stage.
     // Therefore, types are eagerly recorded.
 
+    // Blocks are consumed by the transforms, JSX is compiled at the end
+    let is_jsx = [script_setup.as_ref(), script_options.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|script| script.lang.is_jsx());
+
     // 1.1. Imports in `<script>`
     if let Some(ref mut script_options) = script_options {
         process_imports(
@@ -100,6 +110,7 @@ pub fn transform_and_record_scripts(
             &mut module,
             AnalyzeOptions {
                 collect_top_level_stmts: script_setup.is_some(),
+                options_api: ctx.feature_flags.options_api,
                 ..Default::default()
             },
             &mut ctx.bindings_helper,
@@ -151,11 +162,20 @@ pub fn transform_and_record_scripts(
         setup_fn = setup_transform_result.setup_fn;
     }
 
-    TransformScriptsResult {
+    let mut result = TransformScriptsResult {
         module,
         export_obj,
         setup_fn,
+    };
+
+    //
+    // STEP 4: Compile JSX to `h()` calls.
+    //
+    if is_jsx {
+        transform_jsx(&mut result, &mut ctx.bindings_helper);
     }
+
+    result
 }
 
 #[cfg(test)]
@@ -163,8 +183,15 @@ mod tests {
     use swc_core::common::{sync::Lrc, SourceMap, Span};
     use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
 
+    use fervid_core::VueImports;
+    use swc_ecma_parser::TsSyntax;
+
     use super::*;
-    use crate::test_utils::parser::parse_javascript_module;
+    use crate::error::{ScriptError, ScriptErrorKind};
+    use crate::test_utils::{
+        parser::{parse_javascript_module, parse_typescript_module},
+        to_str,
+    };
 
     /// https://github.com/vuejs/core/blob/c0c9432b64091fa15fd8619cfb06828735356a42/packages/compiler-sfc/__tests__/compileScript.spec.ts#L261-L275
     #[test]
@@ -192,6 +219,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_returns_jsx_render_from_script_setup() {
+        // No `<template>`
+        let (res, ctx, errors) = transform_tsx(
+            "
+            import { ref } from 'vue'
+            const count = ref(0)
+            export default <>{count.value}</>",
+            true,
+            false,
+        );
+        assert!(errors.is_empty());
+        assert!(ctx.bindings_helper.setup_render);
+        assert_eq!(
+            "return()=>_h(_Fragment,null,count.value);",
+            last_setup_stmt(&res)
+        );
+        let vue_imports = ctx.bindings_helper.vue_imports;
+        assert!(vue_imports.contains(VueImports::H));
+        assert!(vue_imports.contains(VueImports::Fragment));
+
+        // Plain element with props
+        let (res, ctx, errors) =
+            transform_tsx(r#"export default <div class="a">{msg}</div>"#, true, false);
+        assert!(errors.is_empty());
+        assert_eq!(
+            r#"return()=>_h("div",{class:"a"},msg);"#,
+            last_setup_stmt(&res)
+        );
+        let vue_imports = ctx.bindings_helper.vue_imports;
+        assert!(vue_imports.contains(VueImports::H));
+        assert!(!vue_imports.contains(VueImports::Fragment));
+
+        // Render function is returned as is
+        let (res, _, errors) = transform_tsx("export default () => <div />", true, false);
+        assert!(errors.is_empty());
+        assert_eq!(r#"return()=>_h("div",null);"#, last_setup_stmt(&res));
+    }
+
+    #[test]
+    fn it_compiles_jsx_of_script_setup() {
+        // Components declared in `<script setup lang="tsx">` are used in the `<template>`
+        let (res, ctx, errors) = transform_tsx(
+            "
+            import { ref } from 'vue'
+            const count = ref(0)
+            const Count = () => <>{count.value}</>
+            const Item = () => <li class=\"a\">{count.value}</li>",
+            true,
+            true,
+        );
+        assert!(errors.is_empty());
+        assert!(!ctx.bindings_helper.setup_render);
+        assert_eq!(
+            r#"const Item=()=>_h("li",{class:"a"},count.value);"#,
+            last_setup_stmt(&res)
+        );
+        let vue_imports = ctx.bindings_helper.vue_imports;
+        assert!(vue_imports.contains(VueImports::H));
+        assert!(vue_imports.contains(VueImports::Fragment));
+    }
+
+    #[test]
+    fn it_does_not_render_script_setup_export_with_template() {
+        let (res, ctx, errors) = transform_tsx("export default <div />", true, true);
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::ScriptError(ScriptError {
+                kind: ScriptErrorKind::SetupExport,
+                ..
+            })]
+        ));
+        assert!(!ctx.bindings_helper.setup_render);
+        assert!(!ctx.bindings_helper.vue_imports.contains(VueImports::H));
+        assert!(!last_setup_stmt(&res).starts_with("return"));
+    }
+
+    #[test]
+    fn it_compiles_jsx_of_options_api_script() {
+        let (res, ctx, errors) = transform_tsx(
+            "
+            const Item = () => <li>item</li>
+            export default { setup() { return () => <ul><Item /></ul> } }",
+            false,
+            false,
+        );
+        assert!(errors.is_empty());
+        assert!(!ctx.bindings_helper.setup_render);
+        assert!(ctx.bindings_helper.vue_imports.contains(VueImports::H));
+        assert_eq!(
+            r#"const Item=()=>_h("li",null,"item");"#,
+            to_str(&*res.module)
+        );
+        assert_eq!(
+            r#"{setup(){return()=>_h("ul",null,_h(Item,null));}}"#,
+            to_str(&res.export_obj)
+        );
+    }
+
+    fn transform_tsx(
+        input: &str,
+        is_setup: bool,
+        has_template: bool,
+    ) -> (
+        TransformScriptsResult,
+        TransformSfcContext,
+        Vec<TransformError>,
+    ) {
+        let module = parse_typescript_module(
+            input,
+            0,
+            TsSyntax {
+                tsx: true,
+                ..Default::default()
+            },
+        )
+        .expect("Should be parseable")
+        .0;
+        let script = SfcScriptBlock {
+            content: Box::new(module),
+            lang: fervid_core::SfcScriptLang::Tsx,
+            is_setup,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TransformSfcContext::anonymous();
+        ctx.has_template = has_template;
+        let mut errors = Vec::new();
+        let res = if is_setup {
+            transform_and_record_scripts(&mut ctx, Some(script), None, &mut errors)
+        } else {
+            transform_and_record_scripts(&mut ctx, None, Some(script), &mut errors)
+        };
+
+        (res, ctx, errors)
+    }
+
+    fn last_setup_stmt(res: &TransformScriptsResult) -> String {
+        let setup_body = res
+            .setup_fn
+            .as_ref()
+            .and_then(|setup_fn| setup_fn.body.as_ref())
+            .expect("Should have setup");
+        to_str(setup_body.stmts.last().expect("Should have statements"))
+    }
+
     fn check_import_dedupe(script_content: &str, script_setup_content: &str, expected: &str) {
         macro_rules! ts {
             ($input: expr) => {
@@ -0,0 +1,102 @@
+//! JSX of the `lang="jsx"` and `lang="tsx"` scripts, compiled to the `h()` calls of Vue.
+//!
+//! Without a `<template>`, `export default` of `<script setup>` renders the component:
+//! ```vue
+//! <script setup lang="tsx">
+//! const count = ref(0)
+//! export default <div>{count.value}</div>
+//! </script>
+//! ```
+//! `setup` then returns the render function, see [`BindingsHelper::setup_render`].
+//! With a `<template>`, such an export is an error, and JSX only declares the components used there.
+
+use fervid_core::VueImports;
+use swc_core::{
+    common::{comments::SingleThreadedComments, sync::Lrc, Globals, Mark, SourceMap, GLOBALS},
+    ecma::{
+        ast::{JSXElement, JSXFragment},
+        transforms::react::{jsx, Options, Runtime},
+        visit::{noop_visit_type, Visit, VisitMutWith, VisitWith},
+    },
+};
+
+use crate::{BindingsHelper, TransformScriptsResult};
+
+/// Replaces the JSX elements with `_h("div", props, ...children)`
+/// and the fragments with `_h(_Fragment, null, ...children)`,
+/// both in the module and in the exported object, e.g. `setup() { return () => <div /> }`.
+/// `h` and `Fragment` are added to the Vue imports when used.
+pub fn transform_jsx(scripts: &mut TransformScriptsResult, bindings_helper: &mut BindingsHelper) {
+    let mut usage = JsxUsage::default();
+    scripts.module.visit_with(&mut usage);
+    scripts.export_obj.visit_with(&mut usage);
+    if let Some(ref setup_fn) = scripts.setup_fn {
+        setup_fn.visit_with(&mut usage);
+    }
+
+    if !usage.has_jsx {
+        return;
+    }
+
+    bindings_helper.vue_imports |= VueImports::H;
+    if usage.has_fragment {
+        bindings_helper.vue_imports |= VueImports::Fragment;
+    }
+
+    let mut run = || {
+        let options = Options {
+            runtime: Some(Runtime::Classic),
+            pragma: Some(VueImports::H.as_str().to_owned()),
+            pragma_frag: Some(VueImports::Fragment.as_str().to_owned()),
+            // Namespaced tags, e.g. `<svg:rect>`, are passed to `h` as is
+            throw_if_namespace: Some(false),
+            ..Default::default()
+        };
+
+        // The classic runtime only applies `top_level_mark` to the `_h` and `_Fragment` identifiers.
+        // No resolver or hygiene pass runs over the scripts and the emitter ignores the syntax contexts,
+        // so the marks are not observable in the output
+        let mut pass = jsx(
+            Lrc::new(SourceMap::default()),
+            None::<SingleThreadedComments>,
+            options,
+            Mark::new(),
+            Mark::new(),
+        );
+
+        scripts.module.visit_mut_with(&mut pass);
+        scripts.export_obj.visit_mut_with(&mut pass);
+        if let Some(ref mut setup_fn) = scripts.setup_fn {
+            setup_fn.visit_mut_with(&mut pass);
+        }
+    };
+
+    // Marks can only be created inside `GLOBALS`. When fervid runs inside of an SWC pipeline,
+    // e.g. in a bundler plugin, its globals are reused, so that the marks stay valid there
+    if GLOBALS.is_set() {
+        run();
+    } else {
+        GLOBALS.set(&Globals::new(), run);
+    }
+}
+
+#[derive(Default)]
+struct JsxUsage {
+    has_jsx: bool,
+    has_fragment: bool,
+}
+
+impl Visit for JsxUsage {
+    noop_visit_type!();
+
+    fn visit_jsx_element(&mut self, n: &JSXElement) {
+        self.has_jsx = true;
+        n.visit_children_with(self);
+    }
+
+    fn visit_jsx_fragment(&mut self, n: &JSXFragment) {
+        self.has_jsx = true;
+        self.has_fragment = true;
+        n.visit_children_with(self);
+    }
+}
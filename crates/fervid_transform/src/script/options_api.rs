@@ -21,7 +21,7 @@ mod methods;
 mod props;
 mod setup;
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct AnalyzeOptions {
     /// Setting this to `true` will cause `analyze_script_legacy`
     /// to return Err if no default export was found
@@ -33,6 +33,19 @@ pub struct AnalyzeOptions {
     /// In `PROD` mode they are available to the inline template as module globals,
     /// in `DEV` mode they are available under `$setup` because of `__returned` object
     pub collect_top_level_stmts: bool,
+    /// When `false` (`__VUE_OPTIONS_API__` is disabled), `data`, `computed`, `methods`
+    /// and `inject` are not recorded as bindings, because the runtime ignores them
+    pub options_api: bool,
+}
+
+impl Default for AnalyzeOptions {
+    fn default() -> Self {
+        AnalyzeOptions {
+            require_default_export: false,
+            collect_top_level_stmts: false,
+            options_api: true,
+        }
+    }
 }
 
 pub struct ScriptOptionsTransformResult {
@@ -72,6 +85,13 @@ pub fn transform_and_record_script_options_api(
     if let Some(ref default_export) = maybe_default_export {
         let mut options_api_bindings = get_bindings!();
         analyzer::analyze_default_export(default_export, &mut options_api_bindings);
+
+        if !opts.options_api {
+            options_api_bindings.data.clear();
+            options_api_bindings.computed.clear();
+            options_api_bindings.methods.clear();
+            options_api_bindings.inject.clear();
+        }
     }
 
     ScriptOptionsTransformResult {
@@ -825,6 +845,7 @@ mod tests {
         let opts = AnalyzeOptions {
             require_default_export: false,
             collect_top_level_stmts: true,
+            options_api: true,
         };
 
         // Regular usage
@@ -900,6 +921,7 @@ mod tests {
         let opts = AnalyzeOptions {
             require_default_export: false,
             collect_top_level_stmts: true,
+            options_api: true,
         };
 
         // Different types of exports
@@ -935,4 +957,34 @@ mod tests {
             OptionsApiBindings::default()
         );
     }
+
+    #[test]
+    fn it_skips_options_api_bindings_when_disabled() {
+        let expected = OptionsApiBindings {
+            props: vec![FervidAtom::from("msg")],
+            ..Default::default()
+        };
+
+        let input = r"
+            export default {
+                props: ['msg'],
+                data() {
+                    return { foo: 1 }
+                },
+                computed: {
+                    bar() { return 2 }
+                },
+                methods: {
+                    baz() {}
+                },
+                inject: ['qux']
+            }
+            ";
+        let opts = AnalyzeOptions {
+            options_api: false,
+            ..Default::default()
+        };
+        assert_eq!(*analyze_js(input, opts.clone()).vars, expected);
+        assert_eq!(*analyze_ts(input, opts).vars, expected);
+    }
 }
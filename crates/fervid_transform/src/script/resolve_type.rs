@@ -3,6 +3,7 @@
 use std::{
     cell::{Ref, RefCell},
     ops::Deref,
+    path::Path,
     rc::Rc,
 };
 
@@ -13,14 +14,16 @@ use itertools::Itertools;
 use phf::{phf_set, Set};
 use strum_macros::{AsRefStr, EnumString, IntoStaticStr};
 use swc_core::{
-    common::{pass::Either, Span, Spanned, DUMMY_SP},
+    common::{pass::Either, BytePos, Span, Spanned, DUMMY_SP},
     ecma::ast::{
-        BinExpr, BinaryOp, Class, ClassDecl, Decl, DefaultDecl, ExportDecl, ExportSpecifier, Expr, FnDecl, FnExpr, Function, Ident, Lit, Module, ModuleDecl, ModuleExportName, ModuleItem, Pat, Stmt, Tpl, TsCallSignatureDecl, TsEntityName, TsEnumDecl, TsExprWithTypeArgs, TsFnOrConstructorType, TsFnParam, TsFnType, TsIndexedAccessType, TsInterfaceDecl, TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit, TsLitType, TsMappedType, TsMethodSignature, TsModuleDecl, TsModuleName, TsNamespaceBody, TsNamespaceDecl, TsPropertySignature, TsQualifiedName, TsTplLitType, TsType, TsTypeAnn, TsTypeElement, TsTypeLit, TsTypeOperatorOp, TsTypeQueryExpr, TsTypeRef, TsUnionOrIntersectionType, TsUnionType
+        BinExpr, BinaryOp, Class, ClassDecl, Decl, DefaultDecl, EsVersion, ExportDecl, ExportSpecifier, Expr, FnDecl, FnExpr, Function, Ident, ImportSpecifier, Lit, Module, ModuleDecl, ModuleExportName, ModuleItem, Pat, Stmt, Tpl, TsCallSignatureDecl, TsEntityName, TsEnumDecl, TsExprWithTypeArgs, TsFnOrConstructorType, TsFnParam, TsFnType, TsIndexedAccessType, TsInterfaceDecl, TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit, TsLitType, TsMappedType, TsMethodSignature, TsModuleDecl, TsModuleName, TsNamespaceBody, TsNamespaceDecl, TsPropertySignature, TsQualifiedName, TsTplLitType, TsType, TsTypeAnn, TsTypeElement, TsTypeLit, TsTypeOperatorOp, TsTypeQueryExpr, TsTypeRef, TsUnionOrIntersectionType, TsUnionType
     },
 };
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsSyntax};
 
 use crate::{
     error::{ScriptError, ScriptErrorKind},
+    fs::join_paths,
     ImportBinding, ScopeTypeNode, TransformSfcContext, TypeOrDecl, TypeScope, TypeScopeContainer,
 };
 
@@ -182,18 +185,28 @@ fn resolve_type_elements_impl_type(
                 }
             }
 
-            // TODO
-            // const sourceScope = importSourceToScope(
-            // ctx,
-            // node.argument,
-            // scope,
-            // node.argument.value,
-            // )
-            // const resolved = resolveTypeReference(ctx, node, sourceScope)
-            // if (resolved) {
-            // return resolveTypeElements(ctx, resolved, resolved._ownerScope)
-            // }
-            // break
+            // e.g. `import('./types').Props`
+            if let Some(source_scope) = import_source_to_scope(ctx, &import_type.arg.value, scope) {
+                let resolved = resolve_type_reference(
+                    ctx,
+                    ReferenceTypes::TsType(ts_type),
+                    &source_scope.borrow(),
+                );
+
+                if let Some(resolved) = resolved {
+                    let scope = ctx.get_scope_or_root(resolved.owner_scope);
+                    let scope = &*scope.borrow();
+
+                    return match resolved.value {
+                        TypeOrDecl::Type(ref ts_type) => {
+                            resolve_type_elements_impl_type(ctx, ts_type, scope, None)
+                        }
+                        TypeOrDecl::Decl(ref decl) => {
+                            resolve_type_elements_impl_decl(ctx, &decl.borrow(), scope, None)
+                        }
+                    };
+                }
+            }
 
             Err(error(
                 ScriptErrorKind::ResolveTypeUnsupported,
@@ -909,19 +922,115 @@ fn resolve_global_scope(_ctx: &mut TypeResolveContext) -> Result<Option<Vec<Type
 }
 
 fn resolve_type_from_import<'t>(
-    _ctx: &mut TypeResolveContext,
-    _ts_type: ReferenceTypes<'t>,
-    _name: &str,
-    _scope: &TypeScope,
+    ctx: &mut TypeResolveContext,
+    ts_type: ReferenceTypes<'t>,
+    name: &str,
+    scope: &TypeScope,
 ) -> Option<ScopeTypeNode> {
-    // const { source, imported } = scope.imports[name]
-    // const sourceScope = importSourceToScope(ctx, node, scope, source)
-    // return resolveTypeReference(ctx, node, sourceScope, imported, true)
+    let import = scope.imports.get(&FervidAtom::from(name))?;
+    let source_scope = import_source_to_scope(ctx, &import.source, scope)?;
+    let source_scope = &*source_scope.borrow();
+
+    inner_resolve_type_reference(
+        ctx,
+        ts_type,
+        source_scope,
+        &[import.imported.to_owned()],
+        true,
+    )
+}
+
+/// Finds the file of the import source and records its types.
+/// Only relative sources (e.g. `./types` or `../types.ts`) are supported,
+/// because modules and aliases need the full TS module resolution.
+fn import_source_to_scope(
+    ctx: &mut TypeResolveContext,
+    source: &str,
+    scope: &TypeScope,
+) -> Option<TypeScopeContainer> {
+    // TODO Support importing types from `.vue` files
+    if !source.starts_with('.') || source.ends_with(".vue") {
+        return None;
+    }
+
+    let filename = join_paths(&scope.filename, source);
+
+    // #8339 ts may import .js but we should resolve to corresponding ts or d.ts
+    let filename = filename.strip_suffix(".js").unwrap_or(&filename);
+    let index = |name: &str| {
+        Path::new(filename)
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let candidates = [
+        filename.to_owned(),
+        format!("{filename}.ts"),
+        format!("{filename}.tsx"),
+        format!("{filename}.d.ts"),
+        index("index.ts"),
+        index("index.tsx"),
+        index("index.d.ts"),
+    ];
+
+    for candidate in candidates {
+        if let Some(&scope_id) = ctx.file_scopes.get(&candidate) {
+            return ctx.get_scope(scope_id);
+        }
+
+        if ctx.fs.file_exists(&candidate) {
+            let contents = ctx.fs.read_file(&candidate).unwrap_or_default();
+
+            // Changes in the file should trigger the recompilation
+            ctx.deps.insert(candidate.to_owned());
+            return file_to_scope(ctx, candidate, &contents);
+        }
+    }
 
-    // TODO: Implement when FS access is ready
     None
 }
 
+/// Parses the file and records its imports and types in a new scope
+fn file_to_scope(
+    ctx: &mut TypeResolveContext,
+    filename: String,
+    contents: &str,
+) -> Option<TypeScopeContainer> {
+    let lexer = Lexer::new(
+        Syntax::Typescript(TsSyntax {
+            tsx: filename.ends_with(".tsx"),
+            dts: filename.ends_with(".d.ts"),
+            ..Default::default()
+        }),
+        EsVersion::EsNext,
+        StringInput::new(contents, BytePos(1), BytePos(1 + contents.len() as u32)),
+        None,
+    );
+    let module = Parser::new_from(lexer).parse_typescript_module().ok()?;
+
+    let id = ctx.scopes.len();
+    let scope_container = Rc::new(RefCell::new(TypeScope::new(id, filename.to_owned())));
+    ctx.scopes.push(scope_container.clone());
+    ctx.file_scopes.insert(filename, id);
+
+    let span = module.span;
+    let mut virtual_sfc_block = SfcScriptBlock {
+        content: Box::new(module),
+        lang: fervid_core::SfcScriptLang::Typescript,
+        is_setup: false,
+        span,
+    };
+    record_types(
+        ctx,
+        None,
+        Some(&mut virtual_sfc_block),
+        &mut scope_container.borrow_mut(),
+        false,
+    );
+
+    Some(scope_container)
+}
+
 fn resolve_template_keys(
     ctx: &mut TypeResolveContext,
     tpl: &Tpl,
@@ -1572,6 +1681,39 @@ pub fn record_types(
         for (idx, stmt) in get_body!().enumerate() {
             match stmt {
                 ModuleItem::ModuleDecl(module_decl) => match module_decl {
+                    // Type-only imports are not a part of the user imports
+                    ModuleDecl::Import(import_decl) => {
+                        let is_from_setup = setup_offset.is_some_and(|v| idx >= v);
+
+                        for spec in import_decl.specifiers.iter() {
+                            let (local, imported) = match spec {
+                                ImportSpecifier::Named(named) => (
+                                    &named.local.sym,
+                                    match named.imported {
+                                        Some(ModuleExportName::Ident(ref i)) => i.sym.to_owned(),
+                                        Some(ModuleExportName::Str(ref s)) => s.value.to_owned(),
+                                        None => named.local.sym.to_owned(),
+                                    },
+                                ),
+                                ImportSpecifier::Default(default_spec) => {
+                                    (&default_spec.local.sym, fervid_atom!("default"))
+                                }
+                                ImportSpecifier::Namespace(ns) => {
+                                    (&ns.local.sym, fervid_atom!("*"))
+                                }
+                            };
+
+                            imports
+                                .entry(local.to_owned())
+                                .or_insert_with(|| ImportBinding {
+                                    source: import_decl.src.value.to_owned(),
+                                    imported,
+                                    local: local.to_owned(),
+                                    is_from_setup,
+                                });
+                        }
+                    }
+
                     ModuleDecl::ExportDecl(decl) => {
                         record_type_decl(&mut decl.decl, types, declares, None);
                         record_type_decl(&mut decl.decl, exported_types, exported_declares, None);
@@ -1698,7 +1840,7 @@ pub fn record_types(
         }
     }
 
-    for node in types.values_mut() {
+    for node in types.values_mut().chain(exported_types.values_mut()) {
         // TODO Support both `_ownerScope` and `_ns` (using IDs)
         // for (const key of Object.keys(types)) {
         //     const node = types[key]
@@ -1708,7 +1850,7 @@ pub fn record_types(
         node.owner_scope = scope.id;
     }
 
-    for declare in declares.values_mut() {
+    for declare in declares.values_mut().chain(exported_declares.values_mut()) {
         declare.owner_scope = scope.id;
     }
 
@@ -2794,6 +2936,8 @@ impl Spanned for ResolvedProp {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use fervid_core::SfcDescriptor;
     use fxhash::FxHashSet;
     use swc_core::{alloc::collections::FxHashMap, ecma::ast::IdentName};
@@ -2801,6 +2945,7 @@ mod tests {
 
     use super::*;
     use crate::{
+        fs::MemoryFileSystem,
         script::imports::process_imports,
         test_utils::parser::{parse_typescript_expr, parse_typescript_module},
     };
@@ -3962,15 +4107,17 @@ mod tests {
 
     #[test]
     fn generic_from_external_file() {
-        // TODO Support importing
-        // const files = {
-        //   '/foo.ts': 'export type P<T> = { foo: T }',
-        // }
-        let resolved = resolve(
+        let files = [
+            ("foo.ts", "export type P<T> = { foo: T }"),
+        ];
+
+        let resolved = try_resolve_with_files(
             "
-            type P<T> = { foo: T }
+            import type { P } from './foo'
             defineProps<P<string>>()",
-        );
+            &files,
+        )
+        .expect("Should resolve");
 
         assert_eq!(resolved.props.len(), 1);
         assert_eq!(
@@ -3979,7 +4126,88 @@ mod tests {
         );
     }
 
-    // TODO Imported types
+    #[test]
+    fn external_type_imports_relative_ts() {
+        let files = [
+            ("foo.ts", "export type P = { foo: number }"),
+            ("bar.d.ts", "type X = { bar: string }; export { X as Y }"),
+        ];
+
+        let resolved = try_resolve_with_files(
+            "
+            import { P } from './foo'
+            import { Y as PP } from './bar'
+            defineProps<P & PP>()",
+            &files,
+        )
+        .expect("Should resolve");
+
+        assert_eq!(resolved.props.len(), 2);
+        assert_eq!(
+            resolved.props.get(&fervid_atom!("foo")),
+            Some(&FlagSet::from(Types::Number))
+        );
+        assert_eq!(
+            resolved.props.get(&fervid_atom!("bar")),
+            Some(&FlagSet::from(Types::String))
+        );
+        assert_eq!(
+            resolved.deps,
+            FxHashSet::from_iter(["foo.ts".to_owned(), "bar.d.ts".to_owned()])
+        );
+    }
+
+    #[test]
+    fn external_type_imports_chained() {
+        let files = [
+            (
+                "types/index.ts",
+                "
+                import type { P as PP } from '../nested/bar.js'
+                export interface P extends PP { foo: number }",
+            ),
+            ("nested/bar.ts", "export type P = { bar: () => void }"),
+        ];
+
+        // Directory import, `.js` extension of a TS file and an interface extending an imported type
+        let resolved = try_resolve_with_files(
+            "
+            import type { P } from './types'
+            defineProps<P>()",
+            &files,
+        )
+        .expect("Should resolve");
+
+        assert_eq!(resolved.props.len(), 2);
+        assert_eq!(
+            resolved.props.get(&fervid_atom!("foo")),
+            Some(&FlagSet::from(Types::Number))
+        );
+        assert_eq!(
+            resolved.props.get(&fervid_atom!("bar")),
+            Some(&FlagSet::from(Types::Function))
+        );
+        assert_eq!(
+            resolved.deps,
+            FxHashSet::from_iter(["types/index.ts".to_owned(), "nested/bar.ts".to_owned()])
+        );
+    }
+
+    #[test]
+    fn external_type_imports_import_type() {
+        let files = [
+            ("foo.ts", "export type P = { foo: number }"),
+        ];
+
+        let resolved = try_resolve_with_files("defineProps<import('./foo').P>()", &files)
+            .expect("Should resolve");
+
+        assert_eq!(resolved.props.len(), 1);
+        assert_eq!(
+            resolved.props.get(&fervid_atom!("foo")),
+            Some(&FlagSet::from(Types::Number))
+        );
+    }
 
     #[test]
     fn failed_type_reference() {
@@ -4024,6 +4252,14 @@ mod tests {
 
         // TODO Error should be different (when imports are implemented)
         assert!(result.is_err_and(|e| matches!(e.kind, ScriptErrorKind::ResolveTypeUnresolvable)));
+
+        // File exists, but does not export the type
+        let files = [
+            ("foo.ts", "type X = { foo: number }"),
+        ];
+        let result =
+            try_resolve_with_files("import { X } from './foo'; defineProps<X>()", &files);
+        assert!(result.is_err_and(|e| matches!(e.kind, ScriptErrorKind::ResolveTypeUnresolvable)));
     }
 
     #[test]
@@ -4119,7 +4355,6 @@ mod tests {
     struct ResolveResult {
         props: FxHashMap<FervidAtom, TypesSet>,
         calls: Vec<Either<TsFnType, TsCallSignatureDecl>>,
-        #[allow(dead_code)]
         deps: FxHashSet<String>,
         raw_props: HashMap<FervidAtom, ResolvedProp>,
     }
@@ -4129,6 +4364,13 @@ mod tests {
     }
 
     fn try_resolve(code: &str) -> ResolutionResult<ResolveResult> {
+        try_resolve_with_files(code, &[])
+    }
+
+    fn try_resolve_with_files(
+        code: &str,
+        files: &[(&str, &str)],
+    ) -> ResolutionResult<ResolveResult> {
         let (script_setup_content, _) =
             parse_typescript_module(code, 0, TsSyntax::default()).expect("Should parse");
 
@@ -4153,6 +4395,20 @@ mod tests {
                 props_destructure: crate::PropsDestructureConfig::default(),
                 scope_id: "test",
                 filename: "./Test.vue",
+                warn_unused_bindings: false,
+                suspense: true,
+                feature_flags: Default::default(),
+                minify_css: false,
+                is_native_tag: None,
+                hoist_static: false,
+                cache_handlers: false,
+                whitespace: Default::default(),
+                comments: None,
+                functional: false,
+                max_depth: None,
+                fs: Some(Arc::new(MemoryFileSystem::from_iter(
+                    files.iter().copied(),
+                ))),
             },
         );
 
@@ -2,9 +2,10 @@ use fervid_core::{BindingTypes, IntoIdent, SfcScriptBlock, TemplateGenerationMod
 use swc_core::{
     common::{Span, DUMMY_SP},
     ecma::ast::{
-        BindingIdent, BlockStmt, CallExpr, Callee, Decl, Expr, ExprStmt, Function, Ident,
-        IdentName, KeyValuePatProp, KeyValueProp, ModuleDecl, ModuleItem, ObjectPat, ObjectPatProp,
-        Param, Pat, Prop, PropName, PropOrSpread, Stmt, VarDeclKind,
+        ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr,
+        ExprStmt, Function, Ident, IdentName, KeyValuePatProp, KeyValueProp, ModuleDecl,
+        ModuleItem, ObjectPat, ObjectPatProp, Param, Pat, Prop, PropName, PropOrSpread, ReturnStmt,
+        Stmt, VarDeclKind,
     },
 };
 
@@ -31,8 +32,11 @@ mod define_props;
 mod define_props_destructure;
 mod define_slots;
 mod macros;
+mod unused_bindings;
 mod utils;
 
+pub use self::unused_bindings::report_unused_setup_bindings;
+
 use self::{
     await_detection::detect_await_module_item,
     macros::{postprocess_macros, transform_script_setup_macro_expr},
@@ -62,6 +66,10 @@ pub fn transform_and_record_script_setup(
 
     let mut setup_body_stmts = Vec::<Stmt>::new();
 
+    // Without a `<template>`, `export default <div />` is what the component renders
+    let is_jsx_render_allowed = script_setup.lang.is_jsx() && !ctx.has_template;
+    let mut jsx_render: Option<Box<Expr>> = None;
+
     // Detect `await` usage
     for module_item in script_setup.content.body.iter() {
         if sfc_object_helper.is_async_setup {
@@ -74,6 +82,12 @@ pub fn transform_and_record_script_setup(
     // Go over the whole script setup: process all the statements and declarations
     for module_item in script_setup.content.body {
         let stmt = match module_item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export))
+                if is_jsx_render_allowed =>
+            {
+                jsx_render = Some(export.expr);
+                continue;
+            }
             ModuleItem::ModuleDecl(ref decl) => {
                 // Disallow non-type exports
                 let setup_export_error_span: Option<Span> = check_export(decl);
@@ -173,6 +187,15 @@ pub fn transform_and_record_script_setup(
         );
     }
 
+    // `setup` returns the render function, the same as in `defineComponent({ setup() {} })`
+    if let Some(render) = jsx_render {
+        ctx.bindings_helper.setup_render = true;
+        setup_body_stmts.push(Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(into_render_fn(render)),
+        }));
+    }
+
     // Should we check that this function was not assigned anywhere else?
     let setup_fn = Some(Box::new(Function {
         params: get_setup_fn_params(&sfc_object_helper),
@@ -197,6 +220,25 @@ pub fn transform_and_record_script_setup(
     }
 }
 
+/// `export default () => <div />` is already a render function,
+/// `export default <div />` is wrapped in one to be re-evaluated on each render
+fn into_render_fn(render: Box<Expr>) -> Box<Expr> {
+    if matches!(render.unwrap_parens(), Expr::Arrow(_) | Expr::Fn(_)) {
+        return render;
+    }
+
+    Box::new(Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        params: vec![],
+        body: Box::new(BlockStmtOrExpr::Expr(render)),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    }))
+}
+
 /// Analyzes the declaration in `script setup` context.
 /// These are typically `var`/`let`/`const` declarations, function declarations, etc.
 fn transform_decl_stmt(
@@ -420,14 +462,14 @@ fn get_setup_fn_params(sfc_object_helper: &SfcExportedObjectHelper) -> Vec<Param
 mod tests {
     use crate::{
         error::{ScriptError, ScriptErrorKind, TransformError},
-        script::imports::process_imports,
-        test_utils::parser::*,
+        script::{imports::process_imports, resolve_type::record_types},
+        test_utils::{parser::*, to_str},
         SetupBinding, TransformSfcContext,
     };
-    use fervid_core::{fervid_atom, BindingTypes, SfcScriptBlock};
-    use swc_core::common::DUMMY_SP;
+    use fervid_core::{fervid_atom, BindingTypes, SfcScriptBlock, VueImports};
+    use swc_core::{common::DUMMY_SP, ecma::ast::Expr};
 
-    use super::transform_and_record_script_setup;
+    use super::{transform_and_record_script_setup, TransformScriptSetupResult};
 
     fn analyze_bindings(mut script_setup: SfcScriptBlock) -> Vec<SetupBinding> {
         let mut ctx = TransformSfcContext::anonymous();
@@ -469,6 +511,41 @@ mod tests {
         })
     }
 
+    /// Transforms TS `<script setup>` with its types recorded, e.g. for type-only macros
+    fn transform_ts_with_types(
+        input: &str,
+    ) -> (
+        TransformSfcContext,
+        TransformScriptSetupResult,
+        Vec<TransformError>,
+    ) {
+        let parsed = parse_typescript_module(input, 0, Default::default())
+            .expect("analyze_ts expects the input to be parseable")
+            .0;
+
+        let mut script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Typescript,
+            is_setup: true,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TransformSfcContext::anonymous();
+        ctx.bindings_helper.is_ts = true;
+        let scope = ctx.root_scope();
+        record_types(
+            &mut ctx,
+            Some(&mut script_setup),
+            None,
+            &mut (*scope).borrow_mut(),
+            false,
+        );
+
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(&mut ctx, script_setup, &mut errors);
+        (ctx, result, errors)
+    }
+
     macro_rules! test_js_and_ts {
         ($input: expr, $expected: expr) => {
             assert_eq!(analyze_js_bindings($input), $expected);
@@ -758,6 +835,321 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_transforms_define_model() {
+        let parsed = parse_javascript_module(
+            r#"
+            const model = defineModel()
+            const countModel = defineModel("count", { default: 1, set(v) { return v } })
+            "#,
+            0,
+            Default::default(),
+        )
+        .expect("analyze_js expects the input to be parseable")
+        .0;
+
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Es,
+            is_setup: true,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TransformSfcContext::anonymous();
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(&mut ctx, script_setup, &mut errors);
+        assert!(errors.is_empty());
+
+        // Model props are read-only, models themselves are refs
+        assert_eq!(
+            ctx.bindings_helper.setup_bindings,
+            vec![
+                SetupBinding(fervid_atom!("modelValue"), BindingTypes::Props),
+                SetupBinding(fervid_atom!("model"), BindingTypes::SetupRef),
+                SetupBinding(fervid_atom!("count"), BindingTypes::Props),
+                SetupBinding(fervid_atom!("countModel"), BindingTypes::SetupRef),
+            ]
+        );
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::UseModel));
+
+        // Implicit props and emits
+        let sfc_object_helper = result.sfc_object_helper;
+        assert!(sfc_object_helper.is_setup_props_referenced);
+        assert_eq!(
+            to_str(&sfc_object_helper.props.expect("Should have props")),
+            r#"{"modelValue":{},"modelModifiers":{},"count":{default:1},"countModifiers":{}}"#
+        );
+        assert_eq!(
+            to_str(&sfc_object_helper.emits.expect("Should have emits")),
+            r#"["update:modelValue","update:count"]"#
+        );
+
+        // `useModel` calls
+        let setup_stmts = result
+            .setup_fn
+            .and_then(|setup_fn| setup_fn.body)
+            .expect("Should have setup")
+            .stmts
+            .iter()
+            .map(to_str)
+            .collect::<Vec<_>>();
+        assert!(setup_stmts.contains(&r#"const model=_useModel(__props,"modelValue");"#.to_owned()));
+        assert!(setup_stmts.contains(
+            &r#"const countModel=_useModel(__props,"count",{set(v){return v;}});"#.to_owned()
+        ));
+
+        // Duplicate model names are reported
+        let parsed = parse_javascript_module(
+            "defineModel()\ndefineModel('modelValue')",
+            0,
+            Default::default(),
+        )
+        .expect("analyze_js expects the input to be parseable")
+        .0;
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Es,
+            is_setup: true,
+            span: DUMMY_SP,
+        };
+        let mut ctx = TransformSfcContext::anonymous();
+        let mut errors = Vec::new();
+        transform_and_record_script_setup(&mut ctx, script_setup, &mut errors);
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::ScriptError(ScriptError {
+                kind: ScriptErrorKind::DuplicateDefineModelName,
+                ..
+            })]
+        ));
+    }
+
+    #[test]
+    fn it_records_declared_slots() {
+        let parsed = parse_typescript_module(
+            r#"
+            const slots = defineSlots<{
+                default(props: { msg: string }): any
+                "named-slot"?: (props: {}) => any
+            }>()
+            "#,
+            0,
+            Default::default(),
+        )
+        .expect("analyze_ts expects the input to be parseable")
+        .0;
+
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Typescript,
+            is_setup: true,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TransformSfcContext::anonymous();
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(&mut ctx, script_setup, &mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(
+            ctx.bindings_helper.declared_slots,
+            Some(vec![fervid_atom!("default"), fervid_atom!("named-slot")])
+        );
+
+        // Type arguments are stripped
+        let setup_stmts = result
+            .setup_fn
+            .and_then(|setup_fn| setup_fn.body)
+            .expect("Should have setup")
+            .stmts
+            .iter()
+            .map(to_str)
+            .collect::<Vec<_>>();
+        assert!(setup_stmts.contains(&"const slots=_useSlots();".to_owned()));
+    }
+
+    #[test]
+    fn it_transforms_define_options() {
+        let transform = |input: &str| {
+            let parsed = parse_javascript_module(input, 0, Default::default())
+                .expect("analyze_js expects the input to be parseable")
+                .0;
+
+            let script_setup = SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Es,
+                is_setup: true,
+                span: DUMMY_SP,
+            };
+
+            let mut ctx = TransformSfcContext::anonymous();
+            let mut errors = Vec::new();
+            let result = transform_and_record_script_setup(&mut ctx, script_setup, &mut errors);
+            (result, errors)
+        };
+
+        // Options are merged into the component object and removed from `setup`
+        let (result, errors) = transform(r#"defineOptions({ name: "Foo", inheritAttrs: false })"#);
+        assert!(errors.is_empty());
+        let fields = result
+            .sfc_object_helper
+            .untyped_fields
+            .iter()
+            .map(to_str)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![r#"name:"Foo""#, "inheritAttrs:false"], fields);
+        assert!(result
+            .setup_fn
+            .and_then(|setup_fn| setup_fn.body)
+            .expect("Should have setup")
+            .stmts
+            .iter()
+            .all(|stmt| !to_str(stmt).contains("defineOptions")));
+
+        // `props`, `emits`, `expose` and `slots` are reported
+        let (_, errors) = transform("defineOptions({ props: [], 'emits': [] })");
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                TransformError::ScriptError(ScriptError {
+                    kind: ScriptErrorKind::DefineOptionsEmits,
+                    ..
+                }),
+                TransformError::ScriptError(ScriptError {
+                    kind: ScriptErrorKind::DefineOptionsProps,
+                    ..
+                })
+            ]
+        ));
+    }
+
+    #[test]
+    fn it_transforms_with_defaults() {
+        let transform = |input: &str| {
+            let (ctx, result, errors) = transform_ts_with_types(input);
+            assert!(errors.is_empty());
+            (ctx, result)
+        };
+
+        // Interface is resolved, static defaults are placed into the props
+        let (ctx, result) = transform(
+            r#"
+            interface Props {
+                msg?: string
+                labels?: string[]
+                count: number
+                onClick?: () => void
+            }
+            const props = withDefaults(defineProps<Props>(), {
+                msg: "hello",
+                labels: () => ["one", "two"],
+                onClick() {}
+            })
+            "#,
+        );
+
+        let Some(Expr::Object(props)) = result.sfc_object_helper.props.as_deref() else {
+            panic!("Props should be an object")
+        };
+        let mut props = props.props.iter().map(to_str).collect::<Vec<_>>();
+        props.sort();
+        assert_eq!(
+            props,
+            vec![
+                r#"count:{type:Number,required:true}"#,
+                r#"labels:{type:Array,required:false,default:()=>["one","two"]}"#,
+                r#"msg:{type:String,required:false,default:"hello"}"#,
+                r#"onClick:{type:Function,required:false,default(){}}"#,
+            ]
+        );
+
+        let setup_bindings = &ctx.bindings_helper.setup_bindings;
+        for prop in ["msg", "labels", "count", "onClick"] {
+            assert!(setup_bindings.contains(&SetupBinding(prop.into(), BindingTypes::Props)));
+        }
+        assert!(setup_bindings.contains(&SetupBinding(
+            fervid_atom!("props"),
+            BindingTypes::SetupReactiveConst
+        )));
+        assert!(result.sfc_object_helper.is_setup_props_referenced);
+
+        // Non-static defaults are merged at runtime
+        let (ctx, result) = transform(
+            r#"
+            const props = withDefaults(defineProps<{ msg?: string }>(), { ...defaults })
+            "#,
+        );
+        assert_eq!(
+            to_str(&result.sfc_object_helper.props.expect("Should have props")),
+            r#"_mergeDefaults({msg:{type:String,required:false}},{...defaults})"#
+        );
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::MergeDefaults));
+    }
+
+    #[test]
+    fn it_transforms_type_only_define_emits() {
+        let emits = |input: &str| {
+            let (_, result, errors) = transform_ts_with_types(input);
+            assert!(errors.is_empty());
+            assert!(result.sfc_object_helper.is_setup_emit_referenced);
+            to_str(&result.sfc_object_helper.emits.expect("Should have emits"))
+        };
+
+        // Call signatures keep the declaration order
+        assert_eq!(
+            emits(
+                r#"
+                const emit = defineEmits<{
+                    (e: "save", id: number): void
+                    (e: "change" | "update", value: string): void
+                    (e: "save"): void
+                }>()
+                "#
+            ),
+            r#"["save","change","update"]"#
+        );
+
+        // Function type
+        assert_eq!(
+            emits(r#"const emit = defineEmits<(e: "foo" | "bar") => void>()"#),
+            r#"["foo","bar"]"#
+        );
+
+        // Shorthand object syntax, including a resolved type alias
+        let shorthand = emits(
+            r#"
+            type Emits = { save: [id: number] }
+            const emit = defineEmits<Emits & { "update:value": [] }>()
+            "#,
+        );
+        assert!(
+            shorthand == r#"["save","update:value"]"# || shorthand == r#"["update:value","save"]"#,
+            "{shorthand}"
+        );
+
+        // Both syntaxes at once are not allowed
+        let (_, _, errors) = transform_ts_with_types(
+            r#"
+            const emit = defineEmits<{
+                (e: "foo"): void
+                bar: []
+            }>()
+            "#,
+        );
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::ScriptError(ScriptError {
+                kind: ScriptErrorKind::DefineEmitsMixedCallAndPropertySyntax,
+                ..
+            })]
+        ));
+    }
+
     // https://github.com/vuejs/core/blob/140a7681cc3bba22f55d97fd85a5eafe97a1230f/packages/compiler-sfc/__tests__/compileScript.spec.ts#L871-L890
     #[test]
     fn non_type_named_exports() {
@@ -1,9 +1,21 @@
 use fervid_core::{BindingTypes, FervidAtom};
-use fxhash::FxHashSet;
 use itertools::{Either, Itertools};
-use swc_core::{common::{Spanned, DUMMY_SP}, ecma::ast::{ArrayLit, CallExpr, Expr, ExprOrSpread, Ident, Lit, Str, TsFnOrConstructorType, TsFnParam, TsLit, TsType}};
-
-use crate::{atoms::EMIT_HELPER, error::{ScriptError, ScriptErrorKind, TransformError}, script::resolve_type::{resolve_type_elements, resolve_union_type, ResolvedElements, TypeResolveContext}, SetupBinding, SfcExportedObjectHelper, TypeOrDecl};
+use swc_core::{
+    common::{Spanned, DUMMY_SP},
+    ecma::ast::{
+        ArrayLit, CallExpr, Expr, ExprOrSpread, Ident, Lit, Str, TsFnOrConstructorType, TsFnParam,
+        TsLit, TsType,
+    },
+};
+
+use crate::{
+    atoms::EMIT_HELPER,
+    error::{ScriptError, ScriptErrorKind, TransformError},
+    script::resolve_type::{
+        resolve_type_elements, resolve_union_type, ResolvedElements, TypeResolveContext,
+    },
+    SetupBinding, SfcExportedObjectHelper, TypeOrDecl,
+};
 
 use super::macros::TransformMacroResult;
 
@@ -69,7 +81,7 @@ pub fn process_define_emits(
     // Change binding to be `SetupConst`
     if is_var_decl {
         sfc_object_helper.is_setup_emit_referenced = true;
-        
+
         if let Some(var_bindings) = var_bindings {
             if is_ident && var_bindings.len() == 1 {
                 let first_binding = &mut var_bindings[0];
@@ -93,8 +105,8 @@ pub fn process_define_emits(
 fn extract_runtime_emits(
     ctx: &mut TypeResolveContext,
     type_arg: &TsType,
-) -> Result<FxHashSet<FervidAtom>, ScriptError> {
-    let mut emits = FxHashSet::<FervidAtom>::default();
+) -> Result<Vec<FervidAtom>, ScriptError> {
+    let mut emits = Vec::<FervidAtom>::new();
 
     // Handle cases like `defineEmits<(e: 'foo' | 'bar') => void>()`
     if let TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(ref ts_fn_type)) = type_arg
@@ -116,7 +128,7 @@ fn extract_runtime_emits(
 
     let mut has_property = false;
     for key in props.into_keys() {
-        emits.insert(key);
+        add_event_name(&mut emits, key);
         has_property = true;
     }
 
@@ -151,7 +163,7 @@ fn extract_runtime_emits(
 fn extract_event_names(
     ctx: &mut TypeResolveContext,
     event_name: &TsFnParam,
-    emits: &mut FxHashSet<FervidAtom>,
+    emits: &mut Vec<FervidAtom>,
 ) {
     let TsFnParam::Ident(ident) = event_name else {
         return;
@@ -174,19 +186,27 @@ fn extract_event_names(
             // No UnaryExpression
             match ts_lit_type.lit {
                 TsLit::Number(ref n) => {
-                    emits.insert(FervidAtom::from(n.value.to_string()));
+                    add_event_name(emits, FervidAtom::from(n.value.to_string()));
                 }
                 TsLit::Str(ref s) => {
-                    emits.insert(s.value.to_owned());
+                    add_event_name(emits, s.value.to_owned());
                 }
                 TsLit::Bool(ref b) => {
-                    emits.insert(FervidAtom::from(b.value.to_string()));
+                    add_event_name(emits, FervidAtom::from(b.value.to_string()));
                 }
                 TsLit::BigInt(ref big_int) => {
-                    emits.insert(FervidAtom::from(big_int.value.to_string()));
+                    add_event_name(emits, FervidAtom::from(big_int.value.to_string()));
                 }
                 TsLit::Tpl(_) => {}
             }
         }
     }
 }
+
+/// Adds the event name once, keeping the declaration order
+#[inline]
+fn add_event_name(emits: &mut Vec<FervidAtom>, event_name: FervidAtom) {
+    if !emits.contains(&event_name) {
+        emits.push(event_name);
+    }
+}
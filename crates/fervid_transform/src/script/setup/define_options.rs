@@ -1,10 +1,13 @@
+use fervid_core::FervidAtom;
 use swc_core::{
     common::{Spanned, DUMMY_SP},
-    ecma::ast::{CallExpr, Expr, ExprOrSpread, Prop, PropOrSpread},
+    ecma::ast::{CallExpr, Expr, ExprOrSpread, Prop, PropName, PropOrSpread},
 };
 
 use crate::{
-    error::{ScriptError, ScriptErrorKind, TransformError}, script::setup::utils::unwrap_ts_node_expr, SfcExportedObjectHelper
+    error::{ScriptError, ScriptErrorKind, TransformError},
+    script::setup::utils::unwrap_ts_node_expr,
+    SfcExportedObjectHelper,
 };
 
 use super::macros::TransformMacroResult;
@@ -76,11 +79,11 @@ pub fn process_define_options(
         };
         let key = match prop.as_ref() {
             Prop::Shorthand(ident) => Some(&ident.sym),
-            Prop::KeyValue(key_value_prop) => key_value_prop.key.as_ident().map(|v| &v.sym),
+            Prop::KeyValue(key_value_prop) => static_key(&key_value_prop.key),
             Prop::Assign(_) => None,
-            Prop::Getter(getter_prop) => getter_prop.key.as_ident().map(|v| &v.sym),
-            Prop::Setter(setter_prop) => setter_prop.key.as_ident().map(|v| &v.sym),
-            Prop::Method(method_prop) => method_prop.key.as_ident().map(|v| &v.sym),
+            Prop::Getter(getter_prop) => static_key(&getter_prop.key),
+            Prop::Setter(setter_prop) => static_key(&setter_prop.key),
+            Prop::Method(method_prop) => static_key(&method_prop.key),
         };
         let Some(key) = key else {
             continue;
@@ -120,3 +123,12 @@ pub fn process_define_options(
 
     valid_macro!(None)
 }
+
+/// Name of the option, e.g. `props` in both `{ props: [] }` and `{ "props": [] }`
+fn static_key(prop_name: &PropName) -> Option<&FervidAtom> {
+    match prop_name {
+        PropName::Ident(ident_name) => Some(&ident_name.sym),
+        PropName::Str(s) => Some(&s.value),
+        _ => None,
+    }
+}
@@ -124,6 +124,8 @@ pub fn process_with_defaults(
 
 /// Extracts runtime and types from `defineProps` call
 fn extract_from_define_props(define_props_call: &CallExpr, out: &mut DefineProps) {
+    out.span = define_props_call.span;
+
     // Runtime
     if let Some(first_argument) = &define_props_call.args.get(0) {
         out.runtime_decl = Some(first_argument.expr.to_owned());
@@ -586,8 +588,8 @@ fn has_static_with_defaults(defaults: Option<&Expr>) -> bool {
             Prop::Getter(getter) => &getter.key,
             Prop::Setter(setter) => &setter.key,
             Prop::Method(method) => &method.key,
-            // No key here, assume to be static
-            Prop::Shorthand(_) => return false,
+            // `{ foo }` is `{ foo: foo }`, the key is static
+            Prop::Shorthand(_) => return true,
             // This is not in the ObjectLit
             Prop::Assign(_) => return true,
         };
@@ -1,21 +1,22 @@
-use fervid_core::VueImports;
+use fervid_core::{FervidAtom, VueImports};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{CallExpr, Callee, Expr, Ident},
+    ecma::ast::{CallExpr, Callee, Expr, Ident, TsTypeParamInstantiation},
 };
 
 use crate::{
     error::{ScriptError, ScriptErrorKind, TransformError},
-    BindingsHelper, SfcExportedObjectHelper,
+    script::resolve_type::{resolve_type_elements, TypeResolveContext},
+    SfcExportedObjectHelper,
 };
 
 use super::macros::TransformMacroResult;
 
 pub fn process_define_slots(
+    ctx: &mut TypeResolveContext,
     call_expr: &CallExpr,
     is_var_decl: bool,
     sfc_object_helper: &mut SfcExportedObjectHelper,
-    bindings_helper: &mut BindingsHelper,
 ) -> TransformMacroResult {
     if sfc_object_helper.has_define_slots {
         return TransformMacroResult::Error(TransformError::ScriptError(ScriptError {
@@ -32,13 +33,19 @@ pub fn process_define_slots(
         }));
     }
 
+    // Slot names are known from the type, e.g. `defineSlots<{ default(props: {}): any }>()`
+    if let Some(ref type_args) = call_expr.type_args {
+        ctx.bindings_helper.declared_slots = read_declared_slots(ctx, type_args);
+    }
+
     // `defineSlots` without a variable declaration
     if !is_var_decl {
         return TransformMacroResult::ValidMacro(None);
     }
 
-    // Add to imports and get the identifier
-    bindings_helper.vue_imports |= VueImports::UseSlots;
+    // Add to imports and get the identifier.
+    // Type arguments are stripped, `useSlots()` does not need them
+    ctx.bindings_helper.vue_imports |= VueImports::UseSlots;
     let use_slots_ident = Ident {
         span: DUMMY_SP,
         ctxt: Default::default(),
@@ -55,3 +62,17 @@ pub fn process_define_slots(
         type_args: None,
     }))))
 }
+
+/// Reads the slot names from the type argument of `defineSlots`.
+/// Returns `None` if the type cannot be resolved, the slots are not validated then.
+fn read_declared_slots(
+    ctx: &mut TypeResolveContext,
+    type_args: &TsTypeParamInstantiation,
+) -> Option<Vec<FervidAtom>> {
+    let slots_type = type_args.params.first()?;
+    let resolved = resolve_type_elements(ctx, slots_type).ok()?;
+
+    let mut slot_names: Vec<FervidAtom> = resolved.props.into_keys().collect();
+    slot_names.sort();
+    Some(slot_names)
+}
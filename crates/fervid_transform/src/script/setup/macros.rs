@@ -155,7 +155,7 @@ pub fn transform_script_setup_macro_expr(
             bindings_helper,
         )
     } else if DEFINE_SLOTS.eq(sym) {
-        process_define_slots(call_expr, is_var_decl, sfc_object_helper, bindings_helper)
+        process_define_slots(ctx, call_expr, is_var_decl, sfc_object_helper)
     } else if DEFINE_OPTIONS.eq(sym) {
         process_define_options(call_expr, is_var_decl, sfc_object_helper, errors)
     } else {
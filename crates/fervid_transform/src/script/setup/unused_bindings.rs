@@ -0,0 +1,350 @@
+//! Detection of `<script setup>` bindings which are never referenced
+
+use fervid_core::{BindingTypes, ComponentBinding, CustomDirectiveBinding, FervidAtom};
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use swc_core::{
+    common::Span,
+    ecma::{
+        ast::{
+            ArrowExpr, BindingIdent, BlockStmt, Callee, CatchClause, Decl, Expr, Function, Ident,
+            IdentName, Module, Stmt, VarDeclarator,
+        },
+        visit::{Visit, VisitWith},
+    },
+};
+
+use crate::{
+    error::{ScriptError, ScriptErrorKind, TransformError},
+    script::common::extract_variables_from_pat,
+    BindingsHelper, SetupBinding,
+};
+
+/// Vue APIs which do not have side effects when called.
+/// Bindings initialized with calls to anything else are not reported.
+const PURE_VUE_APIS: [&str; 8] = [
+    "computed",
+    "reactive",
+    "readonly",
+    "ref",
+    "shallowReactive",
+    "shallowReadonly",
+    "shallowRef",
+    "toRef",
+];
+
+/// Reports a [`ScriptErrorKind::UnusedSetupBinding`] warning for every `<script setup>` binding
+/// which is referenced neither in the template nor in the script.
+///
+/// Bindings initialized by a side-effectful call (e.g. `const stop = watch(...)`) are skipped,
+/// because removing them would change the behaviour.
+/// Must be called after both the scripts and the template were transformed.
+pub fn report_unused_setup_bindings(
+    bindings_helper: &BindingsHelper,
+    module: &Module,
+    setup_fn: Option<&Function>,
+    errors: &mut Vec<TransformError>,
+) {
+    let mut collector = UsageCollector::default();
+    module.visit_with(&mut collector);
+    // The body of `setup` is where the bindings are declared, it is not a nested scope
+    if let Some(body) = setup_fn.and_then(|setup_fn| setup_fn.body.as_ref()) {
+        body.stmts.visit_with(&mut collector);
+    }
+
+    // Components and directives may be resolved to setup bindings without being recorded as used
+    let mut template_resolutions = ResolutionsCollector::default();
+    for component_binding in bindings_helper.components.values() {
+        if let ComponentBinding::Resolved(expr) = component_binding {
+            expr.visit_with(&mut template_resolutions);
+        }
+    }
+    for directive_binding in bindings_helper.custom_directives.values() {
+        if let CustomDirectiveBinding::Resolved(expr) = directive_binding {
+            expr.visit_with(&mut template_resolutions);
+        }
+    }
+
+    for binding in bindings_helper.setup_bindings.iter() {
+        let is_checked = matches!(
+            binding.1,
+            BindingTypes::SetupLet
+                | BindingTypes::SetupConst
+                | BindingTypes::SetupReactiveConst
+                | BindingTypes::SetupMaybeRef
+                | BindingTypes::SetupRef
+                | BindingTypes::LiteralConst
+                | BindingTypes::Component
+                | BindingTypes::Imported
+        );
+        if !is_checked
+            || bindings_helper.used_bindings.contains_key(&binding.0)
+            || template_resolutions.names.contains(&binding.0)
+            || collector.side_effectful.contains(&binding.0)
+        {
+            continue;
+        }
+
+        // The declaration itself is the only occurrence
+        if let Some((1, span)) = collector.usages.get(&binding.0) {
+            errors.push(TransformError::ScriptError(ScriptError {
+                span: *span,
+                kind: ScriptErrorKind::UnusedSetupBinding,
+            }));
+        }
+    }
+}
+
+#[derive(Default)]
+struct UsageCollector {
+    /// Number of occurrences and the span of the first one (usually a declaration)
+    usages: HashMap<FervidAtom, (u32, Span)>,
+    /// Bindings declared using a side-effectful initializer
+    side_effectful: HashSet<FervidAtom>,
+    is_in_side_effectful_decl: bool,
+    /// Variables of the nested functions and blocks, which shadow the setup bindings
+    local_vars: Vec<SetupBinding>,
+}
+
+impl UsageCollector {
+    fn is_local(&self, sym: &FervidAtom) -> bool {
+        self.local_vars.iter().rfind(|it| &it.0 == sym).is_some()
+    }
+}
+
+impl Visit for UsageCollector {
+    fn visit_ident(&mut self, n: &Ident) {
+        if self.is_local(&n.sym) {
+            return;
+        }
+
+        let entry = self.usages.entry(n.sym.to_owned()).or_insert((0, n.span));
+        entry.0 += 1;
+    }
+
+    fn visit_binding_ident(&mut self, n: &BindingIdent) {
+        if self.is_in_side_effectful_decl && !self.is_local(&n.id.sym) {
+            self.side_effectful.insert(n.id.sym.to_owned());
+        }
+
+        n.visit_children_with(self);
+    }
+
+    fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+        let old_is_in_side_effectful_decl = self.is_in_side_effectful_decl;
+        self.is_in_side_effectful_decl = n.init.as_deref().map_or(false, is_side_effectful);
+        n.name.visit_with(self);
+        self.is_in_side_effectful_decl = old_is_in_side_effectful_decl;
+
+        n.init.visit_with(self);
+    }
+
+    fn visit_function(&mut self, n: &Function) {
+        let old_len = self.local_vars.len();
+
+        for param in n.params.iter() {
+            extract_variables_from_pat(&param.pat, &mut self.local_vars, true);
+        }
+        n.visit_children_with(self);
+
+        self.local_vars.drain(old_len..);
+    }
+
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        let old_len = self.local_vars.len();
+
+        for param in n.params.iter() {
+            extract_variables_from_pat(param, &mut self.local_vars, true);
+        }
+        n.visit_children_with(self);
+
+        self.local_vars.drain(old_len..);
+    }
+
+    fn visit_catch_clause(&mut self, n: &CatchClause) {
+        let old_len = self.local_vars.len();
+
+        if let Some(ref param) = n.param {
+            extract_variables_from_pat(param, &mut self.local_vars, true);
+        }
+        n.visit_children_with(self);
+
+        self.local_vars.drain(old_len..);
+    }
+
+    fn visit_block_stmt(&mut self, n: &BlockStmt) {
+        // All variables are treated as block scope, the declarations are hoisted
+        let old_len = self.local_vars.len();
+
+        for stmt in n.stmts.iter() {
+            let Stmt::Decl(decl) = stmt else {
+                continue;
+            };
+
+            match decl {
+                Decl::Class(cls) => self.local_vars.push(SetupBinding(
+                    cls.ident.sym.to_owned(),
+                    BindingTypes::TemplateLocal,
+                )),
+                Decl::Fn(fn_decl) => self.local_vars.push(SetupBinding(
+                    fn_decl.ident.sym.to_owned(),
+                    BindingTypes::TemplateLocal,
+                )),
+                Decl::Var(var_decl) => {
+                    for var_decl_it in var_decl.decls.iter() {
+                        extract_variables_from_pat(&var_decl_it.name, &mut self.local_vars, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+        n.visit_children_with(self);
+
+        self.local_vars.drain(old_len..);
+    }
+}
+
+#[derive(Default)]
+struct ResolutionsCollector {
+    names: HashSet<FervidAtom>,
+}
+
+impl Visit for ResolutionsCollector {
+    fn visit_ident(&mut self, n: &Ident) {
+        self.names.insert(n.sym.to_owned());
+    }
+
+    fn visit_ident_name(&mut self, n: &IdentName) {
+        self.names.insert(n.sym.to_owned());
+    }
+}
+
+fn is_side_effectful(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(call_expr) => match call_expr.callee {
+            Callee::Expr(ref callee) => match callee.as_ref() {
+                Expr::Ident(ident) => !PURE_VUE_APIS.contains(&ident.sym.as_ref()),
+                _ => true,
+            },
+            _ => true,
+        },
+        Expr::New(_) | Expr::Await(_) | Expr::Yield(_) => true,
+        Expr::Paren(paren_expr) => is_side_effectful(&paren_expr.expr),
+        Expr::TsAs(as_expr) => is_side_effectful(&as_expr.expr),
+        Expr::TsNonNull(non_null_expr) => is_side_effectful(&non_null_expr.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::SfcScriptBlock;
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+    use crate::{
+        script::{imports::process_imports, setup::transform_and_record_script_setup},
+        test_utils::parser::parse_typescript_module,
+        TransformSfcContext,
+    };
+
+    fn unused_bindings_count(code: &str) -> usize {
+        let mut module = parse_typescript_module(code, 0, Default::default())
+            .unwrap()
+            .0;
+        let mut ctx = TransformSfcContext::anonymous();
+        let mut errors = Vec::new();
+        process_imports(&mut module, &mut ctx.bindings_helper, true, &mut errors);
+
+        let result = transform_and_record_script_setup(
+            &mut ctx,
+            SfcScriptBlock {
+                content: Box::new(module),
+                lang: fervid_core::SfcScriptLang::Typescript,
+                is_setup: true,
+                span: DUMMY_SP,
+            },
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        let module = Module {
+            span: DUMMY_SP,
+            body: result.module_items,
+            shebang: None,
+        };
+        report_unused_setup_bindings(
+            &ctx.bindings_helper,
+            &module,
+            result.setup_fn.as_deref(),
+            &mut errors,
+        );
+
+        errors
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    TransformError::ScriptError(ScriptError {
+                        kind: ScriptErrorKind::UnusedSetupBinding,
+                        ..
+                    })
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn it_reports_unused_bindings() {
+        assert_eq!(1, unused_bindings_count("const foo = ref(1)"));
+        assert_eq!(1, unused_bindings_count("let bar = 'bar'"));
+        assert_eq!(1, unused_bindings_count("import { baz } from './baz'"));
+        assert_eq!(
+            2,
+            unused_bindings_count("import { ref } from 'vue'\nconst a = ref()\nfunction b() {}")
+        );
+    }
+
+    #[test]
+    fn it_does_not_report_used_bindings() {
+        assert_eq!(
+            0,
+            unused_bindings_count("const foo = ref(1)\nconsole.log(foo)")
+        );
+        assert_eq!(
+            0,
+            unused_bindings_count("import { baz } from './baz'\nconst qux = { baz }\nqux")
+        );
+    }
+
+    #[test]
+    fn it_does_not_count_shadowing_variables_as_usages() {
+        assert_eq!(
+            1,
+            unused_bindings_count("const foo = 1\nfunction f(foo) { return foo }\nf()")
+        );
+        assert_eq!(
+            1,
+            unused_bindings_count(
+                "const foo = 1\nconst f = () => { const foo = 2; return foo }\nf()"
+            )
+        );
+        assert_eq!(
+            1,
+            unused_bindings_count("const foo = 1\ntry {} catch (foo) { console.log(foo) }")
+        );
+        assert_eq!(
+            0,
+            unused_bindings_count("const foo = 1\nfunction f(bar) { return foo + bar }\nf()")
+        );
+    }
+
+    #[test]
+    fn it_does_not_report_side_effectful_initializers() {
+        assert_eq!(
+            0,
+            unused_bindings_count("const stop = watch(foo, () => {})")
+        );
+        assert_eq!(0, unused_bindings_count("const { data } = useFetch()"));
+        assert_eq!(0, unused_bindings_count("const instance = new Foo()"));
+    }
+}
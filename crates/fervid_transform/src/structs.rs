@@ -1,17 +1,22 @@
 //! Exports data structs used by the crate
 
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use fervid_core::{
     BindingTypes, ComponentBinding, CustomDirectiveBinding, FervidAtom, SfcCustomBlock,
-    SfcStyleBlock, SfcTemplateBlock, TemplateGenerationMode, VueImportsSet,
+    SfcStyleBlock, SfcTemplateBlock, TemplateGenerationMode, VueImportsSet, WhitespaceMode,
 };
 use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use smallvec::SmallVec;
-use swc_core::ecma::ast::{
-    Decl, Expr, ExprOrSpread, Function, Id, Module, ObjectLit, PropOrSpread, Str, TsType,
+use swc_core::{
+    common::Span,
+    ecma::ast::{
+        Decl, Expr, ExprOrSpread, Function, Id, Module, ObjectLit, PropOrSpread, Str, TsType,
+    },
 };
 
+use crate::fs::FileSystem;
+
 /// Context object. Currently very minimal but may grow over time.
 pub struct TransformSfcContext {
     pub filename: String,
@@ -21,8 +26,17 @@ pub struct TransformSfcContext {
     /// For Custom Elements
     pub is_ce: bool,
     pub bindings_helper: BindingsHelper,
+    /// Compile-time feature flags of the consumer
+    pub feature_flags: FeatureFlags,
+    /// Whether the SFC has a `<template>`. Without it, `<script setup lang="tsx">`
+    /// may render the component with `export default <div />`
+    pub has_template: bool,
     pub deps: HashSet<String>,
+    /// All the file access, e.g. for the types imported by `defineProps<Props>()`
+    pub fs: Arc<dyn FileSystem>,
     pub(crate) scopes: Vec<TypeScopeContainer>,
+    /// Scopes of the already loaded files by their names
+    pub(crate) file_scopes: HashMap<String, usize>,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -33,10 +47,34 @@ pub enum PropsDestructureConfig {
     Error
 }
 
+/// Compile-time feature flags, which should match the `define` settings of the consumer's bundler.
+/// These influence the emitted code.
+#[derive(Debug, Clone, Copy)]
+pub struct FeatureFlags {
+    /// `__VUE_OPTIONS_API__`. When disabled, Options API (`data`, `computed`, `methods`, `inject`)
+    /// is not analyzed, because the runtime does not support it.
+    pub options_api: bool,
+    /// `__VUE_PROD_DEVTOOLS__`. When enabled in production, the template is not inlined
+    /// so that `<script setup>` bindings stay inspectable by the devtools.
+    pub prod_devtools: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        FeatureFlags {
+            options_api: true,
+            prod_devtools: false,
+        }
+    }
+}
+
+/// Recognizes the native elements of the target platform by their tag names
+pub type IsNativeTag = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
 /// A helper which encompasses all the logic related to bindings,
 /// such as their types, which of them were used, what components and directives
 /// were seen in the template, etc.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct BindingsHelper {
     /// All components present in the `<template>`
     pub components: HashMap<FervidAtom, ComponentBinding>,
@@ -57,8 +95,45 @@ pub struct BindingsHelper {
     /// - inline as last statement of `setup` or
     /// - as a `render` function.
     pub template_generation_mode: TemplateGenerationMode,
+    /// Recognizes the native elements in the `<template>`, other tags are components.
+    /// When `None`, HTML, SVG and MathML tags are native.
+    pub is_native_tag: Option<IsNativeTag>,
+    /// Leave the template identifiers without prefixes (`prefixIdentifiers: false` of the official compiler).
+    /// The render function body is then wrapped in `with (_ctx) {}` to resolve them at runtime,
+    /// which is only allowed outside of ES modules, e.g. for the runtime compilation.
+    pub with_ctx: bool,
+    /// Store the `v-on` handlers in `_cache` (`cacheHandlers` of the official compiler),
+    /// so that the child components are not re-rendered because of the new function identities
+    pub cache_handlers: bool,
+    /// Whether the whitespace of the template is condensed or preserved
+    pub whitespace: WhitespaceMode,
+    /// Keep the template comments as comment vnodes.
+    /// When `None`, they are only kept in development, see [`BindingsHelper::keeps_comments`]
+    pub comments: Option<bool>,
+    /// Maximum nesting of the template elements. Deeper elements are reported and their children dropped.
+    /// When `None`, the nesting is not limited
+    pub max_depth: Option<u32>,
+    /// Generate the SFC as a functional component: a plain render function of the props without an instance.
+    /// Only set when the SFC has no scripts
+    pub functional: bool,
+    /// `setup` returns the render function of `<script setup lang="tsx">` without a `<template>`,
+    /// e.g. for `export default <div />`. Nothing is returned from `setup` after it
+    pub setup_render: bool,
+    /// The SFC has scoped styles, but none of them use `:slotted()`.
+    /// The slot content of `<slot>` then does not get the `-s` scope id, see [`record_slotted_styles`]
+    ///
+    /// [`record_slotted_styles`]: crate::style::record_slotted_styles
+    pub no_slotted: bool,
     /// Identifiers used in the template and their respective binding types
     pub used_bindings: HashMap<FervidAtom, BindingTypes>,
+    /// Names of the CSS Modules compiled by fervid, e.g. `$style` for `<style module>`.
+    /// In the template they resolve to `__cssModules`, e.g. `$style.foo` becomes `__cssModules.$style.foo`.
+    pub css_modules: Vec<FervidAtom>,
+    /// `v-for` directives of the `<template>` and their keys, in order of appearance
+    pub v_for_keys: Vec<VForKey>,
+    /// Slot names declared by `defineSlots<{ ... }>()`.
+    /// `None` when the slots are not declared or their type could not be resolved.
+    pub declared_slots: Option<Vec<FervidAtom>>,
     /// Imported symbols
     pub user_imports: HashMap<FervidAtom, ImportBinding>,
     /// Internal Vue imports used by built-in components, directives and others
@@ -67,6 +142,51 @@ pub struct BindingsHelper {
     pub vue_resolved_imports: Box<VueResolvedImports>,
 }
 
+// `is_native_tag` is a closure, so `Debug` cannot be derived
+impl std::fmt::Debug for BindingsHelper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BindingsHelper")
+            .field("components", &self.components)
+            .field("custom_directives", &self.custom_directives)
+            .field("is_prod", &self.is_prod)
+            .field("is_ts", &self.is_ts)
+            .field("template_scopes", &self.template_scopes)
+            .field("setup_bindings", &self.setup_bindings)
+            .field("options_api_bindings", &self.options_api_bindings)
+            .field("template_generation_mode", &self.template_generation_mode)
+            .field(
+                "is_native_tag",
+                &self.is_native_tag.as_ref().map(|_| "Fn(&str) -> bool"),
+            )
+            .field("with_ctx", &self.with_ctx)
+            .field("cache_handlers", &self.cache_handlers)
+            .field("whitespace", &self.whitespace)
+            .field("comments", &self.comments)
+            .field("max_depth", &self.max_depth)
+            .field("functional", &self.functional)
+            .field("setup_render", &self.setup_render)
+            .field("no_slotted", &self.no_slotted)
+            .field("used_bindings", &self.used_bindings)
+            .field("css_modules", &self.css_modules)
+            .field("v_for_keys", &self.v_for_keys)
+            .field("declared_slots", &self.declared_slots)
+            .field("user_imports", &self.user_imports)
+            .field("vue_imports", &self.vue_imports)
+            .field("vue_resolved_imports", &self.vue_resolved_imports)
+            .finish()
+    }
+}
+
+/// A `v-for` of the `<template>` and its `key`,
+/// e.g. for the tooling which correlates the list updates with the template locations
+#[derive(Debug, Clone)]
+pub struct VForKey {
+    /// Span of the `v-for` directive
+    pub span: Span,
+    /// Transformed `key` of the element, `None` when the list is not keyed
+    pub key: Option<Box<Expr>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ScopeTypeNode {
     pub value: TypeOrDecl,
@@ -99,7 +219,7 @@ pub struct TypeScope {
 pub type TypeScopeContainer = Rc<RefCell<TypeScope>>;
 
 // Todo maybe use SmallVec?
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct OptionsApiBindings {
     pub data: Vec<FervidAtom>,
     pub setup: Vec<SetupBinding>,
@@ -117,7 +237,7 @@ pub struct OptionsApiBindings {
 }
 
 /// Identifier plus a binding type
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SetupBinding(pub FervidAtom, pub BindingTypes);
 
 #[derive(Debug, Clone)]
@@ -141,7 +261,7 @@ pub struct TemplateScope {
 }
 
 /// Imports from "vue" package
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct VueResolvedImports {
     pub ref_import: Option<Id>,
     pub computed: Option<Id>,
@@ -195,6 +315,33 @@ pub struct TransformSfcOptions<'s> {
     pub props_destructure: PropsDestructureConfig,
     pub scope_id: &'s str,
     pub filename: &'s str,
+    /// Report `<script setup>` bindings which are never used as warnings
+    pub warn_unused_bindings: bool,
+    /// Whether the consumer supports `<Suspense>`.
+    /// When `false`, a component with an async `setup` is reported as an error,
+    /// because it can only be rendered inside a `<Suspense>` boundary.
+    pub suspense: bool,
+    /// Compile-time feature flags
+    pub feature_flags: FeatureFlags,
+    /// Minify the emitted `lang="css"` style blocks (whitespace and comments removal)
+    pub minify_css: bool,
+    /// Recognizes the native elements of the target platform instead of the HTML, SVG and MathML tags
+    pub is_native_tag: Option<IsNativeTag>,
+    /// Mark the fully static element subtrees of the template to be created once, outside of `render`
+    pub hoist_static: bool,
+    /// Cache the `v-on` handlers which do not depend on the template scope, see [`BindingsHelper::cache_handlers`]
+    pub cache_handlers: bool,
+    /// Whether the whitespace of the template is condensed or preserved
+    pub whitespace: WhitespaceMode,
+    /// Keep the template comments, see [`BindingsHelper::comments`]
+    pub comments: Option<bool>,
+    /// Compile a script-less SFC as a functional component, see [`BindingsHelper::functional`]
+    pub functional: bool,
+    /// Maximum nesting of the template elements, see [`BindingsHelper::max_depth`]
+    pub max_depth: Option<u32>,
+    /// Provides the files, e.g. to resolve the types imported from relative paths in `defineProps<Props>()`.
+    /// When `None`, an empty in-memory file system is used.
+    pub fs: Option<Arc<dyn FileSystem>>,
 }
 
 pub struct TransformSfcResult {
@@ -208,10 +355,17 @@ pub struct TransformSfcResult {
     pub setup_fn: Option<Box<Function>>,
     /// Transformed template block
     pub template_block: Option<SfcTemplateBlock>,
+    /// Vue helpers used by the template expressions, e.g. `unref`
+    pub template_vue_imports: VueImportsSet,
     /// Transformed style blocks
     pub style_blocks: Vec<SfcStyleBlock>,
     /// Custom blocks
     pub custom_blocks: Vec<SfcCustomBlock>,
+    /// Whether `setup` is async, meaning the component must be rendered inside `<Suspense>`
+    pub is_async_setup: bool,
+    /// Files the SFC depends on, e.g. files with types used in `defineProps`
+    /// or files referenced by style `@import` and `url()`
+    pub deps: HashSet<String>,
 }
 
 #[cfg(test)]
@@ -223,12 +377,47 @@ impl TransformSfcContext {
             bindings_helper: BindingsHelper::default(),
             is_ce: false,
             props_destructure: PropsDestructureConfig::default(),
+            feature_flags: FeatureFlags::default(),
+            has_template: false,
             deps: HashSet::default(),
+            fs: Arc::new(crate::fs::MemoryFileSystem::default()),
             scopes: vec![],
+            file_scopes: HashMap::default(),
         }
     }
 }
 
+impl BindingsHelper {
+    /// Copies the bindings known from the scripts and the styles,
+    /// leaving out everything recorded from the `<template>` (components, directives, used bindings, etc.).
+    /// This allows transforming a new `<template>` without re-transforming the scripts.
+    /// The template generation mode is reset to the separate `render` function.
+    pub fn clone_script_bindings(&self) -> BindingsHelper {
+        BindingsHelper {
+            is_prod: self.is_prod,
+            is_ts: self.is_ts,
+            setup_bindings: self.setup_bindings.to_owned(),
+            options_api_bindings: self.options_api_bindings.to_owned(),
+            is_native_tag: self.is_native_tag.clone(),
+            with_ctx: self.with_ctx,
+            cache_handlers: self.cache_handlers,
+            whitespace: self.whitespace,
+            comments: self.comments,
+            max_depth: self.max_depth,
+            css_modules: self.css_modules.to_owned(),
+            declared_slots: self.declared_slots.to_owned(),
+            user_imports: self.user_imports.to_owned(),
+            vue_resolved_imports: self.vue_resolved_imports.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the template comments are rendered. By default, only in development
+    pub fn keeps_comments(&self) -> bool {
+        self.comments.unwrap_or(!self.is_prod)
+    }
+}
+
 impl TypeScope {
     pub fn new(id: usize, filename: String) -> TypeScope {
         TypeScope {
@@ -1,11 +1,32 @@
-use fervid_core::{fervid_atom, SfcStyleBlock};
+use fervid_core::{
+    fervid_atom, BindingTypes, FervidAtom, IntoIdent, SfcStyleBlock, TemplateGenerationMode,
+    VueImports,
+};
 use fervid_css::*;
+use fxhash::{FxHashSet as HashSet, FxHasher32};
+use std::hash::{Hash, Hasher};
 use swc_core::{
-    common::DUMMY_SP,
-    ecma::ast::{Expr, IdentName, KeyValueProp, Lit, Prop, PropName, PropOrSpread, Str},
+    common::{BytePos, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr,
+            ExprOrSpread, ExprStmt, Function, Ident, IdentName, KeyValueProp, Lit, MethodProp,
+            ModuleItem, ObjectLit, Param, ParenExpr, Pat, Prop, PropName, PropOrSpread, Stmt, Str,
+            VarDecl, VarDeclKind, VarDeclarator,
+        },
+        visit::{Visit, VisitWith},
+    },
 };
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
 
-use crate::{error::TransformError, structs::TransformScriptsResult};
+use crate::{
+    atoms::{CSS_MODULES_HELPER, PROPS_HELPER},
+    error::TransformError,
+    misc::is_setup_key,
+    structs::TransformScriptsResult,
+    template::BindingsHelperTransform,
+    BindingsHelper,
+};
 
 const CSS_PREFIX: &'static str = "data-v-";
 
@@ -35,44 +56,803 @@ pub fn create_style_scope(file_hash: &str) -> String {
     scope
 }
 
+/// A CSS Module compiled from `<style module>`
+#[derive(Debug)]
+pub struct CssModule {
+    /// Name of the module, e.g. `$style`
+    pub name: FervidAtom,
+    /// Pairs of the original and the renamed class names
+    pub classes: Vec<(FervidAtom, FervidAtom)>,
+}
+
+/// Transforms the `lang="css"` style blocks which are either scoped, CSS Modules or need to be minified.
+/// The compiled CSS Modules are added to `css_modules`.
+/// Returns `true` if any scoped block was encountered.
 pub fn transform_style_blocks(
     style_blocks: &mut Vec<SfcStyleBlock>,
     scope: &str,
+    minify: bool,
+    css_modules: &mut Vec<CssModule>,
     errors: &mut Vec<TransformError>,
 ) -> bool {
     // Check work
-    if !style_blocks.iter().any(should_transform_style_block) {
+    if !style_blocks
+        .iter()
+        .any(|block| should_transform_style_block(block, minify))
+    {
         return false;
     }
 
     // TODO Config
-    // TODO Allow minifying CSS
+    let config = || TransformCssConfig {
+        stringify: StringifyOptions {
+            minify,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
 
     // Map errors from `fervid_css` to `fervid_transform`
     let mut css_errors = Vec::new();
+    let mut had_scoped_blocks = false;
 
     for style_block in style_blocks.iter_mut() {
-        if style_block.is_scoped && style_block.lang == "css" {
+        if !should_transform_style_block(style_block, minify) {
+            continue;
+        }
+
+        had_scoped_blocks |= style_block.is_scoped;
+        let block_scope = style_block.is_scoped.then_some(scope);
+
+        let Some(ref module_name) = style_block.module else {
             let result = transform_css(
                 &style_block.content,
                 style_block.span,
-                Some(&scope),
+                block_scope,
                 &mut css_errors,
-                TransformCssConfig::default(),
+                config(),
             );
 
             if let Some(transformed) = result {
                 style_block.content = transformed.into();
             }
+            continue;
+        };
+
+        let module_hash = create_css_module_hash(scope, module_name);
+        let result = transform_css_module(
+            &style_block.content,
+            style_block.span,
+            block_scope,
+            &module_hash,
+            &mut css_errors,
+            config(),
+        );
+
+        // The module is registered even when it failed to compile,
+        // because the template may reference it
+        let classes = match result {
+            Some((transformed, classes)) => {
+                style_block.content = transformed.into();
+                classes
+            }
+            None => vec![],
+        };
+
+        // Blocks with the same module name are merged
+        match css_modules.iter_mut().find(|m| &m.name == module_name) {
+            Some(css_module) => css_module.classes.extend(classes),
+            None => css_modules.push(CssModule {
+                name: module_name.to_owned(),
+                classes,
+            }),
         }
     }
 
     errors.extend(css_errors.into_iter().map(From::from));
 
-    true
+    had_scoped_blocks
+}
+
+/// Remembers the names of the CSS Modules, so that the template can reference them, e.g. `$style.foo`.
+/// This must be called before the template is transformed.
+pub fn record_css_modules(style_blocks: &[SfcStyleBlock], bindings_helper: &mut BindingsHelper) {
+    for style_block in style_blocks.iter() {
+        let Some(ref module_name) = style_block.module else {
+            continue;
+        };
+
+        if !bindings_helper.css_modules.contains(module_name) {
+            bindings_helper.css_modules.push(module_name.to_owned());
+        }
+    }
+}
+
+/// Remembers whether the scoped styles style the slot content with `:slotted()`.
+/// When they do not, `renderSlot` is told to skip the `-s` scope id of the slot content.
+pub fn record_slotted_styles(style_blocks: &[SfcStyleBlock], bindings_helper: &mut BindingsHelper) {
+    let mut scoped_blocks = style_blocks
+        .iter()
+        .filter(|block| block.is_scoped)
+        .peekable();
+    if scoped_blocks.peek().is_none() {
+        return;
+    }
+
+    bindings_helper.no_slotted = !scoped_blocks.any(|block| has_slotted_selectors(&block.content));
+}
+
+/// Adds the compiled CSS Modules to the module and to the exported object:
+/// `const __cssModules = { $style: { foo: "foo_1a2b3c4d" } }` and `__cssModules` component option.
+/// CSS Modules in other languages are not compiled by fervid,
+/// the integrations add them after the preprocessor, e.g. `__cssModules["$style"] = style0`.
+pub fn attach_css_modules(
+    transform_result: &mut TransformScriptsResult,
+    style_blocks: &[SfcStyleBlock],
+    css_modules: Vec<CssModule>,
+) {
+    if !style_blocks.iter().any(|block| block.module.is_some()) {
+        return;
+    }
+
+    let str_prop = |key: FervidAtom, value: Box<Expr>| {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str {
+                span: DUMMY_SP,
+                value: key,
+                raw: None,
+            }),
+            value,
+        })))
+    };
+
+    let modules_obj = ObjectLit {
+        span: DUMMY_SP,
+        props: css_modules
+            .into_iter()
+            .map(|css_module| {
+                let classes_obj = ObjectLit {
+                    span: DUMMY_SP,
+                    props: css_module
+                        .classes
+                        .into_iter()
+                        .map(|(original, renamed)| {
+                            str_prop(
+                                original,
+                                Box::new(Expr::Lit(Lit::Str(Str {
+                                    span: DUMMY_SP,
+                                    value: renamed,
+                                    raw: None,
+                                }))),
+                            )
+                        })
+                        .collect(),
+                };
+
+                str_prop(css_module.name, Box::new(Expr::Object(classes_obj)))
+            })
+            .collect(),
+    };
+
+    // `const __cssModules = {}`
+    transform_result
+        .module
+        .body
+        .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                    id: CSS_MODULES_HELPER.to_owned().into_ident(),
+                    type_ann: None,
+                }),
+                init: Some(Box::new(Expr::Object(modules_obj))),
+                definite: false,
+            }],
+        })))));
+
+    // `__cssModules` option
+    transform_result
+        .export_obj
+        .props
+        .push(PropOrSpread::Prop(Box::new(Prop::Shorthand(
+            CSS_MODULES_HELPER.to_owned().into_ident(),
+        ))));
+}
+
+/// Generates the hash used for renaming the classes of a CSS Module.
+/// It is unique for each module of a component.
+fn create_css_module_hash(scope: &str, module_name: &str) -> String {
+    let mut hasher = FxHasher32::default();
+    scope.hash(&mut hasher);
+    module_name.hash(&mut hasher);
+    format!("{:08x}", hasher.finish())
 }
 
+/// Rewrites CSS `v-bind()` to CSS custom properties, e.g. `v-bind(color)` to `var(--abcd1234-color)`,
+/// and injects `_useCssVars(_ctx => ({ "abcd1234-color": color.value }))` at the start of `setup`.
+///
+/// Must be called before the style blocks are transformed, because it works on the source text.
+pub fn transform_css_vars(
+    style_blocks: &mut [SfcStyleBlock],
+    transform_result: &mut TransformScriptsResult,
+    bindings_helper: &mut BindingsHelper,
+    scope_id: &str,
+    errors: &mut Vec<TransformError>,
+) {
+    let is_prod = bindings_helper.is_prod;
+    let has_script_setup = transform_result.setup_fn.is_some();
+
+    // Bindings of a `<script>`-only component are not known, all of them come from `_ctx`
+    let mut ctx_bindings_helper = BindingsHelper {
+        template_generation_mode: TemplateGenerationMode::Inline,
+        ..Default::default()
+    };
+
+    let mut seen_vars: Vec<String> = Vec::new();
+    let mut uses_props = false;
+    let mut css_vars_obj = ObjectLit {
+        span: DUMMY_SP,
+        props: vec![],
+    };
+
+    for style_block in style_blocks.iter_mut() {
+        let css_vars = parse_css_vars(&style_block.content);
+        if css_vars.is_empty() {
+            continue;
+        }
+
+        style_block.content = rewrite_css_vars(&style_block.content, scope_id, is_prod).into();
+
+        for css_var in css_vars {
+            if seen_vars.contains(&css_var) {
+                continue;
+            }
+
+            let Some(mut expr) = parse_css_var_expr(&css_var) else {
+                errors.push(TransformError::CssError(CssError {
+                    span: style_block.span,
+                    kind: CssErrorKind::VBindInvalidExpression,
+                }));
+                continue;
+            };
+
+            // Expressions are evaluated inside `setup`, like the inline template
+            if has_script_setup {
+                let mut collector = CssVarIdentsCollector::default();
+                expr.visit_with(&mut collector);
+                uses_props |= collector.idents.iter().any(|ident| {
+                    matches!(
+                        bindings_helper.get_var_binding_type(0, ident),
+                        BindingTypes::Props
+                    )
+                });
+
+                let old_mode = std::mem::replace(
+                    &mut bindings_helper.template_generation_mode,
+                    TemplateGenerationMode::Inline,
+                );
+                bindings_helper.transform_expr(&mut expr, 0);
+                bindings_helper.template_generation_mode = old_mode;
+            } else {
+                ctx_bindings_helper.transform_expr(&mut expr, 0);
+            }
+
+            css_vars_obj
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(Str {
+                        span: DUMMY_SP,
+                        value: gen_css_var_name(scope_id, &css_var, is_prod).into(),
+                        raw: None,
+                    }),
+                    value: expr,
+                }))));
+
+            seen_vars.push(css_var);
+        }
+    }
+
+    if css_vars_obj.props.is_empty() {
+        return;
+    }
+
+    // `_useCssVars(_ctx => ({ /* vars */ }))`
+    bindings_helper.vue_imports |= VueImports::UseCssVars;
+    let use_css_vars_stmt = Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            ctxt: Default::default(),
+            callee: Callee::Expr(Box::new(Expr::Ident(
+                VueImports::UseCssVars.as_atom().into_ident(),
+            ))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    params: vec![Pat::Ident(BindingIdent {
+                        id: fervid_atom!("_ctx").into_ident(),
+                        type_ann: None,
+                    })],
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Object(css_vars_obj)),
+                    })))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }],
+            type_args: None,
+        })),
+    });
+
+    // `<script setup>`
+    if let Some(ref mut setup_fn) = transform_result.setup_fn {
+        // `__props.foo` needs the `setup` parameter
+        if uses_props && setup_fn.params.is_empty() {
+            setup_fn.params.push(Param {
+                span: DUMMY_SP,
+                decorators: vec![],
+                pat: Pat::Ident(BindingIdent {
+                    id: PROPS_HELPER.to_owned().into_ident(),
+                    type_ann: None,
+                }),
+            });
+        }
+
+        if let Some(ref mut body) = setup_fn.body {
+            body.stmts.insert(0, use_css_vars_stmt);
+        }
+        return;
+    }
+
+    // `setup` of `<script>`
+    let user_setup = transform_result
+        .export_obj
+        .props
+        .iter_mut()
+        .find_map(|prop| {
+            let PropOrSpread::Prop(prop) = prop else {
+                return None;
+            };
+
+            match prop.as_mut() {
+                Prop::Method(method_prop) if is_setup_key(&method_prop.key) => {
+                    Some(Some(method_prop.function.as_mut()))
+                }
+                Prop::KeyValue(KeyValueProp { key, value }) if is_setup_key(key) => {
+                    match value.as_mut() {
+                        Expr::Fn(fn_expr) => Some(Some(fn_expr.function.as_mut())),
+                        // Other forms, e.g. `setup: mySetup`, are not supported
+                        _ => Some(None),
+                    }
+                }
+                _ => None,
+            }
+        });
+
+    match user_setup {
+        Some(Some(function)) => {
+            if let Some(ref mut body) = function.body {
+                body.stmts.insert(0, use_css_vars_stmt);
+            }
+        }
+
+        Some(None) => {}
+
+        // No `setup` at all, add one
+        None => {
+            transform_result
+                .export_obj
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+                    key: PropName::Ident(IdentName {
+                        span: DUMMY_SP,
+                        sym: fervid_atom!("setup"),
+                    }),
+                    function: Box::new(Function {
+                        params: vec![],
+                        decorators: vec![],
+                        span: DUMMY_SP,
+                        ctxt: Default::default(),
+                        body: Some(BlockStmt {
+                            span: DUMMY_SP,
+                            ctxt: Default::default(),
+                            stmts: vec![use_css_vars_stmt],
+                        }),
+                        is_generator: false,
+                        is_async: false,
+                        type_params: None,
+                        return_type: None,
+                    }),
+                }))));
+        }
+    }
+}
+
+/// Records the bindings referenced from CSS `v-bind()` as used,
+/// so that they are kept in the `setup` bindings and are not reported as unused.
+/// The usages of all the style blocks are merged into the bindings of the whole component,
+/// it is not tracked which block references which binding.
+pub fn record_css_vars_usage(style_blocks: &[SfcStyleBlock], bindings_helper: &mut BindingsHelper) {
+    for style_block in style_blocks.iter() {
+        for css_var in parse_css_vars(&style_block.content) {
+            // Invalid expressions are reported when generating the CSS vars code
+            let Some(expr) = parse_css_var_expr(&css_var) else {
+                continue;
+            };
+
+            let mut collector = CssVarIdentsCollector::default();
+            expr.visit_with(&mut collector);
+
+            // Resolving the binding type records its usage
+            for ident in collector.idents {
+                bindings_helper.get_var_binding_type(0, &ident);
+            }
+        }
+    }
+}
+
+/// Records the files referenced by `@import` and `url()` in the style blocks as dependencies.
+/// The styles are not rewritten.
+pub fn record_style_deps(style_blocks: &[SfcStyleBlock], deps: &mut HashSet<String>) {
+    for style_block in style_blocks.iter() {
+        deps.extend(parse_css_deps(&style_block.content, &style_block.lang));
+    }
+}
+
+fn parse_css_var_expr(css_var: &str) -> Option<Box<Expr>> {
+    let lexer = Lexer::new(
+        Syntax::Typescript(Default::default()),
+        Default::default(),
+        StringInput::new(css_var, BytePos(1), BytePos(1 + css_var.len() as u32)),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+
+    parser.parse_expr().ok()
+}
+
+#[derive(Default)]
+struct CssVarIdentsCollector {
+    idents: Vec<FervidAtom>,
+}
+
+impl Visit for CssVarIdentsCollector {
+    fn visit_ident(&mut self, n: &Ident) {
+        self.idents.push(n.sym.to_owned());
+    }
+}
+
+/// Whether the style block is processed by `fervid_css`,
+/// i.e. it is `lang="css"` and it is either scoped, a CSS Module or should be minified
 #[inline]
-pub fn should_transform_style_block(block: &SfcStyleBlock) -> bool {
-    block.is_scoped && block.lang == "css"
+pub fn should_transform_style_block(block: &SfcStyleBlock, minify: bool) -> bool {
+    block.lang == "css" && (block.is_scoped || block.module.is_some() || minify)
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::fervid_atom;
+    use swc_core::common::Span;
+
+    use super::*;
+    use crate::{test_utils::to_str, SetupBinding};
+    use swc_core::ecma::ast::Module;
+
+    #[test]
+    fn it_records_css_vars_usage() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings.extend([
+            SetupBinding(fervid_atom!("color"), BindingTypes::SetupRef),
+            SetupBinding(fervid_atom!("theme"), BindingTypes::Imported),
+            SetupBinding(fervid_atom!("unused"), BindingTypes::SetupConst),
+        ]);
+
+        let style_blocks = vec![SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: ".a { color: v-bind(color); background: v-bind('theme.bg') }".into(),
+            is_scoped: false,
+            module: None,
+            span: DUMMY_SP,
+        }];
+
+        record_css_vars_usage(&style_blocks, &mut bindings_helper);
+
+        assert_eq!(
+            Some(&BindingTypes::SetupRef),
+            bindings_helper.used_bindings.get(&fervid_atom!("color"))
+        );
+        assert_eq!(
+            Some(&BindingTypes::Imported),
+            bindings_helper.used_bindings.get(&fervid_atom!("theme"))
+        );
+        assert!(!bindings_helper
+            .used_bindings
+            .contains_key(&fervid_atom!("unused")));
+    }
+
+    #[test]
+    fn it_transforms_css_vars() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper
+            .setup_bindings
+            .push(SetupBinding(fervid_atom!("color"), BindingTypes::SetupRef));
+
+        let mut transform_result = TransformScriptsResult {
+            module: Box::new(Module {
+                span: DUMMY_SP,
+                body: vec![],
+                shebang: None,
+            }),
+            export_obj: ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            setup_fn: Some(Box::new(Function {
+                params: vec![],
+                decorators: vec![],
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                body: Some(BlockStmt {
+                    span: DUMMY_SP,
+                    ctxt: Default::default(),
+                    stmts: vec![],
+                }),
+                is_generator: false,
+                is_async: false,
+                type_params: None,
+                return_type: None,
+            })),
+        };
+
+        let mut style_blocks = vec![SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: ".a { color: v-bind(color); width: v-bind('size.width') }".into(),
+            is_scoped: false,
+            module: None,
+            span: DUMMY_SP,
+        }];
+
+        let mut errors = Vec::new();
+        transform_css_vars(
+            &mut style_blocks,
+            &mut transform_result,
+            &mut bindings_helper,
+            "abcd1234",
+            &mut errors,
+        );
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            ".a { color: var(--abcd1234-color); width: var(--abcd1234-size\\.width) }",
+            &*style_blocks[0].content
+        );
+        assert!(bindings_helper.vue_imports.contains(VueImports::UseCssVars));
+
+        let setup_body = transform_result.setup_fn.unwrap().body.unwrap();
+        let Some(Stmt::Expr(ExprStmt { expr, .. })) = setup_body.stmts.first() else {
+            panic!("Expected `useCssVars` call")
+        };
+        assert_eq!(
+            r#"_useCssVars(_ctx=>({"abcd1234-color":color.value,"abcd1234-size.width":_ctx.size.width}))"#,
+            to_str(&**expr)
+        );
+    }
+
+    #[test]
+    fn it_adds_setup_for_css_vars_in_options_api() {
+        let mut bindings_helper = BindingsHelper::default();
+        let mut transform_result = TransformScriptsResult {
+            module: Box::new(Module {
+                span: DUMMY_SP,
+                body: vec![],
+                shebang: None,
+            }),
+            export_obj: ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            setup_fn: None,
+        };
+
+        let mut style_blocks = vec![SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: ".a { color: v-bind(color) }".into(),
+            is_scoped: false,
+            module: None,
+            span: DUMMY_SP,
+        }];
+
+        let mut errors = Vec::new();
+        transform_css_vars(
+            &mut style_blocks,
+            &mut transform_result,
+            &mut bindings_helper,
+            "abcd1234",
+            &mut errors,
+        );
+
+        assert!(to_str(&transform_result.export_obj)
+            .starts_with(r#"{setup(){_useCssVars(_ctx=>({"abcd1234-color":_ctx.color}))"#));
+    }
+
+    #[test]
+    fn it_minifies_style_blocks_when_requested() {
+        let content = "/* comment */\n.a {\n  color: red;\n}\n";
+        let style_block = SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: content.into(),
+            is_scoped: false,
+            module: None,
+            span: Span::new(BytePos(1), BytePos(1 + content.len() as u32)),
+        };
+
+        // Not minified by default
+        let mut style_blocks = vec![style_block.clone()];
+        let mut errors = Vec::new();
+        let had_scoped_blocks = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            false,
+            &mut Vec::new(),
+            &mut errors,
+        );
+        assert!(!had_scoped_blocks);
+        assert_eq!(content, &*style_blocks[0].content);
+
+        // Minified
+        let mut style_blocks = vec![style_block];
+        let had_scoped_blocks = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            true,
+            &mut Vec::new(),
+            &mut errors,
+        );
+        assert!(!had_scoped_blocks);
+        assert!(errors.is_empty());
+        assert_eq!(".a{color:red}", &*style_blocks[0].content);
+    }
+
+    #[test]
+    fn it_records_slotted_styles() {
+        let style_block = |content: &str, is_scoped: bool| SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: content.into(),
+            is_scoped,
+            module: None,
+            span: DUMMY_SP,
+        };
+
+        let check = |style_blocks: Vec<SfcStyleBlock>| {
+            let mut bindings_helper = BindingsHelper::default();
+            record_slotted_styles(&style_blocks, &mut bindings_helper);
+            bindings_helper.no_slotted
+        };
+
+        assert!(!check(vec![]));
+        assert!(!check(vec![style_block(".a{color:red}", false)]));
+        assert!(check(vec![style_block(".a{color:red}", true)]));
+        assert!(!check(vec![
+            style_block(".a{color:red}", true),
+            style_block(":slotted(.b){color:red}", true)
+        ]));
+
+        // Only the scoped styles can style the slot content
+        assert!(check(vec![
+            style_block(".a{color:red}", true),
+            style_block(":slotted(.b){color:red}", false)
+        ]));
+    }
+
+    #[test]
+    fn it_transforms_css_modules() {
+        let content = ".foo { color: red }";
+        let style_block = SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: content.into(),
+            is_scoped: false,
+            module: Some(fervid_atom!("$style")),
+            span: Span::new(BytePos(1), BytePos(1 + content.len() as u32)),
+        };
+        let mut style_blocks = vec![style_block];
+
+        let mut bindings_helper = BindingsHelper::default();
+        record_css_modules(&style_blocks, &mut bindings_helper);
+        assert_eq!(vec![fervid_atom!("$style")], bindings_helper.css_modules);
+
+        let mut css_modules = Vec::new();
+        let mut errors = Vec::new();
+        transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            true,
+            &mut css_modules,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        let hash = create_css_module_hash("data-v-abcd1234", "$style");
+        assert_eq!(
+            format!(".foo_{}{{color:red}}", hash),
+            &*style_blocks[0].content
+        );
+
+        let mut transform_result = TransformScriptsResult {
+            module: Box::new(Module {
+                span: DUMMY_SP,
+                body: vec![],
+                shebang: None,
+            }),
+            export_obj: ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            setup_fn: None,
+        };
+        attach_css_modules(&mut transform_result, &style_blocks, css_modules);
+        assert_eq!(
+            format!(
+                r#"const __cssModules={{"$style":{{"foo":"foo_{}"}}}};"#,
+                hash
+            ),
+            to_str(&transform_result.module)
+        );
+        assert_eq!("{__cssModules}", to_str(&transform_result.export_obj));
+    }
+
+    #[test]
+    fn it_attaches_css_modules_of_other_languages() {
+        let content = ".foo { color: red }";
+        let mut style_blocks = vec![SfcStyleBlock {
+            lang: fervid_atom!("scss"),
+            content: content.into(),
+            is_scoped: false,
+            module: Some(fervid_atom!("$style")),
+            span: Span::new(BytePos(1), BytePos(1 + content.len() as u32)),
+        }];
+
+        let mut bindings_helper = BindingsHelper::default();
+        record_css_modules(&style_blocks, &mut bindings_helper);
+        assert_eq!(vec![fervid_atom!("$style")], bindings_helper.css_modules);
+
+        // Not compiled by fervid
+        let mut css_modules = Vec::new();
+        let mut errors = Vec::new();
+        transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            false,
+            &mut css_modules,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+        assert!(css_modules.is_empty());
+        assert_eq!(content, &*style_blocks[0].content);
+
+        // The integrations add the module to `__cssModules`
+        let mut transform_result = TransformScriptsResult {
+            module: Box::new(Module {
+                span: DUMMY_SP,
+                body: vec![],
+                shebang: None,
+            }),
+            export_obj: ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            setup_fn: None,
+        };
+        attach_css_modules(&mut transform_result, &style_blocks, css_modules);
+        assert_eq!("const __cssModules={};", to_str(&transform_result.module));
+        assert_eq!("{__cssModules}", to_str(&transform_result.export_obj));
+    }
 }
@@ -2,10 +2,14 @@
 
 mod ast_transform;
 mod collect_vars;
+mod constant_attrs;
 mod expr_transform;
+mod hoist;
 mod js_builtins;
 mod resolutions;
 mod v_on;
 mod utils;
 
 pub use ast_transform::transform_and_record_template;
+pub use expr_transform::BindingsHelperTransform;
+pub use hoist::hoist_static_nodes;
@@ -1,23 +1,38 @@
 use fervid_core::{
-    check_attribute_name, fervid_atom, is_from_default_slot, is_html_tag, AttributeOrBinding,
-    BindingTypes, BuiltinType, Conditional, ConditionalNodeSequence, ElementKind, ElementNode,
-    FervidAtom, Interpolation, IntoIdent, Node, PatchFlags, SfcTemplateBlock, StartingTag,
-    StrOrExpr, TemplateGenerationMode, VBindDirective, VSlotDirective, VUE_BUILTINS,
+    check_attribute_name, fervid_atom, get_vue_builtin, is_from_default_slot, is_html_tag,
+    is_mathml_tag, AttributeOrBinding, BindingTypes, BuiltinType, Conditional,
+    ConditionalNodeSequence, ConstantTypes, ElementKind, ElementNode, FervidAtom, Interpolation,
+    IntoIdent, Node, PatchFlags, PatchHints, SfcTemplateBlock, StartingTag, StrOrExpr,
+    TemplateGenerationMode, VBindDirective, VSlotDirective, WhitespaceMode,
 };
 use smallvec::SmallVec;
 use swc_core::{
-    common::DUMMY_SP,
-    ecma::ast::{Bool, Expr, Lit},
+    common::{Span, DUMMY_SP},
+    ecma::ast::{Bool, Expr, Lit, Number, Str},
 };
 
-use crate::{BindingsHelper, TemplateScope};
+use crate::{
+    error::{TemplateError, TemplateErrorKind, TransformError},
+    BindingsHelper, TemplateScope, VForKey,
+};
 
-use super::{collect_vars::collect_variables, expr_transform::BindingsHelperTransform};
+use super::{
+    collect_vars::collect_variables, constant_attrs::fold_constant_class_and_style,
+    expr_transform::BindingsHelperTransform, utils::find_side_effect,
+};
 
 pub struct TemplateVisitor<'s> {
     pub bindings_helper: &'s mut BindingsHelper,
     pub current_scope: u32,
     pub v_for_scope: bool,
+    /// Whether the visited subtree is cached by `v-once`
+    pub v_once_scope: bool,
+    /// Whether the visited subtree is inside `<pre>`, where the whitespace is kept as-is
+    pub pre_scope: bool,
+    /// Errors and warnings found in the template
+    pub errors: Vec<TemplateError>,
+    /// Nesting of the currently visited element
+    pub depth: u32,
 }
 
 /// Transforms the AST template by using information from [`BindingsHelper`].
@@ -29,18 +44,60 @@ pub struct TemplateVisitor<'s> {
 pub fn transform_and_record_template(
     template: &mut SfcTemplateBlock,
     bindings_helper: &mut BindingsHelper,
+    errors: &mut Vec<TransformError>,
 ) {
     // Optimize conditional sequences within template root
-    optimize_children(&mut template.roots, ElementKind::Element);
+    let mut root_errors = Vec::new();
+    // Comments are not rendered in production by default, so they are dropped from the roots as well.
+    // This keeps the only element as the root and thus the attrs fallthrough target.
+    optimize_children(
+        &mut template.roots,
+        ElementKind::Element,
+        bindings_helper.whitespace,
+        bindings_helper.keeps_comments(),
+        false,
+        &mut root_errors,
+    );
+    errors.extend(root_errors.into_iter().map(TransformError::from));
+
+    let non_comment_roots = template
+        .roots
+        .iter()
+        .filter(|r| !matches!(r, Node::Comment(_, _)))
+        .count();
+
+    // Nothing to render: an empty comment vnode is rendered in development, `null` in production
+    if non_comment_roots == 0 {
+        errors.push(TransformError::from(TemplateError {
+            span: template.span,
+            kind: TemplateErrorKind::EmptyTemplate,
+        }));
+
+        if bindings_helper.is_prod {
+            template.roots.clear();
+        } else if template.roots.is_empty() {
+            template
+                .roots
+                .push(Node::Comment(FervidAtom::default(), template.span));
+        }
+    }
 
     // Merge more than 1 child into a separate `<template>` element so that Fragment gets generated.
-    // #11: Do this only when all children are `TextNode`s.
+    // #11: Do not do this when all children are `TextNode`s, they are generated as a sequence.
     if template.roots.len() > 1
         && !template
             .roots
             .iter()
             .all(|r| matches!(r, Node::Text(_, _) | Node::Interpolation(_)))
     {
+        // Root Fragment created only because of the comments is marked for the runtime,
+        // which then still uses the only element for the attrs fallthrough
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::StableFragment;
+        if non_comment_roots == 1 && !bindings_helper.is_prod {
+            patch_hints.flags |= PatchFlags::DevRootFragment;
+        }
+
         let all_roots = std::mem::replace(&mut template.roots, Vec::with_capacity(1));
         let new_root = Node::Element(ElementNode {
             kind: ElementKind::Element,
@@ -51,65 +108,64 @@ pub fn transform_and_record_template(
             },
             children: all_roots,
             template_scope: 0,
-            patch_hints: Default::default(),
+            patch_hints,
             span: template.span,
         });
         template.roots.push(new_root);
     }
 
+    // Scope 0 is the root scope, it has no variables and is its own parent.
+    // Otherwise the first `v-for` or `v-slot` scope would be visible to the whole template
+    if bindings_helper.template_scopes.is_empty() {
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: SmallVec::new(),
+            parent: 0,
+        });
+    }
+
     let mut template_visitor = TemplateVisitor {
         bindings_helper,
         current_scope: 0,
         v_for_scope: false,
+        v_once_scope: false,
+        pre_scope: false,
+        errors: Vec::new(),
+        depth: 0,
     };
 
     for node in template.roots.iter_mut() {
         node.visit_mut_with(&mut template_visitor);
     }
+
+    errors.extend(
+        template_visitor
+            .errors
+            .into_iter()
+            .map(TransformError::from),
+    );
 }
 
-/// Optimizes the children by removing whitespace in between `ElementNode`s,
+/// Optimizes the children by removing the comments (unless `keep_comments`)
+/// and removing or condensing the whitespace (see [`WhitespaceMode`]) unless `in_pre`,
 /// as well as folding `v-if`/`v-else-if`/`v-else` sequences into a `ConditionalNodeSequence`
-fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
-    let children_len = children.len();
-
-    // Discard children mask, limited to 128 children. 0 means to preserve the node, 1 to discard
-    let mut discard_mask: u128 = 0;
-
-    // Filter out whitespace text nodes at the beginning and end of ElementNode
-    match children.first() {
-        Some(Node::Text(v, _)) if v.trim().is_empty() => {
-            discard_mask |= 1 << 0;
-        }
-        _ => {}
-    }
-    match children.last() {
-        Some(Node::Text(v, _)) if v.trim().is_empty() => {
-            discard_mask |= 1 << (children_len - 1);
-        }
-        _ => {}
+fn optimize_children(
+    children: &mut Vec<Node>,
+    element_kind: ElementKind,
+    whitespace: WhitespaceMode,
+    keep_comments: bool,
+    in_pre: bool,
+    errors: &mut Vec<TemplateError>,
+) {
+    // Dropped comments do not affect the whitespace around them, as if they were never there
+    if !keep_comments {
+        children.retain(|child| !matches!(child, Node::Comment(_, _)));
+        merge_adjacent_texts(children);
     }
 
-    // For removing the middle whitespace text nodes, we need sliding windows of three nodes
-    for (index, window) in children.windows(3).enumerate() {
-        match window {
-            [Node::Element(_) | Node::Comment(_, _), Node::Text(middle, _), Node::Element(_) | Node::Comment(_, _)]
-                if middle.trim().is_empty() =>
-            {
-                discard_mask |= 1 << (index + 1);
-            }
-            _ => {}
-        }
+    if !in_pre {
+        optimize_whitespace(children, element_kind, whitespace);
     }
 
-    // Retain based on discard_mask. If a discard bit at `index` is set to 1, the node will be dropped
-    let mut index = 0;
-    children.retain(|_| {
-        let should_retain = discard_mask & (1 << index) == 0;
-        index += 1;
-        should_retain
-    });
-
     // For components, reorder children so that named slots come first
     if matches!(element_kind, ElementKind::Component) && children.len() > 0 {
         children.sort_by(|a, b| {
@@ -125,6 +181,9 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
         let mut seq: Option<ConditionalNodeSequence> = None;
         let mut new_children = Vec::with_capacity(children.len());
 
+        // Keys of the branches are unique among all the sequences of the children
+        let mut branch_key = 0;
+
         /// Finishes the sequence. Pass `child` to also push the current child
         macro_rules! finish_seq {
             () => {
@@ -145,7 +204,12 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
                     unreachable!()
                 };
 
-                optimize_v_if_plus_v_for(child_element)
+                let branch = prepare_fragment_branch(
+                    optimize_v_if_plus_v_for(child_element, errors),
+                    branch_key,
+                );
+                branch_key += 1;
+                branch
             }};
         }
 
@@ -184,7 +248,11 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
             // Check for `v-else-if`
             if let Some(v_else_if) = directives.v_else_if.take() {
                 let Some(ref mut seq) = seq else {
-                    // This must be a warning, v-else-if without v-if
+                    // The element is rendered without the condition
+                    errors.push(TemplateError {
+                        span: child_element.span,
+                        kind: TemplateErrorKind::VElseIfWithoutVIf,
+                    });
                     finish_seq!(child);
                     continue;
                 };
@@ -199,7 +267,13 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
             // Check for `v-else`
             if let Some(_) = directives.v_else {
                 let Some(ref mut cond_seq) = seq else {
-                    // This must be a warning, v-else without v-if
+                    // The element is rendered without the condition and is not reported again
+                    // when the roots are optimized as the children of the root Fragment
+                    directives.v_else = None;
+                    errors.push(TemplateError {
+                        span: child_element.span,
+                        kind: TemplateErrorKind::VElseWithoutVIf,
+                    });
                     finish_seq!(child);
                     continue;
                 };
@@ -219,11 +293,231 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
 
         *children = new_children;
     }
+
+    merge_adjacent_texts(children);
+}
+
+/// Merges adjacent `Node::Text`s, e.g. the ones left after removing the nodes in between.
+/// Texts and interpolations are later generated as a single compound expression,
+/// e.g. `hello {{ name }}!` -> `"hello " + _toDisplayString(name) + "!"`,
+/// and merging the texts beforehand avoids concatenating string literals.
+fn merge_adjacent_texts(children: &mut Vec<Node>) {
+    if children.len() < 2 {
+        return;
+    }
+
+    let mut merged: Vec<Node> = Vec::with_capacity(children.len());
+    for child in children.drain(..) {
+        match (merged.last_mut(), child) {
+            (Some(Node::Text(prev_text, prev_span)), Node::Text(text, span)) => {
+                let mut joined = String::with_capacity(prev_text.len() + text.len());
+                joined.push_str(prev_text);
+                joined.push_str(&text);
+                *prev_text = FervidAtom::from(joined);
+                *prev_span = prev_span.to(span);
+            }
+            (_, child) => merged.push(child),
+        }
+    }
+
+    *children = merged;
+}
+
+/// Checks the children of `<Transition>`, which must render exactly one element or component.
+/// Multiple children, `v-for` and conditional branches with multiple children are not allowed.
+/// https://github.com/vuejs/core/blob/main/packages/compiler-dom/src/transforms/Transition.ts
+fn has_multiple_children(children: &[Node]) -> bool {
+    let mut meaningful_children = children.iter().filter(|child| match child {
+        Node::Comment(_, _) => false,
+        Node::Text(text, _) => !text.trim().is_empty(),
+        _ => true,
+    });
+
+    let (Some(child), None) = (meaningful_children.next(), meaningful_children.next()) else {
+        return true;
+    };
+
+    let is_multiple_branch = |node: &ElementNode| {
+        if node.starting_tag.tag_name == "template" {
+            has_multiple_children(&node.children)
+        } else {
+            has_v_for(node)
+        }
+    };
+
+    match child {
+        Node::Element(element) => has_v_for(element),
+        Node::ConditionalSeq(seq) => {
+            is_multiple_branch(&seq.if_node.node)
+                || seq
+                    .else_if_nodes
+                    .iter()
+                    .any(|else_if_node| is_multiple_branch(&else_if_node.node))
+                || seq
+                    .else_node
+                    .as_ref()
+                    .is_some_and(|else_node| is_multiple_branch(else_node))
+        }
+        _ => false,
+    }
+}
+
+#[inline]
+fn has_v_for(element_node: &ElementNode) -> bool {
+    element_node
+        .starting_tag
+        .directives
+        .as_ref()
+        .is_some_and(|directives| directives.v_for.is_some())
+}
+
+fn has_v_slot(element_node: &ElementNode) -> bool {
+    element_node
+        .starting_tag
+        .directives
+        .as_ref()
+        .is_some_and(|directives| directives.v_slot.is_some())
+}
+
+/// Removes the whitespace-only nodes or makes them a single space
+/// and condenses the whitespace inside the texts, depending on [`WhitespaceMode`]
+fn optimize_whitespace(
+    children: &mut Vec<Node>,
+    element_kind: ElementKind,
+    whitespace: WhitespaceMode,
+) {
+    let should_condense = matches!(whitespace, WhitespaceMode::Condense);
+
+    // Whitespace alone does not create an implicit default slot next to `<template v-slot>`
+    let has_template_slots = matches!(element_kind, ElementKind::Component)
+        && children.iter().any(|child| {
+            matches!(child, Node::Element(child_element)
+                if child_element.starting_tag.tag_name == "template" && has_v_slot(child_element))
+        });
+
+    // Whitespace-only text nodes to drop, decided using their siblings
+    let mut discarded = vec![false; children.len()];
+    for (index, child) in children.iter().enumerate() {
+        let Node::Text(text, _) = child else {
+            continue;
+        };
+        if !is_all_whitespace(text) {
+            continue;
+        }
+
+        let prev = index
+            .checked_sub(1)
+            .and_then(|prev_index| children.get(prev_index));
+        let next = children.get(index + 1);
+        discarded[index] = match (prev, next) {
+            (None, _) | (_, None) => true,
+            _ if has_template_slots => true,
+            _ if !should_condense => false,
+            (Some(Node::Comment(_, _)), Some(Node::Comment(_, _) | Node::Element(_)))
+            | (Some(Node::Element(_)), Some(Node::Comment(_, _))) => true,
+            (Some(Node::Element(_)), Some(Node::Element(_))) => text.contains('\n'),
+            _ => false,
+        };
+    }
+
+    // The kept whitespace-only nodes become a single space
+    for child in children.iter_mut() {
+        let Node::Text(text, _) = child else {
+            continue;
+        };
+
+        if is_all_whitespace(text) {
+            *text = fervid_atom!(" ");
+        } else if should_condense {
+            *text = condense_whitespace(text);
+        }
+    }
+
+    let mut index = 0;
+    children.retain(|_| {
+        let should_retain = !discarded[index];
+        index += 1;
+        should_retain
+    });
+}
+
+/// Whether the text only consists of the HTML whitespace. Unlike `&nbsp;`, it is not rendered as-is
+#[inline]
+fn is_all_whitespace(text: &str) -> bool {
+    text.bytes().all(|b| b.is_ascii_whitespace())
+}
+
+/// Collapses every run of the whitespace to a single space, e.g. `"a \n  b"` to `"a b"`
+fn condense_whitespace(text: &str) -> FervidAtom {
+    let mut condensed = String::with_capacity(text.len());
+    let mut prev_is_whitespace = false;
+    for c in text.chars() {
+        let is_whitespace = c.is_ascii_whitespace();
+        if !is_whitespace {
+            condensed.push(c);
+        } else if !prev_is_whitespace {
+            condensed.push(' ');
+        }
+        prev_is_whitespace = is_whitespace;
+    }
+
+    FervidAtom::from(condensed)
+}
+
+/// Whether a child of `<TransitionGroup>` has a `key`.
+/// `<slot>`s are not checked, because they render the nodes from the outside.
+fn is_keyed(element_node: &ElementNode) -> bool {
+    element_node.starting_tag.tag_name == "slot"
+        || element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .any(|attr| check_attribute_name(attr, "key"))
+}
+
+/// `<template v-if>` with multiple children is rendered as a keyed Fragment branch:
+/// `(_openBlock(), _createElementBlock(_Fragment, { key: 0 }, [/*...*/], 64 /* STABLE_FRAGMENT */))`
+fn prepare_fragment_branch(mut element_node: ElementNode, branch_key: usize) -> ElementNode {
+    // `<template v-for>` creates its own Fragment
+    if element_node.starting_tag.tag_name != "template" || has_v_for(&element_node) {
+        return element_node;
+    }
+
+    element_node.patch_hints.flags |= PatchFlags::StableFragment;
+
+    // User-provided `key` is used as-is
+    let has_key = element_node
+        .starting_tag
+        .attributes
+        .iter()
+        .any(|attr| check_attribute_name(attr, "key"));
+    if !has_key {
+        element_node
+            .starting_tag
+            .attributes
+            .push(AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(fervid_atom!("key"))),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: branch_key as f64,
+                    raw: None,
+                }))),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            }));
+    }
+
+    element_node
 }
 
 // Optimize combined usage of conditional directives and `v-for`
 // https://github.com/vuejs/core/blob/438a74aad840183286fbdb488178510f37218a73/packages/compiler-core/src/transforms/vIf.ts#L260
-fn optimize_v_if_plus_v_for(mut parent: ElementNode) -> ElementNode {
+fn optimize_v_if_plus_v_for(
+    mut parent: ElementNode,
+    errors: &mut Vec<TemplateError>,
+) -> ElementNode {
     // Check that work is needed
     // This must be a `<template>` element with exactly one Element child
     if parent.children.len() != 1 || parent.starting_tag.tag_name != "template" {
@@ -262,6 +556,20 @@ fn optimize_v_if_plus_v_for(mut parent: ElementNode) -> ElementNode {
         child_directives.v_for = parent_directives.v_for.take();
     }
 
+    // The child is rendered instead of the `<template>`, so it takes the `key`, unless it has its own
+    let child_has_key = child
+        .starting_tag
+        .attributes
+        .iter()
+        .any(|attr| check_attribute_name(attr, "key"));
+    for attr in std::mem::take(&mut parent.starting_tag.attributes) {
+        if !check_attribute_name(&attr, "key") {
+            errors.push(ignored_template_attribute(&attr));
+        } else if !child_has_key {
+            child.starting_tag.attributes.push(attr);
+        }
+    }
+
     // Take the child and return it instead
     let Some(Node::Element(child)) = parent.children.pop() else {
         unreachable!()
@@ -270,6 +578,79 @@ fn optimize_v_if_plus_v_for(mut parent: ElementNode) -> ElementNode {
     child
 }
 
+/// Warning for an attribute of the `<template>` which is not rendered as an element
+fn ignored_template_attribute(attr: &AttributeOrBinding) -> TemplateError {
+    let span = match attr {
+        AttributeOrBinding::RegularAttribute { span, .. } => *span,
+        AttributeOrBinding::VBind(v_bind) => v_bind.span,
+        AttributeOrBinding::VOn(v_on) => v_on.span,
+    };
+
+    TemplateError {
+        span,
+        kind: TemplateErrorKind::IgnoredTemplateAttribute,
+    }
+}
+
+/// State of an element kept while its children are visited
+struct ElementExit {
+    element_kind: ElementKind,
+    parent_scope: u32,
+    old_v_for_scope: bool,
+    old_v_once_scope: bool,
+    old_pre_scope: bool,
+    has_dynamic_keys: bool,
+    has_hydration_event_binding: bool,
+    has_ref: bool,
+    has_runtime_directives: bool,
+    has_vnode_hook: bool,
+    ref_key: Option<FervidAtom>,
+    should_use_block: bool,
+}
+
+/// An element on the stack of [`TemplateVisitor::visit_element_node`]
+struct ElementFrame {
+    exit: ElementExit,
+    /// Children taken out of the element
+    children: Vec<Node>,
+    /// Index of the next child to visit
+    next_child: usize,
+    /// Next branch of the `ConditionalSeq` child, see [`conditional_branch`]
+    next_branch: usize,
+    location: ElementLocation,
+}
+
+/// Where the element is in the children of the previous frame
+#[derive(Clone, Copy)]
+enum ElementLocation {
+    Root,
+    Child(usize),
+    Branch(usize, usize),
+}
+
+/// Gets the condition and the element of a branch: `v-if` is 0, then `v-else-if`s, then `v-else`
+fn conditional_branch(
+    conditional_seq: &mut ConditionalNodeSequence,
+    branch: usize,
+) -> Option<(Option<&mut Expr>, &mut ElementNode)> {
+    let else_if_count = conditional_seq.else_if_nodes.len();
+
+    if branch == 0 {
+        let if_node = &mut *conditional_seq.if_node;
+        Some((Some(&mut if_node.condition), &mut if_node.node))
+    } else if branch <= else_if_count {
+        let else_if_node = &mut conditional_seq.else_if_nodes[branch - 1];
+        Some((Some(&mut else_if_node.condition), &mut else_if_node.node))
+    } else if branch == else_if_count + 1 {
+        conditional_seq
+            .else_node
+            .as_deref_mut()
+            .map(|else_node| (None, else_node))
+    } else {
+        None
+    }
+}
+
 trait Visitor {
     fn visit_element_node(&mut self, element_node: &mut ElementNode);
     fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence);
@@ -281,13 +662,184 @@ trait VisitMut {
 }
 
 impl<'a> Visitor for TemplateVisitor<'_> {
+    /// Visits the element and its subtree.
+    ///
+    /// The subtree is traversed with an explicit stack instead of recursion,
+    /// so that the deeply nested templates do not overflow the call stack.
+    /// Children are taken out of their element while they are visited and put back afterwards.
     fn visit_element_node(&mut self, element_node: &mut ElementNode) {
+        let Some(exit) = self.enter_element(element_node) else {
+            return;
+        };
+        let mut stack = vec![ElementFrame {
+            exit,
+            children: std::mem::take(&mut element_node.children),
+            next_child: 0,
+            next_branch: 0,
+            location: ElementLocation::Root,
+        }];
+
+        while let Some(frame) = stack.last_mut() {
+            let child_idx = frame.next_child;
+            let Some(child) = frame.children.get_mut(child_idx) else {
+                // All the children were visited, put them back and finish the element
+                let Some(frame) = stack.pop() else {
+                    unreachable!()
+                };
+                let element = match frame.location {
+                    ElementLocation::Root => Some(&mut *element_node),
+                    ElementLocation::Child(idx) => match stack.last_mut() {
+                        Some(parent) => match parent.children.get_mut(idx) {
+                            Some(Node::Element(element)) => Some(element),
+                            _ => None,
+                        },
+                        None => None,
+                    },
+                    ElementLocation::Branch(idx, branch) => match stack.last_mut() {
+                        Some(parent) => match parent.children.get_mut(idx) {
+                            Some(Node::ConditionalSeq(conditional_seq)) => {
+                                conditional_branch(conditional_seq, branch).map(|(_, node)| node)
+                            }
+                            _ => None,
+                        },
+                        None => None,
+                    },
+                };
+                let Some(element) = element else {
+                    unreachable!("Visited element is not found in its parent")
+                };
+
+                element.children = frame.children;
+                self.exit_element(element, frame.exit);
+                continue;
+            };
+
+            // Enter the next child element, if any
+            let entered = match child {
+                Node::Element(child_element) => {
+                    frame.next_child += 1;
+                    self.enter_element(child_element).map(|exit| {
+                        let children = std::mem::take(&mut child_element.children);
+                        (exit, children, ElementLocation::Child(child_idx))
+                    })
+                }
+
+                // Branches are visited one by one.
+                // Conditions are transformed in the scope of the parent, because in VDOM
+                // the condition wraps around the node (`condition ? if_node : else_node`).
+                Node::ConditionalSeq(conditional_seq) => {
+                    let branch = frame.next_branch;
+                    match conditional_branch(conditional_seq, branch) {
+                        Some((condition, branch_node)) => {
+                            frame.next_branch += 1;
+                            if let Some(condition) = condition {
+                                self.bindings_helper
+                                    .transform_expr(condition, self.current_scope);
+                            }
+
+                            self.enter_element(branch_node).map(|exit| {
+                                let children = std::mem::take(&mut branch_node.children);
+                                (exit, children, ElementLocation::Branch(child_idx, branch))
+                            })
+                        }
+                        None => {
+                            frame.next_child += 1;
+                            frame.next_branch = 0;
+                            None
+                        }
+                    }
+                }
+
+                Node::Interpolation(interpolation) => {
+                    frame.next_child += 1;
+                    self.visit_interpolation(interpolation);
+                    None
+                }
+
+                Node::Text(_, _) | Node::Comment(_, _) => {
+                    frame.next_child += 1;
+                    None
+                }
+            };
+
+            if let Some((exit, children, location)) = entered {
+                stack.push(ElementFrame {
+                    exit,
+                    children,
+                    next_child: 0,
+                    next_branch: 0,
+                    location,
+                });
+            }
+        }
+    }
+
+    fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence) {
+        // In this function, conditions are transformed first
+        // without updating the template scope and collecting its variables.
+        // I believe this is a correct way of doing it, because in VDOM the condition
+        // wraps around the node (`condition ? if_node : else_node`).
+        // However, I am not too sure about the `v-if` & `v-slot` combined usage.
+
+        self.bindings_helper
+            .transform_expr(&mut conditional_node.if_node.condition, self.current_scope);
+        self.visit_element_node(&mut conditional_node.if_node.node);
+
+        for else_if_node in conditional_node.else_if_nodes.iter_mut() {
+            self.bindings_helper
+                .transform_expr(&mut else_if_node.condition, self.current_scope);
+            self.visit_element_node(&mut else_if_node.node);
+        }
+
+        if let Some(ref mut else_node) = conditional_node.else_node {
+            self.visit_element_node(else_node);
+        }
+    }
+
+    fn visit_interpolation(&mut self, interpolation: &mut Interpolation) {
+        interpolation.template_scope = self.current_scope;
+        self.report_side_effect(&interpolation.value, interpolation.span);
+
+        let has_js = self
+            .bindings_helper
+            .transform_expr(&mut interpolation.value, self.current_scope);
+
+        interpolation.patch_flag = has_js;
+    }
+}
+
+impl TemplateVisitor<'_> {
+    /// Transforms the element before its children are visited.
+    /// Returns `None` when the children must not be visited, e.g. for `v-pre`.
+    fn enter_element(&mut self, element_node: &mut ElementNode) -> Option<ElementExit> {
+        if self.is_too_deep(element_node) {
+            return None;
+        }
+        self.depth += 1;
+
+        // `v-pre` subtree is rendered as is, without resolving the components or the bindings
+        let has_v_pre = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .is_some_and(|directives| directives.v_pre.is_some());
+        if has_v_pre {
+            self.transform_v_pre_subtree(element_node);
+            self.depth -= 1;
+            return None;
+        }
+
         let parent_scope = self.current_scope;
         let mut scope_to_use = parent_scope;
 
         // Mark the node with a correct type (element, component or built-in)
         let element_kind = self.recognize_element_kind(&element_node.starting_tag);
         let is_component = matches!(element_kind, ElementKind::Component);
+        let is_componentlike = is_component
+            || matches!(
+                element_kind,
+                ElementKind::Builtin(builtin) if !matches!(builtin, BuiltinType::Slot)
+            );
         element_node.kind = element_kind;
 
         if is_component {
@@ -297,6 +849,21 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         // `v-for` has special behavior with `ref`
         let old_v_for_scope = self.v_for_scope;
 
+        // The whitespace inside `<pre>` is significant
+        let old_pre_scope = self.pre_scope;
+        self.pre_scope |= element_node.starting_tag.tag_name == "pre";
+
+        // `v-once` caches the whole subtree, so the nested `v-once` are redundant
+        let old_v_once_scope = self.v_once_scope;
+        if let Some(ref mut directives) = element_node.starting_tag.directives {
+            if directives.v_once.is_some() {
+                if self.v_once_scope {
+                    directives.v_once = None;
+                }
+                self.v_once_scope = true;
+            }
+        }
+
         // Patch hints
         // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L406
         let has_children = !element_node.children.is_empty();
@@ -310,34 +877,31 @@ impl<'a> Visitor for TemplateVisitor<'_> {
 
         // Check if there is a scoping directive.
         // Find a `v-for` or `v-slot` directive when in ElementNode
-        // and collect their variables into the new template scope
+        // and collect their variables into the new template scope.
+        // `v-for` variables are visible to the element itself (e.g. `:key="item.id"`) and its children,
+        // while `v-slot` variables are only visible to the children.
+        let mut children_scope = parent_scope;
         if let Some(ref mut directives) = element_node.starting_tag.directives {
-            let v_for = directives.v_for.as_mut();
-            let v_slot = directives.v_slot.as_mut();
+            // Collect `v-for` bindings
+            if let Some(v_for) = directives.v_for.as_mut() {
+                self.v_for_scope = true;
+
+                // Transform the iterable, it cannot reference the `v-for` variables
+                let is_dynamic = self
+                    .bindings_helper
+                    .transform_expr(&mut v_for.iterable, parent_scope);
 
-            // Create a new scope
-            if v_for.is_some() || v_slot.is_some() {
                 // New scope will have ID equal to length
                 scope_to_use = self.bindings_helper.template_scopes.len() as u32;
                 self.bindings_helper.template_scopes.push(TemplateScope {
                     variables: SmallVec::new(),
                     parent: parent_scope,
                 });
-            }
-
-            // Collect `v-for` bindings
-            if let Some(v_for) = v_for {
-                self.v_for_scope = true;
 
                 // Get the iterator variable and collect its variables
                 let mut scope = &mut self.bindings_helper.template_scopes[scope_to_use as usize];
                 collect_variables(&v_for.itervar, &mut scope);
 
-                // Transform the iterable
-                let is_dynamic = self
-                    .bindings_helper
-                    .transform_expr(&mut v_for.iterable, scope_to_use);
-
                 // Add patch flags
                 if !is_dynamic {
                     // This is `64 /* STABLE_FRAGMENT */`
@@ -358,34 +922,70 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                     };
                 }
             }
+            children_scope = scope_to_use;
 
             // Collect `v-slot` bindings
             if let Some(VSlotDirective {
                 slot_name, value, ..
-            }) = v_slot
+            }) = directives.v_slot.as_mut()
             {
+                // Transform `v-slot` argument if it is dynamic,
+                // e.g. `<template v-for="item in items" #[item.name]>`
+                if let Some(StrOrExpr::Expr(expr)) = slot_name {
+                    self.bindings_helper.transform_expr(expr, scope_to_use);
+                }
+
+                // New scope will have ID equal to length
+                children_scope = self.bindings_helper.template_scopes.len() as u32;
+                self.bindings_helper.template_scopes.push(TemplateScope {
+                    variables: SmallVec::new(),
+                    parent: scope_to_use,
+                });
+
                 if let Some(v_slot_value) = value {
                     let mut scope =
-                        &mut self.bindings_helper.template_scopes[scope_to_use as usize];
+                        &mut self.bindings_helper.template_scopes[children_scope as usize];
                     collect_variables(v_slot_value, &mut scope);
                 }
+            }
+        }
 
-                // Transform `v-slot` argument if it is dynamic
-                if let Some(StrOrExpr::Expr(expr)) = slot_name {
-                    self.bindings_helper.transform_expr(expr, scope_to_use);
+        // `<template v-for>` renders a Fragment for each item, which only takes the `key`:
+        // `(_openBlock(), _createElementBlock(_Fragment, { key: item.id }, [/*...*/], 64 /* STABLE_FRAGMENT */))`
+        let is_template_v_for = element_node.starting_tag.tag_name == "template"
+            && element_node
+                .starting_tag
+                .directives
+                .as_ref()
+                .is_some_and(|directives| {
+                    directives.v_for.is_some() && directives.v_slot.is_none()
+                });
+        if is_template_v_for {
+            let errors = &mut self.errors;
+            element_node.starting_tag.attributes.retain(|attr| {
+                let is_key = check_attribute_name(attr, "key");
+                if !is_key {
+                    errors.push(ignored_template_attribute(attr));
                 }
-            }
+                is_key
+            });
+            element_node.patch_hints.flags |= PatchFlags::StableFragment;
         }
 
         // Update the element's scope and the Visitor's current scope
-        element_node.template_scope = scope_to_use;
-        self.current_scope = scope_to_use;
+        element_node.template_scope = children_scope;
+        self.current_scope = children_scope;
 
         // TODO Refactor the directives transformation logic
         // and maybe the Visitor as well
 
-        // Transform the VBind and VOn attributes
+        // `:class="'a b'"` is the same as `class="a b"`, which needs no patch flags and can be hoisted
+        fold_constant_class_and_style(&mut element_node.starting_tag.attributes);
+
+        // Transform the VBind and VOn attributes.
+        // The element is as constant as the least constant of its attributes
         let patch_hints = &mut element_node.patch_hints;
+        let mut constant_type = ConstantTypes::CanStringify;
         for attr in element_node.starting_tag.attributes.iter_mut() {
             match attr {
                 // The logic for the patch flags:
@@ -395,9 +995,17 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 //    If there is, check if it is a component
                 // 2. Check if
                 AttributeOrBinding::VBind(v_bind) => {
-                    let has_bindings = self
+                    self.report_side_effect(&v_bind.value, v_bind.span);
+
+                    let value_constant_type = self
                         .bindings_helper
-                        .transform_expr(&mut v_bind.value, scope_to_use);
+                        .transform_expr_constant(&mut v_bind.value, scope_to_use);
+                    let has_bindings = value_constant_type == ConstantTypes::NotConstant;
+
+                    // Only the static attributes are rendered to a string
+                    constant_type = constant_type
+                        .min(value_constant_type)
+                        .min(ConstantTypes::CanHoist);
 
                     // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L676
                     // Force hydration for v-bind with .prop modifier
@@ -411,6 +1019,8 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                         }
 
                         // This is dynamic
+                        constant_type = ConstantTypes::NotConstant;
+
                         // From docs: [FULL_PROPS is] exclusive with CLASS, STYLE and PROPS.
                         patch_hints.flags &=
                             !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
@@ -435,6 +1045,11 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                         continue;
                     }
 
+                    // Template refs are set on the component instance
+                    if argument == "ref" {
+                        constant_type = ConstantTypes::NotConstant;
+                    }
+
                     // For `ref_for`
                     if self.v_for_scope && argument == "ref" {
                         has_ref = true;
@@ -447,8 +1062,9 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                     }
 
                     // Adding `class` and `style` bindings depends on `is_component`
-                    // They are added to PROPS for the components.
-                    if is_component {
+                    // They are added to PROPS for the components and component-like builtins,
+                    // e.g. `<Teleport :to="target">` is `8 /* PROPS */, ["to"]`.
+                    if is_componentlike {
                         patch_hints.flags |= PatchFlags::Props;
                         patch_hints.props.push(argument.to_owned());
                         continue;
@@ -465,6 +1081,9 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 }
 
                 AttributeOrBinding::VOn(ref mut v_on) => {
+                    // Handlers are cached or created on each render
+                    constant_type = ConstantTypes::NotConstant;
+
                     // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L589C54-L589C71
                     // inline before-update hooks need to force block so that it is invoked
                     // before children
@@ -500,9 +1119,14 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 }
 
                 // Transform the regular `ref` in `inline` mode
-                AttributeOrBinding::RegularAttribute { name, value, span } if name == "ref" => {
+                AttributeOrBinding::RegularAttribute {
+                    name, value, span, ..
+                } if name == "ref" => {
                     has_ref = true;
 
+                    // Template refs are set on the component instance
+                    constant_type = ConstantTypes::NotConstant;
+
                     // Get the binding type regardless of template generation mode to mark the ref as "used".
                     // This is the importUsageCheck behavior of the official compiler
                     let binding_type = if value.is_empty() {
@@ -550,6 +1174,40 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 _ => {}
             }
         }
+        patch_hints.constant_type = constant_type;
+
+        // Record the `v-for` with its transformed `key`
+        if let Some(v_for) = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_for.as_ref())
+        {
+            let key = element_node
+                .starting_tag
+                .attributes
+                .iter()
+                .find_map(|attr| match attr {
+                    AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str(argument)),
+                        value,
+                        ..
+                    }) if argument == "key" => Some(value.to_owned()),
+                    AttributeOrBinding::RegularAttribute {
+                        name, value, span, ..
+                    } if name == "key" => Some(Box::new(Expr::Lit(Lit::Str(Str {
+                        span: *span,
+                        value: value.to_owned(),
+                        raw: None,
+                    })))),
+                    _ => None,
+                });
+
+            self.bindings_helper.v_for_keys.push(VForKey {
+                span: v_for.span,
+                key,
+            });
+        }
 
         // Transform the directives
         if let Some(ref mut directives) = element_node.starting_tag.directives {
@@ -566,16 +1224,55 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             maybe_transform!(v_show);
             maybe_transform!(v_text);
 
-            for v_model in directives.v_model.iter_mut() {
-                self.bindings_helper
-                    .transform_v_model(v_model, scope_to_use, patch_hints);
+            // `vShow` is applied by `withDirectives` as well
+            if directives.v_show.is_some() {
+                has_runtime_directives = true;
             }
 
-            // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L700
-            // custom dirs may use beforeUpdate so they need to force blocks
-            // to ensure before-update gets called before children update
-            if !directives.custom.is_empty() {
-                has_runtime_directives = true;
+            for v_model in directives.v_model.iter_mut() {
+                // Assigning to props or read-only bindings fails at runtime,
+                // the error is reported but the code is still generated
+                if let Expr::Ident(ref ident) = *v_model.value {
+                    let binding_type = self
+                        .bindings_helper
+                        .get_var_binding_type(scope_to_use, &ident.sym);
+
+                    let error_kind = match binding_type {
+                        BindingTypes::Props | BindingTypes::PropsAliased => {
+                            Some(TemplateErrorKind::VModelOnProps)
+                        }
+                        BindingTypes::SetupConst
+                        | BindingTypes::SetupReactiveConst
+                        | BindingTypes::LiteralConst
+                        | BindingTypes::Imported
+                        | BindingTypes::Component => Some(TemplateErrorKind::VModelOnConst),
+                        _ => None,
+                    };
+
+                    if let Some(kind) = error_kind {
+                        self.errors.push(TemplateError {
+                            span: v_model.span,
+                            kind,
+                        });
+                    }
+                }
+
+                let is_assignable =
+                    self.bindings_helper
+                        .transform_v_model(v_model, scope_to_use, patch_hints);
+                if !is_assignable {
+                    self.errors.push(TemplateError {
+                        span: v_model.span,
+                        kind: TemplateErrorKind::VModelMalformedExpression,
+                    });
+                }
+            }
+
+            // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L700
+            // custom dirs may use beforeUpdate so they need to force blocks
+            // to ensure before-update gets called before children update
+            if !directives.custom.is_empty() {
+                has_runtime_directives = true;
 
                 if has_children {
                     should_use_block = true;
@@ -597,7 +1294,143 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         }
 
         // Merge conditional nodes and clean up whitespace
-        optimize_children(&mut element_node.children, element_kind);
+        optimize_children(
+            &mut element_node.children,
+            element_kind,
+            self.bindings_helper.whitespace,
+            self.bindings_helper.keeps_comments(),
+            self.pre_scope,
+            &mut self.errors,
+        );
+
+        // Validate the children of transitions and `<KeepAlive>`,
+        // they only work with a single root node and not with a fragment
+        match element_kind {
+            ElementKind::Builtin(BuiltinType::Transition)
+                if !element_node.children.is_empty()
+                    && has_multiple_children(&element_node.children) =>
+            {
+                self.errors.push(TemplateError {
+                    span: element_node.span,
+                    kind: TemplateErrorKind::TransitionInvalidChildren,
+                });
+            }
+
+            ElementKind::Builtin(BuiltinType::KeepAlive)
+                if !element_node.children.is_empty()
+                    && has_multiple_children(&element_node.children) =>
+            {
+                self.errors.push(TemplateError {
+                    span: element_node.span,
+                    kind: TemplateErrorKind::KeepAliveInvalidChildren,
+                });
+            }
+
+            ElementKind::Builtin(BuiltinType::TransitionGroup) => {
+                for child in element_node.children.iter() {
+                    let mut report_if_unkeyed = |node: &ElementNode| {
+                        if !is_keyed(node) {
+                            self.errors.push(TemplateError {
+                                span: node.span,
+                                kind: TemplateErrorKind::TransitionGroupUnkeyedChild,
+                            });
+                        }
+                    };
+
+                    match child {
+                        Node::Element(child_element) => report_if_unkeyed(child_element),
+                        Node::ConditionalSeq(seq) => {
+                            report_if_unkeyed(&seq.if_node.node);
+                            for else_if_node in seq.else_if_nodes.iter() {
+                                report_if_unkeyed(&else_if_node.node);
+                            }
+                            if let Some(ref else_node) = seq.else_node {
+                                report_if_unkeyed(else_node);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            _ => {}
+        }
+
+        // `v-slot` on the component itself cannot be mixed with `<template v-slot>` children
+        if is_componentlike && has_v_slot(element_node) {
+            for child in element_node.children.iter() {
+                let Node::Element(child_element) = child else {
+                    continue;
+                };
+
+                if child_element.starting_tag.tag_name == "template" && has_v_slot(child_element) {
+                    self.errors.push(TemplateError {
+                        span: child_element.span,
+                        kind: TemplateErrorKind::VSlotMixedSlotUsage,
+                    });
+                }
+            }
+        }
+
+        // `<template v-slot v-for>` needs `createSlots`, which codegen does not support yet
+        if is_componentlike {
+            for child in element_node.children.iter() {
+                let Node::Element(child_element) = child else {
+                    continue;
+                };
+
+                let is_dynamic_slot =
+                    child_element.starting_tag.tag_name == "template"
+                        && child_element.starting_tag.directives.as_ref().is_some_and(
+                            |directives| directives.v_slot.is_some() && directives.v_for.is_some(),
+                        );
+
+                if is_dynamic_slot {
+                    self.errors.push(TemplateError {
+                        span: child_element.span,
+                        kind: TemplateErrorKind::DynamicSlotUnsupported,
+                    });
+                }
+            }
+        }
+
+        // `<slot name="...">` must be declared when `defineSlots` has a type
+        if matches!(element_kind, ElementKind::Builtin(BuiltinType::Slot)) {
+            self.check_slot_is_declared(element_node);
+        }
+
+        Some(ElementExit {
+            element_kind,
+            parent_scope,
+            old_v_for_scope,
+            old_v_once_scope,
+            old_pre_scope,
+            has_dynamic_keys,
+            has_hydration_event_binding,
+            has_ref,
+            has_runtime_directives,
+            has_vnode_hook,
+            ref_key,
+            should_use_block,
+        })
+    }
+
+    /// Finishes the element after all its children were visited
+    fn exit_element(&mut self, element_node: &mut ElementNode, exit: ElementExit) {
+        let ElementExit {
+            element_kind,
+            parent_scope,
+            old_v_for_scope,
+            old_v_once_scope,
+            old_pre_scope,
+            has_dynamic_keys,
+            has_hydration_event_binding,
+            has_ref,
+            has_runtime_directives,
+            has_vnode_hook,
+            ref_key,
+            should_use_block,
+        } = exit;
 
         // Patch flag for HTML elements which only contain interpolation and text,
         // e.g. `<p>{{ msg }}</p>`.
@@ -606,10 +1439,7 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             matches!(element_kind, ElementKind::Element) && !element_node.children.is_empty();
         let mut has_dynamic_interpolation = false;
 
-        // Recursively visit children
-        for child in element_node.children.iter_mut() {
-            child.visit_mut_with(self);
-
+        for child in element_node.children.iter() {
             match child {
                 // When Elements are present, TEXT patch flag does not apply
                 Node::Element(_) | Node::ConditionalSeq(_) => {
@@ -621,7 +1451,12 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                     has_dynamic_interpolation |= interpolation.patch_flag;
                 }
 
-                Node::Text(_, _) | Node::Comment(_, _) => {}
+                // Comments are separate vnodes, so the children can't be set as a text
+                Node::Comment(_, _) => {
+                    is_children_text_only = false;
+                }
+
+                Node::Text(_, _) => {}
             }
         }
 
@@ -649,10 +1484,15 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 .push(AttributeOrBinding::RegularAttribute {
                     name: fervid_atom!("ref_key"),
                     value: ref_key,
+                    raw_value: None,
                     span: DUMMY_SP,
                 });
         }
         self.v_for_scope = old_v_for_scope;
+        self.v_once_scope = old_v_once_scope;
+        self.pre_scope = old_pre_scope;
+
+        let patch_hints = &mut element_node.patch_hints;
 
         // Apply other flags
         // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L732
@@ -673,110 +1513,1437 @@ impl<'a> Visitor for TemplateVisitor<'_> {
 
         // Restore the parent scope
         self.current_scope = parent_scope;
+        self.depth -= 1;
     }
 
-    fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence) {
-        // In this function, conditions are transformed first
-        // without updating the template scope and collecting its variables.
-        // I believe this is a correct way of doing it, because in VDOM the condition
-        // wraps around the node (`condition ? if_node : else_node`).
-        // However, I am not too sure about the `v-if` & `v-slot` combined usage.
+    /// Warns about the expression changing the state on every render, e.g. `{{ count++ }}`
+    fn report_side_effect(&mut self, expr: &Expr, fallback_span: Span) {
+        let Some(span) = find_side_effect(expr) else {
+            return;
+        };
 
-        self.bindings_helper
-            .transform_expr(&mut conditional_node.if_node.condition, self.current_scope);
-        self.visit_element_node(&mut conditional_node.if_node.node);
+        self.errors.push(TemplateError {
+            span: if span.is_dummy() { fallback_span } else { span },
+            kind: TemplateErrorKind::SideEffectInExpression,
+        });
+    }
 
-        for else_if_node in conditional_node.else_if_nodes.iter_mut() {
-            self.bindings_helper
-                .transform_expr(&mut else_if_node.condition, self.current_scope);
-            self.visit_element_node(&mut else_if_node.node);
+    /// Reports the element nested deeper than [`BindingsHelper::max_depth`] and drops its children
+    fn is_too_deep(&mut self, element_node: &mut ElementNode) -> bool {
+        match self.bindings_helper.max_depth {
+            Some(max_depth) if self.depth >= max_depth => {}
+            _ => return false,
         }
 
-        if let Some(ref mut else_node) = conditional_node.else_node {
-            self.visit_element_node(else_node);
-        }
+        self.errors.push(TemplateError {
+            span: element_node.span,
+            kind: TemplateErrorKind::NestingTooDeep,
+        });
+        element_node.children.clear();
+        true
     }
 
-    fn visit_interpolation(&mut self, interpolation: &mut Interpolation) {
-        interpolation.template_scope = self.current_scope;
+    /// Marks the whole `v-pre` subtree as plain elements.
+    /// The parser already left the attributes and the `{{ }}` texts unprocessed,
+    /// and the whitespace is not condensed either, only the leading and trailing one is removed.
+    fn transform_v_pre_subtree(&mut self, element_node: &mut ElementNode) {
+        let parent_depth = self.depth;
+        let in_pre = self.pre_scope || element_node.starting_tag.tag_name == "pre";
+        let mut stack = vec![(element_node, parent_depth, in_pre)];
+
+        while let Some((element_node, depth, in_pre)) = stack.pop() {
+            element_node.kind = ElementKind::Element;
+            optimize_children(
+                &mut element_node.children,
+                ElementKind::Element,
+                WhitespaceMode::Preserve,
+                self.bindings_helper.keeps_comments(),
+                in_pre,
+                &mut self.errors,
+            );
 
-        let has_js = self
-            .bindings_helper
-            .transform_expr(&mut interpolation.value, self.current_scope);
+            for child in element_node.children.iter_mut() {
+                let Node::Element(child_element) = child else {
+                    continue;
+                };
 
-        interpolation.patch_flag = has_js;
+                self.depth = depth;
+                if !self.is_too_deep(child_element) {
+                    let in_pre = in_pre || child_element.starting_tag.tag_name == "pre";
+                    stack.push((child_element, depth + 1, in_pre));
+                }
+            }
+        }
+
+        self.depth = parent_depth;
     }
-}
 
-impl TemplateVisitor<'_> {
     // TODO Maybe do this in parser instead, because it sometimes needs this info
     fn recognize_element_kind(&self, starting_tag: &StartingTag) -> ElementKind {
         let tag_name = &starting_tag.tag_name;
 
-        // First, check for a built-in
-        if let Some(builtin_type) = VUE_BUILTINS.get(&tag_name) {
-            // Special case for `<component>`. If it does not have `is`, this is not a built-in
-            if tag_name.eq("component") {
-                let has_is = starting_tag
-                    .attributes
-                    .iter()
-                    .any(|attr| check_attribute_name(attr, "is"));
+        // First, check for a built-in
+        if let Some(builtin_type) = get_vue_builtin(tag_name) {
+            // Special case for `<component>`. If it does not have `is`, this is not a built-in
+            if matches!(builtin_type, BuiltinType::Component) {
+                let has_is = starting_tag
+                    .attributes
+                    .iter()
+                    .any(|attr| check_attribute_name(attr, "is"));
+
+                if !has_is {
+                    return ElementKind::Component;
+                }
+            }
+
+            return ElementKind::Builtin(builtin_type);
+        }
+
+        // Then check if this is a native tag, HTML (including SVG) and MathML by default
+        let is_native_tag = match &self.bindings_helper.is_native_tag {
+            Some(is_native_tag) => is_native_tag(tag_name),
+            None => is_html_tag(tag_name) || is_mathml_tag(tag_name),
+        };
+        if is_native_tag {
+            ElementKind::Element
+        } else {
+            ElementKind::Component
+        }
+    }
+
+    /// Reports `<slot name="foo">` when `foo` is not in `defineSlots<{ ... }>()`.
+    /// Dynamic slot names (`<slot :name="foo">`) cannot be checked.
+    fn check_slot_is_declared(&mut self, element_node: &ElementNode) {
+        let Some(ref declared_slots) = self.bindings_helper.declared_slots else {
+            return;
+        };
+
+        let name_attr = element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .find(|attr| check_attribute_name(attr, "name"));
+
+        let slot_name = match name_attr {
+            Some(AttributeOrBinding::RegularAttribute { value, .. }) => value.to_owned(),
+            Some(_) => return,
+            None => fervid_atom!("default"),
+        };
+
+        if !declared_slots.contains(&slot_name) {
+            self.errors.push(TemplateError {
+                span: element_node.span,
+                kind: TemplateErrorKind::UndeclaredSlot,
+            });
+        }
+    }
+}
+
+impl VisitMut for Node {
+    fn visit_mut_with(&mut self, visitor: &mut impl Visitor) {
+        match self {
+            Node::Element(el) => visitor.visit_element_node(el),
+            Node::ConditionalSeq(cond) => visitor.visit_conditional_node(cond),
+            Node::Interpolation(interpolation) => visitor.visit_interpolation(interpolation),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        error::{ErrorCode, Severity, SeverityLevel},
+        ElementKind, Node, PatchHints, VForDirective, VModelDirective, VueDirectives,
+    };
+    use std::sync::Arc;
+    use swc_core::common::{BytePos, Span, DUMMY_SP};
+
+    use crate::{
+        test_utils::{js, to_str},
+        SetupBinding,
+    };
+
+    use super::*;
+
+    /// Special case: `<component>` without `is` attribute is not a builtin
+    #[test]
+    fn it_distinguishes_component_builtin_and_not() {
+        let starting_tag = StartingTag {
+            tag_name: "component".into(),
+            attributes: vec![],
+            directives: None,
+        };
+
+        let mut bindings_helper = Default::default();
+        let template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
+        };
+        assert!(matches!(
+            template_visitor.recognize_element_kind(&starting_tag),
+            ElementKind::Component
+        ));
+    }
+
+    #[test]
+    fn it_recognizes_builtins_in_any_casing() {
+        let mut bindings_helper = Default::default();
+        let template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
+        };
+
+        let recognize = |tag_name: &str| {
+            template_visitor.recognize_element_kind(&StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            })
+        };
+
+        for tag_name in ["keep-alive", "KeepAlive", "keepAlive"] {
+            assert!(matches!(
+                recognize(tag_name),
+                ElementKind::Builtin(BuiltinType::KeepAlive)
+            ));
+        }
+        for tag_name in ["transition-group", "TransitionGroup", "transitionGroup"] {
+            assert!(matches!(
+                recognize(tag_name),
+                ElementKind::Builtin(BuiltinType::TransitionGroup)
+            ));
+        }
+        assert!(matches!(
+            recognize("Teleport"),
+            ElementKind::Builtin(BuiltinType::Teleport)
+        ));
+
+        // Not a built-in
+        assert!(matches!(recognize("keepalive"), ElementKind::Component));
+        assert!(matches!(recognize("keep-alive-x"), ElementKind::Component));
+        assert!(matches!(recognize("my-component"), ElementKind::Component));
+        assert!(matches!(recognize("div"), ElementKind::Element));
+
+        // `<component>` is the dynamic component only with `is`
+        assert!(matches!(recognize("component"), ElementKind::Component));
+        assert!(matches!(
+            template_visitor.recognize_element_kind(&StartingTag {
+                tag_name: "component".into(),
+                attributes: vec![AttributeOrBinding::RegularAttribute {
+                    name: "is".into(),
+                    value: "div".into(),
+                    raw_value: None,
+                    span: DUMMY_SP,
+                }],
+                directives: None,
+            }),
+            ElementKind::Builtin(BuiltinType::Component)
+        ));
+    }
+
+    #[test]
+    fn it_recognizes_mathml_tags() {
+        let mut bindings_helper = Default::default();
+        let template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
+        };
+
+        let recognize = |tag_name: &str| {
+            template_visitor.recognize_element_kind(&StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            })
+        };
+
+        for tag_name in ["math", "mi", "mn", "mo", "mfrac", "msqrt", "annotation-xml"] {
+            assert!(matches!(recognize(tag_name), ElementKind::Element));
+        }
+
+        // Not a MathML element
+        assert!(matches!(recognize("MFrac"), ElementKind::Component));
+    }
+
+    #[test]
+    fn it_recognizes_custom_native_tags() {
+        let mut bindings_helper = BindingsHelper {
+            is_native_tag: Some(Arc::new(|tag| matches!(tag, "view" | "text"))),
+            ..Default::default()
+        };
+        let template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
+        };
+
+        let recognize = |tag_name: &str| {
+            template_visitor.recognize_element_kind(&StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            })
+        };
+
+        assert!(matches!(recognize("view"), ElementKind::Element));
+        assert!(matches!(recognize("text"), ElementKind::Element));
+
+        // HTML tags are not native for this platform
+        assert!(matches!(recognize("div"), ElementKind::Component));
+
+        // Built-ins are still recognized
+        assert!(matches!(
+            recognize("Teleport"),
+            ElementKind::Builtin(BuiltinType::Teleport)
+        ));
+    }
+
+    #[test]
+    fn it_resolves_scope_variables_on_the_declaring_element() {
+        let Expr::Arrow(slot_arrow) = *js("({ item }) => {}") else {
+            unreachable!()
+        };
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                // <li v-for="(item, index) in item" :key="item.id" :ref="el => refs[index] = el">{{ item }}</li>
+                Node::Element(element(
+                    "li",
+                    vec![
+                        v_bind("key", "item.id"),
+                        v_bind("ref", "el => refs[index] = el"),
+                    ],
+                    Some(VueDirectives {
+                        v_for: Some(VForDirective {
+                            iterable: js("item"),
+                            itervar: js("(item, index)"),
+                            patch_flags: Default::default(),
+                            span: DUMMY_SP,
+                        }),
+                        ..Default::default()
+                    }),
+                    vec![interpolation("item")],
+                )),
+                // <test-component v-slot="{ item }" :foo="item">{{ item }}</test-component>
+                Node::Element(element(
+                    "test-component",
+                    vec![v_bind("foo", "item")],
+                    Some(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: None,
+                            value: slot_arrow.params.into_iter().next().map(Box::new),
+                        }),
+                        ..Default::default()
+                    }),
+                    vec![interpolation("item")],
+                )),
+            ],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let bound_values = |element: &ElementNode| -> Vec<String> {
+            element
+                .starting_tag
+                .attributes
+                .iter()
+                .filter_map(|attr| match attr {
+                    AttributeOrBinding::VBind(v_bind) => Some(to_str(&v_bind.value)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let child_interpolation = |element: &ElementNode| -> String {
+            let Some(Node::Interpolation(interpolation)) = element.children.first() else {
+                panic!("child is not an interpolation")
+            };
+            to_str(&interpolation.value)
+        };
+
+        // Both roots are merged into a `<template>` Fragment
+        let Node::Element(ref fragment) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        let roots = &fragment.children;
+
+        // `v-for` variables are available to the element, but not to the iterable
+        let Node::Element(ref li) = roots[0] else {
+            panic!("root is not an element")
+        };
+        let v_for = li.starting_tag.directives.as_ref().unwrap().v_for.as_ref();
+        assert_eq!("_ctx.item", to_str(&v_for.unwrap().iterable));
+        assert_eq!(
+            vec!["item.id", "el=>_ctx.refs[index]=el", "true"],
+            bound_values(li)
+        );
+        assert_eq!("item", child_interpolation(li));
+
+        // `v-slot` variables are only available to the children
+        let Node::Element(ref component) = roots[1] else {
+            panic!("root is not an element")
+        };
+        assert_eq!(vec!["_ctx.item"], bound_values(component));
+        assert_eq!("item", child_interpolation(component));
+    }
+
+    #[test]
+    fn it_visits_nested_conditional_branches_in_parent_scope() {
+        // <ul>
+        //   <li v-for="item in items">
+        //     <span v-if="item.ok">{{ item.name }}</span>
+        //     <span v-else>{{ msg }}</span>
+        //   </li>
+        // </ul>
+        let conditional_seq = ConditionalNodeSequence {
+            if_node: Box::new(Conditional {
+                condition: *js("item.ok"),
+                node: element("span", vec![], None, vec![interpolation("item.name")]),
+            }),
+            else_if_nodes: vec![],
+            else_node: Some(Box::new(element(
+                "span",
+                vec![],
+                None,
+                vec![interpolation("msg")],
+            ))),
+        };
+        let li = element(
+            "li",
+            vec![],
+            Some(VueDirectives {
+                v_for: Some(VForDirective {
+                    iterable: js("items"),
+                    itervar: js("item"),
+                    patch_flags: Default::default(),
+                    span: DUMMY_SP,
+                }),
+                ..Default::default()
+            }),
+            vec![Node::ConditionalSeq(conditional_seq)],
+        );
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(element(
+                "ul",
+                vec![],
+                None,
+                vec![Node::Element(li)],
+            ))],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let Node::Element(ref ul) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        let Some(Node::Element(li)) = ul.children.first() else {
+            panic!("child is not an element")
+        };
+        let Some(Node::ConditionalSeq(conditional_seq)) = li.children.first() else {
+            panic!("child is not a conditional sequence")
+        };
+        let branch_interpolation = |branch: &ElementNode| -> String {
+            let Some(Node::Interpolation(interpolation)) = branch.children.first() else {
+                panic!("child is not an interpolation")
+            };
+            to_str(&interpolation.value)
+        };
+
+        // The condition and the branches see the `v-for` variables
+        assert_eq!("item.ok", to_str(&conditional_seq.if_node.condition));
+        assert_eq!(
+            "item.name",
+            branch_interpolation(&conditional_seq.if_node.node)
+        );
+        assert_eq!(
+            "_ctx.msg",
+            branch_interpolation(conditional_seq.else_node.as_ref().unwrap())
+        );
+
+        // Patch flags of the branch are computed after its children are visited
+        assert!(conditional_seq
+            .if_node
+            .node
+            .patch_hints
+            .flags
+            .contains(PatchFlags::Text));
+    }
+
+    #[test]
+    fn it_removes_nested_v_once() {
+        let v_once = || {
+            Some(VueDirectives {
+                v_once: Some(()),
+                ..Default::default()
+            })
+        };
+        let has_v_once = |element_node: &ElementNode| {
+            element_node
+                .starting_tag
+                .directives
+                .as_ref()
+                .is_some_and(|directives| directives.v_once.is_some())
+        };
+
+        // <div v-once><span v-once>hello</span></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(element(
+                "div",
+                vec![],
+                v_once(),
+                vec![Node::Element(element(
+                    "span",
+                    vec![],
+                    v_once(),
+                    vec![Node::Text("hello".into(), DUMMY_SP)],
+                ))],
+            ))],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        // The outer `v-once` caches the whole subtree
+        let Node::Element(ref div) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        assert!(has_v_once(div));
+        let Node::Element(ref span) = div.children[0] else {
+            panic!("child is not an element")
+        };
+        assert!(!has_v_once(span));
+    }
+
+    #[test]
+    fn it_keeps_v_pre_subtree_as_is() {
+        // <div v-pre>
+        //   <MyComp :foo="bar">{{ msg }}</MyComp>
+        // </div>
+        let my_comp = ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: "MyComp".into(),
+                attributes: vec![AttributeOrBinding::RegularAttribute {
+                    name: ":foo".into(),
+                    value: "bar".into(),
+                    raw_value: None,
+                    span: DUMMY_SP,
+                }],
+                directives: None,
+            },
+            children: vec![Node::Text("{{ msg }}".into(), DUMMY_SP)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_pre: Some(()),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![
+                    Node::Text("\n  ".into(), DUMMY_SP),
+                    Node::Element(my_comp),
+                    Node::Text("\n".into(), DUMMY_SP),
+                ],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut Vec::new());
+
+        let Node::Element(ref div) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        assert!(div.patch_hints.flags.is_empty());
+
+        // Only the whitespace is removed
+        assert_eq!(1, div.children.len());
+        let Node::Element(ref my_comp) = div.children[0] else {
+            panic!("child is not an element")
+        };
+
+        // The component is neither recognized nor resolved
+        assert!(matches!(my_comp.kind, ElementKind::Element));
+        assert!(bindings_helper.components.is_empty());
+        assert!(my_comp.patch_hints.flags.is_empty());
+        assert!(matches!(
+            my_comp.children.as_slice(),
+            [Node::Text(text, _)] if text == "{{ msg }}"
+        ));
+    }
+
+    #[test]
+    fn it_keeps_whitespace_inside_pre() {
+        // <pre>  keep\n   this  <b> </b>\n</pre>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "pre".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![
+                    Node::Text("  keep\n   this  ".into(), DUMMY_SP),
+                    Node::Element(ElementNode {
+                        kind: ElementKind::Element,
+                        starting_tag: StartingTag {
+                            tag_name: "b".into(),
+                            attributes: vec![],
+                            directives: None,
+                        },
+                        children: vec![Node::Text(" ".into(), DUMMY_SP)],
+                        template_scope: 0,
+                        patch_hints: Default::default(),
+                        span: DUMMY_SP,
+                    }),
+                    Node::Text("\n".into(), DUMMY_SP),
+                ],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut Vec::new());
+
+        let Node::Element(ref pre) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        assert_eq!(3, pre.children.len());
+        assert!(matches!(&pre.children[0], Node::Text(text, _) if text == "  keep\n   this  "));
+        assert!(matches!(&pre.children[2], Node::Text(text, _) if text == "\n"));
+        let Node::Element(ref b) = pre.children[1] else {
+            panic!("child is not an element")
+        };
+        assert!(matches!(b.children.as_slice(), [Node::Text(text, _)] if text == " "));
+    }
+
+    #[test]
+    fn it_records_v_for_keys() {
+        let li_v_for = |attributes, span: Span| {
+            let directives = VueDirectives {
+                v_for: Some(VForDirective {
+                    iterable: js("items"),
+                    itervar: js("item"),
+                    patch_flags: Default::default(),
+                    span,
+                }),
+                ..Default::default()
+            };
+            Node::Element(element("li", attributes, Some(directives), vec![]))
+        };
+        let span = |lo: u32, hi: u32| Span::new(BytePos(lo), BytePos(hi));
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                // <li v-for="item in items" :key="item.id"></li>
+                li_v_for(vec![v_bind("key", "item.id")], span(5, 25)),
+                // <li v-for="item in items" key="static"></li>
+                li_v_for(
+                    vec![AttributeOrBinding::RegularAttribute {
+                        name: "key".into(),
+                        value: "static".into(),
+                        raw_value: None,
+                        span: DUMMY_SP,
+                    }],
+                    span(50, 70),
+                ),
+                // <li v-for="item in items"></li>
+                li_v_for(vec![], span(90, 110)),
+            ],
+            span: DUMMY_SP,
+        };
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut Vec::new());
+
+        let v_for_keys: Vec<(Span, Option<String>)> = bindings_helper
+            .v_for_keys
+            .iter()
+            .map(|v_for_key| (v_for_key.span, v_for_key.key.as_deref().map(to_str)))
+            .collect();
+        assert_eq!(
+            vec![
+                (span(5, 25), Some("item.id".to_string())),
+                (span(50, 70), Some(r#""static""#.to_string())),
+                (span(90, 110), None),
+            ],
+            v_for_keys
+        );
+    }
+
+    #[test]
+    fn it_determines_element_constant_type() {
+        // <div id="a"></div><div :tabindex="-1"></div><div :title="Math.PI + ''"></div><div :id="b"></div>
+        let div = |attributes| Node::Element(element("div", attributes, None, vec![]));
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                div(vec![AttributeOrBinding::RegularAttribute {
+                    name: "id".into(),
+                    value: "a".into(),
+                    raw_value: None,
+                    span: DUMMY_SP,
+                }]),
+                div(vec![v_bind("tabindex", "-1")]),
+                div(vec![v_bind("title", "Math.PI + ''")]),
+                div(vec![v_bind("id", "b")]),
+            ],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        // The roots are merged into a `<template>` Fragment
+        let Node::Element(ref fragment) = sfc_template.roots[0] else {
+            panic!("root is not an element")
+        };
+        let constant_types: Vec<ConstantTypes> = fragment
+            .children
+            .iter()
+            .map(|root| match root {
+                Node::Element(element_node) => element_node.patch_hints.constant_type,
+                _ => panic!("root is not an element"),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ConstantTypes::CanStringify,
+                ConstantTypes::CanHoist,
+                ConstantTypes::CanHoist,
+                ConstantTypes::NotConstant,
+            ],
+            constant_types
+        );
+    }
+
+    #[test]
+    fn it_sets_full_props_for_v_bind_object() {
+        // <div :foo="a" v-bind="{ ...b, c: 1 }" :d="e"></div>
+        let mut v_bind_object = v_bind("", "{ ...b, c: 1 }");
+        if let AttributeOrBinding::VBind(ref mut v_bind) = v_bind_object {
+            v_bind.argument = None;
+        }
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![v_bind("foo", "a"), v_bind_object, v_bind("d", "e")],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let Some(Node::Element(div)) = sfc_template.roots.first() else {
+            panic!("root is not an element")
+        };
+        assert!(div.patch_hints.flags.contains(PatchFlags::FullProps));
+        assert!(!div.patch_hints.flags.contains(PatchFlags::Props));
+        assert!(div.patch_hints.props.is_empty());
+
+        // Identifiers inside the spread are resolved
+        let AttributeOrBinding::VBind(ref v_bind_object) = div.starting_tag.attributes[1] else {
+            panic!("attribute is not v-bind")
+        };
+        assert_eq!("{..._ctx.b,c:1}", to_str(&v_bind_object.value));
+    }
+
+    #[test]
+    fn it_sets_need_patch_for_v_show() {
+        // <div v-show="visible">hello</div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_show: Some(js("visible")),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let Some(Node::Element(div)) = sfc_template.roots.first() else {
+            panic!("root is not an element")
+        };
+        assert_eq!(div.patch_hints.flags, PatchFlags::NeedPatch);
+        assert!(!div.patch_hints.should_use_block);
+    }
+
+    #[test]
+    fn it_uses_props_flag_for_teleport() {
+        // <Teleport :to="target" :class="cls" disabled><div /></Teleport>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "Teleport".into(),
+                    attributes: vec![
+                        v_bind("to", "target"),
+                        v_bind("class", "cls"),
+                        AttributeOrBinding::RegularAttribute {
+                            name: "disabled".into(),
+                            value: "".into(),
+                            raw_value: None,
+                            span: DUMMY_SP,
+                        },
+                    ],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let Some(Node::Element(teleport)) = sfc_template.roots.first() else {
+            panic!("root is not an element")
+        };
+        assert!(matches!(
+            teleport.kind,
+            ElementKind::Builtin(BuiltinType::Teleport)
+        ));
+        assert!(teleport.patch_hints.flags.contains(PatchFlags::Props));
+        assert!(!teleport.patch_hints.flags.contains(PatchFlags::Class));
+        assert_eq!(
+            vec![FervidAtom::from("to"), FervidAtom::from("class")],
+            teleport.patch_hints.props.to_vec()
+        );
+    }
+
+    #[test]
+    fn it_merges_adjacent_texts() {
+        let mut children = vec![
+            Node::Text("hello ".into(), DUMMY_SP),
+            Node::Text("world ".into(), DUMMY_SP),
+            Node::Interpolation(Interpolation {
+                value: js("name"),
+                template_scope: 0,
+                patch_flag: true,
+                span: DUMMY_SP,
+            }),
+            Node::Text("!".into(), DUMMY_SP),
+        ];
+        optimize_children(
+            &mut children,
+            ElementKind::Element,
+            WhitespaceMode::Condense,
+            true,
+            false,
+            &mut Vec::new(),
+        );
+
+        assert_eq!(3, children.len());
+        assert!(matches!(&children[0], Node::Text(text, _) if text == "hello world "));
+        assert!(matches!(&children[2], Node::Text(text, _) if text == "!"));
+    }
+
+    #[test]
+    fn it_condenses_and_preserves_whitespace() {
+        let span = |text: &str| {
+            Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "span".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Text(text.into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        // `\n  <span>a</span> <span>b</span>\n  <span>c</span>\n  hello\n  world <!--x-->\n`
+        let children = || {
+            vec![
+                Node::Text("\n  ".into(), DUMMY_SP),
+                span("a"),
+                Node::Text(" ".into(), DUMMY_SP),
+                span("b"),
+                Node::Text("\n  ".into(), DUMMY_SP),
+                span("c"),
+                Node::Text("\n  hello\n  world ".into(), DUMMY_SP),
+                Node::Comment("x".into(), DUMMY_SP),
+                Node::Text("\n".into(), DUMMY_SP),
+            ]
+        };
+        let texts = |children: &[Node]| -> Vec<String> {
+            children
+                .iter()
+                .map(|child| match child {
+                    Node::Text(text, _) => text.to_string(),
+                    Node::Element(_) => "<span>".to_string(),
+                    Node::Comment(_, _) => "<!---->".to_string(),
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+
+        let mut condensed = children();
+        optimize_children(
+            &mut condensed,
+            ElementKind::Element,
+            WhitespaceMode::Condense,
+            true,
+            false,
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            vec![
+                "<span>",
+                " ",
+                "<span>",
+                "<span>",
+                " hello world ",
+                "<!---->"
+            ],
+            texts(&condensed)
+        );
+
+        let mut preserved = children();
+        optimize_children(
+            &mut preserved,
+            ElementKind::Element,
+            WhitespaceMode::Preserve,
+            true,
+            false,
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            vec![
+                "<span>",
+                " ",
+                "<span>",
+                " ",
+                "<span>",
+                "\n  hello\n  world ",
+                "<!---->"
+            ],
+            texts(&preserved)
+        );
+    }
+
+    #[test]
+    fn it_sets_text_flag_only_for_text_children() {
+        let interpolation = || {
+            Node::Interpolation(Interpolation {
+                value: js("name"),
+                template_scope: 0,
+                patch_flag: false,
+                span: DUMMY_SP,
+            })
+        };
+        let transform_div = |children: Vec<Node>| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "div".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children,
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                span: DUMMY_SP,
+            };
+            transform_and_record_template(
+                &mut sfc_template,
+                &mut Default::default(),
+                &mut Vec::new(),
+            );
+            let Some(Node::Element(div)) = sfc_template.roots.pop() else {
+                panic!("root is not an element")
+            };
+            div
+        };
+
+        // <div>hello {{ name }}!</div>
+        let div = transform_div(vec![
+            Node::Text("hello ".into(), DUMMY_SP),
+            interpolation(),
+            Node::Text("!".into(), DUMMY_SP),
+        ]);
+        assert!(div.patch_hints.flags.contains(PatchFlags::Text));
+
+        // <div>hello {{ name }}<!-- comment --></div>
+        let div = transform_div(vec![
+            Node::Text("hello ".into(), DUMMY_SP),
+            interpolation(),
+            Node::Comment("comment".into(), DUMMY_SP),
+        ]);
+        assert!(!div.patch_hints.flags.contains(PatchFlags::Text));
+    }
+
+    #[test]
+    fn it_validates_transition_children() {
+        let transform = |root: ElementNode| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(root)],
+                span: DUMMY_SP,
+            };
+            let mut errors = Vec::new();
+            transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+            errors
+                .into_iter()
+                .map(|error| match error {
+                    TransformError::TemplateError(template_error) => template_error.kind,
+                    _ => panic!("not a template error"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let key = || AttributeOrBinding::RegularAttribute {
+            name: "key".into(),
+            value: "a".into(),
+            raw_value: None,
+            span: DUMMY_SP,
+        };
+
+        // <Transition><div /><!-- comment --></Transition>
+        let errors = transform(element(
+            "Transition",
+            vec![],
+            None,
+            vec![
+                Node::Element(element("div", vec![], None, vec![])),
+                Node::Comment("comment".into(), DUMMY_SP),
+            ],
+        ));
+        assert!(errors.is_empty());
+
+        // <Transition><div /><span /></Transition>
+        let errors = transform(element(
+            "Transition",
+            vec![],
+            None,
+            vec![
+                Node::Element(element("div", vec![], None, vec![])),
+                Node::Element(element("span", vec![], None, vec![])),
+            ],
+        ));
+        assert!(matches!(
+            errors.as_slice(),
+            [TemplateErrorKind::TransitionInvalidChildren]
+        ));
+
+        // <Transition><div v-for="i in 3" /></Transition>
+        let mut div_v_for = element("div", vec![], None, vec![]);
+        div_v_for.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_for: Some(VForDirective {
+                iterable: js("3"),
+                itervar: js("i"),
+                patch_flags: Default::default(),
+                span: DUMMY_SP,
+            }),
+            ..Default::default()
+        }));
+        let errors = transform(element(
+            "Transition",
+            vec![],
+            None,
+            vec![Node::Element(div_v_for)],
+        ));
+        assert!(matches!(
+            errors.as_slice(),
+            [TemplateErrorKind::TransitionInvalidChildren]
+        ));
+
+        // <KeepAlive><Comp v-if="a" /><template v-else><A /><B /></template></KeepAlive>
+        let mut comp_v_if = element("Comp", vec![], None, vec![]);
+        comp_v_if.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_if: Some(js("a")),
+            ..Default::default()
+        }));
+        let mut template_v_else = element(
+            "template",
+            vec![],
+            None,
+            vec![
+                Node::Element(element("A", vec![], None, vec![])),
+                Node::Element(element("B", vec![], None, vec![])),
+            ],
+        );
+        template_v_else.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_else: Some(()),
+            ..Default::default()
+        }));
+        let errors = transform(element(
+            "KeepAlive",
+            vec![],
+            None,
+            vec![Node::Element(comp_v_if), Node::Element(template_v_else)],
+        ));
+        assert!(matches!(
+            errors.as_slice(),
+            [TemplateErrorKind::KeepAliveInvalidChildren]
+        ));
+
+        // <KeepAlive><Comp /></KeepAlive>
+        let errors = transform(element(
+            "KeepAlive",
+            vec![],
+            None,
+            vec![Node::Element(element("Comp", vec![], None, vec![]))],
+        ));
+        assert!(errors.is_empty());
+
+        // <TransitionGroup><li key="a" /><li /></TransitionGroup>
+        let errors = transform(element(
+            "TransitionGroup",
+            vec![],
+            None,
+            vec![
+                Node::Element(element("li", vec![key()], None, vec![])),
+                Node::Element(element("li", vec![], None, vec![])),
+            ],
+        ));
+        assert!(matches!(
+            errors.as_slice(),
+            [TemplateErrorKind::TransitionGroupUnkeyedChild]
+        ));
+    }
+
+    #[test]
+    fn it_reports_mixed_v_slot_usage() {
+        let with_v_slot =
+            |tag_name: &str, slot_name: Option<&str>, children: Vec<Node>| ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: slot_name.map(|name| StrOrExpr::Str(name.into())),
+                            value: None,
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children,
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            };
+        let transform = |root: ElementNode| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(root)],
+                span: DUMMY_SP,
+            };
+            let mut errors = Vec::new();
+            transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+            errors
+        };
+
+        // <test-component v-slot>hello</test-component>
+        let errors = transform(with_v_slot(
+            "test-component",
+            None,
+            vec![Node::Text("hello".into(), DUMMY_SP)],
+        ));
+        assert!(errors.is_empty());
+
+        // <test-component v-slot><template #foo>hello</template></test-component>
+        let errors = transform(with_v_slot(
+            "test-component",
+            None,
+            vec![Node::Element(with_v_slot(
+                "template",
+                Some("foo"),
+                vec![Node::Text("hello".into(), DUMMY_SP)],
+            ))],
+        ));
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::VSlotMixedSlotUsage,
+                ..
+            })]
+        ));
+    }
+
+    #[test]
+    fn it_reports_dynamic_slots() {
+        // <test-component><template v-for="item in items" #[item]>hello</template></test-component>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "template".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_for: Some(VForDirective {
+                                iterable: js("items"),
+                                itervar: js("item"),
+                                patch_flags: Default::default(),
+                                span: DUMMY_SP,
+                            }),
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some(StrOrExpr::Expr(js("item"))),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::DynamicSlotUnsupported,
+                ..
+            })]
+        ));
+    }
+
+    #[test]
+    fn it_warns_on_side_effects_in_expressions() {
+        // <div :title="title = 'foo'" :onClick="() => count++">{{ count++ }}{{ delete obj.foo }}{{ count + 1 }}</div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![
+                        v_bind("title", "title = 'foo'"),
+                        v_bind("onClick", "() => count++"),
+                    ],
+                    directives: None,
+                },
+                children: ["count++", "delete obj.foo", "count + 1"]
+                    .into_iter()
+                    .map(|value| {
+                        Node::Interpolation(Interpolation {
+                            value: js(value),
+                            template_scope: 0,
+                            patch_flag: false,
+                            span: DUMMY_SP,
+                        })
+                    })
+                    .collect(),
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+
+        // The handler runs on click, not during the render
+        assert_eq!(3, errors.len());
+        assert!(errors.iter().all(|error| matches!(
+            error,
+            TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::SideEffectInExpression,
+                ..
+            })
+        )));
+    }
+
+    #[test]
+    fn it_reports_v_model_on_read_only_bindings() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings.extend([
+            SetupBinding(fervid_atom!("foo"), BindingTypes::Props),
+            SetupBinding(fervid_atom!("bar"), BindingTypes::SetupConst),
+            SetupBinding(fervid_atom!("baz"), BindingTypes::Imported),
+            SetupBinding(fervid_atom!("qux"), BindingTypes::SetupRef),
+        ]);
+
+        let mut transform = |value: &str| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "input".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_model: vec![VModelDirective {
+                                argument: None,
+                                value: js(value),
+                                update_handler: None,
+                                modifiers: vec![],
+                                span: DUMMY_SP,
+                            }],
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                span: DUMMY_SP,
+            };
+            let mut errors = Vec::new();
+            transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut errors);
+
+            // Code is generated regardless
+            let Node::Element(ref input) = sfc_template.roots[0] else {
+                unreachable!()
+            };
+            let directives = input.starting_tag.directives.as_ref().unwrap();
+            assert!(directives.v_model[0].update_handler.is_some());
 
-                if !has_is {
-                    return ElementKind::Component;
-                }
-            }
+            errors
+        };
 
-            return ElementKind::Builtin(*builtin_type);
+        macro_rules! assert_error {
+            ($value: literal, $kind: pat) => {
+                assert!(matches!(
+                    transform($value).as_slice(),
+                    [TransformError::TemplateError(TemplateError {
+                        kind: $kind,
+                        ..
+                    })]
+                ));
+            };
         }
 
-        // Then check if this is an HTML tag
-        if is_html_tag(&starting_tag.tag_name) {
-            ElementKind::Element
-        } else {
-            ElementKind::Component
-        }
-    }
-}
+        assert_error!("foo", TemplateErrorKind::VModelOnProps);
+        assert_error!("bar", TemplateErrorKind::VModelOnConst);
+        assert_error!("baz", TemplateErrorKind::VModelOnConst);
 
-impl VisitMut for Node {
-    fn visit_mut_with(&mut self, visitor: &mut impl Visitor) {
-        match self {
-            Node::Element(el) => visitor.visit_element_node(el),
-            Node::ConditionalSeq(cond) => visitor.visit_conditional_node(cond),
-            Node::Interpolation(interpolation) => visitor.visit_interpolation(interpolation),
-            _ => {}
-        }
+        // Writable bindings and member expressions are fine
+        assert!(transform("qux").is_empty());
+        assert!(transform("bar.value").is_empty());
+        assert!(transform("unknown").is_empty());
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use fervid_core::{ElementKind, Node, PatchHints, VForDirective, VueDirectives};
-    use swc_core::common::DUMMY_SP;
-
-    use crate::test_utils::{js, to_str};
-
-    use super::*;
 
-    /// Special case: `<component>` without `is` attribute is not a builtin
     #[test]
-    fn it_distinguishes_component_builtin_and_not() {
-        let starting_tag = StartingTag {
-            tag_name: "component".into(),
-            attributes: vec![],
-            directives: None,
+    fn it_reports_undeclared_slots() {
+        let mut bindings_helper = BindingsHelper::default();
+
+        let transform = |bindings_helper: &mut BindingsHelper, attributes| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "slot".into(),
+                        attributes,
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                span: DUMMY_SP,
+            };
+            let mut errors = Vec::new();
+            transform_and_record_template(&mut sfc_template, bindings_helper, &mut errors);
+            errors
         };
 
-        let mut bindings_helper = Default::default();
-        let template_visitor = TemplateVisitor {
-            bindings_helper: &mut bindings_helper,
-            current_scope: 0,
-            v_for_scope: false,
+        let name = |value: &str| AttributeOrBinding::RegularAttribute {
+            name: "name".into(),
+            raw_value: None,
+            value: value.into(),
+            span: DUMMY_SP,
         };
+
+        // Slots are not declared, nothing to check
+        assert!(transform(&mut bindings_helper, vec![name("foo")]).is_empty());
+
+        // `defineSlots<{ default(): any, foo(): any }>()`
+        bindings_helper.declared_slots = Some(vec![fervid_atom!("default"), fervid_atom!("foo")]);
+        assert!(transform(&mut bindings_helper, vec![]).is_empty());
+        assert!(transform(&mut bindings_helper, vec![name("foo")]).is_empty());
         assert!(matches!(
-            template_visitor.recognize_element_kind(&starting_tag),
-            ElementKind::Component
+            transform(&mut bindings_helper, vec![name("bar")]).as_slice(),
+            [TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::UndeclaredSlot,
+                ..
+            })]
         ));
     }
 
@@ -805,7 +2972,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template roots: one div
         assert_eq!(1, sfc_template.roots.len());
@@ -844,7 +3011,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template roots: one conditional sequence
         assert_eq!(1, sfc_template.roots.len());
@@ -875,7 +3042,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template roots: two conditional sequences inside one root
         assert_eq!(1, sfc_template.roots.len());
@@ -909,7 +3076,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template roots: two conditional sequences inside one root
         assert_eq!(1, sfc_template.roots.len());
@@ -941,7 +3108,8 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
 
         // Template root children: still two
         assert_eq!(1, sfc_template.roots.len());
@@ -950,6 +3118,22 @@ mod tests {
         };
         assert!(matches!(root.children[0], Node::Element(_)));
         assert!(matches!(root.children[1], Node::Element(_)));
+
+        // Both are reported
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                TransformError::TemplateError(TemplateError {
+                    kind: TemplateErrorKind::VElseIfWithoutVIf,
+                    ..
+                }),
+                TransformError::TemplateError(TemplateError {
+                    kind: TemplateErrorKind::VElseWithoutVIf,
+                    ..
+                })
+            ]
+        ));
+        assert_eq!("template/v-else-without-v-if", errors[1].code());
     }
 
     #[test]
@@ -971,7 +3155,7 @@ mod tests {
             ],
             span: DUMMY_SP,
         };
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
         assert_eq!(2, sfc_template.roots.len());
 
         // Should get merged
@@ -1004,8 +3188,280 @@ mod tests {
             ],
             span: DUMMY_SP,
         };
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+        assert_eq!(1, sfc_template.roots.len());
+    }
+
+    #[test]
+    fn it_keeps_single_root_element() {
+        let div = || {
+            Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: PatchHints::default(),
+                span: DUMMY_SP,
+            })
+        };
+        let comment_and_div = || SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                Node::Text("\n  ".into(), DUMMY_SP),
+                Node::Comment("comment".into(), DUMMY_SP),
+                Node::Text("\n  ".into(), DUMMY_SP),
+                div(),
+                Node::Text("\n".into(), DUMMY_SP),
+            ],
+            span: DUMMY_SP,
+        };
+
+        // <template>
+        //   <!-- comment -->
+        //   <div />
+        // </template>
+        // In development, root Fragment is marked as such
+        let mut sfc_template = comment_and_div();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+        assert_eq!(1, sfc_template.roots.len());
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(root.starting_tag.tag_name, "template");
+        assert_eq!(2, root.children.len());
+        assert!(root.patch_hints.flags.contains(PatchFlags::DevRootFragment));
+        assert!(root.patch_hints.flags.contains(PatchFlags::StableFragment));
+
+        // In production, the comment is dropped and the element is the only root
+        let mut sfc_template = comment_and_div();
+        let mut bindings_helper = BindingsHelper {
+            is_prod: true,
+            ..Default::default()
+        };
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut Vec::new());
+        assert_eq!(1, sfc_template.roots.len());
+        assert!(matches!(
+            sfc_template.roots[0],
+            Node::Element(ElementNode { ref starting_tag, .. }) if starting_tag.tag_name == "div"
+        ));
+
+        // In production with the comments kept, the Fragment is not marked as a dev-only one
+        let mut sfc_template = comment_and_div();
+        let mut bindings_helper = BindingsHelper {
+            is_prod: true,
+            comments: Some(true),
+            ..Default::default()
+        };
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut Vec::new());
+        assert_eq!(1, sfc_template.roots.len());
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(2, root.children.len());
+        assert!(!root.patch_hints.flags.contains(PatchFlags::DevRootFragment));
+        assert!(root.patch_hints.flags.contains(PatchFlags::StableFragment));
+
+        // <template>
+        //   text
+        //   <div />
+        // </template>
+        // Multiple roots, Fragment is not a dev-only one
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                Node::Text("\n  text\n  ".into(), DUMMY_SP),
+                div(),
+                Node::Text("\n".into(), DUMMY_SP),
+            ],
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
         assert_eq!(1, sfc_template.roots.len());
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(2, root.children.len());
+        assert!(!root.patch_hints.flags.contains(PatchFlags::DevRootFragment));
+        assert!(root.patch_hints.flags.contains(PatchFlags::StableFragment));
+    }
+
+    #[test]
+    fn it_limits_nesting_depth() {
+        // 300 nested `<div>`s
+        let deep_template = || {
+            let mut node = Node::Text("deep".into(), DUMMY_SP);
+            for _ in 0..300 {
+                node = Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "div".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![node],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                });
+            }
+            SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![node],
+                span: DUMMY_SP,
+            }
+        };
+        let element_depth = |sfc_template: &SfcTemplateBlock| {
+            let mut depth = 0;
+            let mut current = &sfc_template.roots[0];
+            while let Node::Element(element_node) = current {
+                depth += 1;
+                match element_node.children.first() {
+                    Some(child) => current = child,
+                    None => break,
+                }
+            }
+            depth
+        };
+
+        // Not limited by default
+        let mut sfc_template = deep_template();
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(300, element_depth(&sfc_template));
+
+        // Elements deeper than the limit are dropped
+        let mut sfc_template = deep_template();
+        let mut bindings_helper = BindingsHelper {
+            max_depth: Some(256),
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut errors);
+        assert!(matches!(
+            errors.as_slice(),
+            [TransformError::TemplateError(TemplateError {
+                kind: TemplateErrorKind::NestingTooDeep,
+                ..
+            })]
+        ));
+        assert_eq!(257, element_depth(&sfc_template));
+    }
+
+    #[test]
+    fn it_handles_empty_and_comments_only_template() {
+        let template = |roots: Vec<Node>| SfcTemplateBlock {
+            lang: "html".into(),
+            roots,
+            span: DUMMY_SP,
+        };
+        let is_empty_template_warning = |errors: &[TransformError]| {
+            matches!(
+                errors,
+                [error @ TransformError::TemplateError(TemplateError {
+                    kind: TemplateErrorKind::EmptyTemplate,
+                    ..
+                })] if matches!(error.get_severity(), SeverityLevel::Warning)
+            )
+        };
+
+        // <template>\n</template>
+        // In development, an empty comment is rendered
+        let mut sfc_template = template(vec![Node::Text("\n".into(), DUMMY_SP)]);
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+        assert!(is_empty_template_warning(&errors));
+        assert!(matches!(
+            sfc_template.roots.as_slice(),
+            [Node::Comment(comment, _)] if comment.is_empty()
+        ));
+
+        // <template><!-- comment --></template>
+        // Comment is kept in development
+        let mut sfc_template = template(vec![Node::Comment("comment".into(), DUMMY_SP)]);
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+        assert!(is_empty_template_warning(&errors));
+        assert!(matches!(
+            sfc_template.roots.as_slice(),
+            [Node::Comment(comment, _)] if comment == "comment"
+        ));
+
+        // In production, nothing is rendered
+        let mut sfc_template = template(vec![Node::Comment("comment".into(), DUMMY_SP)]);
+        let mut bindings_helper = BindingsHelper {
+            is_prod: true,
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper, &mut errors);
+        assert!(is_empty_template_warning(&errors));
+        assert!(sfc_template.roots.is_empty());
+    }
+
+    #[test]
+    fn it_keeps_comments_by_option() {
+        // <template><div><!-- comment --><span /></div></template>
+        let transform = |bindings_helper: &mut BindingsHelper| {
+            let span = Node::Element(ElementNode {
+                kind: ElementKind::Element,
+                starting_tag: StartingTag {
+                    tag_name: "span".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            });
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(ElementNode {
+                    kind: ElementKind::Element,
+                    starting_tag: StartingTag {
+                        tag_name: "div".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![Node::Comment("comment".into(), DUMMY_SP), span],
+                    template_scope: 0,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                span: DUMMY_SP,
+            };
+            transform_and_record_template(&mut sfc_template, bindings_helper, &mut Vec::new());
+
+            let Some(Node::Element(div)) = sfc_template.roots.pop() else {
+                panic!("root is not an element")
+            };
+            div.children
+                .iter()
+                .any(|child| matches!(child, Node::Comment(_, _)))
+        };
+
+        // By default, comments are only kept in development
+        assert!(transform(&mut BindingsHelper::default()));
+        assert!(!transform(&mut BindingsHelper {
+            is_prod: true,
+            ..Default::default()
+        }));
+
+        // The option overrides the default
+        assert!(transform(&mut BindingsHelper {
+            is_prod: true,
+            comments: Some(true),
+            ..Default::default()
+        }));
+        assert!(!transform(&mut BindingsHelper {
+            comments: Some(false),
+            ..Default::default()
+        }));
     }
 
     #[test]
@@ -1046,7 +3502,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template roots: one div
         assert_eq!(1, sfc_template.roots.len());
@@ -1102,7 +3558,7 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
 
         // Template root: both children nodes are still present
         assert_eq!(1, sfc_template.roots.len());
@@ -1179,7 +3635,11 @@ mod tests {
                 sfc_template.roots.push(Node::Element(div.clone()));
             }
             sfc_template.roots.push(Node::Element(template));
-            transform_and_record_template(&mut sfc_template, &mut Default::default());
+            transform_and_record_template(
+                &mut sfc_template,
+                &mut Default::default(),
+                &mut Vec::new(),
+            );
 
             let Some(Node::ConditionalSeq(cond)) = sfc_template.roots.pop() else {
                 panic!("root is not a conditional seq")
@@ -1340,6 +3800,219 @@ mod tests {
     }
 
     // text
+    #[test]
+    fn it_generates_keyed_fragment_branches() {
+        let template = |directives, children| {
+            Node::Element(element("template", vec![], Some(directives), children))
+        };
+        let heading = |tag_name: &str| Node::Element(element(tag_name, vec![], None, vec![]));
+        let check_fragment_branch = |node: &ElementNode, expected_key: &str| {
+            assert_eq!(node.starting_tag.tag_name, "template");
+            assert_eq!(2, node.children.len());
+            assert!(node.patch_hints.flags.contains(PatchFlags::StableFragment));
+            assert!(matches!(
+                node.starting_tag.attributes.as_slice(),
+                [AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(argument)),
+                    value,
+                    ..
+                })] if argument == "key" && to_str(&**value) == expected_key
+            ));
+        };
+
+        // <template v-if="true"><h1 /><h2 /></template>
+        // <template v-else><h3 /><h4 /></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(element(
+                "div",
+                vec![],
+                None,
+                vec![
+                    template(
+                        VueDirectives {
+                            v_if: Some(js("true")),
+                            ..Default::default()
+                        },
+                        vec![heading("h1"), heading("h2")],
+                    ),
+                    template(
+                        VueDirectives {
+                            v_else: Some(()),
+                            ..Default::default()
+                        },
+                        vec![heading("h3"), heading("h4")],
+                    ),
+                ],
+            ))],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut Vec::new());
+
+        let Node::Element(ref div) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Some(Node::ConditionalSeq(ref seq)) = div.children.first() else {
+            panic!("Not a conditional sequence")
+        };
+        check_fragment_branch(&seq.if_node.node, "0");
+        check_fragment_branch(seq.else_node.as_ref().expect("Must have else node"), "1");
+    }
+
+    #[test]
+    fn it_keeps_only_key_on_template_v_for() {
+        let class = || AttributeOrBinding::RegularAttribute {
+            name: "class".into(),
+            value: "foo".into(),
+            raw_value: None,
+            span: Span::new(BytePos(1), BytePos(10)),
+        };
+        let v_for = || VForDirective {
+            iterable: js("items"),
+            itervar: js("item"),
+            patch_flags: Default::default(),
+            span: DUMMY_SP,
+        };
+        let node = |tag_name: &str, attributes, directives, children| {
+            Node::Element(element(tag_name, attributes, directives, children))
+        };
+        let keys = |element: &ElementNode| -> Vec<String> {
+            element
+                .starting_tag
+                .attributes
+                .iter()
+                .map(|attr| match attr {
+                    AttributeOrBinding::VBind(v_bind) => to_str(&v_bind.value),
+                    _ => panic!("Only the key is expected"),
+                })
+                .collect()
+        };
+
+        // <template v-for="item in items" :key="item.id" class="foo"><h1 /><h2 /></template>
+        // <template v-if="ok" v-for="item in items" :key="item.id" class="foo"><p /></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![node(
+                "div",
+                vec![],
+                None,
+                vec![
+                    node(
+                        "template",
+                        vec![v_bind("key", "item.id"), class()],
+                        Some(VueDirectives {
+                            v_for: Some(v_for()),
+                            ..Default::default()
+                        }),
+                        vec![
+                            node("h1", vec![], None, vec![]),
+                            node("h2", vec![], None, vec![]),
+                        ],
+                    ),
+                    node(
+                        "template",
+                        vec![v_bind("key", "item.id"), class()],
+                        Some(VueDirectives {
+                            v_if: Some(js("ok")),
+                            v_for: Some(v_for()),
+                            ..Default::default()
+                        }),
+                        vec![node("p", vec![], None, vec![])],
+                    ),
+                ],
+            )],
+            span: DUMMY_SP,
+        };
+
+        let mut errors = Vec::new();
+        transform_and_record_template(&mut sfc_template, &mut Default::default(), &mut errors);
+
+        let Node::Element(ref div) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+
+        // The key stays on the Fragment of each item
+        let Some(Node::Element(ref fragment)) = div.children.first() else {
+            panic!("Not an element")
+        };
+        assert_eq!(vec!["item.id"], keys(fragment));
+        assert!(fragment
+            .patch_hints
+            .flags
+            .contains(PatchFlags::StableFragment));
+
+        // The single child replaces the `<template>` and takes its key
+        let Some(Node::ConditionalSeq(ref seq)) = div.children.get(1) else {
+            panic!("Not a conditional sequence")
+        };
+        let p = &seq.if_node.node;
+        assert_eq!(p.starting_tag.tag_name, "p");
+        assert!(p
+            .starting_tag
+            .directives
+            .as_ref()
+            .is_some_and(|directives| directives.v_for.is_some()));
+        assert_eq!(vec!["item.id"], keys(p));
+
+        // Both `class`es are reported
+        assert_eq!(
+            2,
+            errors
+                .iter()
+                .filter(|e| matches!(
+                    e,
+                    TransformError::TemplateError(TemplateError {
+                        kind: TemplateErrorKind::IgnoredTemplateAttribute,
+                        span,
+                    }) if span.lo == BytePos(1)
+                ))
+                .count()
+        );
+    }
+
+    // :argument="value"
+    fn v_bind(argument: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(argument.into())),
+            value: js(value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: DUMMY_SP,
+        })
+    }
+
+    // {{ value }}
+    fn interpolation(value: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: js(value),
+            template_scope: 0,
+            patch_flag: false,
+            span: DUMMY_SP,
+        })
+    }
+
+    fn element(
+        tag_name: &str,
+        attributes: Vec<AttributeOrBinding>,
+        directives: Option<VueDirectives>,
+        children: Vec<Node>,
+    ) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives: directives.map(Box::new),
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
     fn text_node() -> Node {
         Node::Text("text".into(), DUMMY_SP)
     }
@@ -0,0 +1,364 @@
+//! Folds the constant `:class` and `:style` bindings into the static attributes.
+//!
+//! ```vue
+//! <div class="a" :class="['b', { c: true, d: false }]" :style="{ fontSize: '12px' }"></div>
+//! ```
+//! becomes
+//! ```vue
+//! <div class="a b c" style="font-size: 12px"></div>
+//! ```
+//! so that the element needs no `normalizeClass`/`normalizeStyle` and can be hoisted.
+
+use fervid_core::{AttributeOrBinding, FervidAtom, StrOrExpr, VBindDirective};
+use swc_core::ecma::ast::{Expr, Lit, ObjectLit, Prop, PropName, PropOrSpread, Tpl};
+
+/// Replaces the `:class` and `:style` with the literal values by the static attributes,
+/// merging them with the existing `class` and `style` attributes.
+/// Bindings with modifiers (e.g. `:class.prop`) are left as is.
+pub fn fold_constant_class_and_style(attributes: &mut Vec<AttributeOrBinding>) {
+    let mut idx = 0;
+    while idx < attributes.len() {
+        let AttributeOrBinding::VBind(ref v_bind) = attributes[idx] else {
+            idx += 1;
+            continue;
+        };
+
+        let Some((name, folded)) = fold_v_bind(v_bind) else {
+            idx += 1;
+            continue;
+        };
+        let span = v_bind.span;
+
+        let existing = attributes.iter_mut().find_map(|attr| match attr {
+            AttributeOrBinding::RegularAttribute {
+                name: attr_name,
+                value,
+                raw_value,
+                ..
+            } if *attr_name == name => Some((value, raw_value)),
+            _ => None,
+        });
+
+        match existing {
+            // `class="a" :class="'b'"` is `class="a b"`,
+            // `style="color: red" :style="'color: blue'"` is `style="color: red; color: blue"`,
+            // where the latter wins just like with `normalizeStyle`
+            Some((value, raw_value)) => {
+                if !folded.is_empty() {
+                    let separator = if &*name == "class" { " " } else { "; " };
+                    let mut merged = value.trim_end().trim_end_matches(';').to_string();
+                    if !merged.is_empty() {
+                        merged.push_str(separator);
+                    }
+                    merged.push_str(&folded);
+                    *value = FervidAtom::from(merged);
+                    // The merged value is no longer in the source
+                    *raw_value = None;
+                }
+                attributes.remove(idx);
+            }
+
+            None => {
+                attributes[idx] = AttributeOrBinding::RegularAttribute {
+                    name,
+                    value: FervidAtom::from(folded),
+                    raw_value: None,
+                    span,
+                };
+                idx += 1;
+            }
+        }
+    }
+}
+
+/// Returns the attribute name and its static value when the binding is constant
+fn fold_v_bind(v_bind: &VBindDirective) -> Option<(FervidAtom, String)> {
+    let Some(StrOrExpr::Str(ref argument)) = v_bind.argument else {
+        return None;
+    };
+
+    if v_bind.is_camel || v_bind.is_prop || v_bind.is_attr {
+        return None;
+    }
+
+    let mut out = String::new();
+    match &**argument {
+        "class" => fold_class(&v_bind.value, &mut out)?,
+        "style" => fold_style(&v_bind.value, &mut out)?,
+        _ => return None,
+    }
+
+    Some((argument.to_owned(), out))
+}
+
+/// Follows `normalizeClass`: strings, arrays and objects with the boolean values
+fn fold_class(expr: &Expr, out: &mut String) -> Option<()> {
+    match expr {
+        Expr::Paren(paren) => fold_class(&paren.expr, out),
+
+        Expr::Lit(Lit::Str(s)) => {
+            push_class(&s.value, out);
+            Some(())
+        }
+
+        Expr::Tpl(tpl) => {
+            push_class(static_template(tpl)?, out);
+            Some(())
+        }
+
+        Expr::Array(array) => {
+            for elem in array.elems.iter() {
+                let elem = elem.as_ref()?;
+                if elem.spread.is_some() {
+                    return None;
+                }
+                fold_class(&elem.expr, out)?;
+            }
+            Some(())
+        }
+
+        Expr::Object(obj) => {
+            for (key, value) in static_props(obj)? {
+                let Expr::Lit(Lit::Bool(enabled)) = value else {
+                    return None;
+                };
+                if enabled.value {
+                    push_class(&key, out);
+                }
+            }
+            Some(())
+        }
+
+        _ => None,
+    }
+}
+
+/// Follows `normalizeStyle`: strings, arrays and objects with the string or number values
+fn fold_style(expr: &Expr, out: &mut String) -> Option<()> {
+    match expr {
+        Expr::Paren(paren) => fold_style(&paren.expr, out),
+
+        Expr::Lit(Lit::Str(s)) => {
+            push_declaration(&s.value, out);
+            Some(())
+        }
+
+        Expr::Tpl(tpl) => {
+            push_declaration(static_template(tpl)?, out);
+            Some(())
+        }
+
+        Expr::Array(array) => {
+            for elem in array.elems.iter() {
+                let elem = elem.as_ref()?;
+                if elem.spread.is_some() {
+                    return None;
+                }
+                fold_style(&elem.expr, out)?;
+            }
+            Some(())
+        }
+
+        Expr::Object(obj) => {
+            for (key, value) in static_props(obj)? {
+                let value = match value {
+                    Expr::Lit(Lit::Str(s)) => s.value.to_string(),
+                    Expr::Lit(Lit::Num(n)) => n.value.to_string(),
+                    _ => return None,
+                };
+
+                // `;` in the value would start a new declaration
+                if value.contains(';') {
+                    return None;
+                }
+
+                push_declaration(&format!("{}: {}", hyphenate(&key), value.trim()), out);
+            }
+            Some(())
+        }
+
+        _ => None,
+    }
+}
+
+fn push_class(class: &str, out: &mut String) {
+    let class = class.trim();
+    if class.is_empty() {
+        return;
+    }
+    if !out.is_empty() {
+        out.push(' ');
+    }
+    out.push_str(class);
+}
+
+fn push_declaration(declaration: &str, out: &mut String) {
+    let declaration = declaration.trim().trim_end_matches(';').trim_end();
+    if declaration.is_empty() {
+        return;
+    }
+    if !out.is_empty() {
+        out.push_str("; ");
+    }
+    out.push_str(declaration);
+}
+
+/// Object properties with the static keys, e.g. `{ foo: 1, 'bar-baz': 2 }`
+fn static_props(obj: &ObjectLit) -> Option<Vec<(String, &Expr)>> {
+    obj.props
+        .iter()
+        .map(|prop| {
+            let PropOrSpread::Prop(prop) = prop else {
+                return None;
+            };
+            let Prop::KeyValue(key_value) = prop.as_ref() else {
+                return None;
+            };
+
+            let key = match key_value.key {
+                PropName::Ident(ref ident) => ident.sym.to_string(),
+                PropName::Str(ref s) => s.value.to_string(),
+                _ => return None,
+            };
+
+            Some((key, key_value.value.as_ref()))
+        })
+        .collect()
+}
+
+/// Template literal without the expressions, e.g. `` `a b` ``
+fn static_template(tpl: &Tpl) -> Option<&str> {
+    if !tpl.exprs.is_empty() {
+        return None;
+    }
+
+    match tpl.quasis.as_slice() {
+        [quasi] => quasi.cooked.as_deref(),
+        _ => None,
+    }
+}
+
+/// `fontSize` -> `font-size`, the custom properties (`--foo`) are kept as is
+fn hyphenate(key: &str) -> String {
+    if key.starts_with("--") {
+        return key.to_string();
+    }
+
+    let mut out = String::with_capacity(key.len() + 2);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('-');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::DUMMY_SP;
+
+    use crate::test_utils::js;
+
+    use super::*;
+
+    fn v_bind(argument: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(argument.into())),
+            value: js(value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: DUMMY_SP,
+        })
+    }
+
+    fn regular(name: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: name.into(),
+            value: value.into(),
+            raw_value: None,
+            span: DUMMY_SP,
+        }
+    }
+
+    fn fold(mut attributes: Vec<AttributeOrBinding>) -> Vec<(String, String)> {
+        fold_constant_class_and_style(&mut attributes);
+        attributes
+            .into_iter()
+            .map(|attr| match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                    (name.to_string(), value.to_string())
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(argument)),
+                    ..
+                }) => (format!(":{argument}"), String::new()),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    macro_rules! pairs {
+        ($(($name: literal, $value: literal)),*) => {
+            vec![$(($name.to_string(), $value.to_string())),*]
+        };
+    }
+
+    #[test]
+    fn it_folds_constant_class() {
+        assert_eq!(
+            pairs![("class", "a b")],
+            fold(vec![v_bind("class", "'a b'")])
+        );
+        assert_eq!(
+            pairs![("class", "a c")],
+            fold(vec![v_bind("class", "['a', { b: false, c: true }]")])
+        );
+        assert_eq!(
+            pairs![("id", "foo"), ("class", "x a")],
+            fold(vec![
+                regular("id", "foo"),
+                regular("class", "x"),
+                v_bind("class", "`a`")
+            ])
+        );
+    }
+
+    #[test]
+    fn it_folds_constant_style() {
+        assert_eq!(
+            pairs![("style", "color: red; font-size: 12px; --gap: 4")],
+            fold(vec![v_bind(
+                "style",
+                "{ color: 'red', fontSize: '12px', '--gap': 4 }"
+            )])
+        );
+        assert_eq!(
+            pairs![("style", "margin: 0; color: blue")],
+            fold(vec![
+                regular("style", "margin: 0;"),
+                v_bind("style", "'color: blue'")
+            ])
+        );
+    }
+
+    #[test]
+    fn it_keeps_dynamic_class_and_style() {
+        assert_eq!(
+            pairs![(":class", "")],
+            fold(vec![v_bind("class", "['a', b]")])
+        );
+        assert_eq!(
+            pairs![(":class", "")],
+            fold(vec![v_bind("class", "{ a: b }")])
+        );
+        assert_eq!(
+            pairs![(":style", "")],
+            fold(vec![v_bind("style", "{ color: color }")])
+        );
+        assert_eq!(pairs![(":title", "")], fold(vec![v_bind("title", "'a'")]));
+    }
+}
@@ -1,13 +1,13 @@
 use fervid_core::{
-    fervid_atom, BindingTypes, FervidAtom, IntoIdent, PatchFlags, PatchHints, StrOrExpr,
-    TemplateGenerationMode, VModelDirective, VueImports,
+    fervid_atom, BindingTypes, ConstantTypes, FervidAtom, IntoIdent, PatchFlags, PatchHints,
+    StrOrExpr, TemplateGenerationMode, VModelDirective, VueImports,
 };
 use swc_core::{
     common::DUMMY_SP,
     ecma::{
         ast::{
-            ArrayLit, ArrayPat, AssignExpr, AssignOp, AssignTarget, AssignTargetPat, BindingIdent,
-            BlockStmt, CallExpr, Callee, CondExpr, Decl, Expr, ExprOrSpread, Ident, IdentName,
+            ArrayPat, AssignExpr, AssignOp, AssignTarget, AssignTargetPat, BindingIdent, BlockStmt,
+            CallExpr, Callee, CondExpr, Decl, Expr, ExprOrSpread, Ident, IdentName,
             KeyValuePatProp, KeyValueProp, Lit, MemberExpr, MemberProp, Null, ObjectLit, ObjectPat,
             ObjectPatProp, Pat, Prop, PropName, PropOrSpread, SimpleAssignTarget, Stmt, UpdateExpr,
             UpdateOp,
@@ -17,8 +17,8 @@ use swc_core::{
 };
 
 use crate::{
-    script::common::extract_variables_from_pat, template::js_builtins::JS_BUILTINS, BindingsHelper,
-    SetupBinding,
+    atoms::CSS_MODULES_HELPER, script::common::extract_variables_from_pat,
+    template::js_builtins::JS_BUILTINS, BindingsHelper, SetupBinding,
 };
 
 use super::utils::wrap_in_event_arrow;
@@ -26,7 +26,8 @@ use super::utils::wrap_in_event_arrow;
 struct TransformVisitor<'s> {
     current_scope: u32,
     bindings_helper: &'s mut BindingsHelper,
-    has_js_bindings: bool,
+    /// The lowest [`ConstantTypes`] of the identifiers met so far
+    constant_type: ConstantTypes,
     is_inline: bool,
 
     /// In ({ x } = y)
@@ -65,18 +66,25 @@ pub enum IdentTransformStrategy {
 
 pub trait BindingsHelperTransform {
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool;
+    fn transform_expr_constant(&mut self, expr: &mut Expr, scope_to_use: u32) -> ConstantTypes;
     fn transform_v_model(
         &mut self,
         v_model: &mut VModelDirective,
         scope_to_use: u32,
         patch_hints: &mut PatchHints,
-    );
+    ) -> bool;
     fn get_var_binding_type(&mut self, starting_scope: u32, variable: &FervidAtom) -> BindingTypes;
 }
 
 impl BindingsHelperTransform for BindingsHelper {
-    /// Transforms the template expression
+    /// Transforms the template expression.
+    /// Returns `true` when the expression depends on the state and needs to be patched.
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool {
+        self.transform_expr_constant(expr, scope_to_use) == ConstantTypes::NotConstant
+    }
+
+    /// Transforms the template expression and returns how constant it is
+    fn transform_expr_constant(&mut self, expr: &mut Expr, scope_to_use: u32) -> ConstantTypes {
         let is_inline = matches!(
             self.template_generation_mode,
             TemplateGenerationMode::Inline
@@ -84,7 +92,7 @@ impl BindingsHelperTransform for BindingsHelper {
         let mut visitor = TransformVisitor {
             current_scope: scope_to_use,
             bindings_helper: self,
-            has_js_bindings: false,
+            constant_type: ConstantTypes::CanStringify,
             is_inline,
             is_in_assign_target: false,
             is_in_destructure_assign: false,
@@ -95,34 +103,35 @@ impl BindingsHelperTransform for BindingsHelper {
         };
         expr.visit_mut_with(&mut visitor);
 
-        visitor.has_js_bindings
+        visitor.constant_type
     }
 
     /// Transforms `v-model` directive by producing
     /// `:value` expression and
     /// `@update:value` handler (`$event => modelValue = $event`).
+    ///
+    /// Returns `false` when the value cannot be assigned to, e.g. `v-model="a?.b"`.
+    /// The handler is not generated in this case.
     fn transform_v_model(
         &mut self,
         v_model: &mut VModelDirective,
         scope_to_use: u32,
         patch_hints: &mut PatchHints,
-    ) {
+    ) -> bool {
         // 0. Ensure that `v-model` value is a valid AssignTarget
-        let Some(assign_target) = convert_expr_to_assign_target(v_model.value.to_owned()) else {
-            // TODO Error
-            return;
-        };
-
-        // 1. Create handler: wrap in `$event => value = $event`
-        let event_expr = Box::new(Expr::Ident(FervidAtom::from("$event").into_ident()));
-        let mut handler = wrap_in_event_arrow(wrap_in_assignment(
-            assign_target,
-            event_expr,
-            AssignOp::Assign,
-        ));
-
-        // 2. Transform handler
-        {
+        let assign_target = convert_expr_to_assign_target(v_model.value.to_owned());
+        let is_valid = assign_target.is_some();
+
+        if let Some(assign_target) = assign_target {
+            // 1. Create handler: wrap in `$event => value = $event`
+            let event_expr = Box::new(Expr::Ident(FervidAtom::from("$event").into_ident()));
+            let mut handler = wrap_in_event_arrow(wrap_in_assignment(
+                assign_target,
+                event_expr,
+                AssignOp::Assign,
+            ));
+
+            // 2. Transform handler
             let is_inline = matches!(
                 self.template_generation_mode,
                 TemplateGenerationMode::Inline
@@ -130,7 +139,7 @@ impl BindingsHelperTransform for BindingsHelper {
             let mut visitor = TransformVisitor {
                 current_scope: scope_to_use,
                 bindings_helper: self,
-                has_js_bindings: false,
+                constant_type: ConstantTypes::CanStringify,
                 is_inline,
                 is_in_assign_target: false,
                 is_in_destructure_assign: false,
@@ -140,35 +149,44 @@ impl BindingsHelperTransform for BindingsHelper {
                 should_consume_update_expr: false,
             };
             handler.visit_mut_with(&mut visitor);
-        }
 
-        // 3. Assign handler
-        v_model.update_handler = Some(handler);
+            // 3. Assign handler
+            v_model.update_handler = Some(handler);
+        }
 
         // 4. Transform value
         self.transform_expr(&mut v_model.value, scope_to_use);
 
-        // 5. (Optional) Transform dynamic argument and set patch hints
-        match v_model.argument {
+        // 5. (Optional) Transform dynamic argument and set patch hints.
+        // Multiple `v-model`s on one element are merged into the same hints.
+        let model_name = match v_model.argument {
             Some(StrOrExpr::Expr(ref mut expr)) => {
                 self.transform_expr(expr, scope_to_use);
+
+                // From docs: [FULL_PROPS is] exclusive with CLASS, STYLE and PROPS.
+                patch_hints.flags &= !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
                 patch_hints.flags |= PatchFlags::FullProps;
+                patch_hints.props.clear();
+                return is_valid;
             }
+            Some(StrOrExpr::Str(ref argument)) => argument.to_owned(),
+            None => fervid_atom!("modelValue"),
+        };
 
-            Some(StrOrExpr::Str(ref argument)) => {
-                patch_hints.flags |= PatchFlags::Props;
-                patch_hints.props.push(argument.to_owned());
-            }
+        // Dynamic keys already make all the props dynamic
+        if patch_hints.flags.contains(PatchFlags::FullProps) {
+            return is_valid;
+        }
 
-            None => {
-                patch_hints.flags |= PatchFlags::Props;
-                patch_hints.props.push(fervid_atom!("modelValue"));
-            }
+        patch_hints.flags |= PatchFlags::Props;
+        if !patch_hints.props.contains(&model_name) {
+            patch_hints.props.push(model_name);
         }
 
-        // TODO Check that SetupConst or SetupReactiveConst are not used as a `v-model` value. Report hard error in this case.
-        // TODO Check in general in all cases
+        // TODO Check in general in all cases, not only for `v-model` (see `TemplateErrorKind::VModelOnConst`)
         // DOCTEXT: Disallow `SetupConst` or `SetupReactiveConst` to be used as a `v-model` value or as an assignment target.
+
+        is_valid
     }
 
     fn get_var_binding_type(&mut self, starting_scope: u32, variable: &FervidAtom) -> BindingTypes {
@@ -471,8 +489,12 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                             value: Box::new(value_expr),
                         })
                         .into();
-                        self.has_js_bindings = true;
+                        self.lower_constant_type(ConstantTypes::NotConstant);
                     } else if let Some(keyvalue) = prop.as_mut_key_value() {
+                        // Only the computed keys are expressions, e.g. `{ [key]: value }`
+                        if let PropName::Computed(ref mut computed) = keyvalue.key {
+                            computed.expr.visit_mut_with(self);
+                        }
                         keyvalue.value.visit_mut_with(self);
                     }
                 }
@@ -663,11 +685,9 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                 ObjectPatProp::KeyValue(key_value) => {
                     key_value.value.visit_mut_with(self);
 
-                    match key_value.key {
-                        // TODO Finish the implementation
-                        PropName::Ident(_) => todo!(),
-                        PropName::Computed(_) => todo!(),
-                        PropName::Str(_) | PropName::Num(_) | PropName::BigInt(_) => {}
+                    // Only the computed key is an expression, e.g. `{ [key]: value }`
+                    if let PropName::Computed(ref mut computed) = key_value.key {
+                        computed.expr.visit_mut_with(self);
                     }
                 }
 
@@ -708,6 +728,11 @@ impl<'s> VisitMut for TransformVisitor<'s> {
 }
 
 impl TransformVisitor<'_> {
+    /// Several identifiers in one expression make it as constant as the least constant of them
+    fn lower_constant_type(&mut self, constant_type: ConstantTypes) {
+        self.constant_type = self.constant_type.min(constant_type);
+    }
+
     /// Determines the strategy with which an Ident needs to be transformed.
     /// This function is needed because SWC's AST is strongly-typed and we cannot simply
     /// transform the Ident as the official compiler does.
@@ -716,7 +741,7 @@ impl TransformVisitor<'_> {
 
         // Try to find variable in the local vars (e.g. arrow function params)
         if let Some(_) = self.local_vars.iter().rfind(|it| &it.0 == symbol) {
-            self.has_js_bindings = true;
+            self.lower_constant_type(ConstantTypes::NotConstant);
             return IdentTransformStrategy::LeaveUnchanged;
         }
 
@@ -726,32 +751,50 @@ impl TransformVisitor<'_> {
 
         // Template local binding doesn't need any processing
         if let BindingTypes::TemplateLocal = binding_type {
-            self.has_js_bindings = true;
+            self.lower_constant_type(ConstantTypes::NotConstant);
             return IdentTransformStrategy::LeaveUnchanged;
         }
 
+        // Identifiers are resolved by `with (_ctx)` at runtime
+        if self.bindings_helper.with_ctx {
+            self.lower_constant_type(match binding_type {
+                BindingTypes::JsGlobal => ConstantTypes::CanHoist,
+                BindingTypes::LiteralConst => ConstantTypes::CanSkipPatch,
+                _ => ConstantTypes::NotConstant,
+            });
+            return IdentTransformStrategy::LeaveUnchanged;
+        }
+
+        // CSS Modules compiled by fervid are static and live in the module scope
+        if matches!(binding_type, BindingTypes::Unresolved)
+            && self.bindings_helper.css_modules.contains(symbol)
+        {
+            self.lower_constant_type(ConstantTypes::CanHoist);
+            return IdentTransformStrategy::Prefix(CSS_MODULES_HELPER.to_owned());
+        }
+
         // Get the prefix which fits the scope (e.g. `_ctx.` for unknown scopes, `$setup.` for setup scope)
         if let Some(prefix) = get_prefix(&binding_type, self.is_inline) {
-            self.has_js_bindings = true;
+            self.lower_constant_type(ConstantTypes::NotConstant);
             return IdentTransformStrategy::Prefix(prefix);
         }
 
+        // Globals live outside of the component, the rest of the constants live in `setup`
+        // and thus cannot be hoisted out of `render`
+        self.lower_constant_type(match binding_type {
+            BindingTypes::JsGlobal => ConstantTypes::CanHoist,
+            BindingTypes::SetupLet
+            | BindingTypes::SetupReactiveConst
+            | BindingTypes::SetupMaybeRef
+            | BindingTypes::SetupRef => ConstantTypes::NotConstant,
+            _ => ConstantTypes::CanSkipPatch,
+        });
+
         // Non-inline logic ends here
         if !self.is_inline {
             return IdentTransformStrategy::LeaveUnchanged;
         }
 
-        // Add a flag that binding is dynamic
-        if matches!(
-            binding_type,
-            BindingTypes::SetupLet
-                | BindingTypes::SetupReactiveConst
-                | BindingTypes::SetupMaybeRef
-                | BindingTypes::SetupRef
-        ) {
-            self.has_js_bindings = true;
-        }
-
         match binding_type {
             // Update expression with MaybeRef: `maybe++` -> `maybe.value++`
             BindingTypes::SetupMaybeRef
@@ -950,14 +993,6 @@ fn wrap_in_assignment(lhs: AssignTarget, rhs_expr: Box<Expr>, op: AssignOp) -> B
 fn convert_expr_to_assign_target(expr: Box<Expr>) -> Option<AssignTarget> {
     // Because AssignTarget is strongly typed, we have to map from `Expr` to `AssignTarget`
     match *expr {
-        Expr::Array(arr) => Some(AssignTarget::Pat(AssignTargetPat::Array(
-            convert_arr_lit_to_pat(arr),
-        ))),
-
-        Expr::Object(obj) => Some(AssignTarget::Pat(AssignTargetPat::Object(
-            convert_obj_lit_to_pat(obj),
-        ))),
-
         Expr::Ident(ident) => Some(AssignTarget::Simple(SimpleAssignTarget::Ident(
             BindingIdent {
                 id: ident,
@@ -970,9 +1005,6 @@ fn convert_expr_to_assign_target(expr: Box<Expr>) -> Option<AssignTarget> {
         Expr::SuperProp(super_prop) => Some(AssignTarget::Simple(SimpleAssignTarget::SuperProp(
             super_prop,
         ))),
-        Expr::OptChain(opt_chain) => Some(AssignTarget::Simple(SimpleAssignTarget::OptChain(
-            opt_chain,
-        ))),
         Expr::TsNonNull(non_null) => Some(AssignTarget::Simple(SimpleAssignTarget::TsNonNull(
             non_null,
         ))),
@@ -985,19 +1017,12 @@ fn convert_expr_to_assign_target(expr: Box<Expr>) -> Option<AssignTarget> {
             SimpleAssignTarget::TsTypeAssertion(type_assert),
         )),
 
-        // Maybe some other expressions can be a valid assignment target, but I trust SWC here
+        // Maybe some other expressions can be a valid assignment target, but I trust SWC here.
+        // Optional chains are not, e.g. `a?.b = 1` is a syntax error.
         _ => None,
     }
 }
 
-fn convert_arr_lit_to_pat(_arr_lit: ArrayLit) -> ArrayPat {
-    todo!()
-}
-
-fn convert_obj_lit_to_pat(_obj_lit: ObjectLit) -> ObjectPat {
-    todo!()
-}
-
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -1006,7 +1031,8 @@ mod tests {
         BindingsHelper, SetupBinding, TemplateScope,
     };
     use fervid_core::{
-        BindingTypes, FervidAtom, PatchHints, StrOrExpr, TemplateGenerationMode, VModelDirective,
+        BindingTypes, ConstantTypes, FervidAtom, PatchFlags, PatchHints, StrOrExpr,
+        TemplateGenerationMode, VModelDirective,
     };
     use smallvec::SmallVec;
     use swc_core::common::DUMMY_SP;
@@ -1064,6 +1090,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_transforms_object_spreads() {
+        let mut helper = BindingsHelper::default();
+
+        macro_rules! test {
+            ($expr: literal, $expected: literal) => {
+                let mut expr = js($expr);
+                assert!(helper.transform_expr(&mut expr, 0));
+
+                assert_eq!(to_str(&expr), $expected);
+            };
+        }
+
+        test!("{ ...a, b: 1 }", "{..._ctx.a,b:1}");
+        test!(
+            "{ b, ...a.c, [d]: e }",
+            "{b:_ctx.b,..._ctx.a.c,[_ctx.d]:_ctx.e}"
+        );
+        test!("{ ...(x => x)(a) }", "{...(x=>x)(_ctx.a)}");
+    }
+
+    #[test]
+    fn it_transforms_destructuring_assignments() {
+        let mut helper = BindingsHelper::default();
+
+        macro_rules! test {
+            ($expr: literal, $expected: literal) => {
+                let mut expr = js($expr);
+                helper.transform_expr(&mut expr, 0);
+
+                assert_eq!(to_str(&expr), $expected);
+            };
+        }
+
+        test!("([a, b] = c)", "([_ctx.a,_ctx.b]=_ctx.c)");
+        test!(
+            "({ a: b, [c]: d } = e)",
+            "({a:_ctx.b,[_ctx.c]:_ctx.d}=_ctx.e)"
+        );
+    }
+
     #[test]
     fn it_transforms_v_model() {
         let mut helper = BindingsHelper::default();
@@ -1233,6 +1300,100 @@ mod tests {
         test!("\"string\"", "\"string\"");
     }
 
+    #[test]
+    fn it_transforms_v_model_member_paths() {
+        let mut helper = BindingsHelper::default();
+        helper.setup_bindings.push(SetupBinding(
+            FervidAtom::from("form"),
+            BindingTypes::SetupReactiveConst,
+        ));
+
+        macro_rules! transform {
+            ($value: literal) => {{
+                let mut v_model = VModelDirective {
+                    argument: None,
+                    value: js($value),
+                    update_handler: None,
+                    modifiers: vec![],
+                    span: DUMMY_SP,
+                };
+                let is_valid =
+                    helper.transform_v_model(&mut v_model, 0, &mut PatchHints::default());
+                (is_valid, v_model)
+            }};
+        }
+
+        // The handler assigns to the exact member expression
+        let (is_valid, v_model) = transform!("form.user.name");
+        assert!(is_valid);
+        assert_eq!(to_str(&v_model.value), "$setup.form.user.name");
+        assert_eq!(
+            to_str(&v_model.update_handler.expect("Handler cannot be None")),
+            "$event=>$setup.form.user.name=$event"
+        );
+
+        let (is_valid, v_model) = transform!("form.users[index].name");
+        assert!(is_valid);
+        assert_eq!(
+            to_str(&v_model.update_handler.expect("Handler cannot be None")),
+            "$event=>$setup.form.users[_ctx.index].name=$event"
+        );
+
+        // Optional chains cannot be assigned to
+        let (is_valid, v_model) = transform!("form?.user.name");
+        assert!(!is_valid);
+        assert!(v_model.update_handler.is_none());
+        assert_eq!(to_str(&v_model.value), "$setup.form?.user.name");
+
+        // Neither can the literals
+        let (is_valid, v_model) = transform!("[form.user, form.name]");
+        assert!(!is_valid);
+        assert!(v_model.update_handler.is_none());
+    }
+
+    #[test]
+    fn it_merges_patch_hints_of_multiple_v_models() {
+        let mut helper = BindingsHelper::default();
+
+        macro_rules! v_model {
+            ($argument: expr) => {
+                VModelDirective {
+                    argument: $argument,
+                    value: js("dummy"),
+                    update_handler: None,
+                    modifiers: vec![],
+                    span: DUMMY_SP,
+                }
+            };
+        }
+
+        // v-model:first="dummy" v-model:second="dummy" v-model:first="dummy"
+        let mut patch_hints = PatchHints::default();
+        for argument in ["first", "second", "first"] {
+            let mut v_model = v_model!(Some(StrOrExpr::Str(FervidAtom::from(argument))));
+            helper.transform_v_model(&mut v_model, 0, &mut patch_hints);
+        }
+        assert!(patch_hints.flags.contains(PatchFlags::Props));
+        assert_eq!(
+            vec![FervidAtom::from("first"), FervidAtom::from("second")],
+            patch_hints.props
+        );
+
+        // v-model="dummy" v-model:[dynamic]="dummy" v-model:second="dummy"
+        let mut patch_hints = PatchHints::default();
+        for argument in [
+            None,
+            Some(StrOrExpr::Expr(js("dynamic"))),
+            Some(StrOrExpr::Str(FervidAtom::from("second"))),
+        ] {
+            let mut v_model = v_model!(argument);
+            helper.transform_v_model(&mut v_model, 0, &mut patch_hints);
+        }
+        assert!(patch_hints.flags.contains(PatchFlags::FullProps));
+        assert!(!patch_hints.flags.contains(PatchFlags::Props));
+        assert!(patch_hints.props.is_empty());
+    }
+
     #[test]
     fn it_works_with_template_scope_hierarchy() {
         let v_root = FervidAtom::from("root");
@@ -1404,6 +1565,81 @@ mod tests {
         println!("Elapsed total: {:?}", st0.elapsed())
     }
 
+    #[test]
+    fn it_leaves_identifiers_unprefixed_with_ctx() {
+        let mut helper = BindingsHelper {
+            with_ctx: true,
+            ..Default::default()
+        };
+        helper.setup_bindings.push(SetupBinding(
+            FervidAtom::from("foo"),
+            BindingTypes::SetupRef,
+        ));
+
+        let mut expr = js("foo + bar.baz + Math.max(x => x)");
+        assert!(helper.transform_expr(&mut expr, 0));
+        assert_eq!(to_str(&expr), "foo+bar.baz+Math.max(x=>x)");
+
+        // Only globals are not dynamic
+        let mut expr = js("Math.PI");
+        assert!(!helper.transform_expr(&mut expr, 0));
+    }
+
+    #[test]
+    fn it_transforms_css_modules() {
+        let mut helper = BindingsHelper::default();
+        helper.css_modules.push(FervidAtom::from("$style"));
+        helper.css_modules.push(FervidAtom::from("classes"));
+
+        // Compiled modules are static
+        let mut expr = js("[$style.foo, classes.bar]");
+        assert!(!helper.transform_expr(&mut expr, 0));
+        assert_eq!(to_str(&expr), "[__cssModules.$style.foo,__cssModules.classes.bar]");
+
+        // Other modules come from the context
+        let mut expr = js("$other.foo");
+        assert!(helper.transform_expr(&mut expr, 0));
+        assert_eq!(to_str(&expr), "_ctx.$other.foo");
+    }
+
+    #[test]
+    fn it_determines_constant_types() {
+        let mut helper = BindingsHelper {
+            template_generation_mode: TemplateGenerationMode::Inline,
+            ..Default::default()
+        };
+        helper.setup_bindings.push(SetupBinding(
+            FervidAtom::from("konst"),
+            BindingTypes::SetupConst,
+        ));
+        helper.setup_bindings.push(SetupBinding(
+            FervidAtom::from("count"),
+            BindingTypes::SetupRef,
+        ));
+        helper.css_modules.push(FervidAtom::from("$style"));
+
+        macro_rules! check {
+            ($expected: expr, $input: literal) => {
+                let mut expr = js($input);
+                assert_eq!(
+                    $expected,
+                    helper.transform_expr_constant(&mut expr, 0),
+                    "{}",
+                    $input
+                );
+            };
+        }
+
+        check!(ConstantTypes::CanStringify, "'foo' + 1");
+        check!(ConstantTypes::CanStringify, "{ a: [1, true] }");
+        check!(ConstantTypes::CanHoist, "Math.max(1, 2)");
+        check!(ConstantTypes::CanHoist, "$style.foo");
+        check!(ConstantTypes::CanSkipPatch, "konst + Math.PI");
+        check!(ConstantTypes::NotConstant, "count + konst");
+        check!(ConstantTypes::NotConstant, "[1, unknown]");
+        check!(ConstantTypes::NotConstant, "items.map(x => x)");
+    }
+
     fn js(input: &str) -> Box<swc_core::ecma::ast::Expr> {
         parse_javascript_expr(input, 0, Default::default())
             .expect("js expects the input to be parseable")
@@ -0,0 +1,404 @@
+//! Detects the fully static element subtrees, so that they are created once
+//! instead of on every render.
+//!
+//! ```vue
+//! <div>
+//!   <p class="intro">Static</p>
+//!   {{ msg }}
+//! </div>
+//! ```
+//! becomes
+//! ```js
+//! const _hoisted_1 = _createElementVNode("p", { class: "intro" }, "Static", -1)
+//! // and in `render`
+//! _createElementVNode("div", null, [_hoisted_1, _createTextVNode(_toDisplayString(msg), 1)])
+//! ```
+
+use fervid_core::{
+    AttributeOrBinding, ConstantTypes, ElementKind, ElementNode, Node, SfcTemplateBlock,
+    VueDirectives,
+};
+
+/// Marks the outermost fully static elements of the template with [`PatchHints::hoisted`].
+/// Should be called after [`transform_and_record_template`], because it relies on the patch flags.
+///
+/// The only root element is never hoisted, because it is rendered as a block.
+///
+/// [`PatchHints::hoisted`]: fervid_core::PatchHints::hoisted
+/// [`transform_and_record_template`]: super::transform_and_record_template
+pub fn hoist_static_nodes(template: &mut SfcTemplateBlock) {
+    if let [Node::Element(root)] = template.roots.as_mut_slice() {
+        hoist_element_children(root);
+        return;
+    }
+
+    hoist_children(&mut template.roots);
+}
+
+/// Hoists the static children of a node which is not static itself
+fn hoist_children(children: &mut [Node]) {
+    let constant_types: Vec<ConstantTypes> = children.iter_mut().map(visit_node).collect();
+    mark_hoisted(children, &constant_types);
+}
+
+/// Marks the children which can be created once at the module level
+fn mark_hoisted(children: &mut [Node], constant_types: &[ConstantTypes]) {
+    for (child, constant_type) in children.iter_mut().zip(constant_types) {
+        if let Node::Element(element_node) = child {
+            if *constant_type >= ConstantTypes::CanHoist {
+                element_node.patch_hints.hoisted = true;
+            }
+        }
+    }
+}
+
+/// Hoists the static children of an element which is not static itself
+fn hoist_element_children(element_node: &mut ElementNode) {
+    // Direct children of the built-ins are patched by the runtime, e.g. by `<Transition>` hooks
+    if let ElementKind::Builtin(_) = element_node.kind {
+        for child in element_node.children.iter_mut() {
+            visit_node(child);
+        }
+        return;
+    }
+
+    hoist_children(&mut element_node.children);
+}
+
+/// Returns how constant the node and all its children are.
+/// When the node cannot be hoisted, its static children are hoisted instead.
+fn visit_node(node: &mut Node) -> ConstantTypes {
+    match node {
+        Node::Text(_, _) | Node::Comment(_, _) => ConstantTypes::CanStringify,
+
+        Node::Interpolation(_) => ConstantTypes::NotConstant,
+
+        Node::Element(element_node) => {
+            let element_constant_type = get_element_constant_type(element_node);
+            if element_constant_type < ConstantTypes::CanHoist {
+                hoist_element_children(element_node);
+                return ConstantTypes::NotConstant;
+            }
+
+            let constant_types: Vec<ConstantTypes> =
+                element_node.children.iter_mut().map(visit_node).collect();
+            let subtree_constant_type = constant_types
+                .iter()
+                .fold(element_constant_type, |acc, it| acc.min(*it));
+            if subtree_constant_type >= ConstantTypes::CanHoist {
+                return subtree_constant_type;
+            }
+
+            mark_hoisted(&mut element_node.children, &constant_types);
+            ConstantTypes::NotConstant
+        }
+
+        // Branches are never static, because they get the `key`s
+        Node::ConditionalSeq(conditional_seq) => {
+            hoist_element_children(&mut conditional_seq.if_node.node);
+            for else_if_node in conditional_seq.else_if_nodes.iter_mut() {
+                hoist_element_children(&mut else_if_node.node);
+            }
+            if let Some(ref mut else_node) = conditional_seq.else_node {
+                hoist_element_children(else_node);
+            }
+
+            ConstantTypes::NotConstant
+        }
+    }
+}
+
+/// Checks the element itself without its children.
+/// The attributes are checked by the transformer, see [`PatchHints::constant_type`].
+///
+/// [`PatchHints::constant_type`]: fervid_core::PatchHints::constant_type
+fn get_element_constant_type(element_node: &ElementNode) -> ConstantTypes {
+    let is_plain_element = matches!(element_node.kind, ElementKind::Element)
+        && element_node.starting_tag.tag_name != "template";
+
+    let has_no_handlers = element_node
+        .starting_tag
+        .attributes
+        .iter()
+        .all(|attr| !matches!(attr, AttributeOrBinding::VOn(_)));
+
+    let has_static_directives = element_node
+        .starting_tag
+        .directives
+        .as_deref()
+        .map_or(true, has_only_static_directives);
+
+    let is_static = is_plain_element
+        && has_no_handlers
+        && has_static_directives
+        && element_node.patch_hints.flags.is_empty()
+        && element_node.patch_hints.props.is_empty()
+        && !element_node.patch_hints.should_use_block;
+
+    if is_static {
+        element_node.patch_hints.constant_type
+    } else {
+        ConstantTypes::NotConstant
+    }
+}
+
+/// `v-pre` and `v-cloak` do not change the rendering
+fn has_only_static_directives(directives: &VueDirectives) -> bool {
+    let VueDirectives {
+        custom,
+        v_cloak: _,
+        v_else,
+        v_else_if,
+        v_for,
+        v_html,
+        v_if,
+        v_memo,
+        v_model,
+        v_once,
+        v_pre: _,
+        v_show,
+        v_slot,
+        v_text,
+    } = directives;
+
+    custom.is_empty()
+        && v_else.is_none()
+        && v_else_if.is_none()
+        && v_for.is_none()
+        && v_html.is_none()
+        && v_if.is_none()
+        && v_memo.is_none()
+        && v_model.is_empty()
+        && v_once.is_none()
+        && v_show.is_none()
+        && v_slot.is_none()
+        && v_text.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        Conditional, ConditionalNodeSequence, Interpolation, PatchFlags, PatchHints, StartingTag,
+    };
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+    use crate::test_utils::js;
+
+    fn element(tag_name: &str, children: Vec<Node>) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn interpolation(expr: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: js(expr),
+            template_scope: 0,
+            patch_flag: true,
+            span: DUMMY_SP,
+        })
+    }
+
+    fn is_hoisted(node: &Node) -> bool {
+        matches!(node, Node::Element(element_node) if element_node.patch_hints.hoisted)
+    }
+
+    #[test]
+    fn it_hoists_static_children_of_dynamic_root() {
+        // <div><p class="intro"><b>Static</b></p>{{ msg }}</div>
+        let mut p = element(
+            "p",
+            vec![Node::Element(element(
+                "b",
+                vec![Node::Text("Static".into(), DUMMY_SP)],
+            ))],
+        );
+        p.starting_tag
+            .attributes
+            .push(AttributeOrBinding::RegularAttribute {
+                name: "class".into(),
+                value: "intro".into(),
+                raw_value: None,
+                span: DUMMY_SP,
+            });
+        let mut template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(element(
+                "div",
+                vec![Node::Element(p), interpolation("msg")],
+            ))],
+            span: DUMMY_SP,
+        };
+
+        hoist_static_nodes(&mut template);
+
+        let Node::Element(ref root) = template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert!(!root.patch_hints.hoisted);
+        assert!(is_hoisted(&root.children[0]));
+        assert!(!is_hoisted(&root.children[1]));
+
+        // Only the outermost static element is hoisted
+        let Node::Element(ref p) = root.children[0] else {
+            panic!("Child is not an element")
+        };
+        assert!(!is_hoisted(&p.children[0]));
+    }
+
+    #[test]
+    fn it_does_not_hoist_single_static_root() {
+        // <div><span>a</span></div>
+        let mut template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(element(
+                "div",
+                vec![Node::Element(element(
+                    "span",
+                    vec![Node::Text("a".into(), DUMMY_SP)],
+                ))],
+            ))],
+            span: DUMMY_SP,
+        };
+
+        hoist_static_nodes(&mut template);
+
+        let Node::Element(ref root) = template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert!(!root.patch_hints.hoisted);
+        assert!(is_hoisted(&root.children[0]));
+    }
+
+    #[test]
+    fn it_hoists_static_roots_of_fragment() {
+        // <h1>Title</h1><p>{{ msg }}</p>
+        let mut template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                Node::Element(element("h1", vec![Node::Text("Title".into(), DUMMY_SP)])),
+                Node::Element(element("p", vec![interpolation("msg")])),
+            ],
+            span: DUMMY_SP,
+        };
+
+        hoist_static_nodes(&mut template);
+
+        assert!(is_hoisted(&template.roots[0]));
+        assert!(!is_hoisted(&template.roots[1]));
+    }
+
+    #[test]
+    fn it_hoists_by_constant_type() {
+        // <span :tabindex="-1">a</span>
+        let mut with_global = element("span", vec![Node::Text("a".into(), DUMMY_SP)]);
+        with_global.patch_hints.constant_type = ConstantTypes::CanHoist;
+
+        // <span :title="setupConst">b</span>
+        let mut with_setup_const = element("span", vec![Node::Text("b".into(), DUMMY_SP)]);
+        with_setup_const.patch_hints.constant_type = ConstantTypes::CanSkipPatch;
+
+        // <div><i>c</i></div> where only `<i>` can be hoisted
+        let mut parent = element(
+            "div",
+            vec![Node::Element(element(
+                "i",
+                vec![Node::Text("c".into(), DUMMY_SP)],
+            ))],
+        );
+        parent.patch_hints.constant_type = ConstantTypes::CanSkipPatch;
+
+        let mut template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                Node::Element(with_global),
+                Node::Element(with_setup_const),
+                Node::Element(parent),
+            ],
+            span: DUMMY_SP,
+        };
+
+        hoist_static_nodes(&mut template);
+
+        assert!(is_hoisted(&template.roots[0]));
+        assert!(!is_hoisted(&template.roots[1]));
+        assert!(!is_hoisted(&template.roots[2]));
+
+        let Node::Element(ref parent) = template.roots[2] else {
+            panic!("Root is not an element")
+        };
+        assert!(is_hoisted(&parent.children[0]));
+    }
+
+    #[test]
+    fn it_does_not_hoist_dynamic_elements() {
+        // <span :id="id"></span>
+        let mut with_binding = element("span", vec![]);
+        with_binding.patch_hints = PatchHints {
+            flags: PatchFlags::Props.into(),
+            props: vec!["id".into()],
+            ..Default::default()
+        };
+
+        // <MyComp></MyComp>
+        let mut component = element("MyComp", vec![]);
+        component.kind = ElementKind::Component;
+
+        // <span v-once>static</span>
+        let mut with_v_once = element("span", vec![Node::Text("static".into(), DUMMY_SP)]);
+        with_v_once.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_once: Some(()),
+            ..Default::default()
+        }));
+
+        // <div v-if="ok"><i>static</i></div>
+        let conditional = Node::ConditionalSeq(ConditionalNodeSequence {
+            if_node: Box::new(Conditional {
+                condition: *js("ok"),
+                node: element(
+                    "div",
+                    vec![Node::Element(element(
+                        "i",
+                        vec![Node::Text("static".into(), DUMMY_SP)],
+                    ))],
+                ),
+            }),
+            else_if_nodes: vec![],
+            else_node: None,
+        });
+
+        let mut template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                Node::Element(with_binding),
+                Node::Element(component),
+                Node::Element(with_v_once),
+                conditional,
+            ],
+            span: DUMMY_SP,
+        };
+
+        hoist_static_nodes(&mut template);
+
+        assert!(!is_hoisted(&template.roots[0]));
+        assert!(!is_hoisted(&template.roots[1]));
+        assert!(!is_hoisted(&template.roots[2]));
+
+        // Static children of the branches are hoisted
+        let Node::ConditionalSeq(ref conditional_seq) = template.roots[3] else {
+            panic!("Not a conditional sequence")
+        };
+        assert!(!conditional_seq.if_node.node.patch_hints.hoisted);
+        assert!(is_hoisted(&conditional_seq.if_node.node.children[0]));
+    }
+}
@@ -438,6 +438,10 @@ mod tests {
             bindings_helper,
             current_scope: 0,
             v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
         }
     }
 }
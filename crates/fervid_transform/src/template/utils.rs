@@ -1,5 +1,14 @@
 use fervid_core::FervidAtom;
-use swc_core::{common::DUMMY_SP, ecma::ast::{ArrowExpr, BindingIdent, BlockStmtOrExpr, Expr, Ident, Pat}};
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrowExpr, AssignExpr, BindingIdent, BlockStmtOrExpr, Expr, Function, Ident, Pat,
+            UnaryExpr, UnaryOp, UpdateExpr,
+        },
+        visit::{Visit, VisitWith},
+    },
+};
 
 /// `foo-bar-baz` -> `FooBarBaz`
 #[inline]
@@ -64,3 +73,38 @@ pub fn wrap_in_event_arrow(expr: Box<Expr>) -> Box<Expr> {
         return_type: None,
     }))
 }
+
+/// Finds an assignment, an update (e.g. `count++`) or a `delete` evaluated by the expression itself.
+/// Nested functions are skipped, because they run later, e.g. as the event handlers.
+pub(crate) fn find_side_effect(expr: &Expr) -> Option<Span> {
+    let mut finder = SideEffectFinder { span: None };
+    expr.visit_with(&mut finder);
+    finder.span
+}
+
+struct SideEffectFinder {
+    span: Option<Span>,
+}
+
+impl Visit for SideEffectFinder {
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        self.span.get_or_insert(n.span);
+    }
+
+    fn visit_update_expr(&mut self, n: &UpdateExpr) {
+        self.span.get_or_insert(n.span);
+    }
+
+    fn visit_unary_expr(&mut self, n: &UnaryExpr) {
+        if let UnaryOp::Delete = n.op {
+            self.span.get_or_insert(n.span);
+            return;
+        }
+
+        n.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_function(&mut self, _: &Function) {}
+}
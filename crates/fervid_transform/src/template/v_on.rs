@@ -1,12 +1,17 @@
 use fervid_core::{fervid_atom, BindingTypes, FervidAtom, IntoIdent, StrOrExpr, VOnDirective};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{
-        ArrowExpr, BinExpr, BinaryOp, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr,
-        ExprOrSpread, Pat, RestPat,
+    ecma::{
+        ast::{
+            ArrowExpr, BinExpr, BinaryOp, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr,
+            ExprOrSpread, Ident, Pat, RestPat,
+        },
+        visit::{Visit, VisitWith},
     },
 };
 
+use crate::BindingsHelper;
+
 use super::{
     ast_transform::TemplateVisitor,
     expr_transform::BindingsHelperTransform,
@@ -28,6 +33,14 @@ impl TemplateVisitor<'_> {
             None => {}
         }
 
+        // `v-on="handlers"` is not cached, same as in the official compiler
+        let should_cache = self.bindings_helper.cache_handlers && v_on.event.is_some();
+
+        // Empty handler, e.g. in `@click.prevent`, never changes
+        if v_on.handler.is_none() {
+            v_on.is_cacheable = should_cache;
+        }
+
         if let Some(mut handler) = v_on.handler.take() {
             // 1. Check the handler shape
             let mut is_member_or_paren = false;
@@ -99,6 +112,14 @@ impl TemplateVisitor<'_> {
                 _ => {}
             }
 
+            // Constant identifiers are used as-is and do not need caching.
+            // Handlers using the template scope, e.g. a `v-for` item, change between renders.
+            let is_const_ident =
+                matches!(unwrap_parens(&handler), Expr::Ident(_)) && !is_non_const_ident;
+            v_on.is_cacheable = should_cache
+                && !is_const_ident
+                && !has_scope_ref(self.bindings_helper, &handler, scope_to_use);
+
             // 2. Add `$event` when needed
             if needs_event {
                 handler = wrap_in_event_arrow(handler);
@@ -241,6 +262,58 @@ fn wrap_in_args_arrow(mut expr: Box<Expr>, needs_check: bool) -> Box<Expr> {
     }))
 }
 
+/// Checks whether the expression uses the variables of the template scope, e.g. of `v-for` or `v-slot`
+fn has_scope_ref(bindings_helper: &BindingsHelper, expr: &Expr, scope_to_use: u32) -> bool {
+    struct ScopeRefVisitor<'h> {
+        bindings_helper: &'h BindingsHelper,
+        scope_to_use: u32,
+        has_scope_ref: bool,
+    }
+
+    impl Visit for ScopeRefVisitor<'_> {
+        fn visit_ident(&mut self, ident: &Ident) {
+            self.has_scope_ref |=
+                is_template_local(self.bindings_helper, self.scope_to_use, &ident.sym);
+        }
+    }
+
+    let mut visitor = ScopeRefVisitor {
+        bindings_helper,
+        scope_to_use,
+        has_scope_ref: false,
+    };
+    expr.visit_with(&mut visitor);
+
+    visitor.has_scope_ref
+}
+
+/// Looks up the variable in the template scope and its parents
+fn is_template_local(
+    bindings_helper: &BindingsHelper,
+    starting_scope: u32,
+    variable: &FervidAtom,
+) -> bool {
+    let mut current_scope_index = starting_scope;
+
+    while let Some(current_scope) = bindings_helper
+        .template_scopes
+        .get(current_scope_index as usize)
+    {
+        if current_scope.variables.contains(variable) {
+            return true;
+        }
+
+        // The root scope has itself as a parent
+        if current_scope.parent == current_scope_index {
+            return false;
+        }
+
+        current_scope_index = current_scope.parent;
+    }
+
+    false
+}
+
 // Mirror what `@babel/parser` does
 fn unwrap_parens(expr: &Expr) -> &Expr {
     let Expr::Paren(p) = expr else {
@@ -254,9 +327,11 @@ fn unwrap_parens(expr: &Expr) -> &Expr {
 mod tests {
     use fervid_core::{fervid_atom, BindingTypes, TemplateGenerationMode};
 
+    use smallvec::SmallVec;
+
     use crate::{
         test_utils::{to_str, ts},
-        BindingsHelper, SetupBinding,
+        BindingsHelper, SetupBinding, TemplateScope,
     };
 
     use super::*;
@@ -307,6 +382,7 @@ mod tests {
                 event: Some("evt".into()),
                 handler: Some(ts($in)),
                 modifiers: vec![],
+                is_cacheable: false,
                 span: DUMMY_SP,
             };
             $visitor.transform_v_on(&mut v_on, 0);
@@ -327,6 +403,10 @@ mod tests {
             bindings_helper: &mut bindings_helper,
             current_scope: 0,
             v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
         };
 
         macro_rules! test {
@@ -471,6 +551,10 @@ mod tests {
             bindings_helper: &mut bindings_helper,
             current_scope: 0,
             v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
         };
 
         macro_rules! test {
@@ -560,6 +644,10 @@ mod tests {
             bindings_helper: &mut bindings_helper,
             current_scope: 0,
             v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
         };
 
         macro_rules! test {
@@ -608,6 +696,10 @@ mod tests {
             bindings_helper: &mut bindings_helper,
             current_scope: 0,
             v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
         };
 
         macro_rules! test {
@@ -633,6 +725,60 @@ mod tests {
         test!("({ lett } = val)", "$event=>({lett:lett}=val)");
     }
 
+    #[test]
+    fn it_marks_cacheable_handlers() {
+        // `function func() {}`
+        let mut bindings_helper = helper(vec![SetupBinding(
+            fervid_atom!("func"),
+            BindingTypes::SetupConst,
+        )]);
+        bindings_helper.cache_handlers = true;
+
+        // `v-for="item in items"` is scope 1
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: SmallVec::new(),
+            parent: 0,
+        });
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: SmallVec::from(vec![fervid_atom!("item")]),
+            parent: 0,
+        });
+
+        let mut template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+            v_once_scope: false,
+            pre_scope: false,
+            errors: Vec::new(),
+            depth: 0,
+        };
+
+        let mut is_cacheable = |handler: Option<&str>, scope: u32| {
+            let mut v_on = VOnDirective {
+                event: Some("click".into()),
+                handler: handler.map(ts),
+                modifiers: vec!["prevent".into()],
+                is_cacheable: false,
+                span: DUMMY_SP,
+            };
+            template_visitor.transform_v_on(&mut v_on, scope);
+            v_on.is_cacheable
+        };
+
+        assert!(is_cacheable(Some("count++"), 0));
+        assert!(is_cacheable(Some("handle"), 0));
+        assert!(is_cacheable(Some("count++"), 1));
+        assert!(is_cacheable(None, 0));
+
+        // Constant handler is used as-is
+        assert!(!is_cacheable(Some("func"), 0));
+
+        // Handler uses the `v-for` item
+        assert!(!is_cacheable(Some("remove(item)"), 1));
+        assert!(!is_cacheable(Some("item.select"), 1));
+    }
+
     fn helper(bindings: Vec<SetupBinding>) -> BindingsHelper {
         let mut bindings_helper = BindingsHelper::default();
         bindings_helper.setup_bindings.extend(bindings);
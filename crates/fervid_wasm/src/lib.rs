@@ -4,8 +4,8 @@ extern crate wee_alloc;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-use fervid::{compile, CompileOptions, CompileResult};
-use swc_core::common::{sync::Lrc, SourceMap, Spanned};
+use fervid::{check::line_and_column, compile, error::{ErrorCode, Severity}, CompileOptions, CompileResult};
+use swc_core::common::Spanned;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(getter_with_clone)]
@@ -16,28 +16,123 @@ pub struct WasmCompileError {
     pub start_column: usize,
     pub end_column: usize,
     pub message: String,
+    /// See `ErrorCode` of `fervid`
+    pub code: String,
+    /// Either `error` or `warning`
+    pub severity: String,
 }
 
 #[wasm_bindgen(getter_with_clone)]
 pub struct WasmCompileResult {
     pub code: String,
     pub errors: Vec<WasmCompileError>,
+    /// Source map of `code`, only present when `source_map` is enabled
+    pub source_map: Option<String>,
+}
+
+/// Options of `compile`, all of them are optional
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Default)]
+pub struct WasmCompileOptions {
+    /// Name of the file, used for the component name and the source map. Default: `anonymous.vue`
+    pub filename: Option<String>,
+    /// Scope ID of the scoped styles, e.g. `data-v-7ba5bd90`. Default: derived from `filename`
+    pub scope_id: Option<String>,
+    /// Production mode, i.e. inline template and no dev-only code. Default: false
+    pub is_prod: Option<bool>,
+    /// Compile the template to the `ssrRender` function. Default: false
+    pub ssr: Option<bool>,
+    /// Generate the source map. Default: false
+    pub source_map: Option<bool>,
+}
+
+#[wasm_bindgen]
+impl WasmCompileOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmCompileOptions {
+        WasmCompileOptions::default()
+    }
+}
+
+/// SFC source assembled from chunks inside the WASM memory, e.g. while reading a stream.
+/// The source is only copied once, and is compiled without passing it back and forth:
+/// ```js
+/// const source = new WasmSource()
+/// for await (const chunk of stream) source.push(chunk)
+/// const result = source.compile(options)
+/// ```
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct WasmSource {
+    source: String,
+}
+
+#[wasm_bindgen]
+impl WasmSource {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmSource {
+        WasmSource::default()
+    }
+
+    /// Appends a chunk of the SFC source
+    pub fn push(&mut self, chunk: &str) {
+        self.source.push_str(chunk);
+    }
+
+    /// Length of the source in bytes
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.source.len()
+    }
+
+    /// Drops the source, so that the instance can be reused for another file
+    pub fn clear(&mut self) {
+        self.source.clear();
+    }
+
+    /// Compiles the assembled source, see `compile`
+    pub fn compile(
+        &self,
+        options: Option<WasmCompileOptions>,
+    ) -> Result<WasmCompileResult, String> {
+        compile_impl(&self.source, options.unwrap_or_default())
+    }
+}
+
+/// Compiles the SFC to a JS module
+#[wasm_bindgen(js_name = compile)]
+pub fn compile_js(
+    source: &str,
+    options: Option<WasmCompileOptions>,
+) -> Result<WasmCompileResult, String> {
+    compile_impl(source, options.unwrap_or_default())
 }
 
+/// Compiles the SFC with the default options.
+/// Kept for the existing consumers, prefer `compile`
 #[wasm_bindgen]
 pub fn compile_sync(source: &str, is_prod: Option<bool>) -> Result<WasmCompileResult, String> {
-    // compile_sync_naive(source, is_prod.unwrap_or(false))
+    compile_impl(
+        source,
+        WasmCompileOptions {
+            is_prod,
+            ..Default::default()
+        },
+    )
+}
+
+fn compile_impl(source: &str, options: WasmCompileOptions) -> Result<WasmCompileResult, String> {
+    let filename = options.filename.as_deref().unwrap_or("anonymous.vue");
     let compile_result = compile(
         source,
         CompileOptions {
-            filename: "anonymous.vue".into(),
-            id: "".into(),
-            is_prod,
+            filename: filename.into(),
+            scope_id: options.scope_id.as_deref().map(Into::into),
+            is_prod: options.is_prod,
             is_custom_element: Some(false),
-            props_destructure: None,
-            ssr: Some(false),
-            gen_default_as: None,
-            source_map: None,
+            ssr: Some(options.ssr.unwrap_or(false)),
+            source_map: options.source_map,
+            ..Default::default()
         },
     );
 
@@ -49,30 +144,36 @@ pub fn compile_sync(source: &str, is_prod: Option<bool>) -> Result<WasmCompileRe
 }
 
 fn convert_compile_result(compiled: CompileResult, source: &str) -> WasmCompileResult {
-    let code = compiled.code;
-
-    let mut errors = vec![];
-    if !compiled.errors.is_empty() {
-        let cm: Lrc<SourceMap> = Default::default();
-        cm.new_source_file(
-            Lrc::new(swc_core::common::FileName::Anon),
-            source.to_owned(),
-        );
-        errors.reserve(compiled.errors.len());
-
-        for error in compiled.errors {
+    // Positions are looked up in the source directly instead of copying it into a `SourceMap`
+    let errors = compiled
+        .errors
+        .iter()
+        .map(|error| {
             let span = error.span();
-            let start = cm.lookup_char_pos(span.lo);
-            let end = cm.lookup_char_pos(span.hi);
-            errors.push(WasmCompileError {
-                start_line_number: start.line,
-                end_line_number: end.line,
-                start_column: start.col.0,
-                end_column: end.col.0,
+            let (start_line, start_column) = line_and_column(source, span.lo.0);
+            let (end_line, end_column) = line_and_column(source, span.hi.0);
+            let severity = if error.is_warning() {
+                "warning"
+            } else {
+                "error"
+            };
+
+            // Columns start from 0
+            WasmCompileError {
+                start_line_number: start_line as usize,
+                end_line_number: end_line as usize,
+                start_column: start_column as usize - 1,
+                end_column: end_column as usize - 1,
                 message: error.to_string(),
-            })
-        }
-    }
+                code: error.code().to_owned(),
+                severity: severity.to_owned(),
+            }
+        })
+        .collect();
 
-    WasmCompileResult { code, errors }
+    WasmCompileResult {
+        code: compiled.code,
+        errors,
+        source_map: compiled.source_map,
+    }
 }
@@ -0,0 +1,118 @@
+const crypto = require('node:crypto')
+const path = require('node:path')
+const { Compiler } = require('@fervid/napi')
+const { transformSync } = require('@swc/core')
+
+/**
+ * @typedef {Object} TransformerConfig
+ * @property {'cjs' | 'esm'} [module] Module format of the output. Default: `esm` when Jest runs in ESM mode, otherwise `cjs`
+ * @property {import('@fervid/napi').FervidJsCompilerOptions['features']} [features] Compile-time feature flags
+ */
+
+const SFC_MAIN = '_sfc_main'
+const VERSION = require('./package.json').version
+
+/** @type {Map<string, Compiler>} */
+const compilers = new Map()
+
+/** @param {TransformerConfig} config */
+function getCompiler(config) {
+  const key = JSON.stringify(config.features ?? null)
+  let compiler = compilers.get(key)
+  if (!compiler) {
+    // Tests run the development build, and source maps are always needed for stack traces
+    compiler = new Compiler({ isProduction: false, sourceMap: true, features: config.features })
+    compilers.set(key, compiler)
+  }
+  return compiler
+}
+
+/**
+ * Module id which does not depend on the machine where the tests run
+ * @param {string} filename
+ * @param {string} rootDir
+ */
+function getModuleId(filename, rootDir) {
+  const relative = path.relative(rootDir, filename).split(path.sep).join('/')
+  return crypto.createHash('sha256').update(relative).digest('hex').slice(0, 8)
+}
+
+/**
+ * Jest transformer for `.vue` files.
+ *
+ * The output is tuned for test runners:
+ * - CJS or ESM depending on the `module` option;
+ * - inline source maps;
+ * - no HMR code;
+ * - `__file` is always set;
+ * - the stable module id is exported as `__moduleId`.
+ *
+ * Styles are not emitted, as they have no effect in the test environment.
+ */
+module.exports = {
+  /**
+   * @param {string} sourceText
+   * @param {string} sourcePath
+   * @param {import('@jest/transform').TransformOptions<TransformerConfig>} options
+   */
+  process(sourceText, sourcePath, options) {
+    const config = options.transformerConfig ?? {}
+    const isEsm = (config.module ?? (options.supportsStaticESM ? 'esm' : 'cjs')) === 'esm'
+    const moduleId = getModuleId(sourcePath, options.config.rootDir)
+
+    const result = getCompiler(config).compileSync(sourceText, {
+      id: moduleId,
+      filename: sourcePath,
+      genDefaultAs: SFC_MAIN,
+    })
+    const errors = result.errors.filter((error) => error.severity === 'error')
+    if (errors.length) {
+      const messages = errors.map((error) => error.message).join('\n')
+      throw new Error(`Failed to compile ${sourcePath}:\n${messages}`)
+    }
+    for (const warning of result.errors) {
+      if (warning.severity !== 'error') {
+        console.warn(`[fervid] ${sourcePath}: ${warning.message}`)
+      }
+    }
+
+    const code = [
+      result.code,
+      `${SFC_MAIN}.__file = ${JSON.stringify(sourcePath)}`,
+      `export const __moduleId = ${JSON.stringify(moduleId)}`,
+      `export default ${SFC_MAIN}`,
+    ].join('\n')
+
+    // Module conversion is done by SWC, which also merges and inlines the source maps
+    const transformed = transformSync(code, {
+      filename: sourcePath,
+      inputSourceMap: result.sourceMap,
+      sourceMaps: 'inline',
+      jsc: {
+        parser: { syntax: 'typescript' },
+        target: 'es2020',
+      },
+      module: isEsm ? undefined : { type: 'commonjs' },
+      swcrc: false,
+      configFile: false,
+    })
+
+    return { code: transformed.code }
+  },
+
+  /**
+   * @param {string} sourceText
+   * @param {string} sourcePath
+   * @param {import('@jest/transform').TransformOptions<TransformerConfig>} options
+   */
+  getCacheKey(sourceText, sourcePath, options) {
+    return crypto
+      .createHash('sha256')
+      .update(VERSION)
+      .update(sourceText)
+      .update(path.relative(options.config.rootDir, sourcePath))
+      .update(JSON.stringify(options.transformerConfig ?? {}))
+      .update(String(options.supportsStaticESM))
+      .digest('hex')
+  },
+}
@@ -0,0 +1,84 @@
+const assert = require('node:assert/strict')
+const fs = require('node:fs')
+const path = require('node:path')
+const { describe, test } = require('node:test')
+const transformer = require('./index')
+
+const COUNTER = path.join(__dirname, 'fixtures/Counter.vue')
+const BROKEN = path.join(__dirname, 'fixtures/Broken.vue')
+
+/**
+ * @param {string} sourcePath
+ * @param {{ supportsStaticESM?: boolean, transformerConfig?: object }} [options]
+ */
+function transform(sourcePath, { supportsStaticESM = false, transformerConfig = {} } = {}) {
+  const source = fs.readFileSync(sourcePath, 'utf8')
+  return transformer.process(source, sourcePath, {
+    config: { rootDir: __dirname },
+    supportsStaticESM,
+    transformerConfig,
+  })
+}
+
+/** @param {string} code */
+function inlineSourceMap(code) {
+  const [, base64] = code.match(/\/\/# sourceMappingURL=data:application\/json;base64,(\S+)/)
+  return JSON.parse(Buffer.from(base64, 'base64').toString())
+}
+
+describe('process', () => {
+  test('should compile the SFC to CommonJS with the module id', () => {
+    const { code } = transform(COUNTER)
+
+    assert.match(code, /require\("vue"\)/)
+    assert.doesNotMatch(code, /^import /m)
+    assert.match(code, /__moduleId/)
+    assert.ok(code.includes(`_sfc_main.__file = ${JSON.stringify(COUNTER)}`))
+  })
+
+  test('should keep ESM when Jest runs in ESM mode', () => {
+    const { code } = transform(COUNTER, { supportsStaticESM: true })
+
+    assert.match(code, /^import .* from "vue"/m)
+    assert.match(code, /^export default _sfc_main/m)
+  })
+
+  test('should inline the source map pointing to the SFC', () => {
+    const map = inlineSourceMap(transform(COUNTER).code)
+
+    assert.ok(map.sources.some((source) => source.endsWith('Counter.vue')))
+    assert.notEqual(map.mappings, '')
+  })
+
+  test('should fail on errors', () => {
+    assert.throws(() => transform(BROKEN), (error) => {
+      assert.ok(error.message.startsWith(`Failed to compile ${BROKEN}:`))
+      assert.match(error.message, /v-else has no adjacent v-if/)
+      return true
+    })
+  })
+
+  test('should log the warnings', (t) => {
+    const warn = t.mock.method(console, 'warn', () => {})
+    const sourcePath = path.join(__dirname, 'fixtures/Renderless.vue')
+    transformer.process("<script>export default { name: 'Renderless' }</script>", sourcePath, {
+      config: { rootDir: __dirname },
+      supportsStaticESM: false,
+      transformerConfig: {},
+    })
+
+    assert.equal(warn.mock.callCount(), 1)
+    assert.match(warn.mock.calls[0].arguments[0], /^\[fervid\] .*Renderless\.vue: The component has neither a `<template>` nor a `render` function/)
+  })
+})
+
+describe('getCacheKey', () => {
+  test('should depend on the source and the transformer config', () => {
+    const options = { config: { rootDir: __dirname }, transformerConfig: {}, supportsStaticESM: false }
+    const key = transformer.getCacheKey('<template />', COUNTER, options)
+
+    assert.equal(transformer.getCacheKey('<template />', COUNTER, options), key)
+    assert.notEqual(transformer.getCacheKey('<template></template>', COUNTER, options), key)
+    assert.notEqual(transformer.getCacheKey('<template />', COUNTER, { ...options, transformerConfig: { module: 'esm' } }), key)
+  })
+})
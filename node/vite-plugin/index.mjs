@@ -0,0 +1,201 @@
+import fs from 'node:fs'
+import { Compiler } from '@fervid/napi'
+import { preprocessCSS } from 'vite'
+
+/**
+ * @typedef {import('@fervid/napi').CompileResult} CompileResult
+ * @typedef {import('@fervid/napi').SerializedError} SerializedError
+ * @typedef {import('vite').Plugin} Plugin
+ * @typedef {import('vite').ResolvedConfig} ResolvedConfig
+ */
+
+/**
+ * @typedef {Object} Options
+ * @property {import('@fervid/napi').FervidJsCompilerOptions['features']} [features] Compile-time feature flags
+ */
+
+const SFC_MAIN = '_sfc_main'
+
+/**
+ * Vite plugin for compiling Vue SFCs with fervid.
+ *
+ * Style blocks are served as separate modules, e.g. `App.vue?vue&type=style&index=0&lang.css`,
+ * so that Vite can process them with its CSS pipeline.
+ * CSS Modules in other languages are served as `lang.module.css` for Vite to compile them.
+ *
+ * @param {Options} [options]
+ * @returns {Plugin}
+ */
+export default function fervid(options = {}) {
+  /** @type {ResolvedConfig} */
+  let config
+
+  /** @type {Compiler} */
+  let clientCompiler
+  /** @type {Compiler} */
+  let ssrCompiler
+
+  /**
+   * Last compilation of each SFC, used to serve the block requests
+   * @type {Map<string, CompileResult>}
+   */
+  const descriptors = new Map()
+
+  /**
+   * Last transformed source of each SFC, tells the template-only edits apart for HMR
+   * @type {Map<string, string>}
+   */
+  const prevSources = new Map()
+
+  /**
+   * @param {string} code
+   * @param {string} filename
+   * @param {boolean} ssr
+   * @param {boolean} [hotReload]
+   */
+  function compileSfc(code, filename, ssr, hotReload = false) {
+    const compiler = ssr ? ssrCompiler : clientCompiler
+    const result = compiler.compileSync(code, {
+      id: filename,
+      filename,
+      genDefaultAs: SFC_MAIN,
+      hotReload,
+      prevSource: hotReload ? prevSources.get(filename) : undefined,
+    })
+    descriptors.set(filename, result)
+    if (hotReload) {
+      prevSources.set(filename, code)
+    }
+    return result
+  }
+
+  /**
+   * Block requests can come before the SFC itself was compiled, e.g. after a dev server restart
+   * @param {string} filename
+   * @param {boolean} ssr
+   */
+  function getDescriptor(filename, ssr) {
+    return descriptors.get(filename) ?? compileSfc(fs.readFileSync(filename, 'utf-8'), filename, ssr)
+  }
+
+  return {
+    name: 'vite:fervid',
+
+    configResolved(resolvedConfig) {
+      config = resolvedConfig
+
+      /** @type {import('@fervid/napi').FervidJsCompilerOptions} */
+      const compilerOptions = {
+        isProduction: config.isProduction,
+        sourceMap: !!config.build.sourcemap || !config.isProduction,
+        features: options.features,
+        cache: true,
+      }
+      clientCompiler = new Compiler(compilerOptions)
+      ssrCompiler = new Compiler({ ...compilerOptions, ssr: true })
+    },
+
+    async load(id, loadOptions) {
+      const request = parseRequest(id)
+      if (!request.query || request.query.type !== 'style') {
+        return
+      }
+
+      const descriptor = getDescriptor(request.filename, !!loadOptions?.ssr)
+      const style = descriptor.styles[request.query.index]
+      if (!style) {
+        return
+      }
+
+      // Styles already compiled by fervid are plain CSS
+      if (style.isCompiled) {
+        return style.code
+      }
+
+      // Other languages are preprocessed by Vite first, then scoped
+      const preprocessed = await preprocessCSS(style.code, `${request.filename}.${style.lang}`, config)
+      if (!style.isScoped) {
+        return preprocessed.code
+      }
+
+      const scoped = clientCompiler.compileStyle(preprocessed.code, {
+        id: `data-v-${descriptor.scopeId}`,
+        scoped: true,
+      })
+      reportErrors(this, scoped.errors)
+      return scoped.code
+    },
+
+    transform(code, id, transformOptions) {
+      const request = parseRequest(id)
+      if (request.query || !request.filename.endsWith('.vue')) {
+        return
+      }
+
+      const ssr = !!transformOptions?.ssr
+      const hotReload = !config.isProduction && !ssr && config.server.hmr !== false
+      const result = compileSfc(code, request.filename, ssr, hotReload)
+      reportErrors(this, result.errors)
+
+      const output = [result.code]
+
+      // Each style is a separate module, `lang.css` lets Vite treat it as CSS after `load`
+      result.styles.forEach((style, index) => {
+        // CSS Modules compiled by fervid are already in `__cssModules`, the others are compiled by Vite
+        if (style.module && !style.isCompiled) {
+          const moduleId = `${request.filename}?vue&type=style&index=${index}&lang.module.css`
+          output.push(`import style${index} from ${JSON.stringify(moduleId)}`)
+          output.push(`__cssModules[${JSON.stringify(style.module)}] = style${index}`)
+          return
+        }
+
+        output.push(`import ${JSON.stringify(`${request.filename}?vue&type=style&index=${index}&lang.css`)}`)
+      })
+
+      output.push(`export default ${SFC_MAIN}`)
+
+      return {
+        code: output.join('\n'),
+        map: result.sourceMap ? JSON.parse(result.sourceMap) : null,
+      }
+    },
+  }
+}
+
+/**
+ * Splits the module id into the filename and the query of a block request
+ * @param {string} id
+ */
+export function parseRequest(id) {
+  const [filename, rawQuery] = id.split('?', 2)
+  const params = new URLSearchParams(rawQuery)
+  if (!params.has('vue')) {
+    return { filename, query: null }
+  }
+
+  return {
+    filename,
+    query: {
+      type: params.get('type'),
+      index: Number(params.get('index')),
+    },
+  }
+}
+
+/**
+ * Warnings are reported as such, the first error fails the build
+ * @param {import('vite').Rollup.PluginContext} ctx
+ * @param {SerializedError[]} errors
+ */
+function reportErrors(ctx, errors) {
+  for (const error of errors) {
+    if (error.severity !== 'error') {
+      ctx.warn(error.message, error.lo)
+    }
+  }
+
+  const error = errors.find((error) => error.severity === 'error')
+  if (error) {
+    ctx.error(error.message, error.lo)
+  }
+}
@@ -0,0 +1,113 @@
+import assert from 'node:assert/strict'
+import { describe, test } from 'node:test'
+import fervid, { parseRequest } from './index.mjs'
+
+const SFC = `<template><div>{{ msg }}</div></template>
+<script setup>
+const msg = 'hi'
+</script>
+<style scoped>
+div { color: red }
+</style>`
+
+/**
+ * @param {{ isProduction?: boolean }} [options]
+ */
+function createPlugin({ isProduction = false } = {}) {
+  const plugin = fervid()
+  plugin.configResolved({
+    isProduction,
+    build: { sourcemap: false },
+    server: { hmr: true },
+  })
+  return plugin
+}
+
+function createContext() {
+  return {
+    warnings: [],
+    warn(message) {
+      this.warnings.push(message)
+    },
+    error(message) {
+      throw new Error(message)
+    },
+  }
+}
+
+describe('transform', () => {
+  test('should compile the SFC and import its styles', () => {
+    const plugin = createPlugin()
+    const { code } = plugin.transform.call(createContext(), SFC, '/src/App.vue')
+
+    assert.match(code, /import "\/src\/App\.vue\?vue&type=style&index=0&lang\.css"/)
+    assert.equal(code.match(/export default _sfc_main/g).length, 1)
+  })
+
+  test('should add the CSS Modules in other languages to the component', () => {
+    const plugin = createPlugin()
+    const source = `<template><div :class="$style.red" /></template>
+<style module lang="scss">
+.red { color: red }
+</style>`
+    const { code } = plugin.transform.call(createContext(), source, '/src/App.vue')
+
+    assert.match(code, /__cssModules\.\$style\.red/)
+    assert.match(code, /import style0 from "\/src\/App\.vue\?vue&type=style&index=0&lang\.module\.css"/)
+    assert.match(code, /__cssModules\["\$style"\] = style0/)
+  })
+
+  test('should add the HMR code of fervid in development', () => {
+    const plugin = createPlugin()
+    const { code } = plugin.transform.call(createContext(), SFC, '/src/App.vue')
+
+    assert.equal(code.match(/_sfc_main\.__hmrId = /g).length, 1)
+    assert.match(code, /import\.meta\.hot\.accept/)
+  })
+
+  test('should not add the HMR code in production and SSR', () => {
+    const prodPlugin = createPlugin({ isProduction: true })
+    const prod = prodPlugin.transform.call(createContext(), SFC, '/src/App.vue')
+    assert.doesNotMatch(prod.code, /__hmrId/)
+
+    const devPlugin = createPlugin()
+    const ssr = devPlugin.transform.call(createContext(), SFC, '/src/App.vue', { ssr: true })
+    assert.doesNotMatch(ssr.code, /__hmrId/)
+  })
+
+  test('should re-render after a template-only edit', () => {
+    const plugin = createPlugin()
+    const ctx = createContext()
+    plugin.transform.call(ctx, SFC, '/src/App.vue')
+
+    const templateEdit = plugin.transform.call(ctx, SFC.replace('<div>', '<p>').replace('</div>', '</p>'), '/src/App.vue')
+    assert.match(templateEdit.code, /_rerender_only = true/)
+
+    const scriptEdit = plugin.transform.call(ctx, SFC.replace("'hi'", "'hello'"), '/src/App.vue')
+    assert.doesNotMatch(scriptEdit.code, /_rerender_only = true/)
+  })
+
+  test('should fail on errors', () => {
+    const plugin = createPlugin()
+    const source = '<template><div v-else>Hello</div></template>'
+
+    assert.throws(() => plugin.transform.call(createContext(), source, '/src/App.vue'), /v-else has no adjacent v-if/)
+  })
+
+  test('should ignore other modules', () => {
+    const plugin = createPlugin()
+
+    assert.equal(plugin.transform.call(createContext(), '', '/src/main.ts'), undefined)
+    assert.equal(plugin.transform.call(createContext(), '', '/src/App.vue?vue&type=style&index=0&lang.css'), undefined)
+  })
+})
+
+describe('parseRequest', () => {
+  test('should parse block requests', () => {
+    assert.deepEqual(parseRequest('/src/App.vue?vue&type=style&index=1&lang.css'), {
+      filename: '/src/App.vue',
+      query: { type: 'style', index: 1 },
+    })
+    assert.deepEqual(parseRequest('/src/App.vue'), { filename: '/src/App.vue', query: null })
+  })
+})
@@ -0,0 +1,147 @@
+const { Compiler } = require('@fervid/napi')
+const FervidLoaderPlugin = require('./plugin')
+const { parseQuery } = require('./query')
+
+/**
+ * @typedef {import('@fervid/napi').CompileResult} CompileResult
+ * @typedef {import('@fervid/napi').FervidJsCompilerOptions} FervidJsCompilerOptions
+ */
+
+/**
+ * @typedef {Object} LoaderOptions
+ * @property {FervidJsCompilerOptions['features']} [features] Compile-time feature flags
+ */
+
+/**
+ * Compilers are created once per set of options and shared between the loader runs,
+ * so that their caches are reused
+ * @type {Map<string, Compiler>}
+ */
+const compilers = new Map()
+
+/**
+ * Last compilation of each SFC, used to serve the block requests
+ * @type {Map<string, CompileResult>}
+ */
+const descriptors = new Map()
+
+/**
+ * Webpack/Rspack loader for `.vue` files.
+ *
+ * The main request (`App.vue`) returns the compiled component.
+ * Style blocks are requested separately (`App.vue?vue&type=style&index=0`)
+ * and are routed to the style rules of the project with a match resource, e.g. `App.vue.0.scss`.
+ * `lang="css"` styles are scoped by fervid itself, scoped styles in other languages
+ * need `FervidLoaderPlugin` to be scoped after their preprocessor.
+ *
+ * @this {import('webpack').LoaderContext<LoaderOptions>}
+ * @param {string} source
+ */
+function fervidLoader(source) {
+  const options = this.getOptions() || {}
+  const isProduction = this.mode === 'production'
+  const compiler = getCompiler({
+    isProduction,
+    sourceMap: this.sourceMap,
+    features: options.features,
+    cache: true,
+  })
+
+  const filename = this.resourcePath
+  const query = parseQuery(this.resourceQuery)
+
+  // Block request
+  if (query) {
+    const descriptor = descriptors.get(filename) ?? compile(compiler, source, filename)
+    const style = query.type === 'style' ? descriptor.styles[query.index] : undefined
+    this.callback(null, style ? style.code : '')
+    return
+  }
+
+  // Main request
+  const result = compile(compiler, source, filename)
+  for (const error of result.errors) {
+    if (error.severity === 'error') {
+      this.emitError(new Error(error.message))
+    } else {
+      this.emitWarning(new Error(error.message))
+    }
+  }
+
+  const output = [result.code]
+  result.styles.forEach((style, index) => {
+    const needsScoping = style.isScoped && !style.isCompiled
+    if (needsScoping && !this._compiler?.[FervidLoaderPlugin.NS]) {
+      this.emitWarning(
+        new Error(
+          `<style scoped lang="${style.lang}"> is not scoped, add FervidLoaderPlugin to the webpack config`,
+        ),
+      )
+    }
+
+    const scopeId = needsScoping ? result.scopeId : null
+    const request = JSON.stringify(styleRequest(this, style, index, scopeId))
+
+    // CSS Modules compiled by fervid are already in `__cssModules`, the others are compiled by `css-loader`
+    if (style.module && !style.isCompiled) {
+      output.push(`import style${index} from ${request}`)
+      output.push(`__cssModules[${JSON.stringify(style.module)}] = style${index}`)
+      return
+    }
+
+    output.push(`import ${request}`)
+  })
+
+  // Source map is inlined for the bundler to pick up
+  const map = result.sourceMap ? JSON.parse(result.sourceMap) : undefined
+  this.callback(null, output.join('\n'), map)
+}
+
+/**
+ * @param {FervidJsCompilerOptions} options
+ */
+function getCompiler(options) {
+  const key = JSON.stringify(options)
+  let compiler = compilers.get(key)
+  if (!compiler) {
+    compiler = new Compiler(options)
+    compilers.set(key, compiler)
+  }
+  return compiler
+}
+
+/**
+ * @param {Compiler} compiler
+ * @param {string} source
+ * @param {string} filename
+ */
+function compile(compiler, source, filename) {
+  const result = compiler.compileSync(source, { id: filename, filename })
+  descriptors.set(filename, result)
+  return result
+}
+
+/**
+ * Builds a request of a style block which goes through this loader first,
+ * and then through the loaders matching the style language, e.g. `App.vue.0.scss`.
+ * CSS Modules which are not compiled by fervid are requested as e.g. `App.vue.0.module.scss`,
+ * so that `css-loader` compiles them as such.
+ * @param {import('webpack').LoaderContext<LoaderOptions>} loaderContext
+ * @param {import('@fervid/napi').Style} style
+ * @param {number} index
+ * @param {string | null} scopeId Scope id of the styles to scope after the preprocessor
+ */
+function styleRequest(loaderContext, style, index, scopeId) {
+  const lang = style.isCompiled ? 'css' : style.lang
+  const module = style.module && !style.isCompiled ? '.module' : ''
+  const matchResource = `${loaderContext.resourcePath}.${index}${module}.${lang}`
+  let query = `?vue&type=style&index=${index}`
+  if (scopeId) {
+    query += `&id=${scopeId}`
+  }
+  return `${matchResource}!=!${__filename}!${loaderContext.resourcePath}${query}`
+}
+
+module.exports = fervidLoader
+module.exports.parseQuery = parseQuery
+module.exports.FervidLoaderPlugin = FervidLoaderPlugin
@@ -0,0 +1,125 @@
+const assert = require('node:assert/strict')
+const fs = require('node:fs')
+const path = require('node:path')
+const { describe, test } = require('node:test')
+const fervidLoader = require('./index')
+const { FervidLoaderPlugin } = require('./index')
+const pitcher = require('./pitcher')
+const stylePostLoader = require('./stylePostLoader')
+
+const FIXTURE = path.join(__dirname, 'fixtures/Scoped.vue')
+
+/**
+ * Runs a loader with a minimal loader context
+ * @param {Function} loader
+ * @param {string} source
+ * @param {{ resourcePath?: string, resourceQuery?: string, withPlugin?: boolean, loaders?: { path: string, request: string }[] }} [options]
+ */
+function runLoader(loader, source, { resourcePath = FIXTURE, resourceQuery = '', withPlugin = true, loaders = [] } = {}) {
+  const ctx = {
+    errors: [],
+    warnings: [],
+    result: undefined,
+    mode: 'development',
+    sourceMap: false,
+    resourcePath,
+    resourceQuery,
+    loaders,
+    _compiler: withPlugin ? { [FervidLoaderPlugin.NS]: true } : {},
+    getOptions() {
+      return {}
+    },
+    emitError(error) {
+      this.errors.push(error.message)
+    },
+    emitWarning(warning) {
+      this.warnings.push(warning.message)
+    },
+    callback(error, code) {
+      assert.equal(error, null)
+      this.result = code
+    },
+  }
+  const returned = loader.call(ctx, source)
+  return { ctx, returned }
+}
+
+describe('loader', () => {
+  test('should compile the SFC and request its scoped styles after the preprocessor', () => {
+    const source = fs.readFileSync(FIXTURE, 'utf8')
+    const { ctx } = runLoader(fervidLoader, source)
+
+    assert.deepEqual(ctx.errors, [])
+    assert.deepEqual(ctx.warnings, [])
+    const scopeId = ctx.result.match(/__scopeId: "data-v-(\w+)"/)[1]
+    assert.ok(ctx.result.includes(`Scoped.vue.0.scss!=!${require.resolve('./index')}!${FIXTURE}?vue&type=style&index=0&id=${scopeId}`))
+  })
+
+  test('should serve the style block request', () => {
+    const source = fs.readFileSync(FIXTURE, 'utf8')
+    const { ctx } = runLoader(fervidLoader, source, { resourceQuery: '?vue&type=style&index=0&id=abc' })
+
+    assert.match(ctx.result, /\$color: red;/)
+  })
+
+  test('should warn about the scoped styles without the plugin', () => {
+    const source = fs.readFileSync(FIXTURE, 'utf8')
+    const { ctx } = runLoader(fervidLoader, source, { withPlugin: false })
+
+    assert.deepEqual(ctx.warnings, [
+      '<style scoped lang="scss"> is not scoped, add FervidLoaderPlugin to the webpack config',
+    ])
+  })
+
+  test('should emit the errors', () => {
+    const source = '<template><div v-else>Hello</div></template>'
+    const { ctx } = runLoader(fervidLoader, source, { resourcePath: '/src/Broken.vue' })
+
+    assert.equal(ctx.errors.length, 1)
+    assert.match(ctx.errors[0], /v-else has no adjacent v-if/)
+  })
+})
+
+describe('pitcher', () => {
+  const loaders = [
+    { path: '/node_modules/style-loader/dist/cjs.js', request: 'style-loader' },
+    { path: '/node_modules/css-loader/dist/cjs.js', request: 'css-loader' },
+    { path: require.resolve('./pitcher'), request: require.resolve('./pitcher') },
+    { path: '/node_modules/sass-loader/dist/cjs.js', request: 'sass-loader' },
+    { path: require.resolve('./index'), request: require.resolve('./index') },
+  ]
+
+  test('should put the style post loader after the preprocessor', () => {
+    const { returned } = runLoader(pitcher.pitch, '', { resourceQuery: '?vue&type=style&index=0&id=abc', loaders })
+
+    const request = [
+      '-!style-loader',
+      'css-loader',
+      require.resolve('./stylePostLoader'),
+      'sass-loader',
+      require.resolve('./index'),
+      `${FIXTURE}?vue&type=style&index=0&id=abc`,
+    ].join('!')
+    assert.equal(returned, `export { default } from ${JSON.stringify(request)}\nexport * from ${JSON.stringify(request)}`)
+  })
+
+  test('should skip the styles which are not scoped', () => {
+    assert.equal(runLoader(pitcher.pitch, '', { resourceQuery: '?vue&type=style&index=0', loaders }).returned, undefined)
+    assert.equal(runLoader(pitcher.pitch, '', { loaders }).returned, undefined)
+  })
+})
+
+describe('stylePostLoader', () => {
+  test('should scope the preprocessed styles', () => {
+    const { ctx } = runLoader(stylePostLoader, '.red { color: red }', { resourceQuery: '?vue&type=style&index=0&id=abc' })
+
+    assert.deepEqual(ctx.errors, [])
+    assert.match(ctx.result, /\.red\[data-v-abc\]/)
+  })
+
+  test('should emit the errors', () => {
+    const { ctx } = runLoader(stylePostLoader, '.red { color: red', { resourceQuery: '?vue&type=style&index=0&id=abc' })
+
+    assert.ok(ctx.errors.length > 0)
+  })
+})
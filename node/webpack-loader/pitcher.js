@@ -0,0 +1,37 @@
+const { parseQuery } = require('./query')
+
+const STYLE_POST_LOADER = require.resolve('./stylePostLoader')
+
+/** @param {{ path: string }} loader */
+const isCssLoader = (loader) => /(\/|\\|@)css-loader/.test(loader.path)
+
+/** @param {string} source */
+module.exports = (source) => source
+
+/**
+ * Scoped styles in other languages than CSS are scoped after their preprocessor,
+ * i.e. the request `style!css!sass!fervid!App.vue` becomes `style!css!stylePostLoader!sass!fervid!App.vue`.
+ * When there is no `css-loader`, the post loader is the last one.
+ *
+ * @this {import('webpack').LoaderContext<{}>}
+ */
+module.exports.pitch = function () {
+  const query = parseQuery(this.resourceQuery)
+  if (!query || query.type !== 'style' || !query.id) {
+    return
+  }
+
+  const loaders = this.loaders.filter((loader) => loader.path !== __filename)
+  const postLoaderIndex = loaders.findIndex(isCssLoader) + 1
+  const request = [
+    ...loaders.slice(0, postLoaderIndex).map((loader) => loader.request),
+    STYLE_POST_LOADER,
+    ...loaders.slice(postLoaderIndex).map((loader) => loader.request),
+    this.resourcePath + this.resourceQuery,
+  ].join('!')
+
+  // `-!` skips the configured loaders, they are all listed in the request already.
+  // The default export is the classes of a CSS Module
+  const proxied = JSON.stringify(`-!${request}`)
+  return `export { default } from ${proxied}\nexport * from ${proxied}`
+}
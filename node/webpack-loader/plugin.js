@@ -0,0 +1,24 @@
+const PITCHER = require.resolve('./pitcher')
+
+/** Marks the compilers the plugin is applied to, see `fervidLoader` */
+const NS = 'fervid-webpack-loader'
+
+/**
+ * Style block requests (`App.vue.0.scss`) match the style rules of the project.
+ * The plugin adds the pitcher in front of them, which scopes the styles
+ * right after the preprocessor loaders (see `pitcher.js`).
+ */
+class FervidLoaderPlugin {
+  /** @param {import('webpack').Compiler} compiler */
+  apply(compiler) {
+    compiler[NS] = true
+    compiler.options.module.rules.unshift({
+      // Rules match the match resource of the block request, e.g. `App.vue.0.scss`
+      test: /\.vue\.\d+(\.module)?\.\w+$/,
+      loader: PITCHER,
+    })
+  }
+}
+
+module.exports = FervidLoaderPlugin
+module.exports.NS = NS
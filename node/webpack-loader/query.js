@@ -0,0 +1,20 @@
+/**
+ * Parses the query of a block request, e.g. `?vue&type=style&index=0`.
+ * `id` is the scope id of scoped styles which are not compiled by fervid.
+ * Returns `null` for requests of the SFC itself.
+ * @param {string} resourceQuery
+ */
+function parseQuery(resourceQuery) {
+  const params = new URLSearchParams(resourceQuery.replace(/^\?/, ''))
+  if (!params.has('vue')) {
+    return null
+  }
+
+  return {
+    type: params.get('type'),
+    index: Number(params.get('index')),
+    id: params.get('id'),
+  }
+}
+
+module.exports = { parseQuery }
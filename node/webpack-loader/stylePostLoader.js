@@ -0,0 +1,29 @@
+const { Compiler } = require('@fervid/napi')
+const { parseQuery } = require('./query')
+
+/** Scoping does not depend on the compiler options */
+const compiler = new Compiler()
+
+/**
+ * Scopes the preprocessed CSS of a style block, see `pitcher.js`
+ *
+ * @this {import('webpack').LoaderContext<{}>}
+ * @param {string} source
+ */
+module.exports = function stylePostLoader(source) {
+  const query = parseQuery(this.resourceQuery)
+  const result = compiler.compileStyle(source, {
+    id: `data-v-${query.id}`,
+    scoped: true,
+  })
+
+  for (const error of result.errors) {
+    if (error.severity === 'error') {
+      this.emitError(new Error(error.message))
+    } else {
+      this.emitWarning(new Error(error.message))
+    }
+  }
+
+  this.callback(null, result.code)
+}